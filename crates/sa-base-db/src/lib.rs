@@ -1,11 +1,18 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use salsa::Setter;
 
 use sa_config::ResolvedFoundryConfig;
-use sa_paths::NormalizedPath;
-pub use sa_vfs::FileId;
+use sa_paths::{NormalizedPath, WorkspacePath};
+use sa_project_model::NodeModulesCache;
+use sa_syntax::cst::Cst;
+pub use sa_vfs::{FileId, OverlayEdit};
+pub use salsa::Durability;
+
+mod cancellation;
+
+pub use cancellation::{CancelOnDrop, CancellationToken};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ProjectId(u32);
@@ -34,6 +41,10 @@ pub struct FileInput {
     pub text: Arc<str>,
     pub version: u32,
     pub kind: LanguageKind,
+    /// The edits that turned the previous version of the text into this one, in order. Empty
+    /// when the text was replaced wholesale.
+    #[returns(ref)]
+    pub edits: Arc<[OverlayEdit]>,
 }
 
 #[salsa::input(debug)]
@@ -42,12 +53,23 @@ pub struct FilePathInput {
     pub path: Arc<NormalizedPath>,
 }
 
+/// Bumped whenever a file is added or removed. [`Database::file_ids`] reads it, so queries that
+/// enumerate files re-run when the file set changes even if no file they saw was edited.
+#[salsa::input(debug)]
+pub struct FileSetInput {
+    pub generation: u64,
+}
+
 #[salsa::input(debug)]
 pub struct ProjectInput {
     #[returns(ref)]
     pub workspace: Arc<sa_project_model::FoundryWorkspace>,
     #[returns(ref)]
     pub config: Arc<ResolvedFoundryConfig>,
+    /// Kept apart from the config so a `forge build` does not invalidate analysis that only
+    /// depends on sources and settings.
+    #[returns(ref)]
+    pub artifacts: Arc<Vec<sa_project_model::ContractArtifact>>,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -56,6 +78,7 @@ struct InputStorage {
     paths: HashMap<FileId, FilePathInput>,
     path_to_file_id: HashMap<NormalizedPath, FileId>,
     projects: HashMap<ProjectId, ProjectInput>,
+    file_set: Option<FileSetInput>,
 }
 
 impl InputStorage {
@@ -89,6 +112,106 @@ impl InputStorage {
     }
 }
 
+/// The newest syntax tree of each file with the version it was built from. It lives outside
+/// salsa so that the tree of the next version can reuse it instead of parsing from scratch.
+#[derive(Default, Debug, Clone)]
+struct SyntaxTrees {
+    trees: Arc<Mutex<HashMap<FileInput, (u32, Arc<Cst>)>>>,
+}
+
+impl SyntaxTrees {
+    fn get(&self, file: FileInput) -> Option<(u32, Arc<Cst>)> {
+        self.trees
+            .lock()
+            .expect("syntax trees lock")
+            .get(&file)
+            .cloned()
+    }
+
+    fn insert(&self, file: FileInput, version: u32, cst: Arc<Cst>) {
+        let mut trees = self.trees.lock().expect("syntax trees lock");
+        match trees.get(&file) {
+            Some((newest, _)) if *newest > version => {}
+            _ => {
+                trees.insert(file, (version, cst));
+            }
+        }
+    }
+
+    fn remove(&self, file: FileInput) {
+        self.trees.lock().expect("syntax trees lock").remove(&file);
+    }
+
+    fn len(&self) -> usize {
+        self.trees.lock().expect("syntax trees lock").len()
+    }
+}
+
+/// A memoized query result whose size is reported by [`Database::memo_sizes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoKey {
+    ParsedFile(FileInput),
+    FileItems(FileInput),
+    /// The DefMap inside the program of a project.
+    DefMap(ProjectInput),
+    Program(ProjectInput),
+}
+
+/// The estimated heap size of a memoized result and, for maps, its number of entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoSize {
+    pub bytes: usize,
+    pub entries: usize,
+}
+
+/// The sizes queries recorded for the results salsa keeps, so they can be reported without
+/// running the queries. Re-running a query overwrites its size; removing a file or project
+/// drops the sizes of its results.
+#[derive(Default, Debug, Clone)]
+struct MemoSizes {
+    sizes: Arc<Mutex<HashMap<MemoKey, MemoSize>>>,
+}
+
+impl MemoSizes {
+    fn insert(&self, key: MemoKey, size: MemoSize) {
+        self.sizes
+            .lock()
+            .expect("memo sizes lock")
+            .insert(key, size);
+    }
+
+    fn retain(&self, keep: impl Fn(&MemoKey) -> bool) {
+        self.sizes
+            .lock()
+            .expect("memo sizes lock")
+            .retain(|key, _| keep(key));
+    }
+
+    fn to_vec(&self) -> Vec<(MemoKey, MemoSize)> {
+        let sizes = self.sizes.lock().expect("memo sizes lock");
+        sizes.iter().map(|(key, size)| (*key, *size)).collect()
+    }
+}
+
+/// Where node_modules packages were found, per project root. It lives outside salsa like the
+/// syntax trees; it is cleared whenever the file set or a project changes, since either may
+/// follow an install.
+#[derive(Default, Debug, Clone)]
+struct NodeModules {
+    projects: Arc<Mutex<HashMap<NormalizedPath, Arc<NodeModulesCache>>>>,
+}
+
+impl NodeModules {
+    fn get(&self, root: &NormalizedPath) -> Arc<NodeModulesCache> {
+        let mut projects = self.projects.lock().expect("node_modules lock");
+        Arc::clone(projects.entry(root.clone()).or_default())
+    }
+
+    fn clear(&self) {
+        self.projects.lock().expect("node_modules lock").clear();
+    }
+}
+
 #[salsa::db]
 pub trait SaDatabase: salsa::Database {}
 
@@ -96,16 +219,50 @@ pub trait SaDatabase: salsa::Database {}
 impl<Db: salsa::Database> SaDatabase for Db {}
 
 #[salsa::db]
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Database {
     storage: salsa::Storage<Self>,
     inputs: InputStorage,
+    syntax_trees: SyntaxTrees,
+    memo_sizes: MemoSizes,
+    node_modules: NodeModules,
+    cancellation: CancellationToken,
 }
 
 #[salsa::db]
 impl salsa::Database for Database {}
 
+impl Default for Database {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: salsa::Storage::default(),
+            inputs: InputStorage::default(),
+            syntax_trees: SyntaxTrees::default(),
+            memo_sizes: MemoSizes::default(),
+            node_modules: NodeModules::default(),
+            cancellation: CancellationToken::default(),
+        };
+        db.inputs.file_set = Some(FileSetInput::new(&db, 0));
+        db
+    }
+}
+
 impl Database {
+    /// Ties this handle to `token`: cancelling it aborts work running on this handle at its next
+    /// checkpoint, without affecting other handles.
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    /// Unwinds with [`salsa::Cancelled`] if a newer revision is pending or this handle's
+    /// cancellation token was cancelled. Long loops call it between items.
+    pub fn check_cancelled(&self) {
+        salsa::Database::unwind_if_revision_cancelled(self);
+        if self.cancellation.is_cancelled() {
+            std::panic::resume_unwind(Box::new(salsa::Cancelled::PendingWrite));
+        }
+    }
+
     pub fn file_input(&self, file_id: FileId) -> FileInput {
         self.inputs.file_input(file_id)
     }
@@ -116,19 +273,104 @@ impl Database {
         text: Arc<str>,
         version: u32,
         kind: LanguageKind,
+    ) {
+        self.set_file_input_with_durability(file_id, text, version, kind, Durability::LOW);
+    }
+
+    /// Fields that did not change are left alone, so re-applying an unchanged file does not
+    /// start a new revision at `durability` (which would revalidate every query of that tier).
+    fn set_file_input_with_durability(
+        &mut self,
+        file_id: FileId,
+        text: Arc<str>,
+        version: u32,
+        kind: LanguageKind,
+        durability: Durability,
     ) {
         let input = self.inputs.files.get(&file_id).copied();
         match input {
             Some(input) => {
-                input.set_text(self).to(text);
-                input.set_version(self).to(version);
-                input.set_kind(self).to(kind);
+                if input.text(self).as_ref() != text.as_ref() {
+                    input.set_text(self).with_durability(durability).to(text);
+                }
+                if input.version(self) != version {
+                    input
+                        .set_version(self)
+                        .with_durability(durability)
+                        .to(version);
+                }
+                if input.kind(self) != kind {
+                    input.set_kind(self).with_durability(durability).to(kind);
+                }
             }
             None => {
-                let input = FileInput::new(self, text, version, kind);
+                let input = FileInput::builder(text, version, kind, Arc::from([]))
+                    .durability(durability)
+                    .new(self);
                 self.inputs.files.insert(file_id, input);
+                self.bump_file_set();
+            }
+        }
+    }
+
+    /// Records the edits that produced the current text of `file_id`, so its syntax tree can be
+    /// reparsed from the previous version's. Set it along with the text, at the same durability.
+    pub fn set_file_edits(
+        &mut self,
+        file_id: FileId,
+        edits: Arc<[OverlayEdit]>,
+        durability: Durability,
+    ) {
+        let Some(input) = self.inputs.files.get(&file_id).copied() else {
+            return;
+        };
+        if input.edits(self).as_ref() != edits.as_ref() {
+            input.set_edits(self).with_durability(durability).to(edits);
+        }
+    }
+
+    /// Forgets `file_id` and its path mapping. Queries that enumerated it re-run without it.
+    pub fn remove_file(&mut self, file_id: FileId) {
+        let removed = self.inputs.files.remove(&file_id);
+        if let Some(input) = removed {
+            self.syntax_trees.remove(input);
+            self.memo_sizes.retain(|key| match key {
+                MemoKey::ParsedFile(file) | MemoKey::FileItems(file) => *file != input,
+                MemoKey::DefMap(_) | MemoKey::Program(_) => true,
+            });
+        }
+        let removed = removed.is_some();
+        if let Some(input) = self.inputs.paths.remove(&file_id) {
+            let path = input.path(self).clone();
+            if self.inputs.path_to_file_id.get(path.as_ref()) == Some(&file_id) {
+                self.inputs.path_to_file_id.remove(path.as_ref());
             }
         }
+        if removed {
+            self.bump_file_set();
+        }
+    }
+
+    /// Removes every file for which `keep` returns false.
+    pub fn retain_files(&mut self, mut keep: impl FnMut(FileId) -> bool) {
+        let stale = self
+            .inputs
+            .files
+            .keys()
+            .copied()
+            .filter(|file_id| !keep(*file_id))
+            .collect::<Vec<_>>();
+        for file_id in stale {
+            self.remove_file(file_id);
+        }
+    }
+
+    fn bump_file_set(&mut self) {
+        self.node_modules.clear();
+        if let Some(input) = self.inputs.file_set {
+            let generation = input.generation(self);
+            input.set_generation(self).to(generation.wrapping_add(1));
+        }
     }
 
     pub fn set_file(
@@ -139,8 +381,23 @@ impl Database {
         kind: LanguageKind,
         path: Arc<NormalizedPath>,
     ) {
-        self.set_file_input(file_id, text, version, kind);
-        self.set_file_path(file_id, path);
+        self.set_file_with_durability(file_id, text, version, kind, path, Durability::LOW);
+    }
+
+    /// Like [`Database::set_file`], for files that rarely change. Dependency files are set with
+    /// `Durability::HIGH` so that queries reading only them are not revalidated after edits to
+    /// low-durability application files.
+    pub fn set_file_with_durability(
+        &mut self,
+        file_id: FileId,
+        text: Arc<str>,
+        version: u32,
+        kind: LanguageKind,
+        path: Arc<NormalizedPath>,
+        durability: Durability,
+    ) {
+        self.set_file_input_with_durability(file_id, text, version, kind, durability);
+        self.set_file_path_with_durability(file_id, path, durability);
     }
 
     pub fn file_path(&self, file_id: FileId) -> Arc<NormalizedPath> {
@@ -148,17 +405,28 @@ impl Database {
     }
 
     pub fn set_file_path(&mut self, file_id: FileId, path: Arc<NormalizedPath>) {
+        self.set_file_path_with_durability(file_id, path, Durability::LOW);
+    }
+
+    fn set_file_path_with_durability(
+        &mut self,
+        file_id: FileId,
+        path: Arc<NormalizedPath>,
+        durability: Durability,
+    ) {
         let input = self.inputs.paths.get(&file_id).copied();
         match input {
             Some(input) => {
                 let previous = input.path(self).clone();
                 if previous.as_ref() != path.as_ref() {
                     self.inputs.path_to_file_id.remove(previous.as_ref());
+                    input.set_path(self).with_durability(durability).to(path);
                 }
-                input.set_path(self).to(path);
             }
             None => {
-                let input = FilePathInput::new(self, path);
+                let input = FilePathInput::builder(path)
+                    .durability(durability)
+                    .new(self);
                 self.inputs.paths.insert(file_id, input);
             }
         }
@@ -168,7 +436,12 @@ impl Database {
             .insert(stored_path.path(self).as_ref().clone(), file_id);
     }
 
+    /// Returns every file in the database. Files excluded from indexing only get here when the
+    /// user opens them, and then they belong to every consumer like any other file.
     pub fn file_ids(&self) -> impl Iterator<Item = FileId> + '_ {
+        if let Some(file_set) = self.inputs.file_set {
+            file_set.generation(self);
+        }
         self.inputs.files.keys().copied()
     }
 
@@ -184,7 +457,43 @@ impl Database {
         self.inputs.project_input_opt(project_id)
     }
 
+    /// Forgets where node_modules packages were found, e.g. after a package directory or symlink
+    /// appeared or disappeared without changing any file the database knows.
+    pub fn clear_node_modules(&self) {
+        self.node_modules.clear();
+    }
+
+    /// The sizes of the query results salsa holds, as recorded when they were computed.
+    pub fn memo_sizes(&self) -> Vec<(MemoKey, MemoSize)> {
+        self.memo_sizes.to_vec()
+    }
+
+    /// How many files have a syntax tree kept for incremental reparsing.
+    pub fn syntax_tree_count(&self) -> usize {
+        self.syntax_trees.len()
+    }
+
+    pub fn project_ids(&self) -> Vec<ProjectId> {
+        let mut ids = self.inputs.projects.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Returns the project with the deepest root containing `path`.
+    pub fn project_for_path(&self, path: &NormalizedPath) -> Option<ProjectId> {
+        self.inputs
+            .projects
+            .iter()
+            .filter_map(|(project_id, input)| {
+                let root = input.workspace(self).root();
+                WorkspacePath::new(root, path).map(|_| (root.as_str().len(), *project_id))
+            })
+            .max()
+            .map(|(_, project_id)| project_id)
+    }
+
     pub fn set_project_input(&mut self, project_id: ProjectId, config: Arc<ResolvedFoundryConfig>) {
+        self.node_modules.clear();
         let workspace = Arc::new(config.workspace().clone());
         let input = self.inputs.projects.get(&project_id).copied();
         match input {
@@ -193,11 +502,35 @@ impl Database {
                 input.set_config(self).to(config);
             }
             None => {
-                let input = ProjectInput::new(self, workspace, config);
+                let input = ProjectInput::new(self, workspace, config, Arc::default());
                 self.inputs.projects.insert(project_id, input);
             }
         }
     }
+
+    /// Forgets a project, e.g. a workspace folder the client closed. Its files stay until they
+    /// are removed separately.
+    pub fn remove_project(&mut self, project_id: ProjectId) {
+        if let Some(input) = self.inputs.projects.remove(&project_id) {
+            self.memo_sizes.retain(|key| match key {
+                MemoKey::DefMap(project) | MemoKey::Program(project) => *project != input,
+                MemoKey::ParsedFile(_) | MemoKey::FileItems(_) => true,
+            });
+            // The owner and index filter of the project's files changed.
+            self.bump_file_set();
+        }
+    }
+
+    /// Replaces the build artifacts of a loaded project; unknown projects are ignored.
+    pub fn set_project_artifacts(
+        &mut self,
+        project_id: ProjectId,
+        artifacts: Arc<Vec<sa_project_model::ContractArtifact>>,
+    ) {
+        if let Some(input) = self.inputs.projects.get(&project_id).copied() {
+            input.set_artifacts(self).to(artifacts);
+        }
+    }
 }
 
 pub trait SaDatabaseExt {
@@ -205,6 +538,15 @@ pub trait SaDatabaseExt {
     fn file_path(&self, file_id: FileId) -> Arc<NormalizedPath>;
     fn file_ids(&self) -> Vec<FileId>;
     fn project_input(&self, project_id: ProjectId) -> ProjectInput;
+    fn check_cancelled(&self);
+    /// The newest syntax tree built for `file` and the version of the text it was built from.
+    fn syntax_tree(&self, file: FileInput) -> Option<(u32, Arc<Cst>)>;
+    /// Keeps `cst` as the tree of `file` at `version`, unless a newer one is kept already.
+    fn set_syntax_tree(&self, file: FileInput, version: u32, cst: Arc<Cst>);
+    /// Records the size of the result a query is about to return; see [`MemoKey`].
+    fn record_memo_size(&self, key: MemoKey, size: MemoSize);
+    /// The node_modules lookups of the project rooted at `root`, shared by its resolvers.
+    fn node_modules(&self, root: &NormalizedPath) -> Arc<NodeModulesCache>;
 }
 
 impl SaDatabaseExt for Database {
@@ -223,16 +565,40 @@ impl SaDatabaseExt for Database {
     fn project_input(&self, project_id: ProjectId) -> ProjectInput {
         self.project_input(project_id)
     }
+
+    fn check_cancelled(&self) {
+        self.check_cancelled()
+    }
+
+    fn syntax_tree(&self, file: FileInput) -> Option<(u32, Arc<Cst>)> {
+        self.syntax_trees.get(file)
+    }
+
+    fn set_syntax_tree(&self, file: FileInput, version: u32, cst: Arc<Cst>) {
+        self.syntax_trees.insert(file, version, cst);
+    }
+
+    fn record_memo_size(&self, key: MemoKey, size: MemoSize) {
+        self.memo_sizes.insert(key, size);
+    }
+
+    fn node_modules(&self, root: &NormalizedPath) -> Arc<NodeModulesCache> {
+        self.node_modules.get(root)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::panic::AssertUnwindSafe;
     use std::sync::Arc;
 
-    use super::{Database, FileId, LanguageKind, ProjectId};
+    use super::{
+        CancellationToken, Database, Durability, FileId, LanguageKind, MemoKey, MemoSize,
+        ProjectId, SaDatabaseExt,
+    };
     use sa_config::ResolvedFoundryConfig;
     use sa_paths::NormalizedPath;
-    use sa_project_model::{FoundryProfile, FoundryWorkspace};
+    use sa_project_model::{FoundryProfile, FoundryWorkspace, IndexFilter};
 
     fn path(value: &str) -> Arc<NormalizedPath> {
         Arc::new(NormalizedPath::new(value))
@@ -286,6 +652,62 @@ mod tests {
         assert_eq!(input.config(&db), &config);
     }
 
+    #[test]
+    fn project_for_path_picks_nearest_root() {
+        let mut db = Database::default();
+        let profile = FoundryProfile::new("default");
+        for (raw, root) in [(0, "/workspace"), (1, "/workspace/lib/dep"), (2, "/other")] {
+            let workspace = FoundryWorkspace::new(NormalizedPath::new(root));
+            let config = ResolvedFoundryConfig::new(workspace, profile.clone());
+            db.set_project_input(ProjectId::from_raw(raw), Arc::new(config));
+        }
+
+        let project_for = |value: &str| db.project_for_path(&NormalizedPath::new(value));
+        assert_eq!(
+            project_for("/workspace/src/Main.sol"),
+            Some(ProjectId::from_raw(0))
+        );
+        assert_eq!(
+            project_for("/workspace/lib/dep/src/Dep.sol"),
+            Some(ProjectId::from_raw(1))
+        );
+        assert_eq!(
+            project_for("/workspace/lib/dependency/Dep.sol"),
+            Some(ProjectId::from_raw(0))
+        );
+        assert_eq!(project_for("/elsewhere/Main.sol"), None);
+        assert_eq!(db.project_ids().len(), 3);
+    }
+
+    #[test]
+    fn file_ids_keep_open_files_excluded_by_index_filter() {
+        let mut db = Database::default();
+        let filter = IndexFilter::new(Vec::new(), vec!["out".to_string()]).expect("filter");
+        let workspace =
+            FoundryWorkspace::new(NormalizedPath::new("/workspace")).with_index_filter(filter);
+        let config = ResolvedFoundryConfig::new(workspace, FoundryProfile::new("default"));
+        db.set_project_input(ProjectId::from_raw(0), Arc::new(config));
+
+        let kept = FileId::from_raw(0);
+        let excluded = FileId::from_raw(1);
+        for (file_id, file_path) in [
+            (kept, "/workspace/src/Main.sol"),
+            (excluded, "/workspace/out/Main.sol"),
+        ] {
+            db.set_file(
+                file_id,
+                Arc::from("contract Main {}"),
+                0,
+                LanguageKind::Solidity,
+                path(file_path),
+            );
+        }
+
+        let mut file_ids = db.file_ids().collect::<Vec<_>>();
+        file_ids.sort();
+        assert_eq!(file_ids, vec![kept, excluded]);
+    }
+
     #[test]
     fn set_file_registers_path_mapping() {
         let mut db = Database::default();
@@ -304,6 +726,91 @@ mod tests {
         assert_eq!(db.file_path(file_id).as_ref(), file_path.as_ref());
     }
 
+    #[test]
+    fn remove_file_drops_inputs_and_path_mapping() {
+        let mut db = Database::default();
+        let kept = FileId::from_raw(0);
+        let removed = FileId::from_raw(1);
+        for (file_id, file_path) in [
+            (kept, "/workspace/src/Kept.sol"),
+            (removed, "/workspace/src/Removed.sol"),
+        ] {
+            db.set_file(
+                file_id,
+                Arc::from("contract C {}"),
+                0,
+                LanguageKind::Solidity,
+                path(file_path),
+            );
+        }
+
+        db.retain_files(|file_id| file_id != removed);
+
+        let mut file_ids = db.file_ids().collect::<Vec<_>>();
+        file_ids.sort();
+        assert_eq!(file_ids, vec![kept, excluded]);
+        assert_eq!(
+            db.file_id_for_path(path("/workspace/src/Removed.sol").as_ref()),
+            None
+        );
+        assert_eq!(
+            db.file_id_for_path(path("/workspace/src/Kept.sol").as_ref()),
+            Some(kept)
+        );
+    }
+
+    #[test]
+    fn resetting_unchanged_files_keeps_their_inputs() {
+        let mut db = Database::default();
+        let file_id = FileId::from_raw(0);
+        let original: Arc<str> = Arc::from("contract Dep {}");
+        db.set_file_with_durability(
+            file_id,
+            Arc::clone(&original),
+            0,
+            LanguageKind::Solidity,
+            path("/workspace/lib/dep/src/Dep.sol"),
+            Durability::HIGH,
+        );
+
+        db.set_file_with_durability(
+            file_id,
+            Arc::from("contract Dep {}"),
+            0,
+            LanguageKind::Solidity,
+            path("/workspace/lib/dep/src/Dep.sol"),
+            Durability::HIGH,
+        );
+        assert!(Arc::ptr_eq(db.file_input(file_id).text(&db), &original));
+
+        db.set_file(
+            file_id,
+            Arc::from("contract Dep { uint x; }"),
+            1,
+            LanguageKind::Solidity,
+            path("/workspace/lib/dep/src/Dep.sol"),
+        );
+        assert_eq!(
+            db.file_input(file_id).text(&db).as_ref(),
+            "contract Dep { uint x; }"
+        );
+    }
+
+    #[test]
+    fn cancelled_token_aborts_only_its_handle() {
+        let db = Database::default();
+        let mut request_db = db.clone();
+        let token = CancellationToken::new();
+        request_db.set_cancellation(token.clone());
+
+        let check =
+            |db: &Database| salsa::Cancelled::catch(AssertUnwindSafe(|| db.check_cancelled()));
+        assert!(check(&request_db).is_ok());
+        drop(token.drop_guard());
+        assert!(check(&request_db).is_err());
+        assert!(check(&db).is_ok());
+    }
+
     #[test]
     fn file_id_for_path_updates_when_path_changes() {
         let mut db = Database::default();
@@ -319,4 +826,46 @@ mod tests {
         assert_eq!(db.file_id_for_path(new_path.as_ref()), Some(file_id));
         assert_eq!(db.file_path(file_id).as_ref(), new_path.as_ref());
     }
+
+    #[test]
+    fn node_modules_lookups_are_shared_until_the_file_set_changes() {
+        let mut db = Database::default();
+        let root = NormalizedPath::new("/workspace");
+        let cache = db.node_modules(&root);
+        assert!(Arc::ptr_eq(&cache, &db.clone().node_modules(&root)));
+        assert!(!Arc::ptr_eq(
+            &cache,
+            &db.node_modules(&NormalizedPath::new("/other"))
+        ));
+
+        db.set_file_input(
+            FileId::from_raw(0),
+            Arc::from("contract A {}"),
+            0,
+            LanguageKind::Solidity,
+        );
+        assert!(!Arc::ptr_eq(&cache, &db.node_modules(&root)));
+    }
+
+    #[test]
+    fn memo_sizes_are_dropped_with_their_file() {
+        let mut db = Database::default();
+        let file_id = FileId::from_raw(0);
+        db.set_file_input(
+            file_id,
+            Arc::from("contract A {}"),
+            0,
+            LanguageKind::Solidity,
+        );
+        let key = MemoKey::ParsedFile(db.file_input(file_id));
+        let size = MemoSize {
+            bytes: 8,
+            entries: 0,
+        };
+        db.record_memo_size(key, size);
+        assert_eq!(db.memo_sizes(), vec![(key, size)]);
+
+        db.remove_file(file_id);
+        assert!(db.memo_sizes().is_empty());
+    }
 }
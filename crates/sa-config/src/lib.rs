@@ -7,7 +7,7 @@ use foundry_compilers::error::SolcError;
 use foundry_compilers::solc::Solc;
 use foundry_config::fmt::FormatterConfig;
 use foundry_config::{Config, figment::Profile};
-use sa_project_model::{FoundryProfile, FoundryWorkspace};
+use sa_project_model::{FoundryProfile, FoundryWorkspace, IndexFilter};
 use solar_config::{ImportRemapping, Opts as SolarOpts};
 
 mod formatting;
@@ -64,6 +64,11 @@ impl ResolvedFoundryConfig {
         self
     }
 
+    pub fn with_index_filter(mut self, index_filter: IndexFilter) -> Self {
+        self.workspace = self.workspace.with_index_filter(index_filter);
+        self
+    }
+
     pub fn with_foundry_config(mut self, config: Config) -> Self {
         let mut config = config;
         sync_profile(&mut config, &self.active_profile);
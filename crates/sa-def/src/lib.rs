@@ -1,40 +1,59 @@
 use std::collections::HashMap;
 
 use sa_base_db::FileId;
+pub use sa_intern::Name;
 use sa_intern::{InternId, Interner};
-use sa_span::TextRange;
+use sa_span::{TextRange, TextSize};
 use sa_syntax::Parse;
-use solar_ast::{Ident, ItemKind, SourceUnit};
+use sa_syntax::visit::{Visitor, walk_stmt};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use solar_ast::{Block, Ident, ItemFunction, ItemKind, SourceUnit, Stmt, StmtKind, yul};
 use tracing::warn;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ContractId(InternId);
+/// A definition's file and its index among the file's definitions of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct LocalId {
+    file_id: FileId,
+    index: InternId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContractId(LocalId);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct FunctionId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StructId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct StructId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnumId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct EnumId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EventId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct EventId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ErrorId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ErrorId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModifierId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ModifierId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VariableId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct VariableId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UdvtId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct UdvtId(InternId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FieldId(LocalId);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VariantId(LocalId);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct YulFunctionId(LocalId);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DefId {
     Contract(ContractId),
     Function(FunctionId),
@@ -45,9 +64,31 @@ pub enum DefId {
     Modifier(ModifierId),
     Variable(VariableId),
     Udvt(UdvtId),
+    Field(FieldId),
+    Variant(VariantId),
+    YulFunction(YulFunctionId),
+}
+
+impl DefId {
+    fn new(kind: DefKind, id: LocalId) -> Self {
+        match kind {
+            DefKind::Contract => DefId::Contract(ContractId(id)),
+            DefKind::Function => DefId::Function(FunctionId(id)),
+            DefKind::Struct => DefId::Struct(StructId(id)),
+            DefKind::Enum => DefId::Enum(EnumId(id)),
+            DefKind::Event => DefId::Event(EventId(id)),
+            DefKind::Error => DefId::Error(ErrorId(id)),
+            DefKind::Modifier => DefId::Modifier(ModifierId(id)),
+            DefKind::Variable => DefId::Variable(VariableId(id)),
+            DefKind::Udvt => DefId::Udvt(UdvtId(id)),
+            DefKind::Field => DefId::Field(FieldId(id)),
+            DefKind::Variant => DefId::Variant(VariantId(id)),
+            DefKind::YulFunction => DefId::YulFunction(YulFunctionId(id)),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DefKind {
     Contract,
     Function,
@@ -58,12 +99,19 @@ pub enum DefKind {
     Modifier,
     Variable,
     Udvt,
+    /// A struct field; its container is the struct's name.
+    Field,
+    /// An enum variant; its container is the enum's name.
+    Variant,
+    /// A function declared in inline assembly; its container is the name of the enclosing
+    /// Solidity function (or its keyword for constructors, `receive` and `fallback`).
+    YulFunction,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DefLocation {
     file_id: FileId,
-    name: String,
+    name: Name,
     range: TextRange,
 }
 
@@ -76,17 +124,22 @@ impl DefLocation {
         &self.name
     }
 
+    /// The name shared with every other holder of the same text; cloning it does not allocate.
+    pub fn interned_name(&self) -> &Name {
+        &self.name
+    }
+
     pub fn range(&self) -> TextRange {
         self.range
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DefEntry {
     id: DefId,
     kind: DefKind,
     location: DefLocation,
-    container: Option<String>,
+    container: Option<Name>,
 }
 
 impl DefEntry {
@@ -105,14 +158,23 @@ impl DefEntry {
     pub fn container(&self) -> Option<&str> {
         self.container.as_deref()
     }
+
+    /// A constructor, `receive` or `fallback` function, indexed under its keyword.
+    pub fn is_special_function(&self) -> bool {
+        self.kind == DefKind::Function
+            && matches!(
+                self.location.name.as_str(),
+                "constructor" | "receive" | "fallback"
+            )
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct DefMap {
     entries: Vec<DefEntry>,
     index: HashMap<DefId, usize>,
-    name_index: HashMap<DefNameKey, Vec<usize>>,
-    file_name_index: HashMap<FileNameKey, Vec<usize>>,
+    name_index: HashMap<DefKind, HashMap<Name, Vec<usize>>>,
+    file_name_index: HashMap<FileId, HashMap<Name, Vec<usize>>>,
 }
 
 impl PartialEq for DefMap {
@@ -123,11 +185,54 @@ impl PartialEq for DefMap {
 
 impl Eq for DefMap {}
 
+/// Only the entries are written; the indexes are rebuilt when reading the map back.
+impl Serialize for DefMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.entries.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DefMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut map = DefMap::default();
+        for entry in Vec::<DefEntry>::deserialize(deserializer)? {
+            map.insert_entry(entry);
+        }
+        Ok(map)
+    }
+}
+
 impl DefMap {
     pub fn entries(&self) -> &[DefEntry] {
         &self.entries
     }
 
+    /// A rough estimate of the heap memory held by the map and its indexes. Names are shared
+    /// with the indexes, so they are only counted for the entries.
+    pub fn estimated_bytes(&self) -> usize {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| {
+                size_of::<DefEntry>()
+                    + entry.location.name.len()
+                    + entry
+                        .container
+                        .as_ref()
+                        .map_or(0, |container| container.len())
+            })
+            .sum::<usize>();
+        let index = self.index.len() * size_of::<(DefId, usize)>();
+        let name_index = self
+            .name_index
+            .values()
+            .chain(self.file_name_index.values())
+            .flat_map(HashMap::values)
+            .map(|indices| size_of::<(Name, Vec<usize>)>() + indices.len() * 8)
+            .sum::<usize>();
+        entries + index + name_index
+    }
+
     pub fn entry(&self, id: DefId) -> Option<&DefEntry> {
         self.index.get(&id).and_then(|idx| self.entries.get(*idx))
     }
@@ -138,24 +243,21 @@ impl DefMap {
     }
 
     pub fn entries_by_name(&self, kind: DefKind, name: &str) -> Option<Vec<&DefEntry>> {
-        let key = DefNameKey {
-            kind,
-            name: name.to_string(),
-        };
-        self.name_index.get(&key).map(|indices| {
+        let indices = self.name_index.get(&kind)?.get(name)?;
+        Some(
             indices
                 .iter()
                 .filter_map(|idx| self.entries.get(*idx))
-                .collect()
-        })
+                .collect(),
+        )
     }
 
     pub fn entries_by_name_in_file(&self, file_id: FileId, name: &str) -> Vec<&DefEntry> {
-        let key = FileNameKey {
-            file_id,
-            name: name.to_string(),
-        };
-        let Some(indices) = self.file_name_index.get(&key) else {
+        let Some(indices) = self
+            .file_name_index
+            .get(&file_id)
+            .and_then(|names| names.get(name))
+        else {
             return Vec::new();
         };
         indices
@@ -192,568 +294,344 @@ impl DefMap {
     }
 }
 
+/// The key a definition is interned under within its file.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct DefNameKey {
-    kind: DefKind,
-    name: String,
+struct DefKey {
+    name: Name,
+    container: Option<Name>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct FileNameKey {
-    file_id: FileId,
-    name: String,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct ContractKey {
-    file_id: FileId,
-    name: String,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct FunctionKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct StructKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
+/// The definitions of one file with their ids, as produced by [`FileDefs::lower`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDefMap {
+    entries: Vec<DefEntry>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct EnumKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
+impl FileDefMap {
+    pub fn entries(&self) -> &[DefEntry] {
+        &self.entries
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct EventKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
-}
+impl DefMap {
+    /// Collects and lowers every file, then merges the results.
+    pub fn collect<'a>(files: impl IntoIterator<Item = (FileId, &'a str)>) -> DefMap {
+        let files = files
+            .into_iter()
+            .map(|(file_id, text)| FileDefs::collect(text).lower(file_id))
+            .collect::<Vec<_>>();
+        Self::merge(&files)
+    }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct ErrorKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
-}
+    /// Builds the project map out of already lowered files. Nothing is re-interned, so this only
+    /// costs the index rebuild.
+    pub fn merge<'a>(files: impl IntoIterator<Item = &'a FileDefMap>) -> DefMap {
+        let mut map = DefMap::default();
+        for file in files {
+            for entry in &file.entries {
+                map.insert_entry(entry.clone());
+            }
+        }
+        map
+    }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct ModifierKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
+    fn insert_entry(&mut self, entry: DefEntry) {
+        let id = entry.id;
+        let idx = self.entries.len();
+        self.name_index
+            .entry(entry.kind)
+            .or_default()
+            .entry(entry.location.name.clone())
+            .or_default()
+            .push(idx);
+        self.file_name_index
+            .entry(entry.location.file_id)
+            .or_default()
+            .entry(entry.location.name.clone())
+            .or_default()
+            .push(idx);
+        self.entries.push(entry);
+        self.index.insert(id, idx);
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct VariableKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
+/// The definitions declared in one file, before they are given ids. Collecting them only needs
+/// the file's text, so files can be collected independently (and in parallel).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDefs {
+    defs: Vec<FileDef>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct UdvtKey {
-    file_id: FileId,
-    name: String,
-    container: Option<String>,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileDef {
+    kind: DefKind,
+    name: Name,
+    range: TextRange,
+    container: Option<Name>,
 }
 
-#[derive(Debug, Default)]
-pub struct DefDatabase {
-    contract_interner: Interner<ContractKey>,
-    function_interner: Interner<FunctionKey>,
-    struct_interner: Interner<StructKey>,
-    enum_interner: Interner<EnumKey>,
-    event_interner: Interner<EventKey>,
-    error_interner: Interner<ErrorKey>,
-    modifier_interner: Interner<ModifierKey>,
-    variable_interner: Interner<VariableKey>,
-    udvt_interner: Interner<UdvtKey>,
-}
+impl FileDefs {
+    pub fn collect(text: &str) -> Self {
+        let parse = sa_syntax::parse_file(text);
+        let mut defs = Vec::new();
+        collect_source_unit(&parse, text, parse.tree(), &mut defs);
+        Self { defs }
+    }
 
-impl DefDatabase {
-    pub fn new() -> Self {
-        Self::default()
+    /// Assigns ids to the definitions of `file_id`. Ids are interned per file, so they only
+    /// depend on this file's definitions and stay the same while other files change.
+    pub fn lower(&self, file_id: FileId) -> FileDefMap {
+        let mut interners: HashMap<DefKind, Interner<DefKey>> = HashMap::new();
+        let entries = self
+            .defs
+            .iter()
+            .map(|def| {
+                let index = interners.entry(def.kind).or_default().intern(DefKey {
+                    name: def.name.clone(),
+                    container: def.container.clone(),
+                });
+                DefEntry {
+                    id: DefId::new(def.kind, LocalId { file_id, index }),
+                    kind: def.kind,
+                    location: DefLocation {
+                        file_id,
+                        name: def.name.clone(),
+                        range: def.range,
+                    },
+                    container: def.container.clone(),
+                }
+            })
+            .collect();
+        FileDefMap { entries }
     }
 
-    pub fn collect<'a>(&mut self, files: impl IntoIterator<Item = (FileId, &'a str)>) -> DefMap {
-        let mut map = DefMap::default();
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
 
-        for (file_id, text) in files {
-            let parse = sa_syntax::parse_file(text);
-            let tree = parse.tree();
-            self.collect_source_unit(&parse, file_id, tree, &mut map);
-        }
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
 
-        map
+    pub fn estimated_bytes(&self) -> usize {
+        self.defs
+            .iter()
+            .map(|def| {
+                size_of::<FileDef>()
+                    + def.name.len()
+                    + def
+                        .container
+                        .as_ref()
+                        .map_or(0, |container| container.len())
+            })
+            .sum()
     }
+}
 
-    fn collect_source_unit(
-        &mut self,
-        parse: &Parse,
-        file_id: FileId,
-        unit: &SourceUnit<'_>,
-        map: &mut DefMap,
-    ) {
-        for item in unit.items.iter() {
-            match &item.kind {
-                ItemKind::Contract(contract) => {
-                    let ident = contract.name;
-                    let name = ident_text(parse, ident);
-                    let range = ident_range(parse, ident);
-                    if let Some(range) = range {
-                        let id = self.intern_contract(file_id, &name);
-                        map.insert_entry(DefEntry {
-                            id: DefId::Contract(id),
-                            kind: DefKind::Contract,
-                            location: DefLocation {
-                                file_id,
-                                name: name.clone(),
-                                range,
-                            },
-                            container: None,
-                        });
-                        self.collect_contract_items(parse, file_id, &name, &contract.body, map);
-                    }
-                }
-                ItemKind::Function(function) => {
-                    if let Some(ident) = function.header.name {
-                        let name = ident_text(parse, ident);
-                        let Some(range) = ident_range(parse, ident) else {
-                            continue;
-                        };
-                        match function.kind {
-                            solar_ast::FunctionKind::Modifier => {
-                                let id = self.intern_modifier(file_id, &name, None);
-                                map.insert_entry(DefEntry {
-                                    id: DefId::Modifier(id),
-                                    kind: DefKind::Modifier,
-                                    location: DefLocation {
-                                        file_id,
-                                        name: name.clone(),
-                                        range,
-                                    },
-                                    container: None,
-                                });
-                            }
-                            _ => {
-                                let id = self.intern_function(file_id, &name, None);
-                                map.insert_entry(DefEntry {
-                                    id: DefId::Function(id),
-                                    kind: DefKind::Function,
-                                    location: DefLocation {
-                                        file_id,
-                                        name: name.clone(),
-                                        range,
-                                    },
-                                    container: None,
-                                });
-                            }
-                        }
-                    }
-                }
-                ItemKind::Variable(item) => {
-                    if let Some(ident) = item.name {
-                        let name = ident_text(parse, ident);
-                        let Some(range) = ident_range(parse, ident) else {
-                            continue;
-                        };
-                        let id = self.intern_variable(file_id, &name, None);
-                        map.insert_entry(DefEntry {
-                            id: DefId::Variable(id),
-                            kind: DefKind::Variable,
-                            location: DefLocation {
-                                file_id,
-                                name: name.clone(),
-                                range,
-                            },
-                            container: None,
-                        });
-                    }
-                }
-                ItemKind::Struct(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_struct(file_id, &name, None);
-                    map.insert_entry(DefEntry {
-                        id: DefId::Struct(id),
-                        kind: DefKind::Struct,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: None,
-                    });
-                }
-                ItemKind::Enum(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_enum(file_id, &name, None);
-                    map.insert_entry(DefEntry {
-                        id: DefId::Enum(id),
-                        kind: DefKind::Enum,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: None,
-                    });
-                }
-                ItemKind::Event(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_event(file_id, &name, None);
-                    map.insert_entry(DefEntry {
-                        id: DefId::Event(id),
-                        kind: DefKind::Event,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: None,
-                    });
-                }
-                ItemKind::Error(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_error(file_id, &name, None);
-                    map.insert_entry(DefEntry {
-                        id: DefId::Error(id),
-                        kind: DefKind::Error,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: None,
-                    });
-                }
-                ItemKind::Udvt(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_udvt(file_id, &name, None);
-                    map.insert_entry(DefEntry {
-                        id: DefId::Udvt(id),
-                        kind: DefKind::Udvt,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: None,
-                    });
-                }
-                _ => {}
+fn collect_source_unit(
+    parse: &Parse,
+    text: &str,
+    unit: &SourceUnit<'static>,
+    defs: &mut Vec<FileDef>,
+) {
+    for item in unit.items.iter() {
+        if let ItemKind::Contract(contract) = &item.kind {
+            let name = ident_text(parse, contract.name);
+            let Some(range) = ident_range(parse, contract.name) else {
+                continue;
+            };
+            defs.push(FileDef {
+                kind: DefKind::Contract,
+                name: name.clone(),
+                range,
+                container: None,
+            });
+            for item in contract.body.iter() {
+                collect_item(parse, text, item, Some(&name), defs);
             }
+        } else {
+            collect_item(parse, text, item, None, defs);
         }
     }
+}
 
-    fn collect_contract_items(
-        &mut self,
-        parse: &Parse,
-        file_id: FileId,
-        contract_name: &str,
-        items: &solar_ast::BoxSlice<'_, solar_ast::Item<'_>>,
-        map: &mut DefMap,
-    ) {
-        for item in items.iter() {
-            match &item.kind {
-                ItemKind::Function(function) => {
-                    if let Some(ident) = function.header.name {
-                        let name = ident_text(parse, ident);
-                        let Some(range) = ident_range(parse, ident) else {
-                            continue;
-                        };
-                        match function.kind {
-                            solar_ast::FunctionKind::Modifier => {
-                                let id = self.intern_modifier(file_id, &name, Some(contract_name));
-                                map.insert_entry(DefEntry {
-                                    id: DefId::Modifier(id),
-                                    kind: DefKind::Modifier,
-                                    location: DefLocation {
-                                        file_id,
-                                        name: name.clone(),
-                                        range,
-                                    },
-                                    container: Some(contract_name.to_string()),
-                                });
-                            }
-                            _ => {
-                                let id = self.intern_function(file_id, &name, Some(contract_name));
-                                map.insert_entry(DefEntry {
-                                    id: DefId::Function(id),
-                                    kind: DefKind::Function,
-                                    location: DefLocation {
-                                        file_id,
-                                        name: name.clone(),
-                                        range,
-                                    },
-                                    container: Some(contract_name.to_string()),
-                                });
-                            }
-                        }
-                    }
-                }
-                ItemKind::Variable(item) => {
-                    if let Some(ident) = item.name {
-                        let name = ident_text(parse, ident);
-                        let Some(range) = ident_range(parse, ident) else {
-                            continue;
-                        };
-                        let id = self.intern_variable(file_id, &name, Some(contract_name));
-                        map.insert_entry(DefEntry {
-                            id: DefId::Variable(id),
-                            kind: DefKind::Variable,
-                            location: DefLocation {
-                                file_id,
-                                name: name.clone(),
-                                range,
-                            },
-                            container: Some(contract_name.to_string()),
-                        });
-                    }
-                }
-                ItemKind::Struct(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_struct(file_id, &name, Some(contract_name));
-                    map.insert_entry(DefEntry {
-                        id: DefId::Struct(id),
-                        kind: DefKind::Struct,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: Some(contract_name.to_string()),
-                    });
-                }
-                ItemKind::Enum(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_enum(file_id, &name, Some(contract_name));
-                    map.insert_entry(DefEntry {
-                        id: DefId::Enum(id),
-                        kind: DefKind::Enum,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: Some(contract_name.to_string()),
-                    });
-                }
-                ItemKind::Event(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
+fn collect_item(
+    parse: &Parse,
+    text: &str,
+    item: &solar_ast::Item<'static>,
+    container: Option<&str>,
+    defs: &mut Vec<FileDef>,
+) {
+    let (kind, ident) = match &item.kind {
+        ItemKind::Function(function) => {
+            let name = match function.header.name {
+                Some(ident) => {
+                    let kind = match function.kind {
+                        solar_ast::FunctionKind::Modifier => DefKind::Modifier,
+                        _ => DefKind::Function,
                     };
-                    let id = self.intern_event(file_id, &name, Some(contract_name));
-                    map.insert_entry(DefEntry {
-                        id: DefId::Event(id),
-                        kind: DefKind::Event,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: Some(contract_name.to_string()),
-                    });
+                    push_def(parse, kind, ident, container, defs);
+                    ident_text(parse, ident)
                 }
-                ItemKind::Error(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_error(file_id, &name, Some(contract_name));
-                    map.insert_entry(DefEntry {
-                        id: DefId::Error(id),
-                        kind: DefKind::Error,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: Some(contract_name.to_string()),
-                    });
+                None => {
+                    if let Some(container) = container {
+                        push_special_function(parse, text, function, container, defs);
+                    }
+                    Name::new(function.kind.to_str())
                 }
-                ItemKind::Udvt(item) => {
-                    let ident = item.name;
-                    let name = ident_text(parse, ident);
-                    let Some(range) = ident_range(parse, ident) else {
-                        continue;
-                    };
-                    let id = self.intern_udvt(file_id, &name, Some(contract_name));
-                    map.insert_entry(DefEntry {
-                        id: DefId::Udvt(id),
-                        kind: DefKind::Udvt,
-                        location: DefLocation {
-                            file_id,
-                            name: name.clone(),
-                            range,
-                        },
-                        container: Some(contract_name.to_string()),
-                    });
+            };
+            if let Some(body) = function.body.as_ref() {
+                collect_block_assembly(parse, body, &name, defs);
+            }
+            return;
+        }
+        ItemKind::Variable(item) => {
+            let Some(ident) = item.name else {
+                return;
+            };
+            (DefKind::Variable, ident)
+        }
+        ItemKind::Struct(item) => {
+            push_def(parse, DefKind::Struct, item.name, container, defs);
+            let name = ident_text(parse, item.name);
+            for field in item.fields.iter() {
+                if let Some(ident) = field.name {
+                    push_def(parse, DefKind::Field, ident, Some(&name), defs);
                 }
-                _ => {}
             }
+            return;
         }
-    }
-
-    fn intern_contract(&mut self, file_id: FileId, name: &str) -> ContractId {
-        let key = ContractKey {
-            file_id,
-            name: name.to_string(),
-        };
-        ContractId(self.contract_interner.intern(key))
-    }
-
-    fn intern_function(
-        &mut self,
-        file_id: FileId,
-        name: &str,
-        container: Option<&str>,
-    ) -> FunctionId {
-        let key = FunctionKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        FunctionId(self.function_interner.intern(key))
-    }
-
-    fn intern_struct(&mut self, file_id: FileId, name: &str, container: Option<&str>) -> StructId {
-        let key = StructKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        StructId(self.struct_interner.intern(key))
-    }
-
-    fn intern_enum(&mut self, file_id: FileId, name: &str, container: Option<&str>) -> EnumId {
-        let key = EnumKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        EnumId(self.enum_interner.intern(key))
-    }
+        ItemKind::Enum(item) => {
+            push_def(parse, DefKind::Enum, item.name, container, defs);
+            let name = ident_text(parse, item.name);
+            for variant in item.variants.iter() {
+                push_def(parse, DefKind::Variant, *variant, Some(&name), defs);
+            }
+            return;
+        }
+        ItemKind::Event(item) => (DefKind::Event, item.name),
+        ItemKind::Error(item) => (DefKind::Error, item.name),
+        ItemKind::Udvt(item) => (DefKind::Udvt, item.name),
+        _ => return,
+    };
+    push_def(parse, kind, ident, container, defs);
+}
 
-    fn intern_event(&mut self, file_id: FileId, name: &str, container: Option<&str>) -> EventId {
-        let key = EventKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        EventId(self.event_interner.intern(key))
-    }
+fn push_def(
+    parse: &Parse,
+    kind: DefKind,
+    ident: Ident,
+    container: Option<&str>,
+    defs: &mut Vec<FileDef>,
+) {
+    let name = ident_text(parse, ident);
+    let Some(range) = ident_range(parse, ident) else {
+        return;
+    };
+    defs.push(FileDef {
+        kind,
+        name,
+        range,
+        container: container.map(Name::new),
+    });
+}
 
-    fn intern_error(&mut self, file_id: FileId, name: &str, container: Option<&str>) -> ErrorId {
-        let key = ErrorKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        ErrorId(self.error_interner.intern(key))
-    }
+/// Constructors, `receive` and `fallback` have no name; they are indexed under their keyword,
+/// with the keyword as their range.
+fn push_special_function(
+    parse: &Parse,
+    text: &str,
+    function: &ItemFunction<'_>,
+    container: &str,
+    defs: &mut Vec<FileDef>,
+) {
+    let keyword = function.kind.to_str();
+    let Some(header) = parse.span_to_text_range(function.header.span) else {
+        return;
+    };
+    let Some(offset) = text
+        .get(usize::from(header.start())..usize::from(header.end()))
+        .and_then(|header| header.find(keyword))
+    else {
+        return;
+    };
+    let start = header.start() + TextSize::from(offset as u32);
+    defs.push(FileDef {
+        kind: DefKind::Function,
+        name: Name::new(keyword),
+        range: TextRange::at(start, TextSize::from(keyword.len() as u32)),
+        container: Some(Name::new(container)),
+    });
+}
 
-    fn intern_modifier(
-        &mut self,
-        file_id: FileId,
-        name: &str,
-        container: Option<&str>,
-    ) -> ModifierId {
-        let key = ModifierKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        ModifierId(self.modifier_interner.intern(key))
+/// Functions declared in the inline assembly of a function body, containered by that function.
+fn collect_block_assembly(
+    parse: &Parse,
+    block: &Block<'static>,
+    container: &str,
+    defs: &mut Vec<FileDef>,
+) {
+    struct AssemblyCollector<'p> {
+        parse: &'p Parse,
+        container: &'p str,
+        defs: &'p mut Vec<FileDef>,
     }
 
-    fn intern_variable(
-        &mut self,
-        file_id: FileId,
-        name: &str,
-        container: Option<&str>,
-    ) -> VariableId {
-        let key = VariableKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        VariableId(self.variable_interner.intern(key))
+    impl<'a> Visitor<'a> for AssemblyCollector<'_> {
+        fn visit_stmt(&mut self, stmt: &'a Stmt<'static>) {
+            if let StmtKind::Assembly(assembly) = &stmt.kind {
+                collect_yul_functions(self.parse, &assembly.block, self.container, self.defs);
+            }
+            walk_stmt(self, stmt);
+        }
     }
 
-    fn intern_udvt(&mut self, file_id: FileId, name: &str, container: Option<&str>) -> UdvtId {
-        let key = UdvtKey {
-            file_id,
-            name: name.to_string(),
-            container: container.map(ToString::to_string),
-        };
-        UdvtId(self.udvt_interner.intern(key))
+    AssemblyCollector {
+        parse,
+        container,
+        defs,
     }
+    .visit_block(block);
 }
 
-impl DefMap {
-    fn insert_entry(&mut self, entry: DefEntry) {
-        let id = entry.id;
-        let idx = self.entries.len();
-        let name_key = DefNameKey {
-            kind: entry.kind,
-            name: entry.location.name.clone(),
-        };
-        let file_name_key = FileNameKey {
-            file_id: entry.location.file_id,
-            name: entry.location.name.clone(),
-        };
-        self.entries.push(entry);
-        self.index.insert(id, idx);
-        self.name_index.entry(name_key).or_default().push(idx);
-        self.file_name_index
-            .entry(file_name_key)
-            .or_default()
-            .push(idx);
+fn collect_yul_functions(
+    parse: &Parse,
+    block: &yul::Block<'_>,
+    container: &str,
+    defs: &mut Vec<FileDef>,
+) {
+    for stmt in block.stmts.iter() {
+        match &stmt.kind {
+            yul::StmtKind::FunctionDef(function) => {
+                push_def(
+                    parse,
+                    DefKind::YulFunction,
+                    function.name,
+                    Some(container),
+                    defs,
+                );
+                collect_yul_functions(parse, &function.body, container, defs);
+            }
+            yul::StmtKind::Block(block) | yul::StmtKind::If(_, block) => {
+                collect_yul_functions(parse, block, container, defs);
+            }
+            yul::StmtKind::For(stmt_for) => {
+                collect_yul_functions(parse, &stmt_for.init, container, defs);
+                collect_yul_functions(parse, &stmt_for.step, container, defs);
+                collect_yul_functions(parse, &stmt_for.body, container, defs);
+            }
+            yul::StmtKind::Switch(switch) => {
+                for case in switch.cases.iter() {
+                    collect_yul_functions(parse, &case.body, container, defs);
+                }
+            }
+            _ => {}
+        }
     }
 }
 
-fn ident_text(parse: &Parse, ident: Ident) -> String {
-    parse.with_session(|| ident.as_str().to_string())
+fn ident_text(parse: &Parse, ident: Ident) -> Name {
+    parse.with_session(|| Name::new(ident.as_str()))
 }
 
 fn ident_range(parse: &Parse, ident: Ident) -> Option<TextRange> {
@@ -770,15 +648,14 @@ fn ident_range(parse: &Parse, ident: Ident) -> Option<TextRange> {
 
 #[cfg(test)]
 mod tests {
-    use super::{DefDatabase, DefKind};
+    use super::{DefKind, DefMap, FileDefs};
     use sa_base_db::FileId;
 
     #[test]
     fn stable_ids_for_top_level_items() {
-        let mut db = DefDatabase::new();
         let file_id = FileId::from_raw(0);
 
-        let before = db.collect([(
+        let before = DefMap::collect([(
             file_id,
             "contract Foo { function bar() public { uint256 x = 1; } }",
         )]);
@@ -789,7 +666,7 @@ mod tests {
             .entry_by_name(DefKind::Function, "bar")
             .expect("function bar");
 
-        let after = db.collect([(
+        let after = DefMap::collect([(
             file_id,
             "contract Foo { function bar() public { uint256 x = 2; } }",
         )]);
@@ -804,13 +681,38 @@ mod tests {
         assert_eq!(bar_before.id(), bar_after.id());
     }
 
+    #[test]
+    fn ids_survive_edits_to_other_files() {
+        let a = FileId::from_raw(0);
+        let b = FileId::from_raw(1);
+        let a_text = "contract A { function f() public {} }";
+
+        let before = DefMap::collect([(b, "contract B {}"), (a, a_text)]);
+        let after = DefMap::collect([
+            (b, "contract C {} contract B { function f() public {} }"),
+            (a, a_text),
+        ]);
+
+        let id = |map: &DefMap, kind, name, container| {
+            map.entry_by_name_in_container(kind, name, container)
+                .map(|entry| entry.id())
+        };
+        assert_eq!(
+            id(&before, DefKind::Contract, "A", None),
+            id(&after, DefKind::Contract, "A", None)
+        );
+        assert_eq!(
+            id(&before, DefKind::Function, "f", Some("A")),
+            id(&after, DefKind::Function, "f", Some("A"))
+        );
+    }
+
     #[test]
     fn ids_differ_for_same_name_in_different_files() {
-        let mut db = DefDatabase::new();
         let file_a = FileId::from_raw(0);
         let file_b = FileId::from_raw(1);
 
-        let map = db.collect([(file_a, "contract Foo {}"), (file_b, "contract Foo {}")]);
+        let map = DefMap::collect([(file_a, "contract Foo {}"), (file_b, "contract Foo {}")]);
 
         let mut ids = map
             .entries()
@@ -825,9 +727,8 @@ mod tests {
 
     #[test]
     fn missing_names_return_none() {
-        let mut db = DefDatabase::new();
         let file_id = FileId::from_raw(0);
-        let map = db.collect([(file_id, "contract Foo {}")]);
+        let map = DefMap::collect([(file_id, "contract Foo {}")]);
 
         assert!(map.entry_by_name(DefKind::Contract, "Bar").is_none());
         assert!(map.entries_by_name(DefKind::Contract, "Bar").is_none());
@@ -835,10 +736,9 @@ mod tests {
 
     #[test]
     fn indexes_events_errors_modifiers_and_variables() {
-        let mut db = DefDatabase::new();
         let file_id = FileId::from_raw(0);
 
-        let map = db.collect([(
+        let map = DefMap::collect([(
             file_id,
             "type Price is uint256;\
              event TopEvent();\
@@ -895,12 +795,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn indexes_struct_fields_and_enum_variants_under_their_parent() {
+        let file_id = FileId::from_raw(0);
+        let text = "struct Point { uint256 x; uint256 y; }\
+                    contract Foo {\
+                        enum Kind { A, B }\
+                        uint256 x;\
+                    }";
+
+        let map = DefMap::collect([(file_id, text)]);
+
+        let x = map
+            .entry_by_name_in_container(DefKind::Field, "x", Some("Point"))
+            .expect("field x");
+        let range = x.location().range();
+        assert_eq!(
+            &text[usize::from(range.start())..usize::from(range.end())],
+            "x"
+        );
+        assert!(
+            map.entry_by_name_in_container(DefKind::Field, "y", Some("Point"))
+                .is_some()
+        );
+        assert!(
+            map.entry_by_name_in_container(DefKind::Variant, "B", Some("Kind"))
+                .is_some()
+        );
+        assert_eq!(
+            map.entries_by_name_in_container(DefKind::Variable, "x", Some("Foo"))
+                .len(),
+            1
+        );
+        let state = map
+            .entry_by_name_in_container(DefKind::Variable, "x", Some("Foo"))
+            .expect("state variable x");
+        assert_ne!(state.id(), x.id());
+    }
+
+    #[test]
+    fn indexes_special_functions_under_their_keyword() {
+        let file_id = FileId::from_raw(0);
+        let text = "contract Foo {\
+                        constructor() {}\
+                        receive() external payable {}\
+                        fallback() external {}\
+                    }\
+                    contract Bar { constructor() {} }";
+
+        let map = DefMap::collect([(file_id, text)]);
+
+        let constructor = map
+            .entry_by_name_in_container(DefKind::Function, "constructor", Some("Foo"))
+            .expect("constructor");
+        let range = constructor.location().range();
+        assert_eq!(
+            &text[usize::from(range.start())..usize::from(range.end())],
+            "constructor"
+        );
+        assert!(constructor.is_special_function());
+        for name in ["receive", "fallback"] {
+            let entry = map
+                .entry_by_name_in_container(DefKind::Function, name, Some("Foo"))
+                .expect(name);
+            assert!(entry.is_special_function());
+        }
+        let other = map
+            .entry_by_name_in_container(DefKind::Function, "constructor", Some("Bar"))
+            .expect("Bar constructor");
+        assert_ne!(other.id(), constructor.id());
+    }
+
     #[test]
     fn container_scoped_entries_are_distinct() {
-        let mut db = DefDatabase::new();
         let file_id = FileId::from_raw(0);
 
-        let map = db.collect([(
+        let map = DefMap::collect([(
             file_id,
             "function bar() {}\
              contract Foo {\
@@ -986,11 +956,10 @@ mod tests {
 
     #[test]
     fn entries_by_name_in_file_filters_by_file() {
-        let mut db = DefDatabase::new();
         let file_a = FileId::from_raw(0);
         let file_b = FileId::from_raw(1);
 
-        let map = db.collect([
+        let map = DefMap::collect([
             (
                 file_a,
                 "type Price is uint256;\
@@ -1048,10 +1017,9 @@ mod tests {
 
     #[test]
     fn entry_accessors_and_indexes_cover_multiple_paths() {
-        let mut db = DefDatabase::new();
         let file_id = FileId::from_raw(0);
 
-        let map = db.collect([(
+        let map = DefMap::collect([(
             file_id,
             "contract Foo { function bar() {} } function bar() {}",
         )]);
@@ -1084,4 +1052,75 @@ mod tests {
                 .is_empty()
         );
     }
+
+    #[test]
+    fn indexes_assembly_functions_under_the_enclosing_function() {
+        let file_id = FileId::from_raw(0);
+        let text = "contract Foo {\
+                        function bar() public {\
+                            assembly {\
+                                function double(x) -> y { y := add(x, x) }\
+                                if 1 { function inner() {} }\
+                            }\
+                        }\
+                        constructor() { assembly { function init() {} } }\
+                    }";
+
+        let map = DefMap::collect([(file_id, text)]);
+
+        let double = map
+            .entry_by_name_in_container(DefKind::YulFunction, "double", Some("bar"))
+            .expect("assembly function double");
+        let range = double.location().range();
+        assert_eq!(
+            &text[usize::from(range.start())..usize::from(range.end())],
+            "double"
+        );
+        assert!(
+            map.entry_by_name_in_container(DefKind::YulFunction, "inner", Some("bar"))
+                .is_some()
+        );
+        assert!(
+            map.entry_by_name_in_container(DefKind::YulFunction, "init", Some("constructor"))
+                .is_some()
+        );
+        assert!(map.entry_by_name(DefKind::Function, "double").is_none());
+    }
+
+    #[test]
+    fn def_map_round_trips_through_serde() {
+        let file_id = FileId::from_raw(3);
+        let map = DefMap::collect([(
+            file_id,
+            "contract Foo { struct S { uint256 x; } function bar() public {} }",
+        )]);
+
+        let json = serde_json::to_string(&map).expect("serialize");
+        let restored: DefMap = serde_json::from_str(&json).expect("deserialize");
+
+        assert_eq!(restored, map);
+        let bar = map
+            .entry_by_name_in_container(DefKind::Function, "bar", Some("Foo"))
+            .expect("function bar");
+        assert_eq!(restored.entry(bar.id()), Some(bar));
+        assert_eq!(restored.entries_by_name_in_file(file_id, "x").len(), 1);
+    }
+
+    #[test]
+    fn file_defs_collected_separately_match_collect() {
+        let a = (FileId::from_raw(0), "contract A { function f() public {} }");
+        let b = (FileId::from_raw(1), "struct S { uint256 x; }\nerror E();");
+
+        let together = DefMap::collect([a, b]);
+        let a_defs = FileDefs::collect(a.1);
+        let b_defs = FileDefs::collect(b.1);
+        assert_eq!(a_defs.len(), 2);
+        let separate = DefMap::merge(&[a_defs.lower(a.0), b_defs.lower(b.0)]);
+
+        assert_eq!(together, separate);
+        let function = separate
+            .entry_by_name_in_container(DefKind::Function, "f", Some("A"))
+            .expect("function f");
+        assert_eq!(function.location().file_id(), a.0);
+    }
 }
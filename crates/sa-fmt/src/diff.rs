@@ -0,0 +1,303 @@
+use sa_ide_assists::TextEdit;
+use sa_span::{TextRange, TextSize};
+
+/// Upper bound on the LCS table size before falling back to a single replacement edit.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+/// Computes line-granular edits that turn `old` into `new`.
+///
+/// Edits are non-overlapping and sorted by offset into `old`.
+pub fn diff(old: &str, new: &str) -> Vec<TextEdit> {
+    if old == new {
+        return Vec::new();
+    }
+
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(left, right)| left.1 == right.1)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(left, right)| left.1 == right.1)
+        .count();
+
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+    let old_start = old_lines.get(prefix).map_or(old.len(), |line| line.0);
+    let old_end = old_mid
+        .last()
+        .map_or(old_start, |line| line.0 + line.1.len());
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        let new_text = new_mid.iter().map(|line| line.1).collect::<String>();
+        return edit(old_start, old_end, new_text).into_iter().collect();
+    }
+
+    let mut edits = Vec::new();
+    let matches = lcs_matches(old_mid, new_mid);
+    let (mut old_idx, mut new_idx) = (0, 0);
+    for (match_old, match_new) in matches
+        .into_iter()
+        .chain(std::iter::once((old_mid.len(), new_mid.len())))
+    {
+        if match_old - old_idx == match_new - new_idx {
+            // Equal-sized hunks are split per line so edits stay as small as possible.
+            for (old_line, new_line) in old_mid[old_idx..match_old]
+                .iter()
+                .zip(&new_mid[new_idx..match_new])
+            {
+                if old_line.1 == new_line.1 {
+                    continue;
+                }
+                let end = old_line.0 + old_line.1.len();
+                edits.extend(edit(old_line.0, end, new_line.1.to_string()));
+            }
+        } else {
+            let start = old_mid.get(old_idx).map_or(old_end, |line| line.0);
+            let end = old_mid.get(match_old).map_or(old_end, |line| line.0);
+            let new_text = new_mid[new_idx..match_new]
+                .iter()
+                .map(|line| line.1)
+                .collect::<String>();
+            edits.extend(edit(start, end, new_text));
+        }
+        old_idx = match_old + 1;
+        new_idx = match_new + 1;
+    }
+    edits
+}
+
+/// Restricts `edit`, one of the edits [`diff`] made to `old`, to the whole lines of `range`.
+///
+/// A hunk that reaches past `range` is split where the lines it replaces cross the boundary:
+/// the replacement is cut after as many non-whitespace characters as the replaced text has
+/// before the boundary, at a line start. Returns `None` if nothing of `edit` falls in `range` or
+/// the hunk cannot be split there, e.g. because formatting joined the lines on both sides.
+pub(crate) fn clip(old: &str, edit: &TextEdit, range: TextRange) -> Option<TextEdit> {
+    if edit.range.is_empty() {
+        let offset = edit.range.start();
+        return (range.start() <= offset && offset <= range.end()).then(|| edit.clone());
+    }
+    let start = edit.range.start().max(range.start());
+    let end = edit.range.end().min(range.end());
+    if start >= end {
+        return None;
+    }
+    if start == edit.range.start() && end == edit.range.end() {
+        return Some(edit.clone());
+    }
+
+    let hunk_start = usize::from(edit.range.start());
+    let replaced = old.get(hunk_start..usize::from(edit.range.end()))?;
+    let new = edit.new_text.as_str();
+    if non_whitespace(replaced) != non_whitespace(new) {
+        return None;
+    }
+    let new_start = split_replacement(replaced, new, usize::from(start) - hunk_start, true)?;
+    let new_end = split_replacement(replaced, new, usize::from(end) - hunk_start, false)?;
+    if new_start > new_end {
+        return None;
+    }
+    Some(TextEdit {
+        range: TextRange::new(start, end),
+        new_text: new[new_start..new_end].to_string(),
+    })
+}
+
+/// Where `new` splits like `replaced` does at the line start `offset`: after as much code, with
+/// the side of the split that is kept (after it if `keep_after`) getting at most as many of the
+/// blank lines there as it had before.
+fn split_replacement(replaced: &str, new: &str, offset: usize, keep_after: bool) -> Option<usize> {
+    if offset == 0 {
+        return Some(0);
+    }
+    if offset == replaced.len() {
+        return Some(new.len());
+    }
+    let (head, tail) = (replaced.get(..offset)?, &replaced[offset..]);
+    let code = non_whitespace(head);
+    let mut seen = 0;
+    let mut line_starts = Vec::new();
+    for (start, line) in split_lines(new) {
+        if seen == code {
+            line_starts.push(start);
+        }
+        seen += non_whitespace(line);
+    }
+    if seen == code && (new.is_empty() || new.ends_with('\n')) {
+        line_starts.push(new.len());
+    }
+
+    let is_blank = |line: &&str| line.trim().is_empty();
+    let gap = line_starts.len().checked_sub(1)?;
+    let index = if keep_after {
+        gap - gap.min(tail.split_inclusive('\n').take_while(is_blank).count())
+    } else {
+        gap.min(
+            head.split_inclusive('\n')
+                .rev()
+                .take_while(is_blank)
+                .count(),
+        )
+    };
+    Some(line_starts[index])
+}
+
+fn non_whitespace(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+fn edit(start: usize, end: usize, new_text: String) -> Option<TextEdit> {
+    let start = TextSize::try_from(start).ok()?;
+    let end = TextSize::try_from(end).ok()?;
+    Some(TextEdit {
+        range: TextRange::new(start, end),
+        new_text,
+    })
+}
+
+/// Splits `text` into `(offset, line)` pairs, keeping line terminators attached.
+fn split_lines(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line)
+        })
+        .collect()
+}
+
+/// Returns index pairs of a longest common subsequence of `old` and `new` lines.
+fn lcs_matches(old: &[(usize, &str)], new: &[(usize, &str)]) -> Vec<(usize, usize)> {
+    let width = new.len() + 1;
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i].1 == new[j].1 {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i].1 == new[j].1 {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use sa_ide_assists::TextEdit;
+    use sa_span::{TextRange, TextSize};
+
+    use super::{clip, diff};
+
+    fn apply(text: &str, edits: &[sa_ide_assists::TextEdit]) -> String {
+        let mut result = text.to_string();
+        for edit in edits.iter().rev() {
+            let start = usize::from(edit.range.start());
+            let end = usize::from(edit.range.end());
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn identical_texts_produce_no_edits() {
+        assert!(diff("a\nb\n", "a\nb\n").is_empty());
+    }
+
+    #[test]
+    fn edits_only_touch_changed_lines() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "a\nB\nc\nd\nE\n";
+        let edits = diff(old, new);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].new_text, "B\n");
+        assert_eq!(edits[1].new_text, "E\n");
+        assert_eq!(apply(old, &edits), new);
+    }
+
+    #[test]
+    fn adjacent_changed_lines_are_split() {
+        let old = "a\nb\nc\n";
+        let new = "A\nB\nc\n";
+        let edits = diff(old, new);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].new_text, "A\n");
+        assert_eq!(edits[1].new_text, "B\n");
+    }
+
+    #[test]
+    fn handles_insertions_deletions_and_missing_trailing_newline() {
+        let cases = [
+            ("a\nc", "a\nb\nc\n"),
+            ("a\nb\nc\n", "a\nc\n"),
+            ("", "a\n"),
+            ("a\n", ""),
+            ("x\ny\n", "y\nx\n"),
+        ];
+        for (old, new) in cases {
+            assert_eq!(apply(old, &diff(old, new)), new, "{old:?} -> {new:?}");
+        }
+    }
+
+    fn lines(text: &str, first: &str, last: &str) -> TextRange {
+        let start = text.find(first).expect("first");
+        let end = text.find(last).expect("last");
+        TextRange::new(
+            TextSize::try_from(start).expect("start"),
+            TextSize::try_from(end).expect("end"),
+        )
+    }
+
+    #[test]
+    fn clipping_splits_a_hunk_at_the_range_boundary() {
+        let old = "{\n  x\n  = 1;\n  y\n  = 2;\n}\n";
+        let edits = diff(old, "{\n  x = 1;\n  y = 2;\n}\n");
+        assert_eq!(edits.len(), 1);
+
+        let clipped = clip(old, &edits[0], lines(old, "  x", "  y")).expect("clipped");
+        assert_eq!(clipped.new_text, "  x = 1;\n");
+        assert_eq!(apply(old, &[clipped]), "{\n  x = 1;\n  y\n  = 2;\n}\n");
+    }
+
+    #[test]
+    fn clipping_a_whole_text_edit_keeps_the_blank_lines_of_each_side() {
+        let old = "a\n\n\nb\n\n\nc\n";
+        let edit = TextEdit {
+            range: TextRange::new(TextSize::from(0), TextSize::of(old)),
+            new_text: "a\n\nb\n\nc\n".to_string(),
+        };
+        let clipped = clip(old, &edit, lines(old, "a", "b")).expect("clipped");
+        assert_eq!(apply(old, &[clipped]), "a\n\nb\n\n\nc\n");
+        let clipped = clip(old, &edit, lines(old, "b", "c")).expect("clipped");
+        assert_eq!(apply(old, &[clipped]), "a\n\n\nb\n\nc\n");
+    }
+
+    #[test]
+    fn clipping_leaves_out_lines_formatting_joined_across_the_boundary() {
+        let old = "x\ny\n";
+        let edits = diff(old, "x y\n");
+        assert_eq!(clip(old, &edits[0], lines(old, "x", "y")), None);
+    }
+}
@@ -0,0 +1,95 @@
+//! Solidity formatting built on `forge-fmt`, honoring the `[fmt]` section of foundry.toml.
+
+use forge_fmt::FormatterResult;
+use sa_ide_assists::TextEdit;
+use sa_span::TextRange;
+use tracing::debug;
+
+mod diff;
+mod range;
+
+pub use diff::diff;
+pub use forge_fmt::FormatterConfig;
+pub use range::expand_to_syntax_units;
+
+/// Extra passes allowed for the formatter to reach a fixed point.
+const MAX_STABILIZE_PASSES: usize = 2;
+
+/// Formats `text`, returning `None` if the source could not be formatted.
+///
+/// The result is idempotent: formatting it again yields the same text.
+pub fn format(text: &str, config: &FormatterConfig) -> Option<String> {
+    let mut formatted = format_once(text, config)?;
+    for _ in 0..MAX_STABILIZE_PASSES {
+        let Some(next) = format_once(&formatted, config) else {
+            break;
+        };
+        if next == formatted {
+            return Some(formatted);
+        }
+        formatted = next;
+    }
+    debug!("formatter did not reach a fixed point");
+    Some(formatted)
+}
+
+/// Formats `text` and returns the minimal line-based edits to apply.
+pub fn format_edits(text: &str, config: &FormatterConfig) -> Option<Vec<TextEdit>> {
+    let formatted = format(text, config)?;
+    Some(diff(text, &formatted))
+}
+
+/// Formats only the parts of `text` touched by `ranges`.
+///
+/// Each range is first expanded to whole items and statements (see [`expand_to_syntax_units`]),
+/// then the whole-document edits are clipped to the expanded ranges, so the output agrees with
+/// whole-file formatting inside them and leaves the text outside them alone. Pass the ranges of
+/// recent edits to format just the changed lines.
+pub fn format_ranges(
+    text: &str,
+    ranges: &[TextRange],
+    config: &FormatterConfig,
+) -> Option<Vec<TextEdit>> {
+    let edits = format_edits(text, config)?;
+    if edits.is_empty() {
+        return Some(edits);
+    }
+    let parse = sa_syntax::parse_file(text);
+    let mut expanded = ranges
+        .iter()
+        .map(|range| expand_to_syntax_units(&parse, text, *range))
+        .collect::<Vec<_>>();
+    expanded.sort_by_key(|range| range.start());
+    // Touching ranges are merged too, so an insertion between them is not kept twice.
+    let mut merged: Vec<TextRange> = Vec::with_capacity(expanded.len());
+    for range in expanded {
+        match merged.last_mut() {
+            Some(last) if range.start() <= last.end() => {
+                *last = TextRange::new(last.start(), last.end().max(range.end()));
+            }
+            _ => merged.push(range),
+        }
+    }
+    Some(
+        edits
+            .iter()
+            .flat_map(|edit| {
+                merged
+                    .iter()
+                    .filter_map(|range| diff::clip(text, edit, *range))
+            })
+            .collect(),
+    )
+}
+
+fn format_once(text: &str, config: &FormatterConfig) -> Option<String> {
+    match forge_fmt::format(text, config.clone()) {
+        FormatterResult::Ok(formatted)
+        | FormatterResult::OkWithDiagnostics(formatted, _)
+        | FormatterResult::ErrRecovered(formatted, _) => Some(formatted),
+        FormatterResult::Err(_) => {
+            debug!("failed to format source");
+            None
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use sa_span::{LineIndex, TextRange, TextSize};
+use sa_syntax::Parse;
+use sa_syntax::ast::{Block, Item, ItemKind, Stmt, StmtKind, interface::Span};
+
+/// Expands `range` so that neither end falls inside an item or statement, then widens it to
+/// whole lines.
+///
+/// Units that fully contain `range` are only taken whole when they are leaves (simple
+/// statements or non-function items), so selecting a statement inside a function does not pull
+/// in the entire function.
+pub fn expand_to_syntax_units(parse: &Parse, text: &str, range: TextRange) -> TextRange {
+    let units = collect_units(parse);
+    let contains_range =
+        |unit: &Unit| unit.range.start() <= range.start() && range.end() <= unit.range.end();
+
+    let mut expanded = units
+        .iter()
+        .filter(|unit| contains_range(unit))
+        .min_by_key(|unit| unit.range.len())
+        .filter(|unit| unit.is_leaf)
+        .map_or(range, |unit| unit.range);
+
+    let last = if range.is_empty() {
+        range.end()
+    } else {
+        range.end() - TextSize::from(1)
+    };
+    let partial = |offset: TextSize| {
+        units
+            .iter()
+            .filter(|unit| !contains_range(unit))
+            .filter(|unit| unit.range.start() <= offset && offset < unit.range.end())
+            .min_by_key(|unit| unit.range.len())
+            .map(|unit| unit.range)
+    };
+    if let Some(unit) = partial(range.start()) {
+        expanded = TextRange::new(unit.start().min(expanded.start()), expanded.end());
+    }
+    if let Some(unit) = partial(last) {
+        expanded = TextRange::new(expanded.start(), unit.end().max(expanded.end()));
+    }
+    expand_to_lines(text, expanded)
+}
+
+fn expand_to_lines(text: &str, range: TextRange) -> TextRange {
+    let index = LineIndex::new(text);
+    let start = range.start().min(index.len());
+    let end = range.end().min(index.len());
+    let end_line = index.line_col(end);
+    let line_end = if end > start && end_line.line > 0 && end_line.col == 0 {
+        end
+    } else {
+        index
+            .line_range(end_line.line + 1)
+            .map_or(index.len(), |line| line.start())
+    };
+    TextRange::new(index.line_start(start), line_end)
+}
+
+struct Unit {
+    range: TextRange,
+    is_leaf: bool,
+}
+
+fn collect_units(parse: &Parse) -> Vec<Unit> {
+    let mut collector = UnitCollector {
+        parse,
+        units: Vec::new(),
+    };
+    parse.with_session(|| {
+        for item in parse.tree().items.iter() {
+            collector.collect_item(item);
+            if let ItemKind::Contract(contract) = &item.kind {
+                for member in contract.body.iter() {
+                    collector.collect_item(member);
+                }
+            }
+        }
+    });
+    collector.units
+}
+
+struct UnitCollector<'a> {
+    parse: &'a Parse,
+    units: Vec<Unit>,
+}
+
+impl UnitCollector<'_> {
+    fn push(&mut self, span: Span, is_leaf: bool) {
+        if let Some(range) = self.parse.span_to_text_range(span) {
+            self.units.push(Unit { range, is_leaf });
+        }
+    }
+
+    fn collect_item(&mut self, item: &Item<'_>) {
+        match &item.kind {
+            ItemKind::Contract(_) => self.push(item.span, false),
+            ItemKind::Function(function) => match function.body.as_ref() {
+                Some(body) => {
+                    self.push(item.span, false);
+                    self.collect_block(body);
+                }
+                None => self.push(item.span, true),
+            },
+            _ => self.push(item.span, true),
+        }
+    }
+
+    fn collect_block(&mut self, block: &Block<'_>) {
+        for stmt in block.stmts.iter() {
+            self.collect_stmt(stmt);
+        }
+    }
+
+    fn collect_stmt(&mut self, stmt: &Stmt<'_>) {
+        let is_leaf = !matches!(
+            stmt.kind,
+            StmtKind::Block(_)
+                | StmtKind::UncheckedBlock(_)
+                | StmtKind::For { .. }
+                | StmtKind::If(..)
+                | StmtKind::While(..)
+                | StmtKind::DoWhile(..)
+                | StmtKind::Try(_)
+        );
+        self.push(stmt.span, is_leaf);
+        match &stmt.kind {
+            StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => {
+                self.collect_block(block);
+            }
+            StmtKind::For { init, body, .. } => {
+                if let Some(init) = init.as_deref() {
+                    self.collect_stmt(init);
+                }
+                self.collect_stmt(body);
+            }
+            StmtKind::If(_, then_branch, else_branch) => {
+                self.collect_stmt(then_branch);
+                if let Some(else_branch) = else_branch.as_deref() {
+                    self.collect_stmt(else_branch);
+                }
+            }
+            StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => {
+                self.collect_stmt(body);
+            }
+            StmtKind::Try(stmt_try) => {
+                for clause in stmt_try.clauses.iter() {
+                    self.collect_block(&clause.block);
+                }
+            }
+            _ => {}
+        }
+    }
+}
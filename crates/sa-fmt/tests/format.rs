@@ -0,0 +1,138 @@
+use sa_fmt::{FormatterConfig, format, format_edits, format_ranges};
+use sa_span::{TextRange, TextSize};
+
+fn apply(text: &str, edits: &[sa_ide_assists::TextEdit]) -> String {
+    let mut result = text.to_string();
+    for edit in edits.iter().rev() {
+        let start = usize::from(edit.range.start());
+        let end = usize::from(edit.range.end());
+        result.replace_range(start..end, &edit.new_text);
+    }
+    result
+}
+
+#[test]
+fn format_is_idempotent() {
+    let text = "contract Foo{function bar()public returns(uint256){return 1;}}\n";
+    let formatted = format(text, &FormatterConfig::default()).expect("formatted");
+    assert_eq!(
+        formatted,
+        "contract Foo {\n    function bar() public returns (uint256) {\n        return 1;\n    }\n}\n"
+    );
+    let again = format(&formatted, &FormatterConfig::default()).expect("formatted");
+    assert_eq!(again, formatted);
+}
+
+#[test]
+fn format_honors_fmt_config() {
+    let text = "import {Foo} from \"./Foo.sol\";\n\ncontract Bar {\nfunction baz() public {}\n}\n";
+    let config = FormatterConfig {
+        bracket_spacing: true,
+        tab_width: 2,
+        ..FormatterConfig::default()
+    };
+    let formatted = format(text, &config).expect("formatted");
+    assert!(formatted.contains("import { Foo } from \"./Foo.sol\";"));
+    assert!(formatted.contains("\n  function baz() public {}\n"));
+}
+
+#[test]
+fn format_edits_only_touch_changed_lines() {
+    let text = r#"contract Foo {
+    uint256 a;
+
+    function bar()public {}
+
+    uint256 b;
+}
+"#;
+    let edits = format_edits(text, &FormatterConfig::default()).expect("edits");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "    function bar() public {}\n");
+    assert_eq!(
+        apply(text, &edits),
+        format(text, &FormatterConfig::default()).expect("formatted")
+    );
+
+    let formatted = apply(text, &edits);
+    let edits = format_edits(&formatted, &FormatterConfig::default()).expect("edits");
+    assert!(edits.is_empty());
+}
+
+#[test]
+fn format_ranges_only_touches_selected_statement() {
+    let text = r#"contract Foo {
+    function a() public {
+        uint256 x=1;
+        uint256 y=2;
+    }
+
+    function b()public {}
+}
+"#;
+    let start = text.find("x=1").expect("x");
+    let range = TextRange::new(
+        TextSize::try_from(start).expect("start"),
+        TextSize::try_from(start + 1).expect("end"),
+    );
+    let edits = format_ranges(text, &[range], &FormatterConfig::default()).expect("edits");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "        uint256 x = 1;\n");
+}
+
+#[test]
+fn format_ranges_expands_partial_items() {
+    let text = r#"contract Foo {
+    function a() public {
+        uint256 x=1;
+    }
+
+    function b()public {}
+}
+"#;
+    // Selection starts mid-statement and ends inside `b`.
+    let start = text.find("=1").expect("start");
+    let end = text.find("public {}").expect("end");
+    let range = TextRange::new(
+        TextSize::try_from(start).expect("start"),
+        TextSize::try_from(end).expect("end"),
+    );
+    let edits = format_ranges(text, &[range], &FormatterConfig::default()).expect("edits");
+    let texts = edits
+        .iter()
+        .map(|edit| edit.new_text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        vec!["        uint256 x = 1;\n", "    function b() public {}\n"]
+    );
+}
+
+#[test]
+fn format_ranges_clips_a_hunk_that_spans_the_range_boundary() {
+    let text = r#"contract Foo {
+    function a() public {
+        uint256 x
+            = 1;
+        uint256 y
+            = 2;
+    }
+}
+"#;
+    let start = text.find("x\n").expect("x");
+    let range = TextRange::new(
+        TextSize::try_from(start).expect("start"),
+        TextSize::try_from(start + 1).expect("end"),
+    );
+    let config = FormatterConfig::default();
+    let whole = format_edits(text, &config).expect("edits");
+    assert_eq!(whole.len(), 1, "the two statements are one hunk");
+
+    let edits = format_ranges(text, &[range], &config).expect("edits");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "        uint256 x = 1;\n");
+    assert_eq!(
+        apply(text, &edits),
+        text.replace("uint256 x\n            = 1;", "uint256 x = 1;")
+    );
+}
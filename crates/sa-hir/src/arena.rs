@@ -0,0 +1,43 @@
+//! Append-only storage for HIR nodes, addressed by their index. A file's nodes share one arena
+//! so lowering does not allocate per node and comparing two lowerings walks contiguous slices.
+
+use std::ops::{Index, Range};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Arena<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> Arena<T> {
+    pub(crate) fn alloc(&mut self, item: T) -> u32 {
+        let idx = self.len();
+        self.items.push(item);
+        idx
+    }
+
+    pub(crate) fn len(&self) -> u32 {
+        u32::try_from(self.items.len()).expect("arena holds at most u32::MAX items")
+    }
+
+    pub(crate) fn slice(&self, range: Range<u32>) -> &[T] {
+        &self.items[range.start as usize..range.end as usize]
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.items.shrink_to_fit();
+    }
+}
+
+impl<T> Index<u32> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, idx: u32) -> &T {
+        &self.items[idx as usize]
+    }
+}
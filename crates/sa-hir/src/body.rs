@@ -0,0 +1,819 @@
+//! Function bodies lowered from the syntax tree into index-addressed expressions and
+//! statements, with a source map back to text ranges. Names stay unresolved here; locals are
+//! scoped on top of this in `locals`.
+//!
+//! The nodes of every function in a file live in one [`Arena`] per node kind, so ids are unique
+//! within the file and each body is a range of it.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use sa_base_db::{FileId, FileInput};
+use sa_span::{TextRange, TextSize, range_contains};
+use sa_syntax::Parse;
+use sa_syntax::ast::{
+    Block, CallArgs, Expr as AstExpr, ExprKind, IndexKind, Item, ItemFunction, ItemKind,
+    Stmt as AstStmt, StmtKind, VariableDefinition, interface::SpannedOption,
+};
+
+use crate::HirDatabase;
+use crate::arena::Arena;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StmtId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindingId(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Ident(String),
+    Member {
+        base: ExprId,
+        name: String,
+    },
+    Call {
+        callee: ExprId,
+        args: Vec<ExprId>,
+    },
+    CallOptions {
+        callee: ExprId,
+        options: Vec<(String, ExprId)>,
+    },
+    Index {
+        base: ExprId,
+        index: Option<ExprId>,
+    },
+    Slice {
+        base: ExprId,
+        start: Option<ExprId>,
+        end: Option<ExprId>,
+    },
+    Assign {
+        lhs: ExprId,
+        op: Option<&'static str>,
+        rhs: ExprId,
+    },
+    Binary {
+        lhs: ExprId,
+        op: &'static str,
+        rhs: ExprId,
+    },
+    Unary {
+        op: &'static str,
+        operand: ExprId,
+    },
+    Ternary {
+        cond: ExprId,
+        then_expr: ExprId,
+        else_expr: ExprId,
+    },
+    Tuple(Vec<Option<ExprId>>),
+    Array(Vec<ExprId>),
+    Delete(ExprId),
+    Payable(Vec<ExprId>),
+    /// Literals and type expressions (`new T`, `type(T)`, `uint256`), by their source text.
+    Literal(String),
+    New(String),
+    TypeCall(String),
+    Type(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    /// A variable declaration; `bindings` has a hole for every skipped tuple component.
+    Let {
+        bindings: Vec<Option<BindingId>>,
+        initializer: Option<ExprId>,
+    },
+    Expr(ExprId),
+    Block(Vec<StmtId>),
+    Unchecked(Vec<StmtId>),
+    If {
+        cond: ExprId,
+        then_branch: StmtId,
+        else_branch: Option<StmtId>,
+    },
+    While {
+        cond: ExprId,
+        body: StmtId,
+    },
+    DoWhile {
+        body: StmtId,
+        cond: ExprId,
+    },
+    For {
+        init: Option<StmtId>,
+        cond: Option<ExprId>,
+        next: Option<ExprId>,
+        body: StmtId,
+    },
+    Try {
+        expr: ExprId,
+        clauses: Vec<TryClause>,
+    },
+    Emit {
+        path: String,
+        args: Vec<ExprId>,
+    },
+    Revert {
+        path: String,
+        args: Vec<ExprId>,
+    },
+    Return(Option<ExprId>),
+    Break,
+    Continue,
+    Placeholder,
+    /// Inline assembly, lowered separately by `yul`.
+    Assembly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryClause {
+    pub name: Option<String>,
+    pub params: Vec<BindingId>,
+    /// Always a `Stmt::Block`.
+    pub block: StmtId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Parameter,
+    NamedReturn,
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    /// The range of the name.
+    pub range: TextRange,
+    /// The declared type as written.
+    pub ty: String,
+}
+
+/// The nodes of all function bodies in a file.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct BodyArena {
+    exprs: Arena<Expr>,
+    stmts: Arena<Stmt>,
+    bindings: Arena<Binding>,
+    expr_ranges: Arena<TextRange>,
+    stmt_ranges: Arena<TextRange>,
+}
+
+impl BodyArena {
+    fn shrink_to_fit(&mut self) {
+        self.exprs.shrink_to_fit();
+        self.stmts.shrink_to_fit();
+        self.bindings.shrink_to_fit();
+        self.expr_ranges.shrink_to_fit();
+        self.stmt_ranges.shrink_to_fit();
+    }
+}
+
+#[derive(Clone)]
+pub struct Body {
+    arena: Arc<BodyArena>,
+    exprs: Range<u32>,
+    stmts: Range<u32>,
+    bindings: Range<u32>,
+    params: Vec<BindingId>,
+    returns: Vec<BindingId>,
+    modifier_args: Vec<ExprId>,
+    block: Option<StmtId>,
+}
+
+impl Body {
+    pub fn expr(&self, id: ExprId) -> &Expr {
+        &self.arena.exprs[id.0]
+    }
+
+    pub fn stmt(&self, id: StmtId) -> &Stmt {
+        &self.arena.stmts[id.0]
+    }
+
+    pub fn binding(&self, id: BindingId) -> &Binding {
+        &self.arena.bindings[id.0]
+    }
+
+    pub fn exprs(&self) -> impl Iterator<Item = (ExprId, &Expr)> {
+        self.exprs
+            .clone()
+            .map(|idx| (ExprId(idx), &self.arena.exprs[idx]))
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (BindingId, &Binding)> {
+        self.bindings
+            .clone()
+            .map(|idx| (BindingId(idx), &self.arena.bindings[idx]))
+    }
+
+    pub fn params(&self) -> &[BindingId] {
+        &self.params
+    }
+
+    pub fn returns(&self) -> &[BindingId] {
+        &self.returns
+    }
+
+    /// Arguments of the modifier invocations in the function header.
+    pub fn modifier_args(&self) -> &[ExprId] {
+        &self.modifier_args
+    }
+
+    /// The function block; `None` for functions without a body.
+    pub fn block(&self) -> Option<StmtId> {
+        self.block
+    }
+
+    fn nodes(&self) -> (&[Expr], &[Stmt], &[Binding]) {
+        (
+            self.arena.exprs.slice(self.exprs.clone()),
+            self.arena.stmts.slice(self.stmts.clone()),
+            self.arena.bindings.slice(self.bindings.clone()),
+        )
+    }
+}
+
+/// Only this body's nodes are compared, and not at all when both share an arena.
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.exprs, &self.stmts, &self.bindings) == (&other.exprs, &other.stmts, &other.bindings)
+            && (&self.params, &self.returns, &self.modifier_args, self.block)
+                == (
+                    &other.params,
+                    &other.returns,
+                    &other.modifier_args,
+                    other.block,
+                )
+            && (Arc::ptr_eq(&self.arena, &other.arena) || self.nodes() == other.nodes())
+    }
+}
+
+impl Eq for Body {}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (exprs, stmts, bindings) = self.nodes();
+        f.debug_struct("Body")
+            .field("exprs", &exprs)
+            .field("stmts", &stmts)
+            .field("bindings", &bindings)
+            .field("params", &self.params)
+            .field("returns", &self.returns)
+            .field("modifier_args", &self.modifier_args)
+            .field("block", &self.block)
+            .finish()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BodySourceMap {
+    arena: Arc<BodyArena>,
+    exprs: Range<u32>,
+    stmts: Range<u32>,
+    /// Between the parameter list and the return list (or the end of the header), where
+    /// parameters are already visible, e.g. in modifier arguments.
+    header_scope: Option<TextRange>,
+}
+
+impl BodySourceMap {
+    pub fn expr_range(&self, id: ExprId) -> TextRange {
+        self.arena.expr_ranges[id.0]
+    }
+
+    pub fn stmt_range(&self, id: StmtId) -> TextRange {
+        self.arena.stmt_ranges[id.0]
+    }
+
+    pub fn header_scope(&self) -> Option<TextRange> {
+        self.header_scope
+    }
+
+    /// The innermost expression containing `offset`.
+    pub fn expr_at(&self, offset: TextSize) -> Option<ExprId> {
+        self.exprs
+            .clone()
+            .map(|idx| (idx, self.arena.expr_ranges[idx]))
+            .filter(|(_, range)| range_contains(*range, offset))
+            .min_by_key(|(_, range)| u32::from(range.len()))
+            .map(|(idx, _)| ExprId(idx))
+    }
+
+    fn ranges(&self) -> (&[TextRange], &[TextRange]) {
+        (
+            self.arena.expr_ranges.slice(self.exprs.clone()),
+            self.arena.stmt_ranges.slice(self.stmts.clone()),
+        )
+    }
+}
+
+impl PartialEq for BodySourceMap {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.exprs, &self.stmts, self.header_scope)
+            == (&other.exprs, &other.stmts, other.header_scope)
+            && (Arc::ptr_eq(&self.arena, &other.arena) || self.ranges() == other.ranges())
+    }
+}
+
+impl Eq for BodySourceMap {}
+
+impl fmt::Debug for BodySourceMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (expr_ranges, stmt_ranges) = self.ranges();
+        f.debug_struct("BodySourceMap")
+            .field("expr_ranges", &expr_ranges)
+            .field("stmt_ranges", &stmt_ranges)
+            .field("header_scope", &self.header_scope)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionBody {
+    name: Option<String>,
+    container: Option<String>,
+    range: TextRange,
+    body: Body,
+    source_map: BodySourceMap,
+}
+
+impl FunctionBody {
+    /// `None` for constructors, `fallback` and `receive`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn container(&self) -> Option<&str> {
+        self.container.as_deref()
+    }
+
+    /// The whole function item.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    pub fn source_map(&self) -> &BodySourceMap {
+        &self.source_map
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileBodies {
+    functions: Vec<FunctionBody>,
+}
+
+impl FileBodies {
+    pub fn functions(&self) -> &[FunctionBody] {
+        &self.functions
+    }
+
+    pub fn function_at(&self, offset: TextSize) -> Option<&FunctionBody> {
+        self.functions
+            .iter()
+            .find(|function| range_contains(function.range, offset))
+    }
+}
+
+unsafe impl salsa::Update for FileBodies {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn file_bodies_for_file(db: &dyn HirDatabase, file: FileInput) -> FileBodies {
+    let text = file.text(db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    let mut arena = BodyArena::default();
+    let mut lowered = Vec::new();
+    for item in parse.tree().items.iter() {
+        lower_item(&parse, text.as_ref(), item, None, &mut arena, &mut lowered);
+    }
+    arena.shrink_to_fit();
+    let arena = Arc::new(arena);
+    let functions = lowered
+        .into_iter()
+        .map(|function| function.finish(&arena))
+        .collect();
+    FileBodies { functions }
+}
+
+pub fn file_bodies(db: &dyn HirDatabase, file_id: FileId) -> FileBodies {
+    file_bodies_for_file(db, db.file_input(file_id)).clone()
+}
+
+/// A function lowered into the file's arena, before the arena is shared.
+struct LoweredFunction {
+    name: Option<String>,
+    container: Option<String>,
+    range: TextRange,
+    body: LoweredBody,
+}
+
+struct LoweredBody {
+    exprs: Range<u32>,
+    stmts: Range<u32>,
+    bindings: Range<u32>,
+    params: Vec<BindingId>,
+    returns: Vec<BindingId>,
+    modifier_args: Vec<ExprId>,
+    block: Option<StmtId>,
+    header_scope: Option<TextRange>,
+}
+
+impl LoweredFunction {
+    fn finish(self, arena: &Arc<BodyArena>) -> FunctionBody {
+        let body = self.body;
+        FunctionBody {
+            name: self.name,
+            container: self.container,
+            range: self.range,
+            body: Body {
+                arena: arena.clone(),
+                exprs: body.exprs.clone(),
+                stmts: body.stmts.clone(),
+                bindings: body.bindings,
+                params: body.params,
+                returns: body.returns,
+                modifier_args: body.modifier_args,
+                block: body.block,
+            },
+            source_map: BodySourceMap {
+                arena: arena.clone(),
+                exprs: body.exprs,
+                stmts: body.stmts,
+                header_scope: body.header_scope,
+            },
+        }
+    }
+}
+
+fn lower_item(
+    parse: &Parse,
+    text: &str,
+    item: &Item<'_>,
+    container: Option<&str>,
+    arena: &mut BodyArena,
+    functions: &mut Vec<LoweredFunction>,
+) {
+    match &item.kind {
+        ItemKind::Contract(contract) => {
+            let name = parse.with_session(|| contract.name.to_string());
+            for item in contract.body.iter() {
+                lower_item(parse, text, item, Some(&name), arena, functions);
+            }
+        }
+        ItemKind::Function(function) => {
+            let Some(range) = parse.span_to_text_range(item.span) else {
+                return;
+            };
+            let body = BodyLowering::new(parse, text, arena).lower_function(function);
+            functions.push(LoweredFunction {
+                name: function
+                    .header
+                    .name
+                    .map(|name| parse.with_session(|| name.to_string())),
+                container: container.map(str::to_string),
+                range,
+                body,
+            });
+        }
+        _ => {}
+    }
+}
+
+struct BodyLowering<'a> {
+    parse: &'a Parse,
+    text: &'a str,
+    arena: &'a mut BodyArena,
+    body: LoweredBody,
+}
+
+impl<'a> BodyLowering<'a> {
+    fn new(parse: &'a Parse, text: &'a str, arena: &'a mut BodyArena) -> Self {
+        let body = LoweredBody {
+            exprs: arena.exprs.len()..arena.exprs.len(),
+            stmts: arena.stmts.len()..arena.stmts.len(),
+            bindings: arena.bindings.len()..arena.bindings.len(),
+            params: Vec::new(),
+            returns: Vec::new(),
+            modifier_args: Vec::new(),
+            block: None,
+            header_scope: None,
+        };
+        Self {
+            parse,
+            text,
+            arena,
+            body,
+        }
+    }
+
+    fn lower_function(mut self, function: &ItemFunction<'_>) -> LoweredBody {
+        let header = &function.header;
+        self.body.header_scope = self.header_scope(function);
+        for param in header.parameters.vars.iter() {
+            if let Some(binding) = self.add_binding(param, BindingKind::Parameter) {
+                self.body.params.push(binding);
+            }
+        }
+        if let Some(returns) = header.returns.as_ref() {
+            for param in returns.vars.iter() {
+                if let Some(binding) = self.add_binding(param, BindingKind::NamedReturn) {
+                    self.body.returns.push(binding);
+                }
+            }
+        }
+        for modifier in header.modifiers.iter() {
+            let args = self.lower_call_args(&modifier.arguments);
+            self.body.modifier_args.extend(args);
+        }
+        if let Some(block) = function.body.as_ref() {
+            self.body.block = Some(self.lower_block(block, false));
+        }
+        self.body.exprs.end = self.arena.exprs.len();
+        self.body.stmts.end = self.arena.stmts.len();
+        self.body.bindings.end = self.arena.bindings.len();
+        self.body
+    }
+
+    fn header_scope(&self, function: &ItemFunction<'_>) -> Option<TextRange> {
+        let header_range = self.parse.span_to_text_range(function.header.span)?;
+        let params = self
+            .parse
+            .span_to_text_range(function.header.parameters.span)?;
+        let returns = function
+            .header
+            .returns
+            .as_ref()
+            .and_then(|returns| self.parse.span_to_text_range(returns.span));
+        let scope = match returns {
+            Some(returns) if params.end() <= returns.start() => {
+                TextRange::new(params.end(), returns.start())
+            }
+            None if params.end() <= header_range.end() => {
+                TextRange::new(params.end(), header_range.end())
+            }
+            _ => return None,
+        };
+        (!scope.is_empty()).then_some(scope)
+    }
+
+    fn lower_block(&mut self, block: &Block<'_>, unchecked: bool) -> StmtId {
+        let stmts = block
+            .stmts
+            .iter()
+            .map(|stmt| self.lower_stmt(stmt))
+            .collect();
+        let stmt = if unchecked {
+            Stmt::Unchecked(stmts)
+        } else {
+            Stmt::Block(stmts)
+        };
+        let range = self.range(block.span);
+        self.alloc_stmt(stmt, range)
+    }
+
+    fn lower_stmt(&mut self, stmt: &AstStmt<'_>) -> StmtId {
+        let lowered = match &stmt.kind {
+            StmtKind::DeclSingle(var) => {
+                let binding = self.add_binding(var, BindingKind::Local);
+                let initializer = var.initializer.as_deref().map(|expr| self.lower_expr(expr));
+                Stmt::Let {
+                    bindings: vec![binding],
+                    initializer,
+                }
+            }
+            StmtKind::DeclMulti(vars, expr) => {
+                let bindings = vars
+                    .iter()
+                    .map(|var| match var {
+                        SpannedOption::Some(var) => self.add_binding(var, BindingKind::Local),
+                        SpannedOption::None(_) => None,
+                    })
+                    .collect();
+                let initializer = Some(self.lower_expr(expr));
+                Stmt::Let {
+                    bindings,
+                    initializer,
+                }
+            }
+            StmtKind::Block(block) => return self.lower_block(block, false),
+            StmtKind::UncheckedBlock(block) => return self.lower_block(block, true),
+            StmtKind::For {
+                init,
+                cond,
+                next,
+                body,
+            } => {
+                let init = init.as_deref().map(|init| self.lower_stmt(init));
+                let cond = cond.as_deref().map(|cond| self.lower_expr(cond));
+                let next = next.as_deref().map(|next| self.lower_expr(next));
+                let body = self.lower_stmt(body);
+                Stmt::For {
+                    init,
+                    cond,
+                    next,
+                    body,
+                }
+            }
+            StmtKind::If(cond, then_branch, else_branch) => {
+                let cond = self.lower_expr(cond);
+                let then_branch = self.lower_stmt(then_branch);
+                let else_branch = else_branch.as_deref().map(|stmt| self.lower_stmt(stmt));
+                Stmt::If {
+                    cond,
+                    then_branch,
+                    else_branch,
+                }
+            }
+            StmtKind::While(cond, body) => {
+                let cond = self.lower_expr(cond);
+                let body = self.lower_stmt(body);
+                Stmt::While { cond, body }
+            }
+            StmtKind::DoWhile(body, cond) => {
+                let body = self.lower_stmt(body);
+                let cond = self.lower_expr(cond);
+                Stmt::DoWhile { body, cond }
+            }
+            StmtKind::Try(stmt_try) => {
+                let expr = self.lower_expr(stmt_try.expr.as_ref());
+                let clauses = stmt_try
+                    .clauses
+                    .iter()
+                    .map(|clause| {
+                        let params = clause
+                            .args
+                            .vars
+                            .iter()
+                            .filter_map(|param| self.add_binding(param, BindingKind::Parameter))
+                            .collect();
+                        TryClause {
+                            name: clause
+                                .name
+                                .map(|name| self.parse.with_session(|| name.to_string())),
+                            params,
+                            block: self.lower_block(&clause.block, false),
+                        }
+                    })
+                    .collect();
+                Stmt::Try { expr, clauses }
+            }
+            StmtKind::Emit(path, args) => Stmt::Emit {
+                path: self.parse.with_session(|| path.to_string()),
+                args: self.lower_call_args(args),
+            },
+            StmtKind::Revert(path, args) => Stmt::Revert {
+                path: self.parse.with_session(|| path.to_string()),
+                args: self.lower_call_args(args),
+            },
+            StmtKind::Return(expr) => {
+                Stmt::Return(expr.as_deref().map(|expr| self.lower_expr(expr)))
+            }
+            StmtKind::Expr(expr) => Stmt::Expr(self.lower_expr(expr)),
+            StmtKind::Break => Stmt::Break,
+            StmtKind::Continue => Stmt::Continue,
+            StmtKind::Placeholder => Stmt::Placeholder,
+            StmtKind::Assembly(_) => Stmt::Assembly,
+        };
+        let range = self.range(stmt.span);
+        self.alloc_stmt(lowered, range)
+    }
+
+    fn lower_expr(&mut self, expr: &AstExpr<'_>) -> ExprId {
+        let lowered = match &expr.kind {
+            ExprKind::Ident(ident) => Expr::Ident(self.parse.with_session(|| ident.to_string())),
+            ExprKind::Member(base, name) => Expr::Member {
+                base: self.lower_expr(base),
+                name: self.parse.with_session(|| name.to_string()),
+            },
+            ExprKind::Call(callee, args) => Expr::Call {
+                callee: self.lower_expr(callee),
+                args: self.lower_call_args(args),
+            },
+            ExprKind::CallOptions(callee, options) => Expr::CallOptions {
+                callee: self.lower_expr(callee),
+                options: options
+                    .iter()
+                    .map(|option| {
+                        (
+                            self.parse.with_session(|| option.name.to_string()),
+                            self.lower_expr(option.value.as_ref()),
+                        )
+                    })
+                    .collect(),
+            },
+            ExprKind::Index(base, IndexKind::Index(index)) => Expr::Index {
+                base: self.lower_expr(base),
+                index: index.as_deref().map(|index| self.lower_expr(index)),
+            },
+            ExprKind::Index(base, IndexKind::Range(start, end)) => Expr::Slice {
+                base: self.lower_expr(base),
+                start: start.as_deref().map(|start| self.lower_expr(start)),
+                end: end.as_deref().map(|end| self.lower_expr(end)),
+            },
+            ExprKind::Assign(lhs, op, rhs) => Expr::Assign {
+                lhs: self.lower_expr(lhs),
+                op: op.as_ref().map(|op| op.kind.to_str()),
+                rhs: self.lower_expr(rhs),
+            },
+            ExprKind::Binary(lhs, op, rhs) => Expr::Binary {
+                lhs: self.lower_expr(lhs),
+                op: op.kind.to_str(),
+                rhs: self.lower_expr(rhs),
+            },
+            ExprKind::Unary(op, operand) => Expr::Unary {
+                op: op.kind.to_str(),
+                operand: self.lower_expr(operand),
+            },
+            ExprKind::Ternary(cond, then_expr, else_expr) => Expr::Ternary {
+                cond: self.lower_expr(cond),
+                then_expr: self.lower_expr(then_expr),
+                else_expr: self.lower_expr(else_expr),
+            },
+            ExprKind::Tuple(items) => Expr::Tuple(
+                items
+                    .iter()
+                    .map(|item| match item {
+                        SpannedOption::Some(expr) => Some(self.lower_expr(expr)),
+                        SpannedOption::None(_) => None,
+                    })
+                    .collect(),
+            ),
+            ExprKind::Array(items) => {
+                Expr::Array(items.iter().map(|item| self.lower_expr(item)).collect())
+            }
+            ExprKind::Delete(expr) => Expr::Delete(self.lower_expr(expr)),
+            ExprKind::Payable(args) => Expr::Payable(self.lower_call_args(args)),
+            ExprKind::Lit(..) => Expr::Literal(self.source_text(expr.span)),
+            ExprKind::New(_) => Expr::New(self.source_text(expr.span)),
+            ExprKind::TypeCall(_) => Expr::TypeCall(self.source_text(expr.span)),
+            ExprKind::Type(_) => Expr::Type(self.source_text(expr.span)),
+        };
+        let range = self.range(expr.span);
+        self.alloc_expr(lowered, range)
+    }
+
+    fn lower_call_args(&mut self, args: &CallArgs<'_>) -> Vec<ExprId> {
+        args.exprs().map(|expr| self.lower_expr(expr)).collect()
+    }
+
+    fn add_binding(
+        &mut self,
+        var: &VariableDefinition<'_>,
+        kind: BindingKind,
+    ) -> Option<BindingId> {
+        let name = var.name?;
+        let range = self.parse.span_to_text_range(name.span)?;
+        let binding = Binding {
+            name: self.parse.with_session(|| name.to_string()),
+            kind,
+            range,
+            ty: self.source_text(var.ty.span),
+        };
+        Some(BindingId(self.arena.bindings.alloc(binding)))
+    }
+
+    fn alloc_expr(&mut self, expr: Expr, range: TextRange) -> ExprId {
+        self.arena.expr_ranges.alloc(range);
+        ExprId(self.arena.exprs.alloc(expr))
+    }
+
+    fn alloc_stmt(&mut self, stmt: Stmt, range: TextRange) -> StmtId {
+        self.arena.stmt_ranges.alloc(range);
+        StmtId(self.arena.stmts.alloc(stmt))
+    }
+
+    /// Spans the parser could not map get an empty range at the start of the file.
+    fn range(&self, span: sa_syntax::ast::interface::Span) -> TextRange {
+        self.parse
+            .span_to_text_range(span)
+            .unwrap_or_else(|| TextRange::empty(TextSize::from(0)))
+    }
+
+    fn source_text(&self, span: sa_syntax::ast::interface::Span) -> String {
+        let range = self.range(span);
+        self.text
+            .get(usize::from(range.start())..usize::from(range.end()))
+            .unwrap_or_default()
+            .to_string()
+    }
+}
@@ -0,0 +1,359 @@
+//! Plain-text views of what the HIR knows about a file or project, for triaging resolution
+//! bugs. The format is meant for people and may change at any time.
+
+use std::fmt::Write;
+
+use sa_base_db::{FileId, ProjectId};
+use sa_def::DefKind;
+use sa_span::TextRange;
+
+use crate::body::{Body, BodySourceMap, Expr, ExprId, Stmt, StmtId, file_bodies};
+use crate::{HirDatabase, HirProgram, local_scopes, lowered_program, yul_hir};
+
+/// Every import, item, function body and local binding of `file_id`.
+pub fn hir_debug_dump(db: &dyn HirDatabase, project_id: ProjectId, file_id: FileId) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "file {}", db.file_path(file_id).as_str());
+
+    let program = lowered_program(db, project_id);
+    if let Some(file) = program.files.get(&file_id) {
+        for import in &file.imports {
+            let target = import
+                .resolved_path
+                .as_ref()
+                .map_or("<unresolved>", |path| path.as_str());
+            let _ = writeln!(out, "import {:?} -> {target}", import.path);
+        }
+        let _ = writeln!(out, "\nitems");
+        write_items(&mut out, &program, file_id);
+    }
+
+    for function in file_bodies(db, file_id).functions() {
+        let name = match (function.container(), function.name()) {
+            (Some(container), Some(name)) => format!("{container}.{name}"),
+            (Some(container), None) => format!("{container}.<special>"),
+            (None, Some(name)) => name.to_string(),
+            (None, None) => "<special>".to_string(),
+        };
+        let _ = writeln!(out, "\nfn {name} {}", fmt_range(function.range()));
+        let mut printer = BodyPrinter {
+            body: function.body(),
+            source_map: function.source_map(),
+            out: &mut out,
+        };
+        printer.print_signature();
+        match function.body().block() {
+            Some(block) => printer.print_stmt(block, 1),
+            None => printer.line(1, "<no body>"),
+        }
+    }
+
+    let locals = local_scopes(db, file_id);
+    if !locals.defs().is_empty() {
+        let _ = writeln!(out, "\nlocals");
+        for def in locals.defs() {
+            let _ = writeln!(
+                out,
+                "  {:?} {} {} scope {}",
+                def.kind(),
+                def.name(),
+                fmt_range(def.range()),
+                fmt_range(def.scope())
+            );
+        }
+    }
+
+    for block in yul_hir(db, file_id).blocks() {
+        let _ = writeln!(out, "\nassembly {}", fmt_range(block.range()));
+        for def in block.defs() {
+            let _ = writeln!(out, "  def {} {}", def.label(), fmt_range(def.range()));
+        }
+        for reference in block.references() {
+            let _ = writeln!(
+                out,
+                "  ref {} {} -> {:?}",
+                reference.name(),
+                fmt_range(reference.range()),
+                reference.resolution()
+            );
+        }
+    }
+    out
+}
+
+/// Every file of the project with its imports and the definitions the DefMap holds for it.
+pub fn defmap_debug_dump(db: &dyn HirDatabase, project_id: ProjectId) -> String {
+    let program = lowered_program(db, project_id);
+    let mut files = program
+        .files
+        .values()
+        .map(|file| (file.path.as_str(), file))
+        .collect::<Vec<_>>();
+    files.sort_by_key(|(path, _)| *path);
+
+    let mut out = String::new();
+    for (path, file) in files {
+        let _ = writeln!(out, "{path}");
+        for import in &file.imports {
+            let target = import
+                .resolved_path
+                .as_ref()
+                .map_or("<unresolved>", |path| path.as_str());
+            let _ = writeln!(out, "  import {:?} -> {target}", import.path);
+        }
+        write_items(&mut out, &program, file.file_id);
+    }
+    out
+}
+
+/// One line per DefMap entry declared in `file_id`, in source order.
+fn write_items(out: &mut String, program: &HirProgram, file_id: FileId) {
+    let mut entries = program
+        .def_map()
+        .entries()
+        .iter()
+        .filter(|entry| entry.location().file_id() == file_id)
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.location().range().start());
+    for entry in entries {
+        let name = match entry.container() {
+            Some(container) => format!("{container}.{}", entry.location().name()),
+            None => entry.location().name().to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "  {} {name} {} {:?}",
+            kind_label(entry.kind()),
+            fmt_range(entry.location().range()),
+            entry.id()
+        );
+    }
+}
+
+fn kind_label(kind: DefKind) -> &'static str {
+    match kind {
+        DefKind::Contract => "contract",
+        DefKind::Function => "function",
+        DefKind::Struct => "struct",
+        DefKind::Enum => "enum",
+        DefKind::Event => "event",
+        DefKind::Error => "error",
+        DefKind::Modifier => "modifier",
+        DefKind::Variable => "variable",
+        DefKind::Udvt => "type",
+        DefKind::Field => "field",
+        DefKind::Variant => "variant",
+        DefKind::YulFunction => "yul function",
+    }
+}
+
+fn fmt_range(range: TextRange) -> String {
+    format!("{}..{}", u32::from(range.start()), u32::from(range.end()))
+}
+
+struct BodyPrinter<'a> {
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    out: &'a mut String,
+}
+
+impl BodyPrinter<'_> {
+    fn line(&mut self, depth: usize, text: &str) {
+        let _ = writeln!(self.out, "{}{text}", "  ".repeat(depth));
+    }
+
+    fn print_signature(&mut self) {
+        let body = self.body;
+        for id in body.params().iter().chain(body.returns()) {
+            let binding = body.binding(*id);
+            let line = format!(
+                "{:?} {} {} {}",
+                binding.kind,
+                binding.ty,
+                binding.name,
+                fmt_range(binding.range)
+            );
+            self.line(1, &line);
+        }
+        for arg in body.modifier_args() {
+            let line = format!("modifier arg {}", self.expr(*arg));
+            self.line(1, &line);
+        }
+    }
+
+    fn print_stmt(&mut self, id: StmtId, depth: usize) {
+        let body = self.body;
+        let range = fmt_range(self.source_map.stmt_range(id));
+        match body.stmt(id) {
+            Stmt::Block(stmts) | Stmt::Unchecked(stmts) => {
+                let label = match body.stmt(id) {
+                    Stmt::Unchecked(_) => "unchecked",
+                    _ => "block",
+                };
+                self.line(depth, &format!("{label} {range}"));
+                for stmt in stmts {
+                    self.print_stmt(*stmt, depth + 1);
+                }
+            }
+            Stmt::Let {
+                bindings,
+                initializer,
+            } => {
+                let names = bindings
+                    .iter()
+                    .map(|binding| match binding {
+                        Some(binding) => {
+                            let binding = body.binding(*binding);
+                            format!("{} {}", binding.ty, binding.name)
+                        }
+                        None => String::new(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let init = initializer
+                    .map(|expr| format!(" = {}", self.expr(expr)))
+                    .unwrap_or_default();
+                self.line(depth, &format!("let ({names}){init} {range}"));
+            }
+            Stmt::Expr(expr) => {
+                let line = format!("expr {} {range}", self.expr(*expr));
+                self.line(depth, &line);
+            }
+            Stmt::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let line = format!("if {} {range}", self.expr(*cond));
+                self.line(depth, &line);
+                self.print_stmt(*then_branch, depth + 1);
+                if let Some(else_branch) = else_branch {
+                    self.line(depth, "else");
+                    self.print_stmt(*else_branch, depth + 1);
+                }
+            }
+            Stmt::While { cond, body: inner } => {
+                let line = format!("while {} {range}", self.expr(*cond));
+                self.line(depth, &line);
+                self.print_stmt(*inner, depth + 1);
+            }
+            Stmt::DoWhile { body: inner, cond } => {
+                let line = format!("do-while {} {range}", self.expr(*cond));
+                self.line(depth, &line);
+                self.print_stmt(*inner, depth + 1);
+            }
+            Stmt::For {
+                init,
+                cond,
+                next,
+                body: inner,
+            } => {
+                let cond = cond.map(|expr| self.expr(expr)).unwrap_or_default();
+                let next = next.map(|expr| self.expr(expr)).unwrap_or_default();
+                self.line(depth, &format!("for (; {cond}; {next}) {range}"));
+                if let Some(init) = init {
+                    self.print_stmt(*init, depth + 1);
+                }
+                self.print_stmt(*inner, depth + 1);
+            }
+            Stmt::Try { expr, clauses } => {
+                let line = format!("try {} {range}", self.expr(*expr));
+                self.line(depth, &line);
+                for clause in clauses {
+                    let params = clause
+                        .params
+                        .iter()
+                        .map(|param| body.binding(*param).name.clone())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let name = clause.name.as_deref().unwrap_or("");
+                    self.line(depth, &format!("clause {name}({params})"));
+                    self.print_stmt(clause.block, depth + 1);
+                }
+            }
+            Stmt::Emit { path, args } | Stmt::Revert { path, args } => {
+                let keyword = match body.stmt(id) {
+                    Stmt::Emit { .. } => "emit",
+                    _ => "revert",
+                };
+                let line = format!("{keyword} {path}({}) {range}", self.exprs(args));
+                self.line(depth, &line);
+            }
+            Stmt::Return(expr) => {
+                let value = expr.map(|expr| self.expr(expr)).unwrap_or_default();
+                self.line(depth, &format!("return {value} {range}"));
+            }
+            Stmt::Break => self.line(depth, &format!("break {range}")),
+            Stmt::Continue => self.line(depth, &format!("continue {range}")),
+            Stmt::Placeholder => self.line(depth, &format!("_ {range}")),
+            Stmt::Assembly => self.line(depth, &format!("assembly {range}")),
+        }
+    }
+
+    fn exprs(&self, exprs: &[ExprId]) -> String {
+        exprs
+            .iter()
+            .map(|expr| self.expr(*expr))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn expr(&self, id: ExprId) -> String {
+        match self.body.expr(id) {
+            Expr::Ident(name) => name.clone(),
+            Expr::Member { base, name } => format!("{}.{name}", self.expr(*base)),
+            Expr::Call { callee, args } => format!("{}({})", self.expr(*callee), self.exprs(args)),
+            Expr::CallOptions { callee, options } => {
+                let options = options
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {}", self.expr(*value)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}{{{options}}}", self.expr(*callee))
+            }
+            Expr::Index { base, index } => {
+                let index = index.map(|index| self.expr(index)).unwrap_or_default();
+                format!("{}[{index}]", self.expr(*base))
+            }
+            Expr::Slice { base, start, end } => {
+                let start = start.map(|start| self.expr(start)).unwrap_or_default();
+                let end = end.map(|end| self.expr(end)).unwrap_or_default();
+                format!("{}[{start}:{end}]", self.expr(*base))
+            }
+            Expr::Assign { lhs, op, rhs } => format!(
+                "{} {}= {}",
+                self.expr(*lhs),
+                op.unwrap_or(""),
+                self.expr(*rhs)
+            ),
+            Expr::Binary { lhs, op, rhs } => {
+                format!("({} {op} {})", self.expr(*lhs), self.expr(*rhs))
+            }
+            Expr::Unary { op, operand } => format!("{op}({})", self.expr(*operand)),
+            Expr::Ternary {
+                cond,
+                then_expr,
+                else_expr,
+            } => format!(
+                "({} ? {} : {})",
+                self.expr(*cond),
+                self.expr(*then_expr),
+                self.expr(*else_expr)
+            ),
+            Expr::Tuple(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| item.map(|item| self.expr(item)).unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("({items})")
+            }
+            Expr::Array(items) => format!("[{}]", self.exprs(items)),
+            Expr::Delete(expr) => format!("delete {}", self.expr(*expr)),
+            Expr::Payable(args) => format!("payable({})", self.exprs(args)),
+            Expr::Literal(text) | Expr::New(text) | Expr::TypeCall(text) | Expr::Type(text) => {
+                text.clone()
+            }
+        }
+    }
+}
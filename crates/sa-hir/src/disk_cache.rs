@@ -0,0 +1,179 @@
+//! Per-file derived data (parsed imports and collected definitions) persisted between runs.
+//!
+//! Entries are keyed by a hash of the file text, so a stale entry can never be served for
+//! changed contents. The whole file is discarded when its format version or the analyzer
+//! version differs, since both parsing and collection may change between releases.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use sa_base_db::LanguageKind;
+use sa_def::FileDefs;
+use sa_syntax::ParsedImport;
+use salsa::Setter;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{HirDatabase, file_items, parse_file};
+
+/// File name of the cache inside the project's cache directory.
+pub const HIR_CACHE_FILE: &str = "solidity-analyzer-hir.json";
+
+const FORMAT_VERSION: u32 = 4;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HirCache {
+    entries: HashMap<ContentKey, CachedFile>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct ContentKey {
+    hash: u64,
+    len: usize,
+}
+
+impl ContentKey {
+    /// FNV-1a: stable across processes and Rust releases, unlike `DefaultHasher`.
+    fn new(text: &str) -> Self {
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        for byte in text.as_bytes() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Self {
+            hash,
+            len: text.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedFile {
+    key: ContentKey,
+    imports: Vec<ParsedImport>,
+    defs: FileDefs,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheContents {
+    format: u32,
+    analyzer_version: String,
+    files: Vec<CachedFile>,
+}
+
+impl HirCache {
+    /// Reads the cache at `path`. A missing, unreadable or outdated cache loads as empty.
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let contents = match serde_json::from_str::<CacheContents>(&text) {
+            Ok(contents) => contents,
+            Err(error) => {
+                debug!(?error, path = %path.display(), "discarding unreadable hir cache");
+                return Self::default();
+            }
+        };
+        if contents.format != FORMAT_VERSION
+            || contents.analyzer_version != env!("CARGO_PKG_VERSION")
+        {
+            debug!(path = %path.display(), "discarding outdated hir cache");
+            return Self::default();
+        }
+        Self {
+            entries: contents
+                .files
+                .into_iter()
+                .map(|file| (file.key, file))
+                .collect(),
+        }
+    }
+
+    /// Writes the cache to `path`, replacing any previous one atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut files = self.entries.values().cloned().collect::<Vec<_>>();
+        files.sort_by_key(|file| (file.key.hash, file.key.len));
+        let contents = CacheContents {
+            format: FORMAT_VERSION,
+            analyzer_version: env!("CARGO_PKG_VERSION").to_string(),
+            files,
+        };
+        let json = serde_json::to_string(&contents).map_err(io::Error::other)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, text: &str, imports: Vec<ParsedImport>, defs: FileDefs) {
+        let key = ContentKey::new(text);
+        self.entries.insert(key, CachedFile { key, imports, defs });
+    }
+
+    pub(crate) fn imports(&self, text: &str) -> Option<&[ParsedImport]> {
+        self.entries
+            .get(&ContentKey::new(text))
+            .map(|file| file.imports.as_slice())
+    }
+
+    pub(crate) fn defs(&self, text: &str) -> Option<&FileDefs> {
+        self.entries
+            .get(&ContentKey::new(text))
+            .map(|file| &file.defs)
+    }
+}
+
+/// Holds the cache loaded at startup. Queries consult it before parsing a file.
+#[salsa::input(singleton, debug)]
+pub struct HirCacheInput {
+    #[returns(ref)]
+    pub cache: Arc<HirCache>,
+}
+
+pub(crate) fn cached<T>(
+    db: &dyn HirDatabase,
+    lookup: impl FnOnce(&HirCache) -> Option<T>,
+) -> Option<T> {
+    HirCacheInput::try_get(db).and_then(|input| lookup(input.cache(db)))
+}
+
+pub fn set_hir_cache(db: &mut sa_base_db::Database, cache: HirCache) {
+    let cache = Arc::new(cache);
+    match HirCacheInput::try_get(&*db) {
+        Some(input) => {
+            input.set_cache(db).to(cache);
+        }
+        None => {
+            HirCacheInput::new(db, cache);
+        }
+    }
+}
+
+/// Collects the derived data of every Solidity file in `db` into a cache that can be saved.
+pub fn export_hir_cache(db: &dyn HirDatabase) -> HirCache {
+    let mut cache = HirCache::default();
+    for file_id in db.file_ids() {
+        db.check_cancelled();
+        let input = db.file_input(file_id);
+        if input.kind(db) != LanguageKind::Solidity {
+            continue;
+        }
+        let imports = parse_file(db, input).imports().to_vec();
+        let defs = file_items(db, input).defs().clone();
+        cache.insert(input.text(db), imports, defs);
+    }
+    cache
+}
@@ -0,0 +1,275 @@
+//! What each file makes visible to the files importing it, computed once per program instead of
+//! walking imports for every lookup.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use sa_base_db::{FileId, ProjectId};
+use sa_def::{DefId, DefKind, DefMap, Name};
+use sa_syntax::ParsedImportItems;
+
+use crate::{HirDatabase, HirFile, lowered_program};
+
+/// The symbols a file makes visible: its own definitions plus everything it imports through
+/// plain imports and `import {a as b}` (under the local name), in import order. Module aliases
+/// (`import "x" as M`) are not symbols and are not listed.
+///
+/// Members of contracts are included under their own name, as the name-based resolver has
+/// always found them; [`ExportMap::top_level`] filters them out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportMap {
+    names: Vec<(Name, Vec<ExportedDef>)>,
+    index: HashMap<Name, usize>,
+    /// Indices into `names`, sorted case-insensitively so a prefix selects a contiguous run.
+    sorted: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ExportedDef {
+    id: DefId,
+    top_level: bool,
+}
+
+impl ExportMap {
+    pub fn get(&self, name: &str) -> impl Iterator<Item = DefId> + '_ {
+        self.index
+            .get(name)
+            .into_iter()
+            .flat_map(|idx| self.names[*idx].1.iter().map(|def| def.id))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Every exported name with its definitions, members included.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, DefId)> + '_ {
+        self.names
+            .iter()
+            .flat_map(|(name, defs)| defs.iter().map(move |def| (name.as_str(), def.id)))
+    }
+
+    /// The names another file can import, e.g. in `import {...} from`.
+    pub fn top_level(&self) -> impl Iterator<Item = (&str, DefId)> + '_ {
+        self.names.iter().flat_map(|(name, defs)| {
+            defs.iter()
+                .filter(|def| def.top_level)
+                .map(move |def| (name.as_str(), def.id))
+        })
+    }
+
+    /// Like [`ExportMap::top_level`], limited to names starting with `prefix` regardless of
+    /// ASCII case. Only the matching names are visited.
+    pub fn top_level_with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, DefId)> + 'a {
+        let start = self
+            .sorted
+            .partition_point(|idx| cmp_ignore_case(&self.names[*idx as usize].0, prefix).is_lt());
+        self.sorted[start..]
+            .iter()
+            .map(|idx| &self.names[*idx as usize])
+            .take_while(move |(name, _)| starts_with_ignore_case(name, prefix))
+            .flat_map(|(name, defs)| {
+                defs.iter()
+                    .filter(|def| def.top_level)
+                    .map(move |def| (name.as_str(), def.id))
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn insert(&mut self, name: &Name, def: ExportedDef) -> bool {
+        let idx = match self.index.get(name.as_str()) {
+            Some(idx) => *idx,
+            None => {
+                self.index.insert(name.clone(), self.names.len());
+                self.names.push((name.clone(), Vec::new()));
+                self.names.len() - 1
+            }
+        };
+        let defs = &mut self.names[idx].1;
+        if defs.iter().any(|existing| existing.id == def.id) {
+            return false;
+        }
+        defs.push(def);
+        true
+    }
+
+    fn build_prefix_index(&mut self) {
+        let mut sorted = (0..self.names.len() as u32).collect::<Vec<_>>();
+        sorted.sort_by(|a, b| {
+            cmp_ignore_case(&self.names[*a as usize].0, &self.names[*b as usize].0)
+        });
+        self.sorted = sorted;
+    }
+
+    fn defs(&self, name: &str) -> Vec<ExportedDef> {
+        self.index
+            .get(name)
+            .map(|idx| self.names[*idx].1.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn estimated_bytes(&self) -> usize {
+        self.names
+            .iter()
+            .map(|(name, defs)| {
+                2 * size_of::<Name>()
+                    + name.len()
+                    + size_of::<usize>()
+                    + defs.len() * size_of::<ExportedDef>()
+            })
+            .sum::<usize>()
+            + self.sorted.len() * size_of::<u32>()
+    }
+}
+
+fn cmp_ignore_case(a: &str, b: &str) -> Ordering {
+    a.bytes()
+        .map(|byte| byte.to_ascii_lowercase())
+        .cmp(b.bytes().map(|byte| byte.to_ascii_lowercase()))
+}
+
+pub(crate) fn starts_with_ignore_case(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len()
+        && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// Files are processed dependencies first, so an acyclic import graph settles in one pass;
+/// import cycles are iterated until no map grows.
+pub(crate) fn collect_exports(
+    defs: &DefMap,
+    files: &HashMap<FileId, HirFile>,
+) -> HashMap<FileId, ExportMap> {
+    let mut exports: HashMap<FileId, ExportMap> = HashMap::new();
+    for entry in defs.entries() {
+        // Fields and variants are only reachable through their struct or enum, assembly functions
+        // only from their assembly block, and special functions cannot be named at all.
+        if matches!(
+            entry.kind(),
+            DefKind::Field | DefKind::Variant | DefKind::YulFunction
+        ) || entry.is_special_function()
+        {
+            continue;
+        }
+        exports
+            .entry(entry.location().file_id())
+            .or_default()
+            .insert(
+                entry.location().interned_name(),
+                ExportedDef {
+                    id: entry.id(),
+                    top_level: entry.container().is_none(),
+                },
+            );
+    }
+
+    let order = dependency_order(files);
+    loop {
+        let mut changed = false;
+        for file_id in &order {
+            let file = &files[file_id];
+            for import in &file.imports {
+                let Some(imported_id) = import.file_id else {
+                    continue;
+                };
+                if imported_id == *file_id {
+                    continue;
+                }
+                let additions = match &import.items {
+                    ParsedImportItems::Plain => exports
+                        .get(&imported_id)
+                        .map(|imported| {
+                            imported
+                                .names
+                                .iter()
+                                .flat_map(|(name, defs)| {
+                                    defs.iter().map(move |def| (name.clone(), *def))
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default(),
+                    ParsedImportItems::Aliases(aliases) => {
+                        let imported = exports.get(&imported_id);
+                        aliases
+                            .iter()
+                            .flat_map(|alias| {
+                                imported
+                                    .map(|imported| imported.defs(&alias.name))
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .map(|def| (Name::new(alias.local_name()), def))
+                            })
+                            .collect()
+                    }
+                    ParsedImportItems::SourceAlias(_) | ParsedImportItems::Glob(_) => Vec::new(),
+                };
+                let map = exports.entry(*file_id).or_default();
+                for (name, def) in additions {
+                    changed |= map.insert(&name, def);
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    for map in exports.values_mut() {
+        map.build_prefix_index();
+    }
+    exports
+}
+
+/// A post-order over the imports, so every file comes after the files it imports (cycles aside).
+fn dependency_order(files: &HashMap<FileId, HirFile>) -> Vec<FileId> {
+    let mut roots = files.keys().copied().collect::<Vec<_>>();
+    roots.sort();
+    let mut order = Vec::with_capacity(files.len());
+    let mut visited = HashSet::new();
+    for root in roots {
+        if !visited.insert(root) {
+            continue;
+        }
+        let mut stack = vec![(root, 0usize)];
+        while let Some(&(file_id, next)) = stack.last() {
+            let imported = files[&file_id]
+                .imports
+                .get(next)
+                .map(|import| import.file_id);
+            match imported {
+                Some(imported) => {
+                    if let Some((_, next)) = stack.last_mut() {
+                        *next += 1;
+                    }
+                    if let Some(imported) = imported
+                        && files.contains_key(&imported)
+                        && visited.insert(imported)
+                    {
+                        stack.push((imported, 0));
+                    }
+                }
+                None => {
+                    order.push(file_id);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    order
+}
+
+/// The export map of `file_id`; empty for files outside the project.
+pub fn exports(db: &dyn HirDatabase, project_id: ProjectId, file_id: FileId) -> ExportMap {
+    lowered_program(db, project_id)
+        .exports(file_id)
+        .cloned()
+        .unwrap_or_default()
+}
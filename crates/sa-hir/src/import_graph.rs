@@ -0,0 +1,221 @@
+//! The project's import graph, built from the per-file [`file_imports`] queries. Solidity allows
+//! import cycles, so cycles are reported rather than rejected.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use sa_base_db::{FileId, ProjectId, ProjectInput};
+
+use crate::{HirDatabase, file_imports};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportGraph {
+    edges: BTreeMap<FileId, Vec<FileId>>,
+    reverse_edges: BTreeMap<FileId, Vec<FileId>>,
+}
+
+impl ImportGraph {
+    pub fn files(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.edges.keys().copied()
+    }
+
+    /// The files `file_id` imports directly, without duplicates, in import order.
+    pub fn imports(&self, file_id: FileId) -> &[FileId] {
+        self.edges.get(&file_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The files importing `file_id` directly.
+    pub fn importers(&self, file_id: FileId) -> &[FileId] {
+        self.reverse_edges.get(&file_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (FileId, FileId)> + '_ {
+        self.edges
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |to| (*from, *to)))
+    }
+
+    /// Every file reachable from `file_id` through imports, excluding `file_id` itself unless
+    /// it is part of a cycle.
+    pub fn transitive_imports(&self, file_id: FileId) -> BTreeSet<FileId> {
+        reachable(&self.edges, file_id)
+    }
+
+    /// Every file that reaches `file_id` through imports, i.e. the files affected by an edit
+    /// to it.
+    pub fn transitive_importers(&self, file_id: FileId) -> BTreeSet<FileId> {
+        reachable(&self.reverse_edges, file_id)
+    }
+
+    /// The strongly connected components in reverse topological order: a component is listed
+    /// after every component it imports.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<FileId>> {
+        Tarjan::new(&self.edges).run()
+    }
+
+    /// The import cycles of the project: components with more than one file, and files
+    /// importing themselves.
+    pub fn cycles(&self) -> Vec<ImportCycle> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| match component.as_slice() {
+                [file_id] => self.imports(*file_id).contains(file_id),
+                _ => true,
+            })
+            .map(|files| ImportCycle { files })
+            .collect()
+    }
+
+    /// The cycle `file_id` is part of, if any.
+    pub fn cycle_containing(&self, file_id: FileId) -> Option<ImportCycle> {
+        self.cycles()
+            .into_iter()
+            .find(|cycle| cycle.files.contains(&file_id))
+    }
+}
+
+unsafe impl salsa::Update for ImportGraph {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCycle {
+    files: Vec<FileId>,
+}
+
+impl ImportCycle {
+    /// The files of the cycle, sorted by id.
+    pub fn files(&self) -> &[FileId] {
+        &self.files
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn import_graph_for_project(db: &dyn HirDatabase, project: ProjectInput) -> ImportGraph {
+    let mut graph = ImportGraph::default();
+    for file_id in db.file_ids() {
+        db.check_cancelled();
+        let mut imports = Vec::new();
+        for target in file_imports(db, project, file_id).resolved_files() {
+            if !imports.contains(&target) {
+                imports.push(target);
+            }
+        }
+        for target in &imports {
+            graph
+                .reverse_edges
+                .entry(*target)
+                .or_default()
+                .push(file_id);
+        }
+        graph.edges.insert(file_id, imports);
+    }
+    for importers in graph.reverse_edges.values_mut() {
+        importers.sort();
+    }
+    graph
+}
+
+pub fn import_graph(db: &dyn HirDatabase, project_id: ProjectId) -> ImportGraph {
+    import_graph_for_project(db, db.project_input(project_id)).clone()
+}
+
+fn reachable(edges: &BTreeMap<FileId, Vec<FileId>>, start: FileId) -> BTreeSet<FileId> {
+    let mut seen = BTreeSet::new();
+    let mut stack = edges.get(&start).cloned().unwrap_or_default();
+    while let Some(file_id) = stack.pop() {
+        if seen.insert(file_id)
+            && let Some(next) = edges.get(&file_id)
+        {
+            stack.extend(next.iter().copied());
+        }
+    }
+    seen
+}
+
+/// Iterative Tarjan, so deep import chains cannot overflow the stack.
+struct Tarjan<'a> {
+    edges: &'a BTreeMap<FileId, Vec<FileId>>,
+    index: HashMap<FileId, usize>,
+    low_link: HashMap<FileId, usize>,
+    stack: Vec<FileId>,
+    on_stack: BTreeSet<FileId>,
+    components: Vec<Vec<FileId>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(edges: &'a BTreeMap<FileId, Vec<FileId>>) -> Self {
+        Self {
+            edges,
+            index: HashMap::new(),
+            low_link: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Vec<FileId>> {
+        let edges = self.edges;
+        for &file_id in edges.keys() {
+            if !self.index.contains_key(&file_id) {
+                self.visit(file_id);
+            }
+        }
+        self.components
+    }
+
+    fn visit(&mut self, root: FileId) {
+        let edges = self.edges;
+        let mut work = vec![(root, 0usize)];
+        self.open(root);
+        while let Some(&(file_id, next)) = work.last() {
+            let targets = edges.get(&file_id).map_or(&[][..], Vec::as_slice);
+            if let Some(&target) = targets.get(next) {
+                if let Some((_, next)) = work.last_mut() {
+                    *next += 1;
+                }
+                if !self.index.contains_key(&target) {
+                    self.open(target);
+                    work.push((target, 0));
+                } else if self.on_stack.contains(&target) {
+                    let low = self.low_link[&file_id].min(self.index[&target]);
+                    self.low_link.insert(file_id, low);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some((parent, _)) = work.last() {
+                let low = self.low_link[parent].min(self.low_link[&file_id]);
+                self.low_link.insert(*parent, low);
+            }
+            if self.low_link[&file_id] == self.index[&file_id] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    component.push(member);
+                    if member == file_id {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn open(&mut self, file_id: FileId) {
+        let index = self.index.len();
+        self.index.insert(file_id, index);
+        self.low_link.insert(file_id, index);
+        self.stack.push(file_id);
+        self.on_stack.insert(file_id);
+    }
+}
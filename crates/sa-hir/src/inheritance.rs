@@ -0,0 +1,201 @@
+//! Direct base contracts resolved to DefIds for the whole project, with the reverse "derived by"
+//! edges. Linearization, type hierarchy and goto-implementation all read bases from here.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sa_base_db::{FileInput, ProjectId, ProjectInput};
+use sa_def::{DefId, DefKind};
+use sa_syntax::Parse;
+use sa_syntax::ast::{ItemContract, ItemKind};
+
+use crate::{
+    HirDatabase, HirProgram, file_syntax, lowered_program_for_project, resolve_contract_path,
+};
+
+/// The base paths of every contract in a file as written, e.g. `["Lib", "Base"]` for
+/// `is Lib.Base`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FileContractBases {
+    contracts: HashMap<String, Vec<Vec<String>>>,
+}
+
+impl FileContractBases {
+    pub(crate) fn bases(&self, contract_name: &str) -> &[Vec<String>] {
+        self.contracts.get(contract_name).map_or(&[], Vec::as_slice)
+    }
+}
+
+unsafe impl salsa::Update for FileContractBases {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub(crate) fn file_contract_bases(db: &dyn HirDatabase, file: FileInput) -> FileContractBases {
+    let text = file.text(db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    let mut contracts = HashMap::new();
+    parse.with_session(|| {
+        for item in parse.tree().items.iter() {
+            if let ItemKind::Contract(contract) = &item.kind {
+                contracts
+                    .entry(contract.name.as_str().to_string())
+                    .or_insert_with(|| contract_base_paths(contract));
+            }
+        }
+    });
+    // The parser drops the base list of some malformed headers; recover it from the CST.
+    let mut cst = None;
+    for (name, bases) in &mut contracts {
+        if bases.is_empty()
+            && let Some(contract) = cst
+                .get_or_insert_with(|| file_syntax(db, file).cst())
+                .contract_by_name(name)
+        {
+            *bases = contract.base_paths();
+        }
+    }
+    FileContractBases { contracts }
+}
+
+/// The base paths of `contract_name` as declared in `parse`, for callers working on text that
+/// is not in the database (e.g. a completion prefix).
+pub fn contract_base_paths_in_parse(parse: &Parse, contract_name: &str) -> Vec<Vec<String>> {
+    parse.with_session(|| {
+        parse
+            .tree()
+            .items
+            .iter()
+            .find_map(|item| match &item.kind {
+                ItemKind::Contract(contract) if contract.name.as_str() == contract_name => {
+                    Some(contract_base_paths(contract))
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    })
+}
+
+pub(crate) fn contract_base_paths(contract: &ItemContract<'_>) -> Vec<Vec<String>> {
+    contract
+        .bases
+        .iter()
+        .filter_map(|base| {
+            let segments: Vec<String> = base
+                .name
+                .segments()
+                .iter()
+                .map(|segment| segment.as_str().to_string())
+                .collect();
+            (!segments.is_empty()).then_some(segments)
+        })
+        .collect()
+}
+
+/// The direct bases of `contract` in declaration order. Bases that do not resolve to a contract
+/// are skipped.
+pub(crate) fn direct_bases(
+    db: &dyn HirDatabase,
+    program: &HirProgram,
+    contract: DefId,
+) -> Vec<DefId> {
+    let Some(entry) = program.def_map().entry(contract) else {
+        return Vec::new();
+    };
+    let file_id = entry.location().file_id();
+    file_contract_bases(db, db.file_input(file_id))
+        .bases(entry.location().name())
+        .iter()
+        .filter_map(|path| resolve_contract_path(program, file_id, path))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InheritanceGraph {
+    bases: HashMap<DefId, Vec<DefId>>,
+    derived: HashMap<DefId, Vec<DefId>>,
+}
+
+impl InheritanceGraph {
+    /// The bases listed in the `is` clause of `contract`, in declaration order.
+    pub fn direct_bases(&self, contract: DefId) -> &[DefId] {
+        self.bases.get(&contract).map_or(&[], Vec::as_slice)
+    }
+
+    /// The contracts listing `contract` in their `is` clause.
+    pub fn direct_derived(&self, contract: DefId) -> &[DefId] {
+        self.derived.get(&contract).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every contract `contract` inherits from, directly or not, nearest first.
+    pub fn all_bases(&self, contract: DefId) -> Vec<DefId> {
+        walk(&self.bases, contract)
+    }
+
+    /// Every contract inheriting from `contract`, directly or not, nearest first.
+    pub fn all_derived(&self, contract: DefId) -> Vec<DefId> {
+        walk(&self.derived, contract)
+    }
+}
+
+unsafe impl salsa::Update for InheritanceGraph {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn inheritance_graph_for_project(
+    db: &dyn HirDatabase,
+    project: ProjectInput,
+) -> InheritanceGraph {
+    let program = lowered_program_for_project(db, project);
+    let mut graph = InheritanceGraph::default();
+    for entry in program.def_map().entries() {
+        if entry.kind() != DefKind::Contract {
+            continue;
+        }
+        db.check_cancelled();
+        let bases = direct_bases(db, &program, entry.id());
+        for base in &bases {
+            let derived = graph.derived.entry(*base).or_default();
+            if !derived.contains(&entry.id()) {
+                derived.push(entry.id());
+            }
+        }
+        graph.bases.insert(entry.id(), bases);
+    }
+    graph
+}
+
+pub fn inheritance_graph(db: &dyn HirDatabase, project_id: ProjectId) -> InheritanceGraph {
+    inheritance_graph_for_project(db, db.project_input(project_id)).clone()
+}
+
+fn walk(edges: &HashMap<DefId, Vec<DefId>>, start: DefId) -> Vec<DefId> {
+    let mut seen = HashSet::from([start]);
+    let mut result = Vec::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(contract) = queue.pop_front() {
+        for next in edges.get(&contract).into_iter().flatten() {
+            if seen.insert(*next) {
+                result.push(*next);
+                queue.push_back(*next);
+            }
+        }
+    }
+    result
+}
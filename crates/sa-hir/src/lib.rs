@@ -1,21 +1,59 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 
-use sa_base_db::{FileId, FileInput, ProjectId, ProjectInput};
-use sa_def::{DefDatabase, DefEntry, DefId, DefKind, DefMap};
+use sa_base_db::{FileId, FileInput, MemoKey, MemoSize, ProjectId, ProjectInput};
+use sa_def::{DefEntry, DefId, DefKind, DefMap, FileDefMap, FileDefs, Name};
 use sa_paths::NormalizedPath;
 use sa_project_model::{
     FoundryResolver, FoundryWorkspace, Remapping, resolve_import_path_with_resolver,
 };
-use sa_sema::{ResolveOutcome, ResolvedSymbol, ResolvedSymbolKind, SemaDatabase};
-use sa_span::{TextRange, TextSize, is_ident_byte};
+use sa_sema::{
+    BoundFunction, CallResolution, ResolveOutcome, ResolvedSymbol, ResolvedSymbolKind, SemaDatabase,
+};
+use sa_span::{LineIndex, TextRange, TextSize};
 use sa_syntax::ast::ItemKind;
+use sa_syntax::cst::{Cst, SyntaxKind, parse_cst};
 use sa_syntax::tokens::IdentRangeCollector;
 use sa_syntax::{Parse, ParsedImport, ParsedImportItems};
-
+use tracing::debug_span;
+
+use crate::inheritance::contract_base_paths;
+
+mod arena;
+mod body;
+mod debug_dump;
+mod disk_cache;
+mod exports;
+mod import_graph;
+mod inheritance;
+mod linearize;
 mod locals;
+mod member;
+mod yul;
 
+pub use body::{
+    Binding, BindingId, BindingKind, Body, BodySourceMap, Expr, ExprId, FileBodies, FunctionBody,
+    Stmt, StmtId, TryClause, file_bodies,
+};
+pub use debug_dump::{defmap_debug_dump, hir_debug_dump};
+pub use disk_cache::{HIR_CACHE_FILE, HirCache, HirCacheInput, export_hir_cache, set_hir_cache};
+pub use exports::{ExportMap, exports};
+pub use import_graph::{ImportCycle, ImportGraph, import_graph, import_graph_for_project};
+pub use inheritance::{
+    InheritanceGraph, contract_base_paths_in_parse, inheritance_graph,
+    inheritance_graph_for_project,
+};
+pub use linearize::{
+    Linearization, LinearizationError, LinearizationErrorKind, linearization_errors,
+    linearized_bases,
+};
 pub use locals::{LocalDef, LocalDefKind, LocalScopes, local_references, local_scopes};
+pub use sa_sema::{PathIndex, path_index};
+pub use yul::{
+    AssemblyBlock, YUL_BUILTINS, YulDef, YulDefKind, YulHir, YulReference, YulResolution,
+    is_yul_builtin, yul_hir,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Definition {
@@ -23,6 +61,13 @@ pub enum Definition {
     Local(LocalDef),
 }
 
+/// The overload a call site binds to, or the overloads it could not be narrowed down from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedCall {
+    Resolved(DefId),
+    Ambiguous(Vec<DefId>),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DefinitionLocation {
     pub file_id: FileId,
@@ -32,7 +77,7 @@ pub struct DefinitionLocation {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VisibleDefinition {
-    name: String,
+    name: Name,
     kind: DefKind,
 }
 
@@ -62,9 +107,18 @@ impl ParsedFile {
         Self { imports }
     }
 
-    fn imports(&self) -> &[ParsedImport] {
+    pub(crate) fn imports(&self) -> &[ParsedImport] {
         &self.imports
     }
+
+    pub fn estimated_bytes(&self) -> usize {
+        self.imports
+            .iter()
+            .map(|import| {
+                size_of::<ParsedImport>() + import.path.len() + import_items_bytes(&import.items)
+            })
+            .sum()
+    }
 }
 
 unsafe impl salsa::Update for ParsedFile {
@@ -82,18 +136,148 @@ unsafe impl salsa::Update for ParsedFile {
 #[salsa::tracked(returns(ref))]
 pub fn parse_file(db: &dyn HirDatabase, file: FileInput) -> ParsedFile {
     let text = file.text(db);
-    let imports = sa_syntax::parse_imports_with_items(text);
-    ParsedFile::new(imports)
+    let imports = disk_cache::cached(db, |cache| cache.imports(text).map(<[_]>::to_vec))
+        .unwrap_or_else(|| sa_syntax::parse_imports_with_items(text));
+    let parsed = ParsedFile::new(imports);
+    db.record_memo_size(
+        MemoKey::ParsedFile(file),
+        MemoSize {
+            bytes: parsed.estimated_bytes(),
+            entries: 0,
+        },
+    );
+    parsed
 }
 
 pub fn parse(db: &dyn HirDatabase, file_id: FileId) -> ParsedFile {
     parse_file(db, db.file_input(file_id)).clone()
 }
 
+/// The lines of a file, shared by every request converting between offsets and positions in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLineIndex {
+    index: Arc<LineIndex>,
+}
+
+impl FileLineIndex {
+    pub fn index(&self) -> &Arc<LineIndex> {
+        &self.index
+    }
+}
+
+unsafe impl salsa::Update for FileLineIndex {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn file_line_index(db: &dyn HirDatabase, file: FileInput) -> FileLineIndex {
+    FileLineIndex {
+        index: Arc::new(LineIndex::new(file.text(db))),
+    }
+}
+
+/// The concrete syntax tree of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSyntax {
+    cst: Arc<Cst>,
+}
+
+impl FileSyntax {
+    pub fn cst(&self) -> &Arc<Cst> {
+        &self.cst
+    }
+}
+
+unsafe impl salsa::Update for FileSyntax {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+/// Reparses the tree kept for the previous version of the file when the edits since are known,
+/// so typing in a function body only reparses that body. The kept tree is not tracked, which is
+/// sound as [`Cst::reparse`] gives the same tree as a full parse.
+#[salsa::tracked(returns(ref))]
+pub fn file_syntax(db: &dyn HirDatabase, file: FileInput) -> FileSyntax {
+    let text = file.text(db);
+    let version = file.version(db);
+    let edits = file.edits(db);
+    let cst = match db.syntax_tree(file) {
+        Some((previous, cst)) if !edits.is_empty() && previous.checked_add(1) == Some(version) => {
+            let edits = edits
+                .iter()
+                .map(|edit| (edit.range, edit.new_text.as_str()));
+            Arc::new(cst.reparse(edits, text))
+        }
+        _ => Arc::new(parse_cst(text)),
+    };
+    db.set_syntax_tree(file, version, Arc::clone(&cst));
+    FileSyntax { cst }
+}
+
+pub fn syntax(db: &dyn HirDatabase, file_id: FileId) -> Arc<Cst> {
+    Arc::clone(file_syntax(db, db.file_input(file_id)).cst())
+}
+
+/// The definitions declared in a file, collected from its text alone so that an edit only
+/// re-collects the edited file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileItems {
+    defs: FileDefs,
+}
+
+impl FileItems {
+    pub fn defs(&self) -> &FileDefs {
+        &self.defs
+    }
+}
+
+unsafe impl salsa::Update for FileItems {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn file_items(db: &dyn HirDatabase, file: FileInput) -> FileItems {
+    let text = file.text(db);
+    let defs = disk_cache::cached(db, |cache| cache.defs(text).cloned())
+        .unwrap_or_else(|| FileDefs::collect(text));
+    db.record_memo_size(
+        MemoKey::FileItems(file),
+        MemoSize {
+            bytes: defs.estimated_bytes(),
+            entries: 0,
+        },
+    );
+    FileItems { defs }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HirProgram {
     defs: DefMap,
     files: HashMap<FileId, HirFile>,
+    exports: HashMap<FileId, ExportMap>,
 }
 
 impl HirProgram {
@@ -105,56 +289,105 @@ impl HirProgram {
         &self.defs
     }
 
+    /// What `file_id` makes visible to files importing it.
+    pub fn exports(&self, file_id: FileId) -> Option<&ExportMap> {
+        self.exports.get(&file_id)
+    }
+
+    /// A rough estimate of the heap memory held by the program's files, imports and export maps,
+    /// excluding
+    /// its [`DefMap`] (see [`DefMap::estimated_bytes`]).
+    pub fn estimated_bytes(&self) -> usize {
+        self.files
+            .values()
+            .map(|file| {
+                let imports = file
+                    .imports
+                    .iter()
+                    .map(|import| {
+                        size_of::<Import>()
+                            + import.path.len()
+                            + import
+                                .resolved_path
+                                .as_ref()
+                                .map_or(0, |path| path.as_str().len())
+                            + import_items_bytes(&import.items)
+                    })
+                    .sum::<usize>();
+                size_of::<(FileId, HirFile)>() + file.path.as_str().len() + imports
+            })
+            .sum::<usize>()
+            + self
+                .exports
+                .values()
+                .map(|exports| size_of::<(FileId, ExportMap)>() + exports.estimated_bytes())
+                .sum::<usize>()
+    }
+
     pub fn visible_definitions_in_file(&self, file_id: FileId) -> Vec<VisibleDefinition> {
+        self.visible_definitions_with_prefix(file_id, "")
+    }
+
+    /// The visible definitions whose names start with `prefix`, ignoring ASCII case, found
+    /// through the export map's prefix index rather than by filtering every definition.
+    pub fn visible_definitions_with_prefix(
+        &self,
+        file_id: FileId,
+        prefix: &str,
+    ) -> Vec<VisibleDefinition> {
         let mut defs = Vec::new();
         let mut seen = HashSet::new();
+        if let Some(exports) = self.exports.get(&file_id) {
+            for (name, id) in exports.top_level_with_prefix(prefix) {
+                if let Some(entry) = self.defs.entry(id) {
+                    self.push_visible_definition(
+                        Name::new(name),
+                        entry.kind(),
+                        &mut defs,
+                        &mut seen,
+                    );
+                }
+            }
+        }
+        let mut aliases = Vec::new();
         let mut visited = HashSet::new();
-        self.collect_visible_definitions(file_id, &mut defs, &mut seen, &mut visited);
+        self.collect_module_aliases(file_id, &mut aliases, &mut seen, &mut visited);
+        defs.extend(
+            aliases
+                .into_iter()
+                .filter(|alias| exports::starts_with_ignore_case(&alias.name, prefix)),
+        );
         defs
     }
 
-    fn collect_visible_definitions(
+    /// Module aliases (`import "x" as M`) are visible through plain imports like any symbol,
+    /// but are not definitions, so the export maps leave them out.
+    fn collect_module_aliases(
         &self,
         file_id: FileId,
         defs: &mut Vec<VisibleDefinition>,
-        seen: &mut HashSet<(String, DefKind)>,
+        seen: &mut HashSet<(Name, DefKind)>,
         visited: &mut HashSet<FileId>,
     ) {
         if !visited.insert(file_id) {
             return;
         }
-        self.push_top_level_defs_in_file(file_id, defs, seen);
-
         let Some(file) = self.files.get(&file_id) else {
             return;
         };
         for import in &file.imports {
-            let Some(imported_id) = import.file_id else {
-                continue;
-            };
             match &import.items {
                 ParsedImportItems::Plain => {
-                    self.collect_visible_definitions(imported_id, defs, seen, visited);
-                }
-                ParsedImportItems::Aliases(aliases) => {
-                    for alias in aliases {
-                        let entries = self.exported_entries_for_name(imported_id, &alias.name);
-                        for entry in entries {
-                            if entry.container().is_some() {
-                                continue;
-                            }
-                            self.push_visible_definition(
-                                alias.local_name().to_string(),
-                                entry.kind(),
-                                defs,
-                                seen,
-                            );
-                        }
+                    if let Some(imported_id) = import.file_id {
+                        self.collect_module_aliases(imported_id, defs, seen, visited);
                     }
                 }
                 ParsedImportItems::SourceAlias(alias) | ParsedImportItems::Glob(alias) => {
-                    self.push_visible_definition(alias.clone(), DefKind::Udvt, defs, seen);
+                    if import.file_id.is_some() {
+                        self.push_visible_definition(Name::new(alias), DefKind::Udvt, defs, seen);
+                    }
                 }
+                ParsedImportItems::Aliases(_) => {}
             }
         }
     }
@@ -170,11 +403,11 @@ impl HirProgram {
             if entry.location().file_id() != file_id {
                 continue;
             }
-            if entry.container() != Some(contract_name) {
+            if entry.container() != Some(contract_name) || entry.is_special_function() {
                 continue;
             }
             self.push_visible_definition(
-                entry.location().name().to_string(),
+                entry.location().interned_name().clone(),
                 entry.kind(),
                 &mut defs,
                 &mut seen,
@@ -187,7 +420,7 @@ impl HirProgram {
         &self,
         file_id: FileId,
         defs: &mut Vec<VisibleDefinition>,
-        seen: &mut HashSet<(String, DefKind)>,
+        seen: &mut HashSet<(Name, DefKind)>,
     ) {
         for entry in self.defs.entries() {
             if entry.location().file_id() != file_id {
@@ -197,7 +430,7 @@ impl HirProgram {
                 continue;
             }
             self.push_visible_definition(
-                entry.location().name().to_string(),
+                entry.location().interned_name().clone(),
                 entry.kind(),
                 defs,
                 seen,
@@ -207,82 +440,36 @@ impl HirProgram {
 
     fn push_visible_definition(
         &self,
-        name: String,
+        name: Name,
         kind: DefKind,
         defs: &mut Vec<VisibleDefinition>,
-        seen: &mut HashSet<(String, DefKind)>,
+        seen: &mut HashSet<(Name, DefKind)>,
     ) {
         if seen.insert((name.clone(), kind)) {
             defs.push(VisibleDefinition { name, kind });
         }
     }
 
-    fn exported_entries_for_name<'a>(&'a self, file_id: FileId, name: &str) -> Vec<&'a DefEntry> {
-        let mut entries = Vec::new();
-        let mut visited = HashSet::new();
-        self.collect_exported_entries(file_id, name, &mut visited, &mut entries);
-        entries
-    }
-
     fn exported_names_for_definition(
         &self,
         file_id: FileId,
         target_file_id: FileId,
         target_name: &str,
     ) -> Vec<String> {
-        let mut visible = Vec::new();
-        let mut seen = HashSet::new();
-        let mut visited = HashSet::new();
-        self.collect_visible_definitions(file_id, &mut visible, &mut seen, &mut visited);
-
-        visible
-            .into_iter()
-            .filter_map(|def| {
-                let entries = self.exported_entries_for_name(file_id, def.name());
-                let matches_target = entries.iter().any(|entry| {
-                    entry.location().file_id() == target_file_id
-                        && entry.location().name() == target_name
-                });
-                matches_target.then(|| def.name().to_string())
-            })
-            .collect()
-    }
-
-    fn collect_exported_entries<'a>(
-        &'a self,
-        file_id: FileId,
-        name: &str,
-        visited: &mut HashSet<FileId>,
-        entries: &mut Vec<&'a DefEntry>,
-    ) {
-        if !visited.insert(file_id) {
-            return;
-        }
-
-        entries.extend(self.defs.entries_by_name_in_file(file_id, name));
-
-        let Some(file) = self.files.get(&file_id) else {
-            return;
+        let Some(exports) = self.exports.get(&file_id) else {
+            return Vec::new();
         };
-        for import in &file.imports {
-            let Some(imported_id) = import.file_id else {
-                continue;
-            };
-            match &import.items {
-                ParsedImportItems::Plain => {
-                    self.collect_exported_entries(imported_id, name, visited, entries);
-                }
-                ParsedImportItems::Aliases(aliases) => {
-                    for alias in aliases {
-                        if alias.local_name() != name {
-                            continue;
-                        }
-                        self.collect_exported_entries(imported_id, &alias.name, visited, entries);
-                    }
-                }
-                ParsedImportItems::SourceAlias(_) | ParsedImportItems::Glob(_) => {}
+        let mut names = Vec::new();
+        for (name, id) in exports.top_level() {
+            let matches_target = self.defs.entry(id).is_some_and(|entry| {
+                entry.location().file_id() == target_file_id
+                    && entry.location().name() == target_name
+            });
+            if matches_target && !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
             }
         }
+        names
     }
 
     pub fn resolve_contract(&self, file_id: FileId, name: &str) -> Option<DefId> {
@@ -537,69 +724,25 @@ impl HirProgram {
         }
 
         let file = self.files.get(&file_id)?;
-        let mut visited = HashSet::new();
-        visited.insert(file_id);
         for import in &file.imports {
             let Some(imported_id) = import.file_id else {
                 continue;
             };
+            if imported_id == file_id {
+                continue;
+            }
             let Some(imported_name) = import.imported_name_for_local(name) else {
                 continue;
             };
-            if let Some(entry) =
-                self.resolve_in_exports(imported_id, &imported_name, kind, &mut visited)
-            {
-                return Some(entry);
-            }
-        }
-
-        None
-    }
-
-    fn resolve_in_exports(
-        &self,
-        file_id: FileId,
-        name: &str,
-        kind: DefKind,
-        visited: &mut HashSet<FileId>,
-    ) -> Option<DefId> {
-        if !visited.insert(file_id) {
-            return None;
-        }
-
-        if let Some(entry) = self
-            .defs
-            .entries_by_name_in_file(file_id, name)
-            .into_iter()
-            .find(|entry| entry.kind() == kind)
-        {
-            return Some(entry.id());
-        }
-
-        let file = self.files.get(&file_id)?;
-        for import in &file.imports {
-            let Some(imported_id) = import.file_id else {
+            let Some(exports) = self.exports.get(&imported_id) else {
                 continue;
             };
-            match &import.items {
-                ParsedImportItems::Plain => {
-                    if let Some(entry) = self.resolve_in_exports(imported_id, name, kind, visited) {
-                        return Some(entry);
-                    }
-                }
-                ParsedImportItems::Aliases(aliases) => {
-                    for alias in aliases {
-                        if alias.local_name() != name {
-                            continue;
-                        }
-                        if let Some(entry) =
-                            self.resolve_in_exports(imported_id, &alias.name, kind, visited)
-                        {
-                            return Some(entry);
-                        }
-                    }
-                }
-                ParsedImportItems::SourceAlias(_) | ParsedImportItems::Glob(_) => {}
+            if let Some(id) = exports.get(&imported_name).find(|id| {
+                self.defs
+                    .entry(*id)
+                    .is_some_and(|entry| entry.kind() == kind)
+            }) {
+                return Some(id);
             }
         }
 
@@ -626,6 +769,21 @@ struct HirFile {
     imports: Vec<Import>,
 }
 
+fn import_items_bytes(items: &ParsedImportItems) -> usize {
+    match items {
+        ParsedImportItems::Plain => 0,
+        ParsedImportItems::SourceAlias(alias) | ParsedImportItems::Glob(alias) => alias.len(),
+        ParsedImportItems::Aliases(aliases) => aliases
+            .iter()
+            .map(|alias| {
+                size_of::<sa_syntax::ImportAlias>()
+                    + alias.name.len()
+                    + alias.alias.as_ref().map_or(0, String::len)
+            })
+            .sum(),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Import {
     path: String,
@@ -659,54 +817,134 @@ impl Import {
     }
 }
 
+/// The imports of a single file, resolved against the project's remappings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileImports {
+    imports: Vec<Import>,
+}
+
+impl FileImports {
+    /// The files this file imports that are known to the database, in import order.
+    pub fn resolved_files(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.imports.iter().filter_map(|import| import.file_id)
+    }
+}
+
+unsafe impl salsa::Update for FileImports {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn file_imports(db: &dyn HirDatabase, project: ProjectInput, file_id: FileId) -> FileImports {
+    let workspace = project.workspace(db);
+    let remappings = project.config(db).active_profile().remappings();
+    let input = db.file_input(file_id);
+    let path = db.file_path(file_id);
+    let resolver = FoundryResolver::new(&workspace, remappings)
+        .ok()
+        .map(|resolver| resolver.with_node_modules(db.node_modules(workspace.root())));
+    let imports = collect_imports(
+        &workspace,
+        remappings,
+        resolver.as_ref(),
+        &path,
+        parse_file(db, input),
+        path_index(db),
+        input.text(db),
+    );
+    FileImports { imports }
+}
+
+/// The definitions declared in a file; see [`file_items`].
+pub fn file_defs(db: &dyn HirDatabase, file_id: FileId) -> FileDefs {
+    file_items(db, db.file_input(file_id)).defs().clone()
+}
+
+/// The definitions of a file with their ids; see [`FileDefs::lower`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDefIds {
+    defs: FileDefMap,
+}
+
+impl FileDefIds {
+    pub fn defs(&self) -> &FileDefMap {
+        &self.defs
+    }
+}
+
+unsafe impl salsa::Update for FileDefIds {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn file_def_ids(db: &dyn HirDatabase, _project: ProjectInput, file_id: FileId) -> FileDefIds {
+    let defs = file_items(db, db.file_input(file_id)).defs().lower(file_id);
+    FileDefIds { defs }
+}
+
+/// Aggregates the per-file [`file_def_ids`] and [`file_imports`] queries, so an edit only
+/// re-lowers the edited file; what remains here is merging the files' definitions. On a cold
+/// start the per-file queries run on salsa's thread pool. Files are merged in id order, so the
+/// program does not depend on which worker finished first or on hash map order.
 #[salsa::tracked]
 pub fn lowered_program_for_project(db: &dyn HirDatabase, project: ProjectInput) -> HirProgram {
-    let workspace = project.workspace(db).clone();
-    let remappings = project.config(db).active_profile().remappings();
-    let mut def_db = DefDatabase::new();
-    let mut file_texts = Vec::new();
-    let mut files = HashMap::new();
-
-    let mut path_to_file_id = HashMap::new();
-    for file_id in db.file_ids() {
-        let path = db.file_path(file_id);
-        path_to_file_id.insert(path.as_str().to_string(), file_id);
-    }
-
-    for file_id in db.file_ids() {
-        let text = db.file_input(file_id).text(db).clone();
-        file_texts.push((file_id, text.clone()));
-
-        let path = db.file_path(file_id);
-        let parsed = parse_file(db, db.file_input(file_id));
-        let imports = collect_imports(
-            &workspace,
-            remappings,
-            &path,
-            parsed,
-            &path_to_file_id,
-            &text,
-        );
-        files.insert(
+    let _span = debug_span!("lowered_program", root = %project.workspace(db).root()).entered();
+    let mut file_ids = db.file_ids();
+    file_ids.sort_unstable();
+    let lowered: Vec<(HirFile, FileDefIds)> = salsa::par_map(db, file_ids, |db, file_id| {
+        db.check_cancelled();
+        let file = HirFile {
             file_id,
-            HirFile {
-                file_id,
-                path: (*path).clone(),
-                imports,
-            },
-        );
-    }
+            path: (*db.file_path(file_id)).clone(),
+            imports: file_imports(db, project, file_id).imports.clone(),
+        };
+        (file, file_def_ids(db, project, file_id).clone())
+    });
 
-    let def_map = def_db.collect(
-        file_texts
-            .iter()
-            .map(|(file_id, text)| (*file_id, text.as_ref())),
-    );
+    let def_map = DefMap::merge(lowered.iter().map(|(_, ids)| ids.defs()));
+    let files = lowered
+        .into_iter()
+        .map(|(file, _)| (file.file_id, file))
+        .collect();
+    let exports = exports::collect_exports(&def_map, &files);
 
-    HirProgram {
+    let program = HirProgram {
         defs: def_map,
         files,
-    }
+        exports,
+    };
+    let def_map = program.def_map();
+    db.record_memo_size(
+        MemoKey::DefMap(project),
+        MemoSize {
+            bytes: def_map.estimated_bytes(),
+            entries: def_map.entries().len(),
+        },
+    );
+    db.record_memo_size(
+        MemoKey::Program(project),
+        MemoSize {
+            bytes: program.estimated_bytes(),
+            entries: 0,
+        },
+    );
+    program
 }
 
 pub fn lowered_program(db: &dyn HirDatabase, project_id: ProjectId) -> HirProgram {
@@ -722,6 +960,18 @@ pub fn visible_definitions(
     program.visible_definitions_in_file(file_id)
 }
 
+/// The visible definitions of `file_id` whose names start with `prefix`; see
+/// [`HirProgram::visible_definitions_with_prefix`].
+pub fn visible_definitions_with_prefix(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+    prefix: &str,
+) -> Vec<VisibleDefinition> {
+    let program = lowered_program(db, project_id);
+    program.visible_definitions_with_prefix(file_id, prefix)
+}
+
 pub fn contract_member_definitions_at_offset(
     db: &dyn HirDatabase,
     project_id: ProjectId,
@@ -730,7 +980,7 @@ pub fn contract_member_definitions_at_offset(
 ) -> Vec<VisibleDefinition> {
     let text = db.file_input(file_id).text(db);
     let parse = sa_syntax::parse_file(text.as_ref());
-    let Some(contract_info) = contract_info_at_offset(&parse, text.as_ref(), offset) else {
+    let Some(contract_info) = contract_info_at_offset(db, file_id, &parse, offset) else {
         return Vec::new();
     };
     let program = lowered_program(db, project_id);
@@ -742,9 +992,14 @@ struct ContractInfo {
     bases: Vec<Vec<String>>,
 }
 
-fn contract_info_at_offset(parse: &Parse, text: &str, offset: TextSize) -> Option<ContractInfo> {
+fn contract_info_at_offset(
+    db: &dyn HirDatabase,
+    file_id: FileId,
+    parse: &Parse,
+    offset: TextSize,
+) -> Option<ContractInfo> {
     contract_info_at_offset_from_parse(parse, offset)
-        .or_else(|| contract_info_at_offset_fallback(text, offset))
+        .or_else(|| contract_info_at_offset_from_cst(&syntax(db, file_id), offset))
 }
 
 fn contract_info_at_offset_from_parse(parse: &Parse, offset: TextSize) -> Option<ContractInfo> {
@@ -764,466 +1019,46 @@ fn contract_info_at_offset_from_parse(parse: &Parse, offset: TextSize) -> Option
     })
 }
 
-fn contract_info_at_offset_fallback(text: &str, offset: TextSize) -> Option<ContractInfo> {
-    let offset = usize::from(offset).min(text.len());
-    let bytes = text.as_bytes();
-    if bytes.is_empty() {
-        return None;
-    }
-
-    let mut scanner = FallbackScanner::new(text);
-    let mut brace_depth = 0usize;
-
-    while let Some(token) = scanner.next_token() {
-        match token.kind {
-            FallbackTokenKind::Punct('{') => brace_depth += 1,
-            FallbackTokenKind::Punct('}') => brace_depth = brace_depth.saturating_sub(1),
-            FallbackTokenKind::Ident(ref ident)
-                if brace_depth == 0 && is_contract_keyword(ident) =>
-            {
-                if let Some(decl) = parse_contract_decl(&mut scanner, text, token.start) {
-                    brace_depth = 0;
-                    if offset >= decl.start && offset <= decl.close_brace {
-                        return Some(ContractInfo {
-                            name: decl.name,
-                            bases: decl.bases,
-                        });
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-
-    None
-}
-
-fn is_contract_keyword(ident: &str) -> bool {
-    matches!(ident, "contract" | "library" | "interface")
-}
-
-struct ContractDecl {
-    start: usize,
-    open_brace: usize,
-    close_brace: usize,
-    name: String,
-    bases: Vec<Vec<String>>,
-}
-
-fn parse_contract_decl(
-    scanner: &mut FallbackScanner<'_>,
-    text: &str,
-    contract_start: usize,
-) -> Option<ContractDecl> {
-    let name_token = scanner.next_token()?;
-    let name = match name_token.kind {
-        FallbackTokenKind::Ident(ident) => ident,
-        _ => return None,
-    };
-
-    let mut is_end: Option<usize> = None;
-    let mut open_brace: Option<usize> = None;
-
-    while let Some(token) = scanner.next_token() {
-        match token.kind {
-            FallbackTokenKind::Ident(ident) if ident == "is" => {
-                is_end = Some(token.end);
-            }
-            FallbackTokenKind::Punct('{') => {
-                open_brace = Some(token.start);
-                break;
-            }
-            _ => {}
-        }
-    }
-
-    let open_brace = open_brace?;
-    let close_brace = matching_close_brace(text, open_brace).unwrap_or(text.len());
-    scanner.idx = close_brace.saturating_add(1);
-
-    let bases = is_end
-        .map(|start| parse_base_paths_in_range(text, start, open_brace))
-        .unwrap_or_default();
-
-    Some(ContractDecl {
-        start: contract_start,
-        open_brace,
-        close_brace,
-        name,
-        bases,
+fn contract_info_at_offset_from_cst(cst: &Cst, offset: TextSize) -> Option<ContractInfo> {
+    let contract = cst.contract_at_offset(offset)?;
+    Some(ContractInfo {
+        name: contract.name()?.text().to_string(),
+        bases: contract.base_paths(),
     })
 }
 
-fn matching_close_brace(text: &str, open_brace: usize) -> Option<usize> {
-    let bytes = text.as_bytes();
-    if open_brace >= bytes.len() {
-        return None;
-    }
-    let mut depth = 0usize;
-    let mut i = open_brace.saturating_add(1);
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b == b'/' && i + 1 < bytes.len() {
-            let next = bytes[i + 1];
-            if next == b'/' {
-                i += 2;
-                while i < bytes.len() && bytes[i] != b'\n' {
-                    i += 1;
-                }
-                continue;
-            }
-            if next == b'*' {
-                i += 2;
-                while i + 1 < bytes.len() {
-                    if bytes[i] == b'*' && bytes[i + 1] == b'/' {
-                        i += 2;
-                        break;
-                    }
-                    i += 1;
-                }
-                continue;
-            }
-        }
-        if b == b'\'' || b == b'"' {
-            i = skip_string(bytes, i);
-            continue;
-        }
-        if b == b'{' {
-            depth += 1;
-        } else if b == b'}' {
-            if depth == 0 {
-                return Some(i);
-            }
-            depth = depth.saturating_sub(1);
-        }
-        i += 1;
-    }
-    None
-}
-
-fn parse_base_paths_in_range(text: &str, start: usize, end: usize) -> Vec<Vec<String>> {
-    let bytes = text.as_bytes();
-    let mut i = start.min(bytes.len());
-    let end = end.min(bytes.len());
-
-    let mut bases: Vec<Vec<String>> = Vec::new();
-    let mut current: Vec<String> = Vec::new();
-    let mut paren_depth = 0usize;
-
-    while i < end {
-        let b = bytes[i];
-        if b == b'/' && i + 1 < end {
-            let next = bytes[i + 1];
-            if next == b'/' {
-                i += 2;
-                while i < end && bytes[i] != b'\n' {
-                    i += 1;
-                }
-                continue;
-            }
-            if next == b'*' {
-                i += 2;
-                while i + 1 < end {
-                    if bytes[i] == b'*' && bytes[i + 1] == b'/' {
-                        i += 2;
-                        break;
-                    }
-                    i += 1;
-                }
-                continue;
-            }
-        }
-        if b == b'\'' || b == b'"' {
-            i = skip_string(bytes, i);
-            continue;
-        }
-        if b == b'(' {
-            paren_depth += 1;
-            i += 1;
-            continue;
-        }
-        if b == b')' {
-            paren_depth = paren_depth.saturating_sub(1);
-            i += 1;
-            continue;
-        }
-        if paren_depth > 0 {
-            i += 1;
-            continue;
-        }
-        if is_ident_byte(b) {
-            let start_ident = i;
-            i += 1;
-            while i < end && is_ident_byte(bytes[i]) {
-                i += 1;
-            }
-            if let Ok(ident) = std::str::from_utf8(&bytes[start_ident..i]) {
-                current.push(ident.to_string());
-            }
-            continue;
-        }
-        if b == b',' {
-            if !current.is_empty() {
-                bases.push(std::mem::take(&mut current));
-            }
-            i += 1;
-            continue;
-        }
-        i += 1;
-    }
-
-    if !current.is_empty() {
-        bases.push(current);
-    }
-
-    bases
-}
-
-fn contract_decl_by_name_fallback(text: &str, contract_name: &str) -> Option<ContractDecl> {
-    let bytes = text.as_bytes();
-    if bytes.is_empty() {
-        return None;
-    }
-    let mut scanner = FallbackScanner::new(text);
-    let mut brace_depth = 0usize;
-    while let Some(token) = scanner.next_token() {
-        match token.kind {
-            FallbackTokenKind::Punct('{') => brace_depth += 1,
-            FallbackTokenKind::Punct('}') => brace_depth = brace_depth.saturating_sub(1),
-            FallbackTokenKind::Ident(ref ident)
-                if brace_depth == 0 && is_contract_keyword(ident) =>
-            {
-                if let Some(decl) = parse_contract_decl(&mut scanner, text, token.start) {
-                    brace_depth = 0;
-                    if decl.name == contract_name {
-                        return Some(decl);
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-    None
-}
-
-fn fallback_contract_member_definitions(text: &str, contract_name: &str) -> Vec<VisibleDefinition> {
-    let Some(decl) = contract_decl_by_name_fallback(text, contract_name) else {
+fn contract_member_definitions_from_cst(cst: &Cst, contract_name: &str) -> Vec<VisibleDefinition> {
+    let Some(body) = cst
+        .contract_by_name(contract_name)
+        .and_then(|contract| contract.child_node(SyntaxKind::ContractBody))
+    else {
         return Vec::new();
     };
 
-    let mut scanner = FallbackScanner::new(text);
-    scanner.idx = decl.open_brace.saturating_add(1);
-    let mut brace_depth = 1usize;
-
     let mut defs = Vec::new();
     let mut seen = HashSet::new();
-    let mut pending_kind: Option<DefKind> = None;
-    let mut statement_idents: Vec<String> = Vec::new();
-    let mut statement_has_decl_keyword = false;
-    let mut statement_skip_variable = false;
-
-    while let Some(token) = scanner.next_token() {
-        if token.start > decl.close_brace {
-            break;
-        }
-        match token.kind {
-            FallbackTokenKind::Ident(ident) => {
-                if brace_depth != 1 {
-                    continue;
-                }
-                if let Some(kind) = pending_kind.take() {
-                    if seen.insert((ident.clone(), kind)) {
-                        defs.push(VisibleDefinition { name: ident, kind });
-                    }
-                    continue;
-                }
-                match ident.as_str() {
-                    "function" => {
-                        pending_kind = Some(DefKind::Function);
-                        statement_has_decl_keyword = true;
-                    }
-                    "event" => {
-                        pending_kind = Some(DefKind::Event);
-                        statement_has_decl_keyword = true;
-                    }
-                    "error" => {
-                        pending_kind = Some(DefKind::Error);
-                        statement_has_decl_keyword = true;
-                    }
-                    "modifier" => {
-                        pending_kind = Some(DefKind::Modifier);
-                        statement_has_decl_keyword = true;
-                    }
-                    "struct" => {
-                        pending_kind = Some(DefKind::Struct);
-                        statement_has_decl_keyword = true;
-                    }
-                    "enum" => {
-                        pending_kind = Some(DefKind::Enum);
-                        statement_has_decl_keyword = true;
-                    }
-                    "type" => {
-                        pending_kind = Some(DefKind::Udvt);
-                        statement_has_decl_keyword = true;
-                    }
-                    "using" | "pragma" | "import" => {
-                        statement_skip_variable = true;
-                    }
-                    _ => {
-                        statement_idents.push(ident);
-                    }
-                }
-            }
-            FallbackTokenKind::Punct('{') => {
-                if brace_depth == 1 {
-                    statement_idents.clear();
-                    statement_has_decl_keyword = false;
-                    statement_skip_variable = false;
-                    pending_kind = None;
-                }
-                brace_depth += 1;
-            }
-            FallbackTokenKind::Punct('}') => {
-                if brace_depth > 0 {
-                    brace_depth = brace_depth.saturating_sub(1);
-                }
-                if brace_depth == 0 {
-                    break;
-                }
-            }
-            FallbackTokenKind::Punct(';') => {
-                if brace_depth == 1 {
-                    if !statement_has_decl_keyword
-                        && !statement_skip_variable
-                        && let Some(name) = statement_idents.last()
-                    {
-                        let name = name.clone();
-                        if seen.insert((name.clone(), DefKind::Variable)) {
-                            defs.push(VisibleDefinition {
-                                name,
-                                kind: DefKind::Variable,
-                            });
-                        }
-                    }
-                    statement_idents.clear();
-                    statement_has_decl_keyword = false;
-                    statement_skip_variable = false;
-                }
-            }
-            _ => {}
-        }
-    }
-
-    defs
-}
-
-fn skip_string(bytes: &[u8], start: usize) -> usize {
-    let quote = bytes[start];
-    let mut i = start.saturating_add(1);
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b == b'\\' {
-            i = i.saturating_add(2);
+    for member in body.child_nodes() {
+        let kind = match member.kind() {
+            SyntaxKind::Function => DefKind::Function,
+            SyntaxKind::Modifier => DefKind::Modifier,
+            SyntaxKind::Event => DefKind::Event,
+            SyntaxKind::ErrorDef => DefKind::Error,
+            SyntaxKind::Struct => DefKind::Struct,
+            SyntaxKind::Enum => DefKind::Enum,
+            SyntaxKind::Udvt => DefKind::Udvt,
+            SyntaxKind::Variable => DefKind::Variable,
+            _ => continue,
+        };
+        // Constructors, `receive` and `fallback` have no name to complete.
+        let Some(name) = member.name() else {
             continue;
-        }
-        if b == quote {
-            return i.saturating_add(1);
-        }
-        i += 1;
-    }
-    bytes.len()
-}
-
-struct FallbackScanner<'a> {
-    bytes: &'a [u8],
-    idx: usize,
-}
-
-struct FallbackToken {
-    kind: FallbackTokenKind,
-    start: usize,
-    end: usize,
-}
-
-enum FallbackTokenKind {
-    Ident(String),
-    Punct(char),
-}
-
-impl<'a> FallbackScanner<'a> {
-    fn new(text: &'a str) -> Self {
-        Self {
-            bytes: text.as_bytes(),
-            idx: 0,
-        }
-    }
-
-    fn next_token(&mut self) -> Option<FallbackToken> {
-        self.skip_trivia();
-        if self.idx >= self.bytes.len() {
-            return None;
-        }
-        let start = self.idx;
-        let b = self.bytes[self.idx];
-        if is_ident_byte(b) {
-            self.idx += 1;
-            while self.idx < self.bytes.len() && is_ident_byte(self.bytes[self.idx]) {
-                self.idx += 1;
-            }
-            let end = self.idx;
-            let ident = std::str::from_utf8(&self.bytes[start..end])
-                .ok()?
-                .to_string();
-            Some(FallbackToken {
-                kind: FallbackTokenKind::Ident(ident),
-                start,
-                end,
-            })
-        } else {
-            self.idx += 1;
-            Some(FallbackToken {
-                kind: FallbackTokenKind::Punct(b as char),
-                start,
-                end: self.idx,
-            })
-        }
-    }
-
-    fn skip_trivia(&mut self) {
-        while self.idx < self.bytes.len() {
-            let b = self.bytes[self.idx];
-            if b.is_ascii_whitespace() {
-                self.idx += 1;
-                continue;
-            }
-            if b == b'/' && self.idx + 1 < self.bytes.len() {
-                let next = self.bytes[self.idx + 1];
-                if next == b'/' {
-                    self.idx += 2;
-                    while self.idx < self.bytes.len() && self.bytes[self.idx] != b'\n' {
-                        self.idx += 1;
-                    }
-                    continue;
-                }
-                if next == b'*' {
-                    self.idx += 2;
-                    while self.idx + 1 < self.bytes.len() {
-                        if self.bytes[self.idx] == b'*' && self.bytes[self.idx + 1] == b'/' {
-                            self.idx += 2;
-                            break;
-                        }
-                        self.idx += 1;
-                    }
-                    continue;
-                }
-            }
-            if b == b'\'' || b == b'"' {
-                self.idx = skip_string(self.bytes, self.idx);
-                continue;
-            }
-            break;
+        };
+        let name = Name::new(name.text());
+        if seen.insert((name.clone(), kind)) {
+            defs.push(VisibleDefinition { name, kind });
         }
     }
+    defs
 }
 
 fn contract_member_definitions_with_inheritance(
@@ -1238,23 +1073,12 @@ fn contract_member_definitions_with_inheritance(
     let mut current_defs =
         program.contract_member_definitions_in_file(file_id, &contract_info.name);
     if current_defs.is_empty() {
-        let text = db.file_input(file_id).text(db);
-        current_defs = fallback_contract_member_definitions(text.as_ref(), &contract_info.name);
+        current_defs =
+            contract_member_definitions_from_cst(&syntax(db, file_id), &contract_info.name);
     }
     merge_visible_definitions(current_defs, &mut defs, &mut seen);
 
-    let mut visited = HashSet::new();
-    let mut pending = Vec::new();
-    for base_path in &contract_info.bases {
-        if let Some(base_id) = resolve_contract_path(program, file_id, base_path) {
-            pending.push(base_id);
-        }
-    }
-
-    while let Some(base_id) = pending.pop() {
-        if !visited.insert(base_id) {
-            continue;
-        }
+    for base_id in linearize::linearize_base_paths(db, program, file_id, &contract_info.bases) {
         let Some(entry) = program.def_map().entry(base_id) else {
             continue;
         };
@@ -1263,17 +1087,9 @@ fn contract_member_definitions_with_inheritance(
 
         let mut base_defs = program.contract_member_definitions_in_file(base_file_id, base_name);
         if base_defs.is_empty() {
-            let text = db.file_input(base_file_id).text(db);
-            base_defs = fallback_contract_member_definitions(text.as_ref(), base_name);
+            base_defs = contract_member_definitions_from_cst(&syntax(db, base_file_id), base_name);
         }
         merge_visible_definitions(base_defs, &mut defs, &mut seen);
-
-        let base_paths = contract_bases_in_file(db, base_file_id, base_name);
-        for base_path in base_paths {
-            if let Some(next_id) = resolve_contract_path(program, base_file_id, &base_path) {
-                pending.push(next_id);
-            }
-        }
     }
 
     defs
@@ -1282,10 +1098,10 @@ fn contract_member_definitions_with_inheritance(
 fn merge_visible_definitions(
     additions: Vec<VisibleDefinition>,
     defs: &mut Vec<VisibleDefinition>,
-    seen: &mut HashSet<(String, DefKind)>,
+    seen: &mut HashSet<(Name, DefKind)>,
 ) {
     for def in additions {
-        if seen.insert((def.name().to_string(), def.kind())) {
+        if seen.insert((def.name.clone(), def.kind)) {
             defs.push(def);
         }
     }
@@ -1305,67 +1121,16 @@ fn resolve_contract_path(program: &HirProgram, file_id: FileId, path: &[String])
     }
 }
 
-fn contract_bases_in_file(
-    db: &dyn HirDatabase,
-    file_id: FileId,
-    contract_name: &str,
-) -> Vec<Vec<String>> {
-    let text = db.file_input(file_id).text(db);
-    let parse = sa_syntax::parse_file(text.as_ref());
-    let bases = contract_bases_in_parse(&parse, contract_name);
-    if bases.is_empty() {
-        contract_bases_in_file_fallback(text.as_ref(), contract_name)
-    } else {
-        bases
-    }
-}
-
-fn contract_bases_in_file_fallback(text: &str, contract_name: &str) -> Vec<Vec<String>> {
-    contract_decl_by_name_fallback(text, contract_name)
-        .map(|decl| decl.bases)
-        .unwrap_or_default()
-}
-
-fn contract_bases_in_parse(parse: &Parse, contract_name: &str) -> Vec<Vec<String>> {
-    parse.with_session(|| {
-        for item in parse.tree().items.iter() {
-            if let ItemKind::Contract(contract) = &item.kind
-                && contract.name.as_str() == contract_name
-            {
-                return contract_base_paths(contract);
-            }
-        }
-        Vec::new()
-    })
-}
-
-fn contract_base_paths(contract: &sa_syntax::ast::ItemContract<'_>) -> Vec<Vec<String>> {
-    contract
-        .bases
-        .iter()
-        .filter_map(|base| {
-            let segments: Vec<String> = base
-                .name
-                .segments()
-                .iter()
-                .map(|segment| segment.as_str().to_string())
-                .collect();
-            (!segments.is_empty()).then_some(segments)
-        })
-        .collect()
-}
-
 fn collect_imports(
     workspace: &FoundryWorkspace,
     remappings: &[Remapping],
+    resolver: Option<&FoundryResolver>,
     current_path: &NormalizedPath,
     parsed: &ParsedFile,
-    path_to_file_id: &HashMap<String, FileId>,
+    path_index: &PathIndex,
     text: &str,
 ) -> Vec<Import> {
-    let resolver = FoundryResolver::new(workspace, remappings).ok();
     let resolved_by_path = resolver
-        .as_ref()
         .and_then(|resolver| resolver.resolved_imports(current_path, text).ok())
         .map(|imports| {
             let mut resolved = HashMap::new();
@@ -1390,14 +1155,14 @@ fn collect_imports(
                         remappings,
                         current_path,
                         &path,
-                        resolver.as_ref(),
+                        resolver,
                     )
                 });
             let resolved =
                 resolved.or_else(|| resolve_relative_import_fallback(current_path, &path));
             let file_id = resolved
                 .as_ref()
-                .and_then(|resolved| path_to_file_id.get(resolved.as_str()).copied());
+                .and_then(|resolved| path_index.file_id(resolved.as_str()));
             Import {
                 path,
                 resolved_path: resolved,
@@ -1434,6 +1199,10 @@ impl<'db> Semantics<'db> {
     }
 
     pub fn resolve_local(&self, file_id: FileId, offset: TextSize) -> Option<LocalDef> {
+        let yul = yul_hir(self.db, file_id);
+        if yul.block_at(offset).is_some() {
+            return self.resolve_yul_local(&yul, file_id, offset);
+        }
         let text = self.db.file_input(file_id).text(self.db);
         let locator = IdentRangeCollector::new();
         let (qualifier, name) = locator.qualified_name_at_offset(text.as_ref(), offset)?;
@@ -1451,11 +1220,98 @@ impl<'db> Semantics<'db> {
         self.source_to_def(file_id, offset).map(Definition::Global)
     }
 
+    fn resolve_yul_local(
+        &self,
+        yul: &YulHir,
+        file_id: FileId,
+        offset: TextSize,
+    ) -> Option<LocalDef> {
+        let locals = local_scopes(self.db, file_id);
+        let find_def = |range: TextRange| {
+            locals
+                .defs()
+                .iter()
+                .find(|local| local.range() == range)
+                .cloned()
+        };
+        if let Some(def) = yul.def_at(offset) {
+            return find_def(def.range());
+        }
+        let reference = yul.reference_at(offset)?;
+        match reference.resolution() {
+            YulResolution::Yul(range) => find_def(range),
+            YulResolution::External => locals
+                .resolve(reference.name(), reference.range().start())
+                .filter(|local| !local.kind().is_yul()),
+            YulResolution::Builtin | YulResolution::Unresolved => None,
+        }
+    }
+
+    /// Links a Yul name that is not declared in the assembly block to a state variable or
+    /// constant, looking through the bases of the enclosing contract.
+    fn resolve_yul_global(&self, yul: &YulHir, file_id: FileId, offset: TextSize) -> Option<DefId> {
+        let reference = yul.reference_at(offset)?;
+        if reference.resolution() != YulResolution::External {
+            return None;
+        }
+        let name = reference.name();
+        let shadowed = local_scopes(self.db, file_id)
+            .resolve(name, reference.range().start())
+            .is_some_and(|local| !local.kind().is_yul());
+        if shadowed {
+            return None;
+        }
+        let program = lowered_program(self.db, self.project_id);
+        let text = self.db.file_input(file_id).text(self.db);
+        let parse = sa_syntax::parse_file(text.as_ref());
+        if let Some(info) = contract_info_at_offset(self.db, file_id, &parse, offset)
+            && let Some(contract) = program
+                .def_map()
+                .entries_by_name_in_file(file_id, &info.name)
+                .into_iter()
+                .find(|entry| entry.kind() == DefKind::Contract)
+        {
+            for base in linearized_bases(self.db, &program, contract.id()).bases() {
+                let Some(base) = program.def_map().entry(*base) else {
+                    continue;
+                };
+                let found = program
+                    .def_map()
+                    .entries_by_name_in_file(base.location().file_id(), name)
+                    .into_iter()
+                    .find(|entry| {
+                        entry.kind() == DefKind::Variable
+                            && entry.container() == Some(base.location().name())
+                    });
+                if let Some(entry) = found {
+                    return Some(entry.id());
+                }
+            }
+        }
+        program.resolve_symbol(file_id, name).filter(|def_id| {
+            program
+                .def_map()
+                .entry(*def_id)
+                .is_some_and(|entry| entry.kind() == DefKind::Variable)
+        })
+    }
+
     pub fn source_to_def_location(
         &self,
         file_id: FileId,
         offset: TextSize,
     ) -> Option<DefinitionLocation> {
+        let yul = yul_hir(self.db, file_id);
+        if yul.block_at(offset).is_some() {
+            let def_id = self.resolve_yul_global(&yul, file_id, offset)?;
+            let program = lowered_program(self.db, self.project_id);
+            let entry = program.def_map().entry(def_id)?;
+            return Some(DefinitionLocation {
+                file_id: entry.location().file_id(),
+                range: entry.location().range(),
+                origin_range: None,
+            });
+        }
         if let Some(outcome) = self.sema_resolution(file_id, offset) {
             return match outcome {
                 ResolveOutcome::Resolved(symbol) => Some(DefinitionLocation {
@@ -1463,7 +1319,24 @@ impl<'db> Semantics<'db> {
                     range: symbol.definition_range,
                     origin_range: Some(symbol.origin_range),
                 }),
-                ResolveOutcome::Unresolved { .. } => None,
+                ResolveOutcome::Unresolved { origin_range } => {
+                    if let Some(function) = self.bound_function_at(file_id, offset) {
+                        return Some(DefinitionLocation {
+                            file_id: function.file_id,
+                            range: function.range,
+                            origin_range: Some(origin_range),
+                        });
+                    }
+                    let program = lowered_program(self.db, self.project_id);
+                    let entry = program
+                        .def_map()
+                        .entry(self.resolve_member(file_id, offset)?)?;
+                    Some(DefinitionLocation {
+                        file_id: entry.location().file_id(),
+                        range: entry.location().range(),
+                        origin_range: Some(origin_range),
+                    })
+                }
                 ResolveOutcome::Unavailable => None,
             };
         }
@@ -1479,22 +1352,86 @@ impl<'db> Semantics<'db> {
     }
 
     pub fn source_to_def(&self, file_id: FileId, offset: TextSize) -> Option<DefId> {
+        let yul = yul_hir(self.db, file_id);
+        if yul.block_at(offset).is_some() {
+            return self.resolve_yul_global(&yul, file_id, offset);
+        }
         if let Some(outcome) = self.sema_resolution(file_id, offset) {
             return match outcome {
                 ResolveOutcome::Resolved(symbol) => {
                     let program = lowered_program(self.db, self.project_id);
                     def_id_from_symbol(&program, &symbol)
                 }
-                ResolveOutcome::Unresolved { .. } => None,
+                ResolveOutcome::Unresolved { .. } => self
+                    .resolve_bound_function(file_id, offset)
+                    .or_else(|| self.resolve_member(file_id, offset)),
                 ResolveOutcome::Unavailable => None,
             };
         }
         self.source_to_def_fallback(file_id, offset)
     }
 
+    /// For `receiver.name`, the function attached to the receiver's type under that name by a
+    /// `using ... for` directive.
+    pub fn bound_function_at(&self, file_id: FileId, offset: TextSize) -> Option<BoundFunction> {
+        let text = self.db.file_input(file_id).text(self.db);
+        let locator = IdentRangeCollector::new();
+        let (qualifier, name) = locator.qualified_name_at_offset(text.as_ref(), offset)?;
+        let qualifier = qualifier?;
+        // The innermost expression at the last segment of `a.b` is `a.b` itself.
+        let last_segment = qualifier.name.rfind('.').map_or(0, |idx| idx + 1);
+        let receiver_offset = qualifier.start + TextSize::from(last_segment as u32);
+        let project = self.db.project_input(self.project_id);
+        let snapshot = sa_sema::sema_snapshot_for_file(self.db, project, file_id);
+        snapshot
+            .for_file(file_id)?
+            .bound_functions_at(file_id, receiver_offset)?
+            .into_iter()
+            .find(|function| function.name == name)
+    }
+
+    fn resolve_bound_function(&self, file_id: FileId, offset: TextSize) -> Option<DefId> {
+        let function = self.bound_function_at(file_id, offset)?;
+        let program = lowered_program(self.db, self.project_id);
+        program
+            .def_map()
+            .entries_by_name_in_file(function.file_id, &function.name)
+            .into_iter()
+            .find(|entry| {
+                entry.kind() == DefKind::Function && entry.location().range() == function.range
+            })
+            .map(|entry| entry.id())
+    }
+
+    /// Resolves the member accessed at `offset` (`balanceOf` in `token.balanceOf(owner)`) by
+    /// typing its receiver, which may itself be a member access, call or index expression.
+    pub fn resolve_member(&self, file_id: FileId, offset: TextSize) -> Option<DefId> {
+        member::resolve_member(self.db, self.project_id, file_id, offset)
+    }
+
+    /// Resolves the innermost call containing `offset` to the overload it binds to.
+    pub fn resolve_call(&self, file_id: FileId, offset: TextSize) -> Option<ResolvedCall> {
+        let project = self.db.project_input(self.project_id);
+        let snapshot = sa_sema::sema_snapshot_for_file(self.db, project, file_id);
+        let resolution = snapshot.for_file(file_id)?.resolve_call(file_id, offset)?;
+        let program = lowered_program(self.db, self.project_id);
+        match resolution {
+            CallResolution::Resolved(symbol) => {
+                def_id_from_symbol(&program, &symbol).map(ResolvedCall::Resolved)
+            }
+            CallResolution::Ambiguous(symbols) => {
+                let def_ids = symbols
+                    .iter()
+                    .filter_map(|symbol| def_id_from_symbol(&program, symbol))
+                    .collect::<Vec<_>>();
+                (!def_ids.is_empty()).then_some(ResolvedCall::Ambiguous(def_ids))
+            }
+        }
+    }
+
     fn sema_resolution(&self, file_id: FileId, offset: TextSize) -> Option<ResolveOutcome> {
         let project = self.db.project_input(self.project_id);
-        let snapshot = sa_sema::sema_snapshot_for_project(self.db, project);
+        let snapshot = sa_sema::sema_snapshot_for_file(self.db, project, file_id);
         let snapshot = snapshot.for_file(file_id)?;
         match snapshot.resolve_definition(file_id, offset) {
             ResolveOutcome::Unavailable => None,
@@ -1506,6 +1443,11 @@ impl<'db> Semantics<'db> {
         let text = self.db.file_input(file_id).text(self.db);
         let locator = IdentRangeCollector::new();
         let (qualifier, name) = locator.qualified_name_at_offset(text.as_ref(), offset)?;
+        if qualifier.is_some()
+            && let Some(def_id) = self.resolve_member(file_id, offset)
+        {
+            return Some(def_id);
+        }
         let program = lowered_program(self.db, self.project_id);
         match qualifier {
             Some(qualifier) => {
@@ -1560,7 +1502,11 @@ fn def_id_from_symbol(program: &HirProgram, symbol: &ResolvedSymbol) -> Option<D
         .def_map()
         .entries_by_name_in_file(symbol.definition_file_id, &symbol.name)
         .into_iter()
-        .filter(|entry| entry.kind() == kind && entry.container() == symbol.container.as_deref())
+        .filter(|entry| {
+            // Solar lowers struct fields to variables.
+            (kind == DefKind::Variable && entry.kind() == DefKind::Field)
+                || (entry.kind() == kind && entry.container() == symbol.container.as_deref())
+        })
     {
         if entry.location().range() == symbol.definition_range {
             return Some(entry.id());
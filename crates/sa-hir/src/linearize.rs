@@ -0,0 +1,225 @@
+//! C3 linearization of contract inheritance, computed over the DefMap the same way solc's
+//! `linearizeBaseContracts` does: `L(C) = C + merge(L(Bn), ..., L(B1), [Bn, ..., B1])` for
+//! `contract C is B1, ..., Bn`.
+
+use std::collections::{HashMap, VecDeque};
+
+use sa_base_db::{FileId, ProjectId};
+use sa_def::{DefId, DefKind};
+use sa_span::TextRange;
+
+use crate::inheritance::direct_bases;
+use crate::{HirDatabase, HirProgram, lowered_program, resolve_contract_path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linearization {
+    bases: Vec<DefId>,
+    error: Option<LinearizationError>,
+}
+
+impl Linearization {
+    /// The contract followed by its bases, most derived first. When linearization fails the
+    /// order is completed best-effort, so every base is still listed exactly once.
+    pub fn bases(&self) -> &[DefId] {
+        &self.bases
+    }
+
+    pub fn error(&self) -> Option<&LinearizationError> {
+        self.error.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinearizationErrorKind {
+    /// No order satisfies the declared base order of every contract in the hierarchy.
+    Impossible,
+    /// The contract inherits from itself, directly or through its bases.
+    Cyclic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearizationError {
+    contract: DefId,
+    file_id: FileId,
+    range: TextRange,
+    kind: LinearizationErrorKind,
+}
+
+impl LinearizationError {
+    pub fn contract(&self) -> DefId {
+        self.contract
+    }
+
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// The range of the contract's name.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn kind(&self) -> LinearizationErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self.kind {
+            LinearizationErrorKind::Impossible => "linearization of inheritance graph impossible",
+            LinearizationErrorKind::Cyclic => "contract inherits from itself",
+        }
+    }
+}
+
+pub fn linearized_bases(
+    db: &dyn HirDatabase,
+    program: &HirProgram,
+    contract: DefId,
+) -> Linearization {
+    let mut linearizer = Linearizer::new(db, program);
+    let bases = linearizer.linearize(contract);
+    let error = linearizer
+        .errors
+        .into_iter()
+        .find(|error| error.contract == contract);
+    Linearization { bases, error }
+}
+
+/// Linearization errors of the contracts declared in `file_id`.
+pub fn linearization_errors(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+) -> Vec<LinearizationError> {
+    let program = lowered_program(db, project_id);
+    let mut linearizer = Linearizer::new(db, &program);
+    for entry in program.def_map().entries() {
+        if entry.kind() == DefKind::Contract && entry.location().file_id() == file_id {
+            linearizer.linearize(entry.id());
+        }
+    }
+    let mut errors = linearizer
+        .errors
+        .into_iter()
+        .filter(|error| error.file_id == file_id)
+        .collect::<Vec<_>>();
+    errors.sort_by_key(|error| error.range.start());
+    errors
+}
+
+/// Orders the bases of a contract that may not be in the DefMap yet (e.g. the one being
+/// edited), given the paths of its direct bases as written in `file_id`.
+pub(crate) fn linearize_base_paths(
+    db: &dyn HirDatabase,
+    program: &HirProgram,
+    file_id: FileId,
+    base_paths: &[Vec<String>],
+) -> Vec<DefId> {
+    let bases = base_paths
+        .iter()
+        .filter_map(|path| resolve_contract_path(program, file_id, path))
+        .collect::<Vec<_>>();
+    Linearizer::new(db, program).merge_bases(&bases).0
+}
+
+struct Linearizer<'a> {
+    db: &'a dyn HirDatabase,
+    program: &'a HirProgram,
+    memo: HashMap<DefId, Vec<DefId>>,
+    in_progress: Vec<DefId>,
+    errors: Vec<LinearizationError>,
+}
+
+impl<'a> Linearizer<'a> {
+    fn new(db: &'a dyn HirDatabase, program: &'a HirProgram) -> Self {
+        Self {
+            db,
+            program,
+            memo: HashMap::new(),
+            in_progress: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn linearize(&mut self, contract: DefId) -> Vec<DefId> {
+        if let Some(bases) = self.memo.get(&contract) {
+            return bases.clone();
+        }
+        if self.in_progress.contains(&contract) {
+            self.record_error(contract, LinearizationErrorKind::Cyclic);
+            return vec![contract];
+        }
+        if self.program.def_map().entry(contract).is_none() {
+            return vec![contract];
+        }
+        let bases = direct_bases(self.db, self.program, contract);
+
+        self.in_progress.push(contract);
+        let (merged, consistent) = self.merge_bases(&bases);
+        self.in_progress.pop();
+        if !consistent {
+            self.record_error(contract, LinearizationErrorKind::Impossible);
+        }
+
+        let mut result = Vec::with_capacity(merged.len() + 1);
+        result.push(contract);
+        result.extend(merged.into_iter().filter(|base| *base != contract));
+        self.memo.insert(contract, result.clone());
+        result
+    }
+
+    fn merge_bases(&mut self, bases: &[DefId]) -> (Vec<DefId>, bool) {
+        let mut lists = bases
+            .iter()
+            .rev()
+            .map(|base| VecDeque::from(self.linearize(*base)))
+            .collect::<Vec<_>>();
+        lists.push(bases.iter().rev().copied().collect());
+        c3_merge(lists)
+    }
+
+    fn record_error(&mut self, contract: DefId, kind: LinearizationErrorKind) {
+        if self.errors.iter().any(|error| error.contract == contract) {
+            return;
+        }
+        let Some(entry) = self.program.def_map().entry(contract) else {
+            return;
+        };
+        self.errors.push(LinearizationError {
+            contract,
+            file_id: entry.location().file_id(),
+            range: entry.location().range(),
+            kind,
+        });
+    }
+}
+
+/// Returns the merged order and whether it is consistent. When no head is free of the other
+/// lists' tails, the head of the first list is taken anyway so the order stays usable.
+fn c3_merge(mut lists: Vec<VecDeque<DefId>>) -> (Vec<DefId>, bool) {
+    let mut result = Vec::new();
+    let mut consistent = true;
+    loop {
+        lists.retain(|list| !list.is_empty());
+        let Some(first) = lists.first() else {
+            break;
+        };
+        let candidate = lists.iter().filter_map(|list| list.front()).find(|head| {
+            lists
+                .iter()
+                .all(|list| !list.iter().skip(1).any(|item| item == *head))
+        });
+        let next = match candidate {
+            Some(head) => *head,
+            None => {
+                consistent = false;
+                first[0]
+            }
+        };
+        result.push(next);
+        for list in &mut lists {
+            list.retain(|item| *item != next);
+        }
+    }
+    (result, consistent)
+}
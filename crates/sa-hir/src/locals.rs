@@ -1,18 +1,27 @@
 use sa_base_db::FileInput;
 use sa_span::{TextRange, TextSize, range_contains};
-use sa_syntax::Parse;
-use sa_syntax::ast::{
-    Block, CallArgs, Expr, ExprKind, IndexKind, Item, ItemFunction, ItemKind, Stmt, StmtKind,
-    StmtTry, TryCatchClause, VariableDefinition, interface::SpannedOption,
-};
 
 use crate::HirDatabase;
+use crate::body::{
+    BindingId, BindingKind, Body, BodySourceMap, Expr, Stmt, StmtId, file_bodies,
+    file_bodies_for_file,
+};
+use crate::yul::{YulDefKind, YulResolution, yul_hir, yul_hir_for_file};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LocalDefKind {
     Parameter,
     NamedReturn,
     Local,
+    /// A variable, parameter or return variable declared inside `assembly`.
+    YulVariable,
+    YulFunction,
+}
+
+impl LocalDefKind {
+    pub fn is_yul(self) -> bool {
+        matches!(self, LocalDefKind::YulVariable | LocalDefKind::YulFunction)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -41,7 +50,7 @@ impl LocalDef {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct LocalScopes {
     defs: Vec<LocalDef>,
 }
@@ -78,9 +87,23 @@ unsafe impl salsa::Update for LocalScopes {
 
 #[salsa::tracked(returns(ref))]
 pub fn local_scopes_for_file(db: &dyn HirDatabase, file: FileInput) -> LocalScopes {
-    let text = file.text(db);
-    let parse = sa_syntax::parse_file(text.as_ref());
-    LocalScopeCollector::new(&parse).collect()
+    let mut scopes = LocalScopes::default();
+    for function in file_bodies_for_file(db, file).functions() {
+        LocalScopeCollector::new(function.body(), function.source_map(), &mut scopes.defs)
+            .collect();
+    }
+    for block in yul_hir_for_file(db, file).blocks() {
+        scopes.defs.extend(block.defs().iter().map(|def| LocalDef {
+            name: def.name().to_string(),
+            kind: match def.kind() {
+                YulDefKind::Function => LocalDefKind::YulFunction,
+                _ => LocalDefKind::YulVariable,
+            },
+            range: def.range(),
+            scope: def.scope(),
+        }));
+    }
+    scopes
 }
 
 pub fn local_scopes(db: &dyn HirDatabase, file_id: sa_base_db::FileId) -> LocalScopes {
@@ -92,225 +115,165 @@ pub fn local_references(
     file_id: sa_base_db::FileId,
     local: &LocalDef,
 ) -> Vec<TextRange> {
-    let text = db.file_input(file_id).text(db);
-    let parse = sa_syntax::parse_file(text.as_ref());
     let locals = local_scopes(db, file_id);
-    let mut collector = LocalReferenceCollector::new(&parse, &locals, local);
-    collector.collect();
-    collector.ranges.push(local.range());
-    collector
-        .ranges
-        .sort_by_key(|range| (u32::from(range.start()), u32::from(range.end())));
-    collector.ranges.dedup();
-    collector.ranges
+    let mut ranges = Vec::new();
+    for function in file_bodies(db, file_id).functions() {
+        for (expr_id, expr) in function.body().exprs() {
+            let Expr::Ident(name) = expr else {
+                continue;
+            };
+            let range = function.source_map().expr_range(expr_id);
+            if locals
+                .resolve(name, range.start())
+                .is_some_and(|resolved| resolved.range() == local.range())
+            {
+                ranges.push(range);
+            }
+        }
+    }
+    for reference in yul_hir(db, file_id)
+        .blocks()
+        .iter()
+        .flat_map(|block| block.references())
+    {
+        let matches = match reference.resolution() {
+            YulResolution::Yul(range) => range == local.range(),
+            YulResolution::External => locals
+                .resolve(reference.name(), reference.range().start())
+                .is_some_and(|resolved| resolved.range() == local.range()),
+            YulResolution::Builtin | YulResolution::Unresolved => false,
+        };
+        if matches {
+            ranges.push(reference.range());
+        }
+    }
+    ranges.push(local.range());
+    ranges.sort_by_key(|range| (u32::from(range.start()), u32::from(range.end())));
+    ranges.dedup();
+    ranges
 }
 
 struct LocalScopeCollector<'a> {
-    parse: &'a Parse,
-    defs: Vec<LocalDef>,
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    defs: &'a mut Vec<LocalDef>,
     scopes: Vec<TextRange>,
 }
 
 impl<'a> LocalScopeCollector<'a> {
-    fn new(parse: &'a Parse) -> Self {
+    fn new(body: &'a Body, source_map: &'a BodySourceMap, defs: &'a mut Vec<LocalDef>) -> Self {
         Self {
-            parse,
-            defs: Vec::new(),
+            body,
+            source_map,
+            defs,
             scopes: Vec::new(),
         }
     }
 
-    fn collect(mut self) -> LocalScopes {
-        for item in self.parse.tree().items.iter() {
-            self.collect_item(item);
-        }
-        LocalScopes { defs: self.defs }
-    }
-
-    fn collect_item(&mut self, item: &Item<'_>) {
-        match &item.kind {
-            ItemKind::Contract(contract) => {
-                for item in contract.body.iter() {
-                    self.collect_item(item);
-                }
-            }
-            ItemKind::Function(function) => self.collect_function(function),
-            _ => {}
-        }
-    }
-
-    fn collect_function(&mut self, function: &ItemFunction<'_>) {
-        let Some(body) = function.body.as_ref() else {
-            return;
-        };
-        let Some(body_range) = self.parse.span_to_text_range(body.span) else {
+    fn collect(mut self) {
+        let Some(block) = self.body.block() else {
             return;
         };
-        let header_range = self
-            .parse
-            .span_to_text_range(function.header.span)
-            .unwrap_or(body_range);
-        let params_range = self
-            .parse
-            .span_to_text_range(function.header.parameters.span);
-        let returns_range = function
-            .header
-            .returns
-            .as_ref()
-            .and_then(|returns| self.parse.span_to_text_range(returns.span));
-        let header_param_scope = match (params_range, returns_range) {
-            (Some(params), Some(returns)) if params.end() <= returns.start() => {
-                Some(TextRange::new(params.end(), returns.start()))
-            }
-            (Some(params), None) if params.end() <= header_range.end() => {
-                Some(TextRange::new(params.end(), header_range.end()))
-            }
-            _ => None,
-        };
-
-        if let Some(scope) = header_param_scope.filter(|range| !range.is_empty()) {
+        if let Some(scope) = self.source_map.header_scope() {
             self.push_scope(scope);
-            for param in function.header.parameters.vars.iter() {
-                self.add_param(param, LocalDefKind::Parameter);
+            for &param in self.body.params() {
+                self.add_binding(param);
             }
             self.pop_scope();
         }
 
-        self.push_scope(body_range);
-        for param in function.header.parameters.vars.iter() {
-            self.add_param(param, LocalDefKind::Parameter);
+        self.push_scope(self.source_map.stmt_range(block));
+        for &param in self.body.params() {
+            self.add_binding(param);
         }
-        if let Some(returns) = function.header.returns.as_ref() {
-            for param in returns.vars.iter() {
-                self.add_param(param, LocalDefKind::NamedReturn);
-            }
+        for &ret in self.body.returns() {
+            self.add_binding(ret);
         }
-        self.collect_block_stmts(body);
+        self.collect_block_stmts(block);
         self.pop_scope();
     }
 
-    fn add_param(&mut self, param: &VariableDefinition<'_>, kind: LocalDefKind) {
-        let Some(name) = param.name else {
-            return;
-        };
-        let Some(range) = self.parse.span_to_text_range(name.span) else {
-            return;
-        };
-        let Some(scope) = self.scopes.last().copied() else {
-            return;
-        };
-        let name = self.parse.with_session(|| name.to_string());
-        self.defs.push(LocalDef {
-            name,
-            kind,
-            range,
-            scope,
-        });
-    }
-
-    fn collect_block_stmts(&mut self, block: &Block<'_>) {
-        for stmt in block.stmts.iter() {
-            self.collect_stmt(stmt);
+    fn collect_block_stmts(&mut self, block: StmtId) {
+        let body = self.body;
+        if let Stmt::Block(stmts) | Stmt::Unchecked(stmts) = body.stmt(block) {
+            for &stmt in stmts {
+                self.collect_stmt(stmt);
+            }
         }
     }
 
-    fn collect_stmt(&mut self, stmt: &Stmt<'_>) {
-        match &stmt.kind {
-            StmtKind::DeclSingle(var) => {
-                self.add_local(var);
-            }
-            StmtKind::DeclMulti(vars, _) => {
-                for var in vars.iter() {
-                    if let SpannedOption::Some(var) = var {
-                        self.add_local(var);
-                    }
+    fn collect_stmt(&mut self, stmt: StmtId) {
+        let body = self.body;
+        match body.stmt(stmt) {
+            Stmt::Let { bindings, .. } => {
+                for &binding in bindings.iter().flatten() {
+                    self.add_binding(binding);
                 }
             }
-            StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => {
-                self.collect_block(block);
+            Stmt::Block(_) | Stmt::Unchecked(_) => {
+                self.collect_stmt_with_scope(stmt, |this| this.collect_block_stmts(stmt));
             }
-            StmtKind::For { init, body, .. } => {
-                self.collect_for(stmt, init.as_deref(), body);
+            Stmt::For {
+                init, body: inner, ..
+            } => {
+                let (init, inner) = (*init, *inner);
+                self.collect_stmt_with_scope(stmt, |this| {
+                    if let Some(init) = init {
+                        this.collect_stmt(init);
+                    }
+                    this.collect_stmt(inner);
+                });
             }
-            StmtKind::If(_, then_branch, else_branch) => {
-                self.collect_stmt_with_scope(then_branch);
-                if let Some(else_branch) = else_branch.as_deref() {
-                    self.collect_stmt_with_scope(else_branch);
+            Stmt::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let (then_branch, else_branch) = (*then_branch, *else_branch);
+                self.collect_stmt_with_scope(then_branch, |this| this.collect_stmt(then_branch));
+                if let Some(else_branch) = else_branch {
+                    self.collect_stmt_with_scope(else_branch, |this| {
+                        this.collect_stmt(else_branch)
+                    });
                 }
             }
-            StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => {
-                self.collect_stmt_with_scope(body);
-            }
-            StmtKind::Try(stmt_try) => {
-                self.collect_try(stmt_try);
+            Stmt::While { body: inner, .. } | Stmt::DoWhile { body: inner, .. } => {
+                let inner = *inner;
+                self.collect_stmt_with_scope(inner, |this| this.collect_stmt(inner));
+            }
+            Stmt::Try { clauses, .. } => {
+                for clause in clauses {
+                    self.collect_stmt_with_scope(clause.block, |this| {
+                        for &param in &clause.params {
+                            this.add_binding(param);
+                        }
+                        this.collect_block_stmts(clause.block);
+                    });
+                }
             }
             _ => {}
         }
     }
 
-    fn collect_block(&mut self, block: &Block<'_>) {
-        let Some(scope_range) = self.parse.span_to_text_range(block.span) else {
-            return;
-        };
-        self.push_scope(scope_range);
-        self.collect_block_stmts(block);
-        self.pop_scope();
-    }
-
-    fn collect_for(&mut self, stmt: &Stmt<'_>, init: Option<&Stmt<'_>>, body: &Stmt<'_>) {
-        let Some(scope_range) = self.parse.span_to_text_range(stmt.span) else {
-            return;
-        };
-        self.push_scope(scope_range);
-        if let Some(init) = init {
-            self.collect_stmt(init);
-        }
-        self.collect_stmt(body);
-        self.pop_scope();
-    }
-
-    fn collect_try(&mut self, stmt_try: &StmtTry<'_>) {
-        for clause in stmt_try.clauses.iter() {
-            self.collect_try_clause(clause);
-        }
-    }
-
-    fn collect_try_clause(&mut self, clause: &TryCatchClause<'_>) {
-        let Some(scope_range) = self.parse.span_to_text_range(clause.block.span) else {
-            return;
-        };
-        self.push_scope(scope_range);
-        for param in clause.args.vars.iter() {
-            self.add_param(param, LocalDefKind::Parameter);
-        }
-        self.collect_block_stmts(&clause.block);
-        self.pop_scope();
-    }
-
-    fn collect_stmt_with_scope(&mut self, stmt: &Stmt<'_>) {
-        let Some(scope_range) = self.parse.span_to_text_range(stmt.span) else {
-            return;
-        };
-        self.push_scope(scope_range);
-        self.collect_stmt(stmt);
+    fn collect_stmt_with_scope(&mut self, stmt: StmtId, f: impl FnOnce(&mut Self)) {
+        self.push_scope(self.source_map.stmt_range(stmt));
+        f(self);
         self.pop_scope();
     }
 
-    fn add_local(&mut self, var: &VariableDefinition<'_>) {
-        let Some(name) = var.name else {
-            return;
-        };
-        let Some(range) = self.parse.span_to_text_range(name.span) else {
-            return;
-        };
+    fn add_binding(&mut self, binding: BindingId) {
         let Some(scope) = self.scopes.last().copied() else {
             return;
         };
-        let name = self.parse.with_session(|| name.to_string());
+        let binding = self.body.binding(binding);
         self.defs.push(LocalDef {
-            name,
-            kind: LocalDefKind::Local,
-            range,
+            name: binding.name.clone(),
+            kind: match binding.kind {
+                BindingKind::Parameter => LocalDefKind::Parameter,
+                BindingKind::NamedReturn => LocalDefKind::NamedReturn,
+                BindingKind::Local => LocalDefKind::Local,
+            },
+            range: binding.range,
             scope,
         });
     }
@@ -323,211 +286,3 @@ impl<'a> LocalScopeCollector<'a> {
         self.scopes.pop();
     }
 }
-
-struct LocalReferenceCollector<'a> {
-    parse: &'a Parse,
-    locals: &'a LocalScopes,
-    target: &'a LocalDef,
-    ranges: Vec<TextRange>,
-}
-
-impl<'a> LocalReferenceCollector<'a> {
-    fn new(parse: &'a Parse, locals: &'a LocalScopes, target: &'a LocalDef) -> Self {
-        Self {
-            parse,
-            locals,
-            target,
-            ranges: Vec::new(),
-        }
-    }
-
-    fn collect(&mut self) {
-        for item in self.parse.tree().items.iter() {
-            self.collect_item(item);
-        }
-    }
-
-    fn collect_item(&mut self, item: &Item<'_>) {
-        match &item.kind {
-            ItemKind::Contract(contract) => {
-                for item in contract.body.iter() {
-                    self.collect_item(item);
-                }
-            }
-            ItemKind::Function(function) => {
-                for modifier in function.header.modifiers.iter() {
-                    self.collect_call_args(&modifier.arguments);
-                }
-                if let Some(body) = function.body.as_ref() {
-                    self.collect_block(body);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    fn collect_block(&mut self, block: &Block<'_>) {
-        for stmt in block.stmts.iter() {
-            self.collect_stmt(stmt);
-        }
-    }
-
-    fn collect_stmt(&mut self, stmt: &Stmt<'_>) {
-        match &stmt.kind {
-            StmtKind::DeclSingle(var) => {
-                if let Some(expr) = var.initializer.as_deref() {
-                    self.collect_expr(expr);
-                }
-            }
-            StmtKind::DeclMulti(_, expr) => {
-                self.collect_expr(expr);
-            }
-            StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => {
-                self.collect_block(block);
-            }
-            StmtKind::For {
-                init,
-                cond,
-                next,
-                body,
-            } => {
-                if let Some(init) = init.as_deref() {
-                    self.collect_stmt(init);
-                }
-                if let Some(cond) = cond.as_deref() {
-                    self.collect_expr(cond);
-                }
-                if let Some(next) = next.as_deref() {
-                    self.collect_expr(next);
-                }
-                self.collect_stmt(body);
-            }
-            StmtKind::If(cond, then_branch, else_branch) => {
-                self.collect_expr(cond);
-                self.collect_stmt(then_branch);
-                if let Some(else_branch) = else_branch.as_deref() {
-                    self.collect_stmt(else_branch);
-                }
-            }
-            StmtKind::While(cond, body) => {
-                self.collect_expr(cond);
-                self.collect_stmt(body);
-            }
-            StmtKind::DoWhile(body, cond) => {
-                self.collect_stmt(body);
-                self.collect_expr(cond);
-            }
-            StmtKind::Try(stmt_try) => {
-                self.collect_expr(stmt_try.expr.as_ref());
-                for clause in stmt_try.clauses.iter() {
-                    self.collect_block(&clause.block);
-                }
-            }
-            StmtKind::Emit(_, args) | StmtKind::Revert(_, args) => {
-                self.collect_call_args(args);
-            }
-            StmtKind::Return(expr) => {
-                if let Some(expr) = expr.as_deref() {
-                    self.collect_expr(expr);
-                }
-            }
-            StmtKind::Expr(expr) => {
-                self.collect_expr(expr);
-            }
-            _ => {}
-        }
-    }
-
-    fn collect_expr(&mut self, expr: &Expr<'_>) {
-        match &expr.kind {
-            ExprKind::Ident(ident) => {
-                self.record_ident(*ident);
-            }
-            ExprKind::Array(items) => {
-                for item in items.iter() {
-                    self.collect_expr(item);
-                }
-            }
-            ExprKind::Assign(lhs, _, rhs) | ExprKind::Binary(lhs, _, rhs) => {
-                self.collect_expr(lhs);
-                self.collect_expr(rhs);
-            }
-            ExprKind::Call(callee, args) => {
-                self.collect_expr(callee);
-                self.collect_call_args(args);
-            }
-            ExprKind::CallOptions(callee, args) => {
-                self.collect_expr(callee);
-                for arg in args.iter() {
-                    self.collect_expr(arg.value.as_ref());
-                }
-            }
-            ExprKind::Delete(expr) => {
-                self.collect_expr(expr);
-            }
-            ExprKind::Index(expr, index) => {
-                self.collect_expr(expr);
-                self.collect_index(index);
-            }
-            ExprKind::Member(expr, _) => {
-                self.collect_expr(expr);
-            }
-            ExprKind::Payable(args) => {
-                self.collect_call_args(args);
-            }
-            ExprKind::Ternary(cond, then_expr, else_expr) => {
-                self.collect_expr(cond);
-                self.collect_expr(then_expr);
-                self.collect_expr(else_expr);
-            }
-            ExprKind::Tuple(items) => {
-                for item in items.iter() {
-                    if let SpannedOption::Some(expr) = item {
-                        self.collect_expr(expr);
-                    }
-                }
-            }
-            ExprKind::Unary(_, expr) => {
-                self.collect_expr(expr);
-            }
-            ExprKind::Lit(_, _) | ExprKind::New(_) | ExprKind::Type(_) | ExprKind::TypeCall(_) => {}
-        }
-    }
-
-    fn collect_call_args(&mut self, args: &CallArgs<'_>) {
-        for expr in args.exprs() {
-            self.collect_expr(expr);
-        }
-    }
-
-    fn collect_index(&mut self, index: &IndexKind<'_>) {
-        match index {
-            IndexKind::Index(expr) => {
-                if let Some(expr) = expr.as_deref() {
-                    self.collect_expr(expr);
-                }
-            }
-            IndexKind::Range(start, end) => {
-                if let Some(expr) = start.as_deref() {
-                    self.collect_expr(expr);
-                }
-                if let Some(expr) = end.as_deref() {
-                    self.collect_expr(expr);
-                }
-            }
-        }
-    }
-
-    fn record_ident(&mut self, ident: sa_syntax::ast::interface::Ident) {
-        let Some(range) = self.parse.span_to_text_range(ident.span) else {
-            return;
-        };
-        let name = self.parse.with_session(|| ident.to_string());
-        let Some(resolved) = self.locals.resolve(&name, range.start()) else {
-            return;
-        };
-        if resolved.range() == self.target.range() {
-            self.ranges.push(range);
-        }
-    }
-}
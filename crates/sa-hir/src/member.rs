@@ -0,0 +1,402 @@
+//! Resolution of member accesses (`token.balanceOf`, `a.b().c[i].d`) from the lowered bodies.
+//! The receiver is typed from the declaration it names: a local, a state variable, a call
+//! result, `this` or `super`. The member is then looked up in the receiver's contract and its
+//! bases, or in its struct.
+
+use std::collections::HashMap;
+
+use sa_base_db::{FileId, FileInput, ProjectId};
+use sa_def::{DefId, DefKind};
+use sa_span::{TextRange, TextSize, range_contains};
+use sa_syntax::Parse;
+use sa_syntax::ast::{ItemKind, interface::Span};
+
+use crate::body::{Expr, ExprId, FunctionBody, file_bodies_for_file};
+use crate::{HirDatabase, HirProgram, linearized_bases, local_scopes, lowered_program};
+
+/// The declared types of the state variables, function returns and struct fields of a file,
+/// keyed by the range of the declaration's name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct DeclTypes {
+    types: HashMap<TextRange, Vec<String>>,
+}
+
+impl DeclTypes {
+    fn types(&self, name_range: TextRange) -> &[String] {
+        self.types.get(&name_range).map_or(&[], Vec::as_slice)
+    }
+}
+
+unsafe impl salsa::Update for DeclTypes {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub(crate) fn file_decl_types(db: &dyn HirDatabase, file: FileInput) -> DeclTypes {
+    let text = file.text(db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    let mut types = DeclTypes::default();
+    for item in parse.tree().items.iter() {
+        match &item.kind {
+            ItemKind::Contract(contract) => {
+                for item in contract.body.iter() {
+                    collect_decl_types(&parse, text.as_ref(), &item.kind, &mut types);
+                }
+            }
+            kind => collect_decl_types(&parse, text.as_ref(), kind, &mut types),
+        }
+    }
+    types
+}
+
+fn collect_decl_types(parse: &Parse, text: &str, kind: &ItemKind<'_>, types: &mut DeclTypes) {
+    let source_text = |span: Span| {
+        parse
+            .span_to_text_range(span)
+            .and_then(|range| text.get(usize::from(range.start())..usize::from(range.end())))
+            .map(str::to_string)
+    };
+    match kind {
+        ItemKind::Variable(var) => {
+            if let Some(name) = var.name
+                && let Some(range) = parse.span_to_text_range(name.span)
+                && let Some(ty) = source_text(var.ty.span)
+            {
+                types.types.insert(range, vec![ty]);
+            }
+        }
+        ItemKind::Function(function) => {
+            if let Some(name) = function.header.name
+                && let Some(range) = parse.span_to_text_range(name.span)
+            {
+                let returns = function
+                    .header
+                    .returns
+                    .iter()
+                    .flat_map(|returns| returns.vars.iter())
+                    .filter_map(|var| source_text(var.ty.span))
+                    .collect();
+                types.types.insert(range, returns);
+            }
+        }
+        ItemKind::Struct(strukt) => {
+            for field in strukt.fields.iter() {
+                if let Some(name) = field.name
+                    && let Some(range) = parse.span_to_text_range(name.span)
+                    && let Some(ty) = source_text(field.ty.span)
+                {
+                    types.types.insert(range, vec![ty]);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The type of a receiver expression.
+#[derive(Debug, Clone)]
+enum Ty {
+    /// A type as written in `file_id`, e.g. `IERC20` or `mapping(address => Lib.Point)`.
+    Written { ty: String, file_id: FileId },
+    /// A contract, struct or enum.
+    Def(DefId),
+    /// `super` inside the contract.
+    Super(DefId),
+}
+
+/// The definition of the member accessed at `offset`, e.g. `balanceOf` in
+/// `token.balanceOf(owner)`.
+pub(crate) fn resolve_member(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+    offset: TextSize,
+) -> Option<DefId> {
+    let bodies = file_bodies_for_file(db, db.file_input(file_id));
+    let function = bodies.function_at(offset)?;
+    let expr = function.source_map().expr_at(offset)?;
+    let Expr::Member { base, .. } = function.body().expr(expr) else {
+        return None;
+    };
+    if range_contains(function.source_map().expr_range(*base), offset) {
+        return None;
+    }
+    let program = lowered_program(db, project_id);
+    let contract = function.container().and_then(|name| {
+        program
+            .def_map()
+            .entries_by_name_in_file(file_id, name)
+            .into_iter()
+            .find(|entry| entry.kind() == DefKind::Contract)
+            .map(|entry| entry.id())
+    });
+    MemberResolver {
+        db,
+        program: &program,
+        file_id,
+        function,
+        contract,
+    }
+    .resolve_expr(expr)
+}
+
+struct MemberResolver<'a> {
+    db: &'a dyn HirDatabase,
+    program: &'a HirProgram,
+    file_id: FileId,
+    function: &'a FunctionBody,
+    contract: Option<DefId>,
+}
+
+impl MemberResolver<'_> {
+    /// The definition an identifier or member expression names.
+    fn resolve_expr(&self, expr: ExprId) -> Option<DefId> {
+        match self.function.body().expr(expr) {
+            Expr::Ident(name) => {
+                if self.local_type(expr, name).is_some() {
+                    return None;
+                }
+                self.contract
+                    .and_then(|contract| self.lookup_in_bases(contract, name, false))
+                    .or_else(|| self.program.resolve_symbol(self.file_id, name))
+            }
+            Expr::Member { base, name } => {
+                if let Expr::Ident(qualifier) = self.function.body().expr(*base)
+                    && self.local_type(*base, qualifier).is_none()
+                    && let Some(def) =
+                        self.program
+                            .resolve_qualified_symbol(self.file_id, qualifier, name)
+                {
+                    return Some(def);
+                }
+                self.lookup_member(self.infer(*base)?, name)
+            }
+            _ => None,
+        }
+    }
+
+    fn infer(&self, expr: ExprId) -> Option<Ty> {
+        match self.function.body().expr(expr) {
+            Expr::Ident(name) => match name.as_str() {
+                "this" => self.contract.map(Ty::Def),
+                "super" => self.contract.map(Ty::Super),
+                _ => match self.local_type(expr, name) {
+                    Some(ty) => Some(Ty::Written {
+                        ty,
+                        file_id: self.file_id,
+                    }),
+                    None => self.def_type(self.resolve_expr(expr)?),
+                },
+            },
+            Expr::Member { .. } => self.def_type(self.resolve_expr(expr)?),
+            Expr::Call { callee, .. } => {
+                let mut callee = *callee;
+                while let Expr::CallOptions { callee: inner, .. } =
+                    self.function.body().expr(callee)
+                {
+                    callee = *inner;
+                }
+                match self.function.body().expr(callee) {
+                    Expr::New(text) => Some(Ty::Written {
+                        ty: text.trim_start_matches("new").trim().to_string(),
+                        file_id: self.file_id,
+                    }),
+                    Expr::Ident(_) | Expr::Member { .. } => {
+                        let def = self.resolve_expr(callee)?;
+                        match def {
+                            // A conversion (`IERC20(token)`) or a struct constructor.
+                            DefId::Contract(_) | DefId::Struct(_) => Some(Ty::Def(def)),
+                            DefId::Function(_) => {
+                                let entry = self.program.def_map().entry(def)?;
+                                let file_id = entry.location().file_id();
+                                let types = file_decl_types(self.db, self.db.file_input(file_id));
+                                let ty = types.types(entry.location().range()).first()?.clone();
+                                Some(Ty::Written { ty, file_id })
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Index { base, .. } => match self.infer(*base)? {
+                Ty::Written { ty, file_id } => Some(Ty::Written {
+                    ty: element_type(&ty)?,
+                    file_id,
+                }),
+                _ => None,
+            },
+            Expr::Tuple(items) => match items.as_slice() {
+                [Some(item)] => self.infer(*item),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Resolves a written type naming a contract or struct to its definition.
+    fn normalize(&self, ty: Ty) -> Ty {
+        match ty {
+            Ty::Written { ty, file_id } => match resolve_type_path(self.program, file_id, &ty) {
+                Some(def) => Ty::Def(def),
+                None => Ty::Written { ty, file_id },
+            },
+            ty => ty,
+        }
+    }
+
+    /// The declared type of the local `name` visible at `expr`.
+    fn local_type(&self, expr: ExprId, name: &str) -> Option<String> {
+        let start = self.function.source_map().expr_range(expr).start();
+        let local = local_scopes(self.db, self.file_id).resolve(name, start)?;
+        self.function
+            .body()
+            .bindings()
+            .find(|(_, binding)| binding.range == local.range())
+            .map(|(_, binding)| binding.ty.clone())
+    }
+
+    fn def_type(&self, def: DefId) -> Option<Ty> {
+        let entry = self.program.def_map().entry(def)?;
+        match entry.kind() {
+            DefKind::Contract | DefKind::Struct | DefKind::Enum => Some(Ty::Def(def)),
+            DefKind::Variable | DefKind::Field => {
+                let file_id = entry.location().file_id();
+                let types = file_decl_types(self.db, self.db.file_input(file_id));
+                let ty = types.types(entry.location().range()).first()?.clone();
+                Some(Ty::Written { ty, file_id })
+            }
+            _ => None,
+        }
+    }
+
+    fn lookup_member(&self, ty: Ty, name: &str) -> Option<DefId> {
+        match self.normalize(ty) {
+            Ty::Written { .. } => None,
+            Ty::Def(def @ DefId::Contract(_)) => self.lookup_in_bases(def, name, false),
+            Ty::Def(def @ (DefId::Struct(_) | DefId::Enum(_))) => {
+                let parent = self.program.def_map().entry(def)?;
+                let kind = match def {
+                    DefId::Struct(_) => DefKind::Field,
+                    _ => DefKind::Variant,
+                };
+                self.program
+                    .def_map()
+                    .entries_by_name_in_file(parent.location().file_id(), name)
+                    .into_iter()
+                    .find(|entry| {
+                        entry.kind() == kind && entry.container() == Some(parent.location().name())
+                    })
+                    .map(|entry| entry.id())
+            }
+            Ty::Super(contract) => self.lookup_in_bases(contract, name, true),
+            Ty::Def(_) => None,
+        }
+    }
+
+    /// `name` declared in `contract` or the nearest of its bases.
+    fn lookup_in_bases(&self, contract: DefId, name: &str, skip_self: bool) -> Option<DefId> {
+        let linearization = linearized_bases(self.db, self.program, contract);
+        let bases = linearization.bases();
+        let bases = if skip_self {
+            bases.get(1..).unwrap_or_default()
+        } else {
+            bases
+        };
+        bases.iter().find_map(|base| {
+            let base = self.program.def_map().entry(*base)?;
+            self.program
+                .def_map()
+                .entries_by_name_in_file(base.location().file_id(), name)
+                .into_iter()
+                .find(|entry| entry.container() == Some(base.location().name()))
+                .map(|entry| entry.id())
+        })
+    }
+}
+
+/// The contract or struct a written type names, e.g. `IERC20`, `Lib.Point` or `M.Token`.
+fn resolve_type_path(program: &HirProgram, file_id: FileId, ty: &str) -> Option<DefId> {
+    let ty = ty.trim();
+    if ty.starts_with("mapping") || ty.ends_with(']') {
+        return None;
+    }
+    let segments = ty.split('.').map(str::trim).collect::<Vec<_>>();
+    let def = match segments.as_slice() {
+        [name] => program
+            .resolve_contract(file_id, name)
+            .or_else(|| program.resolve_symbol_kind(file_id, DefKind::Struct, name)),
+        [qualifier, name] => program
+            .resolve_qualified_symbol(file_id, qualifier, name)
+            .or_else(|| program.resolve_contract_qualified_symbol(file_id, qualifier, name)),
+        _ => None,
+    }?;
+    matches!(def, DefId::Contract(_) | DefId::Struct(_)).then_some(def)
+}
+
+/// The type produced by indexing `ty`: the value of a mapping or the element of an array.
+fn element_type(ty: &str) -> Option<String> {
+    let ty = ty.trim();
+    if let Some(rest) = ty.strip_prefix("mapping") {
+        let inner = rest.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+        let mut depth = 0usize;
+        for (idx, ch) in inner.char_indices() {
+            match ch {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth = depth.saturating_sub(1),
+                '=' if depth == 0 && inner[idx..].starts_with("=>") => {
+                    let value = inner[idx + 2..].trim();
+                    // Named mapping values (`mapping(address => uint256 balance)`).
+                    let value = match value.rsplit_once(char::is_whitespace) {
+                        Some((ty, name)) if !value.starts_with("mapping") && is_ident(name) => ty,
+                        _ => value,
+                    };
+                    return Some(value.trim().to_string());
+                }
+                _ => {}
+            }
+        }
+        return None;
+    }
+    let ty = ty.strip_suffix(']')?;
+    let open = ty.rfind('[')?;
+    Some(ty[..open].trim().to_string())
+}
+
+fn is_ident(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::element_type;
+
+    #[test]
+    fn element_type_of_mappings_and_arrays() {
+        assert_eq!(
+            element_type("mapping(address => IERC20)").as_deref(),
+            Some("IERC20")
+        );
+        assert_eq!(
+            element_type("mapping(address => mapping(uint256 => Lib.Point))").as_deref(),
+            Some("mapping(uint256 => Lib.Point)")
+        );
+        assert_eq!(
+            element_type("mapping(address owner => Vault vault)").as_deref(),
+            Some("Vault")
+        );
+        assert_eq!(element_type("Vault[2][]").as_deref(), Some("Vault[2]"));
+        assert_eq!(element_type("Vault"), None);
+    }
+}
@@ -0,0 +1,616 @@
+//! Lowering of inline `assembly` blocks. Yul scopes differ from Solidity ones: functions are
+//! visible in the whole block that declares them, and a function body only sees its own
+//! parameters, return variables and the functions around it. A name that is neither declared in
+//! Yul nor a builtin refers to a Solidity variable, which is left for `Semantics` to link.
+
+use sa_base_db::{FileId, FileInput};
+use sa_span::{TextRange, TextSize, range_contains};
+use sa_syntax::Parse;
+use sa_syntax::ast::{Block, Item, ItemKind, PathSlice, Stmt, StmtKind, interface::Ident, yul};
+
+use crate::HirDatabase;
+
+/// EVM builtins of the Yul dialect used by inline assembly.
+pub const YUL_BUILTINS: &[&str] = &[
+    "stop",
+    "add",
+    "sub",
+    "mul",
+    "div",
+    "sdiv",
+    "mod",
+    "smod",
+    "exp",
+    "not",
+    "lt",
+    "gt",
+    "slt",
+    "sgt",
+    "eq",
+    "iszero",
+    "and",
+    "or",
+    "xor",
+    "byte",
+    "shl",
+    "shr",
+    "sar",
+    "addmod",
+    "mulmod",
+    "signextend",
+    "keccak256",
+    "pc",
+    "pop",
+    "mload",
+    "mstore",
+    "mstore8",
+    "sload",
+    "sstore",
+    "tload",
+    "tstore",
+    "msize",
+    "gas",
+    "address",
+    "balance",
+    "selfbalance",
+    "caller",
+    "callvalue",
+    "calldataload",
+    "calldatasize",
+    "calldatacopy",
+    "codesize",
+    "codecopy",
+    "extcodesize",
+    "extcodecopy",
+    "returndatasize",
+    "returndatacopy",
+    "mcopy",
+    "extcodehash",
+    "create",
+    "create2",
+    "call",
+    "callcode",
+    "delegatecall",
+    "staticcall",
+    "return",
+    "revert",
+    "selfdestruct",
+    "invalid",
+    "log0",
+    "log1",
+    "log2",
+    "log3",
+    "log4",
+    "chainid",
+    "basefee",
+    "blobbasefee",
+    "origin",
+    "gasprice",
+    "blockhash",
+    "blobhash",
+    "coinbase",
+    "timestamp",
+    "number",
+    "difficulty",
+    "prevrandao",
+    "gaslimit",
+    "memoryguard",
+];
+
+pub fn is_yul_builtin(name: &str) -> bool {
+    YUL_BUILTINS.contains(&name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YulDefKind {
+    Variable,
+    Parameter,
+    Return,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YulDef {
+    name: String,
+    kind: YulDefKind,
+    range: TextRange,
+    scope: TextRange,
+    visible_from: TextSize,
+    params: Vec<String>,
+    returns: Vec<String>,
+}
+
+impl YulDef {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> YulDefKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn scope(&self) -> TextRange {
+        self.scope
+    }
+
+    /// Parameter names of a function; empty for variables.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Return variable names of a function; empty for variables.
+    pub fn returns(&self) -> &[String] {
+        &self.returns
+    }
+
+    /// `function f(a, b) -> r` for functions, `let x` for variables.
+    pub fn label(&self) -> String {
+        match self.kind {
+            YulDefKind::Function => {
+                let mut label = format!("function {}({})", self.name, self.params.join(", "));
+                if !self.returns.is_empty() {
+                    label.push_str(" -> ");
+                    label.push_str(&self.returns.join(", "));
+                }
+                label
+            }
+            YulDefKind::Variable => format!("let {}", self.name),
+            YulDefKind::Parameter => format!("parameter {}", self.name),
+            YulDefKind::Return => format!("return {}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YulResolution {
+    /// A variable or function declared in the same assembly block, by the range of its name.
+    Yul(TextRange),
+    Builtin,
+    /// A Solidity variable: a local, a state variable or a constant.
+    External,
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YulReference {
+    name: String,
+    range: TextRange,
+    suffix: Option<String>,
+    resolution: YulResolution,
+}
+
+impl YulReference {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The range of the referenced name, without any `.slot`/`.offset` suffix.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    /// The suffix of an access like `x.slot` or `data.length`.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    pub fn resolution(&self) -> YulResolution {
+        self.resolution
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyBlock {
+    range: TextRange,
+    defs: Vec<YulDef>,
+    references: Vec<YulReference>,
+    functions: Vec<TextRange>,
+}
+
+impl AssemblyBlock {
+    /// The whole `assembly { ... }` statement.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn defs(&self) -> &[YulDef] {
+        &self.defs
+    }
+
+    pub fn references(&self) -> &[YulReference] {
+        &self.references
+    }
+
+    /// Yul definitions usable at `offset`, innermost first.
+    pub fn visible_defs(&self, offset: TextSize) -> Vec<&YulDef> {
+        let mut defs = self
+            .defs
+            .iter()
+            .filter(|def| {
+                range_contains(def.scope, offset)
+                    && def.visible_from <= offset
+                    && (def.kind == YulDefKind::Function || !self.crosses_function(def, offset))
+            })
+            .collect::<Vec<_>>();
+        defs.sort_by_key(|def| u32::from(def.scope.len()));
+        defs
+    }
+
+    /// Whether Solidity variables can be named at `offset`, which is not the case inside
+    /// Yul functions.
+    pub fn sees_solidity(&self, offset: TextSize) -> bool {
+        !self
+            .functions
+            .iter()
+            .any(|function| range_contains(*function, offset))
+    }
+
+    fn crosses_function(&self, def: &YulDef, offset: TextSize) -> bool {
+        self.functions.iter().any(|function| {
+            range_contains(*function, offset) && !range_contains(*function, def.range.start())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct YulHir {
+    blocks: Vec<AssemblyBlock>,
+}
+
+impl YulHir {
+    pub fn blocks(&self) -> &[AssemblyBlock] {
+        &self.blocks
+    }
+
+    pub fn block_at(&self, offset: TextSize) -> Option<&AssemblyBlock> {
+        self.blocks
+            .iter()
+            .filter(|block| range_contains(block.range, offset))
+            .min_by_key(|block| u32::from(block.range.len()))
+    }
+
+    pub fn reference_at(&self, offset: TextSize) -> Option<&YulReference> {
+        self.block_at(offset)?
+            .references
+            .iter()
+            .find(|reference| covers(reference.range, offset))
+    }
+
+    pub fn def_at(&self, offset: TextSize) -> Option<&YulDef> {
+        self.block_at(offset)?
+            .defs
+            .iter()
+            .find(|def| covers(def.range, offset))
+    }
+
+    pub fn def(&self, range: TextRange) -> Option<&YulDef> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.defs.iter())
+            .find(|def| def.range == range)
+    }
+}
+
+fn covers(range: TextRange, offset: TextSize) -> bool {
+    range.start() <= offset && offset <= range.end()
+}
+
+unsafe impl salsa::Update for YulHir {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+pub fn yul_hir_for_file(db: &dyn HirDatabase, file: FileInput) -> YulHir {
+    let text = file.text(db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    YulLowering::new(&parse).lower()
+}
+
+pub fn yul_hir(db: &dyn HirDatabase, file_id: FileId) -> YulHir {
+    yul_hir_for_file(db, db.file_input(file_id)).clone()
+}
+
+struct PendingReference {
+    name: String,
+    range: TextRange,
+    suffix: Option<String>,
+    is_call: bool,
+}
+
+struct YulLowering<'a> {
+    parse: &'a Parse,
+    blocks: Vec<AssemblyBlock>,
+    defs: Vec<YulDef>,
+    references: Vec<PendingReference>,
+    functions: Vec<TextRange>,
+}
+
+impl<'a> YulLowering<'a> {
+    fn new(parse: &'a Parse) -> Self {
+        Self {
+            parse,
+            blocks: Vec::new(),
+            defs: Vec::new(),
+            references: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+
+    fn lower(mut self) -> YulHir {
+        for item in self.parse.tree().items.iter() {
+            self.lower_item(item);
+        }
+        YulHir {
+            blocks: self.blocks,
+        }
+    }
+
+    fn lower_item(&mut self, item: &Item<'_>) {
+        match &item.kind {
+            ItemKind::Contract(contract) => {
+                for item in contract.body.iter() {
+                    self.lower_item(item);
+                }
+            }
+            ItemKind::Function(function) => {
+                if let Some(body) = function.body.as_ref() {
+                    self.lower_block(body);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lower_block(&mut self, block: &Block<'_>) {
+        for stmt in block.stmts.iter() {
+            self.lower_stmt(stmt);
+        }
+    }
+
+    fn lower_stmt(&mut self, stmt: &Stmt<'_>) {
+        match &stmt.kind {
+            StmtKind::Assembly(assembly) => {
+                if let Some(range) = self.parse.span_to_text_range(stmt.span) {
+                    self.lower_assembly(range, &assembly.block);
+                }
+            }
+            StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => self.lower_block(block),
+            StmtKind::For { init, body, .. } => {
+                if let Some(init) = init.as_deref() {
+                    self.lower_stmt(init);
+                }
+                self.lower_stmt(body);
+            }
+            StmtKind::If(_, then_branch, else_branch) => {
+                self.lower_stmt(then_branch);
+                if let Some(else_branch) = else_branch.as_deref() {
+                    self.lower_stmt(else_branch);
+                }
+            }
+            StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => self.lower_stmt(body),
+            StmtKind::Try(stmt_try) => {
+                for clause in stmt_try.clauses.iter() {
+                    self.lower_block(&clause.block);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lower_assembly(&mut self, range: TextRange, block: &yul::Block<'_>) {
+        self.lower_yul_block(block);
+        let defs = std::mem::take(&mut self.defs);
+        let functions = std::mem::take(&mut self.functions);
+        let mut assembly = AssemblyBlock {
+            range,
+            defs,
+            references: Vec::new(),
+            functions,
+        };
+        assembly.references = std::mem::take(&mut self.references)
+            .into_iter()
+            .map(|reference| resolve_reference(&assembly, reference))
+            .collect();
+        self.blocks.push(assembly);
+    }
+
+    fn lower_yul_block(&mut self, block: &yul::Block<'_>) {
+        let Some(scope) = self.parse.span_to_text_range(block.span) else {
+            return;
+        };
+        self.lower_yul_stmts(&block.stmts, scope);
+    }
+
+    /// Functions are declared up front since they can be called before their definition.
+    fn lower_yul_stmts(&mut self, stmts: &[yul::Stmt<'_>], scope: TextRange) {
+        for stmt in stmts {
+            if let yul::StmtKind::FunctionDef(function) = &stmt.kind {
+                self.add_function(function, scope);
+            }
+        }
+        for stmt in stmts {
+            self.lower_yul_stmt(stmt, scope);
+        }
+    }
+
+    fn lower_yul_stmt(&mut self, stmt: &yul::Stmt<'_>, scope: TextRange) {
+        match &stmt.kind {
+            yul::StmtKind::Block(block) => self.lower_yul_block(block),
+            yul::StmtKind::AssignSingle(path, expr) => {
+                self.add_path(path);
+                self.lower_yul_expr(expr);
+            }
+            yul::StmtKind::AssignMulti(paths, call) => {
+                for path in paths.iter() {
+                    self.add_path(path);
+                }
+                self.lower_yul_call(call);
+            }
+            yul::StmtKind::Expr(call) => self.lower_yul_call(call),
+            yul::StmtKind::If(cond, block) => {
+                self.lower_yul_expr(cond);
+                self.lower_yul_block(block);
+            }
+            yul::StmtKind::For(stmt_for) => {
+                // Variables declared in the init block are visible in the rest of the loop.
+                let Some(for_scope) = self.parse.span_to_text_range(stmt.span) else {
+                    return;
+                };
+                self.lower_yul_stmts(&stmt_for.init.stmts, for_scope);
+                self.lower_yul_expr(&stmt_for.cond);
+                self.lower_yul_block(&stmt_for.step);
+                self.lower_yul_block(&stmt_for.body);
+            }
+            yul::StmtKind::Switch(switch) => {
+                self.lower_yul_expr(&switch.selector);
+                for case in switch.cases.iter() {
+                    self.lower_yul_block(&case.body);
+                }
+            }
+            yul::StmtKind::FunctionDef(function) => {
+                if let Some(range) = self.parse.span_to_text_range(stmt.span) {
+                    self.lower_function(function, range);
+                }
+            }
+            yul::StmtKind::VarDecl(names, init) => {
+                if let Some(init) = init {
+                    self.lower_yul_expr(init);
+                }
+                let Some(stmt_range) = self.parse.span_to_text_range(stmt.span) else {
+                    return;
+                };
+                for name in names.iter() {
+                    self.add_def(*name, YulDefKind::Variable, scope, stmt_range.end());
+                }
+            }
+            yul::StmtKind::Leave | yul::StmtKind::Break | yul::StmtKind::Continue => {}
+        }
+    }
+
+    fn lower_function(&mut self, function: &yul::Function<'_>, range: TextRange) {
+        self.functions.push(range);
+        for param in function.parameters.iter() {
+            self.add_def(*param, YulDefKind::Parameter, range, range.start());
+        }
+        for ret in function.returns.iter() {
+            self.add_def(*ret, YulDefKind::Return, range, range.start());
+        }
+        self.lower_yul_block(&function.body);
+    }
+
+    fn lower_yul_expr(&mut self, expr: &yul::Expr<'_>) {
+        match &expr.kind {
+            yul::ExprKind::Path(path) => self.add_path(path),
+            yul::ExprKind::Call(call) => self.lower_yul_call(call),
+            yul::ExprKind::Lit(_) => {}
+        }
+    }
+
+    fn lower_yul_call(&mut self, call: &yul::ExprCall<'_>) {
+        if let Some(range) = self.parse.span_to_text_range(call.name.span) {
+            let name = self.ident_text(call.name);
+            self.references.push(PendingReference {
+                name,
+                range,
+                suffix: None,
+                is_call: true,
+            });
+        }
+        for arg in call.arguments.iter() {
+            self.lower_yul_expr(arg);
+        }
+    }
+
+    fn add_path(&mut self, path: &PathSlice) {
+        let segments = path.segments();
+        let Some(first) = segments.first() else {
+            return;
+        };
+        let Some(range) = self.parse.span_to_text_range(first.span) else {
+            return;
+        };
+        let name = self.ident_text(*first);
+        let suffix = segments.get(1).map(|suffix| self.ident_text(*suffix));
+        self.references.push(PendingReference {
+            name,
+            range,
+            suffix,
+            is_call: false,
+        });
+    }
+
+    fn add_function(&mut self, function: &yul::Function<'_>, scope: TextRange) {
+        let params = function
+            .parameters
+            .iter()
+            .map(|param| self.ident_text(*param))
+            .collect();
+        let returns = function
+            .returns
+            .iter()
+            .map(|ret| self.ident_text(*ret))
+            .collect();
+        let Some(range) = self.parse.span_to_text_range(function.name.span) else {
+            return;
+        };
+        self.defs.push(YulDef {
+            name: self.ident_text(function.name),
+            kind: YulDefKind::Function,
+            range,
+            scope,
+            visible_from: scope.start(),
+            params,
+            returns,
+        });
+    }
+
+    fn add_def(&mut self, name: Ident, kind: YulDefKind, scope: TextRange, visible_from: TextSize) {
+        let Some(range) = self.parse.span_to_text_range(name.span) else {
+            return;
+        };
+        self.defs.push(YulDef {
+            name: self.ident_text(name),
+            kind,
+            range,
+            scope,
+            visible_from,
+            params: Vec::new(),
+            returns: Vec::new(),
+        });
+    }
+
+    fn ident_text(&self, ident: Ident) -> String {
+        self.parse.with_session(|| ident.to_string())
+    }
+}
+
+fn resolve_reference(block: &AssemblyBlock, reference: PendingReference) -> YulReference {
+    let offset = reference.range.start();
+    let def = block.visible_defs(offset).into_iter().find(|def| {
+        def.name == reference.name && (def.kind == YulDefKind::Function) == reference.is_call
+    });
+    let resolution = match def {
+        Some(def) => YulResolution::Yul(def.range),
+        None if reference.is_call && is_yul_builtin(&reference.name) => YulResolution::Builtin,
+        None if !reference.is_call && block.sees_solidity(offset) => YulResolution::External,
+        None => YulResolution::Unresolved,
+    };
+    YulReference {
+        name: reference.name,
+        range: reference.range,
+        suffix: reference.suffix,
+        resolution,
+    }
+}
@@ -0,0 +1,133 @@
+use sa_hir::{BindingKind, Expr, Stmt, file_bodies};
+use sa_paths::NormalizedPath;
+use sa_span::TextSize;
+use sa_test_support::setup_db;
+
+#[test]
+fn lowers_function_bodies_with_source_map() {
+    let text = r#"
+contract Main {
+    function run(uint256 amount) public returns (uint256 out) {
+        uint256 doubled = amount * 2;
+        if (doubled > 10) {
+            out = doubled;
+        }
+        (, uint256 rest) = split(doubled);
+    }
+
+    function split(uint256 value) internal pure returns (uint256, uint256) {
+        return (value, value);
+    }
+}
+"#
+    .to_string();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (db, _, snapshot) = setup_db(vec![(path.clone(), text.clone())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let bodies = file_bodies(&db, file_id);
+    let names = bodies
+        .functions()
+        .iter()
+        .map(|function| (function.container(), function.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![(Some("Main"), Some("run")), (Some("Main"), Some("split"))]
+    );
+
+    let run = &bodies.functions()[0];
+    let body = run.body();
+    let bindings = body
+        .bindings()
+        .map(|(_, binding)| (binding.name.as_str(), binding.kind, binding.ty.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bindings,
+        vec![
+            ("amount", BindingKind::Parameter, "uint256"),
+            ("out", BindingKind::NamedReturn, "uint256"),
+            ("doubled", BindingKind::Local, "uint256"),
+            ("rest", BindingKind::Local, "uint256"),
+        ]
+    );
+
+    let Some(Stmt::Block(stmts)) = body.block().map(|block| body.stmt(block)) else {
+        panic!("expected function block");
+    };
+    assert_eq!(stmts.len(), 3);
+    assert!(matches!(
+        body.stmt(stmts[1]),
+        Stmt::If {
+            else_branch: None,
+            ..
+        }
+    ));
+    let Stmt::Let {
+        bindings,
+        initializer,
+    } = body.stmt(stmts[2])
+    else {
+        panic!("expected tuple declaration");
+    };
+    assert_eq!(bindings.len(), 2);
+    assert!(bindings[0].is_none());
+    let Some(Expr::Call { callee, args }) = initializer.map(|expr| body.expr(expr)) else {
+        panic!("expected call initializer");
+    };
+    assert_eq!(body.expr(*callee), &Expr::Ident("split".to_string()));
+    assert_eq!(args.len(), 1);
+
+    let offset = TextSize::from(text.find("amount * 2").expect("amount use") as u32);
+    let expr = run.source_map().expr_at(offset).expect("expr at amount");
+    assert_eq!(body.expr(expr), &Expr::Ident("amount".to_string()));
+    let range = run.source_map().expr_range(expr);
+    assert_eq!(
+        &text[usize::from(range.start())..usize::from(range.end())],
+        "amount"
+    );
+
+    let binary = body
+        .exprs()
+        .find_map(|(_, expr)| match expr {
+            Expr::Binary { op, .. } => Some(*op),
+            _ => None,
+        })
+        .expect("binary expr");
+    assert_eq!(binary, "*");
+}
+
+#[test]
+fn bodies_of_a_file_share_one_arena() {
+    let text = r#"
+contract Main {
+    function a(uint256 x) public pure returns (uint256) {
+        return x + 1;
+    }
+
+    function b(uint256 y) public pure returns (uint256) {
+        return y * 2;
+    }
+}
+"#;
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (db, _, snapshot) = setup_db(vec![(path.clone(), text)], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let bodies = file_bodies(&db, file_id);
+    let [a, b] = bodies.functions() else {
+        panic!("expected two functions");
+    };
+    let a_exprs = a.body().exprs().map(|(id, _)| id).collect::<Vec<_>>();
+    let b_exprs = b.body().exprs().map(|(id, _)| id).collect::<Vec<_>>();
+    assert!(!a_exprs.is_empty() && !b_exprs.is_empty());
+    assert!(a_exprs.iter().all(|id| !b_exprs.contains(id)));
+    assert!(matches!(a.body().expr(a_exprs[0]), Expr::Ident(name) if name == "x"));
+    assert!(matches!(b.body().expr(b_exprs[0]), Expr::Ident(name) if name == "y"));
+
+    let y_offset = TextSize::from(text.find("y * 2").expect("y") as u32);
+    assert_eq!(b.source_map().expr_at(y_offset), Some(b_exprs[0]));
+    assert_eq!(a.source_map().expr_at(y_offset), None);
+
+    assert_eq!(bodies, file_bodies(&db, file_id));
+}
@@ -0,0 +1,43 @@
+use sa_hir::{defmap_debug_dump, hir_debug_dump};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_db;
+
+#[test]
+fn debug_dumps_list_imports_bodies_and_definitions() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            "import \"./Lib.sol\"; contract Main { function run(uint256 a) public returns (uint256 out) { uint256 x = a + 1; out = x; } }",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Lib.sol"),
+            "library Lib { struct Point { uint256 x; } }",
+        ),
+    ];
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+        .expect("main file id");
+
+    let hir = hir_debug_dump(&db, project_id, main);
+    assert!(hir.starts_with("file /workspace/src/Main.sol\n"), "{hir}");
+    assert!(
+        hir.contains("import \"./Lib.sol\" -> /workspace/src/Lib.sol"),
+        "{hir}"
+    );
+    assert!(hir.contains("\nitems\n  contract Main "), "{hir}");
+    assert!(hir.contains("  function Main.run "), "{hir}");
+    assert!(hir.contains("fn Main.run "), "{hir}");
+    assert!(hir.contains("Parameter uint256 a "), "{hir}");
+    assert!(hir.contains("NamedReturn uint256 out "), "{hir}");
+    assert!(hir.contains("let (uint256 x) = (a + 1) "), "{hir}");
+    assert!(hir.contains("expr out = x "), "{hir}");
+
+    let defs = defmap_debug_dump(&db, project_id);
+    let lib_at = defs.find("/workspace/src/Lib.sol\n").expect("lib file");
+    let main_at = defs.find("/workspace/src/Main.sol\n").expect("main file");
+    assert!(lib_at < main_at, "{defs}");
+    assert!(defs.contains("  contract Main "), "{defs}");
+    assert!(defs.contains("  function Main.run "), "{defs}");
+    assert!(defs.contains("  struct Lib.Point "), "{defs}");
+}
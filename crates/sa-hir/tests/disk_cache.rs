@@ -0,0 +1,77 @@
+use std::fs;
+
+use sa_def::{DefKind, FileDefs};
+use sa_hir::{HirCache, export_hir_cache, lowered_program, set_hir_cache};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_db;
+use tempfile::tempdir;
+
+const MAIN: &str = "contract Main {}";
+
+#[test]
+fn cache_round_trips_through_disk() {
+    let files = vec![(NormalizedPath::new("/workspace/src/Main.sol"), MAIN)];
+    let (db, _project_id, _snapshot) = setup_db(files, vec![]);
+
+    let cache = export_hir_cache(&db);
+    assert_eq!(cache.len(), 1);
+
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("cache").join(sa_hir::HIR_CACHE_FILE);
+    cache.save(&path).expect("save cache");
+    assert_eq!(HirCache::load(&path), cache);
+}
+
+#[test]
+fn outdated_or_corrupt_caches_load_empty() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join(sa_hir::HIR_CACHE_FILE);
+
+    fs::write(
+        &path,
+        r#"{"format":0,"analyzer_version":"0.0.0","files":[]}"#,
+    )
+    .expect("write outdated cache");
+    assert!(HirCache::load(&path).is_empty());
+
+    fs::write(&path, "not json").expect("write corrupt cache");
+    assert!(HirCache::load(&path).is_empty());
+}
+
+#[test]
+fn cached_entries_are_used_only_for_matching_text() {
+    let files = vec![(NormalizedPath::new("/workspace/src/Main.sol"), MAIN)];
+    let (mut db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+        .expect("main file id");
+
+    let mut cache = HirCache::default();
+    cache.insert(MAIN, Vec::new(), FileDefs::collect("contract FromCache {}"));
+    set_hir_cache(&mut db, cache);
+
+    let program = lowered_program(&db, project_id);
+    assert!(
+        program
+            .def_map()
+            .entry_by_name(DefKind::Contract, "FromCache")
+            .is_some()
+    );
+
+    let path = db.file_path(main_id);
+    db.set_file(
+        main_id,
+        "contract Edited {}".into(),
+        1,
+        sa_base_db::LanguageKind::Solidity,
+        path,
+    );
+    let program = lowered_program(&db, project_id);
+    let def_map = program.def_map();
+    assert!(def_map.entry_by_name(DefKind::Contract, "Edited").is_some());
+    assert!(
+        def_map
+            .entry_by_name(DefKind::Contract, "FromCache")
+            .is_none()
+    );
+}
@@ -1,4 +1,4 @@
-use sa_hir::lowered_program;
+use sa_hir::{exports, lowered_program, visible_definitions_with_prefix};
 use sa_paths::NormalizedPath;
 use sa_test_support::setup_db;
 
@@ -122,3 +122,105 @@ contract B {}
     assert!(names.contains(&"A"));
     assert!(names.contains(&"B"));
 }
+
+#[test]
+fn export_maps_follow_plain_imports_and_aliases_through_cycles() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/A.sol"),
+            r#"
+import "./B.sol";
+
+contract A {
+    uint256 inner;
+}
+"#,
+        ),
+        (
+            NormalizedPath::new("/workspace/src/B.sol"),
+            r#"
+import "./A.sol";
+import {Leaf as Renamed} from "./Leaf.sol";
+import "./Leaf.sol" as LeafModule;
+
+contract B {}
+"#,
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Leaf.sol"),
+            r#"
+struct Leaf { uint256 x; }
+"#,
+        ),
+    ];
+
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let a_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/A.sol"))
+        .expect("a file id");
+    let b_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/B.sol"))
+        .expect("b file id");
+
+    let top_level = |file_id| {
+        let mut names = exports(&db, project_id, file_id)
+            .top_level()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    assert_eq!(top_level(a_id), vec!["A", "B", "Renamed"]);
+    assert_eq!(top_level(b_id), vec!["A", "B", "Renamed"]);
+
+    let a_exports = exports(&db, project_id, a_id);
+    assert!(a_exports.contains("inner"));
+    assert!(!a_exports.contains("Leaf"));
+    assert!(!a_exports.contains("LeafModule"));
+    assert_eq!(a_exports.get("Renamed").count(), 1);
+}
+
+#[test]
+fn prefix_lookups_return_only_matching_top_level_names() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Tokens.sol"),
+            r#"
+contract Token { uint256 totalSupply; }
+contract TokenVault {}
+contract Treasury {}
+struct tokenInfo { uint256 id; }
+"#,
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            r#"
+import "./Tokens.sol";
+import "./Tokens.sol" as Tok;
+
+contract Main {}
+"#,
+        ),
+    ];
+
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+        .expect("main file id");
+
+    let names = |prefix: &str| {
+        let mut names = visible_definitions_with_prefix(&db, project_id, main_id, prefix)
+            .iter()
+            .map(|def| def.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    assert_eq!(
+        names("tok"),
+        vec!["Tok", "Token", "TokenVault", "tokenInfo"]
+    );
+    assert_eq!(names("TokenV"), vec!["TokenVault"]);
+    assert_eq!(names("tot"), Vec::<String>::new());
+    assert_eq!(names("").len(), 6);
+}
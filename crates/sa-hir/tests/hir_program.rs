@@ -1,9 +1,14 @@
 use std::sync::Arc;
 
-use sa_base_db::LanguageKind;
+use sa_base_db::{Durability, LanguageKind, OverlayEdit};
 use sa_def::DefKind;
-use sa_hir::{Semantics, contract_member_definitions_at_offset, lowered_program, parse};
+use sa_hir::{
+    Semantics, contract_member_definitions_at_offset, file_def_ids, file_defs, file_imports,
+    lowered_program, parse, syntax,
+};
 use sa_paths::NormalizedPath;
+use sa_span::{TextRange, TextSize};
+use sa_syntax::cst::{Cst, parse_cst};
 use sa_test_support::{extract_offset, setup_db};
 
 fn file_id(snapshot: &sa_vfs::VfsSnapshot, path: &str) -> sa_vfs::FileId {
@@ -46,6 +51,94 @@ fn parse_and_program_update_when_files_change() {
     assert_ne!(program, program_updated);
 }
 
+#[test]
+fn syntax_of_an_edited_file_reuses_the_previous_tree() {
+    let text = "contract A {\n    function f() public {}\n}\ncontract B {\n    function g() public {\n        x;\n    }\n}\n";
+    let (mut db, _, snapshot) = setup_db(
+        vec![(NormalizedPath::new("/workspace/src/Main.sol"), text)],
+        Vec::new(),
+    );
+    let main_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+        .expect("main file id");
+    let before = syntax(&db, main_id);
+
+    let offset = TextSize::of(&text[..text.find("x;").expect("statement")]);
+    let edited = text.replace("x;", "y; x;");
+    let version = db.file_input(main_id).version(&db) + 1;
+    let path = db.file_path(main_id);
+    db.set_file(
+        main_id,
+        Arc::from(edited.as_str()),
+        version,
+        LanguageKind::Solidity,
+        path,
+    );
+    let edit = OverlayEdit {
+        range: TextRange::empty(offset),
+        new_text: "y; ".to_string(),
+    };
+    db.set_file_edits(main_id, Arc::from([edit]), Durability::LOW);
+    let after = syntax(&db, main_id);
+    assert_eq!(*after, parse_cst(&edited));
+
+    // The contract before the edit is shared with the previous tree.
+    let first = |cst: &Cst| cst.contracts().next().expect("A").children().as_ptr();
+    assert!(std::ptr::eq(first(&before), first(&after)));
+}
+
+#[test]
+fn per_file_queries_only_change_for_the_edited_file() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            "import \"./Lib.sol\"; contract Main {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Lib.sol"),
+            "import \"./Missing.sol\"; contract Lib {}",
+        ),
+    ];
+    let (mut db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = file_id(&snapshot, "/workspace/src/Main.sol");
+    let lib_id = file_id(&snapshot, "/workspace/src/Lib.sol");
+    let project = db.project_input(project_id);
+
+    let main_imports = file_imports(&db, project, main_id).clone();
+    assert_eq!(
+        main_imports.resolved_files().collect::<Vec<_>>(),
+        vec![lib_id]
+    );
+    let lib_imports = file_imports(&db, project, lib_id).clone();
+    assert_eq!(lib_imports.resolved_files().count(), 0);
+    let lib_defs = file_defs(&db, lib_id);
+    let lib_def_ids = file_def_ids(&db, project, lib_id).clone();
+    let lib_contract = lowered_program(&db, project_id).resolve_contract(lib_id, "Lib");
+    assert!(lib_contract.is_some());
+
+    let path = db.file_path(main_id);
+    db.set_file(
+        main_id,
+        Arc::from("contract Main {} contract Extra {}"),
+        2,
+        LanguageKind::Solidity,
+        path,
+    );
+
+    assert_eq!(
+        file_imports(&db, project, main_id).resolved_files().count(),
+        0
+    );
+    assert_eq!(file_imports(&db, project, lib_id), &lib_imports);
+    assert_eq!(file_defs(&db, lib_id), lib_defs);
+    assert_eq!(file_defs(&db, main_id).len(), 2);
+    assert_eq!(file_def_ids(&db, project, lib_id), &lib_def_ids);
+
+    let program = lowered_program(&db, project_id);
+    assert!(program.resolve_contract(main_id, "Extra").is_some());
+    assert_eq!(program.resolve_contract(lib_id, "Lib"), lib_contract);
+}
+
 #[test]
 fn visible_definitions_include_aliases_and_source_aliases() {
     let files = vec![
@@ -167,3 +167,28 @@ contract Lib {}
 
     assert!(program.resolve_symbol(main_id, "Missing").is_none());
 }
+
+#[test]
+fn path_index_tracks_added_and_removed_files() {
+    let files = vec![
+        (NormalizedPath::new("/workspace/src/A.sol"), "contract A {}"),
+        (NormalizedPath::new("/workspace/src/B.sol"), "contract B {}"),
+    ];
+    let (mut db, _project_id, snapshot) = setup_db(files, vec![]);
+    let a_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/A.sol"))
+        .expect("a file id");
+    let b_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/B.sol"))
+        .expect("b file id");
+
+    let index = sa_hir::path_index(&db);
+    assert_eq!(index.file_id("/workspace/src/A.sol"), Some(a_id));
+    assert_eq!(index.file_id("/workspace/src/B.sol"), Some(b_id));
+    assert_eq!(index.file_id("/workspace/src/C.sol"), None);
+
+    db.remove_file(b_id);
+    let index = sa_hir::path_index(&db);
+    assert_eq!(index.file_id("/workspace/src/A.sol"), Some(a_id));
+    assert_eq!(index.file_id("/workspace/src/B.sol"), None);
+}
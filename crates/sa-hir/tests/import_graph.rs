@@ -0,0 +1,69 @@
+use sa_hir::import_graph;
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_db;
+
+fn file_id(snapshot: &sa_vfs::VfsSnapshot, path: &str) -> sa_vfs::FileId {
+    snapshot
+        .file_id(&NormalizedPath::new(path))
+        .unwrap_or_else(|| panic!("missing file id for {}", path))
+}
+
+#[test]
+fn import_graph_tracks_edges_closures_and_cycles() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            "import \"./A.sol\"; import \"./A.sol\"; contract Main {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/A.sol"),
+            "import \"./B.sol\"; contract A {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/B.sol"),
+            "import \"./A.sol\"; import \"./Leaf.sol\"; contract B {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Leaf.sol"),
+            "contract Leaf {}",
+        ),
+    ];
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main = file_id(&snapshot, "/workspace/src/Main.sol");
+    let a = file_id(&snapshot, "/workspace/src/A.sol");
+    let b = file_id(&snapshot, "/workspace/src/B.sol");
+    let leaf = file_id(&snapshot, "/workspace/src/Leaf.sol");
+
+    let graph = import_graph(&db, project_id);
+    assert_eq!(graph.imports(main), &[a]);
+    assert_eq!(graph.imports(b), &[a, leaf]);
+    assert!(graph.imports(leaf).is_empty());
+
+    let mut importers_of_a = vec![main, b];
+    importers_of_a.sort();
+    assert_eq!(graph.importers(a), importers_of_a.as_slice());
+
+    assert_eq!(graph.transitive_imports(main).len(), 3);
+    assert!(graph.transitive_imports(leaf).is_empty());
+    let affected = graph.transitive_importers(leaf);
+    assert!(affected.contains(&main) && affected.contains(&a) && affected.contains(&b));
+
+    let components = graph.strongly_connected_components();
+    assert_eq!(components.len(), 3);
+    let position = |file_id| {
+        components
+            .iter()
+            .position(|component| component.contains(&file_id))
+            .expect("component")
+    };
+    assert!(position(leaf) < position(a));
+    assert!(position(a) < position(main));
+
+    let cycles = graph.cycles();
+    assert_eq!(cycles.len(), 1);
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(cycles[0].files(), expected.as_slice());
+    assert_eq!(graph.cycle_containing(b), Some(cycles[0].clone()));
+    assert_eq!(graph.cycle_containing(main), None);
+}
@@ -0,0 +1,39 @@
+use sa_def::{DefId, DefKind};
+use sa_hir::{inheritance_graph, lowered_program};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_db;
+
+fn contract(program: &sa_hir::HirProgram, name: &str) -> DefId {
+    program
+        .def_map()
+        .entry_by_name(DefKind::Contract, name)
+        .expect("contract entry")
+        .id()
+}
+
+#[test]
+fn inheritance_graph_links_bases_and_derived_contracts_across_files() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Base.sol"),
+            "contract A {} contract B is A {} interface I {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            "import \"./Base.sol\" as Base; contract C is Base.B, Base.I {} contract D is C, Missing {}",
+        ),
+    ];
+    let (db, project_id, _) = setup_db(files, vec![]);
+    let program = lowered_program(&db, project_id);
+    let graph = inheritance_graph(&db, project_id);
+    let [a, b, i, c, d] = ["A", "B", "I", "C", "D"].map(|name| contract(&program, name));
+
+    assert_eq!(graph.direct_bases(c), &[b, i]);
+    assert_eq!(graph.direct_bases(d), &[c]);
+    assert!(graph.direct_bases(a).is_empty());
+    assert_eq!(graph.direct_derived(b), &[c]);
+
+    assert_eq!(graph.all_bases(d), vec![c, b, i, a]);
+    assert_eq!(graph.all_derived(a), vec![b, c, d]);
+    assert!(graph.all_derived(d).is_empty());
+}
@@ -0,0 +1,73 @@
+use sa_def::{DefId, DefKind};
+use sa_hir::{LinearizationErrorKind, linearization_errors, linearized_bases, lowered_program};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_db;
+
+fn contract(program: &sa_hir::HirProgram, name: &str) -> DefId {
+    program
+        .def_map()
+        .entry_by_name(DefKind::Contract, name)
+        .expect("contract entry")
+        .id()
+}
+
+fn names(program: &sa_hir::HirProgram, bases: &[DefId]) -> Vec<String> {
+    bases
+        .iter()
+        .map(|id| {
+            program
+                .def_map()
+                .entry(*id)
+                .expect("base entry")
+                .location()
+                .name()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn linearizes_diamond_like_solc() {
+    let files = vec![
+        (
+            NormalizedPath::new("/workspace/src/Base.sol"),
+            "contract A {} contract B is A {} contract C is A {}",
+        ),
+        (
+            NormalizedPath::new("/workspace/src/Main.sol"),
+            "import \"./Base.sol\"; contract D is B, C {}",
+        ),
+    ];
+    let (db, project_id, _snapshot) = setup_db(files, vec![]);
+    let program = lowered_program(&db, project_id);
+
+    let linearization = linearized_bases(&db, &program, contract(&program, "D"));
+    assert_eq!(names(&program, linearization.bases()), ["D", "C", "B", "A"]);
+    assert!(linearization.error().is_none());
+}
+
+#[test]
+fn reports_impossible_linearization() {
+    let files = vec![(
+        NormalizedPath::new("/workspace/src/Main.sol"),
+        "contract X {} contract Y is X {} contract Z is Y, X {}",
+    )];
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = snapshot
+        .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+        .expect("main file id");
+    let program = lowered_program(&db, project_id);
+
+    let linearization = linearized_bases(&db, &program, contract(&program, "Z"));
+    let error = linearization.error().expect("linearization error");
+    assert_eq!(error.kind(), LinearizationErrorKind::Impossible);
+    assert_eq!(
+        names(&program, linearization.bases()).len(),
+        3,
+        "every base is still listed"
+    );
+
+    let errors = linearization_errors(&db, project_id, main_id);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].contract(), contract(&program, "Z"));
+}
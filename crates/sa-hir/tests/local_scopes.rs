@@ -347,3 +347,101 @@ contract Foo {
     let semantics = Semantics::new(&db, project_id);
     assert!(semantics.resolve_local(file_id, offset).is_none());
 }
+
+#[test]
+fn resolves_locals_in_special_function_bodies() {
+    let cases = [
+        (
+            r#"
+contract Foo {
+    modifier guard(uint256 limit) {
+        uint256 before = limit;
+        _;
+        bef/*caret*/ore;
+    }
+}
+"#,
+            "before",
+            LocalDefKind::Local,
+        ),
+        (
+            r#"
+contract Foo {
+    constructor(address owner) {
+        address initial = owner;
+        init/*caret*/ial;
+    }
+}
+"#,
+            "initial",
+            LocalDefKind::Local,
+        ),
+        (
+            r#"
+contract Foo {
+    receive() external payable {
+        uint256 amount = msg.value;
+        amo/*caret*/unt;
+    }
+}
+"#,
+            "amount",
+            LocalDefKind::Local,
+        ),
+        (
+            r#"
+contract Foo {
+    fallback(bytes calldata input) external returns (bytes memory output) {
+        output = inp/*caret*/ut;
+    }
+}
+"#,
+            "input",
+            LocalDefKind::Parameter,
+        ),
+        (
+            r#"
+contract Foo {
+    function bar(address target) public {
+        try Foo(target).bar(target) {
+        } catch (bytes memory data) {
+            da/*caret*/ta;
+        }
+    }
+}
+"#,
+            "data",
+            LocalDefKind::Parameter,
+        ),
+        (
+            r#"
+contract Foo {
+    modifier guard() {
+        assembly {
+            let slot := 0
+            sstore(sl/*caret*/ot, 1)
+        }
+        _;
+    }
+}
+"#,
+            "slot",
+            LocalDefKind::YulVariable,
+        ),
+    ];
+
+    for (source, expected_name, expected_kind) in cases {
+        let (text, offset) = extract_offset(source);
+        let files = vec![(NormalizedPath::new("/workspace/src/Main.sol"), text.clone())];
+        let (db, project_id, snapshot) = setup_db(files, vec![]);
+        let file_id = snapshot
+            .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+            .expect("file id");
+
+        let (kind, range, name) = resolve_local(&db, project_id, file_id, offset);
+        assert_eq!(kind, expected_kind, "{expected_name}");
+        assert_eq!(name, expected_name);
+        let expected = sa_test_support::find_range(&text, expected_name);
+        assert_eq!(range, expected, "{expected_name}");
+    }
+}
@@ -719,3 +719,117 @@ contract Main {
     let semantics = Semantics::new(&db, project_id);
     assert!(semantics.source_to_def(main_id, offset).is_none());
 }
+
+#[test]
+fn resolve_member_types_receivers_through_chains() {
+    let (text, offsets) = extract_offsets(
+        r#"
+interface IERC20 {
+    function balanceOf(address owner) external view returns (uint256);
+}
+
+contract Vault {
+    IERC20 public asset;
+    function token() external view returns (IERC20) { return asset; }
+}
+
+struct Position {
+    Vault vault;
+}
+
+contract Base {
+    function run() public virtual {}
+}
+
+contract Main is Base {
+    mapping(address => Vault) vaults;
+
+    function run() public override {
+        super.ru/*super*/n();
+        this.ru/*this*/n();
+    }
+
+    function check(IERC20 token, Position memory position) public view {
+        token.balance/*param*/Of(msg.sender);
+        vaults[msg.sender].tok/*index*/en().balance/*call*/Of(msg.sender);
+        position.vault.ass/*field*/et();
+        IERC20(address(0)).balance/*cast*/Of(msg.sender);
+    }
+}
+"#,
+        &[
+            "/*super*/",
+            "/*this*/",
+            "/*param*/",
+            "/*index*/",
+            "/*call*/",
+            "/*field*/",
+            "/*cast*/",
+        ],
+    );
+    let files = vec![(NormalizedPath::new("/workspace/src/Main.sol"), text)];
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = file_id(&snapshot, "/workspace/src/Main.sol");
+    let semantics = Semantics::new(&db, project_id);
+    let program = sa_hir::lowered_program(&db, project_id);
+
+    let expected = [
+        ("run", "Base"),
+        ("run", "Main"),
+        ("balanceOf", "IERC20"),
+        ("token", "Vault"),
+        ("balanceOf", "IERC20"),
+        ("asset", "Vault"),
+        ("balanceOf", "IERC20"),
+    ];
+    for (offset, (name, container)) in offsets.into_iter().zip(expected) {
+        let def = semantics
+            .resolve_member(main_id, offset)
+            .unwrap_or_else(|| panic!("member {container}.{name}"));
+        let entry = program.def_map().entry(def).expect("entry");
+        assert_eq!(entry.location().name(), name);
+        assert_eq!(entry.container(), Some(container));
+    }
+}
+
+#[test]
+fn source_to_def_resolves_struct_fields_and_enum_variants() {
+    let (text, offsets) = extract_offsets(
+        r#"
+struct Point {
+    uint256 x;
+    uint256 y;
+}
+
+contract Main {
+    enum Kind { A, B }
+
+    function run(Point memory point) public pure returns (Kind) {
+        point.y/*field*/;
+        return Kind.B/*variant*/;
+    }
+}
+"#,
+        &["/*field*/", "/*variant*/"],
+    );
+    let files = vec![(NormalizedPath::new("/workspace/src/Main.sol"), text)];
+    let (db, project_id, snapshot) = setup_db(files, vec![]);
+    let main_id = file_id(&snapshot, "/workspace/src/Main.sol");
+    let semantics = Semantics::new(&db, project_id);
+    let program = sa_hir::lowered_program(&db, project_id);
+
+    let expected = [
+        ("y", DefKind::Field, "Point"),
+        ("B", DefKind::Variant, "Kind"),
+    ];
+    for (offset, (name, kind, container)) in offsets.into_iter().zip(expected) {
+        let offset = offset - TextSize::from(1);
+        let def = semantics
+            .source_to_def(main_id, offset)
+            .unwrap_or_else(|| panic!("definition of {container}.{name}"));
+        let entry = program.def_map().entry(def).expect("entry");
+        assert_eq!(entry.location().name(), name);
+        assert_eq!(entry.kind(), kind);
+        assert_eq!(entry.container(), Some(container));
+    }
+}
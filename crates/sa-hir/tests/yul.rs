@@ -0,0 +1,84 @@
+use sa_hir::{YulDefKind, YulResolution, yul_hir};
+use sa_paths::NormalizedPath;
+use sa_span::{TextRange, TextSize};
+use sa_test_support::setup_db;
+
+fn range_of(text: &str, needle: &str, nth: usize) -> TextRange {
+    let start = text
+        .match_indices(needle)
+        .nth(nth)
+        .map(|(idx, _)| idx)
+        .unwrap_or_else(|| panic!("missing {needle}"));
+    TextRange::new(
+        TextSize::from(start as u32),
+        TextSize::from((start + needle.len()) as u32),
+    )
+}
+
+#[test]
+fn yul_hir_scopes_and_resolves_assembly_names() {
+    let text = r#"
+contract Main {
+    uint256 counter;
+
+    function run(uint256 amount) public returns (uint256 out) {
+        assembly {
+            let doubled := twice(amount)
+            function twice(v) -> r {
+                r := add(v, v)
+                r := add(r, amount)
+            }
+            sstore(counter.slot, doubled)
+            out := doubled
+        }
+    }
+}
+"#
+    .to_string();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (db, _, snapshot) = setup_db(vec![(path.clone(), text.clone())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let yul = yul_hir(&db, file_id);
+    assert_eq!(yul.blocks().len(), 1);
+    let block = &yul.blocks()[0];
+
+    let defs = block
+        .defs()
+        .iter()
+        .map(|def| (def.name(), def.kind()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        defs,
+        vec![
+            ("twice", YulDefKind::Function),
+            ("doubled", YulDefKind::Variable),
+            ("v", YulDefKind::Parameter),
+            ("r", YulDefKind::Return),
+        ]
+    );
+    let twice = yul.def(range_of(&text, "twice", 1)).expect("twice");
+    assert_eq!(twice.label(), "function twice(v) -> r");
+
+    let resolution_at = |needle: &str, nth: usize| {
+        yul.reference_at(range_of(&text, needle, nth).start())
+            .expect("reference")
+            .resolution()
+    };
+    // Called before its definition.
+    assert_eq!(
+        resolution_at("twice", 0),
+        YulResolution::Yul(range_of(&text, "twice", 1))
+    );
+    assert_eq!(resolution_at("amount", 1), YulResolution::External);
+    assert_eq!(resolution_at("add", 0), YulResolution::Builtin);
+    // Yul functions cannot see Solidity variables.
+    assert_eq!(resolution_at("amount", 2), YulResolution::Unresolved);
+    assert_eq!(resolution_at("out", 1), YulResolution::External);
+
+    let counter = yul
+        .reference_at(range_of(&text, "counter", 1).start())
+        .expect("counter reference");
+    assert_eq!(counter.resolution(), YulResolution::External);
+    assert_eq!(counter.suffix(), Some("slot"));
+}
@@ -0,0 +1,323 @@
+use sa_base_db::FileId;
+use sa_span::{LineIndex, TextRange, TextSize};
+use sa_syntax::cst::{SyntaxKind, parse_cst};
+
+use crate::{SourceChange, TextEdit};
+
+/// Collects the edits of one file. Items are written at column zero, with nested lines indented
+/// by [`EditBuilder::indent_unit`]; the builder indents them to where they are inserted.
+///
+/// Edits are expressed against the original text and must not overlap.
+pub struct EditBuilder<'a> {
+    text: &'a str,
+    line_index: LineIndex,
+    indent_unit: String,
+    edits: Vec<TextEdit>,
+}
+
+impl<'a> EditBuilder<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            line_index: LineIndex::new(text),
+            indent_unit: detect_indent_unit(text),
+            edits: Vec::new(),
+        }
+    }
+
+    /// One level of indentation as the file uses it; four spaces when the file has none.
+    pub fn indent_unit(&self) -> &str {
+        &self.indent_unit
+    }
+
+    pub fn insert(&mut self, offset: TextSize, new_text: impl Into<String>) {
+        self.push(TextRange::empty(offset), new_text.into());
+    }
+
+    pub fn delete(&mut self, range: TextRange) {
+        self.push(range, String::new());
+    }
+
+    /// Replaces `range`, trimmed to the part that actually changes.
+    pub fn replace(&mut self, range: TextRange, new_text: impl Into<String>) {
+        let new_text = new_text.into();
+        let Some(old) = self.slice(range) else {
+            return;
+        };
+        let prefix = common_prefix_len(old, &new_text);
+        let suffix = common_suffix_len(&old[prefix..], &new_text[prefix..]);
+        let start = range.start() + TextSize::from(prefix as u32);
+        let end = range.end() - TextSize::from(suffix as u32);
+        self.push(
+            TextRange::new(start, end),
+            new_text[prefix..new_text.len() - suffix].to_string(),
+        );
+    }
+
+    /// Replaces the node at `range` with `new_text`, indenting its continuation lines like the
+    /// line the node starts on.
+    pub fn replace_node(&mut self, range: TextRange, new_text: &str) {
+        let indent = self.line_indent(range.start());
+        let new_text = indent_lines(new_text, indent, false);
+        self.replace(range, new_text);
+    }
+
+    /// Inserts `item` on its own lines before the line containing `offset`, indented like it.
+    pub fn insert_item(&mut self, offset: TextSize, item: &str) {
+        let line_start = self.line_index.line_start(offset);
+        let indent_len = leading_ws_len(&self.text[usize::from(line_start)..]);
+        let indent = self.line_indent(line_start + TextSize::from(indent_len as u32));
+        let new_text = format!("{}\n", indent_lines(item, indent, true));
+        self.insert(line_start, new_text);
+    }
+
+    /// Adds the import directive `import` after the last import, or after the pragmas when the
+    /// file has no imports. Does nothing when the file already has the same directive.
+    pub fn add_import(&mut self, import: &str) {
+        let cst = parse_cst(self.text);
+        let root = cst.root();
+        let imports = root
+            .child_nodes()
+            .filter(|node| node.kind() == SyntaxKind::Import)
+            .collect::<Vec<_>>();
+        if imports
+            .iter()
+            .any(|node| normalize_ws(&node.text()) == normalize_ws(import))
+        {
+            return;
+        }
+
+        if let Some(last) = imports.last() {
+            self.insert(last.range().end(), format!("\n{import}"));
+        } else if let Some(pragma) = root
+            .child_nodes()
+            .filter(|node| node.kind() == SyntaxKind::Pragma)
+            .last()
+        {
+            self.insert(pragma.range().end(), format!("\n\n{import}"));
+        } else {
+            self.insert(TextSize::from(0), format!("{import}\n\n"));
+        }
+    }
+
+    /// Appends `function` to the end of contract `contract`, separated from the previous member
+    /// by a blank line. Returns `false` when the contract is missing or its body is unclosed.
+    pub fn add_function_to_contract(&mut self, contract: &str, function: &str) -> bool {
+        let cst = parse_cst(self.text);
+        let Some(node) = cst.contract_by_name(contract) else {
+            return false;
+        };
+        let Some(close) = node
+            .child_node(SyntaxKind::ContractBody)
+            .filter(|body| body.is_closed())
+            .and_then(|body| body.last_token())
+        else {
+            return false;
+        };
+
+        let contract_indent = self.line_indent(node.range().start());
+        let indent = format!("{contract_indent}{}", self.indent_unit);
+        let new_text = format!("\n{}\n", indent_lines(function, &indent, true));
+        self.insert(close.range().start(), new_text);
+        true
+    }
+
+    /// The whitespace `offset` is preceded by on its line, or the indentation of its line when
+    /// there is code before it.
+    fn line_indent(&self, offset: TextSize) -> &'a str {
+        let offset = offset.min(self.line_index.len());
+        let line_start = self.line_index.line_start(offset);
+        let prefix = &self.text[usize::from(line_start)..usize::from(offset)];
+        if prefix.chars().all(|ch| ch == ' ' || ch == '\t') {
+            prefix
+        } else {
+            &prefix[..leading_ws_len(prefix)]
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<TextEdit> {
+        self.edits.sort_by_key(|edit| edit.range.start());
+        self.edits
+    }
+
+    pub fn into_source_change(self, file_id: FileId) -> SourceChange {
+        let mut change = SourceChange::default();
+        for edit in self.finish() {
+            change.insert_edit(file_id, edit);
+        }
+        change
+    }
+
+    fn push(&mut self, range: TextRange, new_text: String) {
+        if range.is_empty() && new_text.is_empty() {
+            return;
+        }
+        self.edits.push(TextEdit { range, new_text });
+    }
+
+    fn slice(&self, range: TextRange) -> Option<&'a str> {
+        self.text
+            .get(usize::from(range.start())..usize::from(range.end()))
+    }
+}
+
+fn detect_indent_unit(text: &str) -> String {
+    let mut spaces = None::<usize>;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('\t') {
+            return "\t".to_string();
+        }
+        let width = line.len() - line.trim_start_matches(' ').len();
+        // Odd widths are usually the ` * ` continuation of a block comment.
+        if width > 0 && width % 2 == 0 {
+            spaces = Some(spaces.map_or(width, |spaces| spaces.min(width)));
+        }
+    }
+    " ".repeat(spaces.unwrap_or(4))
+}
+
+fn leading_ws_len(text: &str) -> usize {
+    text.len() - text.trim_start_matches([' ', '\t']).len()
+}
+
+/// Prefixes the non-empty lines of `text` with `indent`, the first one only if `first` is set.
+fn indent_lines(text: &str, indent: &str, first: bool) -> String {
+    text.split('\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            if line.is_empty() || (idx == 0 && !first) {
+                line.to_string()
+            } else {
+                format!("{indent}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn normalize_ws(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, left), right)| left != right)
+        .map_or(a.len().min(b.len()), |((idx, _), _)| idx)
+}
+
+fn common_suffix_len(a: &str, b: &str) -> usize {
+    let mut len = 0;
+    for (left, right) in a.chars().rev().zip(b.chars().rev()) {
+        if left != right {
+            break;
+        }
+        len += left.len_utf8();
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use sa_span::{TextRange, TextSize};
+
+    use super::EditBuilder;
+    use crate::TextEdit;
+
+    fn apply(text: &str, edits: &[TextEdit]) -> String {
+        let mut result = text.to_string();
+        for edit in edits.iter().rev() {
+            let start = usize::from(edit.range.start());
+            let end = usize::from(edit.range.end());
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    fn range(text: &str, needle: &str) -> TextRange {
+        let start = text.find(needle).expect("needle");
+        TextRange::new(
+            TextSize::from(start as u32),
+            TextSize::from((start + needle.len()) as u32),
+        )
+    }
+
+    #[test]
+    fn replace_only_touches_what_changes() {
+        let text = "uint256 total = 1;";
+        let mut builder = EditBuilder::new(text);
+        builder.replace(range(text, "total = 1"), "total = 2");
+        let edits = builder.finish();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range, range(text, "1"));
+        assert_eq!(edits[0].new_text, "2");
+
+        let mut builder = EditBuilder::new(text);
+        builder.replace(range(text, "total"), "total");
+        assert!(builder.finish().is_empty());
+    }
+
+    #[test]
+    fn functions_are_appended_with_the_contract_indentation() {
+        let text = "contract A {\n\tuint256 x;\n}\n\ncontract B {}\n";
+        let mut builder = EditBuilder::new(text);
+        assert_eq!(builder.indent_unit(), "\t");
+        let function = format!(
+            "function f() public {{\n{}x = 1;\n}}",
+            builder.indent_unit()
+        );
+        assert!(builder.add_function_to_contract("A", &function));
+        assert!(builder.add_function_to_contract("B", "function g() public {}"));
+        assert!(!builder.add_function_to_contract("C", "function h() public {}"));
+        assert_eq!(
+            apply(text, &builder.finish()),
+            "contract A {\n\tuint256 x;\n\n\tfunction f() public {\n\t\tx = 1;\n\t}\n}\n\ncontract B {\n\tfunction g() public {}\n}\n"
+        );
+    }
+
+    #[test]
+    fn functions_go_after_bodies_with_yul_functions() {
+        let text = "contract A {\n    function f() public {\n        assembly {\n            function g() {}\n        }\n    }\n}\n";
+        let mut builder = EditBuilder::new(text);
+        assert!(builder.add_function_to_contract("A", "function h() public {}"));
+        assert_eq!(
+            apply(text, &builder.finish()),
+            "contract A {\n    function f() public {\n        assembly {\n            function g() {}\n        }\n    }\n\n    function h() public {}\n}\n"
+        );
+    }
+
+    #[test]
+    fn imports_go_after_existing_imports_or_pragmas() {
+        let text = "pragma solidity ^0.8.0;\n\nimport \"./A.sol\";\n\ncontract C {}\n";
+        let mut builder = EditBuilder::new(text);
+        builder.add_import("import \"./B.sol\";");
+        builder.add_import("import   \"./A.sol\";");
+        assert_eq!(
+            apply(text, &builder.finish()),
+            "pragma solidity ^0.8.0;\n\nimport \"./A.sol\";\nimport \"./B.sol\";\n\ncontract C {}\n"
+        );
+
+        let text = "pragma solidity ^0.8.0;\n\ncontract C {}\n";
+        let mut builder = EditBuilder::new(text);
+        builder.add_import("import \"./B.sol\";");
+        assert_eq!(
+            apply(text, &builder.finish()),
+            "pragma solidity ^0.8.0;\n\nimport \"./B.sol\";\n\ncontract C {}\n"
+        );
+    }
+
+    #[test]
+    fn items_and_nodes_keep_the_surrounding_indentation() {
+        let text = "contract A {\n    function f() public {\n        g();\n    }\n}\n";
+        let mut builder = EditBuilder::new(text);
+        builder.insert_item(range(text, "g();").start(), "uint256 y;");
+        builder.replace_node(range(text, "g();"), "if (y > 0) {\n    g();\n}");
+        assert_eq!(
+            apply(text, &builder.finish()),
+            "contract A {\n    function f() public {\n        uint256 y;\n        if (y > 0) {\n            g();\n        }\n    }\n}\n"
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use sa_base_db::FileId;
+use sa_paths::NormalizedPath;
 use sa_span::TextRange;
 
+mod edit_builder;
 mod lint_fixes;
 
+pub use edit_builder::EditBuilder;
 pub use lint_fixes::{LintFix, LintFixKind, is_fixable_lint, lint_fix};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,9 +20,24 @@ pub struct SourceFileEdit {
     pub edits: Vec<TextEdit>,
 }
 
+/// A file created or moved as part of a [`SourceChange`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSystemEdit {
+    CreateFile {
+        path: NormalizedPath,
+        text: String,
+    },
+    /// Moves `file_id` to `destination` after its text edits are applied.
+    MoveFile {
+        file_id: FileId,
+        destination: NormalizedPath,
+    },
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SourceChange {
     edits: Vec<SourceFileEdit>,
+    file_system_edits: Vec<FileSystemEdit>,
 }
 
 impl SourceChange {
@@ -27,8 +45,26 @@ impl SourceChange {
         &self.edits
     }
 
+    pub fn file_system_edits(&self) -> &[FileSystemEdit] {
+        &self.file_system_edits
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.edits.is_empty()
+        self.edits.is_empty() && self.file_system_edits.is_empty()
+    }
+
+    pub fn create_file(&mut self, path: NormalizedPath, text: impl Into<String>) {
+        self.file_system_edits.push(FileSystemEdit::CreateFile {
+            path,
+            text: text.into(),
+        });
+    }
+
+    pub fn move_file(&mut self, file_id: FileId, destination: NormalizedPath) {
+        self.file_system_edits.push(FileSystemEdit::MoveFile {
+            file_id,
+            destination,
+        });
     }
 
     pub fn insert_edit(&mut self, file_id: FileId, edit: TextEdit) {
@@ -3,17 +3,19 @@ use std::collections::{HashMap, HashSet};
 use sa_base_db::{FileId, ProjectId};
 use sa_def::DefKind;
 use sa_hir::{
-    HirDatabase, contract_member_definitions_at_offset, local_scopes, lowered_program,
-    visible_definitions,
+    HirDatabase, YUL_BUILTINS, YulDefKind, contract_base_paths_in_parse,
+    contract_member_definitions_at_offset, linearized_bases, local_scopes, lowered_program,
+    visible_definitions_with_prefix, yul_hir,
 };
 use sa_paths::NormalizedPath;
 use sa_project_model::{FoundryResolver, resolve_import_path_with_resolver};
 use sa_sema::{SemaCompletionItem, SemaCompletionKind};
-use sa_span::{TextRange, TextSize, is_ident_byte, range_contains};
+use sa_span::{LineIndex, TextRange, TextSize, is_ident_byte, range_contains};
 use sa_syntax::ast::{
     ContractKind, DataLocation, ElementaryType, Item, ItemKind, Stmt, StmtKind, TypeKind,
     VariableDefinition, Visibility, interface::SpannedOption,
 };
+use sa_syntax::cst::{Cst, SyntaxKind, SyntaxNode, SyntaxToken, parse_cst};
 use sa_syntax::{Parse, parse_file};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -72,10 +74,24 @@ pub fn completions(
                 restricted_handled = true;
                 items
             } else {
-                sema_identifier_items(db, project_id, file_id, offset, context.range)
-                    .unwrap_or_else(|| {
-                        identifier_items(db, project_id, file_id, offset, context.range)
-                    })
+                sema_identifier_items(
+                    db,
+                    project_id,
+                    file_id,
+                    offset,
+                    &context.prefix,
+                    context.range,
+                )
+                .unwrap_or_else(|| {
+                    identifier_items(
+                        db,
+                        project_id,
+                        file_id,
+                        offset,
+                        &context.prefix,
+                        context.range,
+                    )
+                })
             }
         }
         CompletionContextKind::Member {
@@ -247,9 +263,67 @@ fn restricted_identifier_items(
     if using_brace_context(text, offset) {
         return Some(Vec::new());
     }
+    assembly_identifier_items(db, project_id, file_id, offset, range)
+}
 
-    let _ = (db, project_id, file_id);
-    None
+/// Inside `assembly` only Yul definitions, builtins and Solidity variables can be named.
+fn assembly_identifier_items(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+    offset: TextSize,
+    range: TextRange,
+) -> Option<Vec<CompletionItem>> {
+    let yul = yul_hir(db, file_id);
+    let block = yul.block_at(offset)?;
+    let mut items = Vec::new();
+    let mut seen = HashSet::new();
+
+    for def in block.visible_defs(offset) {
+        let kind = match def.kind() {
+            YulDefKind::Function => CompletionItemKind::Function,
+            _ => CompletionItemKind::Variable,
+        };
+        push_completion_item(def.name(), kind, range, &mut items, &mut seen);
+    }
+
+    if block.sees_solidity(offset) {
+        let locals = local_scopes(db, file_id);
+        for local in locals.defs() {
+            if !local.kind().is_yul() && local_def_in_scope(local, offset) {
+                push_completion_item(
+                    local.name(),
+                    CompletionItemKind::Variable,
+                    range,
+                    &mut items,
+                    &mut seen,
+                );
+            }
+        }
+        for def in contract_member_definitions_at_offset(db, project_id, file_id, offset) {
+            if def.kind() == DefKind::Variable {
+                push_completion_item(
+                    def.name(),
+                    CompletionItemKind::Variable,
+                    range,
+                    &mut items,
+                    &mut seen,
+                );
+            }
+        }
+    }
+
+    for builtin in YUL_BUILTINS {
+        push_completion_item(
+            builtin,
+            CompletionItemKind::Function,
+            range,
+            &mut items,
+            &mut seen,
+        );
+    }
+
+    Some(items)
 }
 
 fn completion_items_from_names(
@@ -267,7 +341,7 @@ fn completion_items_from_names(
 
 fn struct_literal_field_items(text: &str, offset: TextSize) -> Option<Vec<String>> {
     let mut struct_fields = HashMap::new();
-    collect_fallback_struct_fields(text, &mut struct_fields);
+    collect_fallback_struct_fields(&parse_cst(text), &mut struct_fields);
     struct_literal_fields_at_offset(text, offset, &struct_fields)
 }
 
@@ -297,7 +371,7 @@ fn named_args_items(text: &str, offset: TextSize) -> Option<Vec<String>> {
     }
     if let Some((struct_name, _)) = struct_literal_name_at_offset(text, offset) {
         let mut struct_fields = HashMap::new();
-        collect_fallback_struct_fields(text, &mut struct_fields);
+        collect_fallback_struct_fields(&parse_cst(text), &mut struct_fields);
         if struct_fields.contains_key(&struct_name) {
             return None;
         }
@@ -318,14 +392,17 @@ fn override_list_items(text: &str, offset: TextSize) -> Option<Vec<String>> {
     let Some(contract_name) = contract_name_at_offset(text, &parse, offset) else {
         return Some(Vec::new());
     };
-    let mut bases = contract_bases_in_parse(&parse, &contract_name)
+    let mut paths = contract_base_paths_in_parse(&parse, &contract_name);
+    if paths.is_empty()
+        && let Some(contract) = parse_cst(text).contract_by_name(&contract_name)
+    {
+        paths = contract.base_paths();
+    }
+    let bases = paths
         .into_iter()
         .filter(|segments| !segments.is_empty())
         .map(|segments| segments.join("."))
         .collect::<Vec<_>>();
-    if bases.is_empty() {
-        bases = contract_bases_fallback(text, &contract_name);
-    }
     let used = ident_list_before_offset(text, offset, open_paren);
     let remaining = bases
         .into_iter()
@@ -700,142 +777,10 @@ fn contract_name_at_offset(text: &str, parse: &Parse, offset: TextSize) -> Optio
         return Some(name);
     }
 
-    let limit = usize::from(offset).min(text.len());
-    let prefix = &text[..limit];
-    let mut lexer = FallbackLexer::new(prefix);
-    let mut brace_depth = 0usize;
-    let mut expect_contract_name = false;
-    let mut pending_contract: Option<String> = None;
-    let mut contract_body_depth: Option<usize> = None;
-    let mut current_contract: Option<String> = None;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if expect_contract_name {
-                    pending_contract = Some(ident);
-                    expect_contract_name = false;
-                    continue;
-                }
-                if brace_depth == 0
-                    && matches!(ident.as_str(), "contract" | "interface" | "library")
-                {
-                    expect_contract_name = true;
-                }
-            }
-            FallbackToken::Punct(punct) => match punct {
-                '{' => {
-                    brace_depth += 1;
-                    if let Some(name) = pending_contract.take() {
-                        contract_body_depth = Some(brace_depth);
-                        current_contract = Some(name);
-                    }
-                }
-                '}' => {
-                    if let Some(depth) = contract_body_depth
-                        && brace_depth == depth
-                    {
-                        contract_body_depth = None;
-                        current_contract = None;
-                    }
-                    brace_depth = brace_depth.saturating_sub(1);
-                }
-                _ => {}
-            },
-        }
-    }
-
-    current_contract
-}
-
-fn contract_bases_fallback(text: &str, target_name: &str) -> Vec<String> {
-    let mut lexer = FallbackLexer::new(text);
-    let mut bases = Vec::new();
-    let mut expect_name = false;
-    let mut in_target = false;
-    let mut collecting_bases = false;
-    let mut current = String::new();
-    let mut paren_depth = 0usize;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if expect_name {
-                    in_target = ident == target_name;
-                    expect_name = false;
-                    collecting_bases = false;
-                    current.clear();
-                    paren_depth = 0;
-                    continue;
-                }
-
-                if matches!(ident.as_str(), "contract" | "interface" | "library") {
-                    expect_name = true;
-                    continue;
-                }
-
-                if in_target {
-                    if ident == "is" {
-                        collecting_bases = true;
-                        continue;
-                    }
-                    if collecting_bases
-                        && paren_depth == 0
-                        && (current.is_empty() || current.ends_with('.'))
-                    {
-                        current.push_str(&ident);
-                    }
-                }
-            }
-            FallbackToken::Punct(punct) => {
-                if !in_target {
-                    continue;
-                }
-                match punct {
-                    '{' => {
-                        if !current.is_empty() && !current.ends_with('.') {
-                            bases.push(current.clone());
-                        }
-                        break;
-                    }
-                    ',' => {
-                        if collecting_bases && paren_depth == 0 {
-                            if !current.is_empty() && !current.ends_with('.') {
-                                bases.push(current.clone());
-                            }
-                            current.clear();
-                        }
-                    }
-                    '.' => {
-                        if collecting_bases
-                            && paren_depth == 0
-                            && !current.is_empty()
-                            && !current.ends_with('.')
-                        {
-                            current.push('.');
-                        }
-                    }
-                    '(' => {
-                        if collecting_bases {
-                            paren_depth += 1;
-                            if !current.is_empty() && !current.ends_with('.') {
-                                bases.push(current.clone());
-                            }
-                            current.clear();
-                        }
-                    }
-                    ')' => {
-                        if collecting_bases && paren_depth > 0 {
-                            paren_depth -= 1;
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-    }
-
-    bases
+    parse_cst(text)
+        .contract_at_offset(offset)
+        .and_then(|contract| contract.name())
+        .map(|name| name.text().to_string())
 }
 
 struct CompletionContext {
@@ -875,12 +820,13 @@ fn identifier_items(
     project_id: ProjectId,
     file_id: FileId,
     offset: TextSize,
+    prefix: &str,
     range: TextRange,
 ) -> Vec<CompletionItem> {
     let mut seen = HashSet::new();
     let mut items = Vec::new();
 
-    for def in visible_definitions(db, project_id, file_id) {
+    for def in visible_definitions_with_prefix(db, project_id, file_id, prefix) {
         push_completion_item(
             def.name(),
             completion_kind(def.kind()),
@@ -925,69 +871,35 @@ fn fallback_identifier_items(
     let mut seen = HashSet::new();
     let mut known_types = HashSet::new();
 
-    collect_fallback_imports(text, &mut known_types, &mut items, &mut seen, range);
-    collect_fallback_type_defs(text, &mut known_types, &mut items, &mut seen, range);
+    let cst = parse_cst(text);
+    collect_fallback_imports(&cst, &mut known_types, &mut items, &mut seen, range);
+    collect_fallback_type_defs(&cst, &mut known_types, &mut items, &mut seen, range);
     collect_fallback_locals(text, offset, &known_types, &mut items, &mut seen, range);
 
     items
 }
 
-fn collect_fallback_struct_fields(text: &str, structs: &mut HashMap<String, Vec<String>>) {
-    let mut lexer = FallbackLexer::new(text);
-    let mut brace_depth = 0usize;
-    let mut expect_struct_name = false;
-    let mut pending_struct: Option<String> = None;
-    let mut struct_body_depth: Option<usize> = None;
-    let mut current_struct: Option<String> = None;
-    let mut statement_idents: Vec<String> = Vec::new();
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if expect_struct_name {
-                    pending_struct = Some(ident);
-                    expect_struct_name = false;
-                    continue;
-                }
-                if ident == "struct" && struct_body_depth.is_none() {
-                    expect_struct_name = true;
-                    continue;
-                }
-                if struct_body_depth.is_some() && brace_depth == struct_body_depth.unwrap() {
-                    statement_idents.push(ident);
-                }
-            }
-            FallbackToken::Punct(punct) => match punct {
-                '{' => {
-                    brace_depth += 1;
-                    if let Some(name) = pending_struct.take() {
-                        struct_body_depth = Some(brace_depth);
-                        structs.entry(name.clone()).or_default();
-                        current_struct = Some(name);
-                        statement_idents.clear();
-                    }
-                }
-                '}' => {
-                    if struct_body_depth.is_some() && brace_depth == struct_body_depth.unwrap() {
-                        struct_body_depth = None;
-                        current_struct = None;
-                        statement_idents.clear();
-                    }
-                    brace_depth = brace_depth.saturating_sub(1);
-                }
-                ';' => {
-                    if let Some(body_depth) = struct_body_depth
-                        && brace_depth == body_depth
-                        && let Some(name) = statement_idents.last()
-                        && let Some(struct_name) = current_struct.as_ref()
-                        && let Some(fields) = structs.get_mut(struct_name)
-                    {
-                        fields.push(name.clone());
+fn collect_fallback_struct_fields(cst: &Cst, structs: &mut HashMap<String, Vec<String>>) {
+    for node in cst.root().descendants() {
+        if node.kind() != SyntaxKind::Struct {
+            continue;
+        }
+        let (Some(name), Some(body)) = (node.name(), node.child_node(SyntaxKind::Block)) else {
+            continue;
+        };
+        let fields = structs.entry(name.text().to_string()).or_default();
+        // A field is named by the last identifier before its `;`.
+        let mut last_ident = None;
+        for token in body.tokens() {
+            match token.kind() {
+                SyntaxKind::Ident => last_ident = Some(token.text()),
+                SyntaxKind::Semicolon => {
+                    if let Some(field) = last_ident.take() {
+                        fields.push(field.to_string());
                     }
-                    statement_idents.clear();
                 }
                 _ => {}
-            },
+            }
         }
     }
 }
@@ -1193,138 +1105,71 @@ fn named_fields_before_offset(text: &str, offset: TextSize, open_brace: usize) -
 }
 
 fn collect_fallback_imports(
-    text: &str,
+    cst: &Cst,
     known_types: &mut HashSet<String>,
     items: &mut Vec<CompletionItem>,
     seen: &mut HashSet<(String, CompletionItemKind)>,
     range: TextRange,
 ) {
-    let mut lexer = FallbackLexer::new(text);
-    let mut in_import = false;
-    let mut in_braces = false;
-    let mut pending_name: Option<String> = None;
-    let mut expect_alias = false;
-    let mut saw_star = false;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if !in_import {
-                    if ident == "import" {
-                        in_import = true;
-                        in_braces = false;
-                        pending_name = None;
-                        expect_alias = false;
-                        saw_star = false;
-                    }
-                    continue;
-                }
-
-                if ident == "as" {
-                    expect_alias = true;
-                    continue;
-                }
-                if ident == "from" {
-                    continue;
-                }
-
-                if in_braces {
-                    if expect_alias {
-                        push_fallback_type(&ident, known_types, items, seen, range);
-                        pending_name = None;
-                        expect_alias = false;
-                    } else {
-                        if let Some(name) = pending_name.take() {
-                            push_fallback_type(&name, known_types, items, seen, range);
-                        }
-                        pending_name = Some(ident);
+    for import in cst.root().descendants() {
+        if import.kind() != SyntaxKind::Import {
+            continue;
+        }
+        match import.child_node(SyntaxKind::Block) {
+            // `import {A, B as C} from "..."` binds `A` and `C`.
+            Some(symbols) => {
+                let tokens = symbols.tokens().collect::<Vec<_>>();
+                for symbol in tokens.split(|token| token.kind() == SyntaxKind::Comma) {
+                    let name = import_alias(symbol).or_else(|| {
+                        symbol
+                            .iter()
+                            .find(|token| token.kind() == SyntaxKind::Ident)
+                            .map(|token| token.text())
+                    });
+                    if let Some(name) = name {
+                        push_fallback_type(name, known_types, items, seen, range);
                     }
-                } else if expect_alias {
-                    push_fallback_type(&ident, known_types, items, seen, range);
-                    expect_alias = false;
-                } else if saw_star {
-                    continue;
                 }
             }
-            FallbackToken::Punct(punct) => match punct {
-                '{' => {
-                    if in_import {
-                        in_braces = true;
-                        pending_name = None;
-                        expect_alias = false;
-                    }
-                }
-                '}' => {
-                    if in_braces {
-                        if let Some(name) = pending_name.take() {
-                            push_fallback_type(&name, known_types, items, seen, range);
-                        }
-                        in_braces = false;
-                    }
-                }
-                ',' => {
-                    if in_braces {
-                        if let Some(name) = pending_name.take() {
-                            push_fallback_type(&name, known_types, items, seen, range);
-                        }
-                        expect_alias = false;
-                    }
-                }
-                '*' => {
-                    if in_import {
-                        saw_star = true;
-                    }
-                }
-                ';' => {
-                    if in_import {
-                        if let Some(name) = pending_name.take() {
-                            push_fallback_type(&name, known_types, items, seen, range);
-                        }
-                        in_import = false;
-                        in_braces = false;
-                        expect_alias = false;
-                        saw_star = false;
-                    }
+            // `import "..." as A` and `import * as A from "..."` bind `A`.
+            None => {
+                let tokens = import.tokens().collect::<Vec<_>>();
+                if let Some(name) = import_alias(&tokens) {
+                    push_fallback_type(name, known_types, items, seen, range);
                 }
-                _ => {}
-            },
+            }
         }
     }
 }
 
+/// The identifier after `as` in `tokens`.
+fn import_alias<'a>(tokens: &[&'a SyntaxToken]) -> Option<&'a str> {
+    tokens
+        .iter()
+        .skip_while(|token| token.text() != "as")
+        .nth(1)
+        .filter(|token| token.kind() == SyntaxKind::Ident)
+        .map(|token| token.text())
+}
+
 fn collect_fallback_type_defs(
-    text: &str,
+    cst: &Cst,
     known_types: &mut HashSet<String>,
     items: &mut Vec<CompletionItem>,
     seen: &mut HashSet<(String, CompletionItemKind)>,
     range: TextRange,
 ) {
-    let mut lexer = FallbackLexer::new(text);
-    let mut brace_depth = 0usize;
-    let mut pending_kind: Option<CompletionItemKind> = None;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if let Some(kind) = pending_kind.take() {
-                    if is_type_def_kind(kind) {
-                        known_types.insert(ident.clone());
-                    }
-                    push_completion_item(&ident, kind, range, items, seen);
-                    continue;
-                }
-
-                if brace_depth <= 1
-                    && let Some(kind) = type_def_keyword_kind(&ident)
-                {
-                    pending_kind = Some(kind);
-                }
-            }
-            FallbackToken::Punct(punct) => match punct {
-                '{' => brace_depth += 1,
-                '}' => brace_depth = brace_depth.saturating_sub(1),
-                _ => {}
-            },
+    for node in cst.root().descendants() {
+        let kind = match node.kind() {
+            SyntaxKind::Contract => CompletionItemKind::Contract,
+            SyntaxKind::Struct => CompletionItemKind::Struct,
+            SyntaxKind::Enum => CompletionItemKind::Enum,
+            SyntaxKind::Udvt => CompletionItemKind::Type,
+            _ => continue,
+        };
+        if let Some(name) = node.name() {
+            known_types.insert(name.text().to_string());
+            push_completion_item(name.text(), kind, range, items, seen);
         }
     }
 }
@@ -1338,144 +1183,97 @@ fn collect_fallback_locals(
     range: TextRange,
 ) {
     let limit = usize::from(offset).min(text.len());
-    let prefix = &text[..limit];
-    let mut lexer = FallbackLexer::new(prefix);
+    // In the text before the caret, the body being typed is the last one and is still open.
+    let prefix = parse_cst(&text[..limit]);
+    let Some(function) = prefix
+        .root()
+        .descendants()
+        .into_iter()
+        .rfind(|node| matches!(node.kind(), SyntaxKind::Function | SyntaxKind::Modifier))
+    else {
+        return;
+    };
+    let Some(body) = function.child_node(SyntaxKind::Block) else {
+        return;
+    };
+    if body
+        .tokens()
+        .last()
+        .is_some_and(|token| token.kind() == SyntaxKind::RBrace)
+    {
+        return;
+    }
 
-    let mut brace_depth = 0usize;
-    let mut pending_function_body = false;
-    let mut function_body_depth: Option<usize> = None;
+    let params = function
+        .child_node(SyntaxKind::ParamList)
+        .map(|list| fallback_params(list, known_types))
+        .unwrap_or_default();
+    for name in params.into_iter().chain(fallback_locals(body, known_types)) {
+        push_completion_item(&name, CompletionItemKind::Variable, range, items, seen);
+    }
+}
 
-    let mut params_parsed = false;
-    let mut parsing_params = false;
-    let mut param_depth = 0usize;
-    let mut param_tokens: Vec<String> = Vec::new();
-    let mut pending_params: Vec<String> = Vec::new();
-    let mut current_params: Vec<String> = Vec::new();
-    let mut current_locals: Vec<String> = Vec::new();
+fn fallback_params(list: &SyntaxNode, known_types: &HashSet<String>) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut param_tokens = Vec::new();
+    let mut depth = 0usize;
+    for token in list.tokens() {
+        match token.kind() {
+            SyntaxKind::LParen => depth += 1,
+            SyntaxKind::RParen => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    push_param_tokens(&mut param_tokens, &mut params, known_types);
+                }
+            }
+            SyntaxKind::Comma if depth == 1 => {
+                push_param_tokens(&mut param_tokens, &mut params, known_types);
+            }
+            SyntaxKind::Ident if depth == 1 => param_tokens.push(token.text().to_string()),
+            _ => {}
+        }
+    }
+    // A list that is still being typed.
+    push_param_tokens(&mut param_tokens, &mut params, known_types);
+    params
+}
 
+/// The variables declared by the statements of `body`, nested blocks included.
+fn fallback_locals(body: &SyntaxNode, known_types: &HashSet<String>) -> Vec<String> {
+    let mut locals = Vec::new();
     let mut statement_idents: Vec<String> = Vec::new();
     let mut statement_type_start = false;
     let mut statement_paren_early = false;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if brace_depth <= 1 && is_function_keyword(&ident) {
-                    pending_function_body = true;
-                    params_parsed = false;
-                    parsing_params = false;
-                    param_depth = 0;
-                    param_tokens.clear();
-                    pending_params.clear();
-                    continue;
-                }
-
-                if parsing_params && param_depth == 1 {
-                    param_tokens.push(ident);
-                    continue;
-                }
-
-                if in_function_body(function_body_depth, brace_depth) {
-                    if statement_idents.is_empty() {
-                        statement_type_start = is_type_like(&ident, known_types);
-                        statement_paren_early = false;
-                    }
-                    statement_idents.push(ident);
+    for token in body.descendant_tokens() {
+        match token.kind() {
+            SyntaxKind::Ident => {
+                if statement_idents.is_empty() {
+                    statement_type_start = is_type_like(token.text(), known_types);
+                    statement_paren_early = false;
                 }
+                statement_idents.push(token.text().to_string());
             }
-            FallbackToken::Punct(punct) => match punct {
-                '(' => {
-                    if pending_function_body && !params_parsed && !parsing_params {
-                        parsing_params = true;
-                        param_depth = 1;
-                        param_tokens.clear();
-                        continue;
-                    }
-                    if parsing_params {
-                        param_depth += 1;
-                    }
-                    if in_function_body(function_body_depth, brace_depth)
-                        && statement_type_start
-                        && statement_idents.len() == 1
-                    {
-                        statement_paren_early = true;
-                    }
-                }
-                ')' => {
-                    if parsing_params {
-                        param_depth = param_depth.saturating_sub(1);
-                        if param_depth == 0 {
-                            push_param_tokens(&mut param_tokens, &mut pending_params, known_types);
-                            parsing_params = false;
-                            params_parsed = true;
-                        }
-                    }
-                }
-                ',' => {
-                    if parsing_params && param_depth == 1 {
-                        push_param_tokens(&mut param_tokens, &mut pending_params, known_types);
-                    }
-                }
-                '{' => {
-                    brace_depth += 1;
-                    if pending_function_body {
-                        function_body_depth = Some(brace_depth);
-                        pending_function_body = false;
-                        current_locals.clear();
-                        current_params = std::mem::take(&mut pending_params);
-                        statement_idents.clear();
-                        statement_type_start = false;
-                        statement_paren_early = false;
-                    }
+            SyntaxKind::LParen => {
+                if statement_type_start && statement_idents.len() == 1 {
+                    statement_paren_early = true;
                 }
-                '}' => {
-                    if function_body_depth == Some(brace_depth) {
-                        function_body_depth = None;
-                        current_locals.clear();
-                        current_params.clear();
-                    }
-                    brace_depth = brace_depth.saturating_sub(1);
-                    statement_idents.clear();
-                    statement_type_start = false;
-                    statement_paren_early = false;
-                }
-                ';' => {
-                    if pending_function_body {
-                        pending_function_body = false;
-                        params_parsed = false;
-                        parsing_params = false;
-                        param_depth = 0;
-                        param_tokens.clear();
-                        pending_params.clear();
-                    }
-                    if in_function_body(function_body_depth, brace_depth)
-                        && let Some(name) = statement_var_name(
-                            &statement_idents,
-                            statement_type_start,
-                            statement_paren_early,
-                            known_types,
-                        )
-                    {
-                        current_locals.push(name);
-                    }
-                    statement_idents.clear();
-                    statement_type_start = false;
-                    statement_paren_early = false;
+            }
+            SyntaxKind::Semicolon => {
+                if let Some(name) = statement_var_name(
+                    &statement_idents,
+                    statement_type_start,
+                    statement_paren_early,
+                    known_types,
+                ) {
+                    locals.push(name);
                 }
-                _ => {}
-            },
-        }
-    }
-
-    if in_function_body(function_body_depth, brace_depth) {
-        for name in current_params.into_iter().chain(current_locals) {
-            push_completion_item(&name, CompletionItemKind::Variable, range, items, seen);
+                statement_idents.clear();
+            }
+            SyntaxKind::LBrace | SyntaxKind::RBrace => statement_idents.clear(),
+            _ => {}
         }
     }
-}
-
-fn in_function_body(function_body_depth: Option<usize>, brace_depth: usize) -> bool {
-    function_body_depth.is_some_and(|depth| brace_depth >= depth)
+    locals
 }
 
 fn statement_var_name(
@@ -1531,33 +1329,6 @@ fn push_fallback_type(
     push_completion_item(name, CompletionItemKind::Type, range, items, seen);
 }
 
-fn type_def_keyword_kind(ident: &str) -> Option<CompletionItemKind> {
-    match ident {
-        "contract" | "library" | "interface" => Some(CompletionItemKind::Contract),
-        "struct" => Some(CompletionItemKind::Struct),
-        "enum" => Some(CompletionItemKind::Enum),
-        "type" => Some(CompletionItemKind::Type),
-        _ => None,
-    }
-}
-
-fn is_type_def_kind(kind: CompletionItemKind) -> bool {
-    matches!(
-        kind,
-        CompletionItemKind::Contract
-            | CompletionItemKind::Struct
-            | CompletionItemKind::Enum
-            | CompletionItemKind::Type
-    )
-}
-
-fn is_function_keyword(ident: &str) -> bool {
-    matches!(
-        ident,
-        "function" | "constructor" | "fallback" | "receive" | "modifier"
-    )
-}
-
 fn type_allows_paren(ident: &str) -> bool {
     matches!(ident, "mapping" | "function")
 }
@@ -1687,12 +1458,15 @@ fn sema_identifier_items(
     project_id: ProjectId,
     file_id: FileId,
     offset: TextSize,
+    prefix: &str,
     range: TextRange,
 ) -> Option<Vec<CompletionItem>> {
     let project = db.project_input(project_id);
-    let snapshot = sa_sema::sema_snapshot_for_project(db, project);
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
     let snapshot = snapshot.for_file(file_id)?;
-    let items = snapshot.identifier_completions(file_id, offset)?;
+    let visible = visible_definitions_with_prefix(db, project_id, file_id, prefix);
+    let names = visible.iter().map(|def| def.name());
+    let items = snapshot.identifier_completions(file_id, offset, prefix, names)?;
     Some(
         items
             .into_iter()
@@ -1714,13 +1488,7 @@ fn member_items_for_contract_def(
     range: TextRange,
     access: MemberAccessKind,
 ) -> Vec<CompletionItem> {
-    let Some(entry) = program.def_map().entry(contract_def) else {
-        return Vec::new();
-    };
-    let file_id = entry.location().file_id();
-    let name = entry.location().name();
-
-    contract_members_with_inheritance(db, program, file_id, name, access, range)
+    contract_members_with_inheritance(db, program, contract_def, access, range)
 }
 
 fn member_items_for_named_contract(
@@ -1923,8 +1691,7 @@ struct ContractMemberAstContext<'a> {
 fn contract_members_with_inheritance(
     db: &dyn HirDatabase,
     program: &sa_hir::HirProgram,
-    file_id: FileId,
-    contract_name: &str,
+    contract_def: sa_def::DefId,
     access: MemberAccessKind,
     range: TextRange,
 ) -> Vec<CompletionItem> {
@@ -1932,52 +1699,22 @@ fn contract_members_with_inheritance(
     let mut seen = HashSet::new();
 
     let base_accessible = matches!(access, MemberAccessKind::Type);
-    let context = ContractMemberAstContext {
-        db,
-        file_id,
-        contract_name,
-        origin: None,
-        access,
-        base_accessible,
-        range,
-    };
-    push_contract_members_from_ast(&context, &mut items, &mut seen);
-
-    let mut visited = HashSet::new();
-    let mut pending = Vec::new();
-    for base_path in contract_bases_in_file(db, file_id, contract_name) {
-        if let Some(base_id) = resolve_contract_path(program, file_id, &base_path) {
-            pending.push(base_id);
-        }
-    }
-
-    while let Some(base_id) = pending.pop() {
-        if !visited.insert(base_id) {
-            continue;
-        }
-        let Some(entry) = program.def_map().entry(base_id) else {
+    let linearization = linearized_bases(db, program, contract_def);
+    for (idx, base_id) in linearization.bases().iter().enumerate() {
+        let Some(entry) = program.def_map().entry(*base_id) else {
             continue;
         };
-        let base_file_id = entry.location().file_id();
         let base_name = entry.location().name();
-
         let context = ContractMemberAstContext {
             db,
-            file_id: base_file_id,
+            file_id: entry.location().file_id(),
             contract_name: base_name,
-            origin: Some(base_name.to_string()),
+            origin: (idx > 0).then(|| base_name.to_string()),
             access,
             base_accessible,
             range,
         };
         push_contract_members_from_ast(&context, &mut items, &mut seen);
-
-        let base_paths = contract_bases_in_file(db, base_file_id, base_name);
-        for base_path in base_paths {
-            if let Some(next_id) = resolve_contract_path(program, base_file_id, &base_path) {
-                pending.push(next_id);
-            }
-        }
     }
 
     items
@@ -2172,60 +1909,6 @@ fn allow_variable_visibility(
     }
 }
 
-fn resolve_contract_path(
-    program: &sa_hir::HirProgram,
-    file_id: FileId,
-    path: &[String],
-) -> Option<sa_def::DefId> {
-    let name = path.last()?.as_str();
-    let def_id = if path.len() == 1 {
-        program.resolve_contract(file_id, name)
-    } else {
-        let qualifier = path.first()?.as_str();
-        program.resolve_qualified_symbol(file_id, qualifier, name)
-    };
-    match def_id {
-        Some(def_id @ sa_def::DefId::Contract(_)) => Some(def_id),
-        _ => None,
-    }
-}
-
-fn contract_bases_in_file(
-    db: &dyn HirDatabase,
-    file_id: FileId,
-    contract_name: &str,
-) -> Vec<Vec<String>> {
-    let text = db.file_input(file_id).text(db);
-    let parse = parse_file(text.as_ref());
-    contract_bases_in_parse(&parse, contract_name)
-}
-
-fn contract_bases_in_parse(parse: &Parse, contract_name: &str) -> Vec<Vec<String>> {
-    parse.with_session(|| {
-        for item in parse.tree().items.iter() {
-            if let ItemKind::Contract(contract) = &item.kind
-                && contract.name.as_str() == contract_name
-            {
-                let bases = contract
-                    .bases
-                    .iter()
-                    .filter_map(|base| {
-                        let segments: Vec<String> = base
-                            .name
-                            .segments()
-                            .iter()
-                            .map(|segment| segment.as_str().to_string())
-                            .collect();
-                        (!segments.is_empty()).then_some(segments)
-                    })
-                    .collect();
-                return bases;
-            }
-        }
-        Vec::new()
-    })
-}
-
 fn member_access_needs_patch(text: &str, offset: TextSize) -> bool {
     let bytes = text.as_bytes();
     let mut idx = usize::from(offset).min(bytes.len());
@@ -2242,17 +1925,15 @@ fn parse_prefix_for_member_access(text: &str, offset: TextSize) -> Option<Parse>
     if member_access_needs_patch(&prefix, prefix_offset) {
         prefix.push_str("__sa_dummy();");
     }
-    let mut brace_depth = 0usize;
-    let mut lexer = FallbackLexer::new(&prefix);
-    while let Some(token) = lexer.next_token() {
-        if let FallbackToken::Punct(punct) = token {
-            match punct {
-                '{' => brace_depth += 1,
-                '}' => brace_depth = brace_depth.saturating_sub(1),
-                _ => {}
-            }
-        }
-    }
+    let brace_depth = parse_cst(&prefix)
+        .root()
+        .descendant_tokens()
+        .into_iter()
+        .fold(0usize, |depth, token| match token.kind() {
+            SyntaxKind::LBrace => depth + 1,
+            SyntaxKind::RBrace => depth.saturating_sub(1),
+            _ => depth,
+        });
     for _ in 0..brace_depth {
         prefix.push('}');
     }
@@ -2317,7 +1998,9 @@ fn resolve_contract_def_from_qualified_path(
     let project = db.project_input(project_id);
     let workspace = project.workspace(db);
     let remappings = project.config(db).active_profile().remappings();
-    let resolver = FoundryResolver::new(workspace.as_ref(), remappings).ok();
+    let resolver = FoundryResolver::new(workspace.as_ref(), remappings)
+        .ok()
+        .map(|resolver| resolver.with_node_modules(db.node_modules(workspace.root())));
     let current_path = db.file_path(file_id);
     let resolved = resolve_import_path_with_resolver(
         workspace.as_ref(),
@@ -2341,7 +2024,7 @@ fn resolve_contract_def_from_qualified_path(
             .or_else(|| resolve_relative_import_fallback(current_path.as_ref(), &import_path))
             .or_else(|| remap_fallback.map(|fallback| fallback.path))
     }?;
-    let target_file_id = file_id_for_path(db, &resolved)?;
+    let target_file_id = sa_hir::path_index(db).file_id(resolved.as_str())?;
     contract_def_in_file(&program, target_file_id, name)
 }
 
@@ -2427,12 +2110,6 @@ fn resolve_import_path_with_remappings_fallback(
     })
 }
 
-fn file_id_for_path(db: &dyn HirDatabase, path: &NormalizedPath) -> Option<FileId> {
-    db.file_ids()
-        .into_iter()
-        .find(|file_id| db.file_path(*file_id).as_ref() == path)
-}
-
 fn unique_contract_def(program: &sa_hir::HirProgram, name: &str) -> Option<sa_def::DefId> {
     let entries = program.def_map().entries_by_name(DefKind::Contract, name)?;
     if entries.len() == 1 {
@@ -2452,9 +2129,18 @@ fn sema_member_items(
     range: TextRange,
 ) -> Option<Vec<CompletionItem>> {
     let project = db.project_input(project_id);
-    let snapshot = sa_sema::sema_snapshot_for_project(db, project);
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
     let snapshot = snapshot.for_file(file_id)?;
-    let items = snapshot.member_completions(file_id, offset, receiver_range, receiver)?;
+    let mut items = snapshot.member_completions(file_id, offset, receiver_range, receiver)?;
+    let bound = snapshot
+        .bound_functions_at(file_id, receiver_range.start())
+        .unwrap_or_default();
+    items.extend(bound.into_iter().map(|function| SemaCompletionItem {
+        label: function.name,
+        kind: SemaCompletionKind::Function,
+        detail: function.detail,
+        origin: function.library,
+    }));
     Some(
         items
             .into_iter()
@@ -2641,152 +2327,41 @@ fn consider_local_var<'a>(
 }
 
 fn fallback_member_items(text: &str, receiver: &str, range: TextRange) -> Vec<CompletionItem> {
-    let mut items = Vec::new();
-    let mut lexer = FallbackLexer::new(text);
-
-    let mut awaiting_contract_name = false;
-    let mut awaiting_contract_brace = false;
-    let mut in_target = false;
-    let mut brace_depth = 0usize;
-
-    let mut pending_named_kind: Option<CompletionItemKind> = None;
-    let mut statement_idents: Vec<String> = Vec::new();
-    let mut statement_has_decl_keyword = false;
-    let mut statement_skip_variable = false;
-
-    while let Some(token) = lexer.next_token() {
-        match token {
-            FallbackToken::Ident(ident) => {
-                if !in_target {
-                    if awaiting_contract_name {
-                        if ident == receiver {
-                            awaiting_contract_brace = true;
-                        }
-                        awaiting_contract_name = false;
-                    } else if matches!(ident.as_str(), "contract" | "interface" | "library") {
-                        awaiting_contract_name = true;
-                    }
-                    continue;
-                }
-
-                if let Some(kind) = pending_named_kind.take() {
-                    let detail = None;
-                    let (label, insert_text, insert_text_format) =
-                        apply_callable_format(&ident, kind, detail.as_deref());
-                    items.push(CompletionItem {
-                        label,
-                        kind,
-                        replacement_range: range,
-                        detail,
-                        origin: None,
-                        insert_text,
-                        insert_text_format,
-                    });
-                    continue;
-                }
-
-                if brace_depth == 1 {
-                    match ident.as_str() {
-                        "function" => {
-                            pending_named_kind = Some(CompletionItemKind::Function);
-                            statement_has_decl_keyword = true;
-                        }
-                        "event" => {
-                            pending_named_kind = Some(CompletionItemKind::Event);
-                            statement_has_decl_keyword = true;
-                        }
-                        "error" => {
-                            pending_named_kind = Some(CompletionItemKind::Error);
-                            statement_has_decl_keyword = true;
-                        }
-                        "modifier" => {
-                            pending_named_kind = Some(CompletionItemKind::Modifier);
-                            statement_has_decl_keyword = true;
-                        }
-                        "struct" => {
-                            pending_named_kind = Some(CompletionItemKind::Struct);
-                            statement_has_decl_keyword = true;
-                        }
-                        "enum" => {
-                            pending_named_kind = Some(CompletionItemKind::Enum);
-                            statement_has_decl_keyword = true;
-                        }
-                        "type" => {
-                            pending_named_kind = Some(CompletionItemKind::Type);
-                            statement_has_decl_keyword = true;
-                        }
-                        "using" | "pragma" | "import" => {
-                            statement_skip_variable = true;
-                        }
-                        _ => {
-                            statement_idents.push(ident);
-                        }
-                    }
-                }
-            }
-            FallbackToken::Punct(punct) => match punct {
-                '{' => {
-                    if awaiting_contract_brace {
-                        in_target = true;
-                        brace_depth = 1;
-                        awaiting_contract_brace = false;
-                        statement_idents.clear();
-                        statement_has_decl_keyword = false;
-                        statement_skip_variable = false;
-                        pending_named_kind = None;
-                        continue;
-                    }
-                    if in_target {
-                        if brace_depth == 1 {
-                            statement_idents.clear();
-                            statement_has_decl_keyword = false;
-                            statement_skip_variable = false;
-                            pending_named_kind = None;
-                        }
-                        brace_depth += 1;
-                    }
-                }
-                '}' => {
-                    if in_target {
-                        brace_depth = brace_depth.saturating_sub(1);
-                        if brace_depth == 0 {
-                            in_target = false;
-                        }
-                    }
-                }
-                ';' => {
-                    if in_target && brace_depth == 1 {
-                        if !statement_has_decl_keyword
-                            && !statement_skip_variable
-                            && let Some(name) = statement_idents.last()
-                        {
-                            let detail = None;
-                            let (label, insert_text, insert_text_format) = apply_callable_format(
-                                name,
-                                CompletionItemKind::Variable,
-                                detail.as_deref(),
-                            );
-                            items.push(CompletionItem {
-                                label,
-                                kind: CompletionItemKind::Variable,
-                                replacement_range: range,
-                                detail,
-                                origin: None,
-                                insert_text,
-                                insert_text_format,
-                            });
-                        }
-                        statement_idents.clear();
-                        statement_has_decl_keyword = false;
-                        statement_skip_variable = false;
-                    }
-                }
-                _ => {}
-            },
-        }
-    }
-
-    items
+    let cst = parse_cst(text);
+    let Some(body) = cst
+        .contract_by_name(receiver)
+        .and_then(|contract| contract.child_node(SyntaxKind::ContractBody))
+    else {
+        return Vec::new();
+    };
+    body.child_nodes()
+        .filter_map(|member| {
+            let kind = match member.kind() {
+                SyntaxKind::Function => CompletionItemKind::Function,
+                SyntaxKind::Event => CompletionItemKind::Event,
+                SyntaxKind::ErrorDef => CompletionItemKind::Error,
+                SyntaxKind::Modifier => CompletionItemKind::Modifier,
+                SyntaxKind::Struct => CompletionItemKind::Struct,
+                SyntaxKind::Enum => CompletionItemKind::Enum,
+                SyntaxKind::Udvt => CompletionItemKind::Type,
+                SyntaxKind::Variable => CompletionItemKind::Variable,
+                _ => return None,
+            };
+            let name = member.name()?;
+            let detail = None;
+            let (label, insert_text, insert_text_format) =
+                apply_callable_format(name.text(), kind, detail.as_deref());
+            Some(CompletionItem {
+                label,
+                kind,
+                replacement_range: range,
+                detail,
+                origin: None,
+                insert_text,
+                insert_text_format,
+            })
+        })
+        .collect()
 }
 
 fn completion_from_sema(item: SemaCompletionItem, range: TextRange) -> CompletionItem {
@@ -2866,13 +2441,13 @@ fn make_relative(root: &str, path: &NormalizedPath) -> String {
 fn completion_kind(kind: DefKind) -> CompletionItemKind {
     match kind {
         DefKind::Contract => CompletionItemKind::Contract,
-        DefKind::Function => CompletionItemKind::Function,
+        DefKind::Function | DefKind::YulFunction => CompletionItemKind::Function,
         DefKind::Struct => CompletionItemKind::Struct,
         DefKind::Enum => CompletionItemKind::Enum,
         DefKind::Event => CompletionItemKind::Event,
         DefKind::Error => CompletionItemKind::Error,
         DefKind::Modifier => CompletionItemKind::Modifier,
-        DefKind::Variable => CompletionItemKind::Variable,
+        DefKind::Variable | DefKind::Field | DefKind::Variant => CompletionItemKind::Variable,
         DefKind::Udvt => CompletionItemKind::Type,
     }
 }
@@ -2893,8 +2468,9 @@ fn completion_rank(kind: CompletionItemKind) -> u8 {
 }
 
 fn import_context(text: &str, offset: TextSize) -> Option<CompletionContext> {
-    let idx = usize::from(offset).min(text.len());
-    let line_start = text[..idx].rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+    let index = LineIndex::new(text);
+    let idx = usize::from(offset.min(index.len()));
+    let line_start = usize::from(index.line_start(offset));
     let line = &text[line_start..idx];
     let (quote_pos, _quote_char) = line
         .rfind('"')
@@ -2989,119 +2565,6 @@ fn ident_before(bytes: &[u8], mut idx: usize) -> Option<(usize, usize)> {
     }
 }
 
-struct FallbackLexer<'a> {
-    bytes: &'a [u8],
-    idx: usize,
-}
-
-enum FallbackToken {
-    Ident(String),
-    Punct(char),
-}
-
-impl<'a> FallbackLexer<'a> {
-    fn new(text: &'a str) -> Self {
-        Self {
-            bytes: text.as_bytes(),
-            idx: 0,
-        }
-    }
-
-    fn next_token(&mut self) -> Option<FallbackToken> {
-        loop {
-            self.skip_ws_and_comments();
-            if self.idx >= self.bytes.len() {
-                return None;
-            }
-
-            let byte = self.bytes[self.idx];
-            if is_ident_start(byte) {
-                let start = self.idx;
-                self.idx += 1;
-                while self.idx < self.bytes.len() && is_ident_byte(self.bytes[self.idx]) {
-                    self.idx += 1;
-                }
-                let ident = std::str::from_utf8(&self.bytes[start..self.idx])
-                    .unwrap_or_default()
-                    .to_string();
-                return Some(FallbackToken::Ident(ident));
-            }
-
-            if matches!(byte, b'{' | b'}' | b';' | b',' | b'(' | b')' | b'*') {
-                self.idx += 1;
-                return Some(FallbackToken::Punct(byte as char));
-            }
-
-            // Skip unrecognized byte and continue the loop
-            self.idx += 1;
-        }
-    }
-
-    fn skip_ws_and_comments(&mut self) {
-        while self.idx < self.bytes.len() {
-            let byte = self.bytes[self.idx];
-            if byte.is_ascii_whitespace() {
-                self.idx += 1;
-                continue;
-            }
-            if byte == b'/' {
-                if self.peek_byte(1) == Some(b'/') {
-                    self.idx += 2;
-                    while self.idx < self.bytes.len() && self.bytes[self.idx] != b'\n' {
-                        self.idx += 1;
-                    }
-                    continue;
-                }
-                if self.peek_byte(1) == Some(b'*') {
-                    self.idx += 2;
-                    let mut found_terminator = false;
-                    while self.idx + 1 < self.bytes.len() {
-                        if self.bytes[self.idx] == b'*' && self.bytes[self.idx + 1] == b'/' {
-                            self.idx += 2;
-                            found_terminator = true;
-                            break;
-                        }
-                        self.idx += 1;
-                    }
-                    // If no closing `*/` was found, set idx to EOF
-                    if !found_terminator {
-                        self.idx = self.bytes.len();
-                    }
-                    continue;
-                }
-            }
-            if byte == b'\'' || byte == b'"' {
-                self.skip_string(byte);
-                continue;
-            }
-            break;
-        }
-    }
-
-    fn skip_string(&mut self, quote: u8) {
-        self.idx += 1;
-        while self.idx < self.bytes.len() {
-            let byte = self.bytes[self.idx];
-            if byte == b'\\' {
-                self.idx = (self.idx + 2).min(self.bytes.len());
-                continue;
-            }
-            self.idx += 1;
-            if byte == quote {
-                break;
-            }
-        }
-    }
-
-    fn peek_byte(&self, offset: usize) -> Option<u8> {
-        self.bytes.get(self.idx + offset).copied()
-    }
-}
-
-fn is_ident_start(byte: u8) -> bool {
-    byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$'
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3258,14 +2721,6 @@ contract Foo {
         assert!(!is_builtin_type("customtype"));
     }
 
-    #[test]
-    fn identifier_helpers_accept_valid_names() {
-        assert!(is_ident_start(b'_'));
-        assert!(is_ident_start(b'A'));
-        assert!(is_ident_start(b'$'));
-        assert!(!is_ident_start(b'1'));
-    }
-
     #[test]
     fn completions_resolve_member_access_on_incomplete_local_decl() {
         let (main_text, offset) = extract_offset(
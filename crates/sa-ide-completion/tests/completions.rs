@@ -807,3 +807,35 @@ contract A {
 
     assert!(labels.contains(&"myValue"));
 }
+
+#[test]
+fn completes_yul_names_inside_assembly() {
+    let completions = completions_for_main(
+        r#"
+contract Main {
+    uint256 counter;
+
+    function helper() internal {}
+
+    function run(uint256 amount) public {
+        assembly {
+            function twice(v) -> r {
+                r := add(v, v)
+            }
+            let doubled := twice(amount)
+            pop(/*caret*/doubled)
+        }
+    }
+}
+"#,
+    );
+    let labels = completion_labels(&completions);
+
+    assert!(labels.contains(&"twice()"));
+    assert!(labels.contains(&"doubled"));
+    assert!(labels.contains(&"amount"));
+    assert!(labels.contains(&"counter"));
+    assert!(labels.contains(&"mload()"));
+    assert!(!labels.contains(&"helper()"));
+    assert!(!labels.contains(&"v"));
+}
@@ -2,10 +2,18 @@ use std::collections::HashSet;
 
 use sa_base_db::{FileId, LanguageKind, ProjectId, ProjectInput};
 use sa_def::{DefId, DefKind};
-use sa_hir::{HirDatabase, local_scopes, lowered_program_for_project};
+use sa_hir::{
+    HirDatabase, Semantics, YulResolution, local_scopes, lowered_program,
+    lowered_program_for_project, yul_hir,
+};
 use sa_sema::{ResolvedSymbolKind, SemaSymbol, sema_snapshot_for_project};
 use sa_span::TextRange;
 use sa_syntax::tokens::{IdentRangeCollector, QualifiedIdentRange};
+use serde::{Deserialize, Serialize};
+
+mod project_structure;
+
+pub use project_structure::{ProjectStructure, project_structure};
 
 #[salsa::db]
 pub trait IdeDatabase: HirDatabase {}
@@ -13,7 +21,7 @@ pub trait IdeDatabase: HirDatabase {}
 #[salsa::db]
 impl IdeDatabase for sa_base_db::Database {}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Symbol {
     name: String,
     kind: DefKind,
@@ -135,6 +143,7 @@ pub fn find_references_for_project(
 
     let mut refs = Vec::new();
     for file_id in db.file_ids() {
+        db.check_cancelled();
         let file_input = db.file_input(file_id);
         if file_input.kind(db) != LanguageKind::Solidity {
             continue;
@@ -240,6 +249,7 @@ fn sema_references_for_def(
     let def_range = entry.location().range();
     let snapshot = sema_snapshot_for_project(db, project);
     let snapshot = snapshot.for_file(def_file_id)?;
+    snapshot.index_references(&|| db.check_cancelled());
     let refs = match snapshot.references_for_definition(def_file_id, def_range) {
         Some(refs) => refs,
         None => return Some(Vec::new()),
@@ -318,5 +328,42 @@ pub fn find_references(
     project_id: ProjectId,
     def_id: DefId,
 ) -> Vec<Reference> {
-    find_references_for_project(db, db.project_input(project_id), def_id)
+    let mut refs = find_references_for_project(db, db.project_input(project_id), def_id);
+    refs.extend(assembly_references(db, project_id, def_id));
+    refs.sort_by(|a, b| (a.file_id, a.range.start()).cmp(&(b.file_id, b.range.start())));
+    refs.dedup();
+    refs
+}
+
+/// Solar does not lower inline assembly, so uses of state variables and constants inside
+/// `assembly` blocks are linked through the Yul HIR instead.
+fn assembly_references(
+    db: &dyn IdeDatabase,
+    project_id: ProjectId,
+    def_id: DefId,
+) -> Vec<Reference> {
+    let program = lowered_program(db, project_id);
+    let Some(entry) = program.def_map().entry(def_id) else {
+        return Vec::new();
+    };
+    if entry.kind() != DefKind::Variable {
+        return Vec::new();
+    }
+    let semantics = Semantics::new(db, project_id);
+    let mut refs = Vec::new();
+    for file_id in db.file_ids() {
+        if db.file_input(file_id).kind(db) != LanguageKind::Solidity {
+            continue;
+        }
+        let yul = yul_hir(db, file_id);
+        for reference in yul.blocks().iter().flat_map(|block| block.references()) {
+            if reference.resolution() == YulResolution::External
+                && reference.name() == entry.location().name()
+                && semantics.source_to_def(file_id, reference.range().start()) == Some(def_id)
+            {
+                refs.push(Reference::new(file_id, reference.range()));
+            }
+        }
+    }
+    refs
 }
@@ -0,0 +1,39 @@
+use sa_base_db::ProjectId;
+use sa_paths::NormalizedPath;
+use sa_project_model::{DependencyPackage, discover_dependencies};
+
+use crate::IdeDatabase;
+
+/// A machine-readable outline of a project: its roots, installed dependencies and the active
+/// compiler settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectStructure {
+    pub root: NormalizedPath,
+    pub source_roots: Vec<NormalizedPath>,
+    pub test_roots: Vec<NormalizedPath>,
+    pub script_roots: Vec<NormalizedPath>,
+    pub library_roots: Vec<NormalizedPath>,
+    pub include_paths: Vec<NormalizedPath>,
+    pub dependencies: Vec<DependencyPackage>,
+    pub profile: String,
+    pub solc_version: Option<String>,
+}
+
+/// Not a salsa query: dependency packages are read from disk, so installing one is picked up
+/// without waiting for a project reload.
+pub fn project_structure(db: &dyn IdeDatabase, project_id: ProjectId) -> ProjectStructure {
+    let project = db.project_input(project_id);
+    let workspace = project.workspace(db);
+    let profile = project.config(db).active_profile();
+    ProjectStructure {
+        root: workspace.root().clone(),
+        source_roots: vec![workspace.src().clone()],
+        test_roots: vec![workspace.test().clone()],
+        script_roots: vec![workspace.script().clone()],
+        library_roots: workspace.libs().cloned().collect(),
+        include_paths: workspace.include_paths().to_vec(),
+        dependencies: discover_dependencies(workspace, profile.remappings()),
+        profile: profile.name().to_string(),
+        solc_version: profile.solc_version().map(str::to_string),
+    }
+}
@@ -32,6 +32,21 @@ fn symbol_search_finds_workspace_symbols() {
     );
 }
 
+#[test]
+fn symbol_index_round_trips_through_serde() {
+    let files = vec![(
+        NormalizedPath::new("/workspace/src/Main.sol"),
+        r#"contract Main { function run() public {} }"#,
+    )];
+    let (db, project_id, _) = setup_db(files, vec![]);
+
+    let symbols = sa_ide_db::symbol_search(&db, project_id, "");
+    assert!(!symbols.is_empty());
+    let json = serde_json::to_string(&symbols).expect("serialize symbols");
+    let restored: Vec<sa_ide_db::Symbol> = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(restored, symbols);
+}
+
 #[test]
 fn find_references_across_files() {
     let files = vec![
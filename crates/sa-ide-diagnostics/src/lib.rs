@@ -16,7 +16,7 @@ use sa_paths::NormalizedPath;
 use sa_project_model::project_paths_from_config;
 use sa_sema::VfsOverlayFileLoader;
 use sa_span::{TextRange, TextSize};
-use sa_vfs::VfsSnapshot;
+use sa_vfs::{Vfs, VfsSnapshot};
 use solar::ast;
 use solar::ast::visit::Visit as _;
 use solar::interface::diagnostics::{Diag, DiagCtxt, InMemoryEmitter, Level};
@@ -25,6 +25,10 @@ use solar::interface::{Session, SourceMap};
 use solar::sema::Compiler;
 use solar::sema::hir::Visit as _;
 
+mod slither;
+
+pub use slither::parse_slither_report;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Diagnostic {
     pub file_path: NormalizedPath,
@@ -48,6 +52,12 @@ pub enum DiagnosticSource {
     Solc,
     Solar,
     ForgeLint,
+    /// A failing test reported by `forge test`.
+    ForgeTest,
+    /// A finding imported from a `slither --json` report.
+    Slither,
+    /// A check the analyzer runs on its own semantic model, such as ERC compliance.
+    Analyzer,
 }
 
 impl DiagnosticSource {
@@ -56,6 +66,9 @@ impl DiagnosticSource {
             DiagnosticSource::Solc => "solc",
             DiagnosticSource::Solar => "solar",
             DiagnosticSource::ForgeLint => "forge-lint",
+            DiagnosticSource::ForgeTest => "forge-test",
+            DiagnosticSource::Slither => "slither",
+            DiagnosticSource::Analyzer => "solidity-analyzer",
         }
     }
 }
@@ -83,9 +96,10 @@ fn collect_solar_lints_inner(
     let (emitter, buffer) = InMemoryEmitter::new();
     let dcx = DiagCtxt::new(Box::new(emitter));
     let source_map = Arc::new(SourceMap::empty());
-    if let Some(snapshot) = snapshot {
-        source_map.set_file_loader(VfsOverlayFileLoader::new(snapshot.clone()));
-    }
+    let snapshot = snapshot
+        .cloned()
+        .unwrap_or_else(|| Vfs::default().snapshot());
+    source_map.set_file_loader(VfsOverlayFileLoader::new(snapshot.clone()));
     let opts = solar_opts_from_config(config);
     let session = Session::builder()
         .dcx(dcx)
@@ -150,11 +164,41 @@ fn collect_solar_lints_inner(
         },
     );
 
-    Ok(buffer
-        .read()
+    let mut diagnostics = absolute_files
         .iter()
-        .filter_map(|diag| solar_diag_to_diagnostic(compiler.sess(), diag, &lint_id_set))
-        .collect())
+        .filter_map(|path| invalid_utf8_diagnostic(&snapshot, path))
+        .collect::<Vec<_>>();
+    diagnostics.extend(
+        buffer
+            .read()
+            .iter()
+            .filter_map(|diag| solar_diag_to_diagnostic(compiler.sess(), diag, &lint_id_set)),
+    );
+    Ok(diagnostics)
+}
+
+/// Files that are not valid UTF-8 are still analyzed with the invalid bytes replaced; this
+/// warning explains the replacement characters. Editor buffers are always valid UTF-8.
+fn invalid_utf8_diagnostic(snapshot: &VfsSnapshot, path: &Path) -> Option<Diagnostic> {
+    let file_path = NormalizedPath::new(path.to_string_lossy());
+    if snapshot
+        .file_id(&file_path)
+        .is_some_and(|file_id| snapshot.has_overlay(file_id))
+    {
+        return None;
+    }
+    if !sa_vfs::read_text(path).ok()?.lossy {
+        return None;
+    }
+    Some(Diagnostic {
+        file_path,
+        range: TextRange::empty(TextSize::from(0)),
+        severity: DiagnosticSeverity::Warning,
+        code: Some("invalid-utf8".to_string()),
+        source: DiagnosticSource::Solar,
+        fixable: false,
+        message: "file is not valid UTF-8; invalid bytes were replaced with U+FFFD".to_string(),
+    })
 }
 
 fn collect_lint_ids() -> Vec<&'static str> {
@@ -0,0 +1,90 @@
+//! Findings from a `slither --json` report.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use sa_paths::NormalizedPath;
+use sa_span::{TextRange, TextSize};
+use serde_json::Value;
+
+use crate::{Diagnostic, DiagnosticSeverity, DiagnosticSource};
+
+/// Converts the detector results of a `slither --json` report into diagnostics, one per finding,
+/// on the first element of the finding that has a source mapping. Paths are resolved against
+/// `root`, where Slither is expected to have run; findings in dependencies are dropped.
+pub fn parse_slither_report(json: &str, root: &Path) -> Result<Vec<Diagnostic>> {
+    let report = serde_json::from_str::<Value>(json).context("invalid Slither JSON")?;
+    if report.get("success").and_then(Value::as_bool) == Some(false) {
+        let error = report
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        bail!("Slither failed: {error}");
+    }
+    let detectors = report
+        .pointer("/results/detectors")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(detectors
+        .iter()
+        .filter_map(|finding| finding_to_diagnostic(finding, root))
+        .collect())
+}
+
+fn finding_to_diagnostic(finding: &Value, root: &Path) -> Option<Diagnostic> {
+    let mapping = finding
+        .get("elements")?
+        .as_array()?
+        .iter()
+        .filter_map(|element| element.get("source_mapping"))
+        .find(|mapping| mapping.get("start").is_some())?;
+    if mapping.get("is_dependency").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let path = mapping
+        .get("filename_relative")
+        .and_then(Value::as_str)
+        .map(|relative| root.join(relative))
+        .or_else(|| {
+            mapping
+                .get("filename_absolute")
+                .and_then(Value::as_str)
+                .map(|absolute| absolute.into())
+        })?;
+    let start = mapping.get("start")?.as_u64()?;
+    let length = mapping.get("length").and_then(Value::as_u64).unwrap_or(0);
+    let range = TextRange::at(
+        TextSize::from(u32::try_from(start).ok()?),
+        TextSize::from(u32::try_from(length).ok()?),
+    );
+    let check = finding.get("check").and_then(Value::as_str);
+    let description = finding
+        .get("description")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim();
+    Some(Diagnostic {
+        file_path: NormalizedPath::new(path.to_string_lossy()),
+        range,
+        severity: severity(finding.get("impact").and_then(Value::as_str)),
+        code: check.map(str::to_string),
+        source: DiagnosticSource::Slither,
+        fixable: false,
+        message: if description.is_empty() {
+            check.unwrap_or("Slither finding").to_string()
+        } else {
+            description.to_string()
+        },
+    })
+}
+
+/// High impact findings are errors and medium ones warnings; low, informational and
+/// optimization findings are shown as information.
+fn severity(impact: Option<&str>) -> DiagnosticSeverity {
+    match impact {
+        Some("High") => DiagnosticSeverity::Error,
+        Some("Medium") => DiagnosticSeverity::Warning,
+        _ => DiagnosticSeverity::Info,
+    }
+}
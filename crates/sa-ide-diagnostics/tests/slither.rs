@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use sa_ide_diagnostics::{DiagnosticSeverity, DiagnosticSource, parse_slither_report};
+use sa_paths::NormalizedPath;
+use sa_span::{TextRange, TextSize};
+
+const REPORT: &str = r#"{
+    "success": true,
+    "error": null,
+    "results": {
+        "detectors": [
+            {
+                "check": "reentrancy-eth",
+                "impact": "High",
+                "confidence": "Medium",
+                "description": "Reentrancy in Vault.withdraw() (src/Vault.sol#10-15):\n\tExternal calls:\n",
+                "elements": [
+                    {
+                        "type": "function",
+                        "name": "withdraw",
+                        "source_mapping": {
+                            "start": 120,
+                            "length": 80,
+                            "filename_relative": "src/Vault.sol",
+                            "filename_absolute": "/ci/checkout/src/Vault.sol",
+                            "is_dependency": false
+                        }
+                    }
+                ]
+            },
+            {
+                "check": "solc-version",
+                "impact": "Informational",
+                "description": "Pragma version^0.8.0 allows old versions",
+                "elements": [
+                    {
+                        "type": "pragma",
+                        "source_mapping": {
+                            "start": 0,
+                            "length": 23,
+                            "filename_relative": "lib/forge-std/src/Test.sol",
+                            "is_dependency": true
+                        }
+                    }
+                ]
+            },
+            {
+                "check": "naming-convention",
+                "impact": "Informational",
+                "description": "Variable Vault._X is not in mixedCase",
+                "elements": [
+                    {
+                        "type": "variable",
+                        "source_mapping": {
+                            "start": 40,
+                            "length": 2,
+                            "filename_relative": "src/Vault.sol",
+                            "is_dependency": false
+                        }
+                    }
+                ]
+            }
+        ]
+    }
+}"#;
+
+#[test]
+fn slither_findings_become_diagnostics() {
+    let diagnostics = parse_slither_report(REPORT, Path::new("/workspace")).expect("report");
+
+    assert_eq!(diagnostics.len(), 2);
+    let reentrancy = &diagnostics[0];
+    assert_eq!(
+        reentrancy.file_path,
+        NormalizedPath::new("/workspace/src/Vault.sol")
+    );
+    assert_eq!(
+        reentrancy.range,
+        TextRange::at(TextSize::from(120), TextSize::from(80))
+    );
+    assert_eq!(reentrancy.severity, DiagnosticSeverity::Error);
+    assert_eq!(reentrancy.code.as_deref(), Some("reentrancy-eth"));
+    assert_eq!(reentrancy.source, DiagnosticSource::Slither);
+    assert!(reentrancy.message.ends_with("External calls:"));
+    assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Info);
+}
+
+#[test]
+fn failed_slither_runs_are_errors() {
+    let error = parse_slither_report(
+        r#"{"success": false, "error": "compilation failed", "results": {}}"#,
+        Path::new("/workspace"),
+    )
+    .expect_err("failed run");
+    assert!(error.to_string().contains("compilation failed"));
+    assert!(parse_slither_report("not json", Path::new("/workspace")).is_err());
+}
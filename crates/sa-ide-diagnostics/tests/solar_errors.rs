@@ -70,3 +70,32 @@ contract Broken {
         "expected semantic error diagnostics",
     );
 }
+
+#[test]
+fn solar_lints_warn_about_invalid_utf8_and_keep_going() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    let config = setup_config(root);
+    let file_path = root.join("src/Latin1.sol");
+    fs::write(
+        &file_path,
+        b"\xEF\xBB\xBFpragma solidity ^0.8.20;\r\n// caf\xE9\r\ncontract Latin1 {\r\n    function run() public { uint256 value = ; }\r\n}\r\n",
+    )
+    .expect("write source");
+
+    let lints =
+        collect_solar_lints(&config, std::slice::from_ref(&file_path)).expect("collect lints");
+    let normalized_file_path = NormalizedPath::new(file_path.to_string_lossy());
+
+    assert!(lints.iter().any(|diag| {
+        diag.file_path == normalized_file_path
+            && diag.severity == DiagnosticSeverity::Warning
+            && diag.code.as_deref() == Some("invalid-utf8")
+    }));
+    assert!(
+        lints.iter().any(|diag| {
+            diag.file_path == normalized_file_path && diag.severity == DiagnosticSeverity::Error
+        }),
+        "expected the file to still be parsed"
+    );
+}
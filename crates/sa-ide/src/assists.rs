@@ -0,0 +1,387 @@
+use heck::AsPascalCase;
+use sa_base_db::{FileId, ProjectId};
+use sa_def::DefKind;
+use sa_hir::{HirDatabase, lowered_program};
+use sa_ide_assists::{EditBuilder, SourceChange};
+use sa_span::TextRange;
+use sa_syntax::{
+    Parse,
+    ast::{
+        ContractKind, ElementaryType, Item, ItemContract, ItemKind, Type, TypeKind,
+        VariableDefinition, Visibility,
+    },
+};
+
+use crate::code_actions::{AssistResolveStrategy, CodeAction, CodeActionKind};
+use crate::syntax_utils::{find_item_by_name_range, type_text};
+
+const GENERATE_GETTER: &str = "generate_getter";
+
+/// Computes the refactoring assists available for `range` in `file_id`.
+///
+/// Edits are only computed for the assists selected by `resolve`; the rest are returned with
+/// `edit: None` so callers can list them cheaply. `project_id` is only consulted to resolve
+/// types declared in other files; pass `None` when no workspace is loaded.
+pub fn assists(
+    db: &dyn HirDatabase,
+    project_id: Option<ProjectId>,
+    file_id: FileId,
+    range: TextRange,
+    resolve: &AssistResolveStrategy,
+) -> Vec<CodeAction> {
+    let text = db.file_input(file_id).text(db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    let ctx = AssistContext {
+        db,
+        project_id,
+        file_id,
+        text: text.as_ref(),
+        parse: &parse,
+    };
+
+    let mut actions = Vec::new();
+    if let Some(action) = generate_getter(&ctx, range, resolve) {
+        actions.push(action);
+    }
+    actions
+}
+
+struct AssistContext<'a> {
+    db: &'a dyn HirDatabase,
+    project_id: Option<ProjectId>,
+    file_id: FileId,
+    text: &'a str,
+    parse: &'a Parse,
+}
+
+struct GetterParam {
+    ty: String,
+    name: String,
+}
+
+struct StructField {
+    ty: String,
+    name: String,
+}
+
+enum GetterReturn {
+    Value(String),
+    Struct {
+        ty: String,
+        fields: Vec<StructField>,
+    },
+}
+
+/// Generates an external view getter for a private or internal state variable.
+///
+/// The signature mirrors the accessor solc synthesizes for `public` state variables: mapping
+/// keys and array indices become parameters, and structs are returned member-wise with
+/// mapping and array members omitted.
+fn generate_getter(
+    ctx: &AssistContext<'_>,
+    range: TextRange,
+    resolve: &AssistResolveStrategy,
+) -> Option<CodeAction> {
+    let parse = ctx.parse;
+    let (contract_item, contract, var) = parse.with_session(|| {
+        parse.tree().items.iter().find_map(|item| {
+            let ItemKind::Contract(contract) = &item.kind else {
+                return None;
+            };
+            if matches!(
+                contract.kind,
+                ContractKind::Interface | ContractKind::Library
+            ) {
+                return None;
+            }
+            contract.body.iter().find_map(|member| {
+                let ItemKind::Variable(var) = &member.kind else {
+                    return None;
+                };
+                let member_range = parse.span_to_text_range(member.span)?;
+                let contains =
+                    member_range.start() <= range.start() && range.end() <= member_range.end();
+                contains.then_some((item, contract, var))
+            })
+        })
+    })?;
+
+    if matches!(var.visibility, Some(Visibility::Public)) {
+        return None;
+    }
+    let var_name = parse.with_session(|| var.name.map(|name| name.as_str().to_string()))?;
+    let getter_name = getter_name(&var_name);
+    if contract_declares_member(parse, contract, &getter_name) {
+        return None;
+    }
+
+    let edit = if resolve.should_resolve(GENERATE_GETTER) {
+        Some(getter_edit(
+            ctx,
+            contract_item,
+            var,
+            &var_name,
+            &getter_name,
+        )?)
+    } else {
+        None
+    };
+
+    Some(CodeAction {
+        id: GENERATE_GETTER.to_string(),
+        title: format!("Generate getter `{getter_name}`"),
+        kind: CodeActionKind::Generate,
+        edit,
+    })
+}
+
+fn getter_edit(
+    ctx: &AssistContext<'_>,
+    contract_item: &Item<'static>,
+    var: &VariableDefinition<'static>,
+    var_name: &str,
+    getter_name: &str,
+) -> Option<SourceChange> {
+    let parse = ctx.parse;
+    let mut params = Vec::new();
+    let mut access = var_name.to_string();
+    let mut ty = &var.ty;
+    loop {
+        match &ty.kind {
+            TypeKind::Mapping(mapping) => {
+                let name = parse
+                    .with_session(|| mapping.key_name.map(|name| name.as_str().to_string()))
+                    .unwrap_or_else(|| format!("arg{}", params.len()));
+                let key_ty = type_text(parse, ctx.text, &mapping.key)?;
+                let key_ty = match &mapping.key.kind {
+                    TypeKind::Elementary(ElementaryType::String | ElementaryType::Bytes) => {
+                        format!("{key_ty} calldata")
+                    }
+                    _ => key_ty,
+                };
+                access.push_str(&format!("[{name}]"));
+                params.push(GetterParam { ty: key_ty, name });
+                ty = &mapping.value;
+            }
+            TypeKind::Array(array) => {
+                let name = format!("arg{}", params.len());
+                access.push_str(&format!("[{name}]"));
+                params.push(GetterParam {
+                    ty: "uint256".to_string(),
+                    name,
+                });
+                ty = &array.element;
+            }
+            _ => break,
+        }
+    }
+    let returns = getter_return(ctx, ty)?;
+
+    let contract_name = parse.with_session(|| contract_item.name().map(|name| name.to_string()))?;
+    let mut builder = EditBuilder::new(ctx.text);
+    let function = render_getter(
+        getter_name,
+        &params,
+        &returns,
+        &access,
+        builder.indent_unit(),
+    );
+    if !builder.add_function_to_contract(&contract_name, &function) {
+        return None;
+    }
+    Some(builder.into_source_change(ctx.file_id))
+}
+
+fn getter_name(var_name: &str) -> String {
+    let trimmed = var_name.trim_start_matches('_');
+    if trimmed.len() != var_name.len() && !trimmed.is_empty() {
+        return trimmed.to_string();
+    }
+    format!("get{}", AsPascalCase(var_name))
+}
+
+fn contract_declares_member(parse: &Parse, contract: &ItemContract<'_>, name: &str) -> bool {
+    parse.with_session(|| {
+        contract
+            .body
+            .iter()
+            .any(|member| member.name().is_some_and(|ident| ident.as_str() == name))
+    })
+}
+
+fn getter_return(ctx: &AssistContext<'_>, ty: &Type<'_>) -> Option<GetterReturn> {
+    let ty_text = type_text(ctx.parse, ctx.text, ty)?;
+    match &ty.kind {
+        TypeKind::Elementary(ElementaryType::String | ElementaryType::Bytes) => {
+            Some(GetterReturn::Value(format!("{ty_text} memory")))
+        }
+        TypeKind::Custom(path) => {
+            let name = ctx.parse.with_session(|| {
+                path.segments()
+                    .last()
+                    .map(|ident| ident.as_str().to_string())
+            })?;
+            let Some(fields) = struct_fields(ctx, &name) else {
+                return Some(GetterReturn::Value(ty_text));
+            };
+            if fields.is_empty() {
+                return None;
+            }
+            Some(GetterReturn::Struct {
+                ty: ty_text,
+                fields,
+            })
+        }
+        _ => Some(GetterReturn::Value(ty_text)),
+    }
+}
+
+enum StructMatch<'a> {
+    Name(&'a str),
+    Range(TextRange),
+}
+
+/// Returns the accessor-visible fields of struct `name`, or `None` if `name` is not a struct.
+fn struct_fields(ctx: &AssistContext<'_>, name: &str) -> Option<Vec<StructField>> {
+    if let Some(fields) = struct_fields_in_parse(ctx, ctx.parse, ctx.text, StructMatch::Name(name))
+    {
+        return Some(fields);
+    }
+
+    let project_id = ctx.project_id?;
+    let program = lowered_program(ctx.db, project_id);
+    let entries = program.def_map().entries_by_name(DefKind::Struct, name)?;
+    let entry = entries
+        .iter()
+        .find(|entry| entry.location().file_id() == ctx.file_id)
+        .or_else(|| entries.first())?;
+    let text = ctx.db.file_input(entry.location().file_id()).text(ctx.db);
+    let parse = sa_syntax::parse_file(text.as_ref());
+    let item = find_item_by_name_range(&parse, entry.container(), entry.location().range())?;
+    let item_range = parse.span_to_text_range(item.span)?;
+    struct_fields_in_parse(ctx, &parse, text.as_ref(), StructMatch::Range(item_range))
+}
+
+fn find_struct<'a>(parse: &'a Parse, matcher: &StructMatch<'_>) -> Option<&'a Item<'static>> {
+    parse.with_session(|| {
+        let top_level = parse.tree().items.iter();
+        let nested = parse
+            .tree()
+            .items
+            .iter()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Contract(contract) => Some(contract.body.iter()),
+                _ => None,
+            })
+            .flatten();
+        top_level.chain(nested).find(|item| {
+            if !matches!(item.kind, ItemKind::Struct(_)) {
+                return false;
+            }
+            match matcher {
+                StructMatch::Name(name) => item.name().is_some_and(|ident| ident.as_str() == *name),
+                StructMatch::Range(range) => parse.span_to_text_range(item.span) == Some(*range),
+            }
+        })
+    })
+}
+
+fn is_struct_name(ctx: &AssistContext<'_>, name: &str) -> bool {
+    if find_struct(ctx.parse, &StructMatch::Name(name)).is_some() {
+        return true;
+    }
+    ctx.project_id.is_some_and(|project_id| {
+        lowered_program(ctx.db, project_id)
+            .def_map()
+            .entry_by_name(DefKind::Struct, name)
+            .is_some()
+    })
+}
+
+fn struct_fields_in_parse(
+    ctx: &AssistContext<'_>,
+    parse: &Parse,
+    text: &str,
+    matcher: StructMatch<'_>,
+) -> Option<Vec<StructField>> {
+    let item = find_struct(parse, &matcher)?;
+    let ItemKind::Struct(strukt) = &item.kind else {
+        return None;
+    };
+
+    let mut fields = Vec::new();
+    for field in strukt.fields.iter() {
+        if !is_accessor_visible(field) {
+            continue;
+        }
+        let Some(name) = parse.with_session(|| field.name.map(|name| name.as_str().to_string()))
+        else {
+            continue;
+        };
+        let ty = type_text(parse, text, &field.ty)?;
+        let ty = match &field.ty.kind {
+            TypeKind::Elementary(ElementaryType::String | ElementaryType::Bytes) => {
+                format!("{ty} memory")
+            }
+            TypeKind::Custom(path) => {
+                let field_ty_name = parse.with_session(|| {
+                    path.segments()
+                        .last()
+                        .map(|ident| ident.as_str().to_string())
+                });
+                if field_ty_name.is_some_and(|field_ty_name| is_struct_name(ctx, &field_ty_name)) {
+                    format!("{ty} memory")
+                } else {
+                    ty
+                }
+            }
+            _ => ty,
+        };
+        fields.push(StructField { ty, name });
+    }
+    Some(fields)
+}
+
+fn is_accessor_visible(field: &VariableDefinition<'_>) -> bool {
+    !matches!(field.ty.kind, TypeKind::Mapping(_) | TypeKind::Array(_))
+}
+
+fn render_getter(
+    name: &str,
+    params: &[GetterParam],
+    returns: &GetterReturn,
+    access: &str,
+    body_indent: &str,
+) -> String {
+    let params = params
+        .iter()
+        .map(|param| format!("{} {}", param.ty, param.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let (returns, body) = match returns {
+        GetterReturn::Value(ty) => (ty.clone(), format!("{body_indent}return {access};\n")),
+        GetterReturn::Struct { ty, fields } => {
+            let returns = fields
+                .iter()
+                .map(|field| field.ty.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let values = fields
+                .iter()
+                .map(|field| format!("entry.{}", field.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let body = if fields.len() == 1 {
+                format!(
+                    "{body_indent}{ty} storage entry = {access};\n{body_indent}return {values};\n"
+                )
+            } else {
+                format!(
+                    "{body_indent}{ty} storage entry = {access};\n{body_indent}return ({values});\n"
+                )
+            };
+            (returns, body)
+        }
+    };
+    format!("function {name}({params}) external view returns ({returns}) {{\n{body}}}")
+}
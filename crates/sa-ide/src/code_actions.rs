@@ -12,13 +12,34 @@ pub struct CodeActionDiagnostic {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CodeActionKind {
     QuickFix,
+    Generate,
+}
+
+/// Controls which code actions get their edits computed eagerly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssistResolveStrategy {
+    None,
+    All,
+    Single(String),
+}
+
+impl AssistResolveStrategy {
+    pub fn should_resolve(&self, id: &str) -> bool {
+        match self {
+            AssistResolveStrategy::None => false,
+            AssistResolveStrategy::All => true,
+            AssistResolveStrategy::Single(single) => single == id,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CodeAction {
+    pub id: String,
     pub title: String,
     pub kind: CodeActionKind,
-    pub edit: SourceChange,
+    /// `None` until the action is resolved.
+    pub edit: Option<SourceChange>,
 }
 
 pub fn code_actions(
@@ -47,9 +68,10 @@ pub fn code_actions(
         change.normalize();
 
         actions.push(CodeAction {
+            id: fix.code.to_string(),
             title: fix.title.to_string(),
             kind: CodeActionKind::QuickFix,
-            edit: change,
+            edit: Some(change),
         });
     }
 
@@ -39,6 +39,8 @@ pub fn completions(
     file_id: FileId,
     offset: TextSize,
 ) -> Vec<CompletionItem> {
+    // Completions are requested on every keystroke; a superseded request should not start.
+    db.check_cancelled();
     sa_ide_completion::completions(db, project_id, file_id, offset)
         .into_iter()
         .map(CompletionItem::from)
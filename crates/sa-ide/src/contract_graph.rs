@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_def::{DefId, DefKind};
+use sa_span::{TextRange, TextSize};
+use sa_syntax::cst::SyntaxNode;
+
+/// The inheritance graph of a project, or of the contracts connected to one contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractGraph {
+    pub nodes: Vec<ContractNode>,
+    /// Direct inheritance edges, in the declared base order of each derived contract.
+    pub edges: Vec<ContractEdge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    Contract,
+    Interface,
+    Library,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractNode {
+    pub name: String,
+    pub kind: ContractKind,
+    pub is_abstract: bool,
+    pub file_id: FileId,
+    /// The range of the contract's name.
+    pub range: TextRange,
+    /// Indices into [`ContractGraph::nodes`] of the C3 linearization, starting with the
+    /// contract itself.
+    pub linearization: Vec<usize>,
+}
+
+/// `derived is base`, as indices into [`ContractGraph::nodes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractEdge {
+    pub derived: usize,
+    pub base: usize,
+}
+
+/// Every contract of `project_id`, or with a `target`, only the contract declared around the
+/// offset in that file together with its bases and the contracts deriving from it.
+pub(crate) fn contract_graph(
+    db: &Database,
+    project_id: ProjectId,
+    target: Option<(FileId, TextSize)>,
+) -> Option<ContractGraph> {
+    let program = sa_hir::lowered_program(db, project_id);
+    let inheritance = sa_hir::inheritance_graph(db, project_id);
+    let contracts = match target {
+        Some((file_id, offset)) => {
+            let cst = sa_hir::syntax(db, file_id);
+            let name = cst.contract_at_offset(offset)?.name()?.text().to_string();
+            let contract = program
+                .def_map()
+                .entries_by_name_in_file(file_id, &name)
+                .into_iter()
+                .find(|entry| entry.kind() == DefKind::Contract)?
+                .id();
+            let mut contracts = vec![contract];
+            contracts.extend(inheritance.all_bases(contract));
+            contracts.extend(inheritance.all_derived(contract));
+            contracts
+        }
+        None => program
+            .def_map()
+            .entries()
+            .iter()
+            .filter(|entry| entry.kind() == DefKind::Contract)
+            .map(|entry| entry.id())
+            .collect(),
+    };
+    let def_map = program.def_map();
+    let mut entries = contracts
+        .iter()
+        .filter_map(|id| def_map.entry(*id))
+        .collect::<Vec<_>>();
+    entries.sort_by_cached_key(|entry| {
+        let location = entry.location();
+        (
+            db.file_path(location.file_id()).as_str().to_string(),
+            location.range().start(),
+        )
+    });
+    entries.dedup_by_key(|entry| entry.id());
+
+    let index = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (entry.id(), index))
+        .collect::<HashMap<DefId, usize>>();
+    let mut csts = HashMap::new();
+    let mut graph = ContractGraph::default();
+    for (derived, entry) in entries.iter().enumerate() {
+        db.check_cancelled();
+        let location = entry.location();
+        let cst = csts
+            .entry(location.file_id())
+            .or_insert_with(|| sa_hir::syntax(db, location.file_id()));
+        let (kind, is_abstract) = cst
+            .contract_by_name(location.name())
+            .map_or((ContractKind::Contract, false), contract_header);
+        let linearization = sa_hir::linearized_bases(db, &program, entry.id())
+            .bases()
+            .iter()
+            .filter_map(|base| index.get(base).copied())
+            .collect();
+        graph.nodes.push(ContractNode {
+            name: location.name().to_string(),
+            kind,
+            is_abstract,
+            file_id: location.file_id(),
+            range: location.range(),
+            linearization,
+        });
+        for base in inheritance.direct_bases(entry.id()) {
+            if let Some(&base) = index.get(base) {
+                graph.edges.push(ContractEdge { derived, base });
+            }
+        }
+    }
+    Some(graph)
+}
+
+/// The kind keyword of a contract and whether it is marked `abstract`.
+pub(crate) fn contract_header(contract: &SyntaxNode) -> (ContractKind, bool) {
+    let mut is_abstract = false;
+    for token in contract.tokens().filter(|token| !token.kind().is_trivia()) {
+        match token.text() {
+            "abstract" => is_abstract = true,
+            "interface" => return (ContractKind::Interface, is_abstract),
+            "library" => return (ContractKind::Library, is_abstract),
+            _ => return (ContractKind::Contract, is_abstract),
+        }
+    }
+    (ContractKind::Contract, is_abstract)
+}
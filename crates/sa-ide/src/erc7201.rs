@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{U256, keccak256};
+use sa_base_db::{Database, FileId};
+use sa_span::TextRange;
+use sa_syntax::cst::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use crate::metrics::all_tokens;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespacedStorageIssueKind {
+    /// The slot an accessor points the struct at is not the one ERC-7201 derives from its id.
+    WrongSlot,
+    /// The struct is written through storage that does not come from its accessor.
+    OutsideNamespace,
+}
+
+/// A misuse of a struct annotated with `@custom:storage-location erc7201:<id>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespacedStorageIssue {
+    pub kind: NamespacedStorageIssueKind,
+    /// The name of the namespaced struct.
+    pub name: String,
+    pub namespace: String,
+    /// The range of the slot constant or literal, or of the variable written to.
+    pub range: TextRange,
+    pub message: String,
+}
+
+/// The storage slot ERC-7201 assigns to the namespace `id`, as a `0x`-prefixed hex string:
+/// `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`.
+pub fn erc7201_slot(id: &str) -> String {
+    let inner = U256::from_be_bytes(keccak256(id.as_bytes()).0) - U256::from(1);
+    let mut slot = keccak256(inner.to_be_bytes::<32>()).0;
+    slot[31] = 0;
+    format!(
+        "0x{}",
+        slot.iter().map(|b| format!("{b:02x}")).collect::<String>()
+    )
+}
+
+struct Namespace {
+    name: String,
+    id: String,
+    /// Functions returning the struct from storage, with the slot expression they assign.
+    accessors: Vec<(String, Option<SlotExpr>)>,
+}
+
+enum SlotExpr {
+    Literal(SyntaxToken),
+    Constant(SyntaxToken),
+}
+
+/// Checks the namespaced storage structs declared in `file_id`: the slot their accessors use
+/// must match the ERC-7201 formula, and outside the accessors the struct may only be written
+/// through storage pointers the accessors return. Pointers bound with inline assembly elsewhere
+/// and state variables of the struct type place it outside its namespace.
+pub(crate) fn namespaced_storage_issues(
+    db: &Database,
+    file_id: FileId,
+) -> Vec<NamespacedStorageIssue> {
+    let cst = sa_hir::syntax(db, file_id);
+    let mut issues = Vec::new();
+    for scope in std::iter::once(cst.root()).chain(cst.contracts()) {
+        db.check_cancelled();
+        let members = match scope.kind() {
+            SyntaxKind::Contract => match scope.child_node(SyntaxKind::ContractBody) {
+                Some(body) => body,
+                None => continue,
+            },
+            _ => scope,
+        };
+        let mut namespaces = namespaced_structs(members);
+        if namespaces.is_empty() {
+            continue;
+        }
+        let functions = members
+            .child_nodes()
+            .filter(|node| node.kind() == SyntaxKind::Function)
+            .collect::<Vec<_>>();
+        for namespace in &mut namespaces {
+            namespace.accessors = functions
+                .iter()
+                .filter(|function| returns_storage_of(function, &namespace.name))
+                .filter_map(|function| {
+                    let name = function.name()?.text().to_string();
+                    Some((name, assigned_slot(function)))
+                })
+                .collect();
+            check_slots(members, namespace, &mut issues);
+        }
+        for function in &functions {
+            check_writes(function, &namespaces, &mut issues);
+        }
+        check_state_variables(members, &namespaces, &mut issues);
+    }
+    issues
+}
+
+/// The structs among `members` whose docs carry an `erc7201` storage location.
+fn namespaced_structs(members: &SyntaxNode) -> Vec<Namespace> {
+    let mut namespaces = Vec::new();
+    let mut comments = Vec::new();
+    for child in members.children() {
+        match child {
+            SyntaxElement::Token(token)
+                if matches!(
+                    token.kind(),
+                    SyntaxKind::LineComment | SyntaxKind::BlockComment
+                ) =>
+            {
+                comments.push(token.text());
+            }
+            SyntaxElement::Token(token) if token.kind() == SyntaxKind::Whitespace => {}
+            SyntaxElement::Node(node) if node.kind() == SyntaxKind::Struct => {
+                let id = comments
+                    .iter()
+                    .find_map(|comment| storage_location(comment));
+                if let (Some(id), Some(name)) = (id, node.name()) {
+                    namespaces.push(Namespace {
+                        name: name.text().to_string(),
+                        id,
+                        accessors: Vec::new(),
+                    });
+                }
+                comments.clear();
+            }
+            _ => comments.clear(),
+        }
+    }
+    namespaces
+}
+
+/// The id of the `@custom:storage-location erc7201:<id>` tag in `comment`.
+fn storage_location(comment: &str) -> Option<String> {
+    let (_, rest) = comment.split_once("@custom:storage-location")?;
+    let id = rest.trim_start().strip_prefix("erc7201:")?;
+    let id = id
+        .split(|c: char| c.is_whitespace() || c == '*')
+        .next()
+        .filter(|id| !id.is_empty())?;
+    Some(id.to_string())
+}
+
+/// Whether `function` declares `returns (<name> storage ...)`.
+fn returns_storage_of(function: &SyntaxNode, name: &str) -> bool {
+    let mut after_returns = false;
+    for child in function.children() {
+        match child {
+            SyntaxElement::Token(token) if token.text() == "returns" => after_returns = true,
+            SyntaxElement::Node(node) if after_returns && node.kind() == SyntaxKind::ParamList => {
+                let tokens = node.tokens().collect::<Vec<_>>();
+                return tokens
+                    .windows(2)
+                    .any(|pair| pair[0].text() == name && pair[1].text() == "storage");
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// The value of the first `<pointer>.slot := <value>` in the body of `function`.
+fn assigned_slot(function: &SyntaxNode) -> Option<SlotExpr> {
+    let body = function.child_node(SyntaxKind::Block)?;
+    let tokens = all_tokens(body);
+    tokens.windows(5).find_map(|window| {
+        let [dot, slot, colon, equals, value] = window else {
+            return None;
+        };
+        if dot.kind() != SyntaxKind::Dot
+            || slot.text() != "slot"
+            || colon.text() != ":"
+            || equals.text() != "="
+        {
+            return None;
+        }
+        match value.kind() {
+            SyntaxKind::Number => Some(SlotExpr::Literal((*value).clone())),
+            SyntaxKind::Ident => Some(SlotExpr::Constant((*value).clone())),
+            _ => None,
+        }
+    })
+}
+
+fn check_slots(
+    members: &SyntaxNode,
+    namespace: &Namespace,
+    issues: &mut Vec<NamespacedStorageIssue>,
+) {
+    let expected = erc7201_slot(&namespace.id);
+    for (_, slot) in &namespace.accessors {
+        let Some(slot) = slot else {
+            continue;
+        };
+        let (range, literal, id) = match slot {
+            SlotExpr::Literal(token) => (token.range(), Some(token.text().to_string()), None),
+            SlotExpr::Constant(token) => {
+                let Some(constant) = constant_initializer(members, token.text()) else {
+                    continue;
+                };
+                constant
+            }
+        };
+        let mismatch = match (literal, id) {
+            (Some(literal), _) => {
+                (!same_slot(&literal, &expected)).then(|| format!("is `{literal}`"))
+            }
+            (None, Some(id)) if id != namespace.id => Some(format!("is derived from `{id}`")),
+            _ => None,
+        };
+        if let Some(mismatch) = mismatch {
+            issues.push(NamespacedStorageIssue {
+                kind: NamespacedStorageIssueKind::WrongSlot,
+                name: namespace.name.clone(),
+                namespace: namespace.id.clone(),
+                range,
+                message: format!(
+                    "the ERC-7201 slot of `{}` (erc7201:{}) is `{expected}`, but its slot {mismatch}",
+                    namespace.name, namespace.id
+                ),
+            });
+        }
+    }
+}
+
+/// The name range of the constant `name` with, when it is set to a hex literal, that literal,
+/// or when it is computed from a string, that string.
+fn constant_initializer(
+    members: &SyntaxNode,
+    name: &str,
+) -> Option<(TextRange, Option<String>, Option<String>)> {
+    let variable = members.child_nodes().find(|node| {
+        node.kind() == SyntaxKind::Variable && node.name().is_some_and(|token| token.text() == name)
+    })?;
+    let range = variable.name()?.range();
+    let tokens = all_tokens(variable);
+    let value = tokens
+        .iter()
+        .position(|token| token.text() == "=")
+        .map(|index| &tokens[index + 1..])
+        .unwrap_or_default();
+    match value {
+        [literal, semicolon] if literal.kind() == SyntaxKind::Number && semicolon.text() == ";" => {
+            Some((range, Some(literal.text().to_string()), None))
+        }
+        _ => {
+            let id = value
+                .iter()
+                .find(|token| token.kind() == SyntaxKind::String)
+                .map(|token| token.text().trim_matches(['"', '\'']).to_string());
+            Some((range, None, id))
+        }
+    }
+}
+
+fn same_slot(literal: &str, expected: &str) -> bool {
+    let digits = |value: &str| {
+        let value = value
+            .trim_start_matches("0x")
+            .replace('_', "")
+            .to_ascii_lowercase();
+        format!("{value:0>64}")
+    };
+    digits(literal) == digits(expected)
+}
+
+/// Reports local storage pointers to a namespaced struct that are written through but were not
+/// obtained from one of its accessors.
+fn check_writes(
+    function: &SyntaxNode,
+    namespaces: &[Namespace],
+    issues: &mut Vec<NamespacedStorageIssue>,
+) {
+    let Some(name) = function.name() else {
+        return;
+    };
+    let Some(body) = function.child_node(SyntaxKind::Block) else {
+        return;
+    };
+    let tokens = all_tokens(body);
+    for namespace in namespaces {
+        let is_accessor = |text: &str| {
+            namespace
+                .accessors
+                .iter()
+                .any(|(accessor, _)| accessor == text)
+        };
+        if is_accessor(name.text()) {
+            continue;
+        }
+
+        // Each pointer with its declaration and whether it was last bound by an accessor.
+        let mut pointers = HashMap::new();
+        let mut reported = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            let rest = &tokens[index..];
+            if let [ty, storage, pointer, ..] = rest
+                && ty.text() == namespace.name
+                && storage.text() == "storage"
+                && pointer.kind() == SyntaxKind::Ident
+            {
+                let from_accessor = statement_from(rest)
+                    .iter()
+                    .any(|token| is_accessor(token.text()));
+                pointers.insert(pointer.text(), (*pointer, from_accessor));
+                continue;
+            }
+            let Some((declaration, from_accessor)) = pointers.get_mut(token.text()) else {
+                continue;
+            };
+            match rest {
+                // `pointer = accessor()` rebinds the pointer to the namespace.
+                [_, assign, next, ..] if assign.text() == "=" && next.text() != "=" => {
+                    *from_accessor = statement_from(rest)
+                        .iter()
+                        .any(|token| is_accessor(token.text()));
+                }
+                // `pointer.slot := ...` in assembly rebinds it to an arbitrary slot.
+                [_, dot, slot, colon, ..]
+                    if dot.kind() == SyntaxKind::Dot
+                        && slot.text() == "slot"
+                        && colon.text() == ":" =>
+                {
+                    *from_accessor = false;
+                }
+                _ if !*from_accessor
+                    && !reported.contains(&token.text())
+                    && writes_through(&tokens, index) =>
+                {
+                    reported.push(token.text());
+                    issues.push(NamespacedStorageIssue {
+                        kind: NamespacedStorageIssueKind::OutsideNamespace,
+                        name: namespace.name.clone(),
+                        namespace: namespace.id.clone(),
+                        range: declaration.range(),
+                        message: format!(
+                            "`{}` is written to, but it does not come from an accessor of the namespaced struct `{}` (erc7201:{})",
+                            declaration.text(),
+                            namespace.name,
+                            namespace.id
+                        ),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The tokens up to the end of the statement starting at `tokens[0]`.
+fn statement_from<'a, 'b>(tokens: &'b [&'a SyntaxToken]) -> &'b [&'a SyntaxToken] {
+    let end = tokens
+        .iter()
+        .position(|token| token.kind() == SyntaxKind::Semicolon)
+        .unwrap_or(tokens.len());
+    &tokens[..end]
+}
+
+/// Whether the pointer at `tokens[index]` starts a statement that writes through it: an
+/// assignment or increment of one of its members, a `push`/`pop`, or a `delete`.
+fn writes_through(tokens: &[&SyntaxToken], index: usize) -> bool {
+    let previous = index.checked_sub(1).map(|previous| tokens[previous]);
+    let member_access = tokens
+        .get(index + 1)
+        .is_some_and(|token| token.kind() == SyntaxKind::Dot);
+    if previous.is_some_and(|token| token.text() == "delete") {
+        return member_access;
+    }
+    let starts_statement = previous.is_none_or(|token| {
+        matches!(
+            token.kind(),
+            SyntaxKind::Semicolon | SyntaxKind::LBrace | SyntaxKind::RBrace
+        )
+    });
+    if !starts_statement || !member_access {
+        return false;
+    }
+    let statement = statement_from(&tokens[index..]);
+    statement.iter().enumerate().any(|(i, token)| {
+        let previous = i.checked_sub(1).map(|previous| statement[previous].text());
+        let next = statement.get(i + 1).map(|next| next.text());
+        match token.text() {
+            // Not `==`, `=>`, `!=`, `<=` or `>=`; compound assignments like `+=` count.
+            "=" => {
+                !matches!(next, Some("=" | ">")) && !matches!(previous, Some("=" | "!" | "<" | ">"))
+            }
+            "+" | "-" => next == Some(token.text()),
+            "push" | "pop" => previous == Some(".") && next == Some("("),
+            _ => false,
+        }
+    })
+}
+
+/// Reports state variables of a namespaced struct type, which live in the regular storage
+/// layout instead of the namespace.
+fn check_state_variables(
+    members: &SyntaxNode,
+    namespaces: &[Namespace],
+    issues: &mut Vec<NamespacedStorageIssue>,
+) {
+    for variable in members
+        .child_nodes()
+        .filter(|node| node.kind() == SyntaxKind::Variable)
+    {
+        let Some(name) = variable.name() else {
+            continue;
+        };
+        let Some(ty) = variable.first_token() else {
+            continue;
+        };
+        let Some(namespace) = namespaces
+            .iter()
+            .find(|namespace| namespace.name == ty.text())
+        else {
+            continue;
+        };
+        issues.push(NamespacedStorageIssue {
+            kind: NamespacedStorageIssueKind::OutsideNamespace,
+            name: namespace.name.clone(),
+            namespace: namespace.id.clone(),
+            range: name.range(),
+            message: format!(
+                "state variable `{}` stores the namespaced struct `{}` (erc7201:{}) outside its namespace",
+                name.text(),
+                namespace.name,
+                namespace.id
+            ),
+        });
+    }
+}
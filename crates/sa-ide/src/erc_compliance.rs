@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_def::DefKind;
+use sa_span::TextRange;
+use sa_syntax::ast::ItemKind;
+use sa_syntax::docs::{DocTagKind, item_docs};
+use serde_json::Value;
+
+use crate::contract_graph::{ContractKind, contract_header};
+
+use Mutability::{NonPayable, Payable, View};
+
+/// A token standard whose required interface can be checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErcStandard {
+    Erc20,
+    Erc165,
+    Erc721,
+    Erc1155,
+}
+
+impl ErcStandard {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErcStandard::Erc20 => "ERC-20",
+            ErcStandard::Erc165 => "ERC-165",
+            ErcStandard::Erc721 => "ERC-721",
+            ErcStandard::Erc1155 => "ERC-1155",
+        }
+    }
+
+    /// Parses `20`, `ERC20` or `ERC-20`, in any case.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.to_ascii_lowercase();
+        let number = value.strip_prefix("erc").unwrap_or(&value);
+        match number.strip_prefix('-').unwrap_or(number) {
+            "20" => Some(ErcStandard::Erc20),
+            "165" => Some(ErcStandard::Erc165),
+            "721" => Some(ErcStandard::Erc721),
+            "1155" => Some(ErcStandard::Erc1155),
+            _ => None,
+        }
+    }
+
+    /// The standard a base contract named `name` implements, for the interfaces and reference
+    /// implementations of OpenZeppelin, solmate and solady.
+    fn from_base_name(name: &str) -> Option<Self> {
+        match name {
+            "IERC20" | "ERC20" => Some(ErcStandard::Erc20),
+            "IERC165" | "ERC165" => Some(ErcStandard::Erc165),
+            "IERC721" | "ERC721" => Some(ErcStandard::Erc721),
+            "IERC1155" | "ERC1155" => Some(ErcStandard::Erc1155),
+            _ => None,
+        }
+    }
+
+    fn requirements(self) -> &'static [Requirement] {
+        match self {
+            ErcStandard::Erc20 => ERC20,
+            ErcStandard::Erc165 => ERC165,
+            ErcStandard::Erc721 => ERC721,
+            ErcStandard::Erc1155 => ERC1155,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErcIssueKind {
+    /// The required function or event is not part of the contract's interface.
+    Missing,
+    /// The member exists, but its mutability, return types or indexed parameters differ.
+    Mismatched,
+}
+
+/// A contract claiming a standard without exposing one of its required members as specified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErcComplianceIssue {
+    pub contract: String,
+    /// The range of the contract's name.
+    pub range: TextRange,
+    pub standard: ErcStandard,
+    pub kind: ErcIssueKind,
+    /// The canonical signature of the required member, such as `transfer(address,uint256)`.
+    pub member: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Mutability {
+    Pure,
+    View,
+    NonPayable,
+    Payable,
+}
+
+impl Mutability {
+    fn parse(value: &str) -> Self {
+        match value {
+            "pure" => Mutability::Pure,
+            "view" => Mutability::View,
+            "payable" => Mutability::Payable,
+            _ => Mutability::NonPayable,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Mutability::Pure => "pure",
+            Mutability::View => "view",
+            Mutability::NonPayable => "nonpayable",
+            Mutability::Payable => "payable",
+        }
+    }
+}
+
+enum Requirement {
+    /// Implementations may be stricter than `mutability`: a `view` function can be `pure`, and
+    /// a `payable` one can reject ether.
+    Function {
+        signature: &'static str,
+        mutability: Mutability,
+        returns: &'static [&'static str],
+    },
+    Event {
+        signature: &'static str,
+        indexed: &'static [bool],
+    },
+}
+
+const fn function(
+    signature: &'static str,
+    mutability: Mutability,
+    returns: &'static [&'static str],
+) -> Requirement {
+    Requirement::Function {
+        signature,
+        mutability,
+        returns,
+    }
+}
+
+const fn event(signature: &'static str, indexed: &'static [bool]) -> Requirement {
+    Requirement::Event { signature, indexed }
+}
+
+const ERC20: &[Requirement] = &[
+    function("totalSupply()", View, &["uint256"]),
+    function("balanceOf(address)", View, &["uint256"]),
+    function("transfer(address,uint256)", NonPayable, &["bool"]),
+    function(
+        "transferFrom(address,address,uint256)",
+        NonPayable,
+        &["bool"],
+    ),
+    function("approve(address,uint256)", NonPayable, &["bool"]),
+    function("allowance(address,address)", View, &["uint256"]),
+    event("Transfer(address,address,uint256)", &[true, true, false]),
+    event("Approval(address,address,uint256)", &[true, true, false]),
+];
+
+const ERC165: &[Requirement] = &[function("supportsInterface(bytes4)", View, &["bool"])];
+
+const ERC721: &[Requirement] = &[
+    function("balanceOf(address)", View, &["uint256"]),
+    function("ownerOf(uint256)", View, &["address"]),
+    function(
+        "safeTransferFrom(address,address,uint256,bytes)",
+        Payable,
+        &[],
+    ),
+    function("safeTransferFrom(address,address,uint256)", Payable, &[]),
+    function("transferFrom(address,address,uint256)", Payable, &[]),
+    function("approve(address,uint256)", Payable, &[]),
+    function("setApprovalForAll(address,bool)", NonPayable, &[]),
+    function("getApproved(uint256)", View, &["address"]),
+    function("isApprovedForAll(address,address)", View, &["bool"]),
+    event("Transfer(address,address,uint256)", &[true, true, true]),
+    event("Approval(address,address,uint256)", &[true, true, true]),
+    event("ApprovalForAll(address,address,bool)", &[true, true, false]),
+];
+
+const ERC1155: &[Requirement] = &[
+    function(
+        "safeTransferFrom(address,address,uint256,uint256,bytes)",
+        NonPayable,
+        &[],
+    ),
+    function(
+        "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+        NonPayable,
+        &[],
+    ),
+    function("balanceOf(address,uint256)", View, &["uint256"]),
+    function("balanceOfBatch(address[],uint256[])", View, &["uint256[]"]),
+    function("setApprovalForAll(address,bool)", NonPayable, &[]),
+    function("isApprovedForAll(address,address)", View, &["bool"]),
+    event(
+        "TransferSingle(address,address,address,uint256,uint256)",
+        &[true, true, true, false, false],
+    ),
+    event(
+        "TransferBatch(address,address,address,uint256[],uint256[])",
+        &[true, true, true, false, false],
+    ),
+    event("ApprovalForAll(address,address,bool)", &[true, true, false]),
+    event("URI(string,uint256)", &[false, true]),
+];
+
+/// Checks the deployable contracts of `file_id` against the standards they claim, by
+/// inheriting a well-known interface or implementation (`IERC20`, `ERC721`, ...) or with a
+/// `@custom:erc 20, 721` NatSpec tag. ERC-721 and ERC-1155 include ERC-165. Abstract contracts
+/// are left alone, as deriving contracts may complete them.
+pub(crate) fn erc_compliance(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    abi: impl Fn(&str) -> Option<Value>,
+) -> Vec<ErcComplianceIssue> {
+    let program = sa_hir::lowered_program(db, project_id);
+    let text = db.file_input(file_id).text(db);
+    let cst = sa_hir::syntax(db, file_id);
+    let annotations = annotated_standards(text);
+
+    let mut issues = Vec::new();
+    for contract in cst.contracts() {
+        db.check_cancelled();
+        if contract_header(contract) != (ContractKind::Contract, false) {
+            continue;
+        }
+        let Some(name) = contract.name() else {
+            continue;
+        };
+        let mut standards = annotations.get(name.text()).cloned().unwrap_or_default();
+        if let Some(entry) = program
+            .def_map()
+            .entries_by_name_in_file(file_id, name.text())
+            .into_iter()
+            .find(|entry| entry.kind() == DefKind::Contract)
+        {
+            let def_map = program.def_map();
+            for base in sa_hir::linearized_bases(db, &program, entry.id()).bases() {
+                if let Some(base) = def_map.entry(*base)
+                    && let Some(standard) = ErcStandard::from_base_name(base.location().name())
+                {
+                    standards.push(standard);
+                }
+            }
+        }
+        if standards
+            .iter()
+            .any(|standard| matches!(standard, ErcStandard::Erc721 | ErcStandard::Erc1155))
+        {
+            standards.push(ErcStandard::Erc165);
+        }
+        standards.sort();
+        standards.dedup();
+        if standards.is_empty() {
+            continue;
+        }
+        let Some(abi) = abi(name.text()) else {
+            continue;
+        };
+        let members = AbiMembers::new(&abi);
+        for standard in standards {
+            for requirement in standard.requirements() {
+                if let Some((kind, member, message)) = members.check(requirement) {
+                    issues.push(ErcComplianceIssue {
+                        contract: name.text().to_string(),
+                        range: name.range(),
+                        standard,
+                        kind,
+                        member: member.to_string(),
+                        message: format!(
+                            "`{}` claims {}: {message}",
+                            name.text(),
+                            standard.as_str()
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// The standards named by the `@custom:erc` tags of each contract in `text`.
+fn annotated_standards(text: &str) -> HashMap<String, Vec<ErcStandard>> {
+    let parse = sa_syntax::parse_file(text);
+    let mut annotations = HashMap::new();
+    parse.with_session(|| {
+        for item in parse.tree().items.iter() {
+            let ItemKind::Contract(contract) = &item.kind else {
+                continue;
+            };
+            let Some(docs) = item_docs(&parse, item) else {
+                continue;
+            };
+            let standards = docs
+                .tags()
+                .iter()
+                .filter(|tag| matches!(&tag.kind, DocTagKind::Custom(name) if name == "erc"))
+                .flat_map(|tag| {
+                    tag.text()
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter_map(ErcStandard::parse)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            if !standards.is_empty() {
+                annotations.insert(contract.name.to_string(), standards);
+            }
+        }
+    });
+    annotations
+}
+
+/// The functions and events of a contract's ABI, by canonical signature.
+struct AbiMembers<'a> {
+    functions: HashMap<String, &'a Value>,
+    events: HashMap<String, &'a Value>,
+}
+
+impl<'a> AbiMembers<'a> {
+    fn new(abi: &'a Value) -> Self {
+        let mut functions = HashMap::new();
+        let mut events = HashMap::new();
+        for item in abi.as_array().into_iter().flatten() {
+            let Some(name) = item.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let signature = format!("{name}({})", param_types(item.get("inputs")).join(","));
+            match item.get("type").and_then(Value::as_str) {
+                Some("function") => functions.insert(signature, item),
+                Some("event") => events.insert(signature, item),
+                _ => None,
+            };
+        }
+        Self { functions, events }
+    }
+
+    /// What is wrong with the contract's version of `requirement`, if anything.
+    fn check(&self, requirement: &Requirement) -> Option<(ErcIssueKind, &'static str, String)> {
+        match *requirement {
+            Requirement::Function {
+                signature,
+                mutability,
+                returns,
+            } => {
+                let Some(function) = self.functions.get(signature) else {
+                    let message = format!(
+                        "missing function `{signature}`{}",
+                        similar(self.functions.keys(), signature)
+                    );
+                    return Some((ErcIssueKind::Missing, signature, message));
+                };
+                let actual = Mutability::parse(
+                    function
+                        .get("stateMutability")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                );
+                if actual > mutability {
+                    let message = format!(
+                        "`{signature}` must be {} but is {}",
+                        mutability.as_str(),
+                        actual.as_str()
+                    );
+                    return Some((ErcIssueKind::Mismatched, signature, message));
+                }
+                let outputs = param_types(function.get("outputs"));
+                if outputs != returns {
+                    let message = format!(
+                        "`{signature}` must return ({}) but returns ({})",
+                        returns.join(","),
+                        outputs.join(",")
+                    );
+                    return Some((ErcIssueKind::Mismatched, signature, message));
+                }
+                None
+            }
+            Requirement::Event { signature, indexed } => {
+                let Some(event) = self.events.get(signature) else {
+                    let message = format!(
+                        "missing event `{signature}`{}",
+                        similar(self.events.keys(), signature)
+                    );
+                    return Some((ErcIssueKind::Missing, signature, message));
+                };
+                let actual = event
+                    .get("inputs")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .map(|input| input.get("indexed").and_then(Value::as_bool) == Some(true))
+                    .collect::<Vec<_>>();
+                if actual != indexed {
+                    let message = format!(
+                        "event `{signature}` must be declared as `{}` but is `{}`",
+                        indexed_signature(signature, indexed),
+                        indexed_signature(signature, &actual)
+                    );
+                    return Some((ErcIssueKind::Mismatched, signature, message));
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Mentions the members sharing the name of `signature`, which likely were meant to match it.
+fn similar<'a>(signatures: impl Iterator<Item = &'a String>, signature: &str) -> String {
+    let name = signature.split('(').next().unwrap_or(signature);
+    let mut found = signatures
+        .filter(|candidate| candidate.split('(').next() == Some(name))
+        .map(|candidate| format!("`{candidate}`"))
+        .collect::<Vec<_>>();
+    if found.is_empty() {
+        return String::new();
+    }
+    found.sort();
+    format!(" (found {})", found.join(", "))
+}
+
+/// `Transfer(address indexed,address indexed,uint256)` for `Transfer(address,address,uint256)`.
+fn indexed_signature(signature: &str, indexed: &[bool]) -> String {
+    let Some((name, params)) = signature
+        .strip_suffix(')')
+        .and_then(|signature| signature.split_once('('))
+    else {
+        return signature.to_string();
+    };
+    let params = params
+        .split(',')
+        .enumerate()
+        .map(|(index, ty)| {
+            if indexed.get(index) == Some(&true) {
+                format!("{ty} indexed")
+            } else {
+                ty.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("{name}({})", params.join(","))
+}
+
+/// The canonical ABI types of `params`, with tuples spelled out as `(T1,T2)`.
+fn param_types(params: Option<&Value>) -> Vec<String> {
+    params
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(canonical_type)
+        .collect()
+}
+
+fn canonical_type(param: &Value) -> String {
+    let ty = param
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match ty.strip_prefix("tuple") {
+        Some(dimensions) => format!(
+            "({}){dimensions}",
+            param_types(param.get("components")).join(",")
+        ),
+        None => ty.to_string(),
+    }
+}
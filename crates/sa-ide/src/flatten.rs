@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_def::DefKind;
+use sa_span::{TextRange, TextSize};
+use sa_syntax::cst::{SyntaxElement, SyntaxKind, SyntaxNode};
+
+/// Renders the contract declared around `offset` with the members of all its bases merged in,
+/// most base first. Each contract's members are headed by a comment naming it; functions and
+/// modifiers overridden further down the hierarchy are left out.
+pub(crate) fn flatten_contract(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    offset: TextSize,
+) -> Option<String> {
+    let program = sa_hir::lowered_program(db, project_id);
+    let text = db.file_input(file_id).text(db).clone();
+    let cst = sa_hir::syntax(db, file_id);
+    let node = cst.contract_at_offset(offset)?;
+    let name = node.name()?.text();
+    let contract = program
+        .def_map()
+        .entries_by_name_in_file(file_id, name)
+        .into_iter()
+        .find(|entry| entry.kind() == DefKind::Contract)?
+        .id();
+    let linearization = sa_hir::linearized_bases(db, &program, contract);
+
+    let mut csts = HashMap::new();
+    let mut overridden = HashSet::new();
+    let mut sections = Vec::new();
+    for (index, base) in linearization.bases().iter().enumerate() {
+        db.check_cancelled();
+        let Some(entry) = program.def_map().entry(*base) else {
+            continue;
+        };
+        let location = entry.location();
+        let base_cst = csts
+            .entry(location.file_id())
+            .or_insert_with(|| sa_hir::syntax(db, location.file_id()));
+        let Some(body) = base_cst
+            .contract_by_name(location.name())
+            .and_then(|contract| contract.child_node(SyntaxKind::ContractBody))
+        else {
+            continue;
+        };
+        let mut members = Vec::new();
+        for member in body.child_nodes() {
+            // `using for` directives in a contract do not apply to contracts deriving from it.
+            if index > 0 && member.kind() == SyntaxKind::Using {
+                continue;
+            }
+            if let Some(key) = override_key(member)
+                && !overridden.insert(key)
+            {
+                continue;
+            }
+            members.push(member.text());
+        }
+        sections.push((location.name().to_string(), members));
+    }
+
+    let body = node.child_node(SyntaxKind::ContractBody)?;
+    let header = &text[usize::from(node.range().start())..usize::from(body.range().start())];
+    let mut out = format!("{} {{\n", header.trim_end());
+    let mut first = true;
+    for (contract, members) in sections.iter().rev() {
+        if members.is_empty() {
+            continue;
+        }
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        let _ = writeln!(out, "    // from {contract}");
+        for member in members {
+            let _ = writeln!(out, "    {member}");
+        }
+    }
+    out.push_str("}\n");
+    Some(out)
+}
+
+/// Inlines `file_id` and every file it imports, dependencies first, into one source file. Import
+/// directives are dropped, `pragma`s are merged and the SPDX license identifiers combined into
+/// one header. Import aliases are not rewritten, so files relying on them need fixing by hand.
+pub(crate) fn flatten_file(db: &Database, project_id: ProjectId, file_id: FileId) -> String {
+    let project = db.project_input(project_id);
+    let graph = sa_hir::import_graph_for_project(db, project);
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    dependencies_first(&graph, file_id, &mut visited, &mut order);
+
+    let root = project.workspace(db).root().as_str().to_string();
+    let mut licenses = Vec::new();
+    let mut pragmas = Vec::new();
+    let mut sections = Vec::new();
+    for file_id in order {
+        db.check_cancelled();
+        let text = db.file_input(file_id).text(db).clone();
+        let cst = sa_hir::syntax(db, file_id);
+        let mut removed = Vec::new();
+        for child in cst.root().children() {
+            match child {
+                SyntaxElement::Node(node) if node.kind() == SyntaxKind::Pragma => {
+                    let pragma = node.text().split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !pragmas.contains(&pragma) {
+                        pragmas.push(pragma);
+                    }
+                    removed.push(node.range());
+                }
+                SyntaxElement::Node(node) if node.kind() == SyntaxKind::Import => {
+                    removed.push(node.range());
+                }
+                SyntaxElement::Token(token) if token.kind() == SyntaxKind::LineComment => {
+                    let Some((_, license)) = token.text().split_once("SPDX-License-Identifier:")
+                    else {
+                        continue;
+                    };
+                    let license = license.trim().to_string();
+                    if !license.is_empty() && !licenses.contains(&license) {
+                        licenses.push(license);
+                    }
+                    removed.push(token.range());
+                }
+                _ => {}
+            }
+        }
+        let body = without_ranges(&text, &removed);
+        if body.is_empty() {
+            continue;
+        }
+        let path = db.file_path(file_id);
+        let path = path.as_str();
+        let path = path
+            .strip_prefix(&root)
+            .map_or(path, |relative| relative.trim_start_matches('/'));
+        sections.push(format!("// {path}\n\n{body}\n"));
+    }
+
+    let mut out = String::new();
+    if !licenses.is_empty() {
+        let _ = writeln!(
+            out,
+            "// SPDX-License-Identifier: {}",
+            licenses.join(" AND ")
+        );
+    }
+    for pragma in &pragmas {
+        let _ = writeln!(out, "{pragma}");
+    }
+    for section in sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&section);
+    }
+    out
+}
+
+/// Post-order over the import graph, so every file comes after the files it imports. Cycles are
+/// cut where they are first entered.
+fn dependencies_first(
+    graph: &sa_hir::ImportGraph,
+    file_id: FileId,
+    visited: &mut HashSet<FileId>,
+    order: &mut Vec<FileId>,
+) {
+    if !visited.insert(file_id) {
+        return;
+    }
+    for &import in graph.imports(file_id) {
+        dependencies_first(graph, import, visited, order);
+    }
+    order.push(file_id);
+}
+
+/// `text` with `ranges` cut out and runs of blank lines collapsed, trimmed.
+fn without_ranges(text: &str, ranges: &[TextRange]) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut start = 0;
+    for range in ranges {
+        kept.push_str(&text[start..usize::from(range.start())]);
+        start = usize::from(range.end());
+    }
+    kept.push_str(&text[start..]);
+
+    let mut out = String::with_capacity(kept.len());
+    let mut blank_lines = 0;
+    for line in kept.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Identifies the members a more derived contract can override: functions by name and
+/// parameter types, `receive` and `fallback` by kind, modifiers by name.
+fn override_key(member: &SyntaxNode) -> Option<String> {
+    match member.kind() {
+        SyntaxKind::Function => {
+            let name = match member.name() {
+                Some(name) => name.text(),
+                None => member.first_token()?.text(),
+            };
+            if name == "constructor" {
+                return None;
+            }
+            let params = member
+                .child_node(SyntaxKind::ParamList)
+                .map(|list| param_types(&list.text()))
+                .unwrap_or_default();
+            Some(format!("function {name}({})", params.join(",")))
+        }
+        SyntaxKind::Modifier => Some(format!("modifier {}", member.name()?.text())),
+        _ => None,
+    }
+}
+
+/// The parameter types of a parameter list as written, without names and data locations.
+fn param_types(list: &str) -> Vec<String> {
+    let inner = list
+        .trim()
+        .strip_prefix('(')
+        .and_then(|list| list.strip_suffix(')'))
+        .unwrap_or(list);
+    let mut params = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                params.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&inner[start..]);
+    params
+        .into_iter()
+        .filter(|param| !param.trim().is_empty())
+        .map(|param| {
+            let mut words = param
+                .split_whitespace()
+                .filter(|word| !matches!(*word, "memory" | "storage" | "calldata"))
+                .collect::<Vec<_>>();
+            if words.len() > 1
+                && words.last().is_some_and(|word| {
+                    *word != "payable"
+                        && word.bytes().all(|byte| {
+                            byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+                        })
+                })
+                && words[words.len() - 2] != "=>"
+            {
+                words.pop();
+            }
+            words.concat()
+        })
+        .collect()
+}
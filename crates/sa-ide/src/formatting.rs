@@ -1,16 +1,10 @@
-use forge_fmt::{FormatterConfig, FormatterResult};
+use forge_fmt::FormatterConfig;
 use sa_span::{TextRange, TextSize};
 
 use crate::TextEdit;
 
 pub fn format_edit(text: &str, config: &FormatterConfig) -> Option<TextEdit> {
-    let formatted = match forge_fmt::format(text, config.clone()) {
-        FormatterResult::Ok(formatted)
-        | FormatterResult::OkWithDiagnostics(formatted, _)
-        | FormatterResult::ErrRecovered(formatted, _) => formatted,
-        FormatterResult::Err(_) => return None,
-    };
-
+    let formatted = sa_fmt::format(text, config)?;
     if formatted == text {
         return None;
     }
@@ -21,3 +15,15 @@ pub fn format_edit(text: &str, config: &FormatterConfig) -> Option<TextEdit> {
         new_text: formatted,
     })
 }
+
+pub fn format_edits(text: &str, config: &FormatterConfig) -> Option<Vec<TextEdit>> {
+    sa_fmt::format_edits(text, config)
+}
+
+pub fn format_range_edits(
+    text: &str,
+    range: TextRange,
+    config: &FormatterConfig,
+) -> Option<Vec<TextEdit>> {
+    sa_fmt::format_ranges(text, &[range], config)
+}
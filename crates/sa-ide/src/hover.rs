@@ -1,11 +1,15 @@
 use sa_base_db::{FileId, ProjectId};
 use sa_def::{DefEntry, DefKind};
-use sa_hir::{Definition, HirDatabase, LocalDef, LocalDefKind, Semantics, lowered_program};
+use sa_hir::{
+    Definition, HirDatabase, LocalDef, LocalDefKind, Semantics, lowered_program, yul_hir,
+};
+use sa_project_model::find_artifact;
 use sa_span::{TextRange, TextSize};
 use sa_syntax::{
     Parse,
-    ast::{Item, ItemKind, VariableDefinition},
+    ast::{ItemKind, VariableDefinition},
     tokens::ident_range_at_offset,
+    visit::{Visitor, walk_variable},
 };
 
 use crate::syntax_utils::{
@@ -40,7 +44,24 @@ pub fn hover(
             let parse = sa_syntax::parse_file(text.as_ref());
 
             let label = build_label(db, project_id, &parse, text.as_ref(), entry);
-            let docs = docs_for_entry_with_parse(db, project_id, def_file_id, &parse, entry);
+            let mut docs = docs_for_entry_with_parse(db, project_id, def_file_id, &parse, entry);
+            if entry.kind() == DefKind::Function
+                && let Some(function) = semantics.bound_function_at(file_id, offset)
+                && function.file_id == def_file_id
+                && function.range == entry.location().range()
+            {
+                let note = format!("Attached to `{}` by `using for`.", function.receiver);
+                docs = Some(match docs {
+                    Some(docs) if !docs.is_empty() => format!("{note}\n\n{docs}"),
+                    _ => note,
+                });
+            }
+            if let Some(note) = artifact_note(db, project_id, entry) {
+                docs = Some(match docs {
+                    Some(docs) if !docs.is_empty() => format!("{docs}\n\n{note}"),
+                    _ => note,
+                });
+            }
             let contents = format_hover_contents(&label, docs.as_deref());
 
             Some(HoverResult {
@@ -49,8 +70,15 @@ pub fn hover(
             })
         }
         Definition::Local(local) => {
-            let parse = sa_syntax::parse_file(hover_text.as_ref());
-            let label = local_label(&parse, hover_text.as_ref(), &local);
+            let label = if local.kind().is_yul() {
+                yul_hir(db, file_id)
+                    .def(local.range())
+                    .map(|def| def.label())
+                    .unwrap_or_else(|| local.name().to_string())
+            } else {
+                let parse = sa_syntax::parse_file(hover_text.as_ref());
+                local_label(&parse, hover_text.as_ref(), &local)
+            };
             Some(HoverResult {
                 range: hover_range.unwrap_or_else(|| local.range()),
                 contents: format_hover_contents(&label, None),
@@ -59,6 +87,41 @@ pub fn hover(
     }
 }
 
+/// The largest runtime code EIP-170 lets a contract deploy.
+const MAX_DEPLOYED_BYTECODE_SIZE: usize = 24_576;
+
+/// Bytecode size of a contract, or the selectors of a function, from the project's `forge
+/// build` artifacts.
+fn artifact_note(db: &dyn HirDatabase, project_id: ProjectId, entry: &DefEntry) -> Option<String> {
+    let project = db.project_input(project_id);
+    let artifacts = project.artifacts(db);
+    let root = project.workspace(db).root();
+    let path = db.file_path(entry.location().file_id());
+    let artifact = |name| find_artifact(artifacts, root, &path, name);
+    match entry.kind() {
+        DefKind::Contract => {
+            let artifact = artifact(entry.location().name())?;
+            let size = artifact.deployed_bytecode_size()?;
+            Some(if size > MAX_DEPLOYED_BYTECODE_SIZE {
+                format!(
+                    "Deployed bytecode: {size} bytes, over the {MAX_DEPLOYED_BYTECODE_SIZE}-byte EIP-170 limit"
+                )
+            } else {
+                format!("Deployed bytecode: {size} bytes")
+            })
+        }
+        DefKind::Function => {
+            let artifact = artifact(entry.container()?)?;
+            let selectors = artifact
+                .selectors_of(entry.location().name())
+                .map(|(signature, selector)| format!("`0x{selector}` `{signature}`"))
+                .collect::<Vec<_>>();
+            (!selectors.is_empty()).then(|| format!("Selector: {}", selectors.join(", ")))
+        }
+        _ => None,
+    }
+}
+
 fn format_hover_contents(label: &str, docs: Option<&str>) -> String {
     let code = format!("```solidity\n{label}\n```");
     match docs {
@@ -111,6 +174,11 @@ fn build_label_with_parse(parse: &Parse, text: &str, entry: &DefEntry) -> String
             _ => {}
         }
     }
+    if entry.kind() == DefKind::Variant
+        && let Some(container) = entry.container()
+    {
+        return format!("{container}.{name}");
+    }
 
     format!("{} {name}", def_kind_label(entry.kind()))
 }
@@ -137,165 +205,57 @@ fn def_kind_label(kind: DefKind) -> &'static str {
         DefKind::Modifier => "modifier",
         DefKind::Variable => "variable",
         DefKind::Udvt => "type",
+        DefKind::Field => "field",
+        DefKind::Variant => "variant",
+        DefKind::YulFunction => "function",
     }
 }
 
 fn local_label(parse: &Parse, text: &str, local: &LocalDef) -> String {
     let label = match local.kind() {
-        LocalDefKind::Parameter => find_param_definition(parse, local, false)
-            .map(|param| format_param(parse, text, param))
-            .unwrap_or_else(|| local.name().to_string()),
-        LocalDefKind::NamedReturn => find_param_definition(parse, local, true)
-            .map(|param| format_param(parse, text, param))
-            .unwrap_or_else(|| local.name().to_string()),
-        LocalDefKind::Local => find_local_definition(parse, local)
-            .map(|param| format_param(parse, text, param))
-            .unwrap_or_else(|| local.name().to_string()),
+        LocalDefKind::Parameter | LocalDefKind::NamedReturn | LocalDefKind::Local => {
+            find_variable_definition(parse, local)
+                .map(|param| format_param(parse, text, param))
+                .unwrap_or_else(|| local.name().to_string())
+        }
+        LocalDefKind::YulVariable | LocalDefKind::YulFunction => local.name().to_string(),
     };
 
     match local.kind() {
         LocalDefKind::Parameter => format!("parameter {label}"),
         LocalDefKind::NamedReturn => format!("return {label}"),
         LocalDefKind::Local => format!("local {label}"),
+        LocalDefKind::YulVariable | LocalDefKind::YulFunction => label,
     }
 }
 
-fn find_param_definition<'a>(
-    parse: &'a Parse,
-    local: &LocalDef,
-    in_returns: bool,
-) -> Option<&'a VariableDefinition<'static>> {
-    for item in parse.tree().items.iter() {
-        let found = find_param_in_item(parse, item, local, in_returns);
-        if found.is_some() {
-            return found;
-        }
-    }
-    None
-}
-
-fn find_param_in_item<'a>(
-    parse: &'a Parse,
-    item: &'a Item<'static>,
-    local: &LocalDef,
-    in_returns: bool,
-) -> Option<&'a VariableDefinition<'static>> {
-    match &item.kind {
-        ItemKind::Contract(contract) => contract
-            .body
-            .iter()
-            .find_map(|item| find_param_in_item(parse, item, local, in_returns)),
-        ItemKind::Function(function) => {
-            let params = if in_returns {
-                function
-                    .header
-                    .returns
-                    .as_ref()
-                    .map(|returns| returns.vars.iter())
-            } else {
-                Some(function.header.parameters.vars.iter())
-            };
-            params
-                .into_iter()
-                .flatten()
-                .find(|param| matches_local_def(parse, local, param))
-        }
-        _ => None,
-    }
-}
-
-fn find_local_definition<'a>(
+/// The parameter, named return or local declared at `local`'s range.
+fn find_variable_definition<'a>(
     parse: &'a Parse,
     local: &LocalDef,
 ) -> Option<&'a VariableDefinition<'static>> {
-    for item in parse.tree().items.iter() {
-        let found = find_local_in_item(parse, item, local);
-        if found.is_some() {
-            return found;
-        }
+    struct Finder<'a, 'p> {
+        parse: &'p Parse,
+        local: &'p LocalDef,
+        found: Option<&'a VariableDefinition<'static>>,
     }
-    None
-}
 
-fn find_local_in_item<'a>(
-    parse: &'a Parse,
-    item: &'a Item<'static>,
-    local: &LocalDef,
-) -> Option<&'a VariableDefinition<'static>> {
-    match &item.kind {
-        ItemKind::Contract(contract) => contract
-            .body
-            .iter()
-            .find_map(|item| find_local_in_item(parse, item, local)),
-        ItemKind::Function(function) => function
-            .body
-            .as_ref()
-            .and_then(|body| find_local_in_block(parse, body, local)),
-        _ => None,
-    }
-}
-
-fn find_local_in_block<'a>(
-    parse: &'a Parse,
-    block: &'a sa_syntax::ast::Block<'static>,
-    local: &LocalDef,
-) -> Option<&'a VariableDefinition<'static>> {
-    for stmt in block.stmts.iter() {
-        if let Some(found) = find_local_in_stmt(parse, stmt, local) {
-            return Some(found);
-        }
-    }
-    None
-}
-
-fn find_local_in_stmt<'a>(
-    parse: &'a Parse,
-    stmt: &'a sa_syntax::ast::Stmt<'static>,
-    local: &LocalDef,
-) -> Option<&'a VariableDefinition<'static>> {
-    match &stmt.kind {
-        sa_syntax::ast::StmtKind::DeclSingle(var) => {
-            matches_local_def(parse, local, var).then_some(var)
-        }
-        sa_syntax::ast::StmtKind::DeclMulti(vars, _) => vars.iter().find_map(|var| {
-            if let sa_syntax::ast::interface::SpannedOption::Some(var) = var {
-                matches_local_def(parse, local, var).then_some(var)
-            } else {
-                None
-            }
-        }),
-        sa_syntax::ast::StmtKind::Block(block)
-        | sa_syntax::ast::StmtKind::UncheckedBlock(block) => {
-            find_local_in_block(parse, block, local)
-        }
-        sa_syntax::ast::StmtKind::For { init, body, .. } => {
-            if let Some(init) = init.as_deref()
-                && let Some(found) = find_local_in_stmt(parse, init, local)
-            {
-                return Some(found);
+    impl<'a> Visitor<'a> for Finder<'a, '_> {
+        fn visit_variable(&mut self, var: &'a VariableDefinition<'static>) {
+            if self.found.is_none() && matches_local_def(self.parse, self.local, var) {
+                self.found = Some(var);
             }
-            find_local_in_stmt(parse, body, local)
-        }
-        sa_syntax::ast::StmtKind::If(_, then_branch, else_branch) => {
-            find_local_in_stmt(parse, then_branch, local).or_else(|| {
-                else_branch
-                    .as_deref()
-                    .and_then(|stmt| find_local_in_stmt(parse, stmt, local))
-            })
+            walk_variable(self, var);
         }
-        sa_syntax::ast::StmtKind::While(_, body) | sa_syntax::ast::StmtKind::DoWhile(body, _) => {
-            find_local_in_stmt(parse, body, local)
-        }
-        sa_syntax::ast::StmtKind::Try(stmt_try) => stmt_try.clauses.iter().find_map(|clause| {
-            clause
-                .args
-                .vars
-                .iter()
-                .find(|param| matches_local_def(parse, local, param))
-                .or_else(|| find_local_in_block(parse, &clause.block, local))
-        }),
-        _ => None,
     }
+
+    let mut finder = Finder {
+        parse,
+        local,
+        found: None,
+    };
+    finder.visit_source_unit(parse.tree());
+    finder.found
 }
 
 fn matches_local_def(parse: &Parse, local: &LocalDef, var: &VariableDefinition<'_>) -> bool {
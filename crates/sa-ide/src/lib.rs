@@ -1,40 +1,81 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use forge_fmt::FormatterConfig;
-use sa_base_db::{Database, FileId, LanguageKind, ProjectId};
+use sa_base_db::{CancellationToken, Database, Durability, FileId, LanguageKind, SaDatabaseExt};
 use sa_config::ResolvedFoundryConfig;
 use sa_hir::{Definition, DefinitionLocation, Semantics};
-use sa_paths::NormalizedPath;
-use sa_project_model::{FoundryProfile, FoundryResolver, FoundryWorkspace};
-use sa_span::{TextRange, TextSize};
+use sa_paths::{NormalizedPath, WorkspacePath};
+use sa_project_model::{FoundryProfile, FoundryResolver, FoundryWorkspace, find_artifact};
+use sa_span::{LineIndex, TextRange, TextSize};
 use sa_vfs::VfsSnapshot;
-use tracing::debug;
+use tracing::{debug, debug_span};
 
+mod assists;
 mod code_actions;
 mod completion;
+mod contract_graph;
+mod erc7201;
+mod erc_compliance;
+mod flatten;
 mod formatting;
 mod hover;
+mod memory;
+mod metrics;
 mod rename;
+mod selector_collisions;
 mod signature_help;
+mod standard_json;
+mod storage_upgrade;
+mod symbol_index;
 mod symbols;
 mod syntax_outline;
 mod syntax_utils;
+mod test_discovery;
 
-pub use code_actions::{CodeAction, CodeActionDiagnostic, CodeActionKind};
+pub use code_actions::{AssistResolveStrategy, CodeAction, CodeActionDiagnostic, CodeActionKind};
 pub use completion::{CompletionInsertTextFormat, CompletionItem, CompletionItemKind};
+pub use contract_graph::{ContractEdge, ContractGraph, ContractKind, ContractNode};
+pub use erc_compliance::{ErcComplianceIssue, ErcIssueKind, ErcStandard};
+pub use erc7201::{NamespacedStorageIssue, NamespacedStorageIssueKind, erc7201_slot};
 pub use hover::HoverResult;
-pub use sa_ide_assists::{SourceChange, SourceFileEdit, TextEdit};
-pub use sa_ide_db::Reference;
+pub use memory::{CacheStats, MemoryUsage, MemoryUsageEntry};
+pub use metrics::{ContractMetrics, FunctionMetrics};
+pub use sa_base_db::{CancellationToken, ProjectId};
+pub use sa_hir::{
+    HIR_CACHE_FILE, HirCache, ImportCycle, ImportGraph, LinearizationError, LinearizationErrorKind,
+};
+pub use sa_ide_assists::{FileSystemEdit, SourceChange, SourceFileEdit, TextEdit};
+pub use sa_ide_db::{ProjectStructure, Reference};
+pub use sa_project_model::ContractArtifact;
+pub use sa_sema::{SemaCacheConfig, StorageSlot};
+pub use selector_collisions::{SelectorCollision, SelectorFunction};
 pub use signature_help::{ParameterInformation, SignatureHelp, SignatureInformation};
+pub use storage_upgrade::{StorageUpgradeIssue, StorageUpgradeIssueKind, compare_layouts};
+pub use symbol_index::SymbolDefinition;
 pub use symbols::WorkspaceSymbol;
 pub use syntax_outline::{SymbolInfo, SymbolKind};
 pub use syntax_utils::docs_for_item;
+pub use test_discovery::{TestContract, TestFunction, TestKind};
+
+/// Why a file has no semantic model. Requests about it fall back to syntax-based heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemaUnavailable {
+    /// The file itself does not parse.
+    ParseErrors,
+    /// The file parses, but it or a file it imports does not compile.
+    CompileErrors,
+}
 
 #[derive(Default)]
 pub struct AnalysisChange {
     vfs: Option<VfsSnapshot>,
     workspace: Option<FoundryWorkspace>,
     config: Option<ResolvedFoundryConfig>,
+    projects: Vec<(ProjectId, ResolvedFoundryConfig)>,
+    removed_projects: Vec<ProjectId>,
+    artifacts: Option<Vec<ContractArtifact>>,
+    project_artifacts: Vec<(ProjectId, Vec<ContractArtifact>)>,
 }
 
 impl AnalysisChange {
@@ -53,6 +94,29 @@ impl AnalysisChange {
     pub fn set_config(&mut self, config: ResolvedFoundryConfig) {
         self.config = Some(config);
     }
+
+    /// Sets the configuration of an additional project, e.g. a nested Foundry root.
+    pub fn set_project_config(&mut self, project_id: ProjectId, config: ResolvedFoundryConfig) {
+        self.projects.push((project_id, config));
+    }
+
+    /// Unloads an additional project. The primary project cannot be removed.
+    pub fn remove_project(&mut self, project_id: ProjectId) {
+        self.removed_projects.push(project_id);
+    }
+
+    /// Sets the `forge build` artifacts of the primary project.
+    pub fn set_artifacts(&mut self, artifacts: Vec<ContractArtifact>) {
+        self.artifacts = Some(artifacts);
+    }
+
+    pub fn set_project_artifacts(
+        &mut self,
+        project_id: ProjectId,
+        artifacts: Vec<ContractArtifact>,
+    ) {
+        self.project_artifacts.push((project_id, artifacts));
+    }
 }
 
 pub struct AnalysisHost {
@@ -69,19 +133,53 @@ impl AnalysisHost {
     }
 
     pub fn apply_change(&mut self, change: AnalysisChange) {
+        // Projects go first: they decide which files are dependencies.
+        if let Some(config) = change.config {
+            self.db.set_project_input(self.project_id, Arc::new(config));
+        } else if let Some(workspace) = change.workspace {
+            let active_profile = FoundryProfile::new("default");
+            let config = ResolvedFoundryConfig::new(workspace, active_profile);
+            self.db.set_project_input(self.project_id, Arc::new(config));
+        }
+
+        for project_id in change.removed_projects {
+            if project_id != self.project_id {
+                self.db.remove_project(project_id);
+            }
+        }
+        for (project_id, config) in change.projects {
+            self.db.set_project_input(project_id, Arc::new(config));
+        }
+        if let Some(artifacts) = change.artifacts {
+            self.db
+                .set_project_artifacts(self.project_id, Arc::new(artifacts));
+        }
+        for (project_id, artifacts) in change.project_artifacts {
+            self.db
+                .set_project_artifacts(project_id, Arc::new(artifacts));
+        }
+
         if let Some(vfs) = change.vfs {
+            // Files that left the VFS (deleted, or renamed to a new id) must stop serving their
+            // old contents, definitions and import targets.
+            self.db
+                .retain_files(|file_id| vfs.file_text(file_id).is_some());
             for (file_id, _) in vfs.iter() {
                 let text = vfs.file_text(file_id);
                 let path = vfs.path(file_id);
                 if let (Some(text), Some(path)) = (text, path) {
                     let version = vfs.file_version(file_id).unwrap_or(0);
-                    self.db.set_file(
+                    let durability = self.file_durability(path);
+                    self.db.set_file_with_durability(
                         file_id,
                         Arc::from(text),
                         version,
                         LanguageKind::Solidity,
                         Arc::new(path.clone()),
+                        durability,
                     );
+                    self.db
+                        .set_file_edits(file_id, Arc::from(vfs.last_edits(file_id)), durability);
                 } else {
                     debug!(
                         ?file_id,
@@ -92,16 +190,46 @@ impl AnalysisHost {
                 }
             }
         }
+    }
 
-        if let Some(config) = change.config {
-            self.db.set_project_input(self.project_id, Arc::new(config));
-        } else if let Some(workspace) = change.workspace {
-            let active_profile = FoundryProfile::new("default");
-            let config = ResolvedFoundryConfig::new(workspace, active_profile);
-            self.db.set_project_input(self.project_id, Arc::new(config));
+    /// Dependencies are high durability: editing application code then never revalidates
+    /// queries that only read dependency files.
+    fn file_durability(&self, path: &NormalizedPath) -> Durability {
+        let is_dependency = self
+            .db
+            .project_for_path(path)
+            .and_then(|project_id| self.db.project_input_opt(project_id))
+            .is_some_and(|input| input.workspace(&self.db).is_dependency_file(path));
+        if is_dependency {
+            Durability::HIGH
+        } else {
+            Durability::LOW
         }
     }
 
+    /// Installs derived data saved by a previous run (see [`Analysis::hir_cache`]). Files whose
+    /// text matches a cached entry skip parsing and definition collection.
+    pub fn set_hir_cache(&mut self, cache: HirCache) {
+        sa_hir::set_hir_cache(&mut self.db, cache);
+    }
+
+    pub fn set_sema_cache_config(&mut self, config: SemaCacheConfig) {
+        sa_sema::set_sema_cache_config(&mut self.db, config);
+    }
+
+    /// Drops the derived data saved by a previous run and makes every semantic snapshot stale,
+    /// so the next queries start from the current inputs.
+    pub fn clear_caches(&mut self) {
+        sa_hir::set_hir_cache(&mut self.db, HirCache::default());
+        sa_sema::rebuild_sema_snapshots(&mut self.db);
+    }
+
+    /// Forgets where node_modules packages were found, e.g. after a package directory or
+    /// symlink appeared or disappeared.
+    pub fn clear_node_modules(&mut self) {
+        self.db.clear_node_modules();
+    }
+
     pub fn snapshot(&self) -> Analysis {
         Analysis {
             db: self.db.clone(),
@@ -129,10 +257,32 @@ pub struct NavigationTarget {
 }
 
 impl Analysis {
+    /// Aborts queries on this snapshot with `salsa::Cancelled` once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.db.set_cancellation(token);
+        self
+    }
+
+    /// Estimates the memory held by `vfs` and by this snapshot's inputs and derived data.
+    pub fn memory_usage(&self, vfs: &VfsSnapshot) -> MemoryUsage {
+        memory::memory_usage(&self.db, vfs)
+    }
+
+    /// Collects the derived data of every file so it can be saved for the next run.
+    pub fn hir_cache(&self) -> HirCache {
+        sa_hir::export_hir_cache(&self.db)
+    }
+
     pub fn file_text(&self, file_id: FileId) -> Arc<str> {
         self.db.file_input(file_id).text(&self.db).clone()
     }
 
+    pub fn file_line_index(&self, file_id: FileId) -> Arc<LineIndex> {
+        sa_hir::file_line_index(&self.db, self.db.file_input(file_id))
+            .index()
+            .clone()
+    }
+
     pub fn file_path(&self, file_id: FileId) -> Arc<NormalizedPath> {
         self.db.file_path(file_id)
     }
@@ -159,12 +309,267 @@ impl Analysis {
             .clone()
     }
 
-    fn workspace_opt(&self) -> Option<Arc<FoundryWorkspace>> {
+    /// Returns the project owning `file_id`: the one with the nearest root, falling back to the
+    /// primary project for files outside every root.
+    pub fn project_for_file(&self, file_id: FileId) -> ProjectId {
+        let path = self.db.file_path(file_id);
+        self.db.project_for_path(&path).unwrap_or(self.project_id)
+    }
+
+    pub fn project_ids(&self) -> Vec<ProjectId> {
+        self.db.project_ids()
+    }
+
+    /// Builds the semantic snapshot of `project_id` ahead of the first request that needs it.
+    pub fn prime_sema(&self, project_id: ProjectId) {
+        let _span = debug_span!("prime_sema", ?project_id).entered();
+        if let Some(project) = self.db.project_input_opt(project_id) {
+            sa_sema::sema_snapshot_for_project(&self.db, project);
+        }
+    }
+
+    /// Reports why semantic analysis does not cover `file_id`, or `None` if it does. Files
+    /// outside every loaded project are never reported.
+    pub fn sema_unavailable(&self, file_id: FileId) -> Option<SemaUnavailable> {
+        if self.file_kind(file_id) != LanguageKind::Solidity {
+            return None;
+        }
+        let project_id = self.file_project(file_id)?;
+        let workspace = self.workspace_opt(project_id)?;
+        WorkspacePath::new(workspace.root(), &self.db.file_path(file_id))?;
+        let project = self.db.project_input(project_id);
+        let snapshot = sa_sema::sema_snapshot_for_file(&self.db, project, file_id);
+        if snapshot.for_file(file_id).is_some() {
+            return None;
+        }
+        let parse = sa_syntax::parse_file(&self.file_text(file_id));
+        Some(if parse.errors().is_empty() {
+            SemaUnavailable::CompileErrors
+        } else {
+            SemaUnavailable::ParseErrors
+        })
+    }
+
+    pub fn config_for_file(&self, file_id: FileId) -> Option<Arc<ResolvedFoundryConfig>> {
+        self.db
+            .project_input_opt(self.project_for_file(file_id))
+            .map(|input| input.config(&self.db).clone())
+    }
+
+    /// Returns the roots, dependencies and active profile of `project_id`, or `None` if it has
+    /// not been loaded.
+    pub fn project_structure(&self, project_id: ProjectId) -> Option<ProjectStructure> {
+        self.workspace_opt(project_id)?;
+        Some(sa_ide_db::project_structure(&self.db, project_id))
+    }
+
+    fn workspace_opt(&self, project_id: ProjectId) -> Option<Arc<FoundryWorkspace>> {
         self.db
-            .project_input_opt(self.project_id)
+            .project_input_opt(project_id)
             .map(|input| input.workspace(&self.db).clone())
     }
 
+    /// Resolves the project for `file_id`, or `None` if it has not been loaded yet.
+    fn file_project(&self, file_id: FileId) -> Option<ProjectId> {
+        let project_id = self.project_for_file(file_id);
+        self.workspace_opt(project_id).map(|_| project_id)
+    }
+
+    /// Returns the `forge build` artifact of the contract `name` from the project owning
+    /// `file_id`, preferring the one compiled from `file_id`. Contracts whose sources are not
+    /// indexed, such as precompiled dependencies, are found by name alone.
+    pub fn contract_artifact(&self, file_id: FileId, name: &str) -> Option<ContractArtifact> {
+        let project_id = self.file_project(file_id)?;
+        let project = self.db.project_input(project_id);
+        let artifacts = project.artifacts(&self.db);
+        let path = self.db.file_path(file_id);
+        find_artifact(artifacts, project.workspace(&self.db).root(), &path, name)
+            .or_else(|| artifacts.iter().find(|artifact| artifact.name() == name))
+            .cloned()
+    }
+
+    /// Returns the solc-compatible ABI of the contract `name` declared in `file_id`.
+    pub fn contract_abi(&self, file_id: FileId, name: &str) -> Option<serde_json::Value> {
+        let project_id = self.file_project(file_id)?;
+        let program = sa_hir::lowered_program(&self.db, project_id);
+        let entry = program
+            .def_map()
+            .entries_by_name_in_file(file_id, name)
+            .into_iter()
+            .find(|entry| entry.kind() == sa_def::DefKind::Contract)?;
+        let project = self.db.project_input(project_id);
+        let snapshot = sa_sema::sema_snapshot_for_file(&self.db, project, file_id);
+        snapshot
+            .for_file(file_id)?
+            .contract_abi(file_id, entry.location().range(), name)
+    }
+
+    /// Returns the storage layout of the contract declared around `offset` in `file_id`.
+    pub fn storage_layout(&self, file_id: FileId, offset: TextSize) -> Option<Vec<StorageSlot>> {
+        let project_id = self.file_project(file_id)?;
+        let cst = sa_hir::syntax(&self.db, file_id);
+        let name = cst.contract_at_offset(offset)?.name()?;
+        let project = self.db.project_input(project_id);
+        let snapshot = sa_sema::sema_snapshot_for_file(&self.db, project, file_id);
+        snapshot
+            .for_file(file_id)?
+            .storage_layout(file_id, name.range(), name.text())
+    }
+
+    /// Compares the storage layout of the contract declared around `offset` in `file_id` with
+    /// an earlier version: the contract declared around `previous`, or without it, the one its
+    /// `@custom:oz-upgrades-from` tag names.
+    pub fn storage_upgrade_issues(
+        &self,
+        file_id: FileId,
+        offset: TextSize,
+        previous: Option<(FileId, TextSize)>,
+    ) -> Option<Vec<StorageUpgradeIssue>> {
+        let project_id = self.file_project(file_id)?;
+        let cst = sa_hir::syntax(&self.db, file_id);
+        let name = cst.contract_at_offset(offset)?.name()?;
+        let previous = match previous {
+            Some((previous_file, previous_offset)) => {
+                let cst = sa_hir::syntax(&self.db, previous_file);
+                let name = cst.contract_at_offset(previous_offset)?.name()?;
+                Some((previous_file, name.range(), name.text().to_string()))
+            }
+            None => None,
+        };
+        storage_upgrade::storage_upgrade_issues(
+            &self.db,
+            project_id,
+            file_id,
+            name.range(),
+            name.text(),
+            previous,
+        )
+    }
+
+    /// Reports storage layout changes that would corrupt proxy storage in the contracts of
+    /// `file_id` tagged `@custom:oz-upgrades-from`.
+    pub fn annotated_storage_upgrade_issues(&self, file_id: FileId) -> Vec<StorageUpgradeIssue> {
+        let Some(project_id) = self.file_project(file_id) else {
+            return Vec::new();
+        };
+        storage_upgrade::annotated_storage_upgrade_issues(&self.db, project_id, file_id)
+    }
+
+    /// Reports contracts in `file_id` that claim ERC-20, ERC-721, ERC-1155 or ERC-165 without
+    /// exposing every required function and event as the standard specifies.
+    pub fn erc_compliance(&self, file_id: FileId) -> Vec<ErcComplianceIssue> {
+        let Some(project_id) = self.file_project(file_id) else {
+            return Vec::new();
+        };
+        erc_compliance::erc_compliance(&self.db, project_id, file_id, |name| {
+            self.contract_abi(file_id, name)
+        })
+    }
+
+    /// Reports ERC-7201 namespaced storage structs in `file_id` whose accessors use the wrong
+    /// slot, or that are written outside their namespace.
+    pub fn namespaced_storage_issues(&self, file_id: FileId) -> Vec<NamespacedStorageIssue> {
+        erc7201::namespaced_storage_issues(&self.db, file_id)
+    }
+
+    /// Reports external functions in `file_id` that share a 4-byte selector, within their
+    /// contract or, for contracts listed in `facets`, with the functions of the other facets of
+    /// the diamond.
+    pub fn selector_collisions(
+        &self,
+        file_id: FileId,
+        facets: &[String],
+    ) -> Vec<SelectorCollision> {
+        let Some(project_id) = self.file_project(file_id) else {
+            return Vec::new();
+        };
+        selector_collisions::selector_collisions(&self.db, project_id, file_id, facets)
+    }
+
+    /// Reports contracts in `file_id` whose inheritance cannot be linearized.
+    pub fn linearization_errors(&self, file_id: FileId) -> Vec<LinearizationError> {
+        let Some(project_id) = self.file_project(file_id) else {
+            return Vec::new();
+        };
+        sa_hir::linearization_errors(&self.db, project_id, file_id)
+    }
+
+    /// The inheritance graph of the project owning `file_id`, or with `offset`, of the contract
+    /// declared around it together with its bases and the contracts deriving from it.
+    pub fn contract_graph(
+        &self,
+        file_id: FileId,
+        offset: Option<TextSize>,
+    ) -> Option<ContractGraph> {
+        let project_id = self.file_project(file_id)?;
+        let target = offset.map(|offset| (file_id, offset));
+        contract_graph::contract_graph(&self.db, project_id, target)
+    }
+
+    /// The inheritance graph of every contract in `project_id`.
+    pub fn project_contract_graph(&self, project_id: ProjectId) -> ContractGraph {
+        contract_graph::contract_graph(&self.db, project_id, None).unwrap_or_default()
+    }
+
+    /// The import graph of `project_id`.
+    pub fn import_graph(&self, project_id: ProjectId) -> ImportGraph {
+        sa_hir::import_graph_for_project(&self.db, self.db.project_input(project_id)).clone()
+    }
+
+    /// Renders the contract declared around `offset` with the members of its bases merged in,
+    /// each group annotated with the contract it comes from.
+    pub fn flatten_contract(&self, file_id: FileId, offset: TextSize) -> Option<String> {
+        let project_id = self.file_project(file_id)?;
+        flatten::flatten_contract(&self.db, project_id, file_id, offset)
+    }
+
+    /// Inlines `file_id` and its import closure into one source file, dependencies first.
+    pub fn flatten_file(&self, file_id: FileId) -> Option<String> {
+        let project_id = self.file_project(file_id)?;
+        Some(flatten::flatten_file(&self.db, project_id, file_id))
+    }
+
+    /// The solc standard-json input compiling `project_id` with its current, possibly unsaved,
+    /// sources and its `foundry.toml` settings, or `None` if it has not been loaded.
+    pub fn standard_json_input(&self, project_id: ProjectId) -> Option<serde_json::Value> {
+        self.workspace_opt(project_id)?;
+        Some(standard_json::standard_json_input(&self.db, project_id))
+    }
+
+    /// The forge test contracts and functions declared in `file_id`.
+    pub fn discover_tests(&self, file_id: FileId) -> Vec<TestContract> {
+        test_discovery::discover_tests(&self.db, file_id)
+    }
+
+    /// Returns the import cycle `file_id` is part of, if any.
+    pub fn import_cycle(&self, file_id: FileId) -> Option<ImportCycle> {
+        let project_id = self.file_project(file_id)?;
+        sa_hir::import_graph_for_project(&self.db, self.db.project_input(project_id))
+            .cycle_containing(file_id)
+    }
+
+    /// A readable dump of the lowered HIR of `file_id`, for debugging.
+    pub fn hir_debug_dump(&self, file_id: FileId) -> Option<String> {
+        let project_id = self.file_project(file_id)?;
+        Some(sa_hir::hir_debug_dump(&self.db, project_id, file_id))
+    }
+
+    /// The syntax tree of `file_id` with byte ranges, or of the innermost node covering
+    /// `range`. The whole-file dump also lists the parse errors.
+    pub fn syntax_tree(&self, file_id: FileId, range: Option<TextRange>) -> String {
+        let cst = sa_hir::syntax(&self.db, file_id);
+        match range {
+            Some(range) => cst.covering_node(range).debug_dump(),
+            None => cst.debug_dump(),
+        }
+    }
+
+    /// A readable dump of the DefMap of `project_id`, for debugging.
+    pub fn defmap_debug_dump(&self, project_id: ProjectId) -> Option<String> {
+        self.workspace_opt(project_id)?;
+        Some(sa_hir::defmap_debug_dump(&self.db, project_id))
+    }
+
     pub fn syntax_outline(&self, file_id: FileId) -> Vec<SymbolInfo> {
         let text = self.file_text(file_id);
         let parse = sa_syntax::parse_file(&text);
@@ -175,7 +580,8 @@ impl Analysis {
         if let Some(target) = self.import_path_definition(file_id, offset) {
             return Some(target);
         }
-        let semantics = Semantics::new(&self.db, self.project_id);
+        let project_id = self.project_for_file(file_id);
+        let semantics = Semantics::new(&self.db, project_id);
         if let Some(local) = semantics.resolve_local(file_id, offset) {
             return Some(NavigationTarget {
                 file_id,
@@ -183,7 +589,7 @@ impl Analysis {
                 origin_range: None,
             });
         }
-        self.workspace_opt()?;
+        self.workspace_opt(project_id)?;
         let DefinitionLocation {
             file_id,
             range,
@@ -197,17 +603,17 @@ impl Analysis {
     }
 
     pub fn find_references(&self, file_id: FileId, offset: TextSize) -> Vec<Reference> {
-        if self.workspace_opt().is_none() {
+        let Some(project_id) = self.file_project(file_id) else {
             return Vec::new();
-        }
-        let semantics = Semantics::new(&self.db, self.project_id);
+        };
+        let _span = debug_span!("find_references", file = %self.db.file_path(file_id), ?project_id)
+            .entered();
+        let semantics = Semantics::new(&self.db, project_id);
         let Some(definition) = semantics.resolve_definition(file_id, offset) else {
             return Vec::new();
         };
         match definition {
-            Definition::Global(def_id) => {
-                sa_ide_db::find_references(&self.db, self.project_id, def_id)
-            }
+            Definition::Global(def_id) => sa_ide_db::find_references(&self.db, project_id, def_id),
             Definition::Local(local) => sa_hir::local_references(&self.db, file_id, &local)
                 .into_iter()
                 .map(|range| Reference::new(file_id, range))
@@ -216,20 +622,23 @@ impl Analysis {
     }
 
     pub fn hover(&self, file_id: FileId, offset: TextSize) -> Option<HoverResult> {
-        self.workspace_opt()?;
-        hover::hover(&self.db, self.project_id, file_id, offset)
+        let project_id = self.file_project(file_id)?;
+        let _span = debug_span!("hover", file = %self.db.file_path(file_id), ?project_id).entered();
+        hover::hover(&self.db, project_id, file_id, offset)
     }
 
     pub fn signature_help(&self, file_id: FileId, offset: TextSize) -> Option<SignatureHelp> {
-        self.workspace_opt()?;
-        signature_help::signature_help(&self.db, self.project_id, file_id, offset)
+        let project_id = self.file_project(file_id)?;
+        signature_help::signature_help(&self.db, project_id, file_id, offset)
     }
 
     pub fn completions(&self, file_id: FileId, offset: TextSize) -> Vec<CompletionItem> {
-        if self.workspace_opt().is_none() {
+        let Some(project_id) = self.file_project(file_id) else {
             return Vec::new();
-        }
-        completion::completions(&self.db, self.project_id, file_id, offset)
+        };
+        let _span =
+            debug_span!("completions", file = %self.db.file_path(file_id), ?project_id).entered();
+        completion::completions(&self.db, project_id, file_id, offset)
     }
 
     pub fn format_document(&self, file_id: FileId, config: &FormatterConfig) -> Option<TextEdit> {
@@ -237,6 +646,27 @@ impl Analysis {
         formatting::format_edit(text.as_ref(), config)
     }
 
+    /// Formats the document and returns minimal edits, or `None` if it could not be formatted.
+    pub fn format_document_edits(
+        &self,
+        file_id: FileId,
+        config: &FormatterConfig,
+    ) -> Option<Vec<TextEdit>> {
+        let text = self.file_text(file_id);
+        formatting::format_edits(text.as_ref(), config)
+    }
+
+    /// Formats the items and statements overlapping `range`, consistent with whole-file output.
+    pub fn format_range(
+        &self,
+        file_id: FileId,
+        range: TextRange,
+        config: &FormatterConfig,
+    ) -> Option<Vec<TextEdit>> {
+        let text = self.file_text(file_id);
+        formatting::format_range_edits(text.as_ref(), range, config)
+    }
+
     pub fn code_actions(
         &self,
         file_id: FileId,
@@ -246,19 +676,41 @@ impl Analysis {
         code_actions::code_actions(file_id, text.as_ref(), diagnostics)
     }
 
+    pub fn assists(
+        &self,
+        file_id: FileId,
+        range: TextRange,
+        resolve: &AssistResolveStrategy,
+    ) -> Vec<CodeAction> {
+        let project_id = self.file_project(file_id);
+        assists::assists(&self.db, project_id, file_id, range, resolve)
+    }
+
+    pub fn resolve_assist(
+        &self,
+        file_id: FileId,
+        range: TextRange,
+        id: &str,
+    ) -> Option<CodeAction> {
+        let resolve = AssistResolveStrategy::Single(id.to_string());
+        self.assists(file_id, range, &resolve)
+            .into_iter()
+            .find(|action| action.id == id && action.edit.is_some())
+    }
+
     pub fn rename(
         &self,
         file_id: FileId,
         offset: TextSize,
         new_name: &str,
     ) -> Option<SourceChange> {
-        self.workspace_opt()?;
-        rename::rename(&self.db, self.project_id, file_id, offset, new_name)
+        let project_id = self.file_project(file_id)?;
+        rename::rename(&self.db, project_id, file_id, offset, new_name)
     }
 
     pub fn document_symbols(&self, file_id: FileId) -> Vec<SymbolInfo> {
-        if self.workspace_opt().is_some()
-            && let Some(symbols) = symbols::document_symbols(&self.db, self.project_id, file_id)
+        if let Some(project_id) = self.file_project(file_id)
+            && let Some(symbols) = symbols::document_symbols(&self.db, project_id, file_id)
         {
             return symbols;
         }
@@ -266,10 +718,45 @@ impl Analysis {
     }
 
     pub fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
-        if self.workspace_opt().is_none() {
-            return Vec::new();
+        let mut symbols = Vec::new();
+        for project_id in self.db.project_ids() {
+            for symbol in symbols::workspace_symbols(&self.db, project_id, query) {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+        symbols
+    }
+
+    /// Every definition of every project, with signatures and selectors where sema has them.
+    pub fn symbol_index(&self) -> Vec<SymbolDefinition> {
+        // Files shared by nested projects are listed once.
+        let mut seen = HashSet::new();
+        let mut symbols = Vec::new();
+        for project_id in self.db.project_ids() {
+            for symbol in symbol_index::symbol_index(&self.db, project_id) {
+                if seen.insert((symbol.file_id, symbol.range, symbol.kind)) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+        symbols
+    }
+
+    /// Size and complexity metrics of every contract of every project.
+    pub fn metrics(&self) -> Vec<ContractMetrics> {
+        // Contracts shared by nested projects are listed once.
+        let mut seen = HashSet::new();
+        let mut contracts = Vec::new();
+        for project_id in self.db.project_ids() {
+            for contract in metrics::metrics(&self.db, project_id) {
+                if seen.insert((contract.file_id, contract.range)) {
+                    contracts.push(contract);
+                }
+            }
         }
-        symbols::workspace_symbols(&self.db, self.project_id, query)
+        contracts
     }
 
     fn import_path_definition(
@@ -281,10 +768,12 @@ impl Analysis {
         let parse = sa_syntax::parse_file(&text);
         let current_path = self.db.file_path(file_id);
 
-        let project = self.db.project_input_opt(self.project_id)?;
+        let project = self.db.project_input_opt(self.project_for_file(file_id))?;
         let workspace = project.workspace(&self.db).clone();
         let remappings = project.config(&self.db).active_profile().remappings();
-        let resolver = FoundryResolver::new(&workspace, remappings).ok()?;
+        let resolver = FoundryResolver::new(&workspace, remappings)
+            .ok()?
+            .with_node_modules(self.db.node_modules(workspace.root()));
 
         for (_, directive) in parse.tree().imports() {
             let Some(range) = parse.span_to_text_range(directive.path.span) else {
@@ -319,9 +808,10 @@ mod tests {
     use sa_config::ResolvedFoundryConfig;
     use sa_paths::NormalizedPath;
     use sa_project_model::{FoundryProfile, FoundryWorkspace};
+    use sa_span::TextSize;
     use sa_vfs::{Vfs, VfsChange};
 
-    use super::{AnalysisChange, AnalysisHost};
+    use super::{AnalysisChange, AnalysisHost, CancellationToken, SemaUnavailable};
 
     #[test]
     fn analysis_host_accepts_vfs_and_workspace_inputs() {
@@ -350,4 +840,191 @@ mod tests {
         assert_eq!(analysis.file_version(file_id), 0);
         assert_eq!(analysis.file_kind(file_id), LanguageKind::Solidity);
     }
+
+    #[test]
+    fn removed_files_leave_the_analysis() {
+        let mut vfs = Vfs::default();
+        let main = NormalizedPath::new("/workspace/src/Main.sol");
+        let dep = NormalizedPath::new("/workspace/src/Dep.sol");
+        vfs.apply_changes(vec![
+            VfsChange::Set {
+                path: main.clone(),
+                text: Arc::from("import \"./Dep.sol\";\ncontract Main is Dep {}"),
+            },
+            VfsChange::Set {
+                path: dep.clone(),
+                text: Arc::from("contract Dep {}"),
+            },
+        ]);
+        let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace"));
+        let config = ResolvedFoundryConfig::new(workspace, FoundryProfile::new("default"));
+
+        let mut host = AnalysisHost::new();
+        let mut change = AnalysisChange::new();
+        change.set_vfs(vfs.snapshot());
+        change.set_config(config);
+        host.apply_change(change);
+        let dep_id = vfs.snapshot().file_id(&dep).expect("dep file id");
+        let symbols = host.snapshot().workspace_symbols("Dep");
+        assert!(symbols.iter().any(|symbol| symbol.file_id() == dep_id));
+
+        vfs.apply_change(VfsChange::Remove { path: dep.clone() });
+        let mut change = AnalysisChange::new();
+        change.set_vfs(vfs.snapshot());
+        host.apply_change(change);
+
+        let analysis = host.snapshot();
+        assert!(analysis.workspace_symbols("Dep").is_empty());
+        assert_eq!(analysis.file_id_for_path(&dep), None);
+    }
+
+    #[test]
+    fn memory_usage_reports_texts_without_running_queries() {
+        let text = "contract Main { function run() public {} }";
+        let mut vfs = Vfs::default();
+        vfs.apply_change(VfsChange::Set {
+            path: NormalizedPath::new("/workspace/src/Main.sol"),
+            text: Arc::from(text),
+        });
+        let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace"));
+        let config = ResolvedFoundryConfig::new(workspace, FoundryProfile::new("default"));
+
+        let mut host = AnalysisHost::new();
+        let mut change = AnalysisChange::new();
+        change.set_vfs(vfs.snapshot());
+        change.set_config(config);
+        host.apply_change(change);
+
+        let analysis = host.snapshot();
+        let usage = analysis.memory_usage(&vfs.snapshot());
+        let entry = |name: &str| {
+            usage
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .unwrap_or_else(|| panic!("missing {name} entry"))
+        };
+        assert_eq!(
+            (entry("vfs texts").count, entry("vfs texts").bytes),
+            (1, text.len())
+        );
+        assert_eq!(entry("file inputs").bytes, text.len());
+        let cache = |name: &str| {
+            usage
+                .caches
+                .iter()
+                .find(|cache| cache.name == name)
+                .unwrap_or_else(|| panic!("missing {name} cache"))
+                .entries
+        };
+        assert_eq!(cache("salsa inputs"), 2);
+        assert_eq!(cache("salsa memos"), 0);
+        assert_eq!(cache("parse cache"), 0);
+        assert_eq!(entry("def maps").count, 0);
+        assert_eq!(cache("syntax trees"), 0);
+        assert!(
+            usage
+                .to_string()
+                .lines()
+                .last()
+                .unwrap_or_default()
+                .starts_with("total")
+        );
+
+        let file_id = analysis
+            .file_id_for_path(&NormalizedPath::new("/workspace/src/Main.sol"))
+            .expect("file id");
+        analysis.syntax_tree(file_id, None);
+        analysis.defmap_debug_dump(analysis.project_for_file(file_id));
+        let usage = analysis.memory_usage(&vfs.snapshot());
+        let cache = |name: &str| {
+            usage
+                .caches
+                .iter()
+                .find(|cache| cache.name == name)
+                .map(|cache| cache.entries)
+        };
+        assert_eq!(cache("syntax trees"), Some(1));
+        assert_eq!(cache("parse cache"), Some(1));
+        assert_eq!(cache("def map entries"), Some(2));
+        assert_eq!(cache("salsa memos"), Some(3));
+        let entry = |name: &str| {
+            usage
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .map(|entry| (entry.count, entry.bytes > 0))
+        };
+        assert_eq!(entry("parsed files"), Some((1, true)));
+        assert_eq!(entry("def maps"), Some((1, true)));
+        assert_eq!(entry("hir programs"), Some((1, true)));
+    }
+
+    #[test]
+    fn cancelled_snapshots_abort_queries() {
+        let mut vfs = Vfs::default();
+        vfs.apply_change(VfsChange::Set {
+            path: NormalizedPath::new("/workspace/src/Main.sol"),
+            text: Arc::from("contract Main {}"),
+        });
+        let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace"));
+        let config = ResolvedFoundryConfig::new(workspace, FoundryProfile::new("default"));
+
+        let mut host = AnalysisHost::new();
+        let mut change = AnalysisChange::new();
+        change.set_vfs(vfs.snapshot());
+        change.set_config(config);
+        host.apply_change(change);
+
+        let token = CancellationToken::new();
+        let cancelled = host.snapshot().with_cancellation(token.clone());
+        token.cancel();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cancelled.workspace_symbols("Main")
+        }));
+        assert!(result.is_err());
+        let file_id = vfs
+            .file_id(&NormalizedPath::new("/workspace/src/Main.sol"))
+            .expect("file id");
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cancelled.completions(file_id, TextSize::from(0))
+        }));
+        assert!(result.is_err());
+        drop(cancelled);
+
+        let symbols = host.snapshot().workspace_symbols("Main");
+        assert!(symbols.iter().any(|symbol| symbol.name() == "Main"));
+    }
+
+    #[test]
+    fn sema_unavailable_reports_files_that_do_not_parse() {
+        let mut vfs = Vfs::default();
+        for (path, text) in [
+            ("/workspace/src/Main.sol", "contract Main {}"),
+            ("/workspace/src/Broken.sol", "contract Broken {"),
+        ] {
+            vfs.apply_change(VfsChange::Set {
+                path: NormalizedPath::new(path),
+                text: Arc::from(text),
+            });
+        }
+        let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace"));
+        let config = ResolvedFoundryConfig::new(workspace, FoundryProfile::new("default"));
+        let mut host = AnalysisHost::new();
+        let mut change = AnalysisChange::new();
+        change.set_vfs(vfs.snapshot());
+        change.set_config(config);
+        host.apply_change(change);
+
+        let analysis = host.snapshot();
+        let file_id = |path: &str| vfs.file_id(&NormalizedPath::new(path)).expect("file id");
+        assert_eq!(
+            analysis.sema_unavailable(file_id("/workspace/src/Main.sol")),
+            None
+        );
+        assert_eq!(
+            analysis.sema_unavailable(file_id("/workspace/src/Broken.sol")),
+            Some(SemaUnavailable::ParseErrors)
+        );
+    }
 }
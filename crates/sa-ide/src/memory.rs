@@ -0,0 +1,150 @@
+use std::fmt;
+
+use sa_base_db::{Database, MemoKey};
+use sa_vfs::VfsSnapshot;
+
+/// Approximate memory held by the major analysis structures. Sizes are estimates of heap
+/// usage, meant for spotting which structure grows, not for exact accounting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub entries: Vec<MemoryUsageEntry>,
+    /// Entry counts of the caches behind those structures.
+    pub caches: Vec<CacheStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryUsageEntry {
+    pub name: &'static str,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub entries: usize,
+}
+
+impl MemoryUsage {
+    pub fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|entry| entry.bytes).sum()
+    }
+
+    fn push(&mut self, name: &'static str, count: usize, bytes: usize) {
+        self.entries.push(MemoryUsageEntry { name, count, bytes });
+    }
+
+    fn push_cache(&mut self, name: &'static str, entries: usize) {
+        self.caches.push(CacheStats { name, entries });
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for cache in &self.caches {
+            writeln!(f, "{:<24} {:>8}", cache.name, cache.entries)?;
+        }
+        if !self.caches.is_empty() {
+            writeln!(f)?;
+        }
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:<24} {:>8} {:>12}",
+                entry.name,
+                entry.count,
+                format_bytes(entry.bytes)
+            )?;
+        }
+        write!(
+            f,
+            "{:<24} {:>8} {:>12}",
+            "total",
+            "",
+            format_bytes(self.total_bytes())
+        )
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Only measures what is already held. Running queries here would allocate the memory being
+/// reported, so derived data is measured from the sizes its queries recorded when they ran.
+pub(crate) fn memory_usage(db: &Database, vfs: &VfsSnapshot) -> MemoryUsage {
+    let mut usage = MemoryUsage::default();
+
+    let (vfs_files, vfs_bytes) = vfs
+        .iter()
+        .filter_map(|(file_id, _)| vfs.file_text(file_id))
+        .fold((0, 0), |(count, bytes), text| {
+            (count + 1, bytes + text.len())
+        });
+    usage.push("vfs texts", vfs_files, vfs_bytes);
+
+    let mut files = 0;
+    let mut text_bytes = 0;
+    for file_id in db.file_ids() {
+        files += 1;
+        text_bytes += db.file_input(file_id).text(db).len();
+    }
+    usage.push("file inputs", files, text_bytes);
+
+    let mut parsed = 0;
+    let mut items = 0;
+    let mut parse_bytes = 0;
+    let mut def_maps = 0;
+    let mut def_map_entries = 0;
+    let mut def_map_bytes = 0;
+    let mut programs = 0;
+    let mut program_bytes = 0;
+    for (key, size) in db.memo_sizes() {
+        match key {
+            MemoKey::ParsedFile(_) => {
+                parsed += 1;
+                parse_bytes += size.bytes;
+            }
+            MemoKey::FileItems(_) => {
+                items += 1;
+                parse_bytes += size.bytes;
+            }
+            MemoKey::DefMap(_) => {
+                def_maps += 1;
+                def_map_entries += size.entries;
+                def_map_bytes += size.bytes;
+            }
+            MemoKey::Program(_) => {
+                programs += 1;
+                program_bytes += size.bytes;
+            }
+        }
+    }
+    usage.push("parsed files", parsed, parse_bytes);
+    usage.push("def maps", def_maps, def_map_bytes);
+    usage.push("hir programs", programs, program_bytes);
+
+    let sema = sa_sema::sema_snapshot_stats();
+    usage.push("sema snapshot sources", sema.live, sema.source_bytes);
+
+    usage.push_cache("salsa inputs", files + db.project_ids().len());
+    // The DefMap is part of its program's memo.
+    usage.push_cache("salsa memos", parsed + items + programs);
+    usage.push_cache("parse cache", parsed);
+    usage.push_cache("def map entries", def_map_entries);
+    usage.push_cache("syntax trees", db.syntax_tree_count());
+    usage.push_cache("sema snapshots", sema.live);
+
+    usage
+}
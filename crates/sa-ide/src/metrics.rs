@@ -0,0 +1,283 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_span::{TextRange, TextSize};
+use sa_syntax::cst::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use crate::contract_graph::{self, ContractGraph, ContractKind};
+
+/// Size and complexity figures of one contract, interface or library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractMetrics {
+    pub name: String,
+    pub kind: ContractKind,
+    pub is_abstract: bool,
+    pub file_id: FileId,
+    /// The range of the contract's name.
+    pub range: TextRange,
+    /// Lines holding code, without blank and comment-only lines.
+    pub lines: usize,
+    /// The longest chain of bases above the contract; 0 when it inherits from nothing.
+    pub inheritance_depth: usize,
+    pub functions: Vec<FunctionMetrics>,
+}
+
+/// Size and complexity figures of one function, constructor, `receive` or `fallback`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionMetrics {
+    pub name: String,
+    /// `external`, `public`, `internal` or `private`.
+    pub visibility: &'static str,
+    /// The range of the name, or of the keyword for constructors, `receive` and `fallback`.
+    pub range: TextRange,
+    pub lines: usize,
+    pub external_calls: usize,
+    pub cyclomatic_complexity: usize,
+}
+
+impl ContractMetrics {
+    /// The number of functions with the given visibility.
+    pub fn function_count(&self, visibility: &str) -> usize {
+        self.functions
+            .iter()
+            .filter(|function| function.visibility == visibility)
+            .count()
+    }
+
+    pub fn external_calls(&self) -> usize {
+        self.functions
+            .iter()
+            .map(|function| function.external_calls)
+            .sum()
+    }
+
+    /// The summed complexity of the contract's functions.
+    pub fn cyclomatic_complexity(&self) -> usize {
+        self.functions
+            .iter()
+            .map(|function| function.cyclomatic_complexity)
+            .sum()
+    }
+}
+
+/// Members of these never reach another contract.
+const BUILTIN_RECEIVERS: &[&str] = &["abi", "block", "bytes", "msg", "string", "super", "tx"];
+const BUILTIN_MEMBERS: &[&str] = &["concat", "pop", "push"];
+/// Yul builtins that call into another contract.
+const YUL_CALLS: &[&str] = &["call", "callcode", "delegatecall", "staticcall"];
+
+/// Metrics for every contract of `project_id`, in the order of its contract graph.
+pub(crate) fn metrics(db: &Database, project_id: ProjectId) -> Vec<ContractMetrics> {
+    let Some(graph) = contract_graph::contract_graph(db, project_id, None) else {
+        return Vec::new();
+    };
+    let mut csts = HashMap::new();
+    for node in &graph.nodes {
+        csts.entry(node.file_id)
+            .or_insert_with(|| sa_hir::syntax(db, node.file_id));
+    }
+    // Calls on a library, or on a value a library is attached to with `using for`, are
+    // internal unless the library function is public; sources do not tell these apart, so
+    // none of them is counted.
+    let mut libraries = HashSet::new();
+    for node in graph
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ContractKind::Library)
+    {
+        libraries.insert(node.name.clone());
+        let functions = csts[&node.file_id]
+            .contract_by_name(&node.name)
+            .and_then(|contract| contract.child_node(SyntaxKind::ContractBody))
+            .into_iter()
+            .flat_map(|body| body.child_nodes())
+            .filter(|member| member.kind() == SyntaxKind::Function)
+            .filter_map(|function| function.name())
+            .map(|name| name.text().to_string());
+        libraries.extend(functions);
+    }
+
+    let mut depths = HashMap::new();
+    let mut metrics = Vec::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        db.check_cancelled();
+        let text = db.file_input(node.file_id).text(db).clone();
+        let line_starts = line_starts(&text);
+        let Some(contract) = csts[&node.file_id].contract_by_name(&node.name) else {
+            continue;
+        };
+        let functions = contract
+            .child_node(SyntaxKind::ContractBody)
+            .into_iter()
+            .flat_map(|body| body.child_nodes())
+            .filter(|member| member.kind() == SyntaxKind::Function)
+            .filter_map(|function| function_metrics(function, &line_starts, &libraries))
+            .collect();
+        metrics.push(ContractMetrics {
+            name: node.name.clone(),
+            kind: node.kind,
+            is_abstract: node.is_abstract,
+            file_id: node.file_id,
+            range: node.range,
+            lines: code_lines(contract, &line_starts),
+            inheritance_depth: inheritance_depth(&graph, index, &mut depths, &mut Vec::new()),
+            functions,
+        });
+    }
+    metrics
+}
+
+fn function_metrics(
+    function: &SyntaxNode,
+    line_starts: &[TextSize],
+    libraries: &HashSet<String>,
+) -> Option<FunctionMetrics> {
+    let keyword = function.first_token()?;
+    let name = function.name().unwrap_or(keyword);
+    // The header's own tokens; parameter lists and the body are child nodes.
+    let visibility = function
+        .tokens()
+        .find_map(|token| match token.text() {
+            "external" => Some("external"),
+            "public" => Some("public"),
+            "internal" => Some("internal"),
+            "private" => Some("private"),
+            _ => None,
+        })
+        .unwrap_or(match keyword.text() {
+            "receive" | "fallback" => "external",
+            _ => "public",
+        });
+    let tokens = function
+        .child_node(SyntaxKind::Block)
+        .map(all_tokens)
+        .unwrap_or_default();
+    Some(FunctionMetrics {
+        name: name.text().to_string(),
+        visibility,
+        range: name.range(),
+        lines: code_lines(function, line_starts),
+        external_calls: external_calls(&tokens, libraries),
+        cyclomatic_complexity: cyclomatic_complexity(&tokens),
+    })
+}
+
+/// Counts member calls that leave the contract: `token.transfer(...)`, `addr.call{...}(...)`,
+/// `this.f()` and the Yul call builtins.
+fn external_calls(tokens: &[&SyntaxToken], libraries: &HashSet<String>) -> usize {
+    let mut calls = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind() != SyntaxKind::Ident {
+            continue;
+        }
+        let next = tokens.get(index + 1).map(|token| token.kind());
+        if !matches!(next, Some(SyntaxKind::LParen | SyntaxKind::LBrace)) {
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| tokens[index]);
+        if previous.is_none_or(|previous| previous.kind() != SyntaxKind::Dot) {
+            if YUL_CALLS.contains(&token.text()) {
+                calls += 1;
+            }
+            continue;
+        }
+        let receiver = index.checked_sub(2).map(|index| tokens[index]);
+        let builtin_receiver = receiver.is_some_and(|receiver| {
+            BUILTIN_RECEIVERS.contains(&receiver.text()) || libraries.contains(receiver.text())
+        });
+        if builtin_receiver
+            || BUILTIN_MEMBERS.contains(&token.text())
+            || libraries.contains(token.text())
+        {
+            continue;
+        }
+        calls += 1;
+    }
+    calls
+}
+
+/// One plus the number of decision points: branches, loops, `catch` clauses, Yul `case`s,
+/// the ternary operator and the short-circuiting `&&` and `||`.
+fn cyclomatic_complexity(tokens: &[&SyntaxToken]) -> usize {
+    let mut complexity = 1;
+    for (index, token) in tokens.iter().enumerate() {
+        let decision = match token.kind() {
+            SyntaxKind::Ident => matches!(token.text(), "if" | "for" | "while" | "catch" | "case"),
+            // `&&` and `||` are lexed as two adjacent punctuation tokens.
+            SyntaxKind::Punct => match token.text() {
+                "?" => true,
+                "&" | "|" => tokens.get(index + 1).is_some_and(|next| {
+                    next.text() == token.text() && next.range().start() == token.range().end()
+                }),
+                _ => false,
+            },
+            _ => false,
+        };
+        if decision {
+            complexity += 1;
+        }
+    }
+    complexity
+}
+
+/// The longest chain of direct bases above `index`. Inheritance cycles do not linearize and
+/// are cut where they close.
+fn inheritance_depth(
+    graph: &ContractGraph,
+    index: usize,
+    depths: &mut HashMap<usize, usize>,
+    visiting: &mut Vec<usize>,
+) -> usize {
+    if let Some(&depth) = depths.get(&index) {
+        return depth;
+    }
+    if visiting.contains(&index) {
+        return 0;
+    }
+    visiting.push(index);
+    let depth = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.derived == index)
+        .map(|edge| inheritance_depth(graph, edge.base, depths, visiting) + 1)
+        .max()
+        .unwrap_or(0);
+    visiting.pop();
+    depths.insert(index, depth);
+    depth
+}
+
+/// Every non-trivia token below `node`, in source order.
+pub(crate) fn all_tokens(node: &SyntaxNode) -> Vec<&SyntaxToken> {
+    let mut tokens = Vec::new();
+    for child in node.children() {
+        match child {
+            SyntaxElement::Token(token) if !token.kind().is_trivia() => tokens.push(token),
+            SyntaxElement::Token(_) => {}
+            SyntaxElement::Node(node) => tokens.extend(all_tokens(node)),
+        }
+    }
+    tokens
+}
+
+fn line_starts(text: &str) -> Vec<TextSize> {
+    let mut starts = vec![TextSize::from(0)];
+    starts.extend(
+        text.match_indices('\n')
+            .map(|(offset, _)| TextSize::from(offset as u32 + 1)),
+    );
+    starts
+}
+
+/// The number of lines of `node` holding a token that is not whitespace or a comment.
+fn code_lines(node: &SyntaxNode, line_starts: &[TextSize]) -> usize {
+    all_tokens(node)
+        .into_iter()
+        .map(|token| {
+            line_starts
+                .partition_point(|start| *start <= token.range().start())
+                .saturating_sub(1)
+        })
+        .collect::<BTreeSet<_>>()
+        .len()
+}
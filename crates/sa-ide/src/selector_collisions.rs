@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_sema::{SelectorEntry, SelectorKind};
+use sa_span::TextRange;
+
+use crate::contract_graph::{ContractKind, contract_header};
+use crate::storage_upgrade::resolve_contract;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorFunction {
+    /// The contract exposing the function, which may inherit it from a base.
+    pub contract: String,
+    pub signature: String,
+    /// Where the function is declared.
+    pub file_id: FileId,
+    pub range: TextRange,
+}
+
+/// Two external functions dispatched by the same 4-byte selector, either within one contract
+/// or across the facets of an EIP-2535 diamond.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorCollision {
+    /// The shared selector as `0x`-prefixed hex.
+    pub selector: String,
+    /// The function of the checked contract.
+    pub function: SelectorFunction,
+    pub other: SelectorFunction,
+    /// The name of the checked contract, where the collision is reported when neither
+    /// function is declared in its file.
+    pub contract_range: TextRange,
+    pub message: String,
+}
+
+/// Reports the selector collisions of the contracts and interfaces in `file_id`. Contracts
+/// named in `facets`, as `Name` or `path/to/File.sol:Name`, are also checked against the other
+/// facets, where even identical functions collide as the diamond can only route one.
+pub(crate) fn selector_collisions(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    facets: &[String],
+) -> Vec<SelectorCollision> {
+    let cst = sa_hir::syntax(db, file_id);
+    let mut facet_tables = None;
+    let mut collisions = Vec::new();
+    for contract in cst.contracts() {
+        db.check_cancelled();
+        if contract_header(contract).0 == ContractKind::Library {
+            continue;
+        }
+        let Some(name) = contract.name() else {
+            continue;
+        };
+        let Some(functions) =
+            function_selectors(db, project_id, file_id, name.range(), name.text())
+        else {
+            continue;
+        };
+        let mut by_selector = HashMap::<&str, &SelectorEntry>::new();
+        for entry in &functions {
+            match by_selector.get(entry.selector.as_str()) {
+                Some(first) => collisions.push(collision(
+                    (name.text(), *first),
+                    (name.text(), entry),
+                    name.range(),
+                )),
+                None => {
+                    by_selector.insert(&entry.selector, entry);
+                }
+            }
+        }
+
+        if facets.is_empty() {
+            continue;
+        }
+        let tables = facet_tables.get_or_insert_with(|| {
+            facets
+                .iter()
+                .filter_map(|reference| resolve_contract(db, project_id, reference))
+                .filter_map(|(facet_file, range, facet)| {
+                    let table = function_selectors(db, project_id, facet_file, range, &facet)?;
+                    Some(((facet_file, range), facet, table))
+                })
+                .collect::<Vec<_>>()
+        });
+        if !tables
+            .iter()
+            .any(|(location, _, _)| *location == (file_id, name.range()))
+        {
+            continue;
+        }
+        for (location, facet, table) in tables.iter() {
+            if *location == (file_id, name.range()) {
+                continue;
+            }
+            for other in table {
+                if let Some(entry) = by_selector.get(other.selector.as_str()) {
+                    collisions.push(collision(
+                        (name.text(), *entry),
+                        (facet.as_str(), other),
+                        name.range(),
+                    ));
+                }
+            }
+        }
+    }
+    collisions
+}
+
+fn function_selectors(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    name_range: TextRange,
+    name: &str,
+) -> Option<Vec<SelectorEntry>> {
+    let project = db.project_input(project_id);
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
+    let table = snapshot
+        .for_file(file_id)?
+        .selector_table(file_id, name_range, name)?;
+    Some(
+        table
+            .into_iter()
+            .filter(|entry| entry.kind == SelectorKind::Function)
+            .collect(),
+    )
+}
+
+fn collision(
+    (contract, entry): (&str, &SelectorEntry),
+    (other_contract, other): (&str, &SelectorEntry),
+    contract_range: TextRange,
+) -> SelectorCollision {
+    let message = if contract == other_contract {
+        format!(
+            "`{}` and `{}` in `{contract}` share the selector {}",
+            entry.signature, other.signature, entry.selector
+        )
+    } else {
+        format!(
+            "`{}` in `{contract}` and `{}` in facet `{other_contract}` share the selector {}",
+            entry.signature, other.signature, entry.selector
+        )
+    };
+    let function = |contract: &str, entry: &SelectorEntry| SelectorFunction {
+        contract: contract.to_string(),
+        signature: entry.signature.clone(),
+        file_id: entry.file_id,
+        range: entry.range,
+    };
+    SelectorCollision {
+        selector: entry.selector.clone(),
+        function: function(contract, entry),
+        other: function(other_contract, other),
+        contract_range,
+        message,
+    }
+}
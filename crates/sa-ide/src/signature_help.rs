@@ -1,6 +1,6 @@
 use sa_base_db::{FileId, ProjectId};
 use sa_def::{DefEntry, DefKind};
-use sa_hir::{HirDatabase, Semantics, lowered_program};
+use sa_hir::{HirDatabase, ResolvedCall, Semantics, lowered_program};
 use sa_span::{TextSize, is_ident_byte};
 use sa_syntax::{
     Parse,
@@ -46,18 +46,57 @@ pub fn signature_help(
     let container = find_container_at_offset(&parse, offset);
 
     // Try to resolve using source_to_def, then validate it matches the expected scope
-    let entry = resolve_function_entry_via_semantics(
+    let entries = match resolve_function_entry_via_semantics(
         db,
         project_id,
         file_id,
         &call,
         &program,
         container.as_deref(),
-    )
-    .or_else(|| {
-        resolve_function_entry_by_name(&program, file_id, &call.name, container.as_deref())
-    })?;
+    ) {
+        Some(entry) => vec![entry],
+        None => resolve_function_entries_via_call(db, project_id, file_id, &call, &program)
+            .or_else(|| {
+                resolve_function_entry_by_name(&program, file_id, &call.name, container.as_deref())
+                    .map(|entry| vec![entry])
+            })?,
+    };
+
+    let signatures = entries
+        .into_iter()
+        .filter_map(|entry| signature_information(db, project_id, entry))
+        .collect::<Vec<_>>();
+    if signatures.is_empty() {
+        return None;
+    }
+
+    // With several candidate overloads, prefer the first one that still takes an argument
+    // at the cursor.
+    let active_signature = signatures
+        .iter()
+        .position(|signature| signature.parameters.len() > call.active_parameter)
+        .unwrap_or(0);
+
+    // Clamp active_parameter to valid range, or None if no parameters
+    let parameter_count = signatures[active_signature].parameters.len();
+    let active_parameter = if parameter_count == 0 {
+        None
+    } else {
+        Some(call.active_parameter.min(parameter_count - 1))
+    };
 
+    Some(SignatureHelp {
+        signatures,
+        active_signature: Some(active_signature),
+        active_parameter,
+    })
+}
+
+fn signature_information(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    entry: &DefEntry,
+) -> Option<SignatureInformation> {
     let def_file_id = entry.location().file_id();
     let def_text = db.file_input(def_file_id).text(db);
     let parse = sa_syntax::parse_file(def_text.as_ref());
@@ -96,21 +135,10 @@ pub fn signature_help(
         entry.container(),
     );
 
-    // Clamp active_parameter to valid range, or None if no parameters
-    let active_parameter = if parameters.is_empty() {
-        None
-    } else {
-        Some(call.active_parameter.min(parameters.len() - 1))
-    };
-
-    Some(SignatureHelp {
-        signatures: vec![SignatureInformation {
-            label,
-            documentation,
-            parameters,
-        }],
-        active_signature: Some(0),
-        active_parameter,
+    Some(SignatureInformation {
+        label,
+        documentation,
+        parameters,
     })
 }
 
@@ -248,6 +276,28 @@ fn resolve_function_entry_via_semantics<'a>(
     Some(entry)
 }
 
+/// Resolves the call around the cursor to its overload, or to every overload it could not
+/// be narrowed down from (e.g. while arguments are still being typed).
+fn resolve_function_entries_via_call<'a>(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+    call: &CallContext,
+    program: &'a sa_hir::HirProgram,
+) -> Option<Vec<&'a DefEntry>> {
+    let semantics = Semantics::new(db, project_id);
+    let def_ids = match semantics.resolve_call(file_id, call.name_offset)? {
+        ResolvedCall::Resolved(def_id) => vec![def_id],
+        ResolvedCall::Ambiguous(def_ids) => def_ids,
+    };
+    let entries = def_ids
+        .into_iter()
+        .filter_map(|def_id| program.def_map().entry(def_id))
+        .filter(|entry| entry.kind() == DefKind::Function)
+        .collect::<Vec<_>>();
+    (!entries.is_empty()).then_some(entries)
+}
+
 /// Fallback resolution by name when source_to_def doesn't resolve.
 ///
 /// Prefers functions in the same container (contract), then same file, then any match.
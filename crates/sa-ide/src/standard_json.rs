@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+
+use sa_base_db::{Database, ProjectId};
+use sa_paths::WorkspacePath;
+use serde_json::{Map, Value, json};
+use tracing::warn;
+
+/// Builds the solc standard-json input that compiles `project_id`: its own source files and
+/// everything they import, with the text the database holds (unsaved edits included), plus the
+/// optimizer, EVM and output settings from `foundry.toml` and the project's remappings.
+/// Source keys are relative to the project root, so solc must run with the root as base path.
+pub(crate) fn standard_json_input(db: &Database, project_id: ProjectId) -> Value {
+    let project = db.project_input(project_id);
+    let workspace = project.workspace(db);
+    let graph = sa_hir::import_graph_for_project(db, project);
+
+    let mut files = BTreeSet::new();
+    for file_id in db.file_ids() {
+        let path = db.file_path(file_id);
+        if db.project_for_path(&path) != Some(project_id) || workspace.is_dependency_file(&path) {
+            continue;
+        }
+        files.insert(file_id);
+        files.extend(graph.transitive_imports(file_id));
+    }
+
+    let mut sources = Map::new();
+    for file_id in files {
+        db.check_cancelled();
+        let path = db.file_path(file_id);
+        let key = WorkspacePath::new(workspace.root(), &path)
+            .map(|relative| relative.as_str().to_string())
+            .unwrap_or_else(|| path.as_str().to_string());
+        let content = db.file_input(file_id).text(db).to_string();
+        sources.insert(key, json!({ "content": content }));
+    }
+
+    let config = project.config(db);
+    let mut settings = match config.compiler_settings() {
+        Ok(settings) => serde_json::to_value(&settings.solc.settings).unwrap_or_default(),
+        Err(error) => {
+            warn!(
+                ?error,
+                "failed to derive compiler settings; using solc defaults"
+            );
+            Value::Null
+        }
+    };
+    if !settings.is_object() {
+        settings = json!({});
+    }
+    let remappings = config
+        .active_profile()
+        .remappings()
+        .iter()
+        .map(|remapping| {
+            let context = remapping
+                .context()
+                .map(|context| format!("{context}:"))
+                .unwrap_or_default();
+            Value::String(format!("{context}{}={}", remapping.from(), remapping.to()))
+        })
+        .collect();
+    settings["remappings"] = Value::Array(remappings);
+
+    json!({
+        "language": "Solidity",
+        "sources": sources,
+        "settings": settings,
+    })
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use sa_base_db::{Database, FileId, ProjectId};
+use sa_def::DefKind;
+use sa_sema::StorageSlot;
+use sa_span::TextRange;
+use sa_syntax::ast::ItemKind;
+use sa_syntax::docs::{DocTagKind, item_docs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageUpgradeIssueKind {
+    /// A variable of the previous version lives at a different slot or offset.
+    Moved,
+    /// A variable keeps its place but not its type.
+    TypeChanged,
+    /// A variable of the previous version is gone.
+    Removed,
+    /// A new variable takes a slot a variable of the previous version used.
+    Overlaps,
+    /// Variables added before a `__gap` are not taken out of it, so the slots after it shift.
+    GapMismatch,
+}
+
+/// A difference between the storage layouts of two versions of a contract that would corrupt
+/// the state of a proxy upgraded from one to the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageUpgradeIssue {
+    pub kind: StorageUpgradeIssueKind,
+    /// The variable concerned, as named in the previous version unless it is new.
+    pub name: String,
+    /// Where the variable is declared in the new version, or the name of the new contract
+    /// when the variable is gone.
+    pub file_id: FileId,
+    pub range: TextRange,
+    pub message: String,
+}
+
+/// Compares the storage layout of the contract `name` at `name_range` in `file_id` with the one
+/// of `previous`, or without it, of the contract its `@custom:oz-upgrades-from` tag names.
+pub(crate) fn storage_upgrade_issues(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    name_range: TextRange,
+    name: &str,
+    previous: Option<(FileId, TextRange, String)>,
+) -> Option<Vec<StorageUpgradeIssue>> {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => {
+            let reference = upgrades_from(db.file_input(file_id).text(db)).remove(name)?;
+            resolve_contract(db, project_id, &reference)?
+        }
+    };
+    let current = storage_layout(db, project_id, file_id, name_range, name)?;
+    let (previous_file, previous_range, previous_name) = previous;
+    let previous = storage_layout(
+        db,
+        project_id,
+        previous_file,
+        previous_range,
+        &previous_name,
+    )?;
+    Some(compare_layouts(&previous, &current, (file_id, name_range)))
+}
+
+/// The storage upgrade issues of every contract in `file_id` with an `@custom:oz-upgrades-from`
+/// tag. Issues about variables declared in other files, such as in a base contract, are placed
+/// on the name of the contract.
+pub(crate) fn annotated_storage_upgrade_issues(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+) -> Vec<StorageUpgradeIssue> {
+    let text = db.file_input(file_id).text(db);
+    let annotated = upgrades_from(text);
+    if annotated.is_empty() {
+        return Vec::new();
+    }
+    let cst = sa_hir::syntax(db, file_id);
+    let mut issues = Vec::new();
+    for contract in cst.contracts() {
+        db.check_cancelled();
+        let Some(name) = contract.name() else {
+            continue;
+        };
+        let Some(reference) = annotated.get(name.text()) else {
+            continue;
+        };
+        let Some(previous) = resolve_contract(db, project_id, reference) else {
+            continue;
+        };
+        let found = storage_upgrade_issues(
+            db,
+            project_id,
+            file_id,
+            name.range(),
+            name.text(),
+            Some(previous),
+        )
+        .unwrap_or_default();
+        issues.extend(found.into_iter().map(|mut issue| {
+            if issue.file_id != file_id {
+                issue.file_id = file_id;
+                issue.range = name.range();
+            }
+            issue
+        }));
+    }
+    issues
+}
+
+/// The contracts of `text` with the reference of their `@custom:oz-upgrades-from` tag.
+fn upgrades_from(text: &str) -> HashMap<String, String> {
+    let parse = sa_syntax::parse_file(text);
+    let mut references = HashMap::new();
+    parse.with_session(|| {
+        for item in parse.tree().items.iter() {
+            let ItemKind::Contract(contract) = &item.kind else {
+                continue;
+            };
+            let reference = item_docs(&parse, item).and_then(|docs| {
+                docs.tags().iter().find_map(|tag| match &tag.kind {
+                    DocTagKind::Custom(name) if name == "oz-upgrades-from" => {
+                        tag.text().split_whitespace().next().map(str::to_string)
+                    }
+                    _ => None,
+                })
+            });
+            if let Some(reference) = reference {
+                references.insert(contract.name.to_string(), reference);
+            }
+        }
+    });
+    references
+}
+
+/// Finds the contract `reference` names, either `Name` or `path/to/File.sol:Name` as the
+/// OpenZeppelin upgrades plugins accept.
+pub(crate) fn resolve_contract(
+    db: &Database,
+    project_id: ProjectId,
+    reference: &str,
+) -> Option<(FileId, TextRange, String)> {
+    let (path, name) = match reference.rsplit_once(':') {
+        Some((path, name)) => (Some(path), name),
+        None => (None, reference),
+    };
+    let program = sa_hir::lowered_program(db, project_id);
+    let entries = program
+        .def_map()
+        .entries_by_name(DefKind::Contract, name)
+        .unwrap_or_default();
+    let entry = entries.into_iter().find(|entry| {
+        path.is_none_or(|path| {
+            db.file_path(entry.location().file_id())
+                .as_str()
+                .ends_with(path)
+        })
+    })?;
+    let location = entry.location();
+    Some((location.file_id(), location.range(), name.to_string()))
+}
+
+fn storage_layout(
+    db: &Database,
+    project_id: ProjectId,
+    file_id: FileId,
+    name_range: TextRange,
+    name: &str,
+) -> Option<Vec<StorageSlot>> {
+    let project = db.project_input(project_id);
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
+    snapshot
+        .for_file(file_id)?
+        .storage_layout(file_id, name_range, name)
+}
+
+/// Diffs two storage layouts. `contract` locates issues about variables the new layout lacks.
+pub fn compare_layouts(
+    previous: &[StorageSlot],
+    current: &[StorageSlot],
+    contract: (FileId, TextRange),
+) -> Vec<StorageUpgradeIssue> {
+    let current_by_name = current
+        .iter()
+        .map(|slot| (slot.name.as_str(), slot))
+        .collect::<HashMap<_, _>>();
+    let mut issues = Vec::new();
+    let mut issue = |kind, slot: Option<&StorageSlot>, name: &str, message: String| {
+        let (file_id, range) = slot.map_or(contract, |slot| (slot.file_id, slot.range));
+        issues.push(StorageUpgradeIssue {
+            kind,
+            name: name.to_string(),
+            file_id,
+            range,
+            message,
+        });
+    };
+
+    for old in previous {
+        let new = current_by_name.get(old.name.as_str()).copied();
+        if let (Some(old_gap), Some(new)) = (gap_slots(old), new) {
+            let Some(new_gap) = gap_slots(new) else {
+                continue;
+            };
+            let (old_end, new_end) = (old_gap.end, new_gap.end);
+            if old_end != new_end {
+                issue(
+                    StorageUpgradeIssueKind::GapMismatch,
+                    Some(new),
+                    &old.name,
+                    format!(
+                        "`{}` ends at slot {new_end} but ended at slot {old_end}; resize it by {} slot(s) so the variables after it keep their slots",
+                        old.name,
+                        signed_difference(old_end, new_end)
+                    ),
+                );
+            }
+            continue;
+        }
+        match new {
+            None if gap_slots(old).is_none() => issue(
+                StorageUpgradeIssueKind::Removed,
+                None,
+                &old.name,
+                format!(
+                    "`{}` ({}, slot {}) was removed; its storage is left to whatever takes its place",
+                    old.name, old.ty, old.slot
+                ),
+            ),
+            None => {}
+            Some(new) if (&new.slot, new.offset) != (&old.slot, old.offset) => issue(
+                StorageUpgradeIssueKind::Moved,
+                Some(new),
+                &old.name,
+                format!(
+                    "`{}` moved from slot {} (offset {}) to slot {} (offset {})",
+                    old.name, old.slot, old.offset, new.slot, new.offset
+                ),
+            ),
+            Some(new) if new.ty != old.ty => issue(
+                StorageUpgradeIssueKind::TypeChanged,
+                Some(new),
+                &old.name,
+                format!(
+                    "`{}` changed type from `{}` to `{}`",
+                    old.name, old.ty, new.ty
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    let previous_names = previous
+        .iter()
+        .map(|slot| slot.name.as_str())
+        .collect::<Vec<_>>();
+    for new in current {
+        if previous_names.contains(&new.name.as_str()) {
+            continue;
+        }
+        let overlapped = previous.iter().find(|old| {
+            if old.slot != new.slot || gap_slots(old).is_some() {
+                return false;
+            }
+            // An old variable spans up to the next one packed into its slot. Packing after the
+            // last one is fine while it stays in place, as the compiler skips its bytes.
+            let in_place = current_by_name
+                .get(old.name.as_str())
+                .is_some_and(|current| {
+                    (&current.slot, current.offset, &current.ty) == (&old.slot, old.offset, &old.ty)
+                });
+            let end = previous
+                .iter()
+                .filter(|next| next.slot == old.slot && next.offset > old.offset)
+                .map(|next| next.offset)
+                .min()
+                .unwrap_or(if in_place { old.offset + 1 } else { 32 });
+            (old.offset..end).contains(&new.offset)
+        });
+        if let Some(old) = overlapped {
+            issue(
+                StorageUpgradeIssueKind::Overlaps,
+                Some(new),
+                &new.name,
+                format!(
+                    "new variable `{}` takes slot {}, which held `{}` ({}) in the previous version",
+                    new.name, new.slot, old.name, old.ty
+                ),
+            );
+        }
+    }
+    issues
+}
+
+/// The slots a `__gap` array reserves, for arrays of full-slot elements.
+fn gap_slots(slot: &StorageSlot) -> Option<std::ops::Range<u128>> {
+    if !slot.name.starts_with("__gap") {
+        return None;
+    }
+    let (element, len) = slot.ty.strip_suffix(']')?.rsplit_once('[')?;
+    if !matches!(element, "uint256" | "int256" | "bytes32") {
+        return None;
+    }
+    let start = slot.slot.parse::<u128>().ok()?;
+    let len = len.parse::<u128>().ok()?;
+    Some(start..start.checked_add(len)?)
+}
+
+fn signed_difference(expected: u128, actual: u128) -> String {
+    if expected >= actual {
+        format!("+{}", expected - actual)
+    } else {
+        format!("-{}", actual - expected)
+    }
+}
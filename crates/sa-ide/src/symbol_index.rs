@@ -0,0 +1,67 @@
+use sa_base_db::{Database, FileId, ProjectId, ProjectInput};
+use sa_def::{DefEntry, DefKind};
+use sa_span::TextRange;
+
+/// One definition of a project, with the ABI details sema knows about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDefinition {
+    pub kind: DefKind,
+    pub name: String,
+    /// The contract, struct, enum or function the definition is nested in.
+    pub container: Option<String>,
+    pub file_id: FileId,
+    /// The range of the definition's name.
+    pub range: TextRange,
+    /// The canonical signature of functions, errors and events, e.g. `transfer(address,uint256)`.
+    pub signature: Option<String>,
+    /// The selector of external functions and errors, or topic 0 of an event.
+    pub selector: Option<String>,
+}
+
+/// Every definition in the DefMap of `project_id`, in DefMap order.
+pub(crate) fn symbol_index(db: &Database, project_id: ProjectId) -> Vec<SymbolDefinition> {
+    let program = sa_hir::lowered_program(db, project_id);
+    let project = db.project_input(project_id);
+    let mut symbols = Vec::new();
+    for entry in program.def_map().entries() {
+        db.check_cancelled();
+        let (signature, selector) = match entry.kind() {
+            DefKind::Function | DefKind::Error | DefKind::Event => abi_details(db, project, entry),
+            _ => (None, None),
+        };
+        let location = entry.location();
+        symbols.push(SymbolDefinition {
+            kind: entry.kind(),
+            name: location.name().to_string(),
+            container: entry.container().map(str::to_string),
+            file_id: location.file_id(),
+            range: location.range(),
+            signature,
+            selector,
+        });
+    }
+    symbols
+}
+
+/// The canonical signature and selector of a function, error or event. Functions without a
+/// selector still get their signature.
+fn abi_details(
+    db: &Database,
+    project: ProjectInput,
+    entry: &DefEntry,
+) -> (Option<String>, Option<String>) {
+    let location = entry.location();
+    let file_id = location.file_id();
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
+    let Some(snapshot) = snapshot.for_file(file_id) else {
+        return (None, None);
+    };
+    let (range, name, container) = (location.range(), location.name(), entry.container());
+    if let Some((signature, selector)) =
+        snapshot.selector_for_definition(file_id, range, name, container)
+    {
+        return (Some(signature), Some(selector));
+    }
+    let signature = snapshot.function_abi_signature_for_definition(file_id, range, name, container);
+    (signature, None)
+}
@@ -1,5 +1,6 @@
 use sa_base_db::{FileId, ProjectId};
-use sa_hir::HirDatabase;
+use sa_def::DefKind;
+use sa_hir::{HirDatabase, lowered_program};
 use sa_sema::{ResolvedSymbolKind, SemaSymbol};
 
 pub type WorkspaceSymbol = sa_ide_db::Symbol;
@@ -18,10 +19,52 @@ pub fn document_symbols(
     file_id: FileId,
 ) -> Option<Vec<crate::SymbolInfo>> {
     let project = db.project_input(project_id);
-    let snapshot = sa_sema::sema_snapshot_for_project(db, project);
+    let snapshot = sa_sema::sema_snapshot_for_file(db, project, file_id);
     let snapshot = snapshot.for_file(file_id)?;
     let symbols = snapshot.document_symbols(file_id)?;
-    Some(symbols.into_iter().map(symbol_info_from_sema).collect())
+    let mut symbols = symbols
+        .into_iter()
+        .map(symbol_info_from_sema)
+        .collect::<Vec<_>>();
+    add_assembly_functions(db, project_id, file_id, &mut symbols);
+    Some(symbols)
+}
+
+/// Sema does not look into inline assembly, so functions declared there come from the DefMap,
+/// nested under the symbol whose range encloses them.
+fn add_assembly_functions(
+    db: &dyn HirDatabase,
+    project_id: ProjectId,
+    file_id: FileId,
+    symbols: &mut Vec<crate::SymbolInfo>,
+) {
+    let program = lowered_program(db, project_id);
+    for entry in program.def_map().entries() {
+        if entry.kind() != DefKind::YulFunction || entry.location().file_id() != file_id {
+            continue;
+        }
+        let range = entry.location().range();
+        insert_nested(
+            symbols,
+            crate::SymbolInfo {
+                kind: crate::SymbolKind::Function,
+                name: entry.location().name().to_string(),
+                range,
+                selection_range: range,
+                children: Vec::new(),
+            },
+        );
+    }
+}
+
+fn insert_nested(symbols: &mut Vec<crate::SymbolInfo>, symbol: crate::SymbolInfo) {
+    let parent = symbols.iter_mut().find(|parent| {
+        parent.range.start() <= symbol.range.start() && symbol.range.end() <= parent.range.end()
+    });
+    match parent {
+        Some(parent) => insert_nested(&mut parent.children, symbol),
+        None => symbols.push(symbol),
+    }
 }
 
 fn symbol_info_from_sema(symbol: SemaSymbol) -> crate::SymbolInfo {
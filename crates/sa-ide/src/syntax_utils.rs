@@ -4,14 +4,12 @@ use std::path::PathBuf;
 use sa_base_db::{FileId, ProjectId};
 use sa_def::{DefEntry, DefKind};
 use sa_hir::{HirDatabase, lowered_program};
-use sa_sema::{SemaFunctionSignature, sema_snapshot_for_project};
+use sa_sema::{SemaFunctionSignature, sema_snapshot_for_file};
 use sa_span::TextRange;
 use sa_syntax::{
     Parse,
-    ast::{
-        CommentKind, DocComment, Item, ItemFunction, ItemKind, NatSpecItem, NatSpecKind, Type,
-        VariableDefinition,
-    },
+    ast::{Item, ItemFunction, ItemKind, Type, VariableDefinition},
+    docs::{DocTagKind, ItemDocs, item_docs},
 };
 use tracing::debug;
 use url::Url;
@@ -113,10 +111,11 @@ pub fn sema_function_signature_for_entry(
     entry: &DefEntry,
 ) -> Option<SemaFunctionSignature> {
     let project = db.project_input(project_id);
-    let snapshot = sema_snapshot_for_project(db, project);
-    let snapshot = snapshot.for_file(entry.location().file_id())?;
+    let file_id = entry.location().file_id();
+    let snapshot = sema_snapshot_for_file(db, project, file_id);
+    let snapshot = snapshot.for_file(file_id)?;
     snapshot.function_signature_for_definition(
-        entry.location().file_id(),
+        file_id,
         entry.location().range(),
         entry.location().name(),
         entry.container(),
@@ -129,10 +128,11 @@ pub fn sema_variable_label_for_entry(
     entry: &DefEntry,
 ) -> Option<String> {
     let project = db.project_input(project_id);
-    let snapshot = sema_snapshot_for_project(db, project);
-    let snapshot = snapshot.for_file(entry.location().file_id())?;
+    let file_id = entry.location().file_id();
+    let snapshot = sema_snapshot_for_file(db, project, file_id);
+    let snapshot = snapshot.for_file(file_id)?;
     snapshot.variable_label_for_definition(
-        entry.location().file_id(),
+        file_id,
         entry.location().range(),
         entry.location().name(),
         entry.container(),
@@ -147,18 +147,12 @@ pub fn sema_variable_label_for_entry(
 /// # Returns
 /// The combined documentation string, or `None` if no documentation exists.
 pub fn docs_for_item(parse: &Parse, item: &Item<'static>) -> Option<String> {
-    parse.with_session(move || {
-        let docs = collect_doc_comments(item);
-        if docs.is_empty() {
-            return None;
-        }
-
-        if has_explicit_natspec_tags(&docs) {
-            render_natspec_docs(&docs)
-        } else {
-            render_plain_docs(&docs)
-        }
-    })
+    let docs = item_docs(parse, item)?;
+    if docs.has_explicit_tags() {
+        render_natspec_docs(&docs)
+    } else {
+        render_plain_docs(&docs)
+    }
 }
 
 pub fn docs_for_item_with_inheritdoc(
@@ -169,12 +163,8 @@ pub fn docs_for_item_with_inheritdoc(
     item: &Item<'static>,
     container: Option<&str>,
 ) -> Option<String> {
+    let docs = item_docs(parse, item)?;
     parse.with_session(move || {
-        let docs = collect_doc_comments(item);
-        if docs.is_empty() {
-            return None;
-        }
-
         let text = db.file_input(file_id).text(db);
         let contract_name = match &item.kind {
             ItemKind::Contract(contract) => Some(contract.name.to_string()),
@@ -187,7 +177,7 @@ pub fn docs_for_item_with_inheritdoc(
             contract_name: contract_name.as_deref(),
         };
 
-        if !has_explicit_natspec_tags(&docs) {
+        if !docs.has_explicit_tags() {
             return render_plain_docs(&docs).map(|doc| linkify_doc_text(&link_ctx, &doc));
         }
 
@@ -216,14 +206,11 @@ pub fn docs_for_item_with_inheritdoc(
     })
 }
 
-fn collect_doc_comments<'a>(item: &'a Item<'static>) -> Vec<&'a DocComment<'static>> {
-    item.docs.iter().collect()
-}
-
-fn render_plain_docs(docs: &[&DocComment<'_>]) -> Option<String> {
+fn render_plain_docs(docs: &ItemDocs) -> Option<String> {
     let combined = docs
+        .tags()
         .iter()
-        .map(|doc| normalized_doc_text(doc))
+        .map(|tag| tag.text())
         .filter(|doc| !doc.is_empty())
         .collect::<Vec<_>>()
         .join("\n");
@@ -234,7 +221,7 @@ fn render_plain_docs(docs: &[&DocComment<'_>]) -> Option<String> {
     }
 }
 
-fn render_natspec_docs(docs: &[&DocComment<'_>]) -> Option<String> {
+fn render_natspec_docs(docs: &ItemDocs) -> Option<String> {
     let rendered = render_natspec_markdown(docs);
     if rendered.is_empty() {
         None
@@ -243,15 +230,7 @@ fn render_natspec_docs(docs: &[&DocComment<'_>]) -> Option<String> {
     }
 }
 
-fn has_explicit_natspec_tags(docs: &[&DocComment<'_>]) -> bool {
-    docs.iter().any(|doc| {
-        doc.natspec
-            .iter()
-            .any(|item| is_explicit_natspec_item(doc, item))
-    })
-}
-
-fn render_natspec_markdown(docs: &[&DocComment<'_>]) -> String {
+fn render_natspec_markdown(docs: &ItemDocs) -> String {
     let sections = collect_natspec_sections(docs);
     render_natspec_sections(&sections)
 }
@@ -321,99 +300,26 @@ impl NatSpecSections {
     }
 }
 
-fn collect_natspec_sections(docs: &[&DocComment<'_>]) -> NatSpecSections {
+fn collect_natspec_sections(docs: &ItemDocs) -> NatSpecSections {
     let mut sections = NatSpecSections::default();
-    let mut last_section: Option<LastSection> = None;
-
-    for doc in docs {
-        let explicit_items = doc
-            .natspec
-            .iter()
-            .filter(|item| is_explicit_natspec_item(doc, item))
-            .collect::<Vec<_>>();
-        if explicit_items.is_empty() {
-            let content = normalized_doc_text(doc);
-            match last_section {
-                Some(LastSection::Title(idx)) => {
-                    append_paragraph_entry(&mut sections.titles, idx, content.as_str());
-                }
-                Some(LastSection::Author(idx)) => {
-                    append_paragraph_entry(&mut sections.authors, idx, content.as_str());
-                }
-                Some(LastSection::Notice(idx)) => {
-                    append_paragraph_entry(&mut sections.notices, idx, content.as_str());
-                }
-                Some(LastSection::Dev(idx)) => {
-                    append_paragraph_entry(&mut sections.devs, idx, content.as_str());
-                }
-                Some(LastSection::Param(idx)) => {
-                    append_list_entry(&mut sections.params, idx, content.as_str());
-                }
-                Some(LastSection::Return(idx)) => {
-                    append_list_entry(&mut sections.returns, idx, content.as_str());
-                }
-                Some(LastSection::Custom(idx)) => {
-                    append_list_entry(&mut sections.customs, idx, content.as_str());
-                }
-                Some(LastSection::Inheritdoc(idx)) => {
-                    append_list_entry(&mut sections.inheritdocs, idx, content.as_str());
-                }
-                None => {
-                    if !content.is_empty() {
-                        let idx = push_paragraph_entry(&mut sections.notices, content.as_str());
-                        last_section = Some(LastSection::Notice(idx));
-                    }
-                }
+    for tag in docs.tags() {
+        let (first, rest) = tag
+            .parts
+            .split_first()
+            .map_or(("", &[][..]), |(first, rest)| (first.as_str(), rest));
+        match &tag.kind {
+            DocTagKind::Title => push_paragraph(&mut sections.titles, first, rest),
+            DocTagKind::Author => push_paragraph(&mut sections.authors, first, rest),
+            DocTagKind::Notice => push_paragraph(&mut sections.notices, first, rest),
+            DocTagKind::Dev => push_paragraph(&mut sections.devs, first, rest),
+            DocTagKind::Param(name) => push_list(&mut sections.params, name, first, rest),
+            DocTagKind::Return(name) => push_list(&mut sections.returns, name, first, rest),
+            DocTagKind::Custom(name) => push_list(&mut sections.customs, name, first, rest),
+            DocTagKind::Inheritdoc(contract) => {
+                push_list(&mut sections.inheritdocs, contract, first, rest)
             }
-            continue;
-        }
-
-        for item in explicit_items {
-            let content = normalized_natspec_content(doc, item);
-            last_section = match item.kind {
-                NatSpecKind::Title => Some(LastSection::Title(push_paragraph_entry(
-                    &mut sections.titles,
-                    content.as_str(),
-                ))),
-                NatSpecKind::Author => Some(LastSection::Author(push_paragraph_entry(
-                    &mut sections.authors,
-                    content.as_str(),
-                ))),
-                NatSpecKind::Notice => Some(LastSection::Notice(push_paragraph_entry(
-                    &mut sections.notices,
-                    content.as_str(),
-                ))),
-                NatSpecKind::Dev => Some(LastSection::Dev(push_paragraph_entry(
-                    &mut sections.devs,
-                    content.as_str(),
-                ))),
-                NatSpecKind::Param { name } => Some(LastSection::Param(push_list_entry(
-                    &mut sections.params,
-                    name.to_string(),
-                    content.as_str(),
-                ))),
-                NatSpecKind::Return { name } => Some(LastSection::Return(push_list_entry(
-                    &mut sections.returns,
-                    name.to_string(),
-                    content.as_str(),
-                ))),
-                NatSpecKind::Custom { name } => Some(LastSection::Custom(push_list_entry(
-                    &mut sections.customs,
-                    name.to_string(),
-                    content.as_str(),
-                ))),
-                NatSpecKind::Inheritdoc { contract } => {
-                    Some(LastSection::Inheritdoc(push_list_entry(
-                        &mut sections.inheritdocs,
-                        contract.to_string(),
-                        content.as_str(),
-                    )))
-                }
-                NatSpecKind::Internal { .. } => last_section,
-            };
         }
     }
-
     sections
 }
 
@@ -447,18 +353,6 @@ fn render_natspec_sections(sections: &NatSpecSections) -> String {
     rendered.join("\n\n")
 }
 
-#[derive(Clone, Copy)]
-enum LastSection {
-    Title(usize),
-    Author(usize),
-    Notice(usize),
-    Dev(usize),
-    Param(usize),
-    Return(usize),
-    Custom(usize),
-    Inheritdoc(usize),
-}
-
 #[derive(Clone)]
 struct ResolvedNatSpec {
     sections: NatSpecSections,
@@ -502,27 +396,26 @@ fn log_inheritdoc_failure(
     );
 }
 
-fn push_paragraph_entry(section: &mut Vec<String>, content: &str) -> usize {
-    section.push(normalize_natspec_text(content));
-    section.len() - 1
-}
-
-fn append_paragraph_entry(section: &mut [String], idx: usize, content: &str) {
-    if let Some(entry) = section.get_mut(idx) {
-        append_markdown(entry, content);
+fn push_paragraph(section: &mut Vec<String>, content: &str, continuation: &[String]) {
+    let mut entry = normalize_natspec_text(content);
+    for part in continuation {
+        append_markdown(&mut entry, part);
     }
+    section.push(entry);
 }
 
-fn push_list_entry(section: &mut Vec<(String, String)>, name: String, content: &str) -> usize {
+fn push_list(
+    section: &mut Vec<(String, String)>,
+    name: &str,
+    content: &str,
+    continuation: &[String],
+) {
     let name = escape_inline_code(name.trim());
-    section.push((name, normalize_natspec_text(content)));
-    section.len() - 1
-}
-
-fn append_list_entry(section: &mut [(String, String)], idx: usize, content: &str) {
-    if let Some((_, entry)) = section.get_mut(idx) {
-        append_markdown(entry, content);
+    let mut entry = normalize_natspec_text(content);
+    for part in continuation {
+        append_markdown(&mut entry, part);
     }
+    section.push((name, entry));
 }
 
 fn render_paragraph_section(label: &str, entries: &[String]) -> Option<String> {
@@ -565,16 +458,13 @@ fn resolve_natspec_for_item_inner(
     item: &Item<'static>,
     visited: &mut HashSet<InheritdocKey>,
 ) -> ResolvedNatSpec {
-    let docs = collect_doc_comments(item);
-    if docs.is_empty() {
+    let Some(docs) = item_docs(ctx.parse, item) else {
         return ResolvedNatSpec {
             sections: NatSpecSections::default(),
             has_explicit_tags: false,
         };
-    }
-
-    let has_explicit_tags = has_explicit_natspec_tags(&docs);
-    if !has_explicit_tags {
+    };
+    if !docs.has_explicit_tags() {
         return ResolvedNatSpec {
             sections: NatSpecSections::default(),
             has_explicit_tags: false,
@@ -707,7 +597,7 @@ fn resolve_base_contract(
     inheritdoc_contract: &str,
 ) -> Option<BaseContract> {
     let project = db.project_input(project_id);
-    let snapshot = sema_snapshot_for_project(db, project);
+    let snapshot = sema_snapshot_for_file(db, project, file_id);
     let snapshot = match snapshot.for_file(file_id) {
         Some(snapshot) => snapshot,
         None => {
@@ -850,7 +740,7 @@ fn sema_inheritdoc_signature(
     })?;
     let name_range = ctx.parse.span_to_text_range(span)?;
     let project = ctx.db.project_input(ctx.project_id);
-    let snapshot = sema_snapshot_for_project(ctx.db, project);
+    let snapshot = sema_snapshot_for_file(ctx.db, project, ctx.file_id);
     let snapshot = snapshot.for_file(ctx.file_id)?;
     snapshot.function_abi_signature_for_definition(
         ctx.file_id,
@@ -888,79 +778,6 @@ fn normalize_signature_type(ty: &str) -> String {
     ty.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
-fn is_explicit_natspec_item(doc: &DocComment<'_>, item: &NatSpecItem) -> bool {
-    if !doc_has_explicit_tag(doc) {
-        return false;
-    }
-
-    match item.kind {
-        NatSpecKind::Internal { .. } => false,
-        NatSpecKind::Param { name }
-        | NatSpecKind::Return { name }
-        | NatSpecKind::Custom { name } => !name.as_str().is_empty(),
-        NatSpecKind::Inheritdoc { contract } => !contract.as_str().is_empty(),
-        _ => true,
-    }
-}
-
-fn doc_has_explicit_tag(doc: &DocComment<'_>) -> bool {
-    let text = doc.symbol.as_str();
-    for line in text.lines() {
-        let trimmed = line.trim_start();
-        let trimmed = match doc.kind {
-            CommentKind::Line => trimmed,
-            CommentKind::Block => trimmed
-                .strip_prefix('*')
-                .map(|rest| rest.trim_start())
-                .unwrap_or(trimmed),
-        };
-        if trimmed.starts_with('@') {
-            return true;
-        }
-    }
-    false
-}
-
-fn normalized_doc_text(doc: &DocComment<'_>) -> String {
-    match doc.kind {
-        CommentKind::Line => doc.symbol.as_str().trim().to_string(),
-        CommentKind::Block => normalize_block_comment_text(doc.symbol.as_str()),
-    }
-}
-
-fn normalized_natspec_content(doc: &DocComment<'_>, item: &NatSpecItem) -> String {
-    let content = doc.natspec_content(item);
-    match doc.kind {
-        CommentKind::Line => content.trim().to_string(),
-        CommentKind::Block => normalize_block_comment_text(content),
-    }
-}
-
-fn normalize_block_comment_text(text: &str) -> String {
-    let mut lines = text
-        .lines()
-        .map(|line| {
-            let mut trimmed = line.trim_start();
-            if let Some(rest) = trimmed.strip_prefix('*') {
-                trimmed = rest;
-                if trimmed.starts_with(' ') {
-                    trimmed = &trimmed[1..];
-                }
-            }
-            trimmed.trim_end().to_string()
-        })
-        .collect::<Vec<_>>();
-
-    while matches!(lines.first(), Some(line) if line.is_empty()) {
-        lines.remove(0);
-    }
-    while matches!(lines.last(), Some(line) if line.is_empty()) {
-        lines.pop();
-    }
-
-    lines.join("\n")
-}
-
 fn normalize_natspec_text(text: &str) -> String {
     let mut lines = text.lines().map(|line| line.trim_end()).collect::<Vec<_>>();
     while matches!(lines.first(), Some(line) if line.trim().is_empty()) {
@@ -1447,7 +1264,7 @@ fn linearized_base_contracts(
     contract_name: &str,
 ) -> Option<Vec<BaseContract>> {
     let project = db.project_input(project_id);
-    let snapshot = sema_snapshot_for_project(db, project);
+    let snapshot = sema_snapshot_for_file(db, project, file_id);
     let snapshot = snapshot.for_file(file_id)?;
     snapshot.with_gcx(|gcx| {
         let source_id = snapshot.source_id_for_file(file_id)?;
@@ -1639,12 +1456,6 @@ contract Tagged {}
         assert!(docs.contains("Details"));
     }
 
-    #[test]
-    fn normalize_block_comment_text_trims_and_strips_stars() {
-        let text = "\n * hello \n *\n * world \n ";
-        assert_eq!(normalize_block_comment_text(text), "hello\n\nworld");
-    }
-
     #[test]
     fn append_markdown_respects_newlines_and_blank_lines() {
         let mut target = "Hello".to_string();
@@ -0,0 +1,93 @@
+use sa_base_db::{Database, FileId};
+use sa_span::TextRange;
+use sa_syntax::cst::{SyntaxKind, SyntaxNode};
+
+use crate::contract_graph::{ContractKind, contract_header};
+
+/// A contract in a `.t.sol` file that forge runs tests from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestContract {
+    pub name: String,
+    pub file_id: FileId,
+    /// The range of the contract's name.
+    pub range: TextRange,
+    pub tests: Vec<TestFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFunction {
+    pub name: String,
+    pub kind: TestKind,
+    /// The range of the function's name.
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestKind {
+    Test,
+    /// A test taking parameters, which forge fills with random inputs.
+    Fuzz,
+    Invariant,
+}
+
+impl TestKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TestKind::Test => "test",
+            TestKind::Fuzz => "fuzz",
+            TestKind::Invariant => "invariant",
+        }
+    }
+}
+
+/// The test contracts of `file_id`, following forge's rules: public or external functions named
+/// `test*` or `invariant*` in non-abstract contracts of `.t.sol` files.
+pub(crate) fn discover_tests(db: &Database, file_id: FileId) -> Vec<TestContract> {
+    if !db.file_path(file_id).as_str().ends_with(".t.sol") {
+        return Vec::new();
+    }
+    let cst = sa_hir::syntax(db, file_id);
+    cst.contracts()
+        .filter(|contract| contract_header(contract) == (ContractKind::Contract, false))
+        .filter_map(|contract| {
+            let name = contract.name()?;
+            let tests = contract
+                .child_node(SyntaxKind::ContractBody)?
+                .child_nodes()
+                .filter(|member| member.kind() == SyntaxKind::Function)
+                .filter_map(test_function)
+                .collect::<Vec<_>>();
+            (!tests.is_empty()).then(|| TestContract {
+                name: name.text().to_string(),
+                file_id,
+                range: name.range(),
+                tests,
+            })
+        })
+        .collect()
+}
+
+fn test_function(function: &SyntaxNode) -> Option<TestFunction> {
+    let name = function.name()?;
+    let callable = function
+        .tokens()
+        .any(|token| matches!(token.text(), "public" | "external"));
+    if !callable {
+        return None;
+    }
+    let has_params = function
+        .child_node(SyntaxKind::ParamList)
+        .is_some_and(|params| !params.text().trim_matches(['(', ')']).trim().is_empty());
+    let kind = match name.text() {
+        text if text.starts_with("invariant") => TestKind::Invariant,
+        text if text.starts_with("testFuzz") => TestKind::Fuzz,
+        text if text.starts_with("test") && has_params => TestKind::Fuzz,
+        text if text.starts_with("test") => TestKind::Test,
+        _ => return None,
+    };
+    Some(TestFunction {
+        name: name.text().to_string(),
+        kind,
+        range: name.range(),
+    })
+}
@@ -1,4 +1,4 @@
-use sa_ide::{CodeActionDiagnostic, CodeActionKind};
+use sa_ide::{AssistResolveStrategy, CodeActionDiagnostic, CodeActionKind};
 use sa_paths::NormalizedPath;
 use sa_test_support::{find_range, setup_analysis};
 
@@ -23,7 +23,7 @@ contract Main {
     assert_eq!(actions.len(), 1);
     assert_eq!(actions[0].kind, CodeActionKind::QuickFix);
 
-    let edits = actions[0].edit.edits();
+    let edits = actions[0].edit.as_ref().expect("edit").edits();
     assert_eq!(edits.len(), 1);
     let file_edit = &edits[0];
     assert_eq!(file_edit.file_id, file_id);
@@ -52,7 +52,7 @@ contract Main {
     assert_eq!(actions.len(), 1);
     assert_eq!(actions[0].kind, CodeActionKind::QuickFix);
 
-    let edits = actions[0].edit.edits();
+    let edits = actions[0].edit.as_ref().expect("edit").edits();
     assert_eq!(edits.len(), 1);
     let file_edit = &edits[0];
     assert_eq!(file_edit.edits.len(), 1);
@@ -82,9 +82,174 @@ contract Main {
     assert_eq!(actions.len(), 1);
     assert_eq!(actions[0].kind, CodeActionKind::QuickFix);
 
-    let edits = actions[0].edit.edits();
+    let edits = actions[0].edit.as_ref().expect("edit").edits();
     assert_eq!(edits.len(), 1);
     let file_edit = &edits[0];
     assert_eq!(file_edit.edits.len(), 1);
     assert_eq!(file_edit.edits[0].new_text, "BadStruct");
 }
+
+fn apply_single_edit(text: &str, actions: &[sa_ide::CodeAction]) -> String {
+    assert_eq!(actions.len(), 1);
+    let edits = actions[0].edit.as_ref().expect("edit").edits();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits.len(), 1);
+    let edit = &edits[0].edits[0];
+    let start = usize::from(edit.range.start());
+    let end = usize::from(edit.range.end());
+    format!("{}{}{}", &text[..start], edit.new_text, &text[end..])
+}
+
+#[test]
+fn generate_getter_for_internal_value() {
+    let text = r#"
+contract Main {
+    uint256 internal _total;
+    string private name;
+}
+"#
+    .trim();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let actions = analysis.assists(
+        file_id,
+        find_range(text, "_total"),
+        &AssistResolveStrategy::All,
+    );
+    assert_eq!(actions[0].kind, CodeActionKind::Generate);
+    assert_eq!(actions[0].title, "Generate getter `total`");
+    let updated = apply_single_edit(text, &actions);
+    assert!(updated.contains(
+        "    function total() external view returns (uint256) {\n        return _total;\n    }\n}"
+    ));
+
+    let actions = analysis.assists(
+        file_id,
+        find_range(text, "name"),
+        &AssistResolveStrategy::All,
+    );
+    let updated = apply_single_edit(text, &actions);
+    assert!(updated.contains(
+        "    function getName() external view returns (string memory) {\n        return name;\n    }\n}"
+    ));
+}
+
+#[test]
+fn generate_getter_for_mapping_and_array() {
+    let text = r#"
+contract Main {
+    mapping(address owner => mapping(string => uint256[])) internal balances;
+}
+"#
+    .trim();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let actions = analysis.assists(
+        file_id,
+        find_range(text, "balances"),
+        &AssistResolveStrategy::All,
+    );
+    let updated = apply_single_edit(text, &actions);
+    assert!(updated.contains(
+        "function getBalances(address owner, string calldata arg1, uint256 arg2) external view returns (uint256) {\n        return balances[owner][arg1][arg2];\n    }"
+    ));
+}
+
+#[test]
+fn generate_getter_for_struct_omits_mappings_and_arrays() {
+    let text = r#"
+contract Main {
+    struct Position {
+        uint256 amount;
+        string label;
+        uint256[] history;
+        mapping(address => bool) approvals;
+    }
+
+    mapping(uint256 => Position) private positions;
+}
+"#
+    .trim();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let actions = analysis.assists(
+        file_id,
+        find_range(text, "positions"),
+        &AssistResolveStrategy::All,
+    );
+    let updated = apply_single_edit(text, &actions);
+    assert!(updated.contains(
+        "    function getPositions(uint256 arg0) external view returns (uint256, string memory) {\n        Position storage entry = positions[arg0];\n        return (entry.amount, entry.label);\n    }\n}"
+    ));
+}
+
+#[test]
+fn generate_getter_skips_public_and_existing_getters() {
+    let text = r#"
+contract Main {
+    uint256 public count;
+    uint256 internal _total;
+
+    function total() external view returns (uint256) {
+        return _total;
+    }
+}
+"#
+    .trim();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    assert!(
+        analysis
+            .assists(
+                file_id,
+                find_range(text, "count"),
+                &AssistResolveStrategy::All
+            )
+            .is_empty()
+    );
+    assert!(
+        analysis
+            .assists(
+                file_id,
+                find_range(text, "_total;"),
+                &AssistResolveStrategy::All
+            )
+            .is_empty()
+    );
+}
+
+#[test]
+fn assists_defer_edits_until_resolved() {
+    let text = r#"
+contract Main {
+    uint256 internal _total;
+}
+"#
+    .trim();
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+    let range = find_range(text, "_total");
+
+    let actions = analysis.assists(file_id, range, &AssistResolveStrategy::None);
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].id, "generate_getter");
+    assert!(actions[0].edit.is_none());
+
+    let resolved = analysis
+        .resolve_assist(file_id, range, &actions[0].id)
+        .expect("resolved action");
+    assert_eq!(resolved.title, actions[0].title);
+    let updated = apply_single_edit(text, &[resolved]);
+    assert!(updated.contains("function total() external view returns (uint256)"));
+
+    assert!(analysis.resolve_assist(file_id, range, "unknown").is_none());
+}
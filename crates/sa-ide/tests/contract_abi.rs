@@ -0,0 +1,61 @@
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+use serde_json::json;
+
+#[test]
+fn contract_abi_matches_solc_layout() {
+    let text = r#"contract Token {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    error Unauthorized(address caller);
+
+    uint256 public totalSupply;
+
+    constructor(uint256 supply) {
+        totalSupply = supply;
+    }
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        return true;
+    }
+
+    function helper() internal {}
+
+    receive() external payable {}
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let abi = analysis.contract_abi(file_id, "Token").expect("abi");
+    let items = abi.as_array().expect("abi array");
+    let find = |ty: &str, name: Option<&str>| {
+        items
+            .iter()
+            .find(|item| item["type"] == ty && name.is_none_or(|name| item["name"] == name))
+    };
+
+    let constructor = find("constructor", None).expect("constructor");
+    assert_eq!(constructor["inputs"][0]["type"], "uint256");
+    assert_eq!(constructor["stateMutability"], "nonpayable");
+
+    let transfer = find("function", Some("transfer")).expect("transfer");
+    assert_eq!(
+        transfer["inputs"],
+        json!([
+            {"name": "to", "type": "address", "internalType": "address"},
+            {"name": "amount", "type": "uint256", "internalType": "uint256"},
+        ])
+    );
+    assert_eq!(transfer["outputs"][0]["type"], "bool");
+
+    assert!(find("function", Some("totalSupply")).is_some());
+    assert!(find("function", Some("helper")).is_none());
+    assert_eq!(
+        find("event", Some("Transfer")).expect("event")["inputs"][0]["indexed"],
+        true
+    );
+    assert!(find("error", Some("Unauthorized")).is_some());
+    assert!(find("receive", None).is_some());
+
+    assert!(analysis.contract_abi(file_id, "Missing").is_none());
+}
@@ -0,0 +1,47 @@
+use sa_ide::ContractKind;
+use sa_paths::NormalizedPath;
+use sa_span::TextSize;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn contract_graph_lists_kinds_edges_and_linearizations() {
+    let text = r#"interface IToken {}
+abstract contract Base is IToken {}
+library Math {}
+contract Token is Base {}
+contract Other {}"#;
+    let path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let graph = analysis.contract_graph(file_id, None).expect("graph");
+    let names = graph
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["IToken", "Base", "Math", "Token", "Other"]);
+    assert_eq!(graph.nodes[0].kind, ContractKind::Interface);
+    assert!(graph.nodes[1].is_abstract);
+    assert_eq!(graph.nodes[2].kind, ContractKind::Library);
+    assert_eq!(graph.nodes[3].kind, ContractKind::Contract);
+    assert!(!graph.nodes[3].is_abstract);
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| (edge.derived, edge.base))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, [(1, 0), (3, 1)]);
+    assert_eq!(graph.nodes[3].linearization, [3, 1, 0]);
+
+    let offset = TextSize::of(&text[..text.find("Token is").expect("token")]);
+    let graph = analysis
+        .contract_graph(file_id, Some(offset))
+        .expect("contract graph");
+    let names = graph
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["IToken", "Base", "Token"]);
+}
@@ -79,3 +79,50 @@ fn document_symbols_match_outline_structure() {
     assert_eq!(udvt_symbol.kind, SymbolKind::Udvt);
     assert_eq!(slice_range(text, udvt_symbol.selection_range), "UserId");
 }
+
+#[test]
+fn document_symbols_list_special_functions_under_their_keyword() {
+    let text = r#"contract Foo {
+    constructor() {}
+    receive() external payable {}
+    fallback() external {}
+}
+"#;
+
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+    let symbols = analysis.document_symbols(file_id);
+
+    let contract = find_symbol(&symbols, "Foo");
+    assert_eq!(contract.children.len(), 3);
+    for keyword in ["constructor", "receive", "fallback"] {
+        let symbol = find_symbol(&contract.children, keyword);
+        assert_eq!(symbol.kind, SymbolKind::Function);
+        assert_eq!(slice_range(text, symbol.selection_range), keyword);
+    }
+}
+
+#[test]
+fn document_symbols_include_assembly_functions() {
+    let text = r#"library LibBit {
+    function popCount(uint256 x) internal pure returns (uint256 c) {
+        assembly {
+            function step(v) -> r { r := v }
+            c := step(x)
+        }
+    }
+}
+"#;
+
+    let path = NormalizedPath::new("/workspace/src/LibBit.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+    let symbols = analysis.document_symbols(file_id);
+
+    let library = find_symbol(&symbols, "LibBit");
+    let function = find_symbol(&library.children, "popCount");
+    let step = find_symbol(&function.children, "step");
+    assert_eq!(step.kind, SymbolKind::Function);
+    assert_eq!(slice_range(text, step.selection_range), "step");
+}
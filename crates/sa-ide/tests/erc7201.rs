@@ -0,0 +1,111 @@
+use sa_ide::{NamespacedStorageIssueKind, erc7201_slot};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn slot_matches_openzeppelin_constants() {
+    assert_eq!(
+        erc7201_slot("openzeppelin.storage.Ownable"),
+        "0x9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300"
+    );
+    assert_eq!(
+        erc7201_slot("openzeppelin.storage.Initializable"),
+        "0xf0c57e16840df040f15088dc2f81fe391c3923bec73e23a9662efc9c229c6a00"
+    );
+}
+
+#[test]
+fn reports_wrong_slots_and_writes_outside_the_namespace() {
+    let text = r#"contract Vault {
+    /// @custom:storage-location erc7201:openzeppelin.storage.Ownable
+    struct OwnableStorage {
+        address _owner;
+    }
+
+    /// @custom:storage-location erc7201:example.main
+    struct MainStorage {
+        uint256 total;
+        uint256[] deposits;
+    }
+
+    bytes32 private constant OwnableStorageLocation =
+        0x9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300;
+    bytes32 private constant MainStorageLocation = 0x1234;
+
+    MainStorage legacy;
+
+    function _getOwnableStorage() private pure returns (OwnableStorage storage $) {
+        assembly {
+            $.slot := OwnableStorageLocation
+        }
+    }
+
+    function _getMainStorage() private pure returns (MainStorage storage $) {
+        assembly {
+            $.slot := MainStorageLocation
+        }
+    }
+
+    function deposit(uint256 amount) external {
+        MainStorage storage $ = _getMainStorage();
+        $.total += amount;
+        $.deposits.push(amount);
+    }
+
+    function transferOwnership(address owner) external {
+        OwnableStorage storage raw;
+        assembly {
+            raw.slot := 0
+        }
+        raw._owner = owner;
+    }
+
+    function owner() external view returns (address) {
+        OwnableStorage storage peek;
+        assembly {
+            peek.slot := 1
+        }
+        return peek._owner;
+    }
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Vault.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let issues = analysis.namespaced_storage_issues(file_id);
+    let found = issues
+        .iter()
+        .map(|issue| {
+            let start = u32::from(issue.range.start()) as usize;
+            let end = u32::from(issue.range.end()) as usize;
+            (issue.kind, issue.name.as_str(), &text[start..end])
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (
+                NamespacedStorageIssueKind::WrongSlot,
+                "MainStorage",
+                "MainStorageLocation"
+            ),
+            (
+                NamespacedStorageIssueKind::OutsideNamespace,
+                "OwnableStorage",
+                "raw"
+            ),
+            (
+                NamespacedStorageIssueKind::OutsideNamespace,
+                "MainStorage",
+                "legacy"
+            ),
+        ]
+    );
+    assert_eq!(
+        issues[0].message,
+        format!(
+            "the ERC-7201 slot of `MainStorage` (erc7201:example.main) is `{}`, but its slot is `0x1234`",
+            erc7201_slot("example.main")
+        )
+    );
+}
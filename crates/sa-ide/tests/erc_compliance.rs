@@ -0,0 +1,125 @@
+use sa_ide::{ErcIssueKind, ErcStandard};
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn annotated_contract_reports_missing_and_mismatched_members() {
+    let text = r#"/// @custom:erc ERC-20
+contract Token {
+    event Transfer(address from, address to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    uint256 public totalSupply;
+    mapping(address => uint256) balances;
+
+    function balanceOf(address owner) external returns (uint256) {
+        return balances[owner];
+    }
+
+    function transfer(address to, uint256 amount) external {}
+
+    function transferFrom(address from, address to, uint256 amount) external returns (bool) {
+        return true;
+    }
+
+    function approve(address spender) external returns (bool) {
+        return true;
+    }
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let issues = analysis.erc_compliance(file_id);
+    let found = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.member.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (ErcIssueKind::Mismatched, "balanceOf(address)"),
+            (ErcIssueKind::Mismatched, "transfer(address,uint256)"),
+            (ErcIssueKind::Missing, "approve(address,uint256)"),
+            (ErcIssueKind::Missing, "allowance(address,address)"),
+            (
+                ErcIssueKind::Mismatched,
+                "Transfer(address,address,uint256)"
+            ),
+        ]
+    );
+    assert!(
+        issues
+            .iter()
+            .all(|issue| issue.standard == ErcStandard::Erc20)
+    );
+    let name_start = text.find("Token").expect("name") as u32;
+    assert_eq!(u32::from(issues[0].range.start()), name_start);
+    assert_eq!(
+        issues[0].message,
+        "`Token` claims ERC-20: `balanceOf(address)` must be view but is nonpayable"
+    );
+    assert_eq!(
+        issues[2].message,
+        "`Token` claims ERC-20: missing function `approve(address,uint256)` (found `approve(address)`)"
+    );
+    assert_eq!(
+        issues[4].message,
+        "`Token` claims ERC-20: event `Transfer(address,address,uint256)` must be declared as \
+         `Transfer(address indexed,address indexed,uint256)` but is \
+         `Transfer(address,address,uint256)`"
+    );
+}
+
+#[test]
+fn inherited_standards_include_erc165() {
+    let text = r#"abstract contract ERC721 {
+    event Transfer(address indexed from, address indexed to, uint256 indexed id);
+
+    function balanceOf(address owner) public view virtual returns (uint256);
+}
+
+contract Collection is ERC721 {
+    function balanceOf(address) public pure override returns (uint256) {
+        return 0;
+    }
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Collection.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let issues = analysis.erc_compliance(file_id);
+    assert!(issues.iter().all(|issue| issue.contract == "Collection"));
+    assert!(
+        issues
+            .iter()
+            .all(|issue| issue.kind == ErcIssueKind::Missing)
+    );
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.standard == ErcStandard::Erc165
+                && issue.member == "supportsInterface(bytes4)")
+    );
+    assert!(
+        issues.iter().any(
+            |issue| issue.standard == ErcStandard::Erc721 && issue.member == "ownerOf(uint256)"
+        )
+    );
+    assert!(
+        !issues
+            .iter()
+            .any(|issue| issue.member == "balanceOf(address)"
+                || issue.member == "Transfer(address,address,uint256)")
+    );
+}
+
+#[test]
+fn contracts_without_claims_are_not_checked() {
+    let text = "contract Plain {\n    function transfer(address to) external {}\n}\n";
+    let path = NormalizedPath::new("/workspace/src/Plain.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    assert!(analysis.erc_compliance(file_id).is_empty());
+}
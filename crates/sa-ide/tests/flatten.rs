@@ -0,0 +1,98 @@
+use sa_paths::NormalizedPath;
+use sa_span::TextSize;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn flatten_contract_merges_base_members_and_drops_overridden_ones() {
+    let base = r#"contract Base {
+    uint256 internal total;
+
+    function name() public view virtual returns (string memory) {
+        return "base";
+    }
+
+    function add(uint256 amount) internal {
+        total += amount;
+    }
+}"#;
+    let token = r#"import "./Base.sol";
+
+contract Token is Base {
+    function name() public view override returns (string memory) {
+        return "token";
+    }
+}"#;
+    let base_path = NormalizedPath::new("/workspace/src/Base.sol");
+    let token_path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(
+        vec![
+            (base_path, base.to_string()),
+            (token_path.clone(), token.to_string()),
+        ],
+        vec![],
+    );
+    let file_id = snapshot.file_id(&token_path).expect("file id");
+    let offset = TextSize::of(&token[..token.find("function").expect("function")]);
+
+    let flattened = analysis
+        .flatten_contract(file_id, offset)
+        .expect("flattened contract");
+    assert_eq!(
+        flattened,
+        r#"contract Token is Base {
+    // from Base
+    uint256 internal total;
+    function add(uint256 amount) internal {
+        total += amount;
+    }
+
+    // from Token
+    function name() public view override returns (string memory) {
+        return "token";
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn flatten_file_inlines_imports_dependencies_first() {
+    let base = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+contract Base {}
+"#;
+    let token = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "./Base.sol";
+
+contract Token is Base {}
+"#;
+    let base_path = NormalizedPath::new("/workspace/src/Base.sol");
+    let token_path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(
+        vec![
+            (token_path.clone(), token.to_string()),
+            (base_path, base.to_string()),
+        ],
+        vec![],
+    );
+    let file_id = snapshot.file_id(&token_path).expect("file id");
+
+    let flattened = analysis.flatten_file(file_id).expect("flattened file");
+    assert_eq!(
+        flattened,
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// src/Base.sol
+
+contract Base {}
+
+// src/Token.sol
+
+contract Token is Base {}
+"#
+    );
+}
@@ -62,3 +62,27 @@ contract Foo {
 
     assert_eq!(edit.new_text, expected);
 }
+
+#[test]
+fn format_document_edits_are_minimal() {
+    let text = r#"
+contract Foo {
+    uint256 a;
+
+    function bar()public {}
+}
+"#
+    .trim_start();
+
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let edits = analysis
+        .format_document_edits(file_id, &FormatterConfig::default())
+        .expect("format edits");
+    assert_eq!(edits.len(), 1);
+    let start = text.find("    function").expect("function line");
+    assert_eq!(usize::from(edits[0].range.start()), start);
+    assert_eq!(edits[0].new_text, "    function bar() public {}\n");
+}
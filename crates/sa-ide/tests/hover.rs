@@ -1,7 +1,12 @@
-use sa_ide::HoverResult;
+use std::sync::Arc;
+
+use sa_config::ResolvedFoundryConfig;
+use sa_ide::{AnalysisChange, AnalysisHost, HoverResult};
 use sa_paths::NormalizedPath;
+use sa_project_model::{FoundryProfile, FoundryWorkspace, parse_artifact};
 use sa_span::{TextRange, TextSize};
-use sa_test_support::{extract_offset, find_range, setup_analysis};
+use sa_test_support::{extract_offset, extract_offsets, find_range, setup_analysis};
+use sa_vfs::{Vfs, VfsChange};
 
 #[test]
 fn hover_includes_contract_docs_and_label() {
@@ -383,3 +388,135 @@ contract Main {
 
     assert!(result.contents.contains("local uint256 catchValue"));
 }
+
+#[test]
+fn hover_shows_yul_function_signature() {
+    let (text, offset) = extract_offset(
+        r#"
+contract Main {
+    function foo() public pure returns (uint256 out) {
+        assembly {
+            out := dou/*caret*/ble(2)
+            function double(v) -> r {
+                r := add(v, v)
+            }
+        }
+    }
+}
+"#,
+    );
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.clone())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let result = analysis.hover(file_id, offset).expect("hover result");
+
+    assert_eq!(result.range, find_range(&text, "double"));
+    assert_eq!(result.contents, "```solidity\nfunction double(v) -> r\n```");
+    let target = analysis
+        .goto_definition(file_id, offset)
+        .expect("definition");
+    let definition = text.find("function double").expect("definition") + "function ".len();
+    assert_eq!(target.range.start(), TextSize::from(definition as u32));
+}
+
+#[test]
+fn hover_notes_functions_attached_with_using_for() {
+    let (text, offset) = extract_offset(
+        r#"
+library MathLib {
+    /// Doubles a number.
+    function double(uint256 x) internal pure returns (uint256) {
+        return x * 2;
+    }
+}
+
+contract Main {
+    using MathLib for uint256;
+
+    function run(uint256 value) public pure returns (uint256) {
+        return value.dou/*caret*/ble();
+    }
+}
+"#,
+    );
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.clone())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let result = analysis.hover(file_id, offset).expect("hover result");
+
+    assert!(result.contents.contains("function double"));
+    assert!(
+        result
+            .contents
+            .contains("Attached to `uint256` by `using for`.")
+    );
+    assert!(result.contents.contains("Doubles a number."));
+}
+
+#[test]
+fn hover_adds_bytecode_size_and_selectors_from_build_artifacts() {
+    let (text, offsets) = extract_offsets(
+        r#"contract Counter {
+    /*contract*/Counter next;
+
+    function increment() public {}
+
+    function run() public {
+        /*function*/increment();
+    }
+}"#,
+        &["/*contract*/", "/*function*/"],
+    );
+    let path = NormalizedPath::new("/workspace/src/Counter.sol");
+    let mut vfs = Vfs::default();
+    vfs.apply_change(VfsChange::Set {
+        path: path.clone(),
+        text: Arc::from(text.as_str()),
+    });
+    let snapshot = vfs.snapshot();
+    let artifact = parse_artifact(
+        "Counter",
+        r#"{
+            "abi": [],
+            "deployedBytecode": {"object": "0x60806040"},
+            "methodIdentifiers": {"increment()": "d09de08a"},
+            "metadata": {"settings": {"compilationTarget": {"src/Counter.sol": "Counter"}}}
+        }"#,
+    )
+    .expect("artifact");
+    let config = ResolvedFoundryConfig::new(
+        FoundryWorkspace::new(NormalizedPath::new("/workspace")),
+        FoundryProfile::new("default"),
+    );
+    let mut host = AnalysisHost::new();
+    let mut change = AnalysisChange::new();
+    change.set_vfs(snapshot.clone());
+    change.set_config(config);
+    change.set_artifacts(vec![artifact]);
+    host.apply_change(change);
+    let analysis = host.snapshot();
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let contract = analysis.hover(file_id, offsets[0]).expect("contract hover");
+    assert!(
+        contract.contents.ends_with("Deployed bytecode: 4 bytes"),
+        "{}",
+        contract.contents
+    );
+    let function = analysis.hover(file_id, offsets[1]).expect("function hover");
+    assert!(
+        function
+            .contents
+            .ends_with("Selector: `0xd09de08a` `increment()`"),
+        "{}",
+        function.contents
+    );
+    assert_eq!(
+        analysis
+            .contract_artifact(file_id, "Counter")
+            .and_then(|artifact| artifact.deployed_bytecode_size()),
+        Some(4)
+    );
+}
@@ -0,0 +1,67 @@
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn metrics_count_functions_calls_and_complexity() {
+    let text = r#"interface IToken {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+library Math {
+    function max(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a > b ? a : b;
+    }
+}
+
+abstract contract Base {
+    uint256[] internal amounts;
+}
+
+contract Vault is Base {
+    using Math for uint256;
+
+    IToken token;
+
+    // Pays out `amount` when both limits allow it.
+    function pay(address to, uint256 amount) external {
+        if (amount > 0 && to != address(0)) {
+            amounts.push(amount.max(1));
+            token.transfer(to, amount);
+        }
+        for (uint256 i = 0; i < 2; i++) {
+            (bool ok, ) = to.call{value: i}("");
+            require(ok || i == 0);
+        }
+    }
+
+    function _check() private view returns (bool) {
+        return msg.sender == address(this);
+    }
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Vault.sol");
+    let (analysis, _snapshot) = setup_analysis(vec![(path, text.to_string())], vec![]);
+
+    let metrics = analysis.metrics();
+    let vault = metrics
+        .iter()
+        .find(|contract| contract.name == "Vault")
+        .expect("Vault metrics");
+    assert_eq!(vault.inheritance_depth, 1);
+    assert_eq!(vault.function_count("external"), 1);
+    assert_eq!(vault.function_count("private"), 1);
+
+    let pay = &vault.functions[0];
+    assert_eq!(pay.name, "pay");
+    assert_eq!(pay.lines, 10);
+    assert_eq!(pay.external_calls, 2);
+    // `if`, `&&`, `for` and `||`.
+    assert_eq!(pay.cyclomatic_complexity, 5);
+    assert_eq!(vault.functions[1].cyclomatic_complexity, 1);
+
+    let base = metrics
+        .iter()
+        .find(|contract| contract.name == "Base")
+        .expect("Base metrics");
+    assert_eq!(base.inheritance_depth, 0);
+    assert_eq!(base.lines, 3);
+}
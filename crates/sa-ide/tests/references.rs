@@ -644,3 +644,102 @@ contract Main {
     assert_reference_ranges(analysis.find_references(file_id, def_offset), expected);
     assert!(analysis.find_references(file_id, caret_offset).is_empty());
 }
+
+#[test]
+fn references_include_assembly_uses() {
+    let (text, offset) = extract_offset(
+        r#"
+contract Main {
+    uint256 counter;
+
+    function run(uint256 amount) public {
+        assembly {
+            let doubled := add(amount, amount)
+            sstore(counter.slot, doubled)
+        }
+        counter = amo/*caret*/unt;
+    }
+}
+"#,
+    );
+    let (analysis, file_id) = setup_single_file_analysis(text.clone());
+
+    let amount_refs = analysis.find_references(file_id, offset);
+    let expected = text
+        .match_indices("amount")
+        .map(|(idx, _)| {
+            TextRange::new(
+                TextSize::from(idx as u32),
+                TextSize::from((idx + "amount".len()) as u32),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_reference_ranges(amount_refs, expected);
+
+    let doubled_offset = TextSize::from(text.rfind("doubled").expect("doubled use") as u32);
+    let doubled_refs = analysis.find_references(file_id, doubled_offset);
+    let expected = text
+        .match_indices("doubled")
+        .map(|(idx, _)| {
+            TextRange::new(
+                TextSize::from(idx as u32),
+                TextSize::from((idx + "doubled".len()) as u32),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_reference_ranges(doubled_refs, expected);
+
+    let slot_offset = TextSize::from(text.find("counter.slot").expect("slot access") as u32);
+    let counter_refs = analysis.find_references(file_id, slot_offset);
+    let expected = text
+        .match_indices("counter")
+        .map(|(idx, _)| {
+            TextRange::new(
+                TextSize::from(idx as u32),
+                TextSize::from((idx + "counter".len()) as u32),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_reference_ranges(counter_refs, expected);
+}
+
+#[test]
+fn references_find_locals_in_constructors_and_catch_clauses() {
+    let (text, offset) = extract_offset(
+        r#"
+contract Main {
+    constructor(address target) {
+        try Main(target).ping() returns (uint256 reply) {
+            reply;
+        } catch (bytes memory rea/*caret*/son) {
+            bytes memory copy = reason;
+            copy;
+            reason;
+        }
+    }
+
+    function ping() external pure returns (uint256) {
+        return 1;
+    }
+}
+"#,
+    );
+    let (analysis, file_id) = setup_single_file_analysis(text.clone());
+
+    let refs = analysis.find_references(file_id, offset);
+    let expected = text
+        .match_indices("reason")
+        .map(|(idx, _)| {
+            TextRange::new(
+                TextSize::from(idx as u32),
+                TextSize::from((idx + "reason".len()) as u32),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(expected.len(), 3);
+    assert_reference_ranges(refs, expected);
+
+    let target_offset = TextSize::from(text.find("target)").expect("target use") as u32 + 1);
+    let target_refs = analysis.find_references(file_id, target_offset);
+    assert_eq!(target_refs.len(), 2);
+}
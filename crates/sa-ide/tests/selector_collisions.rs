@@ -0,0 +1,86 @@
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn functions_sharing_a_selector_collide() {
+    let text = r#"contract Token {
+    function burn(uint256 amount) external {}
+
+    function collate_propagate_storage(bytes16 data) external {}
+
+    function mint(uint256 amount) external {}
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let collisions = analysis.selector_collisions(file_id, &[]);
+    assert_eq!(collisions.len(), 1);
+    let collision = &collisions[0];
+    assert_eq!(collision.selector, "0x42966c68");
+    assert_eq!(collision.function.signature, "burn(uint256)");
+    assert_eq!(
+        collision.other.signature,
+        "collate_propagate_storage(bytes16)"
+    );
+    let start = u32::from(collision.other.range.start()) as usize;
+    let end = u32::from(collision.other.range.end()) as usize;
+    assert_eq!(&text[start..end], "collate_propagate_storage");
+    assert_eq!(
+        collision.message,
+        "`burn(uint256)` and `collate_propagate_storage(bytes16)` in `Token` share the selector \
+         0x42966c68"
+    );
+}
+
+#[test]
+fn facets_collide_with_each_other() {
+    let text = r#"contract OwnershipFacet {
+    function owner() external view returns (address) {}
+
+    function transferOwnership(address next) external {}
+}
+
+contract VaultFacet {
+    function owner() external view returns (address) {}
+
+    function deposit() external payable {}
+}
+
+contract Unlisted {
+    function owner() external view returns (address) {}
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Facets.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    assert!(analysis.selector_collisions(file_id, &[]).is_empty());
+
+    let facets = [
+        "OwnershipFacet".to_string(),
+        "src/Facets.sol:VaultFacet".to_string(),
+    ];
+    let collisions = analysis.selector_collisions(file_id, &facets);
+    let found = collisions
+        .iter()
+        .map(|collision| {
+            (
+                collision.function.contract.as_str(),
+                collision.other.contract.as_str(),
+                collision.other.signature.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("OwnershipFacet", "VaultFacet", "owner()"),
+            ("VaultFacet", "OwnershipFacet", "owner()"),
+        ]
+    );
+    assert_eq!(
+        collisions[0].message,
+        "`owner()` in `OwnershipFacet` and `owner()` in facet `VaultFacet` share the selector \
+         0x8da5cb5b"
+    );
+}
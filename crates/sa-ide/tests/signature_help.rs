@@ -182,3 +182,63 @@ fn signature_help_includes_natspec_sections() {
     assert!(docs.contains("**Returns**"));
     assert!(docs.contains("- `product`: The product."));
 }
+
+#[test]
+fn signature_help_picks_overload_from_argument_types() {
+    let (text, offset) = extract_offset(
+        r#"contract Token {
+    function transfer(uint256 id, uint256 amount) public {}
+    function transfer(address to, uint256 amount) public {}
+
+    function test(address to) public { transfer(to, /*caret*/1); }
+}"#,
+    );
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text)], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let SignatureHelp { signatures, .. } = analysis
+        .signature_help(file_id, offset)
+        .expect("signature help");
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(
+        signatures[0].label,
+        "function transfer(address to, uint256 amount)"
+    );
+}
+
+#[test]
+fn signature_help_lists_ambiguous_overloads() {
+    let (text, offset) = extract_offset(
+        r#"contract Foo {
+    function scale(uint8 value) public {}
+    function scale(uint16 value) public {}
+    function scale(uint8 value, uint8 factor) public {}
+
+    function test() public { scale(/*caret*/1); }
+}"#,
+    );
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text)], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let SignatureHelp {
+        signatures,
+        active_signature,
+        ..
+    } = analysis
+        .signature_help(file_id, offset)
+        .expect("signature help");
+    let labels = signatures
+        .iter()
+        .map(|signature| signature.label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            "function scale(uint8 value)",
+            "function scale(uint16 value)"
+        ]
+    );
+    assert_eq!(active_signature, Some(0));
+}
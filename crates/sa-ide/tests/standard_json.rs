@@ -0,0 +1,40 @@
+use sa_paths::NormalizedPath;
+use sa_project_model::Remapping;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn standard_json_input_includes_project_sources_and_their_imports() {
+    let token = "import \"@oz/Math.sol\";\n\ncontract Token {}\n";
+    let math = "library Math {}\n";
+    let token_path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, snapshot) = setup_analysis(
+        vec![
+            (token_path.clone(), token.to_string()),
+            (
+                NormalizedPath::new("/workspace/lib/oz/Math.sol"),
+                math.to_string(),
+            ),
+            (
+                NormalizedPath::new("/workspace/lib/oz/Unused.sol"),
+                "library Unused {}\n".to_string(),
+            ),
+        ],
+        vec![Remapping::new("@oz/", "lib/oz/")],
+    );
+    let file_id = snapshot.file_id(&token_path).expect("file id");
+    let project_id = analysis.project_for_file(file_id);
+
+    let input = analysis
+        .standard_json_input(project_id)
+        .expect("standard json input");
+    assert_eq!(input["language"], "Solidity");
+    let sources = input["sources"].as_object().expect("sources");
+    assert_eq!(
+        sources.keys().map(String::as_str).collect::<Vec<_>>(),
+        ["lib/oz/Math.sol", "src/Token.sol"]
+    );
+    assert_eq!(sources["src/Token.sol"]["content"], token);
+    assert_eq!(sources["lib/oz/Math.sol"]["content"], math);
+    assert_eq!(input["settings"]["remappings"][0], "@oz/=lib/oz/");
+    assert!(input["settings"].get("optimizer").is_some(), "{input}");
+}
@@ -0,0 +1,93 @@
+use sa_ide::StorageUpgradeIssueKind;
+use sa_paths::NormalizedPath;
+use sa_span::TextSize;
+use sa_test_support::setup_analysis;
+
+const BOX: &str = r#"contract BoxV1 {
+    address owner;
+    bool paused;
+    uint256 value;
+    uint256[48] __gap;
+}
+
+/// @custom:oz-upgrades-from BoxV1
+contract BoxV2 {
+    address owner;
+    uint16 fee;
+    uint128 value;
+    uint256 cap;
+    uint256[48] __gap;
+}
+
+contract BoxV3 {
+    address owner;
+    bool paused;
+    uint256 value;
+    uint256 cap;
+    uint256[47] __gap;
+}"#;
+
+#[test]
+fn annotated_contracts_report_layout_changes() {
+    let path = NormalizedPath::new("/workspace/src/Box.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), BOX.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let issues = analysis.annotated_storage_upgrade_issues(file_id);
+    let found = issues
+        .iter()
+        .map(|issue| {
+            let start = u32::from(issue.range.start()) as usize;
+            let end = u32::from(issue.range.end()) as usize;
+            (issue.kind, issue.name.as_str(), &BOX[start..end])
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (StorageUpgradeIssueKind::Removed, "paused", "BoxV2"),
+            (StorageUpgradeIssueKind::TypeChanged, "value", "value"),
+            (StorageUpgradeIssueKind::GapMismatch, "__gap", "__gap"),
+            (StorageUpgradeIssueKind::Overlaps, "fee", "fee"),
+        ]
+    );
+    assert_eq!(
+        issues[2].message,
+        "`__gap` ends at slot 51 but ended at slot 50; resize it by -1 slot(s) so the variables \
+         after it keep their slots"
+    );
+    assert_eq!(
+        issues[3].message,
+        "new variable `fee` takes slot 0, which held `paused` (bool) in the previous version"
+    );
+}
+
+#[test]
+fn explicit_previous_version_is_compared() {
+    let path = NormalizedPath::new("/workspace/src/Box.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), BOX.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+    let offset = |name: &str| TextSize::from(BOX.find(name).expect("contract") as u32);
+
+    let issues = analysis
+        .storage_upgrade_issues(file_id, offset("BoxV3"), Some((file_id, offset("BoxV1"))))
+        .expect("issues");
+    assert!(issues.is_empty(), "{issues:?}");
+
+    let issues = analysis
+        .storage_upgrade_issues(file_id, offset("BoxV3"), Some((file_id, offset("BoxV2"))))
+        .expect("issues");
+    let found = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (StorageUpgradeIssueKind::Removed, "fee"),
+            (StorageUpgradeIssueKind::TypeChanged, "value"),
+            (StorageUpgradeIssueKind::GapMismatch, "__gap"),
+            (StorageUpgradeIssueKind::Overlaps, "paused"),
+        ]
+    );
+}
@@ -0,0 +1,51 @@
+use sa_def::DefKind;
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn symbol_index_lists_definitions_with_selectors() {
+    let text = r#"contract Token {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    error Insufficient(uint256 available);
+
+    uint256 public total;
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        return true;
+    }
+
+    function _burn(uint256 amount) internal {}
+}"#;
+    let path = NormalizedPath::new("/workspace/src/Token.sol");
+    let (analysis, _snapshot) = setup_analysis(vec![(path, text.to_string())], vec![]);
+
+    let symbols = analysis.symbol_index();
+    let symbol = |name: &str| {
+        symbols
+            .iter()
+            .find(|symbol| symbol.name == name)
+            .unwrap_or_else(|| panic!("missing {name}"))
+    };
+
+    assert_eq!(symbol("Token").kind, DefKind::Contract);
+    assert_eq!(symbol("total").container.as_deref(), Some("Token"));
+
+    let transfer = symbol("transfer");
+    assert_eq!(
+        transfer.signature.as_deref(),
+        Some("transfer(address,uint256)")
+    );
+    assert_eq!(transfer.selector.as_deref(), Some("0xa9059cbb"));
+    assert_eq!(
+        symbol("Transfer").selector.as_deref(),
+        Some("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+    );
+    assert_eq!(
+        symbol("Insufficient").signature.as_deref(),
+        Some("Insufficient(uint256)")
+    );
+
+    let burn = symbol("_burn");
+    assert_eq!(burn.signature.as_deref(), Some("_burn(uint256)"));
+    assert_eq!(burn.selector, None);
+}
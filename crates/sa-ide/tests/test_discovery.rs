@@ -0,0 +1,56 @@
+use sa_ide::TestKind;
+use sa_paths::NormalizedPath;
+use sa_test_support::setup_analysis;
+
+#[test]
+fn discover_tests_follows_forge_naming_rules() {
+    let text = r#"abstract contract BaseTest {
+    function test_inherited() public {}
+}
+
+contract CounterTest is BaseTest {
+    function setUp() public {}
+    function test_Increment() public {}
+    function testFuzz_Set(uint256 x) public {}
+    function test_WithInput(uint256 x) external {}
+    function invariant_total() public view {}
+    function test_helper() internal {}
+}
+"#;
+    let path = NormalizedPath::new("/workspace/test/Counter.t.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    let contracts = analysis.discover_tests(file_id);
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].name, "CounterTest");
+    let tests = contracts[0]
+        .tests
+        .iter()
+        .map(|test| (test.name.as_str(), test.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tests,
+        vec![
+            ("test_Increment", TestKind::Test),
+            ("testFuzz_Set", TestKind::Fuzz),
+            ("test_WithInput", TestKind::Fuzz),
+            ("invariant_total", TestKind::Invariant),
+        ]
+    );
+    let range = contracts[0].tests[0].range;
+    assert_eq!(
+        &text[usize::from(range.start())..usize::from(range.end())],
+        "test_Increment"
+    );
+}
+
+#[test]
+fn discover_tests_ignores_non_test_files() {
+    let text = "contract CounterTest {\n    function test_Increment() public {}\n}\n";
+    let path = NormalizedPath::new("/workspace/src/Counter.sol");
+    let (analysis, snapshot) = setup_analysis(vec![(path.clone(), text.to_string())], vec![]);
+    let file_id = snapshot.file_id(&path).expect("file id");
+
+    assert!(analysis.discover_tests(file_id).is_empty());
+}
@@ -1,7 +1,14 @@
-use std::collections::HashMap;
-use std::hash::Hash;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::ops::Deref;
+use std::sync::{Arc, LazyLock, Mutex};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct InternId(u32);
 
 impl InternId {
@@ -57,9 +64,183 @@ where
     }
 }
 
+/// Serialized as its keys in id order; the lookup map is rebuilt when deserializing, so ids
+/// survive the round trip.
+impl<K: Serialize> Serialize for Interner<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.keys.serialize(serializer)
+    }
+}
+
+impl<'de, K> Deserialize<'de> for Interner<K>
+where
+    K: Deserialize<'de> + Eq + Hash + Clone,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let keys = Vec::<K>::deserialize(deserializer)?;
+        let map = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (key.clone(), InternId::from_raw(idx as u32)))
+            .collect();
+        Ok(Self { map, keys })
+    }
+}
+
+/// An identifier shared by every holder of the same text, so names repeated across entries,
+/// keys and files are stored once. Interned names are dropped with their last holder.
+#[derive(Clone, PartialOrd, Ord)]
+pub struct Name(Arc<str>);
+
+const NAME_SHARDS: usize = 32;
+
+struct NameTable {
+    hasher: RandomState,
+    shards: [Mutex<HashSet<Arc<str>>>; NAME_SHARDS],
+}
+
+impl NameTable {
+    fn shard(&self, text: &str) -> &Mutex<HashSet<Arc<str>>> {
+        &self.shards[self.hasher.hash_one(text) as usize % NAME_SHARDS]
+    }
+}
+
+static NAMES: LazyLock<NameTable> = LazyLock::new(|| NameTable {
+    hasher: RandomState::new(),
+    shards: std::array::from_fn(|_| Mutex::default()),
+});
+
+impl Name {
+    pub fn new(text: &str) -> Self {
+        let mut shard = NAMES
+            .shard(text)
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if let Some(existing) = shard.get(text) {
+            return Self(existing.clone());
+        }
+        if shard.len() == shard.capacity() {
+            // Before growing, drop texts only the table still holds; see `Drop for Name`.
+            shard.retain(|text| Arc::strong_count(text) > 1);
+        }
+        let text = Arc::<str>::from(text);
+        shard.insert(text.clone());
+        Self(text)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for Name {
+    fn drop(&mut self) {
+        // The table holds the other reference. Holders that drop concurrently can each see a
+        // count above two and leave the text to the table alone; `Name::new` sweeps those
+        // entries before a shard grows.
+        if Arc::strong_count(&self.0) != 2 {
+            return;
+        }
+        let mut shard = NAMES
+            .shard(&self.0)
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        // Names are only handed out under the shard lock, so the count cannot grow here.
+        if Arc::strong_count(&self.0) == 2 {
+            shard.remove(&*self.0);
+        }
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for Name {}
+
+/// Hashes as the text, so maps keyed by names can be looked up with a `&str`.
+impl Hash for Name {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for Name {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Name {
+    fn from(text: String) -> Self {
+        Self::new(&text)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Self::new(&text))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Interner;
+    use std::sync::Arc;
+
+    use super::{Interner, NAMES, Name};
 
     #[test]
     fn interning_returns_stable_ids() {
@@ -69,4 +250,59 @@ mod tests {
         assert_eq!(first, second);
         assert_eq!(interner.lookup(first), Some(&"foo"));
     }
+
+    #[test]
+    fn serialization_preserves_ids() {
+        let mut interner = Interner::default();
+        let foo = interner.intern("foo".to_string());
+        let bar = interner.intern("bar".to_string());
+
+        let json = serde_json::to_string(&interner).expect("serialize");
+        let mut restored: Interner<String> = serde_json::from_str(&json).expect("deserialize");
+
+        assert_eq!(restored.lookup(bar), Some(&"bar".to_string()));
+        assert_eq!(restored.intern("foo".to_string()), foo);
+        assert_eq!(restored.len(), 2);
+    }
+
+    #[test]
+    fn names_share_their_text_until_the_last_one_drops() {
+        let first = Name::new("interned_name_test");
+        let second = Name::new("interned_name_test");
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+        assert_eq!(first, "interned_name_test");
+
+        drop(first);
+        drop(second);
+        let third = Name::new("interned_name_test");
+        assert_eq!(Arc::strong_count(&third.0), 2);
+
+        let json = serde_json::to_string(&third).expect("serialize");
+        let restored: Name = serde_json::from_str(&json).expect("deserialize");
+        assert!(Arc::ptr_eq(&restored.0, &third.0));
+    }
+
+    #[test]
+    fn texts_only_the_table_holds_are_swept_before_a_shard_grows() {
+        const LEAKED: &str = "leaked_name_test";
+        let shard = NAMES.shard(LEAKED);
+        // What two last holders dropping at once leave behind.
+        shard.lock().expect("name shard").insert(Arc::from(LEAKED));
+
+        let mut held = Vec::new();
+        for idx in 0.. {
+            if !shard.lock().expect("name shard").contains(LEAKED) {
+                break;
+            }
+            assert!(idx < 100_000, "leaked text was never swept");
+            let text = format!("sweep_name_test_{idx}");
+            if std::ptr::eq(NAMES.shard(&text), shard) {
+                held.push(Name::new(&text));
+            }
+        }
+        assert!(
+            held.iter()
+                .all(|name| shard.lock().expect("name shard").contains(name.as_str()))
+        );
+    }
 }
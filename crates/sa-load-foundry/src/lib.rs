@@ -2,10 +2,28 @@ use std::path::{Path, PathBuf};
 use std::{env, mem};
 
 use anyhow::Context;
+use foundry_compilers::artifacts::remappings::{RelativeRemapping, Remapping as FoundryRemapping};
 use foundry_config::{Config, SolcReq};
 use sa_config::ResolvedFoundryConfig;
 use sa_paths::NormalizedPath;
-use sa_project_model::{FoundryProfile, FoundryWorkspace, Remapping};
+use sa_project_model::{
+    FoundryProfile, FoundryWorkspace, HardhatProject, PythonProject, Remapping,
+    contains_hardhat_config, contains_python_project_config, infer_remappings,
+};
+
+/// Loads the project rooted at `root`, preferring foundry.toml over a Hardhat config, and a
+/// Hardhat config over an Ape or Brownie one.
+pub fn load_project(root: &Path, profile: Option<&str>) -> anyhow::Result<ResolvedFoundryConfig> {
+    if !root.join(Config::FILE_NAME).is_file() {
+        if contains_hardhat_config(root) {
+            return load_hardhat(root);
+        }
+        if contains_python_project_config(root) {
+            return load_python_project(root);
+        }
+    }
+    load_foundry(root, profile)
+}
 
 pub fn load_foundry(root: &Path, profile: Option<&str>) -> anyhow::Result<ResolvedFoundryConfig> {
     let profile_name = profile
@@ -21,8 +39,20 @@ pub fn load_foundry(root: &Path, profile: Option<&str>) -> anyhow::Result<Resolv
     let test = normalize_path(&root_path, &active_config.test);
     let script = normalize_path(&root_path, &active_config.script);
     let lib = normalize_lib_path(&root_path, &active_config.libs);
+    let libs = active_config
+        .libs
+        .iter()
+        .map(|lib| normalize_path(&root_path, lib))
+        .collect::<Vec<_>>();
+    let include_paths = active_config
+        .include_paths
+        .iter()
+        .map(|path| normalize_path(&root_path, path))
+        .collect::<Vec<_>>();
 
-    let workspace = FoundryWorkspace::from_paths(root_normalized, src, lib, test, script);
+    let workspace = FoundryWorkspace::from_paths(root_normalized, src, lib, test, script)
+        .with_libs(libs)
+        .with_include_paths(include_paths);
 
     let formatter = active_config.fmt.clone();
 
@@ -32,6 +62,58 @@ pub fn load_foundry(root: &Path, profile: Option<&str>) -> anyhow::Result<Resolv
         .with_foundry_config(active_config))
 }
 
+pub fn load_hardhat(root: &Path) -> anyhow::Result<ResolvedFoundryConfig> {
+    let project = HardhatProject::load(root)?;
+    let workspace = project.workspace().clone();
+
+    let mut config = Config::with_root(root).sanitized();
+    config.src = PathBuf::from(workspace.src().as_str());
+    config.test = PathBuf::from(workspace.test().as_str());
+    config.script = PathBuf::from(workspace.script().as_str());
+    config.libs = vec![PathBuf::from(workspace.lib().as_str())];
+
+    let mut profile = FoundryProfile::new("default");
+    if let Some(version) = project.solc_version() {
+        config.solc = Some(SolcReq::from(version));
+        profile = profile.with_solc_version(version);
+    }
+
+    Ok(ResolvedFoundryConfig::new(workspace, profile).with_foundry_config(config))
+}
+
+pub fn load_python_project(root: &Path) -> anyhow::Result<ResolvedFoundryConfig> {
+    let project = PythonProject::load(root)?;
+    let workspace = project.workspace().clone();
+
+    let mut config = Config::with_root(root).sanitized();
+    config.src = PathBuf::from(workspace.src().as_str());
+    config.test = PathBuf::from(workspace.test().as_str());
+    config.script = PathBuf::from(workspace.script().as_str());
+    config.libs = vec![PathBuf::from(workspace.lib().as_str())];
+    config.remappings = project
+        .remappings()
+        .iter()
+        .map(|remapping| {
+            RelativeRemapping::new(
+                FoundryRemapping {
+                    context: remapping.context().map(str::to_string),
+                    name: remapping.from().to_string(),
+                    path: remapping.to().to_string(),
+                },
+                root,
+            )
+        })
+        .collect();
+
+    let mut profile = FoundryProfile::new("default").with_remappings(project.remappings().to_vec());
+    if let Some(version) = project.solc_version() {
+        config.solc = Some(SolcReq::from(version));
+        profile = profile.with_solc_version(version);
+    }
+
+    Ok(ResolvedFoundryConfig::new(workspace, profile).with_foundry_config(config))
+}
+
 fn load_config_with_profile(root: &Path, profile: Option<&str>) -> anyhow::Result<Config> {
     let _guard = profile.map(ProfileEnvGuard::set);
     let config = Config::load_with_root(root).with_context(|| match profile {
@@ -55,11 +137,14 @@ fn normalize_lib_path(root: &Path, libs: &[PathBuf]) -> NormalizedPath {
 }
 
 fn profile_from_config(profile: &str, config: &Config) -> FoundryProfile {
-    let remappings: Vec<Remapping> = config
+    let mut remappings: Vec<Remapping> = config
         .remappings
         .iter()
         .map(Remapping::from_relative)
         .collect();
+    if remappings.is_empty() {
+        remappings = infer_remappings(&config.root, &config.libs);
+    }
     let mut profile = FoundryProfile::new(profile);
 
     if let Some(solc) = &config.solc {
@@ -114,7 +199,7 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
-    use super::load_foundry;
+    use super::{load_foundry, load_project};
 
     #[test]
     fn loads_foundry_config_and_profiles() {
@@ -185,4 +270,136 @@ solc_version = "0.8.17"
 
         assert_eq!(active.solc_version(), Some("0.8.17"));
     }
+
+    #[test]
+    fn foundry_profile_env_selects_profile_layout() {
+        let _lock = env_lock();
+        let _solc_guard = EnvGuard::set("FOUNDRY_SOLC_VERSION", None);
+        let _profile_guard = EnvGuard::set("FOUNDRY_PROFILE", Some("ci"));
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+
+        setup_foundry_root(root);
+        fs::create_dir_all(root.join("contracts")).expect("contracts dir");
+        fs::create_dir_all(root.join("deps")).expect("deps dir");
+
+        let foundry_toml = r#"
+[profile.default]
+solc = "0.8.20"
+
+[profile.ci]
+src = "contracts"
+libs = ["deps"]
+"#;
+        fs::write(root.join("foundry.toml"), foundry_toml).expect("write foundry.toml");
+
+        let resolved = load_foundry(root, None).expect("load config");
+        assert_eq!(resolved.active_profile().name(), "ci");
+        assert!(resolved.workspace().src().as_str().ends_with("/contracts"));
+        assert!(resolved.workspace().lib().as_str().ends_with("/deps"));
+        assert_eq!(resolved.active_profile().solc_version(), Some("0.8.20"));
+
+        let resolved = load_foundry(root, Some("default")).expect("load default profile");
+        assert_eq!(resolved.active_profile().name(), "default");
+        assert!(resolved.workspace().src().as_str().ends_with("/src"));
+    }
+
+    #[test]
+    fn loads_every_lib_and_include_path() {
+        let _lock = env_lock();
+        let _guard = EnvGuard::set("FOUNDRY_SOLC_VERSION", None);
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+
+        setup_foundry_root(root);
+        for dir in ["node_modules", "vendor"] {
+            fs::create_dir_all(root.join(dir)).expect("create dir");
+        }
+        let foundry_toml = r#"
+[profile.default]
+libs = ["lib", "node_modules"]
+include_paths = ["vendor"]
+"#;
+        fs::write(root.join("foundry.toml"), foundry_toml).expect("write foundry.toml");
+
+        let resolved = load_foundry(root, None).expect("load config");
+        let workspace = resolved.workspace();
+        let libs = workspace
+            .libs()
+            .map(|lib| lib.as_str().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(libs.len(), 2);
+        assert!(libs[0].ends_with("/lib"));
+        assert!(libs[1].ends_with("/node_modules"));
+        assert_eq!(workspace.include_paths().len(), 1);
+        assert!(workspace.include_paths()[0].as_str().ends_with("/vendor"));
+    }
+
+    #[test]
+    fn load_project_falls_back_to_hardhat_config() {
+        let _lock = env_lock();
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        fs::write(
+            root.join("hardhat.config.ts"),
+            r#"export default { solidity: { compilers: [{ version: "0.8.21" }] } };"#,
+        )
+        .expect("write hardhat config");
+
+        let resolved = load_project(root, None).expect("load hardhat project");
+
+        assert!(resolved.workspace().src().as_str().ends_with("/contracts"));
+        assert!(
+            resolved
+                .workspace()
+                .lib()
+                .as_str()
+                .ends_with("/node_modules")
+        );
+        assert_eq!(resolved.active_profile().solc_version(), Some("0.8.21"));
+    }
+
+    #[test]
+    fn load_project_detects_brownie_config() {
+        let _lock = env_lock();
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        fs::write(
+            root.join("brownie-config.yaml"),
+            "compiler:\n  solc:\n    version: 0.8.17\n",
+        )
+        .expect("write brownie config");
+
+        let resolved = load_project(root, None).expect("load brownie project");
+
+        assert!(resolved.workspace().src().as_str().ends_with("/contracts"));
+        assert!(resolved.workspace().test().as_str().ends_with("/tests"));
+        assert_eq!(resolved.active_profile().solc_version(), Some("0.8.17"));
+    }
+
+    #[test]
+    fn load_project_prefers_foundry_toml() {
+        let _lock = env_lock();
+        let _guard = EnvGuard::set("FOUNDRY_SOLC_VERSION", None);
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+
+        setup_foundry_root(root);
+        fs::write(
+            root.join("foundry.toml"),
+            "[profile.default]\nsolc = \"0.8.20\"\n",
+        )
+        .expect("write foundry.toml");
+        fs::write(
+            root.join("hardhat.config.js"),
+            r#"module.exports = { solidity: "0.7.6" };"#,
+        )
+        .expect("write hardhat config");
+
+        let resolved = load_project(root, None).expect("load config");
+
+        assert!(resolved.workspace().src().as_str().ends_with("/src"));
+        assert_eq!(resolved.active_profile().solc_version(), Some("0.8.20"));
+    }
 }
@@ -1,51 +1,45 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::fmt;
-
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+/// A path with `/` separators and without `.` or redundant `..` components.
+///
+/// Windows paths keep their prefix: drive letters are upper-cased, verbatim (`\\?\`) paths to a
+/// drive or share lose the verbatim marker, and UNC shares become `//server/share`. On Windows the
+/// rest of the path is lower-cased, as the file system ignores case.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct NormalizedPath {
     inner: String,
 }
 
+/// The part of a path `..` cannot leave.
+enum Prefix {
+    None,
+    Root,
+    /// `C:`, followed by the root of the drive when `absolute`.
+    Drive {
+        letter: char,
+        absolute: bool,
+    },
+    /// `//server/share`, or `//./device` and verbatim paths that name neither a drive nor a share.
+    Share(String),
+}
+
 impl NormalizedPath {
     pub fn new(path: impl AsRef<str>) -> Self {
-        let mut path = path.as_ref().replace('\\', "/");
-        let mut extended_prefix = "";
-
-        if let Some(rest) = path.strip_prefix("//?/") {
-            extended_prefix = "//?/";
-            path = rest.to_string();
-        } else if let Some(rest) = path.strip_prefix("//./") {
-            extended_prefix = "//./";
-            path = rest.to_string();
-        }
-
-        let mut prefix = String::new();
-        let mut absolute = false;
-        let mut add_separator_after_prefix = false;
-
-        if path.starts_with("//") {
-            prefix.push_str("//");
-            path = path.trim_start_matches('/').to_string();
-            absolute = true;
-        } else if path.len() >= 2 && path.as_bytes()[1] == b':' {
-            prefix.push_str(&path[..2]);
-            path = path[2..].to_string();
-            if path.starts_with('/') {
-                absolute = true;
-                path = path.trim_start_matches('/').to_string();
-                add_separator_after_prefix = true;
-            }
-        } else if path.starts_with('/') {
-            absolute = true;
-            path = path.trim_start_matches('/').to_string();
-        }
-
-        if !extended_prefix.is_empty() {
-            absolute = true;
-            prefix = format!("{extended_prefix}{prefix}");
-            if prefix == extended_prefix {
-                add_separator_after_prefix = false;
-            }
-        }
+        let path = path.as_ref().replace('\\', "/");
+        let (prefix, path) = split_prefix(&path);
+        let absolute = !matches!(
+            prefix,
+            Prefix::None
+                | Prefix::Drive {
+                    absolute: false,
+                    ..
+                }
+        );
 
         let mut components: Vec<&str> = Vec::new();
         for part in path.split('/') {
@@ -66,31 +60,24 @@ impl NormalizedPath {
             components.push(part);
         }
 
-        let mut normalized = String::new();
-        if !prefix.is_empty() {
-            normalized.push_str(&prefix);
-            if absolute && add_separator_after_prefix {
-                normalized.push('/');
-            }
-        } else if absolute {
-            normalized.push('/');
-        }
-
+        let mut normalized = match &prefix {
+            Prefix::None | Prefix::Drive { .. } => String::new(),
+            Prefix::Root => "/".to_string(),
+            Prefix::Share(share) if components.is_empty() => share.clone(),
+            Prefix::Share(share) => format!("{share}/"),
+        };
         normalized.push_str(&components.join("/"));
-
-        let is_root_with_prefix = !prefix.is_empty() && absolute && components.is_empty();
-        if normalized.len() > 1 && normalized.ends_with('/') && !is_root_with_prefix {
-            normalized.pop();
+        if cfg!(windows) {
+            normalized.make_ascii_lowercase();
+        }
+        if let Prefix::Drive { letter, absolute } = prefix {
+            let root = if absolute { "/" } else { "" };
+            normalized.insert_str(0, &format!("{letter}:{root}"));
         }
-
         if normalized.is_empty() {
             normalized.push('.');
         }
 
-        if cfg!(windows) {
-            normalized.make_ascii_lowercase();
-        }
-
         Self { inner: normalized }
     }
 
@@ -99,8 +86,8 @@ impl NormalizedPath {
     }
 
     fn starts_with(&self, root: &NormalizedPath) -> bool {
-        if root.inner == "/" {
-            return self.inner.starts_with('/');
+        if root.inner.ends_with('/') {
+            return self.inner.starts_with(&root.inner);
         }
         if self.inner == root.inner {
             return true;
@@ -111,8 +98,8 @@ impl NormalizedPath {
     }
 
     fn strip_prefix<'a>(&'a self, root: &NormalizedPath) -> Option<&'a str> {
-        if root.inner == "/" {
-            return self.inner.strip_prefix('/');
+        if root.inner.ends_with('/') {
+            return self.inner.strip_prefix(&root.inner);
         }
         if self.inner == root.inner {
             return Some("");
@@ -123,6 +110,81 @@ impl NormalizedPath {
     }
 }
 
+fn split_prefix(path: &str) -> (Prefix, &str) {
+    if let Some(rest) = path
+        .strip_prefix("//?/")
+        .or_else(|| path.strip_prefix("//./"))
+    {
+        if let Some(share) = rest
+            .get(..4)
+            .filter(|unc| unc.eq_ignore_ascii_case("UNC/"))
+            .map(|_| &rest[4..])
+        {
+            return split_share(share);
+        }
+        if let Some(split) = split_drive(rest) {
+            return split;
+        }
+        let (device, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        return (Prefix::Share(format!("{}{device}", &path[..4])), rest);
+    }
+    if let Some(share) = path.strip_prefix("//") {
+        return split_share(share.trim_start_matches('/'));
+    }
+    if let Some(split) = split_drive(path) {
+        return split;
+    }
+    match path.strip_prefix('/') {
+        Some(rest) => (Prefix::Root, rest),
+        None => (Prefix::None, path),
+    }
+}
+
+fn split_share(path: &str) -> (Prefix, &str) {
+    let mut parts = path.splitn(3, '/');
+    let server = parts.next().unwrap_or_default();
+    let share = parts.next().unwrap_or_default();
+    let rest = parts.next().unwrap_or_default();
+    let prefix = if share.is_empty() {
+        format!("//{server}")
+    } else {
+        format!("//{server}/{share}")
+    };
+    (Prefix::Share(prefix), rest)
+}
+
+fn split_drive(path: &str) -> Option<(Prefix, &str)> {
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || bytes[1] != b':' || !bytes[0].is_ascii_alphabetic() {
+        return None;
+    }
+    let letter = char::from(bytes[0].to_ascii_uppercase());
+    let rest = &path[2..];
+    Some(match rest.strip_prefix('/') {
+        Some(rest) => (
+            Prefix::Drive {
+                letter,
+                absolute: true,
+            },
+            rest,
+        ),
+        None => (
+            Prefix::Drive {
+                letter,
+                absolute: false,
+            },
+            rest,
+        ),
+    })
+}
+
+/// Hashes and compares as its text, so maps keyed by paths can be looked up with a `&str`.
+impl Borrow<str> for NormalizedPath {
+    fn borrow(&self) -> &str {
+        &self.inner
+    }
+}
+
 impl fmt::Display for NormalizedPath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.inner)
@@ -157,11 +219,80 @@ impl fmt::Display for WorkspacePath {
     }
 }
 
+/// Resolves the symlinks in paths, so a file reached through a symlinked directory such as a
+/// dependency under `lib/` and the file at its real path get the same path.
+///
+/// The real path of each directory is cached, as dependencies hold many files and their paths
+/// are resolved on every import. Paths that do not exist are returned unchanged and not cached.
+#[derive(Debug, Default)]
+pub struct RealPathCache {
+    dirs: Mutex<HashMap<NormalizedPath, NormalizedPath>>,
+}
+
+static SHARED: LazyLock<RealPathCache> = LazyLock::new(RealPathCache::default);
+
+impl RealPathCache {
+    /// The cache the server resolves document, index and import paths through.
+    pub fn shared() -> &'static RealPathCache {
+        &SHARED
+    }
+
+    pub fn real_path(&self, path: &NormalizedPath) -> NormalizedPath {
+        let fs_path = Path::new(path.as_str());
+        if fs::symlink_metadata(fs_path).is_ok_and(|metadata| metadata.is_symlink()) {
+            return canonicalize(fs_path).unwrap_or_else(|| path.clone());
+        }
+        let (Some(parent), Some(name)) = (fs_path.parent(), fs_path.file_name()) else {
+            return path.clone();
+        };
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        let parent = NormalizedPath::new(parent.to_string_lossy());
+        let cached = self.lock().get(&parent).cloned();
+        let real_parent = match cached {
+            Some(real_parent) => real_parent,
+            None => {
+                let Some(real_parent) = canonicalize(Path::new(parent.as_str())) else {
+                    return path.clone();
+                };
+                self.lock().insert(parent, real_parent.clone());
+                real_parent
+            }
+        };
+        let separator = if real_parent.inner.ends_with('/') {
+            ""
+        } else {
+            "/"
+        };
+        NormalizedPath::new(format!(
+            "{real_parent}{separator}{}",
+            name.to_string_lossy()
+        ))
+    }
+
+    /// Forgets every resolved directory, for when symlinks may have been replaced.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<NormalizedPath, NormalizedPath>> {
+        self.dirs.lock().expect("real path cache lock")
+    }
+}
+
+fn canonicalize(path: &Path) -> Option<NormalizedPath> {
+    let real = fs::canonicalize(path).ok()?;
+    Some(NormalizedPath::new(real.to_string_lossy()))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
-    use super::{NormalizedPath, WorkspacePath};
+    use super::{NormalizedPath, RealPathCache, WorkspacePath};
 
     #[test]
     fn normalizes_separators_and_components() {
@@ -172,33 +303,47 @@ mod tests {
         assert_eq!(mixed.as_str(), "/workspace/src/lib.sol");
 
         let windows_path = NormalizedPath::new("C:\\workspace\\src\\lib.sol");
-        let expected = if cfg!(windows) {
-            "c:/workspace/src/lib.sol"
-        } else {
-            "C:/workspace/src/lib.sol"
-        };
-        assert_eq!(windows_path.as_str(), expected);
+        assert_eq!(windows_path.as_str(), "C:/workspace/src/lib.sol");
     }
 
     #[test]
     fn preserves_windows_roots_and_unc_paths() {
         let root = NormalizedPath::new("C:\\");
-        let expected_root = if cfg!(windows) { "c:/" } else { "C:/" };
-        assert_eq!(root.as_str(), expected_root);
+        assert_eq!(root.as_str(), "C:/");
 
         let unc = NormalizedPath::new(r"\\server\share\dir");
         assert_eq!(unc.as_str(), "//server/share/dir");
+        let above_share = NormalizedPath::new(r"\\server\share\..\..\dir");
+        assert_eq!(above_share.as_str(), "//server/share/dir");
 
         let extended = NormalizedPath::new(r"\\?\C:\Repo\src");
         let expected_extended = if cfg!(windows) {
-            "//?/c:/repo/src"
+            "C:/repo/src"
         } else {
-            "//?/C:/Repo/src"
+            "C:/Repo/src"
         };
         assert_eq!(extended.as_str(), expected_extended);
 
         let extended_unc = NormalizedPath::new(r"\\?\UNC\server\share\dir");
-        assert_eq!(extended_unc.as_str(), "//?/UNC/server/share/dir");
+        assert_eq!(extended_unc.as_str(), "//server/share/dir");
+
+        let device = NormalizedPath::new(r"\\.\pipe\solc");
+        assert_eq!(device.as_str(), "//./pipe/solc");
+    }
+
+    #[test]
+    fn drive_letters_compare_equal_in_any_case() {
+        let lower = NormalizedPath::new("c:\\workspace\\src");
+        let upper = NormalizedPath::new("C:/workspace/src");
+        let verbatim = NormalizedPath::new(r"\\?\c:\workspace\src");
+        assert_eq!(lower, upper);
+        assert_eq!(verbatim, upper);
+
+        let root = NormalizedPath::new("c:/");
+        let relative = WorkspacePath::new(&root, &upper).expect("path below the drive root");
+        assert_eq!(relative.as_str(), "workspace/src");
+
+        assert_eq!(NormalizedPath::new("c:lib.sol").as_str(), "C:lib.sol");
     }
 
     #[test]
@@ -226,4 +371,34 @@ mod tests {
         let outside = NormalizedPath::new("/other/src/lib.sol");
         assert!(WorkspacePath::new(&root, &outside).is_none());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_below_symlinked_directories_resolve_to_their_real_path() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let real = root.join("node_modules/forge-std/src");
+        std::fs::create_dir_all(&real).expect("create dependency");
+        std::fs::write(real.join("Test.sol"), "contract Test {}").expect("write file");
+        std::fs::create_dir(root.join("lib")).expect("create lib");
+        std::os::unix::fs::symlink(
+            root.join("node_modules/forge-std"),
+            root.join("lib/forge-std"),
+        )
+        .expect("symlink");
+
+        let cache = RealPathCache::default();
+        let path = |path: &std::path::Path| NormalizedPath::new(path.to_string_lossy());
+        let real_file = path(&real.join("Test.sol"));
+        let linked = path(&root.join("lib/forge-std/src/Test.sol"));
+        assert_eq!(cache.real_path(&linked), real_file);
+        assert_eq!(cache.real_path(&real_file), real_file);
+        assert_eq!(
+            cache.real_path(&path(&root.join("lib/forge-std"))),
+            path(&root.join("node_modules/forge-std"))
+        );
+
+        let missing = path(&root.join("lib/missing/Missing.sol"));
+        assert_eq!(cache.real_path(&missing), missing);
+    }
 }
@@ -0,0 +1,255 @@
+//! Solidity interfaces generated from contract ABIs, standing in for dependencies whose source
+//! is not available.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+/// The ABI in `text`: either a bare ABI array or an object with an `abi` field, as in forge
+/// and Hardhat artifacts.
+pub fn parse_abi(text: &str) -> Option<Value> {
+    let value = serde_json::from_str::<Value>(text).ok()?;
+    let abi = match value {
+        Value::Object(mut object) => object.remove("abi")?,
+        abi => abi,
+    };
+    abi.is_array().then_some(abi)
+}
+
+/// Renders `abi` as a Solidity `interface` named `name`. Structs are declared inside the
+/// interface, named after their `internalType` where the ABI records one; contract and enum
+/// types are reduced to their ABI types.
+pub fn abi_interface(name: &str, abi: &Value) -> String {
+    let mut interface = Interface::default();
+    for item in abi.as_array().into_iter().flatten() {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or_default();
+        let inputs = item.get("inputs");
+        match item
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("function")
+        {
+            "function" => {
+                let inputs = interface.params(inputs, Some("calldata"), false);
+                let outputs = interface.params(item.get("outputs"), Some("memory"), false);
+                let returns = if outputs.is_empty() {
+                    String::new()
+                } else {
+                    format!(" returns ({outputs})")
+                };
+                interface.members.push(format!(
+                    "function {name}({inputs}) external{}{returns};",
+                    mutability(item)
+                ));
+            }
+            "event" => {
+                let params = interface.params(inputs, None, true);
+                let anonymous = item.get("anonymous").and_then(Value::as_bool) == Some(true);
+                let anonymous = if anonymous { " anonymous" } else { "" };
+                interface
+                    .members
+                    .push(format!("event {name}({params}){anonymous};"));
+            }
+            "error" => {
+                let params = interface.params(inputs, None, false);
+                interface.has_errors = true;
+                interface.members.push(format!("error {name}({params});"));
+            }
+            "fallback" => interface
+                .members
+                .push(format!("fallback() external{};", mutability(item))),
+            "receive" => interface
+                .members
+                .push("receive() external payable;".to_string()),
+            _ => {}
+        }
+    }
+
+    // Custom errors need 0.8.4; everything else here compiles with any 0.8 release.
+    let pragma = if interface.has_errors {
+        ">=0.8.4"
+    } else {
+        ">=0.8.0"
+    };
+    let mut out = format!(
+        "// SPDX-License-Identifier: UNLICENSED\npragma solidity {pragma};\n\n/// Generated from the ABI of `{name}`; its Solidity source is not available.\ninterface {name} {{\n"
+    );
+    let sections = [&interface.structs, &interface.members];
+    for (index, section) in sections.into_iter().filter(|s| !s.is_empty()).enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        for item in section {
+            let _ = writeln!(out, "    {item}");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[derive(Default)]
+struct Interface {
+    /// Struct declarations in the order they were first used, one line each.
+    structs: Vec<String>,
+    struct_names: Vec<String>,
+    members: Vec<String>,
+    has_errors: bool,
+}
+
+impl Interface {
+    /// Renders a parameter list. `location` is added to reference types; `indexed` params are
+    /// marked as such (events only).
+    fn params(&mut self, params: Option<&Value>, location: Option<&str>, indexed: bool) -> String {
+        params
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|param| {
+                let ty = self.param_type(param);
+                let mut rendered = ty.clone();
+                if let Some(location) = location
+                    && is_reference_type(param, &ty)
+                {
+                    rendered.push(' ');
+                    rendered.push_str(location);
+                }
+                if indexed && param.get("indexed").and_then(Value::as_bool) == Some(true) {
+                    rendered.push_str(" indexed");
+                }
+                if let Some(name) = param
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .filter(|name| is_identifier(name))
+                {
+                    rendered.push(' ');
+                    rendered.push_str(name);
+                }
+                rendered
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn param_type(&mut self, param: &Value) -> String {
+        let ty = param.get("type").and_then(Value::as_str).unwrap_or("bytes");
+        if ty == "function" {
+            return "function() external".to_string();
+        }
+        let Some(dimensions) = ty.strip_prefix("tuple") else {
+            return ty.to_string();
+        };
+        let name = param
+            .get("internalType")
+            .and_then(Value::as_str)
+            .and_then(|internal| internal.strip_prefix("struct "))
+            .map(|internal| {
+                let internal = internal.split('[').next().unwrap_or(internal);
+                internal.rsplit('.').next().unwrap_or(internal).to_string()
+            })
+            .filter(|name| is_identifier(name))
+            .unwrap_or_else(|| format!("Tuple{}", self.struct_names.len()));
+        if !self.struct_names.contains(&name) {
+            self.struct_names.push(name.clone());
+            let fields = param
+                .get("components")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, component)| {
+                    let ty = self.param_type(component);
+                    let field = component
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .filter(|name| is_identifier(name))
+                        .map_or_else(|| format!("field{index}"), str::to_string);
+                    format!("{ty} {field};")
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.structs.push(format!("struct {name} {{ {fields} }}"));
+        }
+        format!("{name}{dimensions}")
+    }
+}
+
+fn mutability(item: &Value) -> &'static str {
+    match item.get("stateMutability").and_then(Value::as_str) {
+        Some("view") => " view",
+        Some("pure") => " pure",
+        Some("payable") => " payable",
+        Some(_) => "",
+        // ABIs from before solc 0.4.16 only have the `constant` and `payable` flags.
+        None if item.get("constant").and_then(Value::as_bool) == Some(true) => " view",
+        None if item.get("payable").and_then(Value::as_bool) == Some(true) => " payable",
+        None => "",
+    }
+}
+
+fn is_reference_type(param: &Value, ty: &str) -> bool {
+    ty.ends_with(']')
+        || matches!(ty, "string" | "bytes")
+        || param
+            .get("type")
+            .and_then(Value::as_str)
+            .is_some_and(|ty| ty.starts_with("tuple"))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{abi_interface, parse_abi};
+
+    #[test]
+    fn renders_functions_events_errors_and_structs() {
+        let abi = json!([
+            {"type": "function", "name": "balanceOf", "stateMutability": "view",
+             "inputs": [{"name": "owner", "type": "address", "internalType": "address"}],
+             "outputs": [{"name": "", "type": "uint256", "internalType": "uint256"}]},
+            {"type": "function", "name": "fill", "stateMutability": "payable",
+             "inputs": [{"name": "order", "type": "tuple", "internalType": "struct Exchange.Order",
+                         "components": [{"name": "maker", "type": "address"}, {"name": "amounts", "type": "uint256[]"}]},
+                        {"name": "note", "type": "string"}],
+             "outputs": []},
+            {"type": "event", "name": "Filled", "anonymous": false,
+             "inputs": [{"name": "maker", "type": "address", "indexed": true}, {"name": "amount", "type": "uint256", "indexed": false}]},
+            {"type": "error", "name": "Expired", "inputs": [{"name": "deadline", "type": "uint256"}]},
+            {"type": "constructor", "inputs": []},
+            {"type": "receive", "stateMutability": "payable"}
+        ]);
+        assert_eq!(
+            abi_interface("Exchange", &abi),
+            r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.4;
+
+/// Generated from the ABI of `Exchange`; its Solidity source is not available.
+interface Exchange {
+    struct Order { address maker; uint256[] amounts; }
+
+    function balanceOf(address owner) external view returns (uint256);
+    function fill(Order calldata order, string calldata note) external payable;
+    event Filled(address indexed maker, uint256 amount);
+    error Expired(uint256 deadline);
+    receive() external payable;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn reads_bare_and_wrapped_abis() {
+        assert!(parse_abi("[]").is_some());
+        assert!(parse_abi(r#"{"abi": [], "bytecode": "0x"}"#).is_some());
+        assert_eq!(parse_abi(r#"{"bytecode": "0x"}"#), None);
+        assert_eq!(parse_abi("not json"), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Contract artifacts written by `forge build` to the project's `out/` directory.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use sa_paths::{NormalizedPath, WorkspacePath};
+use serde_json::Value;
+
+/// What an artifact records about one compiled contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractArtifact {
+    name: String,
+    source: Option<String>,
+    abi: Value,
+    method_identifiers: BTreeMap<String, String>,
+    deployed_bytecode_size: Option<usize>,
+    compiler_version: Option<String>,
+}
+
+impl ContractArtifact {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The source file the contract was compiled from, as given to the compiler (usually
+    /// relative to the project root, e.g. `src/Counter.sol`).
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The contract's ABI as a JSON array.
+    pub fn abi(&self) -> &Value {
+        &self.abi
+    }
+
+    /// Canonical function signatures mapped to their selectors, e.g.
+    /// `transfer(address,uint256)` to `a9059cbb`.
+    pub fn method_identifiers(&self) -> &BTreeMap<String, String> {
+        &self.method_identifiers
+    }
+
+    /// The size of the runtime code in bytes; `None` for interfaces and abstract contracts.
+    pub fn deployed_bytecode_size(&self) -> Option<usize> {
+        self.deployed_bytecode_size
+    }
+
+    pub fn compiler_version(&self) -> Option<&str> {
+        self.compiler_version.as_deref()
+    }
+
+    /// The selectors of the functions named `name`, one per overload.
+    pub fn selectors_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.method_identifiers
+            .iter()
+            .filter(move |(signature, _)| {
+                signature
+                    .split_once('(')
+                    .is_some_and(|(function, _)| function == name)
+            })
+            .map(|(signature, selector)| (signature.as_str(), selector.as_str()))
+    }
+}
+
+/// Reads every contract artifact below `out` (`out/<File>.sol/<Contract>.json`), sorted by
+/// source and name. Build info and files that are not artifacts are skipped, so a missing or
+/// half-written `out/` yields whatever could be read.
+pub fn load_artifacts(out: &Path) -> Vec<ContractArtifact> {
+    let Ok(entries) = fs::read_dir(out) else {
+        return Vec::new();
+    };
+    let mut artifacts = Vec::new();
+    for dir in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if !dir.is_dir() || dir.file_name().is_some_and(|name| name == "build-info") {
+            continue;
+        }
+        let Ok(files) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for file in files
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if file.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            // `Counter.json`, or `Counter.0.8.19.json` when several compiler versions built it.
+            let Some(name) = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+            else {
+                continue;
+            };
+            let artifact = fs::read_to_string(&file)
+                .ok()
+                .and_then(|text| parse_artifact(name, &text));
+            artifacts.extend(artifact);
+        }
+    }
+    artifacts.sort_by(|a, b| (&a.source, &a.name).cmp(&(&b.source, &b.name)));
+    artifacts.dedup_by(|a, b| a.source == b.source && a.name == b.name);
+    artifacts
+}
+
+/// The artifact of the contract `name` compiled from `path`, a source file below `root`.
+pub fn find_artifact<'a>(
+    artifacts: &'a [ContractArtifact],
+    root: &NormalizedPath,
+    path: &NormalizedPath,
+    name: &str,
+) -> Option<&'a ContractArtifact> {
+    let source = WorkspacePath::new(root, path)?;
+    artifacts
+        .iter()
+        .find(|artifact| artifact.name == name && artifact.source() == Some(source.as_str()))
+}
+
+/// Parses the artifact of the contract `name`. Returns `None` when `text` has no ABI.
+pub fn parse_artifact(name: &str, text: &str) -> Option<ContractArtifact> {
+    let artifact = serde_json::from_str::<Value>(text).ok()?;
+    let abi = artifact.get("abi").filter(|abi| abi.is_array())?.clone();
+    let method_identifiers = artifact
+        .get("methodIdentifiers")
+        .and_then(Value::as_object)
+        .map(|identifiers| {
+            identifiers
+                .iter()
+                .filter_map(|(signature, selector)| {
+                    Some((signature.clone(), selector.as_str()?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let deployed_bytecode_size = artifact
+        .pointer("/deployedBytecode/object")
+        .and_then(Value::as_str)
+        .map(|code| code.strip_prefix("0x").unwrap_or(code).len() / 2)
+        .filter(|size| *size > 0);
+    // `metadata` is an object in forge artifacts and a JSON string in solc output.
+    let metadata = match artifact.get("metadata") {
+        Some(Value::String(metadata)) => serde_json::from_str(metadata).ok(),
+        metadata => metadata.cloned(),
+    };
+    let source = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.pointer("/settings/compilationTarget"))
+        .and_then(Value::as_object)
+        .and_then(|targets| targets.keys().next().cloned())
+        .or_else(|| {
+            artifact
+                .pointer("/ast/absolutePath")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+    let compiler_version = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.pointer("/compiler/version"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Some(ContractArtifact {
+        name: name.to_string(),
+        source,
+        abi,
+        method_identifiers,
+        deployed_bytecode_size,
+        compiler_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use sa_paths::NormalizedPath;
+
+    use super::{find_artifact, load_artifacts, parse_artifact};
+
+    const COUNTER: &str = r#"{
+        "abi": [{"type": "function", "name": "increment", "inputs": [], "outputs": [], "stateMutability": "nonpayable"}],
+        "deployedBytecode": {"object": "0x6080604052"},
+        "methodIdentifiers": {"increment()": "d09de08a", "setNumber(uint256)": "3fb5c1cb", "set(uint256)": "60fe47b1"},
+        "metadata": {
+            "compiler": {"version": "0.8.24+commit.e11b9ed9"},
+            "settings": {"compilationTarget": {"src/Counter.sol": "Counter"}}
+        }
+    }"#;
+
+    #[test]
+    fn parses_forge_artifacts() {
+        let artifact = parse_artifact("Counter", COUNTER).expect("artifact");
+        assert_eq!(artifact.name(), "Counter");
+        assert_eq!(artifact.source(), Some("src/Counter.sol"));
+        assert_eq!(artifact.deployed_bytecode_size(), Some(5));
+        assert_eq!(artifact.compiler_version(), Some("0.8.24+commit.e11b9ed9"));
+        assert_eq!(
+            artifact.selectors_of("increment").collect::<Vec<_>>(),
+            [("increment()", "d09de08a")]
+        );
+        assert_eq!(artifact.selectors_of("set").count(), 1);
+        assert_eq!(artifact.abi().as_array().map(Vec::len), Some(1));
+
+        let interface = parse_artifact(
+            "IERC20",
+            r#"{"abi": [], "deployedBytecode": {"object": "0x"}}"#,
+        )
+        .expect("interface artifact");
+        assert_eq!(interface.deployed_bytecode_size(), None);
+        assert_eq!(parse_artifact("Broken", r#"{"bytecode": {}}"#), None);
+    }
+
+    #[test]
+    fn loads_contract_artifacts_and_skips_build_info() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let out = dir.path().join("out");
+        fs::create_dir_all(out.join("Counter.sol")).expect("create artifact dir");
+        fs::create_dir_all(out.join("build-info")).expect("create build info dir");
+        fs::write(out.join("Counter.sol/Counter.json"), COUNTER).expect("write artifact");
+        fs::write(out.join("Counter.sol/Counter.0.8.19.json"), COUNTER).expect("write artifact");
+        fs::write(out.join("build-info/abc.json"), r#"{"abi": []}"#).expect("write build info");
+
+        let artifacts = load_artifacts(&out);
+        let names = artifacts
+            .iter()
+            .map(|artifact| artifact.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Counter"]);
+        let root = NormalizedPath::new("/workspace");
+        let found = find_artifact(
+            &artifacts,
+            &root,
+            &NormalizedPath::new("/workspace/src/Counter.sol"),
+            "Counter",
+        );
+        assert_eq!(found, artifacts.first());
+        let other = NormalizedPath::new("/workspace/src/Other.sol");
+        assert_eq!(find_artifact(&artifacts, &root, &other, "Counter"), None);
+        assert!(load_artifacts(&dir.path().join("missing")).is_empty());
+    }
+}
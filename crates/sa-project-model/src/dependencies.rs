@@ -0,0 +1,122 @@
+//! Dependency packages installed under the workspace library roots.
+
+use std::fs;
+use std::path::Path;
+
+use sa_paths::{NormalizedPath, WorkspacePath};
+use serde_json::Value;
+
+use crate::{FoundryWorkspace, Remapping};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependencyPackage {
+    name: String,
+    version: Option<String>,
+    path: NormalizedPath,
+    remapping: Option<Remapping>,
+}
+
+impl DependencyPackage {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The `version` from the package's package.json, if it has one.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn path(&self) -> &NormalizedPath {
+        &self.path
+    }
+
+    /// The remapping whose target points into this package.
+    pub fn remapping(&self) -> Option<&Remapping> {
+        self.remapping.as_ref()
+    }
+}
+
+/// Lists the packages directly below every library root (`@scope/name` for scoped
+/// node_modules packages), sorted by name.
+pub fn discover_dependencies(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+) -> Vec<DependencyPackage> {
+    let mut packages = Vec::new();
+    for lib in workspace.libs() {
+        for (name, dir) in package_dirs(Path::new(lib.as_str())) {
+            let path = NormalizedPath::new(dir.to_string_lossy());
+            let remapping = remapping_into(workspace.root(), &path, remappings).cloned();
+            packages.push(DependencyPackage {
+                name,
+                version: package_version(&dir),
+                path,
+                remapping,
+            });
+        }
+    }
+    packages.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.path.as_str().cmp(b.path.as_str()))
+    });
+    packages
+}
+
+fn package_dirs(lib: &Path) -> Vec<(String, std::path::PathBuf)> {
+    let mut dirs = Vec::new();
+    for (name, path) in child_dirs(lib) {
+        if name.starts_with('@') {
+            for (package, package_path) in child_dirs(&path) {
+                dirs.push((format!("{name}/{package}"), package_path));
+            }
+        } else {
+            dirs.push((name, path));
+        }
+    }
+    dirs
+}
+
+fn child_dirs(dir: &Path) -> Vec<(String, std::path::PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            (!name.starts_with('.')).then_some((name, path))
+        })
+        .collect()
+}
+
+fn package_version(dir: &Path) -> Option<String> {
+    let text = fs::read_to_string(dir.join("package.json")).ok()?;
+    let manifest = serde_json::from_str::<Value>(&text).ok()?;
+    manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Picks the shortest context-free remapping whose target lies inside `package`.
+fn remapping_into<'a>(
+    root: &NormalizedPath,
+    package: &NormalizedPath,
+    remappings: &'a [Remapping],
+) -> Option<&'a Remapping> {
+    remappings
+        .iter()
+        .filter(|remapping| remapping.context().is_none())
+        .filter(|remapping| {
+            let target = if Path::new(remapping.to()).is_absolute() {
+                NormalizedPath::new(remapping.to())
+            } else {
+                NormalizedPath::new(format!("{}/{}", root.as_str(), remapping.to()))
+            };
+            WorkspacePath::new(package, &target).is_some()
+        })
+        .min_by_key(|remapping| remapping.to().len())
+}
@@ -0,0 +1,342 @@
+//! Hardhat project detection.
+//!
+//! `hardhat.config.{js,ts}` is a program, not data, so only a documented subset of literal
+//! values is read from it:
+//!
+//! - `solidity: "0.8.20"`
+//! - `solidity: { version: "0.8.20" }` and `solidity: { compilers: [{ version: "0.8.20" }] }`
+//!   (the first compiler wins)
+//! - `paths: { root: "...", sources: "...", tests: "..." }`
+//!
+//! Anything computed at runtime (imports, variables, environment lookups) falls back to
+//! Hardhat's defaults.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use sa_paths::NormalizedPath;
+
+use crate::FoundryWorkspace;
+
+pub const HARDHAT_CONFIG_FILES: [&str; 2] = ["hardhat.config.ts", "hardhat.config.js"];
+
+const DEFAULT_SOURCES: &str = "contracts";
+const DEFAULT_TESTS: &str = "test";
+const DEFAULT_SCRIPTS: &str = "scripts";
+const NODE_MODULES: &str = "node_modules";
+
+pub fn find_hardhat_config(root: &Path) -> Option<PathBuf> {
+    HARDHAT_CONFIG_FILES
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+}
+
+pub fn contains_hardhat_config(root: &Path) -> bool {
+    find_hardhat_config(root).is_some()
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HardhatConfig {
+    root: Option<String>,
+    sources: Option<String>,
+    tests: Option<String>,
+    solc_version: Option<String>,
+}
+
+impl HardhatConfig {
+    pub fn parse(text: &str) -> Self {
+        let text = strip_comments(text);
+        let mut config = Self::default();
+
+        if let Some(value) = key_value(&text, "solidity") {
+            config.solc_version = match string_literal(value) {
+                Some(version) => Some(version),
+                None => object_literal(value)
+                    .and_then(|solidity| key_value(solidity, "version"))
+                    .and_then(string_literal),
+            };
+        }
+
+        if let Some(paths) = key_value(&text, "paths").and_then(object_literal) {
+            config.root = key_value(paths, "root").and_then(string_literal);
+            config.sources = key_value(paths, "sources").and_then(string_literal);
+            config.tests = key_value(paths, "tests").and_then(string_literal);
+        }
+
+        config
+    }
+
+    pub fn root(&self) -> Option<&str> {
+        self.root.as_deref()
+    }
+
+    pub fn sources(&self) -> Option<&str> {
+        self.sources.as_deref()
+    }
+
+    pub fn tests(&self) -> Option<&str> {
+        self.tests.as_deref()
+    }
+
+    pub fn solc_version(&self) -> Option<&str> {
+        self.solc_version.as_deref()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HardhatProject {
+    workspace: FoundryWorkspace,
+    solc_version: Option<String>,
+}
+
+impl HardhatProject {
+    pub fn load(root: &Path) -> Result<Self> {
+        let config_path = find_hardhat_config(root)
+            .with_context(|| format!("no hardhat config found in {}", root.display()))?;
+        let text = fs::read_to_string(&config_path)
+            .with_context(|| format!("failed to read {}", config_path.display()))?;
+        let config = HardhatConfig::parse(&text);
+        Ok(Self::from_config(
+            NormalizedPath::new(root.to_string_lossy()),
+            &config,
+        ))
+    }
+
+    /// Lays out the project like Hardhat does: sources and tests relative to `paths.root`,
+    /// dependencies under `node_modules` next to the config file.
+    pub fn from_config(config_dir: NormalizedPath, config: &HardhatConfig) -> Self {
+        let root = match config.root() {
+            Some(root) => join(&config_dir, root),
+            None => config_dir.clone(),
+        };
+        let src = join(&root, config.sources().unwrap_or(DEFAULT_SOURCES));
+        let test = join(&root, config.tests().unwrap_or(DEFAULT_TESTS));
+        let script = join(&root, DEFAULT_SCRIPTS);
+        let lib = join(&config_dir, NODE_MODULES);
+
+        Self {
+            workspace: FoundryWorkspace::from_paths(root, src, lib, test, script),
+            solc_version: config.solc_version.clone(),
+        }
+    }
+
+    pub fn workspace(&self) -> &FoundryWorkspace {
+        &self.workspace
+    }
+
+    pub fn solc_version(&self) -> Option<&str> {
+        self.solc_version.as_deref()
+    }
+}
+
+fn join(base: &NormalizedPath, path: &str) -> NormalizedPath {
+    if Path::new(path).is_absolute() {
+        NormalizedPath::new(path)
+    } else {
+        NormalizedPath::new(format!("{}/{path}", base.as_str()))
+    }
+}
+
+/// Removes `//` and `/* */` comments, leaving string literals intact.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut quote = None;
+    while let Some(ch) = chars.next() {
+        if let Some(open) = quote {
+            out.push(ch);
+            if ch == '\\' {
+                out.extend(chars.next());
+            } else if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        match (ch, chars.peek()) {
+            ('"' | '\'' | '`', _) => {
+                quote = Some(ch);
+                out.push(ch);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = None;
+                for next in chars.by_ref() {
+                    if prev == Some('*') && next == '/' {
+                        break;
+                    }
+                    prev = Some(next);
+                }
+                out.push(' ');
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Returns the text following the first `key:` (optionally quoted) that is not part of a
+/// longer identifier or inside a string.
+fn key_value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let bytes = text.as_bytes();
+    let mut quote = None;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        if let Some(open) = quote {
+            if byte == b'\\' {
+                idx += 1;
+            } else if byte == open {
+                quote = None;
+            }
+            idx += 1;
+            continue;
+        }
+        let key_start = match byte {
+            b'"' | b'\'' if bytes[idx + 1..].starts_with(key.as_bytes()) => idx + 1,
+            b'"' | b'\'' | b'`' => {
+                quote = Some(byte);
+                idx += 1;
+                continue;
+            }
+            _ if bytes[idx..].starts_with(key.as_bytes())
+                && (idx == 0 || !is_ident_byte(bytes[idx - 1])) =>
+            {
+                idx
+            }
+            _ => {
+                idx += 1;
+                continue;
+            }
+        };
+        let mut end = key_start + key.len();
+        if key_start != idx {
+            if bytes.get(end) != Some(&byte) {
+                quote = Some(byte);
+                idx += 1;
+                continue;
+            }
+            end += 1;
+        } else if bytes.get(end).copied().is_some_and(is_ident_byte) {
+            idx += 1;
+            continue;
+        }
+        let rest = text[end..].trim_start();
+        if let Some(value) = rest.strip_prefix(':') {
+            return Some(value.trim_start());
+        }
+        idx = end;
+    }
+    None
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+fn string_literal(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let quote = text
+        .chars()
+        .next()
+        .filter(|ch| matches!(ch, '"' | '\'' | '`'))?;
+    let body = &text[1..];
+    let end = body.find(quote)?;
+    let value = &body[..end];
+    (!value.contains("${")).then(|| value.to_string())
+}
+
+/// Returns the contents of the `{ ... }` literal at the start of `text`.
+fn object_literal(text: &str) -> Option<&str> {
+    let body = text.trim_start().strip_prefix('{')?;
+    let mut depth = 1usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, ch) in body.char_indices() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '"' | '\'' | '`' => quote = Some(ch),
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&body[..idx]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HardhatConfig;
+
+    #[test]
+    fn parses_string_solidity_version() {
+        let config = HardhatConfig::parse(r#"module.exports = { solidity: "0.8.24" };"#);
+        assert_eq!(config.solc_version(), Some("0.8.24"));
+        assert_eq!(config.sources(), None);
+    }
+
+    #[test]
+    fn parses_first_compiler_version_and_paths() {
+        let text = r#"
+import { HardhatUserConfig } from "hardhat/config";
+
+// solidity: "0.4.0",
+const config: HardhatUserConfig = {
+  solidity: {
+    compilers: [
+      { version: '0.8.19', settings: { optimizer: { enabled: true } } },
+      { version: '0.7.6' },
+    ],
+  },
+  /* paths: { sources: "./ignored" } */
+  "paths": {
+    sources: "./src",
+    tests: "./spec",
+  },
+};
+
+export default config;
+"#;
+        let config = HardhatConfig::parse(text);
+        assert_eq!(config.solc_version(), Some("0.8.19"));
+        assert_eq!(config.sources(), Some("./src"));
+        assert_eq!(config.tests(), Some("./spec"));
+    }
+
+    #[test]
+    fn ignores_dynamic_values() {
+        let text = r#"
+const version = process.env.SOLC ?? "0.8.20";
+module.exports = {
+  solidity: version,
+  paths: { sources: `${root}/contracts` },
+};
+"#;
+        let config = HardhatConfig::parse(text);
+        assert_eq!(config.solc_version(), None);
+        assert_eq!(config.sources(), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Path globs that shape which files get indexed.
+//!
+//! Patterns are matched against paths relative to the workspace root (absolute paths outside the
+//! root are matched as-is). A pattern without glob metacharacters names a directory and covers
+//! everything below it. `include` wins over `exclude` and additionally pulls its directories into
+//! the index even when nothing imports them.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use sa_paths::NormalizedPath;
+
+#[derive(Clone, Default)]
+pub struct IndexFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_set: GlobSet,
+    exclude_set: GlobSet,
+}
+
+impl IndexFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Result<Self> {
+        let include_set = build_glob_set(&include)?;
+        let exclude_set = build_glob_set(&exclude)?;
+        Ok(Self {
+            include,
+            exclude,
+            include_set,
+            exclude_set,
+        })
+    }
+
+    pub fn include(&self) -> &[String] {
+        &self.include
+    }
+
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn is_included(&self, root: &NormalizedPath, path: &NormalizedPath) -> bool {
+        !self.include.is_empty() && self.include_set.is_match(relative_path(root, path))
+    }
+
+    pub fn is_excluded(&self, root: &NormalizedPath, path: &NormalizedPath) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let relative = relative_path(root, path);
+        self.exclude_set.is_match(relative) && !self.include_set.is_match(relative)
+    }
+
+    /// Solidity files below the `include` directories that match an include pattern.
+    pub fn included_files(&self, root: &NormalizedPath) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for pattern in &self.include {
+            let base = Path::new(root.as_str()).join(literal_prefix(pattern));
+            collect_sol_files(&base, &mut files);
+        }
+        files.retain(|file| {
+            let path = NormalizedPath::new(file.to_string_lossy());
+            self.is_included(root, &path)
+        });
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+impl fmt::Debug for IndexFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexFilter")
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .finish()
+    }
+}
+
+impl PartialEq for IndexFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.include == other.include && self.exclude == other.exclude
+    }
+}
+
+impl Eq for IndexFilter {}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let glob = if has_glob_meta(pattern) {
+            Glob::new(pattern)
+        } else {
+            Glob::new(&format!("{pattern}/**"))
+        };
+        builder.add(glob.with_context(|| format!("invalid index glob `{pattern}`"))?);
+    }
+    builder
+        .build()
+        .with_context(|| "failed to build index globs")
+}
+
+fn has_glob_meta(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+/// The leading path components of `pattern` that contain no glob metacharacters.
+fn literal_prefix(pattern: &str) -> PathBuf {
+    Path::new(pattern.trim_start_matches("./"))
+        .components()
+        .take_while(|component| !has_glob_meta(&component.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+fn relative_path<'a>(root: &NormalizedPath, path: &'a NormalizedPath) -> &'a str {
+    path.as_str()
+        .strip_prefix(root.as_str())
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(path.as_str())
+}
+
+fn collect_sol_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_sol_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "sol") {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sa_paths::NormalizedPath;
+
+    use super::IndexFilter;
+
+    fn path(value: &str) -> NormalizedPath {
+        NormalizedPath::new(value)
+    }
+
+    #[test]
+    fn directories_and_globs_exclude_relative_paths() {
+        let filter = IndexFilter::new(
+            Vec::new(),
+            vec!["out".to_string(), "**/generated/*.sol".to_string()],
+        )
+        .expect("filter");
+        let root = path("/workspace");
+
+        assert!(filter.is_excluded(&root, &path("/workspace/out/Main.sol")));
+        assert!(filter.is_excluded(&root, &path("/workspace/src/generated/Types.sol")));
+        assert!(!filter.is_excluded(&root, &path("/workspace/src/Main.sol")));
+        assert!(!filter.is_excluded(&root, &path("/workspace/outside/Main.sol")));
+    }
+
+    #[test]
+    fn include_overrides_exclude() {
+        let filter = IndexFilter::new(
+            vec!["lib/big/src/keep".to_string()],
+            vec!["lib/big".to_string()],
+        )
+        .expect("filter");
+        let root = path("/workspace");
+
+        assert!(filter.is_excluded(&root, &path("/workspace/lib/big/src/Drop.sol")));
+        assert!(!filter.is_excluded(&root, &path("/workspace/lib/big/src/keep/Keep.sol")));
+    }
+
+    #[test]
+    fn invalid_globs_are_rejected() {
+        assert!(IndexFilter::new(Vec::new(), vec!["src/[".to_string()]).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use foundry_compilers::{
@@ -10,7 +11,31 @@ use foundry_compilers::{
     },
     resolver::{SolImportAlias, parse::SolParser},
 };
-use sa_paths::NormalizedPath;
+use sa_paths::{NormalizedPath, RealPathCache, WorkspacePath};
+
+mod abi;
+mod artifacts;
+mod dependencies;
+mod hardhat;
+mod index_filter;
+mod node_modules;
+mod python_tooling;
+mod remappings;
+
+pub use abi::{abi_interface, parse_abi};
+pub use artifacts::{ContractArtifact, find_artifact, load_artifacts, parse_artifact};
+pub use dependencies::{DependencyPackage, discover_dependencies};
+pub use hardhat::{
+    HARDHAT_CONFIG_FILES, HardhatConfig, HardhatProject, contains_hardhat_config,
+    find_hardhat_config,
+};
+pub use index_filter::IndexFilter;
+pub use node_modules::{NodeModulesCache, resolve_node_modules_import};
+pub use python_tooling::{
+    APE_CONFIG_FILE, BROWNIE_CONFIG_FILE, PythonFramework, PythonProject, PythonProjectConfig,
+    contains_python_project_config, find_python_project_config,
+};
+pub use remappings::{infer_remappings, parse_remappings_txt};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Remapping {
@@ -101,6 +126,9 @@ pub struct FoundryWorkspace {
     lib: NormalizedPath,
     test: NormalizedPath,
     script: NormalizedPath,
+    extra_libs: Vec<NormalizedPath>,
+    include_paths: Vec<NormalizedPath>,
+    index_filter: IndexFilter,
 }
 
 impl FoundryWorkspace {
@@ -127,7 +155,30 @@ impl FoundryWorkspace {
             lib,
             test,
             script,
+            extra_libs: Vec::new(),
+            include_paths: Vec::new(),
+            index_filter: IndexFilter::default(),
+        }
+    }
+
+    /// Sets every library root; the first one becomes [`FoundryWorkspace::lib`].
+    pub fn with_libs(mut self, libs: Vec<NormalizedPath>) -> Self {
+        let mut libs = libs.into_iter();
+        if let Some(lib) = libs.next() {
+            self.lib = lib;
         }
+        self.extra_libs = libs.collect();
+        self
+    }
+
+    pub fn with_include_paths(mut self, include_paths: Vec<NormalizedPath>) -> Self {
+        self.include_paths = include_paths;
+        self
+    }
+
+    pub fn with_index_filter(mut self, index_filter: IndexFilter) -> Self {
+        self.index_filter = index_filter;
+        self
     }
 
     pub fn root(&self) -> &NormalizedPath {
@@ -142,6 +193,15 @@ impl FoundryWorkspace {
         &self.lib
     }
 
+    /// All library roots, starting with [`FoundryWorkspace::lib`].
+    pub fn libs(&self) -> impl Iterator<Item = &NormalizedPath> {
+        std::iter::once(&self.lib).chain(&self.extra_libs)
+    }
+
+    pub fn include_paths(&self) -> &[NormalizedPath] {
+        &self.include_paths
+    }
+
     pub fn test(&self) -> &NormalizedPath {
         &self.test
     }
@@ -149,6 +209,37 @@ impl FoundryWorkspace {
     pub fn script(&self) -> &NormalizedPath {
         &self.script
     }
+
+    pub fn index_filter(&self) -> &IndexFilter {
+        &self.index_filter
+    }
+
+    /// Returns true if the index filter excludes `path` from indexing.
+    pub fn is_excluded(&self, path: &NormalizedPath) -> bool {
+        self.index_filter.is_excluded(&self.root, path)
+    }
+
+    /// Returns true if `path` belongs to an installed dependency: it lies below a library root
+    /// and not below the project's own source, test or script directories.
+    pub fn is_library_file(&self, path: &NormalizedPath) -> bool {
+        let own = [&self.src, &self.test, &self.script];
+        self.libs()
+            .any(|lib| WorkspacePath::new(lib, path).is_some())
+            && !own
+                .into_iter()
+                .any(|dir| WorkspacePath::new(dir, path).is_some())
+    }
+
+    /// Returns true for files that belong to third-party code: library files, and anything
+    /// below the root's `node_modules/` or Soldeer's `dependencies/` even if they are not
+    /// configured as library roots.
+    pub fn is_dependency_file(&self, path: &NormalizedPath) -> bool {
+        self.is_library_file(path)
+            || ["node_modules", "dependencies"].into_iter().any(|dir| {
+                let dir = NormalizedPath::new(format!("{}/{dir}", self.root.as_str()));
+                WorkspacePath::new(&dir, path).is_some()
+            })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -161,12 +252,23 @@ pub struct ResolvedImport {
 #[derive(Clone, Debug)]
 pub struct FoundryResolver {
     paths: ProjectPathsConfig,
+    node_modules: Arc<NodeModulesCache>,
 }
 
 impl FoundryResolver {
     pub fn new(workspace: &FoundryWorkspace, remappings: &[Remapping]) -> Result<Self> {
         let paths = project_paths_from_config(workspace, remappings)?;
-        Ok(Self { paths })
+        Ok(Self {
+            paths,
+            node_modules: Arc::default(),
+        })
+    }
+
+    /// Resolves node_modules packages through `cache`, which can outlive this resolver, instead
+    /// of one of its own.
+    pub fn with_node_modules(mut self, cache: Arc<NodeModulesCache>) -> Self {
+        self.node_modules = cache;
+        self
     }
 
     pub fn resolve_import_path(
@@ -178,8 +280,16 @@ impl FoundryResolver {
         let import_path = Path::new(import_path.as_ref());
         let current = Path::new(current_path.as_str());
         let cwd = current.parent().unwrap_or_else(|| Path::new("."));
-        let resolved = self.paths.resolve_import(cwd, import_path).ok()?;
-        Some(NormalizedPath::new(resolved.to_string_lossy()))
+        let resolved = self.paths.resolve_import(cwd, import_path).ok();
+        let resolved = match resolved.as_ref().filter(|path| path.is_file()) {
+            Some(resolved) => NormalizedPath::new(resolved.to_string_lossy()),
+            None => self
+                .node_modules
+                .resolve(current_path, &import_path.to_string_lossy())
+                .or_else(|| resolved.map(|path| NormalizedPath::new(path.to_string_lossy())))?,
+        };
+        // Imports through a symlinked dependency must name the file the index loaded.
+        Some(RealPathCache::shared().real_path(&resolved))
     }
 
     pub fn resolved_imports(
@@ -218,14 +328,23 @@ pub fn project_paths_from_config(
     let sources = PathBuf::from(workspace.src().as_str());
     let tests = PathBuf::from(workspace.test().as_str());
     let scripts = PathBuf::from(workspace.script().as_str());
-    let libs = vec![PathBuf::from(workspace.lib().as_str())];
+    let libs = workspace
+        .libs()
+        .map(|lib| PathBuf::from(lib.as_str()))
+        .collect::<Vec<_>>();
+    let include_paths = workspace
+        .include_paths()
+        .iter()
+        .map(|path| PathBuf::from(path.as_str()))
+        .collect::<Vec<_>>();
 
     let mut builder = ProjectPathsConfig::builder()
         .root(&root)
         .sources(sources)
         .tests(tests)
         .scripts(scripts)
-        .libs(libs);
+        .libs(libs)
+        .include_paths(include_paths);
 
     for remapping in remappings {
         builder = builder.remapping(FoundryRemapping {
@@ -287,4 +406,21 @@ mod tests {
         assert_eq!(workspace.test().as_str(), "/workspace/test");
         assert_eq!(workspace.script().as_str(), "/workspace/script");
     }
+
+    #[test]
+    fn extra_libs_follow_the_primary_lib() {
+        let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace")).with_libs(vec![
+            NormalizedPath::new("/workspace/lib"),
+            NormalizedPath::new("/workspace/node_modules"),
+        ]);
+
+        assert_eq!(workspace.lib().as_str(), "/workspace/lib");
+        assert_eq!(
+            workspace
+                .libs()
+                .map(NormalizedPath::as_str)
+                .collect::<Vec<_>>(),
+            vec!["/workspace/lib", "/workspace/node_modules"]
+        );
+    }
 }
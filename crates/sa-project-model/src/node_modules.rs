@@ -0,0 +1,231 @@
+//! Node-style package resolution for imports such as `@openzeppelin/contracts/access/Ownable.sol`.
+//!
+//! Packages are looked up in `node_modules` directories from the importing file upwards, which
+//! also covers pnpm layouts (symlinked packages and the `.pnpm` store). Yarn PnP installs without
+//! `node_modules` are served from `.yarn/unplugged`. When a package declares `exports`, its
+//! subpath patterns are applied to the import; otherwise a bare package import resolves to
+//! `main`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use sa_paths::NormalizedPath;
+use serde_json::Value;
+
+const NODE_MODULES: &str = "node_modules";
+const PACKAGE_JSON: &str = "package.json";
+
+/// Packages found for a `(directory, package name)` lookup.
+type PackageMap = HashMap<(PathBuf, String), Arc<[Package]>>;
+
+/// Where packages are installed as seen from each directory imports are resolved from, with
+/// their parsed `package.json`. Once a package has been located, resolving imports from it
+/// neither walks `node_modules` directories nor rereads its manifest. The owner of a cache
+/// clears it when installs may have changed; see [`crate::FoundryResolver::with_node_modules`].
+#[derive(Debug, Default)]
+pub struct NodeModulesCache {
+    packages: Mutex<PackageMap>,
+}
+
+#[derive(Debug)]
+struct Package {
+    dir: PathBuf,
+    manifest: Option<Value>,
+}
+
+impl NodeModulesCache {
+    /// Forgets every package, for when installs may have changed them.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn resolve(
+        &self,
+        current_path: &NormalizedPath,
+        import_path: &str,
+    ) -> Option<NormalizedPath> {
+        let (package, subpath) = split_package(import_path)?;
+        let current = Path::new(current_path.as_str());
+        let start = current.parent()?;
+
+        start
+            .ancestors()
+            .find_map(|dir| {
+                self.packages(dir, package)
+                    .iter()
+                    .find_map(|package| resolve_in_package(package, subpath))
+            })
+            .map(|path| NormalizedPath::new(path.to_string_lossy()))
+    }
+
+    fn packages(&self, dir: &Path, package: &str) -> Arc<[Package]> {
+        let key = (dir.to_path_buf(), package.to_string());
+        if let Some(packages) = self.lock().get(&key) {
+            return Arc::clone(packages);
+        }
+        let packages = package_dirs(dir, package)
+            .into_iter()
+            .map(|dir| {
+                let manifest = fs::read_to_string(dir.join(PACKAGE_JSON))
+                    .ok()
+                    .and_then(|text| serde_json::from_str::<Value>(&text).ok());
+                Package { dir, manifest }
+            })
+            .collect::<Arc<[_]>>();
+        self.lock().insert(key, Arc::clone(&packages));
+        packages
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PackageMap> {
+        self.packages.lock().expect("node_modules cache lock")
+    }
+}
+
+/// Resolves `import_path` from `current_path` without keeping what it looked up.
+pub fn resolve_node_modules_import(
+    current_path: &NormalizedPath,
+    import_path: &str,
+) -> Option<NormalizedPath> {
+    NodeModulesCache::default().resolve(current_path, import_path)
+}
+
+/// Splits `@scope/name/sub/path.sol` into `("@scope/name", "sub/path.sol")`.
+fn split_package(import_path: &str) -> Option<(&str, &str)> {
+    if import_path.starts_with('.') || import_path.starts_with('/') {
+        return None;
+    }
+    let mut split_at = import_path.find('/');
+    if import_path.starts_with('@') {
+        let scope_end = split_at?;
+        split_at = import_path[scope_end + 1..]
+            .find('/')
+            .map(|idx| scope_end + 1 + idx);
+    }
+    match split_at {
+        Some(idx) => Some((&import_path[..idx], &import_path[idx + 1..])),
+        None => Some((import_path, "")),
+    }
+}
+
+fn package_dirs(dir: &Path, package: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let node_modules = dir.join(NODE_MODULES).join(package);
+    if node_modules.is_dir() {
+        dirs.push(node_modules);
+    }
+
+    let unplugged = dir.join(".yarn/unplugged");
+    if dir.join(".pnp.cjs").is_file()
+        && let Ok(entries) = fs::read_dir(&unplugged)
+    {
+        let mut candidates = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join(NODE_MODULES).join(package))
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        candidates.sort();
+        dirs.extend(candidates);
+    }
+    dirs
+}
+
+/// A package that declares `exports` only makes the subpaths it lists importable.
+fn resolve_in_package(package: &Package, subpath: &str) -> Option<PathBuf> {
+    let manifest = package.manifest.as_ref();
+    let path = match manifest.and_then(|manifest| manifest.get("exports")) {
+        Some(exports) => {
+            let target = resolve_exports(exports, subpath)?;
+            package.dir.join(target.trim_start_matches("./"))
+        }
+        None if subpath.is_empty() => {
+            let main = manifest?.get("main").and_then(Value::as_str)?;
+            package.dir.join(main.trim_start_matches("./"))
+        }
+        None => package.dir.join(subpath),
+    };
+    path.is_file().then_some(path)
+}
+
+/// Maps `subpath` through a package.json `exports` field.
+fn resolve_exports(exports: &Value, subpath: &str) -> Option<String> {
+    let key = if subpath.is_empty() {
+        ".".to_string()
+    } else {
+        format!("./{subpath}")
+    };
+    let map = match exports {
+        Value::Object(map) if map.keys().any(|key| key.starts_with('.')) => map,
+        // Sugar for `{ ".": exports }`.
+        _ => return (key == ".").then(|| export_target(exports)).flatten(),
+    };
+
+    if let Some(target) = map.get(&key) {
+        return export_target(target);
+    }
+
+    // The pattern with the longest prefix before `*` wins, as in Node.
+    let (_, target, matched) = map
+        .iter()
+        .filter_map(|(pattern, target)| {
+            let (prefix, suffix) = pattern.split_once('*')?;
+            let matched = key.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            Some((prefix.len(), target, matched))
+        })
+        .max_by_key(|(prefix_len, _, _)| *prefix_len)?;
+    export_target(target).map(|target| target.replace('*', matched))
+}
+
+/// Picks a concrete path from an export target, preferring the `default` condition.
+fn export_target(target: &Value) -> Option<String> {
+    match target {
+        Value::String(path) => Some(path.clone()),
+        Value::Array(targets) => targets.iter().find_map(export_target),
+        Value::Object(conditions) => conditions
+            .get("default")
+            .and_then(export_target)
+            .or_else(|| conditions.values().find_map(export_target)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{resolve_exports, split_package};
+
+    #[test]
+    fn splits_scoped_and_plain_packages() {
+        assert_eq!(
+            split_package("@openzeppelin/contracts/token/ERC20.sol"),
+            Some(("@openzeppelin/contracts", "token/ERC20.sol"))
+        );
+        assert_eq!(
+            split_package("solmate/src/Owned.sol"),
+            Some(("solmate", "src/Owned.sol"))
+        );
+        assert_eq!(split_package("solmate"), Some(("solmate", "")));
+        assert_eq!(split_package("./Local.sol"), None);
+        assert_eq!(split_package("@scope"), None);
+    }
+
+    #[test]
+    fn exports_apply_exact_and_pattern_entries() {
+        let exports = json!({
+            ".": "./index.sol",
+            "./*": "./src/*",
+            "./token/*": { "import": "./dist/token/*", "default": "./contracts/token/*" },
+        });
+        assert_eq!(resolve_exports(&exports, ""), Some("./index.sol".into()));
+        assert_eq!(
+            resolve_exports(&exports, "Owned.sol"),
+            Some("./src/Owned.sol".into())
+        );
+        assert_eq!(
+            resolve_exports(&exports, "token/ERC20.sol"),
+            Some("./contracts/token/ERC20.sol".into())
+        );
+    }
+}
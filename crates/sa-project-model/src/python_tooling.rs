@@ -0,0 +1,521 @@
+//! Ape and Brownie project detection.
+//!
+//! Both tools keep their settings in YAML. Only a documented subset is read:
+//!
+//! - Ape (`ape-config.yaml`): `contracts_folder`, `solidity.version` and
+//!   `solidity.import_remapping`
+//! - Brownie (`brownie-config.yaml`): `project_structure.{contracts,tests,scripts}`,
+//!   `compiler.solc.version` and `compiler.solc.remappings`
+//!
+//! Remappings are `prefix=target` strings. A target that exists below the project root is taken
+//! as a project path; anything else names an installed package below the tool's package folder
+//! (`$APE_DATA_FOLDER/packages`, `~/.ape/packages` or `~/.brownie/packages`), which also becomes
+//! the library root.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use sa_paths::NormalizedPath;
+
+use crate::{FoundryWorkspace, Remapping};
+
+pub const APE_CONFIG_FILE: &str = "ape-config.yaml";
+pub const BROWNIE_CONFIG_FILE: &str = "brownie-config.yaml";
+
+const DEFAULT_CONTRACTS: &str = "contracts";
+const DEFAULT_TESTS: &str = "tests";
+const DEFAULT_SCRIPTS: &str = "scripts";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PythonFramework {
+    Ape,
+    Brownie,
+}
+
+impl PythonFramework {
+    pub fn config_file(self) -> &'static str {
+        match self {
+            PythonFramework::Ape => APE_CONFIG_FILE,
+            PythonFramework::Brownie => BROWNIE_CONFIG_FILE,
+        }
+    }
+
+    /// The folder dependencies are installed into, if the home directory is known.
+    pub fn packages_dir(self) -> Option<PathBuf> {
+        match self {
+            PythonFramework::Ape => env::var_os("APE_DATA_FOLDER")
+                .map(PathBuf::from)
+                .or_else(|| home_dir().map(|home| home.join(".ape")))
+                .map(|data| data.join("packages")),
+            PythonFramework::Brownie => {
+                home_dir().map(|home| home.join(".brownie").join("packages"))
+            }
+        }
+    }
+}
+
+pub fn find_python_project_config(root: &Path) -> Option<(PythonFramework, PathBuf)> {
+    [PythonFramework::Ape, PythonFramework::Brownie]
+        .into_iter()
+        .map(|framework| (framework, root.join(framework.config_file())))
+        .find(|(_, path)| path.is_file())
+}
+
+pub fn contains_python_project_config(root: &Path) -> bool {
+    find_python_project_config(root).is_some()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PythonProjectConfig {
+    framework: PythonFramework,
+    contracts: Option<String>,
+    tests: Option<String>,
+    scripts: Option<String>,
+    solc_version: Option<String>,
+    remappings: Vec<(String, String)>,
+}
+
+impl PythonProjectConfig {
+    pub fn parse(framework: PythonFramework, text: &str) -> Self {
+        let yaml = Yaml::parse(text);
+        let (contracts, tests, scripts, solc) = match framework {
+            PythonFramework::Ape => (
+                yaml.get(&["contracts_folder"]),
+                None,
+                None,
+                yaml.get(&["solidity"]),
+            ),
+            PythonFramework::Brownie => (
+                yaml.get(&["project_structure", "contracts"]),
+                yaml.get(&["project_structure", "tests"]),
+                yaml.get(&["project_structure", "scripts"]),
+                yaml.get(&["compiler", "solc"]),
+            ),
+        };
+        let remapping_key = match framework {
+            PythonFramework::Ape => "import_remapping",
+            PythonFramework::Brownie => "remappings",
+        };
+        let remappings = solc
+            .and_then(|solc| solc.get(&[remapping_key]))
+            .map(Yaml::items)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(Yaml::as_str)
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+            .collect();
+
+        Self {
+            framework,
+            contracts: contracts.and_then(Yaml::as_str).map(str::to_string),
+            tests: tests.and_then(Yaml::as_str).map(str::to_string),
+            scripts: scripts.and_then(Yaml::as_str).map(str::to_string),
+            solc_version: solc
+                .and_then(|solc| solc.get(&["version"]))
+                .and_then(Yaml::as_str)
+                .map(str::to_string),
+            remappings,
+        }
+    }
+
+    pub fn framework(&self) -> PythonFramework {
+        self.framework
+    }
+
+    pub fn contracts(&self) -> Option<&str> {
+        self.contracts.as_deref()
+    }
+
+    pub fn tests(&self) -> Option<&str> {
+        self.tests.as_deref()
+    }
+
+    pub fn scripts(&self) -> Option<&str> {
+        self.scripts.as_deref()
+    }
+
+    pub fn solc_version(&self) -> Option<&str> {
+        self.solc_version.as_deref()
+    }
+
+    /// The raw `(prefix, target)` remapping pairs.
+    pub fn remappings(&self) -> &[(String, String)] {
+        &self.remappings
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PythonProject {
+    framework: PythonFramework,
+    workspace: FoundryWorkspace,
+    solc_version: Option<String>,
+    remappings: Vec<Remapping>,
+}
+
+impl PythonProject {
+    pub fn load(root: &Path) -> Result<Self> {
+        let (framework, config_path) = find_python_project_config(root)
+            .with_context(|| format!("no ape or brownie config found in {}", root.display()))?;
+        let text = fs::read_to_string(&config_path)
+            .with_context(|| format!("failed to read {}", config_path.display()))?;
+        let config = PythonProjectConfig::parse(framework, &text);
+        let packages = framework
+            .packages_dir()
+            .map(|dir| NormalizedPath::new(dir.to_string_lossy()));
+        Ok(Self::from_config(
+            NormalizedPath::new(root.to_string_lossy()),
+            &config,
+            packages,
+        ))
+    }
+
+    /// Lays out the project like Ape and Brownie do: `contracts/`, `tests/` and `scripts/` below
+    /// the config directory, with installed packages as the library root.
+    pub fn from_config(
+        root: NormalizedPath,
+        config: &PythonProjectConfig,
+        packages: Option<NormalizedPath>,
+    ) -> Self {
+        let src = join(&root, config.contracts().unwrap_or(DEFAULT_CONTRACTS));
+        let test = join(&root, config.tests().unwrap_or(DEFAULT_TESTS));
+        let script = join(&root, config.scripts().unwrap_or(DEFAULT_SCRIPTS));
+        let lib = packages.clone().unwrap_or_else(|| join(&root, "lib"));
+        let remappings = config
+            .remappings()
+            .iter()
+            .map(|(from, to)| {
+                let target = to.trim_end_matches('/');
+                let local = Path::new(root.as_str()).join(target).exists();
+                let to = match &packages {
+                    Some(packages) if !local => join(packages, target),
+                    _ => NormalizedPath::new(target),
+                };
+                Remapping::new(
+                    format!("{}/", from.trim_end_matches('/')),
+                    format!("{}/", to.as_str()),
+                )
+            })
+            .collect();
+
+        Self {
+            framework: config.framework(),
+            workspace: FoundryWorkspace::from_paths(root, src, lib, test, script),
+            solc_version: config.solc_version.clone(),
+            remappings,
+        }
+    }
+
+    pub fn framework(&self) -> PythonFramework {
+        self.framework
+    }
+
+    pub fn workspace(&self) -> &FoundryWorkspace {
+        &self.workspace
+    }
+
+    pub fn solc_version(&self) -> Option<&str> {
+        self.solc_version.as_deref()
+    }
+
+    pub fn remappings(&self) -> &[Remapping] {
+        &self.remappings
+    }
+}
+
+fn join(base: &NormalizedPath, path: &str) -> NormalizedPath {
+    if Path::new(path).is_absolute() {
+        NormalizedPath::new(path)
+    } else {
+        NormalizedPath::new(format!("{}/{path}", base.as_str()))
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The YAML subset these configs use: block mappings, block sequences, flow sequences and
+/// plain or quoted scalars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Yaml {
+    Scalar(String),
+    List(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+struct Line<'a> {
+    indent: usize,
+    text: &'a str,
+}
+
+impl Yaml {
+    fn parse(text: &str) -> Yaml {
+        let lines = text
+            .lines()
+            .filter_map(|line| {
+                let content = strip_comment(line).trim_end();
+                let text = content.trim_start();
+                (!text.is_empty() && text != "---").then(|| Line {
+                    indent: content.len() - text.len(),
+                    text,
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut idx = 0;
+        match lines.first() {
+            Some(first) => parse_block(&lines, &mut idx, first.indent),
+            None => Yaml::Map(Vec::new()),
+        }
+    }
+
+    fn get(&self, path: &[&str]) -> Option<&Yaml> {
+        let mut node = self;
+        for key in path {
+            let Yaml::Map(entries) = node else {
+                return None;
+            };
+            node = entries
+                .iter()
+                .find(|(entry, _)| entry == key)
+                .map(|(_, value)| value)?;
+        }
+        Some(node)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::Scalar(value) if !value.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    fn items(&self) -> Vec<&Yaml> {
+        match self {
+            Yaml::List(items) => items.iter().collect(),
+            Yaml::Scalar(_) => vec![self],
+            Yaml::Map(_) => Vec::new(),
+        }
+    }
+}
+
+fn parse_block(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Yaml {
+    if lines[*idx].text.starts_with('-') {
+        parse_list(lines, idx, indent)
+    } else {
+        parse_map(lines, idx, indent)
+    }
+}
+
+fn parse_list(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Yaml {
+    let mut items = Vec::new();
+    while let Some(line) = lines.get(*idx) {
+        if line.indent > indent {
+            *idx += 1;
+            continue;
+        }
+        if line.indent < indent || !line.text.starts_with('-') {
+            break;
+        }
+        let item = line.text[1..].trim_start();
+        *idx += 1;
+        if item.is_empty() {
+            items.push(parse_nested(lines, idx, indent));
+        } else if let Some((key, value)) = split_key(item) {
+            // `- key: value` opens a mapping whose other keys are indented past the dash.
+            let item_indent = indent + (line.text.len() - item.len());
+            let mut entries = vec![(key, parse_value(lines, idx, item_indent, value))];
+            if let Yaml::Map(rest) = parse_nested(lines, idx, indent) {
+                entries.extend(rest);
+            }
+            items.push(Yaml::Map(entries));
+        } else {
+            items.push(parse_inline(item));
+        }
+    }
+    Yaml::List(items)
+}
+
+fn parse_map(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Yaml {
+    let mut entries = Vec::new();
+    while let Some(line) = lines.get(*idx) {
+        if line.indent < indent {
+            break;
+        }
+        *idx += 1;
+        if line.indent > indent {
+            continue;
+        }
+        let Some((key, value)) = split_key(line.text) else {
+            continue;
+        };
+        let value = parse_value(lines, idx, indent, value);
+        entries.push((key, value));
+    }
+    Yaml::Map(entries)
+}
+
+fn parse_value(lines: &[Line<'_>], idx: &mut usize, indent: usize, value: &str) -> Yaml {
+    if !value.is_empty() {
+        return parse_inline(value);
+    }
+    match lines.get(*idx) {
+        // Block sequences may sit at the same indentation as their key.
+        Some(next) if next.indent == indent && next.text.starts_with('-') => {
+            parse_list(lines, idx, indent)
+        }
+        _ => parse_nested(lines, idx, indent),
+    }
+}
+
+fn parse_nested(lines: &[Line<'_>], idx: &mut usize, indent: usize) -> Yaml {
+    match lines.get(*idx) {
+        Some(next) if next.indent > indent => parse_block(lines, idx, next.indent),
+        _ => Yaml::Scalar(String::new()),
+    }
+}
+
+fn parse_inline(value: &str) -> Yaml {
+    match value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        Some(items) => Yaml::List(
+            split_flow_items(items)
+                .into_iter()
+                .map(|item| Yaml::Scalar(unquote(item)))
+                .collect(),
+        ),
+        None => Yaml::Scalar(unquote(value)),
+    }
+}
+
+fn split_key(text: &str) -> Option<(String, &str)> {
+    let (key, value) = match find_unquoted(text, ':') {
+        Some(pos) if text[pos + 1..].is_empty() || text[pos + 1..].starts_with(' ') => {
+            (&text[..pos], text[pos + 1..].trim())
+        }
+        _ => return None,
+    };
+    Some((unquote(key), value))
+}
+
+fn split_flow_items(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = items;
+    while let Some(pos) = find_unquoted(rest, ',') {
+        parts.push(rest[..pos].trim());
+        rest = &rest[pos + 1..];
+    }
+    parts.push(rest.trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn find_unquoted(text: &str, needle: char) -> Option<usize> {
+    let mut quote = None;
+    for (idx, ch) in text.char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == needle => return Some(idx),
+            None => {}
+        }
+    }
+    None
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (idx, ch) in line.char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' && prev.is_whitespace() => return &line[..idx],
+            None => {}
+        }
+        prev = ch;
+    }
+    line
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PythonFramework, PythonProjectConfig};
+
+    #[test]
+    fn parses_ape_config() {
+        let text = r#"
+name: token
+contracts_folder: src # sources
+dependencies:
+  - name: openzeppelin
+    github: OpenZeppelin/openzeppelin-contracts
+    version: 4.9.3
+solidity:
+  version: 0.8.20
+  import_remapping:
+    - "@openzeppelin=openzeppelin/4.9.3"
+"#;
+        let config = PythonProjectConfig::parse(PythonFramework::Ape, text);
+        assert_eq!(config.contracts(), Some("src"));
+        assert_eq!(config.tests(), None);
+        assert_eq!(config.solc_version(), Some("0.8.20"));
+        assert_eq!(
+            config.remappings(),
+            [(
+                "@openzeppelin".to_string(),
+                "openzeppelin/4.9.3".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_brownie_config() {
+        let text = r#"
+project_structure:
+    contracts: contracts
+    tests: spec
+dependencies:
+- OpenZeppelin/openzeppelin-contracts@4.8.0
+compiler:
+    solc:
+        version: '0.8.17'
+        remappings: ["@openzeppelin=OpenZeppelin/openzeppelin-contracts@4.8.0"]
+"#;
+        let config = PythonProjectConfig::parse(PythonFramework::Brownie, text);
+        assert_eq!(config.contracts(), Some("contracts"));
+        assert_eq!(config.tests(), Some("spec"));
+        assert_eq!(config.scripts(), None);
+        assert_eq!(config.solc_version(), Some("0.8.17"));
+        assert_eq!(
+            config.remappings(),
+            [(
+                "@openzeppelin".to_string(),
+                "OpenZeppelin/openzeppelin-contracts@4.8.0".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Remapping inference for projects whose foundry.toml declares none, mirroring
+//! `forge remappings`.
+//!
+//! Every directory under a library root becomes `<name>/=<lib>/<name>/src/` (or the directory
+//! itself when it has no `src`). Remappings a dependency declares in its own `remappings.txt` or
+//! `foundry.toml` are rebased onto the project root, and nested `lib/` directories are scanned
+//! the same way. When two entries share a name, a top-level library wins, then the shallower one.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Remapping;
+
+const REMAPPINGS_TXT: &str = "remappings.txt";
+const FOUNDRY_TOML: &str = "foundry.toml";
+const MAX_DEPTH: usize = 4;
+
+/// Infers remappings for the libraries in `lib_dirs`, with targets relative to `root`.
+pub fn infer_remappings(root: &Path, lib_dirs: &[PathBuf]) -> Vec<Remapping> {
+    // A library checked out directly under a lib dir is what the project itself installed, so
+    // its remapping beats anything a dependency declares, whatever the depth.
+    let mut top_level = Vec::new();
+    let mut inferred = Vec::new();
+    let mut queue = lib_dirs
+        .iter()
+        .map(|lib| (root.join(lib), 0))
+        .collect::<Vec<_>>();
+    let mut visited = HashSet::new();
+
+    // Breadth-first, so dependencies closer to the project take precedence.
+    let mut idx = 0;
+    while idx < queue.len() {
+        let (lib_dir, depth) = queue[idx].clone();
+        idx += 1;
+        for dep in dependency_dirs(&lib_dir) {
+            let key = dep.canonicalize().unwrap_or_else(|_| dep.clone());
+            if !visited.insert(key) {
+                continue;
+            }
+            let Some(rel) = relative_to(root, &dep) else {
+                continue;
+            };
+            inferred.extend(declared_remappings(&dep, &rel));
+            if depth == 0 {
+                top_level.push(default_remapping(&dep, &rel));
+            } else {
+                inferred.push(default_remapping(&dep, &rel));
+            }
+            if depth + 1 < MAX_DEPTH {
+                queue.push((dep.join("lib"), depth + 1));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    top_level
+        .into_iter()
+        .chain(inferred)
+        .filter(|remapping: &Remapping| {
+            seen.insert((
+                remapping.context().map(str::to_string),
+                remapping.from().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Parses `remappings.txt` contents: one `[context:]from=to` per line, `#` comments allowed.
+pub fn parse_remappings_txt(text: &str) -> Vec<Remapping> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(parse_remapping)
+        .collect()
+}
+
+fn parse_remapping(value: &str) -> Option<Remapping> {
+    let (lhs, to) = value.split_once('=')?;
+    let (context, from) = match lhs.split_once(':') {
+        Some((context, from)) => (Some(context), from),
+        None => (None, lhs),
+    };
+    let (from, to) = (from.trim(), to.trim());
+    if from.is_empty() || to.is_empty() {
+        return None;
+    }
+    let remapping = Remapping::new(from, to);
+    Some(
+        match context.map(str::trim).filter(|context| !context.is_empty()) {
+            Some(context) => remapping.with_context(context),
+            None => remapping,
+        },
+    )
+}
+
+fn dependency_dirs(lib_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(lib_dir) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| !name.starts_with('.'))
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+fn default_remapping(dep: &Path, rel: &str) -> Remapping {
+    let name = dep
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let to = if dep.join("src").is_dir() {
+        format!("{rel}/src/")
+    } else {
+        format!("{rel}/")
+    };
+    Remapping::new(format!("{name}/"), to)
+}
+
+/// Remappings the dependency declares itself, rebased from its own root onto `rel`.
+fn declared_remappings(dep: &Path, rel: &str) -> Vec<Remapping> {
+    let declared = match fs::read_to_string(dep.join(REMAPPINGS_TXT)) {
+        Ok(text) => parse_remappings_txt(&text),
+        Err(_) => fs::read_to_string(dep.join(FOUNDRY_TOML))
+            .map(|text| foundry_toml_remappings(&text))
+            .unwrap_or_default(),
+    };
+    declared
+        .into_iter()
+        .map(|remapping| {
+            let to = format!("{rel}/{}", remapping.to().trim_start_matches("./"));
+            let rebased = Remapping::new(remapping.from(), to);
+            match remapping.context() {
+                Some(context) => {
+                    rebased.with_context(format!("{rel}/{}", context.trim_start_matches("./")))
+                }
+                None => rebased,
+            }
+        })
+        .collect()
+}
+
+/// Reads the `remappings = [...]` array of the default profile without a full TOML parser.
+fn foundry_toml_remappings(text: &str) -> Vec<Remapping> {
+    let mut in_default = false;
+    let mut collecting = false;
+    let mut values = Vec::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if !collecting && line.starts_with('[') {
+            in_default = line == "[profile.default]";
+            continue;
+        }
+        let rest = if collecting {
+            line
+        } else if in_default
+            && let Some(rest) = line.strip_prefix("remappings")
+            && let Some(rest) = rest.trim_start().strip_prefix('=')
+        {
+            collecting = true;
+            rest
+        } else {
+            continue;
+        };
+        values.extend(quoted_strings(rest));
+        if rest.contains(']') {
+            break;
+        }
+    }
+    values
+        .iter()
+        .filter_map(|value| parse_remapping(value))
+        .collect()
+}
+
+fn quoted_strings(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['"', '\'']) {
+        let quote = rest.as_bytes()[start] as char;
+        let body = &rest[start + 1..];
+        let Some(end) = body.find(quote) else {
+            break;
+        };
+        values.push(body[..end].to_string());
+        rest = &body[end + 1..];
+    }
+    values
+}
+
+fn relative_to(root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?;
+    Some(rel.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{foundry_toml_remappings, parse_remappings_txt};
+    use crate::Remapping;
+
+    #[test]
+    fn parses_remappings_txt_with_contexts_and_comments() {
+        let text = "# deps\nforge-std/=lib/forge-std/src/\nsrc:@oz/=lib/oz/contracts/\n\nbroken\n";
+        assert_eq!(
+            parse_remappings_txt(text),
+            vec![
+                Remapping::new("forge-std/", "lib/forge-std/src/"),
+                Remapping::new("@oz/", "lib/oz/contracts/").with_context("src"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_default_profile_remappings_from_foundry_toml() {
+        let text = r#"
+[profile.default]
+src = "src"
+remappings = [
+    "solmate/=lib/solmate/src/", # trailing comment
+    'ds-test/=lib/ds-test/src/',
+]
+
+[profile.ci]
+remappings = ["ignored/=ignored/"]
+"#;
+        assert_eq!(
+            foundry_toml_remappings(text),
+            vec![
+                Remapping::new("solmate/", "lib/solmate/src/"),
+                Remapping::new("ds-test/", "lib/ds-test/src/"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::Path;
+
+use sa_paths::NormalizedPath;
+use sa_project_model::{FoundryWorkspace, Remapping, discover_dependencies};
+use tempfile::tempdir;
+
+fn write(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    fs::write(path, text).expect("write file");
+}
+
+#[test]
+fn discovers_packages_under_every_library_root() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(&root.join("lib/forge-std/src/Test.sol"), "contract Test {}");
+    write(
+        &root.join("node_modules/@openzeppelin/contracts/package.json"),
+        r#"{ "name": "@openzeppelin/contracts", "version": "5.0.2" }"#,
+    );
+    write(&root.join("lib/.git/HEAD"), "ref: refs/heads/main");
+
+    let root_path = NormalizedPath::new(root.to_string_lossy());
+    let workspace = FoundryWorkspace::new(root_path.clone()).with_libs(vec![
+        NormalizedPath::new(format!("{}/lib", root_path.as_str())),
+        NormalizedPath::new(format!("{}/node_modules", root_path.as_str())),
+    ]);
+    let remappings = vec![
+        Remapping::new("forge-std/", "lib/forge-std/src/"),
+        Remapping::new("@openzeppelin/", "node_modules/@openzeppelin/"),
+        Remapping::new(
+            "@openzeppelin/contracts/",
+            "node_modules/@openzeppelin/contracts/",
+        ),
+    ];
+
+    let packages = discover_dependencies(&workspace, &remappings);
+
+    let names = packages
+        .iter()
+        .map(|package| package.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["@openzeppelin/contracts", "forge-std"]);
+
+    let oz = &packages[0];
+    assert_eq!(oz.version(), Some("5.0.2"));
+    assert_eq!(
+        oz.path().as_str(),
+        format!(
+            "{}/node_modules/@openzeppelin/contracts",
+            root_path.as_str()
+        )
+    );
+    assert_eq!(
+        oz.remapping().map(Remapping::from),
+        Some("@openzeppelin/contracts/")
+    );
+
+    let forge_std = &packages[1];
+    assert_eq!(forge_std.version(), None);
+    assert_eq!(
+        forge_std.remapping().map(Remapping::from),
+        Some("forge-std/")
+    );
+}
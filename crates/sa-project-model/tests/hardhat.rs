@@ -0,0 +1,85 @@
+use std::fs;
+
+use sa_paths::NormalizedPath;
+use sa_project_model::{FoundryResolver, HardhatProject, contains_hardhat_config};
+use tempfile::tempdir;
+
+#[test]
+fn hardhat_project_uses_default_layout() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    fs::write(
+        root.join("hardhat.config.js"),
+        r#"module.exports = { solidity: "0.8.24" };"#,
+    )
+    .expect("write hardhat config");
+
+    assert!(contains_hardhat_config(&root));
+    let project = HardhatProject::load(&root).expect("load hardhat project");
+    let workspace = project.workspace();
+    let root_str = root.to_string_lossy();
+
+    assert_eq!(workspace.root().as_str(), root_str);
+    assert_eq!(workspace.src().as_str(), format!("{root_str}/contracts"));
+    assert_eq!(workspace.test().as_str(), format!("{root_str}/test"));
+    assert_eq!(workspace.script().as_str(), format!("{root_str}/scripts"));
+    assert_eq!(workspace.lib().as_str(), format!("{root_str}/node_modules"));
+    assert_eq!(project.solc_version(), Some("0.8.24"));
+}
+
+#[test]
+fn hardhat_project_honors_configured_paths() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let config = r#"
+export default {
+  solidity: { version: "0.8.20" },
+  paths: { sources: "./src/contracts", tests: "./spec" },
+};
+"#;
+    fs::write(root.join("hardhat.config.ts"), config).expect("write hardhat config");
+
+    let project = HardhatProject::load(&root).expect("load hardhat project");
+    let root_str = root.to_string_lossy();
+
+    assert_eq!(
+        project.workspace().src().as_str(),
+        format!("{root_str}/src/contracts")
+    );
+    assert_eq!(
+        project.workspace().test().as_str(),
+        format!("{root_str}/spec")
+    );
+    assert_eq!(project.solc_version(), Some("0.8.20"));
+}
+
+#[test]
+fn hardhat_project_resolves_node_modules_imports() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    fs::write(root.join("hardhat.config.js"), "module.exports = {};").expect("write config");
+    let token_dir = root.join("node_modules/@openzeppelin/contracts/token/ERC20");
+    fs::create_dir_all(&token_dir).expect("create node_modules");
+    fs::create_dir_all(root.join("contracts")).expect("create contracts");
+    fs::write(token_dir.join("ERC20.sol"), "contract ERC20 {}").expect("write dep");
+
+    let project = HardhatProject::load(&root).expect("load hardhat project");
+    assert_eq!(project.solc_version(), None);
+
+    let resolver = FoundryResolver::new(project.workspace(), &[]).expect("resolver");
+    let current = NormalizedPath::new(root.join("contracts/Token.sol").to_string_lossy());
+    let resolved = resolver
+        .resolve_import_path(&current, "@openzeppelin/contracts/token/ERC20/ERC20.sol")
+        .expect("resolved import");
+    assert_eq!(
+        resolved,
+        NormalizedPath::new(token_dir.join("ERC20.sol").to_string_lossy())
+    );
+}
+
+#[test]
+fn load_fails_without_hardhat_config() {
+    let temp = tempdir().expect("tempdir");
+    assert!(!contains_hardhat_config(temp.path()));
+    assert!(HardhatProject::load(temp.path()).is_err());
+}
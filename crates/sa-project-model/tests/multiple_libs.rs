@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::Path;
+
+use sa_paths::NormalizedPath;
+use sa_project_model::{FoundryResolver, FoundryWorkspace};
+use tempfile::tempdir;
+
+fn write(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    fs::write(path, text).expect("write file");
+}
+
+fn normalized(path: &Path) -> NormalizedPath {
+    NormalizedPath::new(path.to_string_lossy())
+}
+
+#[test]
+fn resolves_imports_from_every_lib_and_include_path() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    fs::create_dir_all(root.join("src")).expect("src dir");
+    let from_lib = root.join("lib/solmate/src/Owned.sol");
+    let from_deps = root.join("deps/permit2/src/Permit2.sol");
+    let from_vendor = root.join("vendor/utils/Math.sol");
+    write(&from_lib, "contract Owned {}");
+    write(&from_deps, "contract Permit2 {}");
+    write(&from_vendor, "library Math {}");
+
+    let workspace = FoundryWorkspace::new(normalized(&root))
+        .with_libs(vec![
+            normalized(&root.join("lib")),
+            normalized(&root.join("deps")),
+        ])
+        .with_include_paths(vec![normalized(&root.join("vendor"))]);
+    let resolver = FoundryResolver::new(&workspace, &[]).expect("resolver");
+    let importer = normalized(&root.join("src/Main.sol"));
+
+    assert_eq!(
+        resolver.resolve_import_path(&importer, "solmate/src/Owned.sol"),
+        Some(normalized(&from_lib))
+    );
+    assert_eq!(
+        resolver.resolve_import_path(&importer, "permit2/src/Permit2.sol"),
+        Some(normalized(&from_deps))
+    );
+    assert_eq!(
+        resolver.resolve_import_path(&importer, "utils/Math.sol"),
+        Some(normalized(&from_vendor))
+    );
+}
+
+#[test]
+fn library_files_are_below_a_lib_root_but_not_project_dirs() {
+    let root = NormalizedPath::new("/workspace");
+    let workspace = FoundryWorkspace::new(root).with_libs(vec![
+        NormalizedPath::new("/workspace/lib"),
+        NormalizedPath::new("/workspace/node_modules"),
+    ]);
+
+    assert!(
+        workspace.is_library_file(&NormalizedPath::new("/workspace/lib/solmate/src/Owned.sol"))
+    );
+    assert!(workspace.is_library_file(&NormalizedPath::new(
+        "/workspace/node_modules/@openzeppelin/contracts/access/Ownable.sol"
+    )));
+    assert!(!workspace.is_library_file(&NormalizedPath::new("/workspace/src/Main.sol")));
+    assert!(!workspace.is_library_file(&NormalizedPath::new("/workspace/library/Math.sol")));
+
+    let nested = FoundryWorkspace::from_paths(
+        NormalizedPath::new("/workspace"),
+        NormalizedPath::new("/workspace/src"),
+        NormalizedPath::new("/workspace"),
+        NormalizedPath::new("/workspace/test"),
+        NormalizedPath::new("/workspace/script"),
+    );
+    assert!(!nested.is_library_file(&NormalizedPath::new("/workspace/test/Main.t.sol")));
+    assert!(nested.is_library_file(&NormalizedPath::new("/workspace/vendor/Math.sol")));
+}
+
+#[test]
+fn dependency_files_include_node_modules_and_soldeer_dependencies() {
+    let workspace = FoundryWorkspace::new(NormalizedPath::new("/workspace"))
+        .with_libs(vec![NormalizedPath::new("/workspace/lib")]);
+
+    assert!(workspace.is_dependency_file(&NormalizedPath::new(
+        "/workspace/lib/forge-std/src/Test.sol"
+    )));
+    assert!(workspace.is_dependency_file(&NormalizedPath::new(
+        "/workspace/node_modules/@openzeppelin/contracts/token/ERC20/ERC20.sol"
+    )));
+    assert!(workspace.is_dependency_file(&NormalizedPath::new(
+        "/workspace/dependencies/solady-0.1.0/src/utils/LibString.sol"
+    )));
+    assert!(!workspace.is_dependency_file(&NormalizedPath::new("/workspace/src/Main.sol")));
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::Path;
+
+use sa_paths::NormalizedPath;
+use sa_project_model::{
+    FoundryResolver, FoundryWorkspace, NodeModulesCache, resolve_node_modules_import,
+};
+use tempfile::tempdir;
+
+fn write(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    fs::write(path, text).expect("write file");
+}
+
+fn normalized(path: &Path) -> NormalizedPath {
+    NormalizedPath::new(path.to_string_lossy())
+}
+
+#[test]
+fn resolves_through_parent_node_modules() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let dep = root.join("node_modules/@openzeppelin/contracts/token/ERC20/ERC20.sol");
+    write(&dep, "contract ERC20 {}");
+
+    let importer = normalized(&root.join("packages/core/src/Token.sol"));
+    let resolved =
+        resolve_node_modules_import(&importer, "@openzeppelin/contracts/token/ERC20/ERC20.sol");
+
+    assert_eq!(resolved, Some(normalized(&dep)));
+}
+
+#[test]
+fn nearest_node_modules_wins() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(
+        &root.join("node_modules/solmate/src/Owned.sol"),
+        "contract Owned {}",
+    );
+    let nested = root.join("packages/core/node_modules/solmate/src/Owned.sol");
+    write(&nested, "contract Owned {}");
+
+    let importer = normalized(&root.join("packages/core/src/Token.sol"));
+    let resolved = resolve_node_modules_import(&importer, "solmate/src/Owned.sol");
+
+    assert_eq!(resolved, Some(normalized(&nested)));
+}
+
+#[test]
+fn honors_package_exports_and_main() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let package = root.join("node_modules/@acme/sol");
+    write(
+        &package.join("package.json"),
+        r#"{ "main": "./contracts/Index.sol", "exports": { "./*": "./contracts/*" } }"#,
+    );
+    write(&package.join("contracts/Vault.sol"), "contract Vault {}");
+    write(&package.join("contracts/Index.sol"), "contract Index {}");
+
+    let importer = normalized(&root.join("src/Main.sol"));
+
+    assert_eq!(
+        resolve_node_modules_import(&importer, "@acme/sol/Vault.sol"),
+        Some(normalized(&package.join("contracts/Vault.sol")))
+    );
+    assert_eq!(
+        resolve_node_modules_import(&importer, "@acme/sol"),
+        None,
+        "`main` is ignored when `exports` is present"
+    );
+
+    write(
+        &package.join("package.json"),
+        r#"{ "main": "./contracts/Index.sol" }"#,
+    );
+    assert_eq!(
+        resolve_node_modules_import(&importer, "@acme/sol"),
+        Some(normalized(&package.join("contracts/Index.sol")))
+    );
+}
+
+#[test]
+fn exports_hide_unlisted_subpaths() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let package = root.join("node_modules/@acme/sol");
+    write(
+        &package.join("package.json"),
+        r#"{ "exports": { "./token/*": "./contracts/token/*" } }"#,
+    );
+    write(
+        &package.join("contracts/token/Token.sol"),
+        "contract Token {}",
+    );
+    write(&package.join("internal/Secret.sol"), "contract Secret {}");
+
+    let importer = normalized(&root.join("src/Main.sol"));
+
+    assert_eq!(
+        resolve_node_modules_import(&importer, "@acme/sol/token/Token.sol"),
+        Some(normalized(&package.join("contracts/token/Token.sol")))
+    );
+    assert_eq!(
+        resolve_node_modules_import(&importer, "@acme/sol/internal/Secret.sol"),
+        None
+    );
+}
+
+#[test]
+fn cache_keeps_package_locations_until_cleared() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let cache = NodeModulesCache::default();
+    let importer = normalized(&root.join("src/Main.sol"));
+
+    assert_eq!(cache.resolve(&importer, "solmate/src/Owned.sol"), None);
+
+    let dep = root.join("node_modules/solmate/src/Owned.sol");
+    write(&dep, "contract Owned {}");
+    assert_eq!(
+        cache.resolve(&importer, "solmate/src/Owned.sol"),
+        None,
+        "a package installed later is not seen through the cached lookup"
+    );
+
+    cache.clear();
+    assert_eq!(
+        cache.resolve(&importer, "solmate/src/Owned.sol"),
+        Some(normalized(&dep))
+    );
+}
+
+#[test]
+fn resolves_pnpm_store_siblings() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let store = root.join("node_modules/.pnpm/@acme+vault@1.0.0/node_modules");
+    let dep = store.join("solmate/src/Owned.sol");
+    write(&dep, "contract Owned {}");
+
+    let importer = normalized(&store.join("@acme/vault/contracts/Vault.sol"));
+    let resolved = resolve_node_modules_import(&importer, "solmate/src/Owned.sol");
+
+    assert_eq!(resolved, Some(normalized(&dep)));
+}
+
+#[test]
+fn resolves_yarn_pnp_unplugged_packages() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(&root.join(".pnp.cjs"), "");
+    let dep = root.join(".yarn/unplugged/solmate-npm-6.2.0-abc/node_modules/solmate/src/Owned.sol");
+    write(&dep, "contract Owned {}");
+
+    let importer = normalized(&root.join("contracts/Main.sol"));
+    let resolved = resolve_node_modules_import(&importer, "solmate/src/Owned.sol");
+
+    assert_eq!(resolved, Some(normalized(&dep)));
+}
+
+#[test]
+fn resolver_falls_back_to_node_modules() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let dep = root.join("node_modules/@openzeppelin/contracts/access/Ownable.sol");
+    write(&dep, "contract Ownable {}");
+    fs::create_dir_all(root.join("src")).expect("src dir");
+
+    let workspace = FoundryWorkspace::new(normalized(&root));
+    let resolver = FoundryResolver::new(&workspace, &[]).expect("resolver");
+    let importer = normalized(&root.join("src/Main.sol"));
+
+    assert_eq!(
+        resolver.resolve_import_path(&importer, "@openzeppelin/contracts/access/Ownable.sol"),
+        Some(normalized(&dep))
+    );
+}
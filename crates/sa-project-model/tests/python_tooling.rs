@@ -0,0 +1,73 @@
+use std::fs;
+
+use sa_paths::NormalizedPath;
+use sa_project_model::{
+    FoundryResolver, PythonFramework, PythonProject, PythonProjectConfig,
+    contains_python_project_config, find_python_project_config,
+};
+use tempfile::tempdir;
+
+#[test]
+fn ape_project_uses_contracts_folder_and_default_layout() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    fs::write(
+        root.join("ape-config.yaml"),
+        "name: token\nsolidity:\n  version: 0.8.20\n",
+    )
+    .expect("write ape config");
+
+    assert!(contains_python_project_config(&root));
+    let project = PythonProject::load(&root).expect("load ape project");
+    let workspace = project.workspace();
+    let root_str = root.to_string_lossy();
+
+    assert_eq!(project.framework(), PythonFramework::Ape);
+    assert_eq!(workspace.src().as_str(), format!("{root_str}/contracts"));
+    assert_eq!(workspace.test().as_str(), format!("{root_str}/tests"));
+    assert_eq!(workspace.script().as_str(), format!("{root_str}/scripts"));
+    assert_eq!(project.solc_version(), Some("0.8.20"));
+}
+
+#[test]
+fn brownie_project_resolves_installed_packages() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    let project_dir = root.join("project");
+    let packages = root.join("packages");
+    let token_dir = packages.join("OpenZeppelin/openzeppelin-contracts@4.8.0/contracts/token");
+    fs::create_dir_all(project_dir.join("contracts")).expect("create contracts");
+    fs::create_dir_all(&token_dir).expect("create package");
+    fs::write(token_dir.join("ERC20.sol"), "contract ERC20 {}").expect("write dep");
+    let text = r#"
+compiler:
+  solc:
+    remappings:
+      - "@openzeppelin=OpenZeppelin/openzeppelin-contracts@4.8.0"
+"#;
+    fs::write(project_dir.join("brownie-config.yaml"), text).expect("write brownie config");
+
+    let (framework, _) = find_python_project_config(&project_dir).expect("brownie config");
+    assert_eq!(framework, PythonFramework::Brownie);
+    let config = PythonProjectConfig::parse(framework, text);
+    let project = PythonProject::from_config(
+        NormalizedPath::new(project_dir.to_string_lossy()),
+        &config,
+        Some(NormalizedPath::new(packages.to_string_lossy())),
+    );
+    assert_eq!(
+        project.workspace().lib().as_str(),
+        packages.to_string_lossy()
+    );
+
+    let resolver =
+        FoundryResolver::new(project.workspace(), project.remappings()).expect("resolver");
+    let current = NormalizedPath::new(project_dir.join("contracts/Token.sol").to_string_lossy());
+    let resolved = resolver
+        .resolve_import_path(&current, "@openzeppelin/contracts/token/ERC20.sol")
+        .expect("resolved import");
+    assert_eq!(
+        resolved,
+        NormalizedPath::new(token_dir.join("ERC20.sol").to_string_lossy())
+    );
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sa_project_model::{Remapping, infer_remappings};
+use tempfile::tempdir;
+
+fn write(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    fs::write(path, text).expect("write file");
+}
+
+fn target<'a>(remappings: &'a [Remapping], from: &str) -> Option<&'a str> {
+    remappings
+        .iter()
+        .find(|remapping| remapping.context().is_none() && remapping.from() == from)
+        .map(Remapping::to)
+}
+
+#[test]
+fn infers_src_and_root_remappings_for_each_library() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(&root.join("lib/forge-std/src/Test.sol"), "contract Test {}");
+    write(&root.join("lib/flat/Flat.sol"), "contract Flat {}");
+
+    let remappings = infer_remappings(&root, &[PathBuf::from("lib")]);
+
+    assert_eq!(
+        target(&remappings, "forge-std/"),
+        Some("lib/forge-std/src/")
+    );
+    assert_eq!(target(&remappings, "flat/"), Some("lib/flat/"));
+}
+
+#[test]
+fn rebases_dependency_declared_remappings() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(
+        &root.join("lib/oz/remappings.txt"),
+        "@openzeppelin/=contracts/\n",
+    );
+    write(
+        &root.join("lib/oz/contracts/Ownable.sol"),
+        "contract Ownable {}",
+    );
+    write(
+        &root.join("lib/solady/foundry.toml"),
+        "[profile.default]\nremappings = [\"forge-std/=test/utils/forge-std/\"]\n",
+    );
+    write(&root.join("lib/solady/src/Lib.sol"), "library Lib {}");
+
+    let remappings = infer_remappings(&root, &[PathBuf::from("lib")]);
+
+    assert_eq!(
+        target(&remappings, "@openzeppelin/"),
+        Some("lib/oz/contracts/")
+    );
+    assert_eq!(
+        target(&remappings, "forge-std/"),
+        Some("lib/solady/test/utils/forge-std/")
+    );
+    assert_eq!(target(&remappings, "solady/"), Some("lib/solady/src/"));
+}
+
+#[test]
+fn nested_libraries_do_not_shadow_top_level_ones() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(&root.join("lib/forge-std/src/Test.sol"), "contract Test {}");
+    write(
+        &root.join("lib/dep/lib/forge-std/src/Test.sol"),
+        "contract Test {}",
+    );
+    write(
+        &root.join("lib/dep/lib/ds-test/src/test.sol"),
+        "contract DSTest {}",
+    );
+    write(&root.join("lib/dep/src/Dep.sol"), "contract Dep {}");
+
+    let remappings = infer_remappings(&root, &[PathBuf::from("lib")]);
+
+    assert_eq!(
+        target(&remappings, "forge-std/"),
+        Some("lib/forge-std/src/")
+    );
+    assert_eq!(
+        target(&remappings, "ds-test/"),
+        Some("lib/dep/lib/ds-test/src/")
+    );
+    assert_eq!(target(&remappings, "dep/"), Some("lib/dep/src/"));
+}
+
+#[test]
+fn top_level_libraries_beat_remappings_declared_by_siblings() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    write(
+        &root.join("lib/dep/remappings.txt"),
+        "forge-std/=lib/forge-std/src/\n",
+    );
+    write(
+        &root.join("lib/dep/lib/forge-std/src/Test.sol"),
+        "contract Test {}",
+    );
+    write(&root.join("lib/dep/src/Dep.sol"), "contract Dep {}");
+    write(&root.join("lib/forge-std/src/Test.sol"), "contract Test {}");
+
+    let remappings = infer_remappings(&root, &[PathBuf::from("lib")]);
+
+    assert_eq!(
+        target(&remappings, "forge-std/"),
+        Some("lib/forge-std/src/")
+    );
+    assert_eq!(target(&remappings, "dep/"), Some("lib/dep/src/"));
+}
@@ -0,0 +1,22 @@
+use sa_base_db::FileId;
+use sa_span::TextRange;
+
+use crate::SemaSnapshot;
+
+impl SemaSnapshot {
+    /// Returns the ABI of the contract named `name` at `name_range` as the JSON array
+    /// `solc --abi` prints: functions (including public getters), events, errors, and the
+    /// constructor, receive and fallback functions.
+    pub fn contract_abi(
+        &self,
+        file_id: FileId,
+        name_range: TextRange,
+        name: &str,
+    ) -> Option<serde_json::Value> {
+        self.with_gcx(|gcx| {
+            let item_id = self.item_id_for_name_range(gcx, file_id, name_range, name, None)?;
+            let contract_id = item_id.as_contract()?;
+            serde_json::to_value(gcx.contract_abi(contract_id)).ok()
+        })
+    }
+}
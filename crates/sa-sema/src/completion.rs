@@ -4,8 +4,9 @@ use sa_base_db::FileId;
 use sa_span::{TextRange, TextSize, is_ident_byte, range_contains};
 use sa_syntax::Parse;
 use sa_syntax::ast::{Item, ItemKind, Stmt, StmtKind, TypeKind as AstTypeKind, VariableDefinition};
+use sa_syntax::cst::parse_cst;
 use solar::ast::{FunctionKind, ImportItems, ItemKind as SolarItemKind};
-use solar::interface::{Ident, Span};
+use solar::interface::{Ident, Span, Symbol};
 use solar::sema::builtins::Member;
 use solar::sema::hir;
 use solar::sema::ty::TyKind;
@@ -38,16 +39,24 @@ pub enum SemaCompletionKind {
 }
 
 impl SemaSnapshot {
-    pub fn identifier_completions(
+    /// Completions for the identifier at `offset` whose names start with `prefix`, ignoring ASCII
+    /// case. `visible_names` are the file-level names in scope that match `prefix`, as found by
+    /// the HIR prefix index; only those get resolved rather than everything the imports bring in.
+    pub fn identifier_completions<'a>(
         &self,
         file_id: FileId,
         offset: TextSize,
+        prefix: &str,
+        visible_names: impl IntoIterator<Item = &'a str>,
     ) -> Option<Vec<SemaCompletionItem>> {
         let source_id = self.source_id_for_file(file_id)?;
+        let visible_names = visible_names.into_iter().collect::<Vec<_>>();
         let items = self.with_gcx(|gcx| {
             match identifier_completion_context(self, gcx, source_id, offset) {
                 IdentifierCompletionContext::StructLiteralFields { struct_id } => {
-                    struct_field_completion_items(gcx, struct_id)
+                    let mut items = struct_field_completion_items(gcx, struct_id);
+                    items.retain(|item| starts_with_ignore_case(&item.label, prefix));
+                    items
                 }
                 IdentifierCompletionContext::Scope {
                     contract_id,
@@ -56,18 +65,23 @@ impl SemaSnapshot {
                     let mut items = Vec::new();
                     let mut seen = HashSet::new();
 
-                    collect_source_items(gcx, source_id, &mut items, &mut seen);
-                    collect_imported_items(gcx, source_id, &mut items, &mut seen);
+                    for name in &visible_names {
+                        if let Some(item) = visible_item(gcx, source_id, name) {
+                            push_completion_item(&mut items, &mut seen, item);
+                        }
+                    }
 
                     if let Some(contract_id) = contract_id {
-                        for item in contract_scope_items(gcx, contract_id) {
+                        for item in contract_scope_items(gcx, contract_id, prefix) {
                             push_completion_item(&mut items, &mut seen, item);
                         }
                     }
 
                     if let Some(function_id) = function_id {
                         for item in local_items_for_function(self, gcx, function_id, offset) {
-                            push_completion_item(&mut items, &mut seen, item);
+                            if starts_with_ignore_case(&item.label, prefix) {
+                                push_completion_item(&mut items, &mut seen, item);
+                            }
                         }
                     }
 
@@ -230,88 +244,61 @@ fn push_completion_item(
     }
 }
 
-fn collect_source_items(
-    gcx: Gcx<'_>,
-    source_id: hir::SourceId,
-    items: &mut Vec<SemaCompletionItem>,
-    seen: &mut HashSet<(String, SemaCompletionKind)>,
-) {
-    let source = gcx.hir.source(source_id);
-    for &item_id in source.items {
-        if let Some(item) = completion_item_for_item(gcx, item_id) {
-            push_completion_item(items, seen, item);
-        }
+/// Resolves a file-level name visible in `source_id`, following imports and their aliases.
+fn visible_item(gcx: Gcx<'_>, source_id: hir::SourceId, name: &str) -> Option<SemaCompletionItem> {
+    let symbol = Symbol::intern(name);
+    if let Some(item_id) = exports::find_exported_item(gcx, source_id, symbol) {
+        let mut item = completion_item_for_item(gcx, item_id)?;
+        item.label = name.to_string();
+        return Some(item);
     }
-}
-
-fn collect_imported_items(
-    gcx: Gcx<'_>,
-    source_id: hir::SourceId,
-    items: &mut Vec<SemaCompletionItem>,
-    seen: &mut HashSet<(String, SemaCompletionKind)>,
-) {
-    let Some(source) = gcx.sources.get(source_id) else {
-        return;
-    };
     let hir_source = gcx.hir.source(source_id);
-    let Some(ast) = source.ast.as_ref() else {
-        for &(_, import_source_id) in hir_source.imports {
-            let imported = gcx.hir.source(import_source_id);
-            for &imported_item_id in imported.items {
-                if let Some(item) = completion_item_for_item(gcx, imported_item_id) {
-                    push_completion_item(items, seen, item);
-                }
-            }
-        }
-        return;
-    };
-
-    for (item_id, item) in ast.items.iter_enumerated() {
-        let SolarItemKind::Import(import) = &item.kind else {
-            continue;
-        };
-        let Some(import_source_id) = hir_source
+    let Some(ast) = gcx
+        .sources
+        .get(source_id)
+        .and_then(|source| source.ast.as_ref())
+    else {
+        // Without an AST only the imported sources are known, not what each import selects.
+        let item_id = hir_source
             .imports
             .iter()
-            .find_map(|(import_id, source_id)| (*import_id == item_id).then_some(*source_id))
-        else {
-            continue;
-        };
+            .find_map(|&(_, import_source_id)| {
+                gcx.hir
+                    .source(import_source_id)
+                    .items
+                    .iter()
+                    .copied()
+                    .find(|&item_id| {
+                        gcx.hir
+                            .item(item_id)
+                            .name()
+                            .is_some_and(|ident| ident.name == symbol)
+                    })
+            })?;
+        return completion_item_for_item(gcx, item_id);
+    };
+    let is_module_alias = ast.items.iter().any(|item| {
+        matches!(
+            &item.kind,
+            SolarItemKind::Import(import)
+                if matches!(
+                    &import.items,
+                    ImportItems::Plain(Some(alias)) | ImportItems::Glob(alias)
+                        if alias.name == symbol
+                )
+        )
+    });
+    is_module_alias.then(|| SemaCompletionItem {
+        label: name.to_string(),
+        kind: SemaCompletionKind::Type,
+        detail: None,
+        origin: None,
+    })
+}
 
-        match &import.items {
-            ImportItems::Plain(None) => {
-                for imported_item_id in exports::exported_item_ids(gcx, import_source_id) {
-                    if let Some(item) = completion_item_for_item(gcx, imported_item_id) {
-                        push_completion_item(items, seen, item);
-                    }
-                }
-            }
-            ImportItems::Plain(Some(alias)) | ImportItems::Glob(alias) => {
-                let item = SemaCompletionItem {
-                    label: alias.as_str().to_string(),
-                    kind: SemaCompletionKind::Type,
-                    detail: None,
-                    origin: None,
-                };
-                push_completion_item(items, seen, item);
-            }
-            ImportItems::Aliases(aliases) => {
-                for (original, alias) in aliases.iter() {
-                    let alias_ident = alias.as_ref().unwrap_or(original);
-                    let Some(item_id) =
-                        exports::find_exported_item(gcx, import_source_id, original.name)
-                    else {
-                        continue;
-                    };
-                    let Some(mut item) = completion_item_for_item(gcx, item_id) else {
-                        continue;
-                    };
-                    item.label = alias_ident.as_str().to_string();
-                    push_completion_item(items, seen, item);
-                }
-            }
-        }
-    }
+fn starts_with_ignore_case(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len()
+        && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
 }
 
 fn function_at_offset(
@@ -1180,7 +1167,7 @@ fn variable_name_matches(gcx: Gcx<'_>, var_id: hir::VariableId, receiver: &str)
     name.as_str() == receiver
 }
 
-fn contract_at_offset(
+pub(crate) fn contract_at_offset(
     snapshot: &SemaSnapshot,
     gcx: Gcx<'_>,
     source_id: hir::SourceId,
@@ -1211,236 +1198,19 @@ fn contract_at_offset(
     }
 
     let source = gcx.hir.source(source_id);
-    let name = contract_name_at_offset_fallback(source.file.src.as_str(), offset)?;
+    let cst = parse_cst(source.file.src.as_str());
+    let name = cst.contract_at_offset(offset)?.name()?.text();
     gcx.hir.contract_ids().find(|id| {
         let contract = gcx.hir.contract(*id);
         contract.source == source_id && contract.name.as_str() == name
     })
 }
 
-fn contract_name_at_offset_fallback(text: &str, offset: TextSize) -> Option<String> {
-    let offset = usize::from(offset).min(text.len());
-    let bytes = text.as_bytes();
-    if bytes.is_empty() {
-        return None;
-    }
-
-    let mut scanner = FallbackScanner::new(text);
-    let mut brace_depth = 0usize;
-
-    while let Some(token) = scanner.next_token() {
-        match token.kind {
-            FallbackTokenKind::Punct('{') => brace_depth += 1,
-            FallbackTokenKind::Punct('}') => brace_depth = brace_depth.saturating_sub(1),
-            FallbackTokenKind::Ident(ref ident)
-                if brace_depth == 0 && is_contract_keyword(ident) =>
-            {
-                if let Some(decl) = parse_contract_decl(&mut scanner, text, token.start) {
-                    brace_depth = 0;
-                    if offset >= decl.start && offset <= decl.close_brace {
-                        return Some(decl.name);
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-
-    None
-}
-
-fn is_contract_keyword(ident: &str) -> bool {
-    matches!(ident, "contract" | "library" | "interface")
-}
-
-struct ContractDecl {
-    start: usize,
-    close_brace: usize,
-    name: String,
-}
-
-fn parse_contract_decl(
-    scanner: &mut FallbackScanner<'_>,
-    text: &str,
-    contract_start: usize,
-) -> Option<ContractDecl> {
-    let name_token = scanner.next_token()?;
-    let name = match name_token.kind {
-        FallbackTokenKind::Ident(ident) => ident,
-        _ => return None,
-    };
-
-    let mut open_brace: Option<usize> = None;
-    while let Some(token) = scanner.next_token() {
-        if let FallbackTokenKind::Punct('{') = token.kind {
-            open_brace = Some(token.start);
-            break;
-        }
-    }
-    let open_brace = open_brace?;
-    let close_brace = matching_close_brace(text, open_brace).unwrap_or(text.len());
-    scanner.idx = close_brace.saturating_add(1);
-
-    Some(ContractDecl {
-        start: contract_start,
-        close_brace,
-        name,
-    })
-}
-
-fn matching_close_brace(text: &str, open_brace: usize) -> Option<usize> {
-    let bytes = text.as_bytes();
-    if open_brace >= bytes.len() {
-        return None;
-    }
-    let mut depth = 0usize;
-    let mut i = open_brace.saturating_add(1);
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b == b'/' && i + 1 < bytes.len() {
-            let next = bytes[i + 1];
-            if next == b'/' {
-                i += 2;
-                while i < bytes.len() && bytes[i] != b'\n' {
-                    i += 1;
-                }
-                continue;
-            }
-            if next == b'*' {
-                i += 2;
-                while i + 1 < bytes.len() {
-                    if bytes[i] == b'*' && bytes[i + 1] == b'/' {
-                        i += 2;
-                        break;
-                    }
-                    i += 1;
-                }
-                continue;
-            }
-        }
-        if b == b'\'' || b == b'"' {
-            i = skip_string(bytes, i);
-            continue;
-        }
-        if b == b'{' {
-            depth += 1;
-        } else if b == b'}' {
-            if depth == 0 {
-                return Some(i);
-            }
-            depth = depth.saturating_sub(1);
-        }
-        i += 1;
-    }
-    None
-}
-
-fn skip_string(bytes: &[u8], start: usize) -> usize {
-    let quote = bytes[start];
-    let mut i = start.saturating_add(1);
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b == b'\\' {
-            i = i.saturating_add(2);
-            continue;
-        }
-        if b == quote {
-            return i.saturating_add(1);
-        }
-        i += 1;
-    }
-    bytes.len()
-}
-
-struct FallbackScanner<'a> {
-    bytes: &'a [u8],
-    idx: usize,
-}
-
-struct FallbackToken {
-    kind: FallbackTokenKind,
-    start: usize,
-}
-
-enum FallbackTokenKind {
-    Ident(String),
-    Punct(char),
-}
-
-impl<'a> FallbackScanner<'a> {
-    fn new(text: &'a str) -> Self {
-        Self {
-            bytes: text.as_bytes(),
-            idx: 0,
-        }
-    }
-
-    fn next_token(&mut self) -> Option<FallbackToken> {
-        self.skip_trivia();
-        if self.idx >= self.bytes.len() {
-            return None;
-        }
-        let start = self.idx;
-        let b = self.bytes[self.idx];
-        if is_ident_byte(b) {
-            self.idx += 1;
-            while self.idx < self.bytes.len() && is_ident_byte(self.bytes[self.idx]) {
-                self.idx += 1;
-            }
-            let ident = std::str::from_utf8(&self.bytes[start..self.idx])
-                .ok()?
-                .to_string();
-            Some(FallbackToken {
-                kind: FallbackTokenKind::Ident(ident),
-                start,
-            })
-        } else {
-            self.idx += 1;
-            Some(FallbackToken {
-                kind: FallbackTokenKind::Punct(b as char),
-                start,
-            })
-        }
-    }
-
-    fn skip_trivia(&mut self) {
-        while self.idx < self.bytes.len() {
-            let b = self.bytes[self.idx];
-            if b.is_ascii_whitespace() {
-                self.idx += 1;
-                continue;
-            }
-            if b == b'/' && self.idx + 1 < self.bytes.len() {
-                let next = self.bytes[self.idx + 1];
-                if next == b'/' {
-                    self.idx += 2;
-                    while self.idx < self.bytes.len() && self.bytes[self.idx] != b'\n' {
-                        self.idx += 1;
-                    }
-                    continue;
-                }
-                if next == b'*' {
-                    self.idx += 2;
-                    while self.idx + 1 < self.bytes.len() {
-                        if self.bytes[self.idx] == b'*' && self.bytes[self.idx + 1] == b'/' {
-                            self.idx += 2;
-                            break;
-                        }
-                        self.idx += 1;
-                    }
-                    continue;
-                }
-            }
-            if b == b'\'' || b == b'"' {
-                self.idx = skip_string(self.bytes, self.idx);
-                continue;
-            }
-            break;
-        }
-    }
-}
-
-fn contract_scope_items(gcx: Gcx<'_>, contract_id: hir::ContractId) -> Vec<SemaCompletionItem> {
+fn contract_scope_items(
+    gcx: Gcx<'_>,
+    contract_id: hir::ContractId,
+    prefix: &str,
+) -> Vec<SemaCompletionItem> {
     let mut items = Vec::new();
     let contract = gcx.hir.contract(contract_id);
     let bases = if contract.linearized_bases.is_empty() {
@@ -1452,11 +1222,15 @@ fn contract_scope_items(gcx: Gcx<'_>, contract_id: hir::ContractId) -> Vec<SemaC
     for (idx, base_id) in bases.iter().enumerate() {
         let base = gcx.hir.contract(*base_id);
         for &item_id in base.items {
-            if idx > 0 {
-                let item = gcx.hir.item(item_id);
-                if !item.is_visible_in_derived_contracts() {
-                    continue;
-                }
+            let item = gcx.hir.item(item_id);
+            if idx > 0 && !item.is_visible_in_derived_contracts() {
+                continue;
+            }
+            if !item
+                .name()
+                .is_some_and(|ident| starts_with_ignore_case(ident.as_str(), prefix))
+            {
+                continue;
             }
             if let Some(item) = completion_item_for_item(gcx, item_id) {
                 items.push(item);
@@ -1730,7 +1504,7 @@ fn detail_for_item_id(gcx: Gcx<'_>, item_id: hir::ItemId) -> Option<String> {
     detail_for_ty(gcx, ty)
 }
 
-fn detail_for_ty<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> Option<String> {
+pub(crate) fn detail_for_ty<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> Option<String> {
     match ty.kind {
         TyKind::FnPtr(_) => {
             let params = ty.parameters().unwrap_or_default();
@@ -0,0 +1,737 @@
+//! Intraprocedural data-flow over HIR function bodies: reaching definitions of local
+//! variables and taint from caller-controlled values to dangerous sinks.
+//!
+//! Solar lowers every loop to `Loop` with explicit `break`s, so the structured statements are
+//! walked directly: branches are joined and loop bodies are re-run until their entry state is
+//! stable. Only locals and parameters are tracked; storage writes do not carry taint.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use sa_base_db::FileId;
+use sa_span::{TextRange, TextSize, range_contains};
+use solar::interface::Span;
+use solar::sema::{Gcx, hir};
+
+use crate::SemaSnapshot;
+
+/// Loop bodies reach a fixed point within a few rounds; this bounds pathological nesting.
+const MAX_LOOP_ITERATIONS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaintSource {
+    MsgSender,
+    MsgData,
+    TxOrigin,
+    /// A parameter of a public or external function.
+    Parameter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaintSink {
+    DelegateCall,
+    SelfDestruct,
+    Transfer,
+    Send,
+    Call,
+}
+
+/// The sources and sinks a detector is interested in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintConfig {
+    pub sources: Vec<TaintSource>,
+    pub sinks: Vec<TaintSink>,
+}
+
+impl Default for TaintConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![
+                TaintSource::MsgSender,
+                TaintSource::MsgData,
+                TaintSource::TxOrigin,
+                TaintSource::Parameter,
+            ],
+            sinks: vec![
+                TaintSink::DelegateCall,
+                TaintSink::SelfDestruct,
+                TaintSink::Transfer,
+                TaintSink::Send,
+                TaintSink::Call,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessStep {
+    pub range: TextRange,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintFinding {
+    pub source: TaintSource,
+    pub sink: TaintSink,
+    /// `Contract.function`, or the bare name of a free function.
+    pub function: String,
+    pub file_id: FileId,
+    /// The sink call.
+    pub range: TextRange,
+    /// How the value travelled, from the source to the sink.
+    pub witness: Vec<WitnessStep>,
+}
+
+impl SemaSnapshot {
+    /// Runs taint tracking over every function body in `file_id`.
+    pub fn taint_findings(
+        &self,
+        file_id: FileId,
+        config: &TaintConfig,
+    ) -> Option<Vec<TaintFinding>> {
+        let source_id = self.source_id_for_file(file_id)?;
+        Some(self.with_gcx(|gcx| {
+            let source = gcx.hir.source(source_id);
+            let mut findings = Vec::new();
+            for function_id in source_functions(gcx, source) {
+                let mut flow = Flow::new(self, gcx, file_id, &source.file.src, config, None);
+                flow.run(function_id);
+                findings.extend(flow.findings);
+            }
+            findings.sort_by_key(|finding| finding.range.start());
+            findings
+        }))
+    }
+
+    /// Returns the definitions (declarations, parameters and assignments) of the local variable
+    /// read at `offset` that may reach that read.
+    pub fn reaching_definitions(
+        &self,
+        file_id: FileId,
+        offset: TextSize,
+    ) -> Option<Vec<TextRange>> {
+        let source_id = self.source_id_for_file(file_id)?;
+        self.with_gcx(|gcx| {
+            let source = gcx.hir.source(source_id);
+            let config = TaintConfig::default();
+            for function_id in source_functions(gcx, source) {
+                let function = gcx.hir.function(function_id);
+                let contains = self
+                    .span_to_text_range(function.span)
+                    .is_some_and(|range| range_contains(range, offset));
+                if !contains {
+                    continue;
+                }
+                let mut flow =
+                    Flow::new(self, gcx, file_id, &source.file.src, &config, Some(offset));
+                flow.run(function_id);
+                if let Some(defs) = flow.query {
+                    let mut defs = defs
+                        .into_iter()
+                        .map(|(start, end)| TextRange::new(start.into(), end.into()))
+                        .collect::<Vec<_>>();
+                    defs.sort_by_key(|range| range.start());
+                    return Some(defs);
+                }
+            }
+            None
+        })
+    }
+}
+
+fn source_functions<'gcx>(gcx: Gcx<'gcx>, source: &hir::Source<'gcx>) -> Vec<hir::FunctionId> {
+    let mut functions = Vec::new();
+    for &item_id in source.items {
+        match item_id {
+            hir::ItemId::Function(id) => functions.push(id),
+            hir::ItemId::Contract(id) => {
+                let contract = gcx.hir.contract(id);
+                functions.extend(contract.items.iter().filter_map(|item| item.as_function()));
+                functions.extend(contract.ctor);
+                functions.extend(contract.fallback);
+                functions.extend(contract.receive);
+            }
+            _ => {}
+        }
+    }
+    functions
+}
+
+type Witness = Arc<Vec<WitnessStep>>;
+
+#[derive(Clone, Debug)]
+struct Taint {
+    source: TaintSource,
+    witness: Witness,
+}
+
+impl Taint {
+    fn step(&self, range: TextRange, label: String) -> Self {
+        let mut witness = (*self.witness).clone();
+        witness.push(WitnessStep { range, label });
+        Self {
+            source: self.source,
+            witness: Arc::new(witness),
+        }
+    }
+}
+
+/// Picks the taint with the shorter witness, so joins are deterministic.
+fn join_taint(left: Option<Taint>, right: Option<Taint>) -> Option<Taint> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(if right.witness.len() < left.witness.len() {
+            right
+        } else {
+            left
+        }),
+        (left, right) => left.or(right),
+    }
+}
+
+type DefSite = (u32, u32);
+
+#[derive(Clone, Debug, Default)]
+struct State {
+    defs: HashMap<hir::VariableId, BTreeSet<DefSite>>,
+    taint: HashMap<hir::VariableId, Taint>,
+}
+
+impl State {
+    fn join(&mut self, other: &State) {
+        for (var, sites) in &other.defs {
+            self.defs
+                .entry(*var)
+                .or_default()
+                .extend(sites.iter().copied());
+        }
+        for (var, taint) in &other.taint {
+            let joined = join_taint(self.taint.remove(var), Some(taint.clone()));
+            self.taint.extend(joined.map(|taint| (*var, taint)));
+        }
+    }
+
+    /// Taint witnesses do not take part: only whether a variable is tainted matters for
+    /// reaching a fixed point.
+    fn same_facts(&self, other: &State) -> bool {
+        self.defs == other.defs
+            && self.taint.len() == other.taint.len()
+            && self.taint.keys().all(|var| other.taint.contains_key(var))
+    }
+}
+
+fn join_states(left: Option<State>, right: Option<State>) -> Option<State> {
+    match (left, right) {
+        (Some(mut left), Some(right)) => {
+            left.join(&right);
+            Some(left)
+        }
+        (left, right) => left.or(right),
+    }
+}
+
+#[derive(Default)]
+struct LoopFrame {
+    breaks: Option<State>,
+    continues: Option<State>,
+}
+
+struct Flow<'a, 'gcx> {
+    snapshot: &'a SemaSnapshot,
+    gcx: Gcx<'gcx>,
+    file_id: FileId,
+    text: &'a str,
+    config: &'a TaintConfig,
+    function: String,
+    /// `None` once control cannot reach the current statement.
+    state: Option<State>,
+    loops: Vec<LoopFrame>,
+    findings: Vec<TaintFinding>,
+    query_offset: Option<TextSize>,
+    query: Option<BTreeSet<DefSite>>,
+}
+
+impl<'a, 'gcx> Flow<'a, 'gcx> {
+    fn new(
+        snapshot: &'a SemaSnapshot,
+        gcx: Gcx<'gcx>,
+        file_id: FileId,
+        text: &'a str,
+        config: &'a TaintConfig,
+        query_offset: Option<TextSize>,
+    ) -> Self {
+        Self {
+            snapshot,
+            gcx,
+            file_id,
+            text,
+            config,
+            function: String::new(),
+            state: Some(State::default()),
+            loops: Vec::new(),
+            findings: Vec::new(),
+            query_offset,
+            query: None,
+        }
+    }
+
+    fn run(&mut self, function_id: hir::FunctionId) {
+        let function = self.gcx.hir.function(function_id);
+        let name = function
+            .name
+            .map(|name| name.as_str().to_string())
+            .unwrap_or_else(|| function.kind.to_str().to_string());
+        self.function = match function.contract {
+            Some(contract) => format!("{}.{name}", self.gcx.hir.contract(contract).name),
+            None => name,
+        };
+
+        let external = function.visibility >= hir::Visibility::Public;
+        for &param in function.parameters.iter().chain(function.returns) {
+            let Some(range) = self.var_name_range(param) else {
+                continue;
+            };
+            let taint = (external
+                && function.parameters.contains(&param)
+                && self.config.sources.contains(&TaintSource::Parameter))
+            .then(|| Taint {
+                source: TaintSource::Parameter,
+                witness: Arc::new(vec![WitnessStep {
+                    range,
+                    label: format!("parameter `{}`", self.text_at(range)),
+                }]),
+            });
+            let state = self.state.get_or_insert_with(State::default);
+            state.defs.entry(param).or_default().insert(def_site(range));
+            if let Some(taint) = taint {
+                state.taint.insert(param, taint);
+            }
+        }
+
+        if let Some(body) = function.body {
+            self.block(body.stmts);
+        }
+    }
+
+    fn block(&mut self, stmts: &[hir::Stmt<'gcx>]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &hir::Stmt<'gcx>) {
+        if self.state.is_none() {
+            return;
+        }
+        match &stmt.kind {
+            hir::StmtKind::DeclSingle(var_id) => {
+                let var = self.gcx.hir.variable(*var_id);
+                let taint = var.initializer.and_then(|init| self.expr(init));
+                self.define_var(*var_id, taint);
+            }
+            hir::StmtKind::DeclMulti(vars, expr) => {
+                let taint = self.expr(expr);
+                for var_id in vars.iter().flatten() {
+                    self.define_var(*var_id, taint.clone());
+                }
+            }
+            hir::StmtKind::Block(block) | hir::StmtKind::UncheckedBlock(block) => {
+                self.block(block.stmts);
+            }
+            hir::StmtKind::Loop(block, _) => self.loop_(block.stmts),
+            hir::StmtKind::Emit(expr) => {
+                self.expr(expr);
+            }
+            hir::StmtKind::Revert(expr) => {
+                self.expr(expr);
+                self.state = None;
+            }
+            hir::StmtKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+                self.state = None;
+            }
+            hir::StmtKind::Break => {
+                let state = self.state.take();
+                if let Some(frame) = self.loops.last_mut() {
+                    frame.breaks = join_states(frame.breaks.take(), state);
+                }
+            }
+            hir::StmtKind::Continue => {
+                let state = self.state.take();
+                if let Some(frame) = self.loops.last_mut() {
+                    frame.continues = join_states(frame.continues.take(), state);
+                }
+            }
+            hir::StmtKind::If(cond, then_branch, else_branch) => {
+                self.expr(cond);
+                let entry = self.state.clone();
+                self.stmt(then_branch);
+                let after_then = std::mem::replace(&mut self.state, entry);
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+                self.state = join_states(after_then, self.state.take());
+            }
+            hir::StmtKind::Try(stmt_try) => {
+                let taint = self.expr(&stmt_try.expr);
+                let entry = self.state.clone();
+                let mut exit = None;
+                for (idx, clause) in stmt_try.clauses.iter().enumerate() {
+                    self.state = entry.clone();
+                    for &arg in clause.args {
+                        // Only the success clause receives the call's results.
+                        self.define_var(arg, if idx == 0 { taint.clone() } else { None });
+                    }
+                    self.block(clause.block.stmts);
+                    exit = join_states(exit, self.state.take());
+                }
+                self.state = exit;
+            }
+            hir::StmtKind::Expr(expr) => {
+                self.expr(expr);
+            }
+            hir::StmtKind::Placeholder | hir::StmtKind::Err(_) => {}
+        }
+    }
+
+    fn loop_(&mut self, stmts: &[hir::Stmt<'gcx>]) {
+        let entry = self.state.clone();
+        let mut head = entry.clone();
+        let mut exit = None;
+        for _ in 0..MAX_LOOP_ITERATIONS {
+            self.loops.push(LoopFrame::default());
+            self.state = head.clone();
+            self.block(stmts);
+            let frame = self.loops.pop().unwrap_or_default();
+            exit = frame.breaks;
+            let back_edge = join_states(self.state.take(), frame.continues);
+            let next = join_states(entry.clone(), back_edge);
+            let stable = match (&head, &next) {
+                (Some(head), Some(next)) => head.same_facts(next),
+                (None, None) => true,
+                _ => false,
+            };
+            head = next;
+            if stable {
+                break;
+            }
+        }
+        self.state = exit;
+    }
+
+    fn define_var(&mut self, var_id: hir::VariableId, taint: Option<Taint>) {
+        if let Some(range) = self.var_name_range(var_id) {
+            self.define(var_id, range, taint);
+        }
+    }
+
+    /// A strong update: `range` becomes the only definition of `var_id` reaching what follows.
+    fn define(&mut self, var_id: hir::VariableId, range: TextRange, taint: Option<Taint>) {
+        let taint = taint.map(|taint| {
+            let label = format!("assigned to `{}`", self.text_at(range));
+            taint.step(range, label)
+        });
+        let Some(state) = self.state.as_mut() else {
+            return;
+        };
+        state.defs.insert(var_id, BTreeSet::from([def_site(range)]));
+        match taint {
+            Some(taint) => {
+                state.taint.insert(var_id, taint);
+            }
+            None => {
+                state.taint.remove(&var_id);
+            }
+        }
+    }
+
+    /// Evaluates `expr` for its effects and returns the taint of its value.
+    fn expr(&mut self, expr: &hir::Expr<'gcx>) -> Option<Taint> {
+        self.state.as_ref()?;
+        if let Some(taint) = self.builtin_source(expr) {
+            return Some(taint);
+        }
+        match &expr.kind {
+            hir::ExprKind::Ident(res) => {
+                let var_id = local_var(self.gcx, res)?;
+                self.record_query(var_id, expr.span);
+                self.state.as_ref()?.taint.get(&var_id).cloned()
+            }
+            hir::ExprKind::Assign(lhs, op, rhs) => {
+                let mut taint = self.expr(rhs);
+                if op.is_some() {
+                    taint = join_taint(taint, self.expr(lhs));
+                }
+                self.assign(lhs, rhs, taint.clone());
+                taint
+            }
+            hir::ExprKind::Call(callee, args, opts) => {
+                let callee_taint = match &callee.kind {
+                    hir::ExprKind::Member(base, _) => self.expr(base),
+                    _ => self.expr(callee),
+                };
+                let mut arg_taints = Vec::new();
+                for arg in args.kind.exprs() {
+                    arg_taints.push(self.expr(arg));
+                }
+                if let Some(opts) = opts {
+                    for opt in *opts {
+                        self.expr(&opt.value);
+                    }
+                }
+                self.check_sink(expr, callee, callee_taint.clone(), &arg_taints);
+                arg_taints.into_iter().fold(callee_taint, join_taint)
+            }
+            hir::ExprKind::Member(base, _) => self.expr(base),
+            hir::ExprKind::Binary(lhs, _, rhs) => {
+                let lhs = self.expr(lhs);
+                let rhs = self.expr(rhs);
+                join_taint(lhs, rhs)
+            }
+            hir::ExprKind::Index(base, index) => {
+                let base = self.expr(base);
+                let index = index.and_then(|index| self.expr(index));
+                join_taint(base, index)
+            }
+            hir::ExprKind::Slice(base, start, end) => {
+                let mut taint = self.expr(base);
+                if let Some(start) = start {
+                    taint = join_taint(taint, self.expr(start));
+                }
+                if let Some(end) = end {
+                    taint = join_taint(taint, self.expr(end));
+                }
+                taint
+            }
+            hir::ExprKind::Ternary(cond, then_expr, else_expr) => {
+                self.expr(cond);
+                let entry = self.state.clone();
+                let then_taint = self.expr(then_expr);
+                let after_then = std::mem::replace(&mut self.state, entry);
+                let else_taint = self.expr(else_expr);
+                self.state = join_states(after_then, self.state.take());
+                join_taint(then_taint, else_taint)
+            }
+            hir::ExprKind::Array(exprs) => exprs
+                .iter()
+                .fold(None, |taint, expr| join_taint(taint, self.expr(expr))),
+            hir::ExprKind::Tuple(exprs) => exprs
+                .iter()
+                .copied()
+                .flatten()
+                .fold(None, |taint, expr| join_taint(taint, self.expr(expr))),
+            hir::ExprKind::Payable(inner) | hir::ExprKind::Unary(_, inner) => self.expr(inner),
+            hir::ExprKind::Delete(inner) => {
+                self.expr(inner);
+                None
+            }
+            hir::ExprKind::Lit(_)
+            | hir::ExprKind::Err(_)
+            | hir::ExprKind::New(_)
+            | hir::ExprKind::TypeCall(_)
+            | hir::ExprKind::Type(_) => None,
+        }
+    }
+
+    fn assign(&mut self, lhs: &hir::Expr<'gcx>, rhs: &hir::Expr<'gcx>, taint: Option<Taint>) {
+        match &lhs.kind {
+            hir::ExprKind::Ident(res) => {
+                if let Some(var_id) = local_var(self.gcx, res)
+                    && let Some(range) = self.snapshot.span_to_text_range(lhs.span)
+                {
+                    self.define(var_id, range, taint);
+                }
+            }
+            hir::ExprKind::Tuple(lhs_exprs) => {
+                let rhs_exprs = match &rhs.kind {
+                    hir::ExprKind::Tuple(rhs_exprs) if rhs_exprs.len() == lhs_exprs.len() => {
+                        Some(*rhs_exprs)
+                    }
+                    _ => None,
+                };
+                for (idx, lhs) in lhs_exprs.iter().enumerate() {
+                    let Some(lhs) = lhs else {
+                        continue;
+                    };
+                    let rhs = rhs_exprs
+                        .and_then(|rhs_exprs| rhs_exprs[idx])
+                        .unwrap_or(rhs);
+                    self.assign(lhs, rhs, taint.clone());
+                }
+            }
+            // Writing into an element or field keeps what the variable already holds.
+            hir::ExprKind::Index(..) | hir::ExprKind::Member(..) => {
+                let (Some(var_id), Some(taint)) = (root_local(self.gcx, lhs), taint) else {
+                    return;
+                };
+                let Some(range) = self.snapshot.span_to_text_range(lhs.span) else {
+                    return;
+                };
+                let label = format!("stored into `{}`", self.text_at(range));
+                if let Some(state) = self.state.as_mut() {
+                    state
+                        .taint
+                        .entry(var_id)
+                        .or_insert_with(|| taint.step(range, label));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn builtin_source(&self, expr: &hir::Expr<'gcx>) -> Option<Taint> {
+        let hir::ExprKind::Member(base, _) = &expr.kind else {
+            return None;
+        };
+        if !matches!(base.kind, hir::ExprKind::Ident(_)) {
+            return None;
+        }
+        let range = self.snapshot.span_to_text_range(expr.span)?;
+        let text = self.text_at(range);
+        let compact = text.split_whitespace().collect::<String>();
+        let source = match compact.as_str() {
+            "msg.sender" => TaintSource::MsgSender,
+            "msg.data" => TaintSource::MsgData,
+            "tx.origin" => TaintSource::TxOrigin,
+            _ => return None,
+        };
+        if !self.config.sources.contains(&source) {
+            return None;
+        }
+        Some(Taint {
+            source,
+            witness: Arc::new(vec![WitnessStep {
+                range,
+                label: format!("`{compact}`"),
+            }]),
+        })
+    }
+
+    /// `delegatecall` is dangerous when either its target or its data is tainted; value
+    /// transfers and self-destructs when the recipient is.
+    fn check_sink(
+        &mut self,
+        call: &hir::Expr<'gcx>,
+        callee: &hir::Expr<'gcx>,
+        callee_taint: Option<Taint>,
+        arg_taints: &[Option<Taint>],
+    ) {
+        let (sink, taint) = match &callee.kind {
+            hir::ExprKind::Member(_, ident) if arg_taints.len() == 1 => {
+                let sink = match ident.as_str() {
+                    "delegatecall" => TaintSink::DelegateCall,
+                    "transfer" => TaintSink::Transfer,
+                    "send" => TaintSink::Send,
+                    "call" => TaintSink::Call,
+                    _ => return,
+                };
+                let taint = if sink == TaintSink::DelegateCall {
+                    join_taint(callee_taint, arg_taints[0].clone())
+                } else {
+                    callee_taint
+                };
+                (sink, taint)
+            }
+            hir::ExprKind::Ident(_) if arg_taints.len() == 1 => {
+                let Some(range) = self.snapshot.span_to_text_range(callee.span) else {
+                    return;
+                };
+                if !matches!(self.text_at(range), "selfdestruct" | "suicide") {
+                    return;
+                }
+                (TaintSink::SelfDestruct, arg_taints[0].clone())
+            }
+            _ => return,
+        };
+        let Some(taint) = taint else {
+            return;
+        };
+        if !self.config.sinks.contains(&sink) {
+            return;
+        }
+        let Some(range) = self.snapshot.span_to_text_range(call.span) else {
+            return;
+        };
+        if self.findings.iter().any(|finding| finding.range == range) {
+            return;
+        }
+        let label = format!("reaches `{}`", self.text_at(range));
+        let witness = taint.step(range, label).witness;
+        self.findings.push(TaintFinding {
+            source: taint.source,
+            sink,
+            function: self.function.clone(),
+            file_id: self.file_id,
+            range,
+            witness: (*witness).clone(),
+        });
+    }
+
+    fn record_query(&mut self, var_id: hir::VariableId, span: Span) {
+        let Some(offset) = self.query_offset else {
+            return;
+        };
+        let Some(range) = self.snapshot.span_to_text_range(span) else {
+            return;
+        };
+        if !range_contains(range, offset) {
+            return;
+        }
+        let Some(sites) = self
+            .state
+            .as_ref()
+            .and_then(|state| state.defs.get(&var_id))
+        else {
+            return;
+        };
+        self.query
+            .get_or_insert_with(BTreeSet::new)
+            .extend(sites.iter().copied());
+    }
+
+    fn var_name_range(&self, var_id: hir::VariableId) -> Option<TextRange> {
+        let var = self.gcx.hir.variable(var_id);
+        let span = var.name.map(|name| name.span).unwrap_or(var.span);
+        self.snapshot.span_to_text_range(span)
+    }
+
+    fn text_at(&self, range: TextRange) -> &'a str {
+        self.text
+            .get(usize::from(range.start())..usize::from(range.end()))
+            .unwrap_or_default()
+    }
+}
+
+fn local_var(gcx: Gcx<'_>, res: &[hir::Res]) -> Option<hir::VariableId> {
+    res.iter().find_map(|res| match res {
+        hir::Res::Item(hir::ItemId::Variable(var_id)) => {
+            let var = gcx.hir.variable(*var_id);
+            (!matches!(
+                var.kind,
+                hir::VarKind::Global
+                    | hir::VarKind::State
+                    | hir::VarKind::Struct
+                    | hir::VarKind::Event
+                    | hir::VarKind::Error
+            ))
+            .then_some(*var_id)
+        }
+        _ => None,
+    })
+}
+
+/// The local variable an element or field write like `a.b[i] = x` ends up storing into.
+fn root_local(gcx: Gcx<'_>, mut expr: &hir::Expr<'_>) -> Option<hir::VariableId> {
+    loop {
+        match &expr.kind {
+            hir::ExprKind::Index(base, _) | hir::ExprKind::Member(base, _) => expr = base,
+            hir::ExprKind::Ident(res) => return local_var(gcx, res),
+            _ => return None,
+        }
+    }
+}
+
+fn def_site(range: TextRange) -> DefSite {
+    (u32::from(range.start()), u32::from(range.end()))
+}
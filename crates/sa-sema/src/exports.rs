@@ -11,20 +11,6 @@ pub(crate) fn exported_item_names(gcx: Gcx<'_>, source_id: hir::SourceId) -> Vec
     names
 }
 
-pub(crate) fn exported_item_ids(gcx: Gcx<'_>, source_id: hir::SourceId) -> Vec<hir::ItemId> {
-    let names = exported_item_names(gcx, source_id);
-    let mut seen = HashSet::new();
-    let mut items = Vec::new();
-    for name in names {
-        if let Some(item_id) = find_exported_item(gcx, source_id, name)
-            && seen.insert(item_id)
-        {
-            items.push(item_id);
-        }
-    }
-    items
-}
-
 pub(crate) fn find_exported_item(
     gcx: Gcx<'_>,
     source_id: hir::SourceId,
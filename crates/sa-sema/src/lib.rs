@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
@@ -13,26 +14,40 @@ use sa_project_model::{
 };
 use sa_span::{TextRange, TextSize, is_ident_byte};
 use sa_vfs::{Vfs, VfsChange, VfsSnapshot};
+use salsa::Setter;
 use solar::interface::diagnostics::{DiagCtxt, ErrorGuaranteed, InMemoryEmitter};
 use solar::interface::source_map::{FileLoader, SourceMap};
 use solar::interface::{Session, Span};
 use solar::sema::Compiler;
 use solar::sema::hir::SourceId;
 use solar::sema::{Gcx, hir};
-use tracing::{debug, warn};
+use tracing::{debug, debug_span, warn};
 
+mod abi;
 mod completion;
 mod contract_members;
+mod dataflow;
 mod exports;
 mod references;
 mod resolve;
+mod selectors;
+mod storage_layout;
 mod symbols;
 mod ty_utils;
+mod using_for;
 
 pub use completion::{SemaCompletionItem, SemaCompletionKind};
+pub use dataflow::{TaintConfig, TaintFinding, TaintSink, TaintSource, WitnessStep};
 pub use references::SemaReference;
-pub use resolve::{ResolveOutcome, ResolvedSymbol, ResolvedSymbolKind};
+pub use resolve::{
+    CallResolution, ResolveOutcome, ResolvedSymbol, ResolvedSymbolKind, TypeInfo, TypeLocation,
+};
+pub use selectors::{
+    SelectorEntry, SelectorKind, error_selector, event_topic, function_selector, to_hex,
+};
+pub use storage_layout::StorageSlot;
 pub use symbols::SemaSymbol;
+pub use using_for::BoundFunction;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SemaFunctionSignature {
@@ -52,6 +67,32 @@ pub struct SemaSnapshot {
     source_id_by_file: HashMap<FileId, SourceId>,
     pub(crate) file_id_by_source: HashMap<SourceId, FileId>,
     reference_index: OnceLock<references::SemaReferenceIndex>,
+    source_bytes: usize,
+}
+
+static LIVE_SNAPSHOTS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_SNAPSHOT_SOURCE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Snapshots alive in this process, wherever they are held (salsa memos, in-flight requests).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SemaSnapshotStats {
+    pub live: usize,
+    /// Source text loaded into the live compilers. Their ASTs and HIR take a multiple of this.
+    pub source_bytes: usize,
+}
+
+pub fn sema_snapshot_stats() -> SemaSnapshotStats {
+    SemaSnapshotStats {
+        live: LIVE_SNAPSHOTS.load(Ordering::Relaxed),
+        source_bytes: LIVE_SNAPSHOT_SOURCE_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+impl Drop for SemaSnapshot {
+    fn drop(&mut self) {
+        LIVE_SNAPSHOTS.fetch_sub(1, Ordering::Relaxed);
+        LIVE_SNAPSHOT_SOURCE_BYTES.fetch_sub(self.source_bytes, Ordering::Relaxed);
+    }
 }
 
 impl SemaSnapshot {
@@ -61,6 +102,27 @@ impl SemaSnapshot {
         path_to_file_id: &HashMap<NormalizedPath, FileId>,
         skip_files: Option<&HashSet<FileId>>,
         resolve_imports: bool,
+    ) -> Result<Self> {
+        Self::new_with_checkpoint(
+            config,
+            vfs,
+            path_to_file_id,
+            skip_files,
+            resolve_imports,
+            &|| {},
+        )
+    }
+
+    /// Like [`SemaSnapshot::new`], but calls `checkpoint` between parsing, lowering and mapping
+    /// sources. A checkpoint aborts the build by unwinding (see
+    /// [`sa_base_db::SaDatabaseExt::check_cancelled`]).
+    pub fn new_with_checkpoint(
+        config: &ResolvedFoundryConfig,
+        vfs: &VfsSnapshot,
+        path_to_file_id: &HashMap<NormalizedPath, FileId>,
+        skip_files: Option<&HashSet<FileId>>,
+        resolve_imports: bool,
+        checkpoint: &dyn Fn(),
     ) -> Result<Self> {
         let (emitter, _buffer) = InMemoryEmitter::new();
         let dcx = DiagCtxt::new(Box::new(emitter));
@@ -75,21 +137,36 @@ impl SemaSnapshot {
         let mut compiler = Compiler::new(session);
 
         let files = collect_workspace_files(config.workspace(), vfs, path_to_file_id, skip_files);
+        checkpoint();
         let parse_result =
             compiler.enter_mut(|compiler| -> std::result::Result<(), ErrorGuaranteed> {
                 let mut parser = compiler.parse();
                 parser.set_resolve_imports(resolve_imports);
                 parser.load_files(files.iter())?;
                 parser.parse();
-                let _ = compiler.lower_asts()?;
                 Ok(())
             });
-        if parse_result.is_err() {
+        checkpoint();
+        let lower_result = match parse_result {
+            Ok(()) => compiler.enter_mut(|compiler| compiler.lower_asts().map(|_| ())),
+            Err(error) => Err(error),
+        };
+        if lower_result.is_err() {
             warn!("sema snapshot built with errors");
         }
+        checkpoint();
 
         let (source_id_by_file, file_id_by_source) =
             compiler.enter(|compiler| build_source_mappings(compiler.gcx(), path_to_file_id));
+        let source_bytes = compiler.enter(|compiler| {
+            let gcx = compiler.gcx();
+            gcx.hir
+                .source_ids()
+                .map(|source_id| gcx.hir.source(source_id).file.src.len())
+                .sum::<usize>()
+        });
+        LIVE_SNAPSHOTS.fetch_add(1, Ordering::Relaxed);
+        LIVE_SNAPSHOT_SOURCE_BYTES.fetch_add(source_bytes, Ordering::Relaxed);
 
         Ok(Self {
             compiler,
@@ -97,6 +174,7 @@ impl SemaSnapshot {
             source_id_by_file,
             file_id_by_source,
             reference_index: OnceLock::new(),
+            source_bytes,
         })
     }
 
@@ -181,13 +259,20 @@ impl SemaSnapshot {
         definition_file_id: FileId,
         definition_range: TextRange,
     ) -> Option<&[SemaReference]> {
-        self.reference_index()
+        self.reference_index(&|| {})
             .references_for(definition_file_id, definition_range)
     }
 
-    fn reference_index(&self) -> &references::SemaReferenceIndex {
+    /// Builds the reference index if it is not built yet, calling `checkpoint` between sources.
+    /// [`SemaSnapshot::references_for_definition`] builds it on first use otherwise, without a
+    /// way to abort.
+    pub fn index_references(&self, checkpoint: &dyn Fn()) {
+        self.reference_index(checkpoint);
+    }
+
+    fn reference_index(&self, checkpoint: &dyn Fn()) -> &references::SemaReferenceIndex {
         self.reference_index
-            .get_or_init(|| references::SemaReferenceIndex::new(self))
+            .get_or_init(|| references::SemaReferenceIndex::new(self, checkpoint))
     }
 
     fn item_id_for_name_range(
@@ -434,27 +519,151 @@ unsafe impl salsa::Update for SemaSnapshotResult {
     }
 }
 
-#[salsa::tracked]
+/// Controls how many solar compilers [`sema_snapshot_for_project`] keeps alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemaCacheConfig {
+    /// Snapshots that stay cached, counted separately for whole projects and for single files.
+    /// The least recently queried ones are dropped when a new revision starts and rebuilt on
+    /// demand. `0` keeps all of them.
+    pub capacity: usize,
+    /// Also build a snapshot without import resolution for projects with unresolved imports,
+    /// so files importing missing paths keep semantic features. When disabled, that snapshot
+    /// is only built if the full one fails, halving memory for such projects.
+    pub fallback_snapshot: bool,
+}
+
+impl Default for SemaCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 8,
+            fallback_snapshot: true,
+        }
+    }
+}
+
+#[salsa::input(singleton, debug)]
+struct SemaCacheInput {
+    fallback_snapshot: bool,
+    /// Bumped by [`rebuild_sema_snapshots`]; every snapshot reads it.
+    generation: u64,
+}
+
+pub fn set_sema_cache_config(db: &mut sa_base_db::Database, config: SemaCacheConfig) {
+    sema_snapshot_for_project::set_lru_capacity(db, config.capacity);
+    sema_snapshot_for_file::set_lru_capacity(db, config.capacity);
+    match SemaCacheInput::try_get(&*db) {
+        Some(input) => {
+            if input.fallback_snapshot(&*db) != config.fallback_snapshot {
+                input.set_fallback_snapshot(db).to(config.fallback_snapshot);
+            }
+        }
+        None => {
+            SemaCacheInput::new(db, config.fallback_snapshot, 0);
+        }
+    }
+}
+
+/// Makes every semantic snapshot stale, so the next query compiles its files again. For
+/// changes the database cannot see, such as a different solc or files edited outside the VFS.
+pub fn rebuild_sema_snapshots(db: &mut sa_base_db::Database) {
+    match SemaCacheInput::try_get(&*db) {
+        Some(input) => {
+            let generation = input.generation(&*db);
+            input.set_generation(db).to(generation.wrapping_add(1));
+        }
+        None => {
+            SemaCacheInput::new(db, SemaCacheConfig::default().fallback_snapshot, 1);
+        }
+    }
+}
+
+fn fallback_snapshot_enabled(db: &dyn SemaDatabase) -> bool {
+    SemaCacheInput::try_get(db).is_none_or(|input| input.fallback_snapshot(db))
+}
+
+fn sema_generation(db: &dyn SemaDatabase) -> u64 {
+    SemaCacheInput::try_get(db).map_or(0, |input| input.generation(db))
+}
+
+// Keep `lru` here and on `sema_snapshot_for_file` in sync with the default capacity.
+#[salsa::tracked(lru = 8)]
 pub fn sema_snapshot_for_project(
     db: &dyn SemaDatabase,
     project: ProjectInput,
 ) -> SemaSnapshotResult {
     let config = project.config(db).clone();
     let workspace = config.workspace().clone();
+    let _span = debug_span!("sema_snapshot_for_project", root = %workspace.root()).entered();
     let remappings = config.active_profile().remappings();
     let (vfs, path_to_file_id) = vfs_snapshot_from_db(db, &workspace);
     let missing_imports = files_with_missing_imports(db, &workspace, remappings, &path_to_file_id);
-    let snapshot = SemaSnapshot::new(
+    build_snapshots(db, &config, &vfs, &path_to_file_id, missing_imports)
+}
+
+/// Like [`sema_snapshot_for_project`], but only compiles `file_id` and the files it imports,
+/// transitively. It reads just the text of those files, so an edit elsewhere in the project
+/// neither invalidates nor rebuilds it: typing in a file rebuilds that file's snapshot and
+/// the snapshots of files importing it, not the whole project.
+///
+/// Use it for queries about a single file; whole-project queries (references, workspace
+/// symbols) need [`sema_snapshot_for_project`].
+#[salsa::tracked(lru = 8)]
+pub fn sema_snapshot_for_file(
+    db: &dyn SemaDatabase,
+    project: ProjectInput,
+    file_id: FileId,
+) -> SemaSnapshotResult {
+    let config = project.config(db).clone();
+    let _span = debug_span!(
+        "sema_snapshot_for_file",
+        root = %config.workspace().root(),
+        file = %db.file_path(file_id)
+    )
+    .entered();
+    let path_to_file_id = workspace_file_ids(db, config.workspace());
+    if !path_to_file_id.values().any(|known| *known == file_id) {
+        return SemaSnapshotResult::new(None, None, HashSet::new());
+    }
+    let (closure, missing_imports) = import_closure(db, project, file_id, &path_to_file_id);
+    let mut vfs = Vfs::default();
+    for file_id in closure {
+        db.check_cancelled();
+        vfs.apply_change(VfsChange::Set {
+            path: (*db.file_path(file_id)).clone(),
+            text: db.file_input(file_id).text(db).clone(),
+        });
+    }
+    build_snapshots(
+        db,
         &config,
-        &vfs,
+        &vfs.snapshot(),
         &path_to_file_id,
+        missing_imports,
+    )
+}
+
+fn build_snapshots(
+    db: &dyn SemaDatabase,
+    config: &ResolvedFoundryConfig,
+    vfs: &VfsSnapshot,
+    path_to_file_id: &HashMap<NormalizedPath, FileId>,
+    missing_imports: HashSet<FileId>,
+) -> SemaSnapshotResult {
+    sema_generation(db);
+    let checkpoint = || db.check_cancelled();
+    let snapshot = SemaSnapshot::new_with_checkpoint(
+        config,
+        vfs,
+        path_to_file_id,
         Some(&missing_imports),
         true,
+        &checkpoint,
     )
     .ok()
     .map(Arc::new);
-    let no_imports_snapshot = if !missing_imports.is_empty() || snapshot.is_none() {
-        SemaSnapshot::new(&config, &vfs, &path_to_file_id, None, false)
+    let wants_fallback = !missing_imports.is_empty() && fallback_snapshot_enabled(db);
+    let no_imports_snapshot = if wants_fallback || snapshot.is_none() {
+        SemaSnapshot::new_with_checkpoint(config, vfs, path_to_file_id, None, false, &checkpoint)
             .ok()
             .map(Arc::new)
     } else {
@@ -463,25 +672,146 @@ pub fn sema_snapshot_for_project(
     SemaSnapshotResult::new(snapshot, no_imports_snapshot, missing_imports)
 }
 
+/// Maps normalized paths to file ids; re-collected only when the file set changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathIndex {
+    path_to_file_id: HashMap<NormalizedPath, FileId>,
+}
+
+impl PathIndex {
+    pub fn file_id(&self, path: &str) -> Option<FileId> {
+        self.path_to_file_id.get(path).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NormalizedPath, FileId)> + '_ {
+        self.path_to_file_id
+            .iter()
+            .map(|(path, file_id)| (path, *file_id))
+    }
+}
+
+unsafe impl salsa::Update for PathIndex {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+/// The path to file id lookup shared by every query resolving paths.
+#[salsa::tracked(returns(ref))]
+pub fn path_index(db: &dyn SemaDatabase) -> PathIndex {
+    let path_to_file_id = db
+        .file_ids()
+        .into_iter()
+        .map(|file_id| ((*db.file_path(file_id)).clone(), file_id))
+        .collect();
+    PathIndex { path_to_file_id }
+}
+
+/// Solidity files inside the workspace, by path. Only reads paths, never file contents.
+fn workspace_file_ids(
+    db: &dyn SemaDatabase,
+    workspace: &FoundryWorkspace,
+) -> HashMap<NormalizedPath, FileId> {
+    path_index(db)
+        .iter()
+        .filter(|(path, file_id)| {
+            db.file_input(*file_id).kind(db) == LanguageKind::Solidity
+                && is_workspace_path(workspace, path)
+        })
+        .map(|(path, file_id)| (path.clone(), file_id))
+        .collect()
+}
+
+/// Walks imports from `root`, returning every reachable file and those among them with an
+/// import that does not resolve to a workspace file.
+fn import_closure(
+    db: &dyn SemaDatabase,
+    project: ProjectInput,
+    root: FileId,
+    path_to_file_id: &HashMap<NormalizedPath, FileId>,
+) -> (Vec<FileId>, HashSet<FileId>) {
+    let mut closure = vec![root];
+    let mut seen = HashSet::from([root]);
+    let mut missing = HashSet::new();
+    let mut next = 0;
+    while let Some(&file_id) = closure.get(next) {
+        next += 1;
+        db.check_cancelled();
+        let imports = file_imports(db, project, file_id);
+        for target in &imports.targets {
+            match target.as_ref().and_then(|path| path_to_file_id.get(path)) {
+                Some(&dependency) => {
+                    if seen.insert(dependency) {
+                        closure.push(dependency);
+                    }
+                }
+                None if !imports.has_parse_errors => {
+                    missing.insert(file_id);
+                }
+                None => {}
+            }
+        }
+    }
+    (closure, missing)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FileImports {
+    /// Resolved path of each import, `None` where resolution failed.
+    targets: Vec<Option<NormalizedPath>>,
+    /// Files with syntax errors are compiled from recovered text, so unresolved imports in
+    /// them do not count as missing (matching [`files_with_missing_imports`]).
+    has_parse_errors: bool,
+}
+
+unsafe impl salsa::Update for FileImports {
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old = unsafe { &mut *old_pointer };
+        if *old == new_value {
+            false
+        } else {
+            *old = new_value;
+            true
+        }
+    }
+}
+
+#[salsa::tracked(returns(ref))]
+fn file_imports(db: &dyn SemaDatabase, project: ProjectInput, file_id: FileId) -> FileImports {
+    let config = project.config(db);
+    let workspace = config.workspace();
+    let remappings = config.active_profile().remappings();
+    let path = db.file_path(file_id);
+    let text = db.file_input(file_id).text(db);
+    let has_parse_errors = !sa_syntax::parse_file(text).errors().is_empty();
+    let targets = FoundryResolver::new(workspace, remappings)
+        .ok()
+        .map(|resolver| resolver.with_node_modules(db.node_modules(workspace.root())))
+        .and_then(|resolver| resolved_import_targets(workspace, remappings, &resolver, &path, text))
+        .unwrap_or_default();
+    FileImports {
+        targets,
+        has_parse_errors,
+    }
+}
+
 fn vfs_snapshot_from_db(
     db: &dyn SemaDatabase,
     workspace: &FoundryWorkspace,
 ) -> (VfsSnapshot, HashMap<NormalizedPath, FileId>) {
+    let path_to_file_id = workspace_file_ids(db, workspace);
     let mut vfs = Vfs::default();
-    let mut path_to_file_id = HashMap::new();
-    for file_id in db.file_ids() {
-        let file_input = db.file_input(file_id);
-        if file_input.kind(db) != LanguageKind::Solidity {
-            continue;
-        }
-        let path = db.file_path(file_id);
-        if !is_workspace_path(workspace, &path) {
-            continue;
-        }
-        path_to_file_id.insert((*path).clone(), file_id);
+    for (path, file_id) in &path_to_file_id {
+        db.check_cancelled();
         vfs.apply_change(VfsChange::Set {
-            path: (*path).clone(),
-            text: file_input.text(db).clone(),
+            path: path.clone(),
+            text: db.file_input(*file_id).text(db).clone(),
         });
     }
 
@@ -495,51 +825,72 @@ fn files_with_missing_imports(
     path_to_file_id: &HashMap<NormalizedPath, FileId>,
 ) -> HashSet<FileId> {
     let mut missing = HashSet::new();
-    let resolver = FoundryResolver::new(workspace, remappings).ok();
+    let Ok(resolver) = FoundryResolver::new(workspace, remappings) else {
+        return missing;
+    };
+    let resolver = resolver.with_node_modules(db.node_modules(workspace.root()));
     for (path, file_id) in path_to_file_id {
+        db.check_cancelled();
         let text = db.file_input(*file_id).text(db);
         let parse = sa_syntax::parse_file(text);
         if !parse.errors().is_empty() {
             continue;
         }
-        if let Some(resolver) = resolver.as_ref() {
-            match resolver.resolved_imports(path, text.as_ref()) {
-                Ok(imports) => {
-                    for import in imports {
-                        let resolved = import.resolved_path.or_else(|| {
-                            resolve_import_path_with_resolver(
-                                workspace,
-                                remappings,
-                                path,
-                                &import.path,
-                                Some(resolver),
-                            )
-                        });
-                        let resolved = resolved
-                            .or_else(|| resolve_relative_import_fallback(path, &import.path));
-                        let Some(resolved) = resolved else {
-                            missing.insert(*file_id);
-                            break;
-                        };
-                        if !path_to_file_id.contains_key(&resolved) {
-                            missing.insert(*file_id);
-                            break;
-                        }
-                    }
-                }
-                Err(error) => {
-                    debug!(
-                        ?error,
-                        path = %path,
-                        "sema: failed to parse imports with foundry parser"
-                    );
-                }
-            }
+        let Some(targets) = resolved_import_targets(workspace, remappings, &resolver, path, text)
+        else {
+            continue;
+        };
+        let has_missing = targets.iter().any(|target| {
+            target
+                .as_ref()
+                .is_none_or(|resolved| !path_to_file_id.contains_key(resolved))
+        });
+        if has_missing {
+            missing.insert(*file_id);
         }
     }
     missing
 }
 
+/// Resolves each import of `text`, or returns `None` if the imports could not be parsed.
+fn resolved_import_targets(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+    resolver: &FoundryResolver,
+    path: &NormalizedPath,
+    text: &str,
+) -> Option<Vec<Option<NormalizedPath>>> {
+    let imports = match resolver.resolved_imports(path, text) {
+        Ok(imports) => imports,
+        Err(error) => {
+            debug!(
+                ?error,
+                path = %path,
+                "sema: failed to parse imports with foundry parser"
+            );
+            return None;
+        }
+    };
+    let targets = imports
+        .into_iter()
+        .map(|import| {
+            import
+                .resolved_path
+                .or_else(|| {
+                    resolve_import_path_with_resolver(
+                        workspace,
+                        remappings,
+                        path,
+                        &import.path,
+                        Some(resolver),
+                    )
+                })
+                .or_else(|| resolve_relative_import_fallback(path, &import.path))
+        })
+        .collect();
+    Some(targets)
+}
+
 fn resolve_relative_import_fallback(
     current_path: &NormalizedPath,
     import_path: &str,
@@ -591,12 +942,13 @@ fn is_workspace_path(workspace: &FoundryWorkspace, path: &NormalizedPath) -> boo
     let roots = [
         workspace.root(),
         workspace.src(),
-        workspace.lib(),
         workspace.test(),
         workspace.script(),
     ];
     roots
-        .iter()
+        .into_iter()
+        .chain(workspace.libs())
+        .chain(workspace.include_paths())
         .any(|root| WorkspacePath::new(root, path).is_some())
 }
 
@@ -684,11 +1036,74 @@ impl VfsOverlayFileLoader {
     }
 }
 
+/// Rewrites a file with syntax errors into one that parses, keeping byte offsets intact so
+/// sema results still map onto the editor text. Returns `None` for files without errors or
+/// when no rewrite parses.
+///
+/// Blanking just the broken statements is tried first: it keeps the rest of the enclosing
+/// function body, so locals and member access around the cursor stay resolvable while the
+/// user is typing. Blanking whole function bodies is the fallback.
 fn recover_source_text(text: &str) -> Option<String> {
-    if sa_syntax::parse_file(text).errors().is_empty() {
+    let parse = sa_syntax::parse_file(text);
+    if parse.errors().is_empty() {
         return None;
     }
+    blank_broken_statements(text, parse.errors()).or_else(|| blank_function_bodies(text))
+}
+
+fn blank_broken_statements(text: &str, errors: &[sa_syntax::SyntaxError]) -> Option<String> {
+    const MAX_PATCHES: usize = 8;
+
+    let mut bytes = text.as_bytes().to_vec();
+    let mut error_offset = first_error_offset(errors)?;
+    for _ in 0..MAX_PATCHES {
+        let (start, end) = broken_statement_range(&bytes, error_offset);
+        if bytes[start..end].iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+        blank_range(&mut bytes, start, end);
+        let patched = String::from_utf8(bytes.clone()).ok()?;
+        let parse = sa_syntax::parse_file(&patched);
+        if parse.errors().is_empty() {
+            return Some(patched);
+        }
+        error_offset = first_error_offset(parse.errors())?;
+    }
+    None
+}
+
+fn first_error_offset(errors: &[sa_syntax::SyntaxError]) -> Option<usize> {
+    errors
+        .iter()
+        .find_map(|error| error.range())
+        .map(|range| usize::from(range.start()))
+}
+
+/// The statement around `offset`: from the previous statement or block boundary up to and
+/// including the next `;`, or up to the next brace. Braces are never blanked, so block
+/// structure survives.
+fn broken_statement_range(bytes: &[u8], offset: usize) -> (usize, usize) {
+    let offset = offset.min(bytes.len());
+    let is_boundary = |b: &u8| matches!(b, b';' | b'{' | b'}');
+    let start = bytes[..offset]
+        .iter()
+        .rposition(is_boundary)
+        .map_or(0, |index| index + 1);
+    let end = bytes[offset..]
+        .iter()
+        .position(is_boundary)
+        .map_or(bytes.len(), |index| {
+            let index = offset + index;
+            if bytes[index] == b';' {
+                index + 1
+            } else {
+                index
+            }
+        });
+    (start, end)
+}
 
+fn blank_function_bodies(text: &str) -> Option<String> {
     let mut bytes = text.as_bytes().to_vec();
     let len = bytes.len();
     let mut i = 0usize;
@@ -849,7 +1264,12 @@ impl FileLoader for VfsOverlayFileLoader {
         if let Some(text) = self.snapshot_text(path) {
             return Ok(text);
         }
-        self.fallback.load_file(path)
+        // Decode the way the VFS does so files outside it still load when they are not valid UTF-8.
+        let decoded = sa_vfs::read_text(path)?;
+        if decoded.lossy {
+            warn!(path = %path.display(), "replaced invalid UTF-8 while loading file");
+        }
+        Ok(decoded.text)
     }
 
     fn load_binary_file(&self, path: &Path) -> io::Result<Vec<u8>> {
@@ -874,8 +1294,10 @@ mod tests {
     use solar::interface::source_map::FileLoader;
 
     use super::{
-        SemaSnapshot, VfsOverlayFileLoader, collect_workspace_files, files_with_missing_imports,
-        sema_snapshot_for_project, vfs_snapshot_from_db,
+        SemaCacheConfig, SemaSnapshot, VfsOverlayFileLoader, collect_workspace_files,
+        files_with_missing_imports, rebuild_sema_snapshots, recover_source_text,
+        sema_snapshot_for_file, sema_snapshot_for_project, set_sema_cache_config,
+        vfs_snapshot_from_db,
     };
 
     fn path_map(entries: &[(NormalizedPath, FileId)]) -> HashMap<NormalizedPath, FileId> {
@@ -1055,13 +1477,32 @@ contract Dep {}
         drop_ast_for_file(&mut snapshot, file_id);
 
         let completions = snapshot
-            .identifier_completions(file_id, offset)
+            .identifier_completions(file_id, offset, "", ["Dep", "Main"])
             .expect("completions");
         let labels: Vec<_> = completions.iter().map(|item| item.label.as_str()).collect();
 
         assert!(labels.contains(&"Dep"));
     }
 
+    #[test]
+    fn recovery_blanks_only_the_statement_being_typed() {
+        let text = r#"
+contract Main {
+    struct Point { uint x; }
+    function f() public {
+        Point memory point;
+        point.
+    }
+    function g() public {}
+}
+"#;
+        let recovered = recover_source_text(text).expect("recovered text");
+        assert_eq!(recovered.len(), text.len());
+        assert!(recovered.contains("Point memory point;"));
+        assert!(!recovered.contains("point."));
+        assert!(recovered.contains("function g() public {}"));
+    }
+
     #[test]
     fn collect_workspace_files_skips_unrecoverable_solidity() {
         let fixture = FixtureBuilder::new()
@@ -1159,6 +1600,132 @@ contract Ok {}
         assert_eq!(ok_snapshot as *const SemaSnapshot, Arc::as_ptr(snapshot));
     }
 
+    #[test]
+    fn disabling_fallback_snapshot_drops_it() {
+        let fixture = FixtureBuilder::new()
+            .expect("fixture builder")
+            .file(
+                "src/Main.sol",
+                r#"
+import "./Missing.sol";
+contract Main {}
+"#,
+            )
+            .file(
+                "src/Ok.sol",
+                r#"
+contract Ok {}
+"#,
+            )
+            .build()
+            .expect("fixture");
+
+        let vfs = fixture.vfs_snapshot();
+        let mut db = Database::default();
+        populate_db_from_vfs(&mut db, vfs);
+
+        let project_id = ProjectId::from_raw(0);
+        db.set_project_input(project_id, Arc::new(fixture.config().clone()));
+        set_sema_cache_config(
+            &mut db,
+            SemaCacheConfig {
+                capacity: 1,
+                fallback_snapshot: false,
+            },
+        );
+
+        let result = sema_snapshot_for_project(&db, db.project_input(project_id));
+        assert!(result.snapshot.is_some());
+        assert!(result.no_imports_snapshot.is_none());
+        let ok_file_id = fixture.file_id("src/Ok.sol").expect("ok file id");
+        assert!(result.for_file(ok_file_id).is_some());
+
+        set_sema_cache_config(&mut db, SemaCacheConfig::default());
+        let result = sema_snapshot_for_project(&db, db.project_input(project_id));
+        assert!(result.no_imports_snapshot.is_some());
+    }
+
+    #[test]
+    fn file_snapshot_is_rebuilt_only_for_edits_in_its_imports() {
+        let fixture = FixtureBuilder::new()
+            .expect("fixture builder")
+            .file(
+                "src/Main.sol",
+                r#"
+import "./Dep.sol";
+contract Main is Dep {}
+"#,
+            )
+            .file("src/Dep.sol", "contract Dep {}")
+            .file("src/Other.sol", "contract Other {}")
+            .build()
+            .expect("fixture");
+
+        let vfs = fixture.vfs_snapshot();
+        let mut db = Database::default();
+        populate_db_from_vfs(&mut db, vfs);
+        let project_id = ProjectId::from_raw(0);
+        db.set_project_input(project_id, Arc::new(fixture.config().clone()));
+
+        let main_id = fixture.file_id("src/Main.sol").expect("main file id");
+        let dep_id = fixture.file_id("src/Dep.sol").expect("dep file id");
+        let other_id = fixture.file_id("src/Other.sol").expect("other file id");
+        let snapshot_ptr = |db: &Database| {
+            let result = sema_snapshot_for_file(db, db.project_input(project_id), main_id);
+            let snapshot = result.for_file(main_id).expect("main snapshot");
+            assert!(snapshot.source_id_for_file(dep_id).is_some());
+            assert!(snapshot.source_id_for_file(other_id).is_none());
+            snapshot as *const SemaSnapshot
+        };
+
+        let before = snapshot_ptr(&db);
+        let other_path = db.file_path(other_id);
+        db.set_file(
+            other_id,
+            Arc::from("contract Other { uint x; }"),
+            1,
+            LanguageKind::Solidity,
+            other_path,
+        );
+        assert_eq!(snapshot_ptr(&db), before);
+
+        let dep_path = db.file_path(dep_id);
+        db.set_file(
+            dep_id,
+            Arc::from("contract Dep { uint y; }"),
+            1,
+            LanguageKind::Solidity,
+            dep_path,
+        );
+        assert_ne!(snapshot_ptr(&db), before);
+    }
+
+    #[test]
+    fn rebuilding_sema_snapshots_compiles_again_without_edits() {
+        let fixture = FixtureBuilder::new()
+            .expect("fixture builder")
+            .file("src/Main.sol", "contract Main {}")
+            .build()
+            .expect("fixture");
+
+        let vfs = fixture.vfs_snapshot();
+        let mut db = Database::default();
+        populate_db_from_vfs(&mut db, vfs);
+        let project_id = ProjectId::from_raw(0);
+        db.set_project_input(project_id, Arc::new(fixture.config().clone()));
+
+        let main_id = fixture.file_id("src/Main.sol").expect("main file id");
+        let snapshot_ptr = |db: &Database| {
+            let result = sema_snapshot_for_file(db, db.project_input(project_id), main_id);
+            result.for_file(main_id).expect("main snapshot") as *const SemaSnapshot
+        };
+
+        let before = snapshot_ptr(&db);
+        assert_eq!(snapshot_ptr(&db), before);
+        rebuild_sema_snapshots(&mut db);
+        assert_ne!(snapshot_ptr(&db), before);
+    }
+
     #[test]
     fn vfs_overlay_loader_reads_snapshot_when_disk_missing() {
         let fixture = FixtureBuilder::new()
@@ -1186,6 +1753,22 @@ contract Main {}
         assert_eq!(loaded, snapshot_text);
     }
 
+    #[test]
+    fn vfs_overlay_loader_decodes_disk_files_leniently() {
+        let fixture = FixtureBuilder::new()
+            .expect("fixture builder")
+            .file("src/Main.sol", "contract Main {}")
+            .build()
+            .expect("fixture");
+
+        let file_path = fixture.root().join("src/Latin1.sol");
+        fs::write(&file_path, b"\xEF\xBB\xBF// caf\xE9\r\ncontract Latin1 {}").expect("write file");
+
+        let loader = VfsOverlayFileLoader::new(fixture.vfs_snapshot().clone());
+        let loaded = loader.load_file(&file_path).expect("load file");
+        assert_eq!(loaded, "// caf\u{FFFD}\r\ncontract Latin1 {}");
+    }
+
     #[cfg(unix)]
     #[test]
     fn file_id_for_path_resolves_symlinked_paths() {
@@ -42,16 +42,19 @@ pub(crate) struct SemaReferenceIndex {
 }
 
 impl SemaReferenceIndex {
-    pub(crate) fn new(snapshot: &SemaSnapshot) -> Self {
+    /// `checkpoint` runs between sources, outside the compiler context, so that it may unwind.
+    pub(crate) fn new(snapshot: &SemaSnapshot, checkpoint: &dyn Fn()) -> Self {
         let mut references = HashMap::new();
         let source_map = Arc::clone(&snapshot.source_map);
         let file_id_by_source = snapshot.file_id_by_source.clone();
 
-        snapshot.with_gcx(|gcx| {
-            for source_id in gcx.hir.source_ids() {
-                let Some(file_id) = file_id_by_source.get(&source_id).copied() else {
-                    continue;
-                };
+        let source_ids = snapshot.with_gcx(|gcx| gcx.hir.source_ids().collect::<Vec<_>>());
+        for source_id in source_ids {
+            checkpoint();
+            let Some(file_id) = file_id_by_source.get(&source_id).copied() else {
+                continue;
+            };
+            snapshot.with_gcx(|gcx| {
                 let source = gcx.hir.source(source_id);
                 let source_text = Arc::clone(&source.file.src);
                 let mut collector = ReferenceCollector::new(
@@ -64,8 +67,8 @@ impl SemaReferenceIndex {
                     &mut references,
                 );
                 collector.collect_source(source);
-            }
-        });
+            });
+        }
 
         for refs in references.values_mut() {
             refs.sort_by(|left, right| {
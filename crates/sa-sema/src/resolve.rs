@@ -38,6 +38,45 @@ pub struct ResolvedSymbol {
     pub origin_range: TextRange,
 }
 
+/// The type of an expression, see [`SemaSnapshot::type_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeInfo {
+    /// The type as Solidity spells it, e.g. `uint256[]` or `struct Main.Point`.
+    pub label: String,
+    /// Where a reference type lives. `None` for value types.
+    pub location: Option<TypeLocation>,
+    /// The expression the type belongs to.
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeLocation {
+    Storage,
+    Transient,
+    Memory,
+    Calldata,
+}
+
+impl From<DataLocation> for TypeLocation {
+    fn from(location: DataLocation) -> Self {
+        match location {
+            DataLocation::Storage => Self::Storage,
+            DataLocation::Transient => Self::Transient,
+            DataLocation::Memory => Self::Memory,
+            DataLocation::Calldata => Self::Calldata,
+        }
+    }
+}
+
+/// Which overload a call binds to, see [`SemaSnapshot::resolve_call`].
+#[derive(Debug, Clone)]
+pub enum CallResolution {
+    Resolved(ResolvedSymbol),
+    /// The overloads the call could not be narrowed down from: those taking as many
+    /// arguments as the call passes, or every overload when none do.
+    Ambiguous(Vec<ResolvedSymbol>),
+}
+
 #[derive(Debug, Clone)]
 pub enum ResolveOutcome {
     Unavailable,
@@ -67,6 +106,69 @@ impl SemaSnapshot {
             resolver.finish()
         })
     }
+
+    /// Resolves the innermost call containing `offset` (in its callee or its arguments) to
+    /// the overload it binds to. Returns `None` outside calls or for calls of things that are
+    /// not declared items (builtins, function pointers).
+    pub fn resolve_call(&self, file_id: FileId, offset: TextSize) -> Option<CallResolution> {
+        let source_id = self.source_id_for_file(file_id)?;
+        let source_map = Arc::clone(&self.source_map);
+        let file_id_by_source = self.file_id_by_source.clone();
+        self.with_gcx(move |gcx| {
+            let source = gcx.hir.source(source_id);
+            let source_text = Arc::clone(&source.file.src);
+            let mut resolver = Resolver::new(
+                gcx,
+                source_map,
+                file_id_by_source,
+                offset,
+                source_id,
+                source_text,
+            );
+            resolver.call = Some(None);
+            resolver.resolve_source(source);
+            resolver
+                .call
+                .flatten()
+                .and_then(|(_, resolution)| resolution)
+        })
+    }
+
+    /// Returns the type of the innermost expression containing `offset`, or `None` when the
+    /// offset is not inside an expression or its type cannot be determined.
+    pub fn type_at(&self, file_id: FileId, offset: TextSize) -> Option<TypeInfo> {
+        let source_id = self.source_id_for_file(file_id)?;
+        self.with_gcx(|gcx| {
+            let (range, ty) = self.expr_ty_at(gcx, source_id, offset)?;
+            Some(TypeInfo {
+                label: ty.display(gcx).to_string(),
+                location: ty.loc().map(TypeLocation::from),
+                range,
+            })
+        })
+    }
+
+    /// The innermost expression containing `offset` and its type.
+    pub(crate) fn expr_ty_at<'gcx>(
+        &self,
+        gcx: Gcx<'gcx>,
+        source_id: hir::SourceId,
+        offset: TextSize,
+    ) -> Option<(TextRange, Ty<'gcx>)> {
+        let source = gcx.hir.source(source_id);
+        let mut resolver = Resolver::new(
+            gcx,
+            Arc::clone(&self.source_map),
+            self.file_id_by_source.clone(),
+            offset,
+            source_id,
+            Arc::clone(&source.file.src),
+        );
+        resolver.expr_ty = Some(None);
+        resolver.resolve_source(source);
+        let (range, ty) = resolver.expr_ty.flatten()?;
+        Some((range, ty?))
+    }
 }
 
 struct Resolver<'gcx> {
@@ -79,6 +181,11 @@ struct Resolver<'gcx> {
     source_id: hir::SourceId,
     source_text: Arc<String>,
     import_name_counts: Option<HashMap<String, usize>>,
+    /// Set to `Some(None)` to track the innermost expression containing the offset and its
+    /// type (`None` when it could not be computed).
+    expr_ty: Option<Option<(TextRange, Option<Ty<'gcx>>)>>,
+    /// Like `expr_ty`, for the innermost call containing the offset.
+    call: Option<Option<(TextRange, Option<CallResolution>)>>,
 }
 
 impl<'gcx> Resolver<'gcx> {
@@ -100,6 +207,8 @@ impl<'gcx> Resolver<'gcx> {
             source_id,
             source_text,
             import_name_counts: None,
+            expr_ty: None,
+            call: None,
         }
     }
 
@@ -264,6 +373,7 @@ impl<'gcx> Resolver<'gcx> {
     }
 
     fn visit_expr(&mut self, expr: &hir::Expr<'gcx>) {
+        self.record_expr_ty(expr);
         if let hir::ExprKind::Call(callee, args, _) = &expr.kind
             && self.handle_named_arg_field(callee, args)
         {
@@ -279,6 +389,9 @@ impl<'gcx> Resolver<'gcx> {
 
         match &expr.kind {
             hir::ExprKind::Call(callee, args, opts) => {
+                // The callee may be resolved below without being visited.
+                self.record_expr_ty(callee);
+                self.record_call(expr, callee, args);
                 let mut handled = false;
                 if let Some(callee_range) = self.span_to_text_range(callee.span)
                     && range_contains(callee_range, self.offset)
@@ -1150,15 +1263,16 @@ impl<'gcx> Resolver<'gcx> {
         })
     }
 
-    fn arg_types(&self, args: &hir::CallArgs<'gcx>) -> Option<Vec<Ty<'gcx>>> {
+    fn arg_types(&mut self, args: &hir::CallArgs<'gcx>) -> Option<Vec<Ty<'gcx>>> {
         match &args.kind {
             hir::CallArgsKind::Unnamed(exprs) => {
                 let mut types = Vec::with_capacity(exprs.len());
                 for expr in exprs.iter() {
-                    match &expr.kind {
-                        hir::ExprKind::Lit(lit) => types.push(self.gcx.type_of_lit(lit)),
-                        _ => return None,
-                    }
+                    let ty = match &expr.kind {
+                        hir::ExprKind::Lit(lit) => self.gcx.type_of_lit(lit),
+                        _ => self.receiver_ty(expr)?,
+                    };
+                    types.push(ty);
                 }
                 Some(types)
             }
@@ -1383,6 +1497,103 @@ impl<'gcx> Resolver<'gcx> {
         Some(TextRange::new(start, end))
     }
 
+    fn record_expr_ty(&mut self, expr: &hir::Expr<'gcx>) {
+        let Some(current) = &self.expr_ty else {
+            return;
+        };
+        let Some(range) = self.span_to_text_range(expr.span) else {
+            return;
+        };
+        if !range_contains(range, self.offset)
+            || current.is_some_and(|(best, _)| range_len(range) >= range_len(best))
+        {
+            return;
+        }
+        let ty = self.receiver_ty(expr);
+        self.expr_ty = Some(Some((range, ty)));
+    }
+
+    fn record_call(
+        &mut self,
+        call: &hir::Expr<'gcx>,
+        callee: &hir::Expr<'gcx>,
+        args: &hir::CallArgs<'gcx>,
+    ) {
+        let Some(current) = &self.call else {
+            return;
+        };
+        let Some(range) = self.span_to_text_range(call.span) else {
+            return;
+        };
+        if !range_contains(range, self.offset)
+            || current
+                .as_ref()
+                .is_some_and(|(best, _)| range_len(range) >= range_len(*best))
+        {
+            return;
+        }
+        let resolution = self.call_resolution(callee, args);
+        self.call = Some(Some((range, resolution)));
+    }
+
+    fn call_resolution(
+        &mut self,
+        callee: &hir::Expr<'gcx>,
+        args: &hir::CallArgs<'gcx>,
+    ) -> Option<CallResolution> {
+        let (items, origin) = match &callee.kind {
+            hir::ExprKind::Ident(res) => {
+                let items = res
+                    .iter()
+                    .filter_map(|res| match res {
+                        hir::Res::Item(item_id) => Some(*item_id),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                (items, callee.span)
+            }
+            hir::ExprKind::Member(base, ident) if self.is_super_expr(base) => {
+                let item = self.super_member_item(ident, Some(args))?;
+                (vec![item], ident.span)
+            }
+            hir::ExprKind::Member(base, ident) => {
+                let items =
+                    self.member_items_for_access(base, ident, ContractMemberAccess::Call)?;
+                (items, ident.span)
+            }
+            _ => return None,
+        };
+        if items.is_empty() {
+            return None;
+        }
+        let origin_range = self.span_to_text_range(origin)?;
+        if let Some(item_id) = self.resolve_call_overloads(&items, args) {
+            return self
+                .symbol_for_item(item_id, origin_range)
+                .map(CallResolution::Resolved);
+        }
+        let arity_matches = items
+            .iter()
+            .copied()
+            .filter(|item_id| {
+                self.gcx
+                    .type_of_item(*item_id)
+                    .parameters()
+                    .is_some_and(|params| params.len() == args.len())
+            })
+            .collect::<Vec<_>>();
+        let candidates = if arity_matches.is_empty() {
+            items
+        } else {
+            arity_matches
+        };
+        let symbols = candidates
+            .into_iter()
+            .filter_map(|item_id| self.symbol_for_item(item_id, origin_range))
+            .collect();
+        Some(CallResolution::Ambiguous(symbols))
+    }
+
     fn consider(&mut self, range: TextRange, resolution: CandidateResolution) {
         let replace = match &self.best {
             None => true,
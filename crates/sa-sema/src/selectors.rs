@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use alloy_primitives::keccak256;
+use sa_base_db::FileId;
+use sa_span::TextRange;
+use solar::ast::FunctionKind;
+use solar::sema::{Gcx, hir};
+
+use crate::SemaSnapshot;
+
+/// Returns the 4-byte selector of a canonical function signature such as
+/// `transfer(address,uint256)`.
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Custom errors are selected like functions, by the first 4 bytes of the signature hash.
+pub fn error_selector(signature: &str) -> [u8; 4] {
+    function_selector(signature)
+}
+
+/// Returns topic 0 of a non-anonymous event with the given canonical signature.
+pub fn event_topic(signature: &str) -> [u8; 32] {
+    keccak256(signature.as_bytes()).0
+}
+
+/// Formats a selector or topic the way solc and block explorers print it, e.g. `0xa9059cbb`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 + bytes.len() * 2);
+    hex.push_str("0x");
+    for byte in bytes {
+        hex.push_str(&format!("{byte:02x}"));
+    }
+    hex
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SelectorKind {
+    Function,
+    Error,
+    Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorEntry {
+    pub kind: SelectorKind,
+    pub name: String,
+    /// The canonical signature the selector is hashed from, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    /// The selector (or topic 0 for events) as `0x`-prefixed hex.
+    pub selector: String,
+    /// Where the function, error or event is declared; inherited ones live in a base contract.
+    pub file_id: FileId,
+    pub range: TextRange,
+}
+
+impl SemaSnapshot {
+    /// Lists the selectors of the contract named `name` at `name_range`: its external
+    /// functions (including public getters), then the errors and non-anonymous events declared
+    /// in it or its bases. Inherited entries are included; overridden ones are listed once.
+    pub fn selector_table(
+        &self,
+        file_id: FileId,
+        name_range: TextRange,
+        name: &str,
+    ) -> Option<Vec<SelectorEntry>> {
+        self.with_gcx(|gcx| {
+            let item_id = self.item_id_for_name_range(gcx, file_id, name_range, name, None)?;
+            let contract_id = item_id.as_contract()?;
+            let mut entries = Vec::new();
+            let mut seen = HashSet::new();
+
+            for function in gcx.interface_functions(contract_id).all() {
+                self.push_selector_entry(
+                    gcx,
+                    function.id.into(),
+                    SelectorKind::Function,
+                    &mut seen,
+                    &mut entries,
+                );
+            }
+
+            let contract = gcx.hir.contract(contract_id);
+            let bases = if contract.linearized_bases.is_empty() {
+                std::slice::from_ref(&contract_id)
+            } else {
+                contract.linearized_bases
+            };
+            for &base_id in bases {
+                for &item_id in gcx.hir.contract(base_id).items {
+                    let kind = match item_id {
+                        hir::ItemId::Error(_) => SelectorKind::Error,
+                        hir::ItemId::Event(event_id) if !gcx.hir.event(event_id).anonymous => {
+                            SelectorKind::Event
+                        }
+                        _ => continue,
+                    };
+                    self.push_selector_entry(gcx, item_id, kind, &mut seen, &mut entries);
+                }
+            }
+
+            Some(entries)
+        })
+    }
+
+    /// The canonical signature and selector of the function, error or event declared at
+    /// `name_range`, with topic 0 standing in for an event's selector. Functions outside the
+    /// external interface (internal, private, modifiers, constructors) have none.
+    pub fn selector_for_definition(
+        &self,
+        file_id: FileId,
+        name_range: TextRange,
+        name: &str,
+        container: Option<&str>,
+    ) -> Option<(String, String)> {
+        self.with_gcx(|gcx| {
+            let item_id = self.item_id_for_name_range(gcx, file_id, name_range, name, container)?;
+            let selector = match item_id {
+                hir::ItemId::Function(function_id) => {
+                    let function = gcx.hir.function(function_id);
+                    if function.kind != FunctionKind::Function
+                        || function.visibility < hir::Visibility::Public
+                    {
+                        return None;
+                    }
+                    function_selector
+                }
+                hir::ItemId::Error(_) => error_selector,
+                hir::ItemId::Event(event_id) if !gcx.hir.event(event_id).anonymous => {
+                    let signature = gcx.item_signature(item_id).to_string();
+                    let topic = to_hex(&event_topic(&signature));
+                    return Some((signature, topic));
+                }
+                _ => return None,
+            };
+            let signature = gcx.item_signature(item_id).to_string();
+            let selector = to_hex(&selector(&signature));
+            Some((signature, selector))
+        })
+    }
+
+    fn push_selector_entry(
+        &self,
+        gcx: Gcx<'_>,
+        item_id: hir::ItemId,
+        kind: SelectorKind,
+        seen: &mut HashSet<(SelectorKind, String)>,
+        entries: &mut Vec<SelectorEntry>,
+    ) {
+        let signature = gcx.item_signature(item_id).to_string();
+        if !seen.insert((kind, signature.clone())) {
+            return;
+        }
+        let item = gcx.hir.item(item_id);
+        let Some(file_id) = self.file_id_for_source(item.source()) else {
+            return;
+        };
+        let Some(range) = self.item_name_range(item) else {
+            return;
+        };
+        let selector = match kind {
+            SelectorKind::Function => to_hex(&function_selector(&signature)),
+            SelectorKind::Error => to_hex(&error_selector(&signature)),
+            SelectorKind::Event => to_hex(&event_topic(&signature)),
+        };
+        entries.push(SelectorEntry {
+            kind,
+            name: gcx.item_name(item_id).to_string(),
+            signature,
+            selector,
+            file_id,
+            range,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_canonical_signatures() {
+        assert_eq!(
+            to_hex(&function_selector("transfer(address,uint256)")),
+            "0xa9059cbb"
+        );
+        assert_eq!(to_hex(&error_selector("Error(string)")), "0x08c379a0");
+        assert_eq!(
+            to_hex(&event_topic("Transfer(address,address,uint256)")),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use alloy_primitives::U256;
+use sa_base_db::FileId;
+use sa_span::TextRange;
+use solar::ast::{DataLocation, ElementaryType};
+use solar::sema::ty::{Ty, TyKind};
+use solar::sema::{Gcx, hir};
+
+use crate::SemaSnapshot;
+
+/// Where one state variable lives, following solc's storage layout rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageSlot {
+    pub name: String,
+    /// The contract declaring the variable; inherited ones come from a base contract.
+    pub contract: String,
+    /// The slot in decimal, as `solc --storage-layout` prints it.
+    pub slot: String,
+    /// The byte offset inside the slot, non-zero for packed value types.
+    pub offset: u32,
+    /// The type as Solidity spells it, e.g. `mapping(address => uint256)`.
+    pub ty: String,
+    pub file_id: FileId,
+    pub range: TextRange,
+}
+
+impl SemaSnapshot {
+    /// Computes the storage layout of the contract named `name` at `name_range`: its state
+    /// variables and those of its bases, most base first. Constants, immutables and transient
+    /// variables take no storage slot and are left out.
+    pub fn storage_layout(
+        &self,
+        file_id: FileId,
+        name_range: TextRange,
+        name: &str,
+    ) -> Option<Vec<StorageSlot>> {
+        self.with_gcx(|gcx| {
+            let item_id = self.item_id_for_name_range(gcx, file_id, name_range, name, None)?;
+            let contract_id = item_id.as_contract()?;
+            let contract = gcx.hir.contract(contract_id);
+            let bases = if contract.linearized_bases.is_empty() {
+                std::slice::from_ref(&contract_id)
+            } else {
+                contract.linearized_bases
+            };
+
+            let mut packer = Packer::default();
+            let mut slots = Vec::new();
+            for &base_id in bases.iter().rev() {
+                let base_name = gcx.item_name(base_id.into()).to_string();
+                for &item_id in gcx.hir.contract(base_id).items {
+                    let hir::ItemId::Variable(var_id) = item_id else {
+                        continue;
+                    };
+                    let var = gcx.hir.variable(var_id);
+                    if var.kind != hir::VarKind::State
+                        || var.mutability.is_some()
+                        || var.data_location == Some(DataLocation::Transient)
+                    {
+                        continue;
+                    }
+                    let ty = gcx.type_of_hir_ty(&var.ty);
+                    let (slot, offset) = packer.place(storage_size(gcx, ty));
+                    let item = gcx.hir.item(item_id);
+                    let (Some(file_id), Some(range)) = (
+                        self.file_id_for_source(item.source()),
+                        self.item_name_range(item),
+                    ) else {
+                        continue;
+                    };
+                    slots.push(StorageSlot {
+                        name: gcx.item_name(item_id).to_string(),
+                        contract: base_name.clone(),
+                        slot: slot.to_string(),
+                        offset,
+                        ty: ty.display(gcx).to_string(),
+                        file_id,
+                        range,
+                    });
+                }
+            }
+            Some(slots)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StorageSize {
+    /// A value type, packed with its neighbours when they fit in one slot.
+    Bytes(u32),
+    /// A reference type, which starts a new slot and is not packed with what follows.
+    Slots(U256),
+}
+
+#[derive(Debug, Default)]
+struct Packer {
+    slot: U256,
+    offset: u32,
+}
+
+impl Packer {
+    fn place(&mut self, size: StorageSize) -> (U256, u32) {
+        match size {
+            StorageSize::Bytes(bytes) => {
+                if self.offset + bytes > 32 {
+                    self.next_slot();
+                }
+                let place = (self.slot, self.offset);
+                self.offset += bytes;
+                place
+            }
+            StorageSize::Slots(slots) => {
+                if self.offset > 0 {
+                    self.next_slot();
+                }
+                let place = (self.slot, 0);
+                self.slot = self.slot.saturating_add(slots);
+                place
+            }
+        }
+    }
+
+    fn next_slot(&mut self) {
+        self.slot = self.slot.saturating_add(U256::from(1));
+        self.offset = 0;
+    }
+
+    /// The number of slots used so far, counting a partly filled one.
+    fn used_slots(&self) -> U256 {
+        if self.offset > 0 {
+            self.slot.saturating_add(U256::from(1))
+        } else {
+            self.slot
+        }
+    }
+}
+
+fn storage_size<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> StorageSize {
+    match ty.peel_refs().kind {
+        TyKind::Elementary(elementary) => match elementary {
+            ElementaryType::Address(_) => StorageSize::Bytes(20),
+            ElementaryType::Bool => StorageSize::Bytes(1),
+            ElementaryType::String | ElementaryType::Bytes => StorageSize::Slots(U256::from(1)),
+            ElementaryType::Int(size)
+            | ElementaryType::UInt(size)
+            | ElementaryType::FixedBytes(size)
+            | ElementaryType::Fixed(size, _)
+            | ElementaryType::UFixed(size, _) => StorageSize::Bytes(u32::from(size.bytes())),
+        },
+        TyKind::Contract(_) => StorageSize::Bytes(20),
+        // Solidity caps enums at 256 members, so they always fit in one byte.
+        TyKind::Enum(_) => StorageSize::Bytes(1),
+        TyKind::Udvt(underlying, _) => storage_size(gcx, underlying),
+        TyKind::FnPtr(function) => match function.visibility {
+            hir::Visibility::External => StorageSize::Bytes(24),
+            _ => StorageSize::Bytes(8),
+        },
+        TyKind::Mapping(..) | TyKind::DynArray(_) => StorageSize::Slots(U256::from(1)),
+        TyKind::Array(element, len) => match storage_size(gcx, element) {
+            StorageSize::Bytes(bytes) => {
+                let per_slot = U256::from(32 / bytes.max(1));
+                let slots = len.saturating_add(per_slot - U256::from(1)) / per_slot;
+                StorageSize::Slots(slots)
+            }
+            StorageSize::Slots(slots) => StorageSize::Slots(len.saturating_mul(slots)),
+        },
+        TyKind::Struct(struct_id) => {
+            let mut packer = Packer::default();
+            for &field in gcx.struct_field_types(struct_id) {
+                packer.place(storage_size(gcx, field));
+            }
+            StorageSize::Slots(packer.used_slots().max(U256::from(1)))
+        }
+        _ => StorageSize::Slots(U256::from(1)),
+    }
+}
@@ -1,5 +1,5 @@
 use sa_base_db::FileId;
-use sa_span::TextRange;
+use sa_span::{TextRange, TextSize};
 use solar::ast::FunctionKind;
 use solar::sema::hir;
 
@@ -62,7 +62,9 @@ fn document_symbol_for_item(
         let contract = gcx.hir.contract(contract_id);
         let mut children = Vec::new();
         for &child_id in contract.items {
-            if let Some(child) = symbol_from_item(snapshot, gcx, child_id)
+            let child = symbol_from_item(snapshot, gcx, child_id)
+                .or_else(|| special_function_symbol(snapshot, gcx, child_id));
+            if let Some(child) = child
                 && child.file_id == file_id
             {
                 children.push(child);
@@ -74,6 +76,39 @@ fn document_symbol_for_item(
     Some(symbol)
 }
 
+/// Constructors, `receive` and `fallback` have no name; the outline lists them under their
+/// keyword. They are left out of workspace symbols since they cannot be searched for by name.
+fn special_function_symbol(
+    snapshot: &SemaSnapshot,
+    gcx: solar::sema::Gcx<'_>,
+    item_id: hir::ItemId,
+) -> Option<SemaSymbol> {
+    let hir::ItemId::Function(id) = item_id else {
+        return None;
+    };
+    let func = gcx.hir.function(id);
+    if func.name.is_some()
+        || !matches!(
+            func.kind,
+            FunctionKind::Constructor | FunctionKind::Receive | FunctionKind::Fallback
+        )
+    {
+        return None;
+    }
+    let item = gcx.hir.item(item_id);
+    let range = snapshot.span_to_text_range(item.span())?;
+    let file_id = *snapshot.file_id_by_source.get(&item.source())?;
+    let keyword = func.kind.to_str();
+    Some(SemaSymbol {
+        kind: ResolvedSymbolKind::Function,
+        name: keyword.to_string(),
+        file_id,
+        range,
+        selection_range: TextRange::at(range.start(), TextSize::of(keyword)),
+        children: Vec::new(),
+    })
+}
+
 fn symbol_from_item(
     snapshot: &SemaSnapshot,
     gcx: solar::sema::Gcx<'_>,
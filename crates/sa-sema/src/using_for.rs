@@ -0,0 +1,79 @@
+use sa_base_db::FileId;
+use sa_span::{TextRange, TextSize};
+use solar::sema::hir;
+use solar::sema::ty::TyKind;
+
+use crate::SemaSnapshot;
+use crate::completion::{contract_at_offset, detail_for_ty};
+use crate::ty_utils::default_memory_if_ref;
+
+/// A library or free function attached to a type by a `using ... for` directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundFunction {
+    pub name: String,
+    /// The library declaring the function, `None` for free functions bound with `using {f} for T`.
+    pub library: Option<String>,
+    /// The type the function is attached to, e.g. `uint256`.
+    pub receiver: String,
+    /// The signature without the bound first parameter, e.g. `(uint256) -> (uint256)`.
+    pub detail: Option<String>,
+    pub file_id: FileId,
+    /// The range of the function name.
+    pub range: TextRange,
+}
+
+impl SemaSnapshot {
+    /// Lists the functions attached to the type of the expression at `offset` by the `using`
+    /// directives in scope there: those of the enclosing contract, file-level ones and `global`
+    /// ones declared next to the type. Directives listing functions (`using {f, g} for T`) are
+    /// included alongside library ones.
+    pub fn bound_functions_at(
+        &self,
+        file_id: FileId,
+        offset: TextSize,
+    ) -> Option<Vec<BoundFunction>> {
+        let source_id = self.source_id_for_file(file_id)?;
+        self.with_gcx(|gcx| {
+            let (_, ty) = self.expr_ty_at(gcx, source_id, offset)?;
+            let ty = default_memory_if_ref(gcx, ty);
+            if matches!(ty.kind, TyKind::Type(_) | TyKind::Module(_)) {
+                return Some(Vec::new());
+            }
+            let contract_id = contract_at_offset(self, gcx, source_id, offset);
+            let receiver = ty.display(gcx).to_string();
+            let mut functions = Vec::new();
+            for member in gcx.members_of(ty, source_id, contract_id).iter() {
+                let Some(hir::Res::Item(hir::ItemId::Function(function_id))) = member.res else {
+                    continue;
+                };
+                let function = gcx.hir.function(function_id);
+                let library = match function.contract {
+                    Some(contract_id) => {
+                        let contract = gcx.hir.contract(contract_id);
+                        if !contract.kind.is_library() {
+                            continue;
+                        }
+                        Some(contract.name.as_str().to_string())
+                    }
+                    None => None,
+                };
+                let item = gcx.hir.item(hir::ItemId::Function(function_id));
+                let Some(file_id) = self.file_id_for_source(item.source()) else {
+                    continue;
+                };
+                let Some(range) = self.item_name_range(item) else {
+                    continue;
+                };
+                functions.push(BoundFunction {
+                    name: member.name.to_string(),
+                    library,
+                    receiver: receiver.clone(),
+                    detail: detail_for_ty(gcx, member.ty),
+                    file_id,
+                    range,
+                });
+            }
+            Some(functions)
+        })
+    }
+}
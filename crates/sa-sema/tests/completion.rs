@@ -63,8 +63,9 @@ contract Foo {}
 
     let snapshot = snapshot_for_fixture(&fixture);
     let main_file_id = fixture.file_id("src/Main.sol").expect("main file id");
+    let visible_names = ["Foo", "Foo", "DepAlias", "Glob", "Renamed", "Main"];
     let items = snapshot
-        .identifier_completions(main_file_id, offset)
+        .identifier_completions(main_file_id, offset, "", visible_names)
         .expect("completions");
 
     let foo_count = items
@@ -120,7 +121,7 @@ contract Main {
     let snapshot = snapshot_for_fixture(&fixture);
     let file_id = fixture.file_id("src/Main.sol").expect("main file id");
     let items = snapshot
-        .identifier_completions(file_id, offset)
+        .identifier_completions(file_id, offset, "", ["Main"])
         .expect("completions");
     let labels = completion_labels(&items);
 
@@ -130,6 +131,45 @@ contract Main {
     assert!(!labels.contains(&"later"));
 }
 
+#[test]
+fn identifier_completions_only_build_items_matching_the_prefix() {
+    let (main_text, offset) = extract_offset(
+        r#"
+pragma solidity ^0.8.20;
+
+contract Token {}
+
+contract Main {
+    uint256 total;
+    uint256 other;
+
+    function transfer() public {}
+
+    function test(uint256 tag) public {
+        uint256 skipped = 1;
+        /*caret*/
+    }
+}
+"#,
+    );
+
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", main_text)
+        .build()
+        .expect("fixture");
+
+    let snapshot = snapshot_for_fixture(&fixture);
+    let file_id = fixture.file_id("src/Main.sol").expect("main file id");
+    let items = snapshot
+        .identifier_completions(file_id, offset, "T", ["Token", "Unknown"])
+        .expect("completions");
+    let mut labels = completion_labels(&items);
+    labels.sort();
+
+    assert_eq!(labels, vec!["Token", "tag", "total", "transfer"]);
+}
+
 #[test]
 fn identifier_completions_include_struct_literal_fields() {
     let (main_text, offset) = extract_offset(
@@ -158,7 +198,7 @@ contract Main {
     let snapshot = snapshot_for_fixture(&fixture);
     let main_file_id = fixture.file_id("src/Main.sol").expect("main file id");
     let items = snapshot
-        .identifier_completions(main_file_id, offset)
+        .identifier_completions(main_file_id, offset, "", ["Foo", "Main"])
         .expect("completions");
     let labels = completion_labels(&items);
 
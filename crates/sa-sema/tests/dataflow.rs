@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use sa_base_db::FileId;
+use sa_sema::{SemaSnapshot, TaintConfig, TaintSink, TaintSource};
+use sa_span::{TextRange, TextSize};
+use sa_test_utils::FixtureBuilder;
+
+fn snapshot_for(text: &str) -> (SemaSnapshot, FileId) {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", text)
+        .build()
+        .expect("fixture");
+    let vfs = fixture.vfs_snapshot();
+    let path_to_file_id = vfs
+        .iter()
+        .map(|(file_id, path)| (path.clone(), file_id))
+        .collect::<HashMap<_, _>>();
+    let snapshot = SemaSnapshot::new(fixture.config(), vfs, &path_to_file_id, None, true)
+        .expect("sema snapshot");
+    let file_id = fixture.file_id("src/Main.sol").expect("file id");
+    (snapshot, file_id)
+}
+
+#[test]
+fn taint_flows_from_msg_sender_through_local_to_delegatecall() {
+    let text = r#"
+contract Proxy {
+    address owner;
+
+    function forward() external {
+        address target = msg.sender;
+        target.delegatecall("");
+    }
+
+    function pay() external {
+        payable(owner).transfer(1);
+    }
+}
+"#;
+    let (snapshot, file_id) = snapshot_for(text);
+
+    let findings = snapshot
+        .taint_findings(file_id, &TaintConfig::default())
+        .expect("findings");
+
+    assert_eq!(findings.len(), 1, "{findings:?}");
+    let finding = &findings[0];
+    assert_eq!(finding.source, TaintSource::MsgSender);
+    assert_eq!(finding.sink, TaintSink::DelegateCall);
+    assert_eq!(finding.function, "Proxy.forward");
+    let call_start = text.find("target.delegatecall").expect("call") as u32;
+    assert_eq!(finding.range.start(), TextSize::from(call_start));
+    let witness = finding
+        .witness
+        .iter()
+        .map(|step| &text[step.range.start().into()..step.range.end().into()])
+        .collect::<Vec<_>>();
+    assert_eq!(
+        witness,
+        vec!["msg.sender", "target", "target.delegatecall(\"\")"]
+    );
+}
+
+#[test]
+fn taint_config_filters_sources() {
+    let text = r#"
+contract Vault {
+    function withdraw(address payable to, uint256 amount) external {
+        to.transfer(amount);
+    }
+}
+"#;
+    let (snapshot, file_id) = snapshot_for(text);
+    let config = TaintConfig {
+        sources: vec![TaintSource::MsgSender],
+        ..TaintConfig::default()
+    };
+
+    let findings = snapshot.taint_findings(file_id, &config).expect("findings");
+    assert!(findings.is_empty(), "{findings:?}");
+
+    let findings = snapshot
+        .taint_findings(file_id, &TaintConfig::default())
+        .expect("findings");
+    assert_eq!(findings.len(), 1, "{findings:?}");
+    assert_eq!(findings[0].source, TaintSource::Parameter);
+    assert_eq!(findings[0].sink, TaintSink::Transfer);
+}
+
+#[test]
+fn reaching_definitions_joins_branches() {
+    let text = r#"
+contract C {
+    function f(bool flag) internal pure returns (uint256) {
+        uint256 x = 1;
+        if (flag) {
+            x = 2;
+        } else {
+            x = 3;
+        }
+        return x;
+    }
+}
+"#;
+    let (snapshot, file_id) = snapshot_for(text);
+    let read = text.find("return x").expect("read") as u32 + "return ".len() as u32;
+
+    let defs = snapshot
+        .reaching_definitions(file_id, TextSize::from(read))
+        .expect("reaching definitions");
+
+    let expected = ["x = 2", "x = 3"]
+        .iter()
+        .map(|assign| {
+            let start = text.find(assign).expect("assignment") as u32;
+            TextRange::new(TextSize::from(start), TextSize::from(start + 1))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(defs, expected);
+}
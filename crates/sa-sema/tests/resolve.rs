@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use sa_base_db::FileId;
 use sa_paths::NormalizedPath;
-use sa_sema::{ResolveOutcome, ResolvedSymbolKind, SemaSnapshot};
+use sa_sema::{CallResolution, ResolveOutcome, ResolvedSymbolKind, SemaSnapshot, TypeLocation};
 use sa_span::{TextRange, TextSize};
 use sa_test_support::{extract_offset, extract_offsets};
 use sa_test_utils::{Fixture, FixtureBuilder};
@@ -894,3 +894,118 @@ contract Main {
     assert_eq!(symbol.kind, ResolvedSymbolKind::Variable);
     assert_eq!(symbol.name, "value");
 }
+
+#[test]
+fn type_at_reports_expression_types_with_locations() {
+    let (main_text, offsets) = extract_offsets(
+        r#"
+contract Main {
+    struct Point {
+        uint256 x;
+    }
+
+    Point[] points;
+
+    function test(uint256[] calldata values) public view returns (uint256) {
+        return /*points*/points[0]./*field*/x + /*values*/values[0];
+    }
+}
+"#,
+        &["/*points*/", "/*field*/", "/*values*/"],
+    );
+    let keyword = TextSize::from(main_text.find("contract").expect("contract keyword") as u32);
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", main_text)
+        .build()
+        .expect("fixture");
+
+    let (snapshot, _) = snapshot_for_fixture(&fixture);
+    let main_file_id = fixture.file_id("src/Main.sol").expect("main file id");
+
+    let points = snapshot
+        .type_at(main_file_id, offsets[0])
+        .expect("points type");
+    assert!(points.label.contains("Point[]"), "{}", points.label);
+    assert_eq!(points.location, Some(TypeLocation::Storage));
+    assert_eq!(points.range, range_from_offset(offsets[0], "points".len()));
+
+    let field = snapshot
+        .type_at(main_file_id, offsets[1])
+        .expect("field type");
+    assert_eq!(field.label, "uint256");
+    assert_eq!(field.location, None);
+    assert_eq!(field.range.start(), offsets[0]);
+
+    let values = snapshot
+        .type_at(main_file_id, offsets[2])
+        .expect("values type");
+    assert_eq!(values.location, Some(TypeLocation::Calldata));
+
+    assert!(snapshot.type_at(main_file_id, keyword).is_none());
+}
+
+#[test]
+fn resolve_call_picks_overload_by_argument_types() {
+    let (main_text, offsets) = extract_offsets(
+        r#"
+contract Token {
+    function transfer(address to) public {}
+    function transfer(address to, uint256 amount) public {}
+    function transfer(uint256 id, uint256 amount) public {}
+    function scale(uint8 x) public {}
+    function scale(uint16 x) public {}
+
+    function test(address to, uint256 amount) public {
+        /*typed*/transfer(to, amount);
+        transfer(/*arg*/to);
+        /*ambiguous*/scale(1);
+    }
+}
+"#,
+        &["/*typed*/", "/*arg*/", "/*ambiguous*/"],
+    );
+    let two_args = main_text
+        .find("function transfer(address to, uint256 amount)")
+        .expect("two-arg overload")
+        + "function ".len();
+    let two_args = range_from_offset(TextSize::from(two_args as u32), "transfer".len());
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", main_text)
+        .build()
+        .expect("fixture");
+
+    let (snapshot, _) = snapshot_for_fixture(&fixture);
+    let main_file_id = fixture.file_id("src/Main.sol").expect("main file id");
+
+    match snapshot.resolve_call(main_file_id, offsets[0]) {
+        Some(CallResolution::Resolved(symbol)) => {
+            assert_eq!(symbol.kind, ResolvedSymbolKind::Function);
+            assert_eq!(symbol.definition_range, two_args);
+            assert_eq!(
+                symbol.origin_range,
+                range_from_offset(offsets[0], "transfer".len())
+            );
+        }
+        other => panic!("expected resolved call, got {other:?}"),
+    }
+
+    match snapshot.resolve_call(main_file_id, offsets[1]) {
+        Some(CallResolution::Resolved(symbol)) => {
+            assert_ne!(symbol.definition_range, two_args);
+        }
+        other => panic!("expected resolved call, got {other:?}"),
+    }
+
+    match snapshot.resolve_call(main_file_id, offsets[2]) {
+        Some(CallResolution::Ambiguous(candidates)) => assert_eq!(candidates.len(), 2),
+        other => panic!("expected ambiguous call, got {other:?}"),
+    }
+
+    assert!(
+        snapshot
+            .resolve_call(main_file_id, TextSize::from(0))
+            .is_none()
+    );
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use sa_sema::{SelectorKind, SemaSnapshot};
+use sa_span::{TextRange, TextSize};
+use sa_test_utils::FixtureBuilder;
+
+#[test]
+fn selector_table_lists_inherited_functions_errors_and_events() {
+    let base = r#"
+contract Base {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Anonymous(uint256 value) anonymous;
+
+    function transfer(address to, uint256 amount) public virtual returns (bool) {
+        return true;
+    }
+
+    function helper() internal {}
+}
+"#;
+    let token = r#"
+import "./Base.sol";
+
+contract Token is Base {
+    error Unauthorized(address caller);
+
+    uint256 public totalSupply;
+
+    function transfer(address to, uint256 amount) public override returns (bool) {
+        return false;
+    }
+}
+"#;
+    let name_start = token.find("Token is").expect("contract name") as u32;
+    let name_range = TextRange::new(
+        TextSize::from(name_start),
+        TextSize::from(name_start + "Token".len() as u32),
+    );
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Base.sol", base)
+        .file("src/Token.sol", token)
+        .build()
+        .expect("fixture");
+
+    let vfs = fixture.vfs_snapshot();
+    let path_to_file_id = vfs
+        .iter()
+        .map(|(file_id, path)| (path.clone(), file_id))
+        .collect::<HashMap<_, _>>();
+    let snapshot = SemaSnapshot::new(fixture.config(), vfs, &path_to_file_id, None, true)
+        .expect("sema snapshot");
+    let token_id = fixture.file_id("src/Token.sol").expect("token file id");
+
+    let table = snapshot
+        .selector_table(token_id, name_range, "Token")
+        .expect("selector table");
+    let mut rows = table
+        .iter()
+        .map(|entry| {
+            (
+                entry.kind,
+                entry.signature.as_str(),
+                entry.selector.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    rows.sort();
+
+    assert_eq!(
+        rows,
+        vec![
+            (SelectorKind::Function, "totalSupply()", "0x18160ddd"),
+            (
+                SelectorKind::Function,
+                "transfer(address,uint256)",
+                "0xa9059cbb"
+            ),
+            (SelectorKind::Error, "Unauthorized(address)", "0x8e4a23d6"),
+            (
+                SelectorKind::Event,
+                "Transfer(address,address,uint256)",
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            ),
+        ]
+    );
+    let transfer = table
+        .iter()
+        .find(|entry| entry.name == "transfer")
+        .expect("transfer entry");
+    assert_eq!(transfer.file_id, token_id);
+}
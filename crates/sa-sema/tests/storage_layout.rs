@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use sa_sema::SemaSnapshot;
+use sa_span::{TextRange, TextSize};
+use sa_test_utils::FixtureBuilder;
+
+#[test]
+fn storage_layout_packs_value_types_and_starts_bases_first() {
+    let base = r#"
+contract Base {
+    address owner;
+    bool paused;
+    uint256 constant LIMIT = 10;
+}
+"#;
+    let vault = r#"
+import "./Base.sol";
+
+contract Vault is Base {
+    struct Position {
+        uint128 amount;
+        uint64 since;
+        address holder;
+    }
+
+    uint256 total;
+    Position last;
+    uint8[40] small;
+    mapping(address => uint256) balances;
+    uint16 flags;
+    address immutable token = address(0);
+}
+"#;
+    let name_start = vault.find("Vault is").expect("contract name") as u32;
+    let name_range = TextRange::new(
+        TextSize::from(name_start),
+        TextSize::from(name_start + "Vault".len() as u32),
+    );
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Base.sol", base)
+        .file("src/Vault.sol", vault)
+        .build()
+        .expect("fixture");
+
+    let vfs = fixture.vfs_snapshot();
+    let path_to_file_id = vfs
+        .iter()
+        .map(|(file_id, path)| (path.clone(), file_id))
+        .collect::<HashMap<_, _>>();
+    let snapshot = SemaSnapshot::new(fixture.config(), vfs, &path_to_file_id, None, true)
+        .expect("sema snapshot");
+    let vault_id = fixture.file_id("src/Vault.sol").expect("vault file id");
+
+    let layout = snapshot
+        .storage_layout(vault_id, name_range, "Vault")
+        .expect("storage layout");
+    let rows = layout
+        .iter()
+        .map(|slot| {
+            (
+                slot.contract.as_str(),
+                slot.name.as_str(),
+                slot.slot.as_str(),
+                slot.offset,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            ("Base", "owner", "0", 0),
+            ("Base", "paused", "0", 20),
+            ("Vault", "total", "1", 0),
+            ("Vault", "last", "2", 0),
+            ("Vault", "small", "4", 0),
+            ("Vault", "balances", "6", 0),
+            ("Vault", "flags", "7", 0),
+        ]
+    );
+    let balances = layout
+        .iter()
+        .find(|slot| slot.name == "balances")
+        .expect("balances slot");
+    assert_eq!(balances.ty, "mapping(address => uint256)");
+    assert_eq!(balances.file_id, vault_id);
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use sa_sema::SemaSnapshot;
+use sa_span::TextSize;
+use sa_test_utils::FixtureBuilder;
+
+#[test]
+fn bound_functions_include_library_and_free_function_directives() {
+    let text = r#"
+library MathLib {
+    function double(uint256 x) internal pure returns (uint256) {
+        return x * 2;
+    }
+
+    function isZero(uint256 x) internal pure returns (bool) {
+        return x == 0;
+    }
+}
+
+function triple(uint256 x) pure returns (uint256) {
+    return x * 3;
+}
+
+using {triple} for uint256;
+
+contract Main {
+    using MathLib for uint256;
+
+    function run(uint256 value) public pure returns (uint256) {
+        return value.double();
+    }
+}
+"#;
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", text)
+        .build()
+        .expect("fixture");
+    let vfs = fixture.vfs_snapshot();
+    let path_to_file_id = vfs
+        .iter()
+        .map(|(file_id, path)| (path.clone(), file_id))
+        .collect::<HashMap<_, _>>();
+    let snapshot = SemaSnapshot::new(fixture.config(), vfs, &path_to_file_id, None, true)
+        .expect("sema snapshot");
+    let file_id = fixture.file_id("src/Main.sol").expect("file id");
+    let receiver = text.find("value.double").expect("receiver") as u32;
+
+    let functions = snapshot
+        .bound_functions_at(file_id, TextSize::from(receiver))
+        .expect("bound functions");
+
+    let mut rows = functions
+        .iter()
+        .map(|function| {
+            (
+                function.name.as_str(),
+                function.library.as_deref(),
+                function.receiver.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            ("double", Some("MathLib"), "uint256"),
+            ("isZero", Some("MathLib"), "uint256"),
+            ("triple", None, "uint256"),
+        ]
+    );
+    let double = functions
+        .iter()
+        .find(|function| function.name == "double")
+        .expect("double");
+    let name_start = text.find("double(uint256").expect("double definition") as u32;
+    assert_eq!(double.range.start(), TextSize::from(name_start));
+    assert_eq!(double.file_id, file_id);
+}
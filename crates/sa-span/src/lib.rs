@@ -1,6 +1,15 @@
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+use serde::{Deserialize, Serialize};
+
+mod line_index;
+
+pub use line_index::{LineCol, LineIndex, PositionEncoding};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
 pub struct TextSize {
     raw: u32,
 }
@@ -91,7 +100,7 @@ pub fn is_ident_byte(byte: u8) -> bool {
     byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TextRange {
     start: TextSize,
     end: TextSize,
@@ -136,71 +145,112 @@ pub fn range_contains(range: TextRange, offset: TextSize) -> bool {
 }
 
 pub mod lsp {
-    use super::{TextRange, TextSize};
+    use super::{LineCol, LineIndex, TextRange, TextSize};
 
+    pub use super::PositionEncoding;
     pub use lsp_types::{Position as LspPosition, Range as LspRange};
 
-    pub fn to_lsp_position(offset: TextSize, text: &str) -> LspPosition {
-        let mut line = 0u32;
-        let mut col = 0u32;
-        let target = offset.raw as usize;
-
-        for (idx, ch) in text.char_indices() {
-            if idx >= target {
-                break;
+    impl PositionEncoding {
+        pub fn from_kind(kind: &lsp_types::PositionEncodingKind) -> Option<Self> {
+            match kind.as_str() {
+                "utf-8" => Some(Self::Utf8),
+                "utf-16" => Some(Self::Utf16),
+                "utf-32" => Some(Self::Utf32),
+                _ => None,
             }
+        }
 
-            if ch == '\n' {
-                line += 1;
-                col = 0;
-                continue;
+        pub fn kind(self) -> lsp_types::PositionEncodingKind {
+            match self {
+                Self::Utf8 => lsp_types::PositionEncodingKind::UTF8,
+                Self::Utf16 => lsp_types::PositionEncodingKind::UTF16,
+                Self::Utf32 => lsp_types::PositionEncodingKind::UTF32,
             }
-
-            col += ch.len_utf16() as u32;
         }
+    }
 
-        LspPosition::new(line, col)
+    pub fn to_lsp_position(offset: TextSize, text: &str) -> LspPosition {
+        to_lsp_position_with(offset, text, PositionEncoding::Utf16)
     }
 
-    pub fn from_lsp_position(position: LspPosition, text: &str) -> Option<TextSize> {
-        let mut line = 0u32;
-        let mut col = 0u32;
+    pub fn to_lsp_position_with(
+        offset: TextSize,
+        text: &str,
+        encoding: PositionEncoding,
+    ) -> LspPosition {
+        to_lsp_position_in(offset, &LineIndex::new(text), encoding)
+    }
 
-        for (idx, ch) in text.char_indices() {
-            if line == position.line && col == position.character {
-                return TextSize::try_from(idx).ok();
-            }
+    pub fn to_lsp_position_in(
+        offset: TextSize,
+        index: &LineIndex,
+        encoding: PositionEncoding,
+    ) -> LspPosition {
+        let position = index.to_wide(encoding, index.line_col(offset));
+        LspPosition::new(position.line, position.col)
+    }
 
-            if ch == '\n' {
-                line += 1;
-                col = 0;
-                continue;
-            }
+    pub fn from_lsp_position(position: LspPosition, text: &str) -> Option<TextSize> {
+        from_lsp_position_with(position, text, PositionEncoding::Utf16)
+    }
 
-            let next_col = col + ch.len_utf16() as u32;
-            if line == position.line && position.character < next_col {
-                return None;
-            }
-            col = next_col;
-        }
+    pub fn from_lsp_position_with(
+        position: LspPosition,
+        text: &str,
+        encoding: PositionEncoding,
+    ) -> Option<TextSize> {
+        from_lsp_position_in(position, &LineIndex::new(text), encoding)
+    }
 
-        if line == position.line && col == position.character {
-            TextSize::try_from(text.len()).ok()
-        } else {
-            None
-        }
+    /// Returns `None` for lines past the end of the text and for columns inside a character.
+    /// Columns past the end of a line mean the end of the line, as the specification asks.
+    pub fn from_lsp_position_in(
+        position: LspPosition,
+        index: &LineIndex,
+        encoding: PositionEncoding,
+    ) -> Option<TextSize> {
+        let line_col = LineCol::new(position.line, position.character);
+        index.offset(index.to_utf8(encoding, line_col)?)
     }
 
     pub fn to_lsp_range(range: TextRange, text: &str) -> LspRange {
-        let start = to_lsp_position(range.start(), text);
-        let end = to_lsp_position(range.end(), text);
+        to_lsp_range_with(range, text, PositionEncoding::Utf16)
+    }
+
+    pub fn to_lsp_range_with(range: TextRange, text: &str, encoding: PositionEncoding) -> LspRange {
+        to_lsp_range_in(range, &LineIndex::new(text), encoding)
+    }
+
+    pub fn to_lsp_range_in(
+        range: TextRange,
+        index: &LineIndex,
+        encoding: PositionEncoding,
+    ) -> LspRange {
+        let start = to_lsp_position_in(range.start(), index, encoding);
+        let end = to_lsp_position_in(range.end(), index, encoding);
         LspRange::new(start, end)
     }
 
     pub fn from_lsp_range(range: LspRange, text: &str) -> Option<TextRange> {
-        let start = from_lsp_position(range.start, text)?;
-        let end = from_lsp_position(range.end, text)?;
-        Some(TextRange::new(start, end))
+        from_lsp_range_with(range, text, PositionEncoding::Utf16)
+    }
+
+    pub fn from_lsp_range_with(
+        range: LspRange,
+        text: &str,
+        encoding: PositionEncoding,
+    ) -> Option<TextRange> {
+        from_lsp_range_in(range, &LineIndex::new(text), encoding)
+    }
+
+    pub fn from_lsp_range_in(
+        range: LspRange,
+        index: &LineIndex,
+        encoding: PositionEncoding,
+    ) -> Option<TextRange> {
+        let start = from_lsp_position_in(range.start, index, encoding)?;
+        let end = from_lsp_position_in(range.end, index, encoding)?;
+        (start <= end).then(|| TextRange::new(start, end))
     }
 }
 
@@ -239,6 +289,38 @@ mod tests {
         assert_eq!(lsp::from_lsp_position(pos, text), Some(offset));
     }
 
+    #[test]
+    fn position_encodings_count_their_own_code_units() {
+        use lsp::PositionEncoding::{Utf8, Utf16, Utf32};
+
+        let text = "string s = \"é😀\"; x";
+        let offset = TextSize::of(text) - TextSize::from(1);
+        for (encoding, character) in [(Utf8, 21), (Utf16, 18), (Utf32, 17)] {
+            let pos = lsp::to_lsp_position_with(offset, text, encoding);
+            assert_eq!(pos, lsp::LspPosition::new(0, character));
+            assert_eq!(
+                lsp::from_lsp_position_with(pos, text, encoding),
+                Some(offset)
+            );
+        }
+
+        // The middle of the emoji is not a position in any encoding that splits it.
+        let inside = |character| lsp::LspPosition::new(0, character);
+        assert_eq!(lsp::from_lsp_position_with(inside(14), text, Utf16), None);
+        assert_eq!(lsp::from_lsp_position_with(inside(15), text, Utf8), None);
+    }
+
+    #[test]
+    fn columns_past_the_end_of_a_line_clamp_to_it() {
+        let text = "ab\r\ncd";
+        let pos = lsp::LspPosition::new(0, 10);
+        assert_eq!(lsp::from_lsp_position(pos, text), Some(TextSize::from(2)));
+        let pos = lsp::LspPosition::new(1, 10);
+        assert_eq!(lsp::from_lsp_position(pos, text), Some(TextSize::from(6)));
+        let pos = lsp::LspPosition::new(2, 0);
+        assert_eq!(lsp::from_lsp_position(pos, text), None);
+    }
+
     #[test]
     fn lsp_range_round_trip() {
         let text = "contract Foo {}\nfunction bar() {}";
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use crate::{TextRange, TextSize};
+
+/// The unit columns are counted in. LSP clients negotiate it through `positionEncoding`, and
+/// those that do not negotiate one use UTF-16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+/// A zero-based line and column. The column is in UTF-8 bytes unless a conversion says
+/// otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+impl LineCol {
+    pub const fn new(line: u32, col: u32) -> Self {
+        Self { line, col }
+    }
+}
+
+/// A non-ASCII character, by the UTF-8 columns it spans within its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    start: u32,
+    end: u32,
+}
+
+impl WideChar {
+    fn len(self, encoding: PositionEncoding) -> u32 {
+        match encoding {
+            PositionEncoding::Utf8 => self.end - self.start,
+            PositionEncoding::Utf16 if self.end - self.start == 4 => 2,
+            PositionEncoding::Utf16 | PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// The lines of a text, for converting between offsets and line/column positions without
+/// rescanning it.
+///
+/// Lines end at `\n`. A `\r` before it still counts as a column of its line, but positions past
+/// the end of a line are clamped to before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Each line without its terminator.
+    lines: Vec<TextRange>,
+    /// The non-ASCII characters of the lines that have any.
+    wide_chars: HashMap<u32, Box<[WideChar]>>,
+    len: TextSize,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut wide_chars = HashMap::new();
+        let mut line_chars = Vec::new();
+        let mut line_start = 0;
+        for (idx, ch) in text.char_indices() {
+            if ch == '\n' {
+                let end = if text[line_start..idx].ends_with('\r') {
+                    idx - 1
+                } else {
+                    idx
+                };
+                if !line_chars.is_empty() {
+                    let chars = std::mem::take(&mut line_chars).into_boxed_slice();
+                    wide_chars.insert(lines.len() as u32, chars);
+                }
+                lines.push(TextRange::new(size(line_start), size(end)));
+                line_start = idx + 1;
+            } else if !ch.is_ascii() {
+                let start = (idx - line_start) as u32;
+                line_chars.push(WideChar {
+                    start,
+                    end: start + ch.len_utf8() as u32,
+                });
+            }
+        }
+        if !line_chars.is_empty() {
+            wide_chars.insert(lines.len() as u32, line_chars.into_boxed_slice());
+        }
+        lines.push(TextRange::new(size(line_start), size(text.len())));
+
+        Self {
+            lines,
+            wide_chars,
+            len: size(text.len()),
+        }
+    }
+
+    pub fn len(&self) -> TextSize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == TextSize::new(0)
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.lines.len() as u32
+    }
+
+    /// The range of `line` without its terminator.
+    pub fn line_range(&self, line: u32) -> Option<TextRange> {
+        self.lines.get(line as usize).copied()
+    }
+
+    /// The start of the line containing `offset`, clamped to the end of the text.
+    pub fn line_start(&self, offset: TextSize) -> TextSize {
+        self.lines[self.line_col(offset).line as usize].start()
+    }
+
+    /// The position of `offset`, clamped to the end of the text.
+    pub fn line_col(&self, offset: TextSize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = self.lines.partition_point(|range| range.start() <= offset) - 1;
+        LineCol::new(line as u32, (offset - self.lines[line].start()).raw())
+    }
+
+    /// Returns `None` for lines past the end of the text and for columns inside a character.
+    /// Columns past the end of a line mean the end of the line.
+    pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
+        let range = self.line_range(line_col.line)?;
+        let col = line_col.col.min(range.len().raw());
+        if self
+            .wide_chars(line_col.line)
+            .iter()
+            .any(|ch| ch.start < col && col < ch.end)
+        {
+            return None;
+        }
+        Some(range.start() + TextSize::new(col))
+    }
+
+    /// Like [`LineIndex::offset`], but lines past the end mean the end of the text and columns
+    /// inside a character mean its start.
+    pub fn offset_clamped(&self, line_col: LineCol) -> TextSize {
+        let Some(range) = self.line_range(line_col.line) else {
+            return self.len;
+        };
+        let mut col = line_col.col.min(range.len().raw());
+        if let Some(ch) = self
+            .wide_chars(line_col.line)
+            .iter()
+            .find(|ch| ch.start < col && col < ch.end)
+        {
+            col = ch.start;
+        }
+        range.start() + TextSize::new(col)
+    }
+
+    /// Converts the UTF-8 column of `line_col` to `encoding`. A column inside a character counts
+    /// all of it.
+    pub fn to_wide(&self, encoding: PositionEncoding, line_col: LineCol) -> LineCol {
+        let mut col = 0;
+        let mut utf8 = 0;
+        for ch in self.wide_chars(line_col.line) {
+            if ch.start >= line_col.col {
+                break;
+            }
+            col += ch.start - utf8 + ch.len(encoding);
+            utf8 = ch.end;
+        }
+        LineCol::new(line_col.line, col + line_col.col.saturating_sub(utf8))
+    }
+
+    /// Converts a column in `encoding` to UTF-8. Returns `None` for lines past the end of the
+    /// text and for columns inside a character; columns past the end of a line stay past it.
+    pub fn to_utf8(&self, encoding: PositionEncoding, line_col: LineCol) -> Option<LineCol> {
+        self.line_range(line_col.line)?;
+        let mut col = 0;
+        let mut utf8 = 0;
+        for ch in self.wide_chars(line_col.line) {
+            let before = col + (ch.start - utf8);
+            if line_col.col <= before {
+                break;
+            }
+            col = before + ch.len(encoding);
+            utf8 = ch.end;
+            if line_col.col < col {
+                return None;
+            }
+        }
+        Some(LineCol::new(
+            line_col.line,
+            utf8.saturating_add(line_col.col - col),
+        ))
+    }
+
+    fn wide_chars(&self, line: u32) -> &[WideChar] {
+        self.wide_chars
+            .get(&line)
+            .map(|chars| &chars[..])
+            .unwrap_or_default()
+    }
+}
+
+fn size(offset: usize) -> TextSize {
+    TextSize::try_from(offset).expect("text is shorter than 4 GiB")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCol, LineIndex, PositionEncoding};
+    use crate::{TextRange, TextSize};
+
+    #[test]
+    fn offsets_map_to_lines_and_back() {
+        let text = "pragma solidity ^0.8.0;\r\n\ncontract A {}";
+        let index = LineIndex::new(text);
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(
+            index.line_range(0),
+            Some(TextRange::new(TextSize::from(0), TextSize::from(23)))
+        );
+        assert_eq!(
+            index.line_range(1),
+            Some(TextRange::empty(TextSize::from(25)))
+        );
+        assert_eq!(index.line_range(3), None);
+
+        let contract = TextSize::from(26);
+        assert_eq!(index.line_col(contract), LineCol::new(2, 0));
+        assert_eq!(index.offset(LineCol::new(2, 0)), Some(contract));
+        assert_eq!(index.line_start(TextSize::from(30)), contract);
+        assert_eq!(index.line_col(TextSize::from(24)), LineCol::new(0, 24));
+    }
+
+    #[test]
+    fn out_of_range_positions_are_clamped() {
+        let text = "ab\r\ncd";
+        let index = LineIndex::new(text);
+        assert_eq!(index.line_col(TextSize::from(100)), LineCol::new(1, 2));
+        assert_eq!(index.offset(LineCol::new(0, 10)), Some(TextSize::from(2)));
+        assert_eq!(
+            index.offset(LineCol::new(1, u32::MAX)),
+            Some(TextSize::from(6))
+        );
+        assert_eq!(index.offset(LineCol::new(2, 0)), None);
+        assert_eq!(index.offset_clamped(LineCol::new(2, 0)), TextSize::from(6));
+    }
+
+    #[test]
+    fn wide_columns_count_code_units_of_their_encoding() {
+        use PositionEncoding::{Utf8, Utf16, Utf32};
+
+        let text = "// é\nstring s = \"é😀\"; x";
+        let index = LineIndex::new(text);
+        let x = index.line_col(TextSize::of(text) - TextSize::from(1));
+        assert_eq!(x, LineCol::new(1, 21));
+        for (encoding, col) in [(Utf8, 21), (Utf16, 18), (Utf32, 17)] {
+            let wide = index.to_wide(encoding, x);
+            assert_eq!(wide, LineCol::new(1, col));
+            assert_eq!(index.to_utf8(encoding, wide), Some(x));
+        }
+
+        // The emoji starts at byte 14 of its line and at UTF-16 unit 13.
+        assert_eq!(index.to_utf8(Utf16, LineCol::new(1, 14)), None);
+        assert_eq!(index.to_utf8(Utf8, LineCol::new(1, 15)), None);
+        assert_eq!(index.offset(LineCol::new(1, 15)), None);
+        assert_eq!(
+            index.offset_clamped(LineCol::new(1, 15)),
+            TextSize::from(6 + 14)
+        );
+        assert_eq!(
+            index.to_wide(Utf16, LineCol::new(1, 15)),
+            LineCol::new(1, 15)
+        );
+        assert_eq!(index.to_wide(Utf16, LineCol::new(0, 5)), LineCol::new(0, 4));
+    }
+}
@@ -0,0 +1,1145 @@
+//! A lossless, error-tolerant concrete syntax tree.
+//!
+//! Every byte of the input, trivia included, belongs to exactly one token, so a tree always
+//! prints back to the text it was parsed from. The parser only recovers item structure
+//! (contracts, their members, parameter lists and balanced bodies); statements and expressions
+//! stay flat token runs inside their `Block`. Parsing never fails: what cannot be placed ends up
+//! in an `Error` node and is reported in [`Cst::errors`].
+//!
+//! The solar AST remains the source of truth for well-formed code. This tree is for the code
+//! being typed, where solar gives up on the whole file.
+
+use std::{fmt::Write, sync::Arc};
+
+use sa_span::{TextRange, TextSize, is_ident_byte};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    Ident,
+    Number,
+    String,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Semicolon,
+    Comma,
+    Dot,
+    /// Any other punctuation, one character per token.
+    Punct,
+    /// A character that cannot start any token.
+    Unknown,
+
+    SourceUnit,
+    Pragma,
+    Import,
+    /// A `contract`, `interface` or `library`, with its `abstract` modifier.
+    Contract,
+    /// The identifier naming the enclosing item.
+    Name,
+    BaseList,
+    Base,
+    ContractBody,
+    /// A function, constructor, `receive` or `fallback`.
+    Function,
+    Modifier,
+    Event,
+    ErrorDef,
+    Struct,
+    Enum,
+    Udvt,
+    Using,
+    /// A state variable or a file-level constant.
+    Variable,
+    ParamList,
+    Block,
+    Error,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace | SyntaxKind::LineComment | SyntaxKind::BlockComment
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    kind: SyntaxKind,
+    range: TextRange,
+    text: Arc<str>,
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// Cloning a node shares its children, so a reparsed tree can keep the subtrees an edit did not
+/// touch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    range: TextRange,
+    children: Arc<[SyntaxElement]>,
+}
+
+impl SyntaxNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> + '_ {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    pub fn child_node(&self, kind: SyntaxKind) -> Option<&SyntaxNode> {
+        self.child_nodes().find(|node| node.kind == kind)
+    }
+
+    /// The direct tokens of this node, trivia excluded.
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken> + '_ {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) if !token.kind.is_trivia() => Some(token),
+            _ => None,
+        })
+    }
+
+    /// This node and every node below it, in source order.
+    pub fn descendants(&self) -> Vec<&SyntaxNode> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            let children = node.child_nodes().collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev());
+        }
+        nodes
+    }
+
+    /// Every token below this node, trivia excluded, in source order.
+    pub fn descendant_tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) if !token.kind.is_trivia() => tokens.push(token),
+                SyntaxElement::Token(_) => {}
+            }
+        }
+    }
+
+    /// The keyword or first token the node starts with.
+    pub fn first_token(&self) -> Option<&SyntaxToken> {
+        self.children.iter().find_map(|child| match child {
+            SyntaxElement::Token(token) if !token.kind.is_trivia() => Some(token),
+            SyntaxElement::Node(node) => node.first_token(),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    pub fn last_token(&self) -> Option<&SyntaxToken> {
+        self.children.iter().rev().find_map(|child| match child {
+            SyntaxElement::Token(token) if !token.kind.is_trivia() => Some(token),
+            SyntaxElement::Node(node) => node.last_token(),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The identifier in the node's `Name` child.
+    pub fn name(&self) -> Option<&SyntaxToken> {
+        self.child_node(SyntaxKind::Name)?.tokens().next()
+    }
+
+    /// The base paths of a contract as written, e.g. `["Lib", "Base"]` for `is Lib.Base`.
+    pub fn base_paths(&self) -> Vec<Vec<String>> {
+        let Some(list) = self.child_node(SyntaxKind::BaseList) else {
+            return Vec::new();
+        };
+        list.child_nodes()
+            .filter(|node| node.kind == SyntaxKind::Base)
+            .map(|base| {
+                base.tokens()
+                    .filter(|token| token.kind == SyntaxKind::Ident)
+                    .map(|token| token.text.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|segments| !segments.is_empty())
+            .collect()
+    }
+
+    /// Whether the node ends with the closing brace of its body.
+    pub fn is_closed(&self) -> bool {
+        self.last_token()
+            .is_some_and(|token| token.kind == SyntaxKind::RBrace)
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text);
+        text
+    }
+
+    /// An indented dump of this node and everything below it: one line per element with its
+    /// kind and byte range, tokens followed by their text.
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
+        self.write_dump(0, &mut out);
+        out
+    }
+
+    fn write_dump(&self, depth: usize, out: &mut String) {
+        let indent = depth * 2;
+        let _ = writeln!(
+            out,
+            "{:indent$}{:?}@{}",
+            "",
+            self.kind,
+            dump_range(self.range)
+        );
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => node.write_dump(depth + 1, out),
+                SyntaxElement::Token(token) => {
+                    let _ = writeln!(
+                        out,
+                        "{:indent$}{:?}@{} {:?}",
+                        "",
+                        token.kind,
+                        dump_range(token.range),
+                        token.text,
+                        indent = indent + 2
+                    );
+                }
+            }
+        }
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => node.write_text(out),
+                SyntaxElement::Token(token) => out.push_str(&token.text),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstError {
+    pub range: TextRange,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cst {
+    root: SyntaxNode,
+    errors: Vec<CstError>,
+}
+
+impl Cst {
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    pub fn errors(&self) -> &[CstError] {
+        &self.errors
+    }
+
+    /// The innermost node containing all of `range`.
+    pub fn covering_node(&self, range: TextRange) -> &SyntaxNode {
+        let mut node = &self.root;
+        while let Some(child) = node
+            .child_nodes()
+            .find(|child| child.range.start() <= range.start() && range.end() <= child.range.end())
+        {
+            node = child;
+        }
+        node
+    }
+
+    /// [`SyntaxNode::debug_dump`] of the root, followed by the parse errors.
+    pub fn debug_dump(&self) -> String {
+        let mut out = self.root.debug_dump();
+        for error in &self.errors {
+            let _ = writeln!(out, "error@{}: {}", dump_range(error.range), error.message);
+        }
+        out
+    }
+
+    pub fn contracts(&self) -> impl Iterator<Item = &SyntaxNode> + '_ {
+        self.root
+            .child_nodes()
+            .filter(|node| node.kind == SyntaxKind::Contract)
+    }
+
+    pub fn contract_by_name(&self, name: &str) -> Option<&SyntaxNode> {
+        self.contracts()
+            .find(|contract| contract.name().is_some_and(|token| &*token.text == name))
+    }
+
+    /// The contract enclosing `offset`, up to its closing brace. A contract whose body is never
+    /// closed extends up to the next item, which is what the user is most likely typing into.
+    pub fn contract_at_offset(&self, offset: TextSize) -> Option<&SyntaxNode> {
+        let items = self.root.child_nodes().collect::<Vec<_>>();
+        items.iter().enumerate().find_map(|(idx, node)| {
+            if node.kind != SyntaxKind::Contract {
+                return None;
+            }
+            let contains_end = if node.is_closed() {
+                offset < node.range.end()
+            } else if let Some(next) = items.get(idx + 1) {
+                offset < next.range.start()
+            } else {
+                offset <= self.root.range.end()
+            };
+            (node.range.start() <= offset && contains_end).then_some(*node)
+        })
+    }
+
+    /// The tree of `text`, which is the text of this tree with `edits` applied in order, each
+    /// range relative to the text the edits before it left.
+    ///
+    /// An edit inside one closed block (typing in a function body) only re-lexes and reparses
+    /// that block: the nodes before it are shared with this tree and the ones after it are moved
+    /// by the length change. Any other edit, including one whose range is outside the tree or
+    /// splits a character, parses `text` from scratch. Both give the same tree as [`parse_cst`]
+    /// on `text`.
+    pub fn reparse<'a>(
+        &self,
+        edits: impl IntoIterator<Item = (TextRange, &'a str)>,
+        text: &str,
+    ) -> Cst {
+        let mut cst: Option<Cst> = None;
+        for (range, new_text) in edits {
+            match cst.as_ref().unwrap_or(self).reparse_block(range, new_text) {
+                Some(next) => cst = Some(next),
+                None => return parse_cst(text),
+            }
+        }
+        match cst {
+            Some(cst) if cst.root.range.end() == TextSize::of(text) => cst,
+            _ => parse_cst(text),
+        }
+    }
+
+    fn reparse_block(&self, range: TextRange, new_text: &str) -> Option<Cst> {
+        let block = self.enclosing_block(range)?;
+        let mut block_text = block.text();
+        let start = usize::from(range.start() - block.range.start());
+        let end = usize::from(range.end() - block.range.start());
+        if !block_text.is_char_boundary(start) || !block_text.is_char_boundary(end) {
+            return None;
+        }
+        block_text.replace_range(start..end, new_text);
+        let delta = new_text.len() as i64 - i64::from(u32::from(range.len()));
+
+        let tokens = lex(&block_text);
+        let token_count = tokens.len();
+        let mut parser = Parser {
+            text: &block_text,
+            tokens,
+            pos: 0,
+            stack: vec![(SyntaxKind::SourceUnit, Vec::new())],
+            errors: Vec::new(),
+        };
+        parser.block();
+        // The block must end exactly where it did; otherwise the edit changed the structure
+        // around it. A block with errors may have recovered differently than it would inside
+        // the whole file, so it is left to the full parse as well.
+        if !parser.errors.is_empty() || parser.pos != token_count {
+            return None;
+        }
+        let (_, mut children) = parser.stack.pop()?;
+        let Some(SyntaxElement::Node(replacement)) = children.pop() else {
+            return None;
+        };
+        if !replacement.is_closed() {
+            return None;
+        }
+        let replacement = shift_node(
+            &replacement,
+            TextSize::from(0),
+            i64::from(u32::from(block.range.start())),
+        );
+
+        // Errors inside the block came from parsing it, and it parses cleanly now. Nothing
+        // inside starts at the opening brace.
+        let errors = self
+            .errors
+            .iter()
+            .filter(|error| {
+                !(block.range.start() < error.range.start()
+                    && error.range.end() <= block.range.end())
+            })
+            .map(|error| CstError {
+                range: shift_range(error.range, block.range.end(), delta),
+                message: error.message.clone(),
+            })
+            .collect();
+        Some(Cst {
+            root: splice(&self.root, block.range, &replacement, delta),
+            errors,
+        })
+    }
+
+    /// The innermost closed block with `range` strictly inside its braces.
+    fn enclosing_block(&self, range: TextRange) -> Option<&SyntaxNode> {
+        let mut node = &self.root;
+        let mut block = None;
+        while let Some(child) = node
+            .child_nodes()
+            .find(|child| child.range.start() < range.start() && range.end() < child.range.end())
+        {
+            if child.kind == SyntaxKind::Block && child.is_closed() {
+                block = Some(child);
+            }
+            node = child;
+        }
+        block
+    }
+}
+
+/// `node` with the block at `block` replaced, and everything after it moved by `delta`. The
+/// nodes before the block are shared rather than copied.
+fn splice(node: &SyntaxNode, block: TextRange, replacement: &SyntaxNode, delta: i64) -> SyntaxNode {
+    if node.kind == SyntaxKind::Block && node.range == block {
+        return replacement.clone();
+    }
+    let children = node
+        .children
+        .iter()
+        .map(|child| match child {
+            SyntaxElement::Node(child)
+                if child.range.start() <= block.start() && block.end() <= child.range.end() =>
+            {
+                SyntaxElement::Node(splice(child, block, replacement, delta))
+            }
+            SyntaxElement::Node(child) => {
+                SyntaxElement::Node(shift_node(child, block.end(), delta))
+            }
+            SyntaxElement::Token(token) => {
+                SyntaxElement::Token(shift_token(token, block.end(), delta))
+            }
+        })
+        .collect();
+    SyntaxNode {
+        kind: node.kind,
+        range: shift_range(node.range, block.end(), delta),
+        children,
+    }
+}
+
+fn shift_node(node: &SyntaxNode, from: TextSize, delta: i64) -> SyntaxNode {
+    if delta == 0 || node.range.end() < from {
+        return node.clone();
+    }
+    let children = node
+        .children
+        .iter()
+        .map(|child| match child {
+            SyntaxElement::Node(child) => SyntaxElement::Node(shift_node(child, from, delta)),
+            SyntaxElement::Token(token) => SyntaxElement::Token(shift_token(token, from, delta)),
+        })
+        .collect();
+    SyntaxNode {
+        kind: node.kind,
+        range: shift_range(node.range, from, delta),
+        children,
+    }
+}
+
+fn shift_token(token: &SyntaxToken, from: TextSize, delta: i64) -> SyntaxToken {
+    SyntaxToken {
+        kind: token.kind,
+        range: shift_range(token.range, from, delta),
+        text: Arc::clone(&token.text),
+    }
+}
+
+/// Moves the ends of `range` that lie at or after `from`.
+fn shift_range(range: TextRange, from: TextSize, delta: i64) -> TextRange {
+    let shift = |offset: TextSize| {
+        if offset >= from {
+            TextSize::from((i64::from(u32::from(offset)) + delta) as u32)
+        } else {
+            offset
+        }
+    };
+    TextRange::new(shift(range.start()), shift(range.end()))
+}
+
+fn dump_range(range: TextRange) -> String {
+    format!("{}..{}", u32::from(range.start()), u32::from(range.end()))
+}
+
+pub fn parse_cst(text: &str) -> Cst {
+    let tokens = lex(text);
+    let mut parser = Parser {
+        text,
+        tokens,
+        pos: 0,
+        stack: vec![(SyntaxKind::SourceUnit, Vec::new())],
+        errors: Vec::new(),
+    };
+    while !parser.at_eof() {
+        parser.item(ItemScope::File);
+    }
+    parser.flush_trivia();
+    let (kind, children) = parser
+        .stack
+        .pop()
+        .unwrap_or((SyntaxKind::SourceUnit, Vec::new()));
+    Cst {
+        root: SyntaxNode {
+            kind,
+            range: TextRange::new(TextSize::from(0), TextSize::of(text)),
+            children: children.into(),
+        },
+        errors: parser.errors,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RawToken {
+    kind: SyntaxKind,
+    start: usize,
+    end: usize,
+}
+
+fn lex(text: &str) -> Vec<RawToken> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let b = bytes[i];
+        let kind = if b.is_ascii_whitespace() {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            SyntaxKind::Whitespace
+        } else if bytes[i..].starts_with(b"//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            SyntaxKind::LineComment
+        } else if bytes[i..].starts_with(b"/*") {
+            i = text[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| i + 2 + end + 2);
+            SyntaxKind::BlockComment
+        } else if b.is_ascii_digit() {
+            while i < bytes.len()
+                && (is_ident_byte(bytes[i])
+                    || (bytes[i] == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)))
+            {
+                i += 1;
+            }
+            SyntaxKind::Number
+        } else if is_ident_byte(b) {
+            while i < bytes.len() && is_ident_byte(bytes[i]) {
+                i += 1;
+            }
+            SyntaxKind::Ident
+        } else if b == b'"' || b == b'\'' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b && bytes[i] != b'\n' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            if bytes.get(i) == Some(&b) {
+                i += 1;
+            }
+            SyntaxKind::String
+        } else if b.is_ascii() {
+            i += 1;
+            match b {
+                b'{' => SyntaxKind::LBrace,
+                b'}' => SyntaxKind::RBrace,
+                b'(' => SyntaxKind::LParen,
+                b')' => SyntaxKind::RParen,
+                b';' => SyntaxKind::Semicolon,
+                b',' => SyntaxKind::Comma,
+                b'.' => SyntaxKind::Dot,
+                _ => SyntaxKind::Punct,
+            }
+        } else {
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+            SyntaxKind::Unknown
+        };
+        // An escape right before the end of the text can step past it.
+        let end = i.min(bytes.len());
+        tokens.push(RawToken { kind, start, end });
+        i = end;
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemScope {
+    File,
+    Contract,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<RawToken>,
+    pos: usize,
+    stack: Vec<(SyntaxKind, Vec<SyntaxElement>)>,
+    errors: Vec<CstError>,
+}
+
+impl Parser<'_> {
+    fn item(&mut self, scope: ItemScope) {
+        match self.current_text() {
+            "pragma" => self.until_semicolon(SyntaxKind::Pragma),
+            "import" => self.until_semicolon(SyntaxKind::Import),
+            "using" => self.until_semicolon(SyntaxKind::Using),
+            "abstract" | "contract" | "interface" | "library" => self.contract(),
+            "function" | "constructor" | "receive" | "fallback" => {
+                self.function(SyntaxKind::Function)
+            }
+            "modifier" => self.function(SyntaxKind::Modifier),
+            "event" => self.function(SyntaxKind::Event),
+            "error" if self.nth(1) == Some(SyntaxKind::Ident) => {
+                self.function(SyntaxKind::ErrorDef)
+            }
+            "struct" => self.braced(SyntaxKind::Struct),
+            "enum" => self.braced(SyntaxKind::Enum),
+            "type" if self.nth(1) == Some(SyntaxKind::Ident) => self.udvt(),
+            _ => match self.current() {
+                Some(SyntaxKind::RBrace) if scope == ItemScope::File => {
+                    self.error_node("unexpected `}`");
+                }
+                Some(SyntaxKind::Semicolon) | Some(SyntaxKind::RParen) => {
+                    self.error_node("expected an item");
+                }
+                _ => self.variable(),
+            },
+        }
+    }
+
+    fn contract(&mut self) {
+        self.start(SyntaxKind::Contract);
+        if self.current_text() == "abstract" {
+            self.bump();
+        }
+        if matches!(self.current_text(), "contract" | "interface" | "library") {
+            self.bump();
+        }
+        self.name();
+        if self.current_text() == "is" {
+            self.start(SyntaxKind::BaseList);
+            self.bump();
+            while !self.at_eof()
+                && self.current() != Some(SyntaxKind::LBrace)
+                && !self.at_item_start()
+            {
+                match self.current() {
+                    Some(SyntaxKind::Comma) => self.bump(),
+                    Some(SyntaxKind::Ident) => self.base(),
+                    _ => self.error_node("expected a base contract"),
+                }
+            }
+            self.finish();
+        }
+        if self.current() == Some(SyntaxKind::LBrace) {
+            self.start(SyntaxKind::ContractBody);
+            self.bump();
+            loop {
+                match self.current() {
+                    None => {
+                        self.error_here("missing `}`");
+                        break;
+                    }
+                    Some(SyntaxKind::RBrace) => {
+                        self.bump();
+                        break;
+                    }
+                    _ if self.at_contract_keyword() => {
+                        self.error_here("missing `}`");
+                        break;
+                    }
+                    _ => self.item(ItemScope::Contract),
+                }
+            }
+            self.finish();
+        } else {
+            self.error_here("expected `{`");
+        }
+        self.finish();
+    }
+
+    fn base(&mut self) {
+        self.start(SyntaxKind::Base);
+        while self.current() == Some(SyntaxKind::Ident) {
+            self.bump();
+            if self.current() != Some(SyntaxKind::Dot) {
+                break;
+            }
+            self.bump();
+        }
+        if self.current() == Some(SyntaxKind::LParen) {
+            self.param_list();
+        }
+        self.finish();
+    }
+
+    /// Functions and everything shaped like them: a keyword, an optional name, a parameter
+    /// list, a header and either a body or `;`.
+    fn function(&mut self, kind: SyntaxKind) {
+        self.start(kind);
+        self.bump();
+        if self.current() == Some(SyntaxKind::Ident) {
+            self.name();
+        }
+        loop {
+            match self.current() {
+                None => {
+                    self.error_here("expected `;` or a body");
+                    break;
+                }
+                Some(SyntaxKind::LParen) => self.param_list(),
+                Some(SyntaxKind::LBrace) => {
+                    self.block();
+                    break;
+                }
+                Some(SyntaxKind::Semicolon) => {
+                    self.bump();
+                    break;
+                }
+                Some(SyntaxKind::RBrace) => {
+                    self.error_here("expected `;` or a body");
+                    break;
+                }
+                _ if self.at_item_start() => {
+                    self.error_here("expected `;` or a body");
+                    break;
+                }
+                _ => self.bump(),
+            }
+        }
+        self.finish();
+    }
+
+    /// `struct` and `enum`: a keyword, a name and a body.
+    fn braced(&mut self, kind: SyntaxKind) {
+        self.start(kind);
+        self.bump();
+        self.name();
+        if self.current() == Some(SyntaxKind::LBrace) {
+            self.block();
+        } else {
+            self.error_here("expected `{`");
+        }
+        self.finish();
+    }
+
+    fn udvt(&mut self) {
+        self.start(SyntaxKind::Udvt);
+        self.bump();
+        self.name();
+        self.rest_of_statement();
+        self.finish();
+    }
+
+    /// A declaration without a leading keyword; its name is the last identifier before `=`
+    /// or `;`.
+    fn variable(&mut self) {
+        let name = self.variable_name();
+        self.start(SyntaxKind::Variable);
+        let start = self.pos;
+        loop {
+            match self.current() {
+                None | Some(SyntaxKind::RBrace) => {
+                    self.error_here("missing `;`");
+                    break;
+                }
+                Some(SyntaxKind::Semicolon) => {
+                    self.bump();
+                    break;
+                }
+                Some(SyntaxKind::LBrace) => self.block(),
+                Some(SyntaxKind::LParen) => self.param_list(),
+                _ if self.pos != start && self.at_item_start() => {
+                    self.error_here("missing `;`");
+                    break;
+                }
+                _ if Some(self.non_trivia_index()) == name => self.name(),
+                _ => self.bump(),
+            }
+        }
+        self.finish();
+    }
+
+    fn variable_name(&self) -> Option<usize> {
+        let mut name = None;
+        let mut depth = 0usize;
+        for (idx, token) in self.tokens.iter().enumerate().skip(self.pos) {
+            match token.kind {
+                SyntaxKind::LParen | SyntaxKind::LBrace => depth += 1,
+                SyntaxKind::RParen | SyntaxKind::RBrace if depth > 0 => depth -= 1,
+                SyntaxKind::RBrace | SyntaxKind::Semicolon => break,
+                SyntaxKind::Punct if depth == 0 && self.token_text(token) == "=" => break,
+                SyntaxKind::Ident if depth == 0 => name = Some(idx),
+                _ => {}
+            }
+        }
+        name
+    }
+
+    fn until_semicolon(&mut self, kind: SyntaxKind) {
+        self.start(kind);
+        self.bump();
+        self.rest_of_statement();
+        self.finish();
+    }
+
+    fn rest_of_statement(&mut self) {
+        loop {
+            match self.current() {
+                None | Some(SyntaxKind::RBrace) => {
+                    self.error_here("missing `;`");
+                    break;
+                }
+                Some(SyntaxKind::Semicolon) => {
+                    self.bump();
+                    break;
+                }
+                Some(SyntaxKind::LBrace) => self.block(),
+                _ if self.at_item_start() => {
+                    self.error_here("missing `;`");
+                    break;
+                }
+                _ => self.bump(),
+            }
+        }
+    }
+
+    fn param_list(&mut self) {
+        self.start(SyntaxKind::ParamList);
+        self.bump();
+        let mut depth = 0usize;
+        loop {
+            match self.current() {
+                None | Some(SyntaxKind::LBrace | SyntaxKind::RBrace | SyntaxKind::Semicolon) => {
+                    self.error_here("missing `)`");
+                    break;
+                }
+                Some(SyntaxKind::LParen) => {
+                    depth += 1;
+                    self.bump();
+                }
+                Some(SyntaxKind::RParen) => {
+                    self.bump();
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                }
+                _ => self.bump(),
+            }
+        }
+        self.finish();
+    }
+
+    /// A balanced `{ ... }`. A member declaration at the start of a statement ends a block that
+    /// was never closed, so the rest of the contract is not swallowed by the body being typed.
+    fn block(&mut self) {
+        self.start(SyntaxKind::Block);
+        self.bump();
+        loop {
+            match self.current() {
+                None => {
+                    self.error_here("missing `}`");
+                    break;
+                }
+                Some(SyntaxKind::RBrace) => {
+                    self.bump();
+                    break;
+                }
+                Some(SyntaxKind::LBrace) => self.block(),
+                _ if self.at_contract_declaration() => {
+                    self.error_here("missing `}`");
+                    break;
+                }
+                _ if self.at_statement_start() && self.current_text() == "assembly" => {
+                    self.assembly();
+                }
+                _ if self.at_statement_start() && self.at_member_start() => {
+                    self.error_here("missing `}`");
+                    break;
+                }
+                _ => self.bump(),
+            }
+        }
+        self.finish();
+    }
+
+    /// `assembly`, its dialect string and flags, and its Yul block.
+    fn assembly(&mut self) {
+        self.bump();
+        while matches!(
+            self.current(),
+            Some(SyntaxKind::String | SyntaxKind::LParen)
+        ) {
+            if self.current() == Some(SyntaxKind::LParen) {
+                self.param_list();
+            } else {
+                self.bump();
+            }
+        }
+        if self.current() == Some(SyntaxKind::LBrace) {
+            self.yul_block();
+        }
+    }
+
+    /// A Yul block. Yul has its own `function`, so only a contract declaration ends one that
+    /// was never closed.
+    fn yul_block(&mut self) {
+        self.start(SyntaxKind::Block);
+        self.bump();
+        loop {
+            match self.current() {
+                None => {
+                    self.error_here("missing `}`");
+                    break;
+                }
+                Some(SyntaxKind::RBrace) => {
+                    self.bump();
+                    break;
+                }
+                Some(SyntaxKind::LBrace) => self.yul_block(),
+                _ if self.at_contract_declaration() => {
+                    self.error_here("missing `}`");
+                    break;
+                }
+                _ => self.bump(),
+            }
+        }
+        self.finish();
+    }
+
+    fn name(&mut self) {
+        if self.current() == Some(SyntaxKind::Ident) {
+            self.start(SyntaxKind::Name);
+            self.bump();
+            self.finish();
+        } else {
+            self.error_here("expected a name");
+        }
+    }
+
+    fn error_node(&mut self, message: &str) {
+        let range = self.current_range();
+        self.start(SyntaxKind::Error);
+        self.bump();
+        self.finish();
+        self.push_error(range, message);
+    }
+
+    fn error_here(&mut self, message: &str) {
+        let range = TextRange::empty(self.current_range().start());
+        self.push_error(range, message);
+    }
+
+    fn push_error(&mut self, range: TextRange, message: &str) {
+        self.errors.push(CstError {
+            range,
+            message: message.to_string(),
+        });
+    }
+
+    fn at_item_start(&self) -> bool {
+        self.at_contract_keyword() || self.at_member_start()
+    }
+
+    fn at_contract_keyword(&self) -> bool {
+        matches!(self.current_text(), "pragma" | "import") || self.at_contract_declaration()
+    }
+
+    fn at_contract_declaration(&self) -> bool {
+        match self.current_text() {
+            "contract" | "interface" | "library" => self.nth(1) == Some(SyntaxKind::Ident),
+            "abstract" => self.nth_text(1) == "contract",
+            _ => false,
+        }
+    }
+
+    fn at_member_start(&self) -> bool {
+        match self.current_text() {
+            "function" | "modifier" | "event" | "struct" | "enum" => {
+                self.nth(1) == Some(SyntaxKind::Ident)
+            }
+            "constructor" => self.nth(1) == Some(SyntaxKind::LParen),
+            _ => self.at_contract_keyword(),
+        }
+    }
+
+    fn at_statement_start(&self) -> bool {
+        self.tokens[..self.pos]
+            .iter()
+            .rev()
+            .find(|token| !token.kind.is_trivia())
+            .is_none_or(|token| {
+                matches!(
+                    token.kind,
+                    SyntaxKind::Semicolon | SyntaxKind::LBrace | SyntaxKind::RBrace
+                )
+            })
+    }
+
+    fn at_eof(&self) -> bool {
+        self.current().is_none()
+    }
+
+    fn current(&self) -> Option<SyntaxKind> {
+        self.nth(0)
+    }
+
+    fn current_text(&self) -> &str {
+        self.nth_text(0)
+    }
+
+    fn current_range(&self) -> TextRange {
+        let token = self.non_trivia(0);
+        let offset = token.map_or(self.text.len(), |token| token.start);
+        let end = token.map_or(self.text.len(), |token| token.end);
+        TextRange::new(TextSize::from(offset as u32), TextSize::from(end as u32))
+    }
+
+    fn nth(&self, n: usize) -> Option<SyntaxKind> {
+        self.non_trivia(n).map(|token| token.kind)
+    }
+
+    fn nth_text(&self, n: usize) -> &str {
+        self.non_trivia(n)
+            .filter(|token| token.kind == SyntaxKind::Ident)
+            .map_or("", |token| self.token_text(token))
+    }
+
+    fn non_trivia(&self, n: usize) -> Option<&RawToken> {
+        self.tokens[self.pos..]
+            .iter()
+            .filter(|token| !token.kind.is_trivia())
+            .nth(n)
+    }
+
+    fn non_trivia_index(&self) -> usize {
+        self.tokens[self.pos..]
+            .iter()
+            .position(|token| !token.kind.is_trivia())
+            .map_or(self.tokens.len(), |idx| self.pos + idx)
+    }
+
+    fn token_text(&self, token: &RawToken) -> &str {
+        &self.text[token.start..token.end]
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        self.flush_trivia();
+        self.stack.push((kind, Vec::new()));
+    }
+
+    fn finish(&mut self) {
+        let Some((kind, children)) = self.stack.pop() else {
+            return;
+        };
+        let range = element_range(&children).unwrap_or_else(|| {
+            let offset = self
+                .tokens
+                .get(self.pos)
+                .map_or(self.text.len(), |token| token.start);
+            TextRange::empty(TextSize::from(offset as u32))
+        });
+        self.push_element(SyntaxElement::Node(SyntaxNode {
+            kind,
+            range,
+            children: children.into(),
+        }));
+    }
+
+    /// Moves past the next non-trivia token, attaching the trivia before it to the current
+    /// node.
+    fn bump(&mut self) {
+        self.flush_trivia();
+        if let Some(token) = self.tokens.get(self.pos).copied() {
+            self.pos += 1;
+            self.push_token(token);
+        }
+    }
+
+    fn flush_trivia(&mut self) {
+        while let Some(token) = self.tokens.get(self.pos).copied() {
+            if !token.kind.is_trivia() {
+                break;
+            }
+            self.pos += 1;
+            self.push_token(token);
+        }
+    }
+
+    fn push_token(&mut self, token: RawToken) {
+        let text = Arc::from(self.token_text(&token));
+        self.push_element(SyntaxElement::Token(SyntaxToken {
+            kind: token.kind,
+            range: TextRange::new(
+                TextSize::from(token.start as u32),
+                TextSize::from(token.end as u32),
+            ),
+            text,
+        }));
+    }
+
+    fn push_element(&mut self, element: SyntaxElement) {
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(element);
+        }
+    }
+}
+
+fn element_range(children: &[SyntaxElement]) -> Option<TextRange> {
+    let range = |element: &SyntaxElement| match element {
+        SyntaxElement::Node(node) => node.range,
+        SyntaxElement::Token(token) => token.range,
+    };
+    let first = range(children.first()?);
+    let last = range(children.last()?);
+    Some(TextRange::new(first.start(), last.end()))
+}
@@ -0,0 +1,211 @@
+//! Doc comments (`///` and `/** */`) attached to the item they document, grouped into NatSpec
+//! tags.
+
+use sa_span::TextRange;
+
+use crate::Parse;
+use crate::ast::{CommentKind, DocComment, Item, NatSpecItem, NatSpecKind};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DocTagKind {
+    Title,
+    Author,
+    Notice,
+    Dev,
+    Param(String),
+    Return(String),
+    Custom(String),
+    Inheritdoc(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocTag {
+    pub kind: DocTagKind,
+    /// The tag's own text followed by the untagged comments that continue it, one entry per
+    /// comment.
+    pub parts: Vec<String>,
+}
+
+impl DocTag {
+    pub fn text(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| part.as_str())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDocs {
+    range: TextRange,
+    tags: Vec<DocTag>,
+    tagged: bool,
+}
+
+impl ItemDocs {
+    /// The range of the doc comments, from the first to the last.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    /// Untagged text before the first tag is a `@notice`, as in solc. `@custom` tags without a
+    /// name, empty `@param`s and internal tags are dropped.
+    pub fn tags(&self) -> &[DocTag] {
+        &self.tags
+    }
+
+    /// Whether any comment uses an `@` tag. Untagged docs are usually free-form prose.
+    pub fn has_explicit_tags(&self) -> bool {
+        self.tagged
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &DocTag)> + '_ {
+        self.tags.iter().filter_map(|tag| match &tag.kind {
+            DocTagKind::Param(name) => Some((name.as_str(), tag)),
+            _ => None,
+        })
+    }
+}
+
+/// The docs of `item`, or `None` when it has no doc comments.
+pub fn item_docs(parse: &Parse, item: &Item<'static>) -> Option<ItemDocs> {
+    let docs = item.docs.iter().collect::<Vec<_>>();
+    let start = parse.span_to_text_range(docs.first()?.span)?;
+    let end = parse.span_to_text_range(docs.last()?.span)?;
+
+    parse.with_session(|| {
+        let mut tags: Vec<DocTag> = Vec::new();
+        let mut tagged = false;
+        for doc in docs {
+            let explicit = doc
+                .natspec
+                .iter()
+                .filter(|natspec| is_explicit_natspec_item(doc, natspec))
+                .collect::<Vec<_>>();
+            if explicit.is_empty() {
+                let content = normalized_doc_text(doc);
+                match tags.last_mut() {
+                    Some(tag) => tag.parts.push(content),
+                    None if !content.is_empty() => tags.push(DocTag {
+                        kind: DocTagKind::Notice,
+                        parts: vec![content],
+                    }),
+                    None => {}
+                }
+                continue;
+            }
+
+            tagged = true;
+            for natspec in explicit {
+                let kind = match natspec.kind {
+                    NatSpecKind::Title => DocTagKind::Title,
+                    NatSpecKind::Author => DocTagKind::Author,
+                    NatSpecKind::Notice => DocTagKind::Notice,
+                    NatSpecKind::Dev => DocTagKind::Dev,
+                    NatSpecKind::Param { name } => DocTagKind::Param(name.to_string()),
+                    NatSpecKind::Return { name } => DocTagKind::Return(name.to_string()),
+                    NatSpecKind::Custom { name } => DocTagKind::Custom(name.to_string()),
+                    NatSpecKind::Inheritdoc { contract } => {
+                        DocTagKind::Inheritdoc(contract.to_string())
+                    }
+                    NatSpecKind::Internal { .. } => continue,
+                };
+                tags.push(DocTag {
+                    kind,
+                    parts: vec![normalized_natspec_content(doc, natspec)],
+                });
+            }
+        }
+        Some(ItemDocs {
+            range: TextRange::new(start.start(), end.end()),
+            tags,
+            tagged,
+        })
+    })
+}
+
+fn is_explicit_natspec_item(doc: &DocComment<'_>, item: &NatSpecItem) -> bool {
+    if !doc_has_explicit_tag(doc) {
+        return false;
+    }
+
+    match item.kind {
+        NatSpecKind::Internal { .. } => false,
+        NatSpecKind::Param { name }
+        | NatSpecKind::Return { name }
+        | NatSpecKind::Custom { name } => !name.as_str().is_empty(),
+        NatSpecKind::Inheritdoc { contract } => !contract.as_str().is_empty(),
+        _ => true,
+    }
+}
+
+fn doc_has_explicit_tag(doc: &DocComment<'_>) -> bool {
+    let text = doc.symbol.as_str();
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let trimmed = match doc.kind {
+            CommentKind::Line => trimmed,
+            CommentKind::Block => trimmed
+                .strip_prefix('*')
+                .map(|rest| rest.trim_start())
+                .unwrap_or(trimmed),
+        };
+        if trimmed.starts_with('@') {
+            return true;
+        }
+    }
+    false
+}
+
+fn normalized_doc_text(doc: &DocComment<'_>) -> String {
+    match doc.kind {
+        CommentKind::Line => doc.symbol.as_str().trim().to_string(),
+        CommentKind::Block => normalize_block_comment_text(doc.symbol.as_str()),
+    }
+}
+
+fn normalized_natspec_content(doc: &DocComment<'_>, item: &NatSpecItem) -> String {
+    let content = doc.natspec_content(item);
+    match doc.kind {
+        CommentKind::Line => content.trim().to_string(),
+        CommentKind::Block => normalize_block_comment_text(content),
+    }
+}
+
+fn normalize_block_comment_text(text: &str) -> String {
+    let mut lines = text
+        .lines()
+        .map(|line| {
+            let mut trimmed = line.trim_start();
+            if let Some(rest) = trimmed.strip_prefix('*') {
+                trimmed = rest;
+                if trimmed.starts_with(' ') {
+                    trimmed = &trimmed[1..];
+                }
+            }
+            trimmed.trim_end().to_string()
+        })
+        .collect::<Vec<_>>();
+
+    while matches!(lines.first(), Some(line) if line.is_empty()) {
+        lines.remove(0);
+    }
+    while matches!(lines.last(), Some(line) if line.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_block_comment_text;
+
+    #[test]
+    fn normalize_block_comment_text_trims_and_strips_stars() {
+        let text = "\n * hello \n *\n * world \n ";
+        assert_eq!(normalize_block_comment_text(text), "hello\n\nworld");
+    }
+}
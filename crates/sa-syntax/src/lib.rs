@@ -1,9 +1,14 @@
 pub mod ast_utils;
+pub mod cst;
+pub mod docs;
 pub mod parse;
 pub mod tokens;
+pub mod visit;
+pub mod yul;
 
 pub use crate::parse::{
     ImportAlias, Parse, ParsedImport, ParsedImportItems, SyntaxError, SyntaxTree, parse_file,
     parse_imports, parse_imports_with_items,
 };
+pub use crate::yul::{YulParse, parse_yul};
 pub use solar_ast as ast;
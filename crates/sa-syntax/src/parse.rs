@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use sa_span::{TextRange, TextSize};
+use serde::{Deserialize, Serialize};
 use solar_ast as ast;
 use solar_interface::diagnostics::{Diag, DiagCtxt, InMemoryEmitter};
 use solar_interface::source_map::FileName;
@@ -10,33 +11,59 @@ use solar_parse::Parser;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SyntaxError {
     message: String,
+    range: Option<TextRange>,
 }
 
 impl SyntaxError {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            range: None,
         }
     }
 
+    pub fn with_range(mut self, range: TextRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
     pub fn message(&self) -> &str {
         &self.message
     }
 
-    fn from_diag(diag: &Diag) -> Self {
-        Self::new(diag.label().to_string())
+    /// Where the parser reported the error, if it pointed at the source.
+    pub fn range(&self) -> Option<TextRange> {
+        self.range
+    }
+
+    fn from_diag(session: &Session, diag: &Diag) -> Self {
+        let error = Self::new(diag.label().to_string());
+        let range = diag
+            .span
+            .primary_span()
+            .filter(|span| !span.is_dummy())
+            .and_then(|span| session.source_map().span_to_range(span).ok())
+            .and_then(|range| {
+                let start = TextSize::try_from(range.start).ok()?;
+                let end = TextSize::try_from(range.end).ok()?;
+                Some(TextRange::new(start, end))
+            });
+        match range {
+            Some(range) => error.with_range(range),
+            None => error,
+        }
     }
 }
 
 pub type SyntaxTree = ast::SourceUnit<'static>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParsedImport {
     pub path: String,
     pub items: ParsedImportItems,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ImportAlias {
     pub name: String,
     pub alias: Option<String>,
@@ -48,7 +75,7 @@ impl ImportAlias {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ParsedImportItems {
     Plain,
     SourceAlias(String),
@@ -68,6 +95,10 @@ impl Parse {
         &self.tree
     }
 
+    pub fn tree_mut(&mut self) -> &mut SyntaxTree {
+        &mut self.tree
+    }
+
     pub fn errors(&self) -> &[SyntaxError] {
         &self.errors
     }
@@ -88,9 +119,7 @@ impl Parse {
 
 pub fn parse_file(text: &str) -> Parse {
     let arena = ast::Arena::new();
-    let (emitter, buffer) = InMemoryEmitter::new();
-    let dcx = DiagCtxt::new(Box::new(emitter));
-    let session = Session::builder().dcx(dcx).build();
+    let (session, buffer) = in_memory_session();
 
     let tree = session.enter_sequential(|| {
         let filename = FileName::Custom("input.sol".into());
@@ -107,7 +136,7 @@ pub fn parse_file(text: &str) -> Parse {
         }
     });
 
-    let errors = collect_errors(Arc::clone(&buffer));
+    let errors = session.enter_sequential(|| collect_errors(&session, Arc::clone(&buffer)));
     // SAFETY: the arena is stored in Parse, so the tree's references stay valid.
     let tree =
         unsafe { std::mem::transmute::<ast::SourceUnit<'_>, ast::SourceUnit<'static>>(tree) };
@@ -120,12 +149,20 @@ pub fn parse_file(text: &str) -> Parse {
     }
 }
 
-fn collect_errors(buffer: Arc<solar_data_structures::sync::RwLock<Vec<Diag>>>) -> Vec<SyntaxError> {
+pub(crate) type DiagBuffer = Arc<solar_data_structures::sync::RwLock<Vec<Diag>>>;
+
+pub(crate) fn in_memory_session() -> (Session, DiagBuffer) {
+    let (emitter, buffer) = InMemoryEmitter::new();
+    let dcx = DiagCtxt::new(Box::new(emitter));
+    (Session::builder().dcx(dcx).build(), buffer)
+}
+
+pub(crate) fn collect_errors(session: &Session, buffer: DiagBuffer) -> Vec<SyntaxError> {
     let guard = buffer.read();
     guard
         .iter()
         .filter(|diag| diag.is_error())
-        .map(SyntaxError::from_diag)
+        .map(|diag| SyntaxError::from_diag(session, diag))
         .collect()
 }
 
@@ -195,6 +232,15 @@ mod tests {
         assert!(!parse.errors().is_empty());
     }
 
+    #[test]
+    fn syntax_errors_carry_source_ranges() {
+        let text = "contract Foo { function f() public { uint x = ; } }";
+        let parse = parse_file(text);
+        let range = parse.errors()[0].range().expect("error range");
+        let semicolon = text.find(" ;").expect("broken statement") + 1;
+        assert_eq!(usize::from(range.start()), semicolon);
+    }
+
     #[test]
     fn parses_empty_file() {
         let parse = parse_file("");
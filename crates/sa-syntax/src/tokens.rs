@@ -38,6 +38,146 @@ pub fn collect_comments(text: &str) -> Vec<Comment> {
     })
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    Keyword,
+    /// An elementary type name such as `uint256`, `address` or `bytes32`.
+    Type,
+    Ident,
+    Number,
+    /// String, unicode and hex string literals.
+    String,
+    Comment,
+    DocComment,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassifiedToken {
+    pub class: TokenClass,
+    pub range: TextRange,
+}
+
+/// Lexes `text` without parsing it. Whitespace is skipped and characters the lexer rejects are
+/// dropped, so the tokens cover the text with gaps.
+pub fn lex(text: &str) -> Vec<ClassifiedToken> {
+    let session = Session::builder()
+        .with_silent_emitter(None)
+        .single_threaded()
+        .build();
+    session.enter_sequential(|| {
+        Lexer::new(&session, text)
+            .filter_map(|token| {
+                let start = TextSize::from(token.span.lo().to_usize() as u32);
+                let end = TextSize::from(token.span.hi().to_usize() as u32);
+                let class = match token.kind {
+                    TokenKind::Eof => return None,
+                    TokenKind::Comment(true, _, _) => TokenClass::DocComment,
+                    TokenKind::Comment(false, _, _) => TokenClass::Comment,
+                    TokenKind::Ident(symbol) => classify_ident(symbol.as_str()),
+                    TokenKind::Literal(..) => {
+                        let first = text.as_bytes().get(usize::from(start)).copied();
+                        if first.is_some_and(|byte| byte.is_ascii_digit() || byte == b'.') {
+                            TokenClass::Number
+                        } else {
+                            TokenClass::String
+                        }
+                    }
+                    _ => TokenClass::Punct,
+                };
+                Some(ClassifiedToken {
+                    class,
+                    range: TextRange::new(start, end),
+                })
+            })
+            .collect()
+    })
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "anonymous",
+    "as",
+    "assembly",
+    "break",
+    "calldata",
+    "catch",
+    "constant",
+    "constructor",
+    "continue",
+    "contract",
+    "delete",
+    "do",
+    "else",
+    "emit",
+    "enum",
+    "error",
+    "event",
+    "external",
+    "fallback",
+    "false",
+    "for",
+    "function",
+    "global",
+    "if",
+    "immutable",
+    "import",
+    "indexed",
+    "interface",
+    "internal",
+    "is",
+    "library",
+    "mapping",
+    "memory",
+    "modifier",
+    "new",
+    "override",
+    "payable",
+    "pragma",
+    "private",
+    "public",
+    "pure",
+    "receive",
+    "return",
+    "returns",
+    "revert",
+    "storage",
+    "struct",
+    "transient",
+    "true",
+    "try",
+    "type",
+    "unchecked",
+    "using",
+    "view",
+    "virtual",
+    "while",
+];
+
+fn classify_ident(ident: &str) -> TokenClass {
+    if KEYWORDS.contains(&ident) {
+        TokenClass::Keyword
+    } else if is_elementary_type(ident) {
+        TokenClass::Type
+    } else {
+        TokenClass::Ident
+    }
+}
+
+fn is_elementary_type(ident: &str) -> bool {
+    if matches!(ident, "address" | "bool" | "string" | "bytes") {
+        return true;
+    }
+    let sized = |prefix: &str, valid: fn(u32) -> bool| {
+        ident.strip_prefix(prefix).is_some_and(|rest| {
+            rest.is_empty() || (!rest.starts_with('0') && rest.parse().is_ok_and(valid))
+        })
+    };
+    sized("uint", |bits| bits % 8 == 0 && (8..=256).contains(&bits))
+        || sized("int", |bits| bits % 8 == 0 && (8..=256).contains(&bits))
+        || sized("bytes", |len| (1..=32).contains(&len))
+}
+
 pub struct IdentRangeCollector {
     session: Session,
 }
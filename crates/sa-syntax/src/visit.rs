@@ -0,0 +1,465 @@
+//! Default traversals of the syntax tree.
+//!
+//! Implement the `visit_*` methods a pass cares about and call the matching `walk_*` function
+//! from them to keep descending; every method defaults to a plain walk. Inline assembly and
+//! types are not descended into.
+
+use crate::ast::interface::SpannedOption;
+use crate::ast::{
+    Block, CallArgs, Expr, ExprKind, IndexKind, Item, ItemFunction, ItemKind, SourceUnit, Stmt,
+    StmtKind, VariableDefinition,
+};
+
+pub trait Visitor<'a> {
+    fn visit_source_unit(&mut self, unit: &'a SourceUnit<'static>) {
+        walk_source_unit(self, unit);
+    }
+
+    fn visit_item(&mut self, item: &'a Item<'static>) {
+        walk_item(self, item);
+    }
+
+    /// Functions, modifiers, constructors, `receive` and `fallback`.
+    fn visit_function(&mut self, function: &'a ItemFunction<'static>) {
+        walk_function(self, function);
+    }
+
+    /// State variables, struct fields, parameters and locals.
+    fn visit_variable(&mut self, var: &'a VariableDefinition<'static>) {
+        walk_variable(self, var);
+    }
+
+    fn visit_block(&mut self, block: &'a Block<'static>) {
+        walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt<'static>) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<'static>) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_call_args(&mut self, args: &'a CallArgs<'static>) {
+        walk_call_args(self, args);
+    }
+}
+
+pub fn walk_source_unit<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    unit: &'a SourceUnit<'static>,
+) {
+    for item in unit.items.iter() {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, item: &'a Item<'static>) {
+    match &item.kind {
+        ItemKind::Contract(contract) => {
+            for item in contract.body.iter() {
+                visitor.visit_item(item);
+            }
+        }
+        ItemKind::Function(function) => visitor.visit_function(function),
+        ItemKind::Variable(var) => visitor.visit_variable(var),
+        ItemKind::Struct(strukt) => {
+            for field in strukt.fields.iter() {
+                visitor.visit_variable(field);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn walk_function<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    function: &'a ItemFunction<'static>,
+) {
+    let header = &function.header;
+    for param in header.parameters.vars.iter() {
+        visitor.visit_variable(param);
+    }
+    if let Some(returns) = header.returns.as_ref() {
+        for param in returns.vars.iter() {
+            visitor.visit_variable(param);
+        }
+    }
+    for modifier in header.modifiers.iter() {
+        visitor.visit_call_args(&modifier.arguments);
+    }
+    if let Some(body) = function.body.as_ref() {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_variable<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    var: &'a VariableDefinition<'static>,
+) {
+    if let Some(initializer) = var.initializer.as_deref() {
+        visitor.visit_expr(initializer);
+    }
+}
+
+pub fn walk_block<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, block: &'a Block<'static>) {
+    for stmt in block.stmts.iter() {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt<'static>) {
+    match &stmt.kind {
+        StmtKind::DeclSingle(var) => visitor.visit_variable(var),
+        StmtKind::DeclMulti(vars, expr) => {
+            for var in vars.iter() {
+                if let SpannedOption::Some(var) = var {
+                    visitor.visit_variable(var);
+                }
+            }
+            visitor.visit_expr(expr);
+        }
+        StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => visitor.visit_block(block),
+        StmtKind::For {
+            init,
+            cond,
+            next,
+            body,
+        } => {
+            if let Some(init) = init.as_deref() {
+                visitor.visit_stmt(init);
+            }
+            if let Some(cond) = cond.as_deref() {
+                visitor.visit_expr(cond);
+            }
+            if let Some(next) = next.as_deref() {
+                visitor.visit_expr(next);
+            }
+            visitor.visit_stmt(body);
+        }
+        StmtKind::If(cond, then_branch, else_branch) => {
+            visitor.visit_expr(cond);
+            visitor.visit_stmt(then_branch);
+            if let Some(else_branch) = else_branch.as_deref() {
+                visitor.visit_stmt(else_branch);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            visitor.visit_expr(cond);
+            visitor.visit_stmt(body);
+        }
+        StmtKind::Try(stmt_try) => {
+            visitor.visit_expr(&stmt_try.expr);
+            for clause in stmt_try.clauses.iter() {
+                for param in clause.args.vars.iter() {
+                    visitor.visit_variable(param);
+                }
+                visitor.visit_block(&clause.block);
+            }
+        }
+        StmtKind::Emit(_, args) | StmtKind::Revert(_, args) => visitor.visit_call_args(args),
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr.as_deref() {
+                visitor.visit_expr(expr);
+            }
+        }
+        StmtKind::Expr(expr) => visitor.visit_expr(expr),
+        StmtKind::Break | StmtKind::Continue | StmtKind::Placeholder | StmtKind::Assembly(_) => {}
+    }
+}
+
+pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr<'static>) {
+    match &expr.kind {
+        ExprKind::Member(base, _) => visitor.visit_expr(base),
+        ExprKind::Call(callee, args) => {
+            visitor.visit_expr(callee);
+            visitor.visit_call_args(args);
+        }
+        ExprKind::CallOptions(callee, options) => {
+            visitor.visit_expr(callee);
+            for option in options.iter() {
+                visitor.visit_expr(&option.value);
+            }
+        }
+        ExprKind::Index(base, index) => {
+            visitor.visit_expr(base);
+            match index {
+                IndexKind::Index(index) => {
+                    if let Some(index) = index.as_deref() {
+                        visitor.visit_expr(index);
+                    }
+                }
+                IndexKind::Range(start, end) => {
+                    if let Some(start) = start.as_deref() {
+                        visitor.visit_expr(start);
+                    }
+                    if let Some(end) = end.as_deref() {
+                        visitor.visit_expr(end);
+                    }
+                }
+            }
+        }
+        ExprKind::Assign(lhs, _, rhs) | ExprKind::Binary(lhs, _, rhs) => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        ExprKind::Unary(_, operand) | ExprKind::Delete(operand) => visitor.visit_expr(operand),
+        ExprKind::Ternary(cond, then_expr, else_expr) => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(then_expr);
+            visitor.visit_expr(else_expr);
+        }
+        ExprKind::Tuple(items) => {
+            for item in items.iter() {
+                if let SpannedOption::Some(item) = item {
+                    visitor.visit_expr(item);
+                }
+            }
+        }
+        ExprKind::Array(items) => {
+            for item in items.iter() {
+                visitor.visit_expr(item);
+            }
+        }
+        ExprKind::Payable(args) => visitor.visit_call_args(args),
+        ExprKind::Ident(_)
+        | ExprKind::Lit(..)
+        | ExprKind::New(_)
+        | ExprKind::TypeCall(_)
+        | ExprKind::Type(_) => {}
+    }
+}
+
+pub fn walk_call_args<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, args: &'a CallArgs<'static>) {
+    for expr in args.exprs() {
+        visitor.visit_expr(expr);
+    }
+}
+
+/// [`Visitor`] over a tree that may be rewritten in place, e.g. through
+/// [`Parse::tree_mut`](crate::Parse::tree_mut).
+pub trait VisitorMut {
+    fn visit_source_unit(&mut self, unit: &mut SourceUnit<'static>) {
+        walk_source_unit_mut(self, unit);
+    }
+
+    fn visit_item(&mut self, item: &mut Item<'static>) {
+        walk_item_mut(self, item);
+    }
+
+    fn visit_function(&mut self, function: &mut ItemFunction<'static>) {
+        walk_function_mut(self, function);
+    }
+
+    fn visit_variable(&mut self, var: &mut VariableDefinition<'static>) {
+        walk_variable_mut(self, var);
+    }
+
+    fn visit_block(&mut self, block: &mut Block<'static>) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt<'static>) {
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr<'static>) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_call_args(&mut self, args: &mut CallArgs<'static>) {
+        walk_call_args_mut(self, args);
+    }
+}
+
+pub fn walk_source_unit_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    unit: &mut SourceUnit<'static>,
+) {
+    for item in unit.items.iter_mut() {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item_mut<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut Item<'static>) {
+    match &mut item.kind {
+        ItemKind::Contract(contract) => {
+            for item in contract.body.iter_mut() {
+                visitor.visit_item(item);
+            }
+        }
+        ItemKind::Function(function) => visitor.visit_function(function),
+        ItemKind::Variable(var) => visitor.visit_variable(var),
+        ItemKind::Struct(strukt) => {
+            for field in strukt.fields.iter_mut() {
+                visitor.visit_variable(field);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn walk_function_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    function: &mut ItemFunction<'static>,
+) {
+    let header = &mut function.header;
+    for param in header.parameters.vars.iter_mut() {
+        visitor.visit_variable(param);
+    }
+    if let Some(returns) = header.returns.as_mut() {
+        for param in returns.vars.iter_mut() {
+            visitor.visit_variable(param);
+        }
+    }
+    for modifier in header.modifiers.iter_mut() {
+        visitor.visit_call_args(&mut modifier.arguments);
+    }
+    if let Some(body) = function.body.as_mut() {
+        visitor.visit_block(body);
+    }
+}
+
+pub fn walk_variable_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    var: &mut VariableDefinition<'static>,
+) {
+    if let Some(initializer) = var.initializer.as_deref_mut() {
+        visitor.visit_expr(initializer);
+    }
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block<'static>) {
+    for stmt in block.stmts.iter_mut() {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt<'static>) {
+    match &mut stmt.kind {
+        StmtKind::DeclSingle(var) => visitor.visit_variable(var),
+        StmtKind::DeclMulti(vars, expr) => {
+            for var in vars.iter_mut() {
+                if let SpannedOption::Some(var) = var {
+                    visitor.visit_variable(var);
+                }
+            }
+            visitor.visit_expr(expr);
+        }
+        StmtKind::Block(block) | StmtKind::UncheckedBlock(block) => visitor.visit_block(block),
+        StmtKind::For {
+            init,
+            cond,
+            next,
+            body,
+        } => {
+            if let Some(init) = init.as_deref_mut() {
+                visitor.visit_stmt(init);
+            }
+            if let Some(cond) = cond.as_deref_mut() {
+                visitor.visit_expr(cond);
+            }
+            if let Some(next) = next.as_deref_mut() {
+                visitor.visit_expr(next);
+            }
+            visitor.visit_stmt(body);
+        }
+        StmtKind::If(cond, then_branch, else_branch) => {
+            visitor.visit_expr(cond);
+            visitor.visit_stmt(then_branch);
+            if let Some(else_branch) = else_branch.as_deref_mut() {
+                visitor.visit_stmt(else_branch);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            visitor.visit_expr(cond);
+            visitor.visit_stmt(body);
+        }
+        StmtKind::Try(stmt_try) => {
+            visitor.visit_expr(&mut stmt_try.expr);
+            for clause in stmt_try.clauses.iter_mut() {
+                for param in clause.args.vars.iter_mut() {
+                    visitor.visit_variable(param);
+                }
+                visitor.visit_block(&mut clause.block);
+            }
+        }
+        StmtKind::Emit(_, args) | StmtKind::Revert(_, args) => visitor.visit_call_args(args),
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr.as_deref_mut() {
+                visitor.visit_expr(expr);
+            }
+        }
+        StmtKind::Expr(expr) => visitor.visit_expr(expr),
+        StmtKind::Break | StmtKind::Continue | StmtKind::Placeholder | StmtKind::Assembly(_) => {}
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr<'static>) {
+    match &mut expr.kind {
+        ExprKind::Member(base, _) => visitor.visit_expr(base),
+        ExprKind::Call(callee, args) => {
+            visitor.visit_expr(callee);
+            visitor.visit_call_args(args);
+        }
+        ExprKind::CallOptions(callee, options) => {
+            visitor.visit_expr(callee);
+            for option in options.iter_mut() {
+                visitor.visit_expr(&mut option.value);
+            }
+        }
+        ExprKind::Index(base, index) => {
+            visitor.visit_expr(base);
+            match index {
+                IndexKind::Index(index) => {
+                    if let Some(index) = index.as_deref_mut() {
+                        visitor.visit_expr(index);
+                    }
+                }
+                IndexKind::Range(start, end) => {
+                    if let Some(start) = start.as_deref_mut() {
+                        visitor.visit_expr(start);
+                    }
+                    if let Some(end) = end.as_deref_mut() {
+                        visitor.visit_expr(end);
+                    }
+                }
+            }
+        }
+        ExprKind::Assign(lhs, _, rhs) | ExprKind::Binary(lhs, _, rhs) => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        ExprKind::Unary(_, operand) | ExprKind::Delete(operand) => visitor.visit_expr(operand),
+        ExprKind::Ternary(cond, then_expr, else_expr) => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(then_expr);
+            visitor.visit_expr(else_expr);
+        }
+        ExprKind::Tuple(items) => {
+            for item in items.iter_mut() {
+                if let SpannedOption::Some(item) = item {
+                    visitor.visit_expr(item);
+                }
+            }
+        }
+        ExprKind::Array(items) => {
+            for item in items.iter_mut() {
+                visitor.visit_expr(item);
+            }
+        }
+        ExprKind::Payable(args) => visitor.visit_call_args(args),
+        ExprKind::Ident(_)
+        | ExprKind::Lit(..)
+        | ExprKind::New(_)
+        | ExprKind::TypeCall(_)
+        | ExprKind::Type(_) => {}
+    }
+}
+
+pub fn walk_call_args_mut<V: VisitorMut + ?Sized>(visitor: &mut V, args: &mut CallArgs<'static>) {
+    for expr in args.exprs_mut() {
+        visitor.visit_expr(expr);
+    }
+}
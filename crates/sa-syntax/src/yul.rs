@@ -0,0 +1,82 @@
+//! Parsing Yul on its own, for `.yul` files and for assembly blocks taken out of a Solidity file.
+
+use std::sync::Arc;
+
+use sa_span::{TextRange, TextSize};
+use solar_ast::{self as ast, yul};
+use solar_interface::source_map::FileName;
+use solar_interface::{Session, Span};
+use solar_parse::Parser;
+
+use crate::parse::{SyntaxError, collect_errors, in_memory_session};
+
+/// A parsed Yul object. A plain `{ ... }` block parses as an object named `object` whose code is
+/// that block, as in solc.
+pub struct YulParse {
+    _arena: ast::Arena,
+    object: Option<yul::Object<'static>>,
+    errors: Vec<SyntaxError>,
+    session: Session,
+}
+
+impl YulParse {
+    /// The parsed object, or `None` when the text is too broken to produce one.
+    pub fn object(&self) -> Option<&yul::Object<'static>> {
+        self.object.as_ref()
+    }
+
+    /// The code of the outermost object.
+    pub fn block(&self) -> Option<&yul::Block<'static>> {
+        self.object.as_ref().map(|object| &object.code.code)
+    }
+
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    pub fn with_session<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.session.enter_sequential(f)
+    }
+
+    /// Ranges are relative to the parsed text; callers re-parsing an assembly block shift them by
+    /// the block's offset.
+    pub fn span_to_text_range(&self, span: Span) -> Option<TextRange> {
+        self.with_session(|| {
+            let range = self.session.source_map().span_to_range(span).ok()?;
+            let start = TextSize::try_from(range.start).ok()?;
+            let end = TextSize::try_from(range.end).ok()?;
+            Some(TextRange::new(start, end))
+        })
+    }
+}
+
+pub fn parse_yul(text: &str) -> YulParse {
+    let arena = ast::Arena::new();
+    let (session, buffer) = in_memory_session();
+
+    let object = session.enter_sequential(|| {
+        let filename = FileName::Custom("input.yul".into());
+        let mut parser =
+            Parser::from_source_code(&session, &arena, filename, text.to_string()).ok()?;
+        match parser.parse_yul_file_object() {
+            Ok(object) => Some(object),
+            Err(err) => {
+                err.emit();
+                None
+            }
+        }
+    });
+
+    let errors = session.enter_sequential(|| collect_errors(&session, Arc::clone(&buffer)));
+    // SAFETY: the arena is stored in YulParse, so the object's references stay valid.
+    let object = object.map(|object| unsafe {
+        std::mem::transmute::<yul::Object<'_>, yul::Object<'static>>(object)
+    });
+
+    YulParse {
+        _arena: arena,
+        object,
+        errors,
+        session,
+    }
+}
@@ -0,0 +1,321 @@
+use sa_span::{TextRange, TextSize};
+use sa_syntax::cst::{SyntaxKind, SyntaxNode, parse_cst};
+
+fn member_names(contract: &SyntaxNode) -> Vec<(SyntaxKind, String)> {
+    contract
+        .child_node(SyntaxKind::ContractBody)
+        .into_iter()
+        .flat_map(|body| body.child_nodes())
+        .map(|member| {
+            let name = member
+                .name()
+                .map_or_else(String::new, |token| token.text().to_string());
+            (member.kind(), name)
+        })
+        .collect()
+}
+
+#[test]
+fn cst_round_trips_broken_text() {
+    let text = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Doc.
+contract Foo is Bar, Lib.Base(1, "x") {
+    uint256 public value = 1;
+    function f(uint a, { /* unclosed */
+        if (a > 1) { value = a;
+    function g() external {}
+    é
+}
+contract
+"#;
+    let cst = parse_cst(text);
+    assert_eq!(cst.root().text(), text);
+    assert_eq!(cst.root().range().end(), TextSize::of(text));
+    assert!(!cst.errors().is_empty());
+}
+
+#[test]
+fn cst_recovers_members_after_an_unclosed_body() {
+    let text = r#"contract Foo {
+    uint256 public value;
+    event Changed(uint256 value);
+    function f() public {
+        if (value > 1) {
+            value = 2;
+    function g() external {}
+    modifier onlyOwner() { _; }
+    struct S { uint a; }
+    enum E { A }
+    type Id is uint256;
+    error Failed(uint code);
+    using Lib for uint256;
+}
+
+contract Next {}
+"#;
+    let cst = parse_cst(text);
+    let contracts = cst.contracts().collect::<Vec<_>>();
+    assert_eq!(contracts.len(), 2);
+    assert_eq!(
+        member_names(contracts[0]),
+        vec![
+            (SyntaxKind::Variable, "value".to_string()),
+            (SyntaxKind::Event, "Changed".to_string()),
+            (SyntaxKind::Function, "f".to_string()),
+            (SyntaxKind::Function, "g".to_string()),
+            (SyntaxKind::Modifier, "onlyOwner".to_string()),
+            (SyntaxKind::Struct, "S".to_string()),
+            (SyntaxKind::Enum, "E".to_string()),
+            (SyntaxKind::Udvt, "Id".to_string()),
+            (SyntaxKind::ErrorDef, "Failed".to_string()),
+            (SyntaxKind::Using, String::new()),
+        ]
+    );
+    assert!(contracts[0].is_closed());
+    assert_eq!(contracts[1].name().map(|token| token.text()), Some("Next"));
+    assert!(
+        cst.errors()
+            .iter()
+            .any(|error| error.message == "missing `}`")
+    );
+}
+
+#[test]
+fn cst_keeps_yul_functions_inside_their_assembly_block() {
+    let text = r#"contract Math {
+    function sum(uint256[] memory xs) internal pure returns (uint256 total) {
+        assembly ("memory-safe") {
+            function g(a, b) -> c {
+                c := add(a, b)
+            }
+            let n := mload(xs)
+            for { let i := 0 } lt(i, n) { i := add(i, 1) } {
+                total := g(total, mload(add(add(xs, 0x20), mul(i, 0x20))))
+            }
+            switch n
+            case 0 { total := 0 }
+            default { leave }
+        }
+    }
+
+    function after() external {}
+}
+
+contract Next {}
+"#;
+    let cst = parse_cst(text);
+    assert_eq!(cst.root().text(), text);
+    assert!(cst.errors().is_empty(), "{:?}", cst.errors());
+
+    let contracts = cst.contracts().collect::<Vec<_>>();
+    assert_eq!(contracts.len(), 2);
+    assert!(contracts[0].is_closed());
+    assert_eq!(
+        member_names(contracts[0]),
+        vec![
+            (SyntaxKind::Function, "sum".to_string()),
+            (SyntaxKind::Function, "after".to_string()),
+        ]
+    );
+    let in_yul = TextSize::of(&text[..text.find("mload(xs)").expect("mload")]);
+    assert_eq!(
+        cst.contract_at_offset(in_yul)
+            .and_then(|contract| contract.name())
+            .map(|token| token.text()),
+        Some("Math")
+    );
+}
+
+#[test]
+fn cst_ends_an_unclosed_assembly_block_at_the_next_contract() {
+    let text = r#"contract A {
+    function f() public {
+        assembly {
+            function g() {}
+            let x := 1
+
+contract B {}
+"#;
+    let cst = parse_cst(text);
+    let contracts = cst.contracts().collect::<Vec<_>>();
+    assert_eq!(contracts.len(), 2);
+    assert_eq!(
+        member_names(contracts[0]),
+        vec![(SyntaxKind::Function, "f".to_string())]
+    );
+    assert_eq!(contracts[1].name().map(|token| token.text()), Some("B"));
+}
+
+#[test]
+fn cst_reads_base_lists() {
+    let text = "abstract contract Foo is Bar, Lib.Base(1, 2), Other { }";
+    let cst = parse_cst(text);
+    let foo = cst.contract_by_name("Foo").expect("contract");
+    assert_eq!(
+        foo.base_paths(),
+        vec![
+            vec!["Bar".to_string()],
+            vec!["Lib".to_string(), "Base".to_string()],
+            vec!["Other".to_string()],
+        ]
+    );
+    assert!(cst.errors().is_empty());
+}
+
+#[test]
+fn cst_finds_the_contract_at_an_offset() {
+    let text = r#"contract A {
+    function f() public {}
+}
+
+contract B is A {
+    function g() public {
+        f(
+"#;
+    let cst = parse_cst(text);
+    let inside_a = TextSize::of(&text[..text.find("function f").expect("f")]);
+    let name = |offset| {
+        cst.contract_at_offset(offset)
+            .and_then(|contract| contract.name())
+            .map(|token| token.text().to_string())
+    };
+    assert_eq!(name(inside_a), Some("A".to_string()));
+    assert_eq!(name(TextSize::of(text)), Some("B".to_string()));
+    assert_eq!(
+        name(TextSize::of(&text[..text.find("\n\n").expect("gap") + 1])),
+        None
+    );
+}
+
+#[test]
+fn cst_reparse_matches_a_full_parse() {
+    let text = r#"contract Foo {
+    uint256 value;
+    function f(uint a) public {
+        if (a > 1) {
+            value = a;
+        }
+    }
+    function g() external {}
+}
+"#;
+    let cst = parse_cst(text);
+    let at = |needle: &str| TextSize::of(&text[..text.find(needle).expect("needle")]);
+    let edits = [
+        // Typing inside the innermost block.
+        (TextRange::empty(at("value = a")), "value += 1; "),
+        // Deleting a statement.
+        (
+            TextRange::at(at("value = a"), TextSize::of("value = a;")),
+            "",
+        ),
+        // Opening a block that is never closed changes the structure.
+        (TextRange::empty(at("value = a")), "while (true) { "),
+        // A new member typed into the body.
+        (TextRange::empty(at("value = a")), "}\n    function h() {"),
+        // Editing outside any block.
+        (TextRange::at(at("g()"), TextSize::of("g")), "renamed"),
+    ];
+    for (range, new_text) in edits {
+        let start = usize::from(range.start());
+        let end = usize::from(range.end());
+        let edited = format!("{}{}{}", &text[..start], new_text, &text[end..]);
+        assert_eq!(
+            cst.reparse([(range, new_text)], &edited),
+            parse_cst(&edited),
+            "{new_text:?}"
+        );
+    }
+}
+
+#[test]
+fn cst_reparse_drops_the_errors_an_edit_fixes() {
+    let text = "contract A {\n    function f() public {\n        assembly (\"memory-safe\" {\n        }\n    }\n}\n";
+    let cst = parse_cst(text);
+    assert_eq!(cst.errors().len(), 1, "{:?}", cst.errors());
+
+    let quote = TextSize::of(&text[..text.find("\" {").expect("flags")]) + TextSize::from(1);
+    let fixed = text.replace("\" {", "\") {");
+    let reparsed = cst.reparse([(TextRange::empty(quote), ")")], &fixed);
+    assert_eq!(reparsed, parse_cst(&fixed));
+    assert!(reparsed.errors().is_empty());
+}
+
+#[test]
+fn cst_reparse_applies_edits_in_order_and_shares_what_they_do_not_touch() {
+    let text = "contract A {\n    function f() public {}\n}\ncontract B {\n    function g() public {\n        x;\n    }\n}\n";
+    let cst = parse_cst(text);
+    let x = TextSize::of(&text[..text.find("x;").expect("x")]);
+    let edits = [
+        (TextRange::empty(x), "y = 1; "),
+        (TextRange::at(x, TextSize::of("y")), "zz"),
+    ];
+    let edited = text.replace("x;", "zz = 1; x;");
+    let reparsed = cst.reparse(edits, &edited);
+    assert_eq!(reparsed, parse_cst(&edited));
+
+    let first = |cst: &sa_syntax::cst::Cst| cst.contracts().next().expect("A").children().as_ptr();
+    assert!(std::ptr::eq(first(&cst), first(&reparsed)));
+}
+
+#[test]
+fn cst_reparse_falls_back_to_a_full_parse_for_ranges_it_cannot_apply() {
+    let text = "contract A {\n    function f() public {\n        string s = \"é\";\n    }\n}\n";
+    let cst = parse_cst(text);
+    let accent = TextSize::of(&text[..text.find('é').expect("accent")]);
+    let edited = "contract B {}\n";
+    for range in [
+        // Inside a character.
+        TextRange::at(accent + TextSize::from(1), TextSize::from(0)),
+        // Past the end of the tree.
+        TextRange::empty(TextSize::of(text) + TextSize::from(10)),
+    ] {
+        assert_eq!(cst.reparse([(range, "x")], edited), parse_cst(edited));
+    }
+    // Edits that do not add up to the text are not trusted either.
+    let body = TextSize::of(&text[..text.find("string").expect("body")]);
+    assert_eq!(
+        cst.reparse([(TextRange::empty(body), "x; ")], edited),
+        parse_cst(edited)
+    );
+}
+
+#[test]
+fn cst_dumps_the_covering_node_with_ranges() {
+    let text = "contract A {\n    function f() public {}\n}\n";
+    let cst = parse_cst(text);
+    let body = TextSize::of(&text[..text.find("{}").expect("body") + 1]);
+    let node = cst.covering_node(TextRange::empty(body));
+    assert_eq!(node.kind(), SyntaxKind::Block);
+    assert_eq!(
+        node.debug_dump(),
+        "Block@37..39\n  LBrace@37..38 \"{\"\n  RBrace@38..39 \"}\"\n"
+    );
+
+    let dump = cst.debug_dump();
+    assert!(
+        dump.starts_with("SourceUnit@0..42\n  Contract@0..41\n"),
+        "{dump}"
+    );
+    assert!(dump.contains("\n      Function@17..39\n"), "{dump}");
+}
+
+#[test]
+fn cst_lists_the_tokens_below_a_node_without_trivia() {
+    let text = "contract A {\n    // note\n    function f() { x; }\n}\n";
+    let cst = parse_cst(text);
+    let texts = cst
+        .root()
+        .descendant_tokens()
+        .into_iter()
+        .map(|token| token.text())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "contract", "A", "{", "function", "f", "(", ")", "{", "x", ";", "}", "}"
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+use sa_syntax::ast::{Item, ItemKind};
+use sa_syntax::docs::{DocTagKind, item_docs};
+use sa_syntax::{Parse, parse_file};
+
+fn function<'a>(parse: &'a Parse, name: &str) -> &'a Item<'static> {
+    parse
+        .tree()
+        .items
+        .iter()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Contract(contract) => Some(contract),
+            _ => None,
+        })
+        .flat_map(|contract| contract.body.iter())
+        .find(|item| {
+            parse.with_session(|| {
+                matches!(&item.kind, ItemKind::Function(func)
+                    if func.header.name.is_some_and(|ident| ident.as_str() == name))
+            })
+        })
+        .expect("function")
+}
+
+#[test]
+fn item_docs_group_tags_with_their_continuations() {
+    let text = r#"
+contract Foo {
+    /// @notice Adds two numbers.
+    /// Overflow reverts.
+    /// @param a The first operand
+    /// @param b The second operand
+    /// @return sum The total
+    /// @custom:security audited
+    function add(uint256 a, uint256 b) public returns (uint256 sum) {}
+}
+"#;
+    let parse = parse_file(text);
+    let docs = item_docs(&parse, function(&parse, "add")).expect("docs");
+    assert!(docs.has_explicit_tags());
+
+    let tags = docs
+        .tags()
+        .iter()
+        .map(|tag| (tag.kind.clone(), tag.text()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        [
+            (
+                DocTagKind::Notice,
+                "Adds two numbers.\nOverflow reverts.".to_string()
+            ),
+            (
+                DocTagKind::Param("a".into()),
+                "The first operand".to_string()
+            ),
+            (
+                DocTagKind::Param("b".into()),
+                "The second operand".to_string()
+            ),
+            (DocTagKind::Return("sum".into()), "The total".to_string()),
+            (DocTagKind::Custom("security".into()), "audited".to_string()),
+        ]
+    );
+    assert_eq!(
+        docs.params().map(|(name, _)| name).collect::<Vec<_>>(),
+        ["a", "b"]
+    );
+
+    let range = docs.range();
+    let covered = &text[usize::from(range.start())..usize::from(range.end())];
+    assert!(covered.starts_with("/// @notice"));
+    assert!(covered.ends_with("audited"));
+}
+
+#[test]
+fn item_docs_treat_untagged_comments_as_a_notice() {
+    let text = r#"
+contract Foo {
+    /**
+     * Does the thing.
+     */
+    function f() public {}
+
+    function g() public {}
+}
+"#;
+    let parse = parse_file(text);
+    let docs = item_docs(&parse, function(&parse, "f")).expect("docs");
+    assert!(!docs.has_explicit_tags());
+    assert_eq!(docs.tags().len(), 1);
+    assert_eq!(docs.tags()[0].kind, DocTagKind::Notice);
+    assert_eq!(docs.tags()[0].text(), "Does the thing.");
+
+    assert!(item_docs(&parse, function(&parse, "g")).is_none());
+}
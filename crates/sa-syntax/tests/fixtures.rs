@@ -75,3 +75,20 @@ fn collect_qualified_matches_multi_level() {
     );
     assert_eq!(slice_range(text, range.range), "Bar");
 }
+
+#[test]
+fn classified_token_fixtures() {
+    let text = r#"/// Doc
+contract Foo {
+    uint256 constant X = 0x10; // note
+    string s = "hi";
+    function f(bytes32 b) public {}
+}"#;
+    let expected = "DocComment:/// Doc\nKeyword:contract\nIdent:Foo\nPunct:{\nType:uint256\nKeyword:constant\nIdent:X\nPunct:=\nNumber:0x10\nPunct:;\nComment:// note\nType:string\nIdent:s\nPunct:=\nString:\"hi\"\nPunct:;\nKeyword:function\nIdent:f\nPunct:(\nType:bytes32\nIdent:b\nPunct:)\nKeyword:public\nPunct:{\nPunct:}\nPunct:}";
+    let tokens = tokens::lex(text)
+        .into_iter()
+        .map(|token| format!("{:?}:{}", token.class, slice_range(text, token.range)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(tokens, expected);
+}
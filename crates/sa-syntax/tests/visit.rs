@@ -0,0 +1,76 @@
+use sa_syntax::ast::{Expr, ExprKind, VariableDefinition};
+use sa_syntax::parse_file;
+use sa_syntax::visit::{Visitor, VisitorMut, walk_expr, walk_expr_mut, walk_variable};
+
+const TEXT: &str = r#"
+contract Foo {
+    uint256 public total = seed();
+
+    function f(uint256 a) public returns (uint256 out) {
+        uint256 b = a + 1;
+        for (uint256 i = 0; i < b; i++) {
+            if (i > 2) {
+                out += g(i);
+            }
+        }
+        try this.g(b) returns (uint256 c) {
+            out = c;
+        } catch {}
+    }
+}
+"#;
+
+#[derive(Default)]
+struct Collector {
+    variables: Vec<String>,
+    idents: usize,
+}
+
+impl<'a> Visitor<'a> for Collector {
+    fn visit_variable(&mut self, var: &'a VariableDefinition<'static>) {
+        if let Some(name) = var.name {
+            self.variables.push(name.to_string());
+        }
+        walk_variable(self, var);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<'static>) {
+        if matches!(expr.kind, ExprKind::Ident(_)) {
+            self.idents += 1;
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[derive(Default)]
+struct IdentCounter {
+    idents: usize,
+}
+
+impl VisitorMut for IdentCounter {
+    fn visit_expr(&mut self, expr: &mut Expr<'static>) {
+        if matches!(expr.kind, ExprKind::Ident(_)) {
+            self.idents += 1;
+        }
+        walk_expr_mut(self, expr);
+    }
+}
+
+#[test]
+fn visitor_reaches_nested_variables_and_expressions() {
+    let parse = parse_file(TEXT);
+    assert!(parse.errors().is_empty());
+    let mut collector = Collector::default();
+    parse.with_session(|| collector.visit_source_unit(parse.tree()));
+    assert_eq!(collector.variables, ["total", "a", "out", "b", "i", "c"]);
+    // seed, a, i, b, i, i, out, g, i, this, b, out, c
+    assert_eq!(collector.idents, 13);
+}
+
+#[test]
+fn mutable_visitor_walks_the_same_tree() {
+    let mut parse = parse_file(TEXT);
+    let mut counter = IdentCounter::default();
+    counter.visit_source_unit(parse.tree_mut());
+    assert_eq!(counter.idents, 13);
+}
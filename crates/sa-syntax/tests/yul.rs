@@ -0,0 +1,47 @@
+use sa_syntax::ast::yul;
+use sa_syntax::parse_yul;
+
+#[test]
+fn parses_a_plain_block_with_spans() {
+    let text = "{\n    function double(x) -> y { y := add(x, x) }\n    let v := double(2)\n}";
+    let parse = parse_yul(text);
+    assert!(parse.errors().is_empty());
+
+    let block = parse.block().expect("block");
+    assert_eq!(block.stmts.len(), 2);
+    let yul::StmtKind::FunctionDef(function) = &block.stmts[0].kind else {
+        panic!("expected a function definition");
+    };
+    let name = parse.with_session(|| function.name.to_string());
+    assert_eq!(name, "double");
+
+    let range = parse
+        .span_to_text_range(block.stmts[1].span)
+        .expect("statement range");
+    assert_eq!(
+        &text[usize::from(range.start())..usize::from(range.end())],
+        "let v := double(2)"
+    );
+}
+
+#[test]
+fn parses_objects() {
+    let text = r#"object "Token" {
+    code { sstore(0, caller()) }
+    object "runtime" {
+        code { return(0, 0) }
+    }
+}"#;
+    let parse = parse_yul(text);
+    assert!(parse.errors().is_empty());
+    let object = parse.object().expect("object");
+    assert_eq!(object.children.len(), 1);
+    assert_eq!(parse.block().expect("code").stmts.len(), 1);
+}
+
+#[test]
+fn reports_errors_for_broken_yul() {
+    let parse = parse_yul("{ let x := }");
+    assert!(!parse.errors().is_empty());
+    assert!(parse.errors()[0].range().is_some());
+}
@@ -384,10 +384,15 @@ where
         P: Serialize,
     {
         let request_id = self.next_id;
-        let request = Request::build(method)
-            .id(request_id)
-            .params(serde_json::to_value(params).expect("serialize request params"))
-            .finish();
+        let params = serde_json::to_value(params).expect("serialize request params");
+        // Requests without parameters (`()`) leave `params` out, as JSON-RPC clients do.
+        let request = match params {
+            Value::Null => Request::build(method).id(request_id).finish(),
+            params => Request::build(method)
+                .id(request_id)
+                .params(params)
+                .finish(),
+        };
         self.next_id += 1;
 
         let response = self
@@ -267,12 +267,10 @@ fn default_foundry_toml() -> String {
 fn load_fixture_vfs(config: &ResolvedFoundryConfig) -> Result<VfsSnapshot> {
     let workspace = config.workspace();
     let mut files = Vec::new();
-    let roots = [
-        workspace.src().as_str(),
-        workspace.lib().as_str(),
-        workspace.test().as_str(),
-        workspace.script().as_str(),
-    ];
+    let roots = [workspace.src(), workspace.test(), workspace.script()]
+        .into_iter()
+        .chain(workspace.libs())
+        .map(NormalizedPath::as_str);
 
     for root in roots {
         let path = PathBuf::from(root);
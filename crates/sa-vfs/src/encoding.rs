@@ -0,0 +1,61 @@
+//! Decoding of file contents read from disk.
+//!
+//! Text is kept the way the editor shows it: a leading UTF-8 byte order mark is dropped (clients
+//! never send it, so every offset would be off by three bytes), line endings are left untouched
+//! so CRLF files keep matching their editor buffers, and invalid UTF-8 is replaced with U+FFFD
+//! instead of failing the whole file.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// The file was not valid UTF-8 and invalid sequences were replaced.
+    pub lossy: bool,
+}
+
+pub fn decode_text(mut bytes: Vec<u8>) -> DecodedText {
+    if bytes.starts_with(UTF8_BOM) {
+        bytes.drain(..UTF8_BOM.len());
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => DecodedText { text, lossy: false },
+        Err(error) => DecodedText {
+            text: String::from_utf8_lossy(error.as_bytes()).into_owned(),
+            lossy: true,
+        },
+    }
+}
+
+pub fn read_text(path: &Path) -> io::Result<DecodedText> {
+    fs::read(path).map(decode_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_text;
+
+    #[test]
+    fn strips_the_byte_order_mark() {
+        let decoded = decode_text(b"\xEF\xBB\xBFcontract A {}".to_vec());
+        assert_eq!(decoded.text, "contract A {}");
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let decoded = decode_text(b"contract A {\r\n}\r\n".to_vec());
+        assert_eq!(decoded.text, "contract A {\r\n}\r\n");
+    }
+
+    #[test]
+    fn replaces_invalid_utf8() {
+        let decoded = decode_text(b"// caf\xE9\ncontract A {}".to_vec());
+        assert_eq!(decoded.text, "// caf\u{FFFD}\ncontract A {}");
+        assert!(decoded.lossy);
+    }
+}
@@ -2,8 +2,17 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use sa_paths::NormalizedPath;
+use sa_span::TextRange;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+mod encoding;
+mod watcher;
+
+pub use encoding::{DecodedText, decode_text, read_text};
+pub use watcher::{VfsWatcher, resolve_changes};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct FileId(u32);
 
 impl FileId {
@@ -16,21 +25,62 @@ impl FileId {
     }
 }
 
+/// One editor edit: `range`, in bytes of the text before the edit, replaced by `new_text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OverlayEdit {
+    pub range: TextRange,
+    pub new_text: String,
+}
+
+/// A file's contents as two layers: what is on disk and what the editor holds. The overlay wins
+/// while it exists.
 #[derive(Clone, Debug)]
 struct FileEntry {
-    text: Arc<str>,
+    disk: Option<Arc<str>>,
+    overlay: Option<Arc<str>>,
     version: u32,
+    /// The edits that turned the previous version into this one; empty when the text was
+    /// replaced wholesale.
+    edits: Arc<[OverlayEdit]>,
+    /// The disk layer changed while the overlay had unsaved edits.
+    disk_conflict: bool,
+}
+
+impl FileEntry {
+    fn text(&self) -> Option<&Arc<str>> {
+        self.overlay.as_ref().or(self.disk.as_ref())
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.overlay
+            .as_ref()
+            .is_some_and(|overlay| self.disk.as_ref() != Some(overlay))
+    }
 }
 
 #[derive(Debug)]
 pub enum VfsChange {
+    /// Contents read from disk.
     Set {
         path: NormalizedPath,
         text: Arc<str>,
     },
-    Remove {
+    /// The file is gone from disk. It stays visible while an overlay exists.
+    Remove { path: NormalizedPath },
+    /// Unsaved editor contents, layered over the disk contents.
+    SetOverlay {
         path: NormalizedPath,
+        text: Arc<str>,
     },
+    /// Unsaved editor contents, the result of applying `edits` in order to the current
+    /// contents. Each edit's range refers to the text left by the edits before it.
+    EditOverlay {
+        path: NormalizedPath,
+        text: Arc<str>,
+        edits: Vec<OverlayEdit>,
+    },
+    /// Drops the editor contents, falling back to the disk contents if there are any.
+    ClearOverlay { path: NormalizedPath },
 }
 
 #[derive(Default, Debug)]
@@ -45,17 +95,69 @@ impl Vfs {
     pub fn apply_change(&mut self, change: VfsChange) {
         match change {
             VfsChange::Set { path, text } => {
-                let file_id = self
-                    .path_to_id
-                    .get(&path)
-                    .copied()
-                    .unwrap_or_else(|| self.alloc_file_id(path.clone()));
-                self.upsert_file(file_id, path, text);
+                let (created, entry) = self.entry(path);
+                if entry.is_dirty() && entry.disk.as_ref().is_some_and(|disk| *disk != text) {
+                    entry.disk_conflict = true;
+                }
+                if !created && entry.overlay.is_none() {
+                    entry.version = entry.version.saturating_add(1);
+                }
+                if entry.overlay.is_none() {
+                    entry.edits = Arc::from([]);
+                }
+                entry.disk = Some(text);
+                if !entry.is_dirty() {
+                    entry.disk_conflict = false;
+                }
+            }
+            VfsChange::SetOverlay { path, text } => {
+                let (created, entry) = self.entry(path);
+                entry.overlay = Some(text);
+                entry.edits = Arc::from([]);
+                if !created {
+                    entry.version = entry.version.saturating_add(1);
+                }
+                if !entry.is_dirty() {
+                    entry.disk_conflict = false;
+                }
+            }
+            VfsChange::EditOverlay { path, text, edits } => {
+                let (created, entry) = self.entry(path);
+                // Edits against a file we never saw cannot be replayed.
+                entry.edits = if created { Arc::from([]) } else { edits.into() };
+                entry.overlay = Some(text);
+                if !created {
+                    entry.version = entry.version.saturating_add(1);
+                }
+                if !entry.is_dirty() {
+                    entry.disk_conflict = false;
+                }
             }
             VfsChange::Remove { path } => {
-                if let Some(file_id) = self.path_to_id.remove(&path) {
-                    self.id_to_path.remove(&file_id);
-                    self.files.remove(&file_id);
+                let Some(entry) = self.existing_entry(&path) else {
+                    return;
+                };
+                if entry.overlay.is_some() {
+                    entry.disk_conflict |= entry.disk.is_some();
+                    entry.disk = None;
+                } else {
+                    self.remove_file(&path);
+                }
+            }
+            VfsChange::ClearOverlay { path } => {
+                let Some(entry) = self.existing_entry(&path) else {
+                    return;
+                };
+                if entry.disk.is_some() {
+                    let changed = entry.is_dirty();
+                    entry.overlay = None;
+                    entry.edits = Arc::from([]);
+                    entry.disk_conflict = false;
+                    if changed {
+                        entry.version = entry.version.saturating_add(1);
+                    }
+                } else {
+                    self.remove_file(&path);
                 }
             }
         }
@@ -67,6 +169,19 @@ impl Vfs {
         }
     }
 
+    pub fn file_id(&self, path: &NormalizedPath) -> Option<FileId> {
+        self.path_to_id.get(path).copied()
+    }
+
+    /// The contents analysis sees for `path`, without taking a snapshot.
+    pub fn file_text(&self, path: &NormalizedPath) -> Option<&str> {
+        let file_id = self.path_to_id.get(path)?;
+        self.files
+            .get(file_id)
+            .and_then(FileEntry::text)
+            .map(AsRef::as_ref)
+    }
+
     pub fn snapshot(&self) -> VfsSnapshot {
         VfsSnapshot {
             path_to_id: self.path_to_id.clone(),
@@ -75,6 +190,34 @@ impl Vfs {
         }
     }
 
+    /// Returns the entry for `path` and whether it was just created with a fresh file id.
+    fn entry(&mut self, path: NormalizedPath) -> (bool, &mut FileEntry) {
+        let (created, file_id) = match self.path_to_id.get(&path) {
+            Some(file_id) => (false, *file_id),
+            None => (true, self.alloc_file_id(path)),
+        };
+        let entry = self.files.entry(file_id).or_insert(FileEntry {
+            disk: None,
+            overlay: None,
+            version: 0,
+            edits: Arc::from([]),
+            disk_conflict: false,
+        });
+        (created, entry)
+    }
+
+    fn existing_entry(&mut self, path: &NormalizedPath) -> Option<&mut FileEntry> {
+        let file_id = self.path_to_id.get(path)?;
+        self.files.get_mut(file_id)
+    }
+
+    fn remove_file(&mut self, path: &NormalizedPath) {
+        if let Some(file_id) = self.path_to_id.remove(path) {
+            self.id_to_path.remove(&file_id);
+            self.files.remove(&file_id);
+        }
+    }
+
     fn alloc_file_id(&mut self, path: NormalizedPath) -> FileId {
         let file_id = FileId::from_raw(self.next_file_id);
         self.next_file_id = self.next_file_id.saturating_add(1);
@@ -82,18 +225,6 @@ impl Vfs {
         self.id_to_path.insert(file_id, path);
         file_id
     }
-
-    fn upsert_file(&mut self, file_id: FileId, path: NormalizedPath, text: Arc<str>) {
-        let version = self
-            .files
-            .get(&file_id)
-            .map(|entry| entry.version.saturating_add(1))
-            .unwrap_or(0);
-
-        self.path_to_id.insert(path.clone(), file_id);
-        self.id_to_path.insert(file_id, path);
-        self.files.insert(file_id, FileEntry { text, version });
-    }
 }
 
 #[derive(Clone, Debug)]
@@ -118,21 +249,58 @@ impl VfsSnapshot {
         self.id_to_path.get(&file_id)
     }
 
+    /// The contents analysis sees: the overlay if there is one, otherwise the disk contents.
     pub fn file_text(&self, file_id: FileId) -> Option<&str> {
-        self.files.get(&file_id).map(|entry| entry.text.as_ref())
+        self.files
+            .get(&file_id)
+            .and_then(FileEntry::text)
+            .map(AsRef::as_ref)
+    }
+
+    pub fn disk_text(&self, file_id: FileId) -> Option<&str> {
+        self.files
+            .get(&file_id)
+            .and_then(|entry| entry.disk.as_deref())
     }
 
     pub fn file_version(&self, file_id: FileId) -> Option<u32> {
         self.files.get(&file_id).map(|entry| entry.version)
     }
+
+    /// The edits that turned the previous version of the file into this one, in order, for
+    /// reparsing incrementally. Empty when the contents were replaced wholesale.
+    pub fn last_edits(&self, file_id: FileId) -> &[OverlayEdit] {
+        self.files
+            .get(&file_id)
+            .map_or(&[], |entry| &entry.edits[..])
+    }
+
+    pub fn has_overlay(&self, file_id: FileId) -> bool {
+        self.files
+            .get(&file_id)
+            .is_some_and(|entry| entry.overlay.is_some())
+    }
+
+    /// Returns true if the overlay differs from the disk contents.
+    pub fn is_dirty(&self, file_id: FileId) -> bool {
+        self.files.get(&file_id).is_some_and(FileEntry::is_dirty)
+    }
+
+    /// Returns true if the file changed on disk while it had unsaved edits.
+    pub fn has_disk_conflict(&self, file_id: FileId) -> bool {
+        self.files
+            .get(&file_id)
+            .is_some_and(|entry| entry.disk_conflict)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use super::{Vfs, VfsChange};
+    use super::{OverlayEdit, Vfs, VfsChange};
     use sa_paths::NormalizedPath;
+    use sa_span::{TextRange, TextSize};
 
     fn path(value: &str) -> NormalizedPath {
         NormalizedPath::new(value)
@@ -261,4 +429,145 @@ mod tests {
         assert_eq!(snapshot.file_text(new_id), Some("contract F { uint x; }"));
         assert_eq!(snapshot.file_version(new_id), Some(0));
     }
+
+    #[test]
+    fn closing_an_overlay_falls_back_to_disk() {
+        let mut vfs = Vfs::default();
+        let path = path("/workspace/src/G.sol");
+
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract G {}"),
+        });
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract G { uint unsaved; }"),
+        });
+        let snapshot = vfs.snapshot();
+        let file_id = snapshot.file_id(&path).expect("file id");
+        assert_eq!(
+            snapshot.file_text(file_id),
+            Some("contract G { uint unsaved; }")
+        );
+        assert_eq!(snapshot.disk_text(file_id), Some("contract G {}"));
+        assert!(snapshot.has_overlay(file_id));
+        assert!(snapshot.is_dirty(file_id));
+        assert_eq!(snapshot.file_version(file_id), Some(1));
+
+        vfs.apply_change(VfsChange::ClearOverlay { path: path.clone() });
+        let snapshot = vfs.snapshot();
+        assert_eq!(snapshot.file_id(&path), Some(file_id));
+        assert_eq!(snapshot.file_text(file_id), Some("contract G {}"));
+        assert!(!snapshot.has_overlay(file_id));
+        assert!(!snapshot.is_dirty(file_id));
+        assert_eq!(snapshot.file_version(file_id), Some(2));
+    }
+
+    #[test]
+    fn overlay_without_disk_contents_is_removed_on_clear() {
+        let mut vfs = Vfs::default();
+        let path = path("/workspace/src/Untitled.sol");
+
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract Untitled {}"),
+        });
+        let snapshot = vfs.snapshot();
+        let file_id = snapshot.file_id(&path).expect("file id");
+        assert_eq!(snapshot.file_version(file_id), Some(0));
+        assert!(snapshot.is_dirty(file_id));
+
+        vfs.apply_change(VfsChange::ClearOverlay { path: path.clone() });
+        assert_eq!(vfs.snapshot().file_id(&path), None);
+    }
+
+    #[test]
+    fn disk_changes_under_a_dirty_overlay_are_conflicts() {
+        let mut vfs = Vfs::default();
+        let path = path("/workspace/src/H.sol");
+
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract H {}"),
+        });
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract H {}"),
+        });
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract H { uint fromDisk; }"),
+        });
+        let snapshot = vfs.snapshot();
+        let file_id = snapshot.file_id(&path).expect("file id");
+        assert!(
+            !snapshot.has_disk_conflict(file_id),
+            "a clean overlay is not a conflict"
+        );
+
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract H { uint edited; }"),
+        });
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract H { uint fromDiskAgain; }"),
+        });
+        let snapshot = vfs.snapshot();
+        assert!(snapshot.has_disk_conflict(file_id));
+        assert_eq!(
+            snapshot.file_text(file_id),
+            Some("contract H { uint edited; }")
+        );
+        let version = snapshot.file_version(file_id);
+
+        vfs.apply_change(VfsChange::Remove { path: path.clone() });
+        let snapshot = vfs.snapshot();
+        assert_eq!(snapshot.file_id(&path), Some(file_id));
+        assert_eq!(snapshot.disk_text(file_id), None);
+        assert_eq!(snapshot.file_version(file_id), version);
+
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract H { uint edited; }"),
+        });
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract H { uint edited; }"),
+        });
+        let snapshot = vfs.snapshot();
+        assert!(!snapshot.has_disk_conflict(file_id));
+        assert!(!snapshot.is_dirty(file_id));
+    }
+
+    #[test]
+    fn edited_overlays_keep_their_edits_until_replaced() {
+        let mut vfs = Vfs::default();
+        let path = path("/workspace/src/I.sol");
+
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract I {}"),
+        });
+        let edit = OverlayEdit {
+            range: TextRange::new(TextSize::from(12), TextSize::from(12)),
+            new_text: " uint x; ".to_string(),
+        };
+        vfs.apply_change(VfsChange::EditOverlay {
+            path: path.clone(),
+            text: Arc::from("contract I { uint x; }"),
+            edits: vec![edit.clone()],
+        });
+        let snapshot = vfs.snapshot();
+        let file_id = snapshot.file_id(&path).expect("file id");
+        assert_eq!(vfs.file_text(&path), Some("contract I { uint x; }"));
+        assert_eq!(snapshot.file_version(file_id), Some(1));
+        assert_eq!(snapshot.last_edits(file_id), &[edit]);
+
+        vfs.apply_change(VfsChange::SetOverlay {
+            path: path.clone(),
+            text: Arc::from("contract I {}"),
+        });
+        assert!(vfs.snapshot().last_edits(file_id).is_empty());
+    }
 }
@@ -0,0 +1,464 @@
+//! Watches workspace roots for changes made outside the editor (`git checkout`, `forge install`,
+//! code generators) and turns them into [`VfsChange`]s.
+//!
+//! Raw events are only recorded as "something happened at this path". Once no event has arrived
+//! for the debounce window, every recorded path is resolved against the disk: existing Solidity
+//! files become `Set`, missing ones `Remove`. This coalesces editor save dances and renames
+//! (the old name disappears, the new one appears) without tracking event order.
+//!
+//! Build output, the compiler cache and VCS state churn constantly and never hold sources the
+//! VFS tracks, so events below the directories a root is watched with are dropped.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, channel};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use sa_paths::NormalizedPath;
+
+use crate::{DecodedText, VfsChange, VfsSnapshot, read_text};
+
+const VCS_DIR: &str = ".git";
+
+pub struct VfsWatcher {
+    watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+    /// Watched roots and the directories below them whose events are dropped.
+    roots: HashMap<PathBuf, Vec<PathBuf>>,
+    pending: BTreeSet<PathBuf>,
+    last_event: Option<Instant>,
+    debounce: Duration,
+}
+
+impl VfsWatcher {
+    pub fn new(debounce: Duration) -> notify::Result<Self> {
+        let (sender, receiver) = channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        Ok(Self {
+            watcher,
+            receiver,
+            roots: HashMap::new(),
+            pending: BTreeSet::new(),
+            last_event: None,
+            debounce,
+        })
+    }
+
+    /// Starts watching `root` recursively, dropping events below `ignored` (such as the build
+    /// output and compiler cache of the project) and below its `.git`. Watching a root again
+    /// only replaces its ignored directories.
+    pub fn watch(
+        &mut self,
+        root: &Path,
+        ignored: impl IntoIterator<Item = PathBuf>,
+    ) -> notify::Result<()> {
+        let ignored = ignored
+            .into_iter()
+            .chain([root.join(VCS_DIR)])
+            .collect::<Vec<_>>();
+        if let Some(existing) = self.roots.get_mut(root) {
+            *existing = ignored;
+            return Ok(());
+        }
+        self.watcher.watch(root, RecursiveMode::Recursive)?;
+        self.roots.insert(root.to_path_buf(), ignored);
+        Ok(())
+    }
+
+    /// Stops watching `root` and drops the changes recorded below it that no other watched root
+    /// covers.
+    pub fn unwatch(&mut self, root: &Path) -> notify::Result<()> {
+        if self.roots.remove(root).is_none() {
+            return Ok(());
+        }
+        self.pending.retain(|path| {
+            !path.starts_with(root) || self.roots.keys().any(|other| path.starts_with(other))
+        });
+        self.watcher.unwatch(root)
+    }
+
+    pub fn is_watching(&self, root: &Path) -> bool {
+        self.roots.contains_key(root)
+    }
+
+    /// Returns the changes that settled since the last call, or nothing while events are still
+    /// arriving. `vfs` is used to skip files whose contents did not change and to expand removed
+    /// directories into the files they contained.
+    pub fn drain(&mut self, vfs: &VfsSnapshot) -> Vec<VfsChange> {
+        self.take_settled()
+            .map(|paths| resolve_changes(paths, vfs))
+            .unwrap_or_default()
+    }
+
+    /// Like [`VfsWatcher::drain`], but returns the settled paths unresolved. Resolving them reads
+    /// the disk, which async callers can then do on a blocking thread.
+    pub fn take_settled(&mut self) -> Option<BTreeSet<PathBuf>> {
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                Ok(event) => self.record(event),
+                Err(error) => self.record_error(error),
+            }
+        }
+        let settled = self
+            .last_event
+            .is_some_and(|last| last.elapsed() >= self.debounce);
+        if self.pending.is_empty() || !settled {
+            return None;
+        }
+        self.last_event = None;
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    fn record(&mut self, event: Event) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        let paths = event
+            .paths
+            .into_iter()
+            .filter(|path| !self.is_ignored(path))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+        self.pending.extend(paths);
+        self.last_event = Some(Instant::now());
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        self.roots
+            .values()
+            .flatten()
+            .any(|ignored| path.starts_with(ignored))
+    }
+
+    /// A dropped or failed event may hide changes anywhere below its paths; rescanning those
+    /// paths (or every root if none are known) keeps the VFS honest.
+    fn record_error(&mut self, error: notify::Error) {
+        if error.paths.is_empty() {
+            self.pending.extend(self.roots.keys().cloned());
+        } else {
+            self.pending.extend(error.paths);
+        }
+        self.last_event = Some(Instant::now());
+    }
+}
+
+/// Resolves paths something happened at against the disk: Solidity files that exist become
+/// `Set` (unless `vfs` already has their contents), directories expand into the Solidity files
+/// below them, and missing paths remove every known file at or below them.
+pub fn resolve_changes(
+    paths: impl IntoIterator<Item = PathBuf>,
+    vfs: &VfsSnapshot,
+) -> Vec<VfsChange> {
+    let paths = paths.into_iter().collect::<BTreeSet<_>>();
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut files = Vec::new();
+            collect_sol_files(&path, &mut files);
+            for file in files {
+                push_set(&file, vfs, &mut seen, &mut changes);
+            }
+        } else if path.is_file() {
+            if is_sol_file(&path) {
+                push_set(&path, vfs, &mut seen, &mut changes);
+            }
+        } else {
+            let removed = NormalizedPath::new(path.to_string_lossy());
+            let prefix = format!("{}/", removed.as_str());
+            let mut gone = vfs
+                .iter()
+                .filter(|(_, known)| *known == &removed || known.as_str().starts_with(&prefix))
+                .map(|(_, known)| known.clone())
+                .collect::<Vec<_>>();
+            gone.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            for path in gone {
+                if seen.insert(path.clone()) {
+                    changes.push(VfsChange::Remove { path });
+                }
+            }
+        }
+    }
+    changes
+}
+
+fn push_set(
+    file: &Path,
+    vfs: &VfsSnapshot,
+    seen: &mut HashSet<NormalizedPath>,
+    changes: &mut Vec<VfsChange>,
+) {
+    let path = NormalizedPath::new(file.to_string_lossy());
+    if !seen.insert(path.clone()) {
+        return;
+    }
+    let Ok(DecodedText { text, .. }) = read_text(file) else {
+        return;
+    };
+    let unchanged = vfs
+        .file_id(&path)
+        .and_then(|file_id| vfs.disk_text(file_id))
+        .is_some_and(|known| known == text);
+    if !unchanged {
+        changes.push(VfsChange::Set {
+            path,
+            text: Arc::from(text),
+        });
+    }
+}
+
+fn is_sol_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "sol")
+}
+
+fn collect_sol_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_sol_files(&path, files);
+        } else if is_sol_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
+    use notify::{Event, EventKind};
+    use sa_paths::NormalizedPath;
+    use tempfile::tempdir;
+
+    use super::VfsWatcher;
+    use crate::{Vfs, VfsChange};
+
+    fn summary(changes: &[VfsChange]) -> Vec<String> {
+        changes
+            .iter()
+            .map(|change| match change {
+                VfsChange::Set { path, text } => format!("set {} {text}", path.as_str()),
+                VfsChange::Remove { path } => format!("remove {}", path.as_str()),
+                VfsChange::SetOverlay { path, text } => format!("overlay {} {text}", path.as_str()),
+                VfsChange::EditOverlay { path, text, .. } => {
+                    format!("edit {} {text}", path.as_str())
+                }
+                VfsChange::ClearOverlay { path } => format!("clear {}", path.as_str()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn events_below_ignored_directories_are_dropped() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let files = [
+            "build/Main.sol/Main.json",
+            "cache/solidity-files-cache.json",
+            ".git/index",
+            "node_modules/pkg/Pkg.sol",
+            "out/Out.sol",
+        ]
+        .map(|file| root.join(file));
+        for file in &files {
+            fs::create_dir_all(file.parent().expect("parent")).expect("create dirs");
+            fs::write(file, "contract C {}").expect("write file");
+        }
+
+        let mut watcher = VfsWatcher::new(Duration::ZERO).expect("watcher");
+        watcher
+            .watch(&root, [root.join("build"), root.join("cache")])
+            .expect("watch root");
+        for file in &files {
+            watcher.record(Event::new(EventKind::Modify(ModifyKind::Any)).add_path(file.clone()));
+        }
+        let changes = watcher.drain(&Vfs::default().snapshot());
+
+        assert_eq!(
+            summary(&changes),
+            files[3..]
+                .iter()
+                .map(|file| format!("set {} contract C {{}}", file.to_string_lossy()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rename_becomes_remove_and_set() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let old = root.join("Old.sol");
+        let new = root.join("New.sol");
+        fs::write(&new, "contract A {}").expect("write renamed file");
+
+        let mut vfs = Vfs::default();
+        vfs.apply_change(VfsChange::Set {
+            path: NormalizedPath::new(old.to_string_lossy()),
+            text: Arc::from("contract A {}"),
+        });
+
+        let mut watcher = VfsWatcher::new(Duration::ZERO).expect("watcher");
+        watcher.record(
+            Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                .add_path(old.clone())
+                .add_path(new.clone()),
+        );
+        let changes = watcher.drain(&vfs.snapshot());
+
+        assert_eq!(
+            summary(&changes),
+            vec![
+                format!("set {} contract A {{}}", new.to_string_lossy()),
+                format!("remove {}", old.to_string_lossy()),
+            ]
+        );
+        assert!(watcher.drain(&vfs.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn coalesces_events_and_skips_unchanged_files() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let same = root.join("Same.sol");
+        let edited = root.join("Edited.sol");
+        let notes = root.join("notes.md");
+        fs::write(&same, "contract Same {}").expect("write same");
+        fs::write(&edited, "contract Edited { uint x; }").expect("write edited");
+        fs::write(&notes, "# notes").expect("write notes");
+
+        let mut vfs = Vfs::default();
+        for (path, text) in [(&same, "contract Same {}"), (&edited, "contract Edited {}")] {
+            vfs.apply_change(VfsChange::Set {
+                path: NormalizedPath::new(path.to_string_lossy()),
+                text: Arc::from(text),
+            });
+        }
+
+        let mut watcher = VfsWatcher::new(Duration::ZERO).expect("watcher");
+        for kind in [
+            EventKind::Create(CreateKind::File),
+            EventKind::Modify(ModifyKind::Any),
+            EventKind::Modify(ModifyKind::Any),
+        ] {
+            watcher.record(
+                Event::new(kind)
+                    .add_path(same.clone())
+                    .add_path(edited.clone())
+                    .add_path(notes.clone()),
+            );
+        }
+        let changes = watcher.drain(&vfs.snapshot());
+
+        assert_eq!(
+            summary(&changes),
+            vec![format!(
+                "set {} contract Edited {{ uint x; }}",
+                edited.to_string_lossy()
+            )]
+        );
+    }
+
+    #[test]
+    fn removed_directory_removes_known_files_below_it() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let dep = root.join("lib/dep");
+
+        let mut vfs = Vfs::default();
+        vfs.apply_change(VfsChange::Set {
+            path: NormalizedPath::new(dep.join("src/Dep.sol").to_string_lossy()),
+            text: Arc::from("contract Dep {}"),
+        });
+        vfs.apply_change(VfsChange::Set {
+            path: NormalizedPath::new(root.join("lib/dependent/Other.sol").to_string_lossy()),
+            text: Arc::from("contract Other {}"),
+        });
+
+        let mut watcher = VfsWatcher::new(Duration::ZERO).expect("watcher");
+        watcher.record(Event::new(EventKind::Remove(RemoveKind::Folder)).add_path(dep.clone()));
+        let changes = watcher.drain(&vfs.snapshot());
+
+        assert_eq!(
+            summary(&changes),
+            vec![format!(
+                "remove {}",
+                dep.join("src/Dep.sol").to_string_lossy()
+            )]
+        );
+    }
+
+    #[test]
+    fn waits_for_the_debounce_window() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let file = root.join("A.sol");
+        fs::write(&file, "contract A {}").expect("write file");
+
+        let vfs = Vfs::default();
+        let mut watcher = VfsWatcher::new(Duration::from_secs(3600)).expect("watcher");
+        watcher.record(Event::new(EventKind::Create(CreateKind::File)).add_path(file));
+
+        assert!(watcher.drain(&vfs.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn reports_changes_made_on_disk() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let vfs = Vfs::default();
+        let mut watcher = VfsWatcher::new(Duration::from_millis(10)).expect("watcher");
+        watcher.watch(&root, []).expect("watch root");
+        assert!(watcher.is_watching(&root));
+
+        let file = root.join("Created.sol");
+        fs::write(&file, "contract Created {}").expect("write file");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut changes = Vec::new();
+        while changes.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+            changes = watcher.drain(&vfs.snapshot());
+        }
+
+        assert_eq!(
+            summary(&changes),
+            vec![format!(
+                "set {} contract Created {{}}",
+                file.to_string_lossy()
+            )]
+        );
+    }
+
+    #[test]
+    fn unwatched_roots_drop_their_pending_changes() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let vfs = Vfs::default();
+        let file = root.join("A.sol");
+        fs::write(&file, "contract A {}").expect("write file");
+        let mut watcher = VfsWatcher::new(Duration::ZERO).expect("watcher");
+        watcher.watch(&root, []).expect("watch root");
+        watcher.record(Event::new(EventKind::Create(CreateKind::File)).add_path(file));
+
+        watcher.unwatch(&root).expect("unwatch root");
+        assert!(!watcher.is_watching(&root));
+        assert!(watcher.drain(&vfs.snapshot()).is_empty());
+    }
+}
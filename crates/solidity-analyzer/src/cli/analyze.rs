@@ -0,0 +1,228 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use sa_ide_diagnostics::{DiagnosticSeverity, collect_solar_lints};
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
+use serde::Serialize;
+use serde_json::json;
+use tower_lsp::lsp_types::Range;
+
+use super::{load, relative_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyzeFormat {
+    Text,
+    Json,
+    /// SARIF 2.1.0, as GitHub code scanning ingests it.
+    Sarif,
+}
+
+impl AnalyzeFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "sarif" => Some(Self::Sarif),
+            _ => None,
+        }
+    }
+}
+
+/// The findings of [`analyze`], sorted by file and position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzeReport {
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Relative to the project root, with forward slashes.
+    pub path: String,
+    /// Zero-based, with UTF-16 columns like LSP ranges.
+    pub range: Range,
+    pub severity: &'static str,
+    pub code: Option<String>,
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// Parses, type-checks and lints the Solidity sources of the project containing `path`.
+/// Sources of installed dependencies are left out; when `path` is a file or a directory
+/// inside the project, only the sources below it are reported.
+pub fn analyze(path: &Path) -> anyhow::Result<AnalyzeReport> {
+    let (state, path) = load(path)?;
+    let config = state
+        .config
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("no project found at {path}"))?;
+    let mut files = state
+        .indexed_files
+        .iter()
+        .filter(|file| Path::new(file.as_str()).starts_with(path.as_str()))
+        .filter(|file| !state.is_library_file(file))
+        .map(|file| file.as_str())
+        .collect::<BTreeSet<_>>();
+    if path.as_str().ends_with(".sol") {
+        files.insert(path.as_str());
+    }
+    let paths = files.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+    let diagnostics = if paths.is_empty() {
+        Vec::new()
+    } else {
+        collect_solar_lints(&config, &paths)?
+    };
+
+    let vfs = state.vfs.snapshot();
+    let analysis = state.analysis_host.snapshot();
+    let mut findings = diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let range = vfs
+                .file_id(&diagnostic.file_path)
+                .map(|file_id| {
+                    let line_index = analysis.file_line_index(file_id);
+                    to_lsp_range_in(diagnostic.range, &line_index, PositionEncoding::Utf16)
+                })
+                .unwrap_or_default();
+            Finding {
+                path: relative_path(&state, &diagnostic.file_path),
+                range,
+                severity: match diagnostic.severity {
+                    DiagnosticSeverity::Error => "error",
+                    DiagnosticSeverity::Warning => "warning",
+                    DiagnosticSeverity::Info => "info",
+                },
+                code: diagnostic.code,
+                source: diagnostic.source.as_str(),
+                message: diagnostic.message,
+            }
+        })
+        .collect::<Vec<_>>();
+    findings.sort_by(|a, b| {
+        (&a.path, a.range.start.line, a.range.start.character).cmp(&(
+            &b.path,
+            b.range.start.line,
+            b.range.start.character,
+        ))
+    });
+    findings.dedup();
+    Ok(AnalyzeReport { findings })
+}
+
+impl AnalyzeReport {
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == "error")
+    }
+
+    pub fn render(&self, format: AnalyzeFormat) -> String {
+        match format {
+            AnalyzeFormat::Text => self.render_text(),
+            AnalyzeFormat::Json => {
+                serde_json::to_string_pretty(&self.findings).expect("findings serialize to JSON")
+            }
+            AnalyzeFormat::Sarif => {
+                serde_json::to_string_pretty(&self.sarif()).expect("SARIF log serializes to JSON")
+            }
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        for finding in &self.findings {
+            let code = finding
+                .code
+                .as_ref()
+                .map(|code| format!("[{code}]"))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "{}:{}:{}: {}{code}: {} ({})",
+                finding.path,
+                finding.range.start.line + 1,
+                finding.range.start.character + 1,
+                finding.severity,
+                finding.message,
+                finding.source
+            );
+        }
+        let count = |severity: &str| {
+            self.findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+        let _ = write!(
+            out,
+            "{} error(s), {} warning(s), {} note(s)",
+            count("error"),
+            count("warning"),
+            count("info")
+        );
+        out
+    }
+
+    fn sarif(&self) -> serde_json::Value {
+        let rule_id = |finding: &Finding| {
+            finding
+                .code
+                .clone()
+                .unwrap_or_else(|| finding.source.to_string())
+        };
+        let rules = self
+            .findings
+            .iter()
+            .map(rule_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|id| json!({ "id": id }))
+            .collect::<Vec<_>>();
+        let results = self
+            .findings
+            .iter()
+            .map(|finding| {
+                let level = match finding.severity {
+                    "error" => "error",
+                    "warning" => "warning",
+                    _ => "note",
+                };
+                json!({
+                    "ruleId": rule_id(finding),
+                    "level": level,
+                    "message": { "text": finding.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": {
+                                "uri": finding.path,
+                                "uriBaseId": "%SRCROOT%",
+                            },
+                            "region": {
+                                "startLine": finding.range.start.line + 1,
+                                "startColumn": finding.range.start.character + 1,
+                                "endLine": finding.range.end.line + 1,
+                                "endColumn": finding.range.end.character + 1,
+                            },
+                        },
+                    }],
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "solidity-analyzer",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+}
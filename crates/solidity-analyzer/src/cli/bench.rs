@@ -0,0 +1,173 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sa_span::TextSize;
+use sa_vfs::VfsChange;
+use serde::Serialize;
+
+use super::{load, relative_path};
+use crate::document;
+
+/// Completion is measured at up to this many positions, spread evenly over the project.
+const COMPLETION_SAMPLES: usize = 50;
+
+/// Timings and memory of one run of [`bench`]. Durations are in milliseconds so reports from
+/// different releases can be diffed directly.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchReport {
+    pub projects: usize,
+    pub files: usize,
+    /// Discovering the project, reading its files and building the analysis database.
+    pub index_ms: f64,
+    /// Building the semantic snapshot of every project from scratch.
+    pub sema_ms: f64,
+    pub completion: LatencyStats,
+    /// Rebuilding the semantic snapshot after appending a comment to one file.
+    pub edit_rebuild_ms: Option<f64>,
+    pub memory_bytes: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let at = |quantile: f64| {
+            let index = ((samples.len() - 1) as f64 * quantile).round() as usize;
+            millis(samples[index])
+        };
+        Self {
+            samples: samples.len(),
+            min_ms: at(0.0),
+            median_ms: at(0.5),
+            p95_ms: at(0.95),
+            max_ms: at(1.0),
+        }
+    }
+}
+
+/// Loads the project at `path` the way the server does and measures the work an editor session
+/// starts with: indexing, semantic analysis, completion and re-analysis after an edit.
+pub fn bench(path: &Path) -> anyhow::Result<BenchReport> {
+    let start = Instant::now();
+    let (mut state, path) = load(path)?;
+    let index = start.elapsed();
+    if state.config.is_none() {
+        anyhow::bail!("no project found at {path}");
+    }
+
+    let analysis = state.analysis_host.snapshot();
+    let projects = analysis.project_ids();
+    let start = Instant::now();
+    for project_id in &projects {
+        analysis.prime_sema(*project_id);
+    }
+    let sema = start.elapsed();
+
+    // Positions right after a `.`, where editors ask for member completions.
+    let vfs = state.vfs.snapshot();
+    let mut files = state
+        .indexed_files
+        .iter()
+        .filter(|file| !state.is_library_file(file))
+        .filter_map(|file| Some((relative_path(&state, file), vfs.file_id(file)?)))
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let positions = files
+        .iter()
+        .flat_map(|(_, file_id)| {
+            let text = analysis.file_text(*file_id);
+            text.match_indices('.')
+                .map(|(offset, _)| (*file_id, TextSize::from(offset as u32 + 1)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let step = positions.len().div_ceil(COMPLETION_SAMPLES).max(1);
+    let completion = positions
+        .iter()
+        .step_by(step)
+        .map(|&(file_id, offset)| {
+            let start = Instant::now();
+            analysis.completions(file_id, offset);
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    let memory_bytes = analysis.memory_usage(&vfs).total_bytes();
+    drop(analysis);
+
+    let edited = files
+        .first()
+        .and_then(|&(_, file_id)| Some((vfs.path(file_id)?.clone(), file_id)));
+    let edit_rebuild = match edited {
+        Some((file_path, file_id)) => {
+            let text = format!("{}\n// bench\n", vfs.file_text(file_id).unwrap_or(""));
+            state.vfs.apply_change(VfsChange::SetOverlay {
+                path: file_path,
+                text: Arc::from(text),
+            });
+            let snapshot = state.vfs.snapshot();
+            document::apply_snapshot(&mut state, snapshot);
+            let analysis = state.analysis_host.snapshot();
+            let start = Instant::now();
+            for project_id in &projects {
+                analysis.prime_sema(*project_id);
+            }
+            Some(start.elapsed())
+        }
+        None => None,
+    };
+
+    Ok(BenchReport {
+        projects: projects.len(),
+        files: state.indexed_files.len(),
+        index_ms: millis(index),
+        sema_ms: millis(sema),
+        completion: LatencyStats::new(completion),
+        edit_rebuild_ms: edit_rebuild.map(millis),
+        memory_bytes,
+    })
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "projects          {}", self.projects)?;
+        writeln!(f, "files             {}", self.files)?;
+        writeln!(f, "index             {:.1} ms", self.index_ms)?;
+        writeln!(f, "sema              {:.1} ms", self.sema_ms)?;
+        writeln!(
+            f,
+            "completion        {} samples, median {:.2} ms, p95 {:.2} ms, max {:.2} ms",
+            self.completion.samples,
+            self.completion.median_ms,
+            self.completion.p95_ms,
+            self.completion.max_ms
+        )?;
+        match self.edit_rebuild_ms {
+            Some(ms) => writeln!(f, "edit rebuild      {ms:.1} ms")?,
+            None => writeln!(f, "edit rebuild      -")?,
+        }
+        write!(
+            f,
+            "memory            {:.1} MiB",
+            self.memory_bytes as f64 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
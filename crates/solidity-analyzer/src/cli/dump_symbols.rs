@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use sa_def::DefKind;
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
+use serde::Serialize;
+use tower_lsp::lsp_types::Range;
+
+use super::{load, relative_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolsFormat {
+    /// One JSON array.
+    Json,
+    /// One JSON object per line, for streaming into other tools.
+    Ndjson,
+}
+
+impl SymbolsFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "json" => Some(Self::Json),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedSymbol {
+    pub kind: &'static str,
+    pub name: String,
+    pub container: Option<String>,
+    /// Relative to the project root, with forward slashes.
+    pub file: String,
+    /// The range of the name; zero-based, with UTF-16 columns like LSP ranges.
+    pub range: Range,
+    pub signature: Option<String>,
+    pub selector: Option<String>,
+}
+
+/// Exports every definition of the project containing `path`, sorted by file and position.
+pub fn dump_symbols(path: &Path) -> anyhow::Result<Vec<ExportedSymbol>> {
+    let (state, _) = load(path)?;
+    let analysis = state.analysis_host.snapshot();
+    let mut symbols = analysis
+        .symbol_index()
+        .into_iter()
+        .map(|symbol| {
+            let line_index = analysis.file_line_index(symbol.file_id);
+            ExportedSymbol {
+                kind: def_kind_name(symbol.kind),
+                name: symbol.name,
+                container: symbol.container,
+                file: relative_path(&state, &analysis.file_path(symbol.file_id)),
+                range: to_lsp_range_in(symbol.range, &line_index, PositionEncoding::Utf16),
+                signature: symbol.signature,
+                selector: symbol.selector,
+            }
+        })
+        .collect::<Vec<_>>();
+    symbols.sort_by(|a, b| {
+        (&a.file, a.range.start.line, a.range.start.character).cmp(&(
+            &b.file,
+            b.range.start.line,
+            b.range.start.character,
+        ))
+    });
+    Ok(symbols)
+}
+
+pub fn render_symbols(symbols: &[ExportedSymbol], format: SymbolsFormat) -> String {
+    match format {
+        SymbolsFormat::Json => {
+            serde_json::to_string_pretty(symbols).expect("symbols serialize to JSON")
+        }
+        SymbolsFormat::Ndjson => symbols
+            .iter()
+            .map(|symbol| serde_json::to_string(symbol).expect("symbol serializes to JSON"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn def_kind_name(kind: DefKind) -> &'static str {
+    match kind {
+        DefKind::Contract => "contract",
+        DefKind::Function => "function",
+        DefKind::Struct => "struct",
+        DefKind::Enum => "enum",
+        DefKind::Event => "event",
+        DefKind::Error => "error",
+        DefKind::Modifier => "modifier",
+        DefKind::Variable => "variable",
+        DefKind::Udvt => "udvt",
+        DefKind::Field => "field",
+        DefKind::Variant => "variant",
+        DefKind::YulFunction => "yulFunction",
+    }
+}
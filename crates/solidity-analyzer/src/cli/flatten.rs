@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use super::load;
+
+/// Inlines `file` and every file it imports into one source file, resolving imports with the
+/// project's remappings exactly as the IDE does.
+pub fn flatten(file: &Path) -> anyhow::Result<String> {
+    let (state, path) = load(file)?;
+    let file_id = state
+        .vfs
+        .snapshot()
+        .file_id(&path)
+        .ok_or_else(|| anyhow::anyhow!("{path} is not part of the workspace"))?;
+    state
+        .analysis_host
+        .snapshot()
+        .flatten_file(file_id)
+        .ok_or_else(|| anyhow::anyhow!("{path} does not belong to a project"))
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use sa_ide::ContractKind;
+use serde_json::json;
+
+use super::{load, relative_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphKind {
+    /// Files, with an edge from each file to the files it imports.
+    Imports,
+    /// Contracts, with an edge from each contract to its direct bases.
+    Inheritance,
+}
+
+impl GraphKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "imports" => Some(Self::Imports),
+            "inheritance" => Some(Self::Inheritance),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dot" => Some(Self::Dot),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A graph node; `id` is unique within the graph and the target of [`GraphEdge`]s.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct GraphNode {
+    id: String,
+    label: String,
+    /// `contract`, `abstract contract`, `interface` or `library`; only set for contracts.
+    kind: Option<&'static str>,
+    /// The declaring file, relative to the project root; only set for contracts.
+    file: Option<String>,
+}
+
+/// Renders the import or inheritance graph of the project containing `path`. Nested projects
+/// are merged into one graph, with files and contracts they share listed once.
+pub fn graph(kind: GraphKind, path: &Path, format: GraphFormat) -> anyhow::Result<String> {
+    let (state, _) = load(path)?;
+    let analysis = state.analysis_host.snapshot();
+    let mut nodes = BTreeMap::new();
+    let mut edges = BTreeSet::new();
+    for project_id in analysis.project_ids() {
+        match kind {
+            GraphKind::Imports => {
+                let graph = analysis.import_graph(project_id);
+                let path = |file_id| relative_path(&state, &analysis.file_path(file_id));
+                for file_id in graph.files() {
+                    let file = path(file_id);
+                    nodes.entry(file.clone()).or_insert_with(|| GraphNode {
+                        id: file.clone(),
+                        label: file,
+                        kind: None,
+                        file: None,
+                    });
+                }
+                for (from, to) in graph.edges() {
+                    edges.insert((path(from), path(to)));
+                }
+            }
+            GraphKind::Inheritance => {
+                let graph = analysis.project_contract_graph(project_id);
+                let ids = graph
+                    .nodes
+                    .iter()
+                    .map(|node| {
+                        let file = relative_path(&state, &analysis.file_path(node.file_id));
+                        let id = format!("{file}:{}", node.name);
+                        let kind = match node.kind {
+                            ContractKind::Contract if node.is_abstract => "abstract contract",
+                            ContractKind::Contract => "contract",
+                            ContractKind::Interface => "interface",
+                            ContractKind::Library => "library",
+                        };
+                        nodes.entry(id.clone()).or_insert_with(|| GraphNode {
+                            id: id.clone(),
+                            label: node.name.clone(),
+                            kind: Some(kind),
+                            file: Some(file),
+                        });
+                        id
+                    })
+                    .collect::<Vec<_>>();
+                for edge in &graph.edges {
+                    edges.insert((ids[edge.derived].clone(), ids[edge.base].clone()));
+                }
+            }
+        }
+    }
+
+    let name = match kind {
+        GraphKind::Imports => "imports",
+        GraphKind::Inheritance => "inheritance",
+    };
+    Ok(match format {
+        GraphFormat::Dot => render_dot(name, nodes.values(), &edges),
+        GraphFormat::Json => {
+            let nodes = nodes
+                .values()
+                .map(|node| match (&node.kind, &node.file) {
+                    (Some(kind), Some(file)) => json!({
+                        "id": node.id,
+                        "name": node.label,
+                        "kind": kind,
+                        "file": file,
+                    }),
+                    _ => json!({ "id": node.id }),
+                })
+                .collect::<Vec<_>>();
+            let edges = edges
+                .iter()
+                .map(|(from, to)| json!({ "from": from, "to": to }))
+                .collect::<Vec<_>>();
+            serde_json::to_string_pretty(&json!({ "nodes": nodes, "edges": edges }))
+                .expect("graph serializes to JSON")
+        }
+    })
+}
+
+fn render_dot<'a>(
+    name: &str,
+    nodes: impl Iterator<Item = &'a GraphNode>,
+    edges: &BTreeSet<(String, String)>,
+) -> String {
+    let mut out = format!("digraph {name} {{\n");
+    for node in nodes {
+        let shape = match node.kind {
+            Some("interface") => ", shape=ellipse",
+            Some("library") => ", shape=component",
+            Some(_) => ", shape=box",
+            None => "",
+        };
+        let style = match node.kind {
+            Some("abstract contract") => ", style=dashed",
+            _ => "",
+        };
+        let _ = writeln!(
+            out,
+            "    {} [label={}{shape}{style}];",
+            quote(&node.id),
+            quote(&node.label)
+        );
+    }
+    for (from, to) in edges {
+        let _ = writeln!(out, "    {} -> {};", quote(from), quote(to));
+    }
+    out.push('}');
+    out
+}
+
+/// A DOT quoted string.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
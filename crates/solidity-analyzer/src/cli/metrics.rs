@@ -0,0 +1,196 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use sa_ide::{ContractKind, ContractMetrics};
+use serde::Serialize;
+
+use super::{load, relative_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsFormat {
+    Table,
+    Json,
+}
+
+impl MetricsFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "table" => Some(Self::Table),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractReport {
+    pub name: String,
+    /// `contract`, `abstract contract`, `interface` or `library`.
+    pub kind: &'static str,
+    /// Relative to the project root, with forward slashes.
+    pub file: String,
+    /// One-based.
+    pub line: u32,
+    pub lines: usize,
+    pub inheritance_depth: usize,
+    pub external_calls: usize,
+    pub cyclomatic_complexity: usize,
+    pub functions: Vec<FunctionReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionReport {
+    pub name: String,
+    pub visibility: &'static str,
+    /// One-based.
+    pub line: u32,
+    pub lines: usize,
+    pub external_calls: usize,
+    pub cyclomatic_complexity: usize,
+}
+
+/// Collects the metrics of the contracts in the project containing `path`, sorted by file and
+/// position. Contracts of installed dependencies are left out; when `path` is a file or a
+/// directory inside the project, only the contracts below it are reported.
+pub fn metrics(path: &Path) -> anyhow::Result<Vec<ContractReport>> {
+    let (state, path) = load(path)?;
+    let analysis = state.analysis_host.snapshot();
+    let mut reports = Vec::new();
+    for contract in analysis.metrics() {
+        let file_path = analysis.file_path(contract.file_id);
+        if !Path::new(file_path.as_str()).starts_with(path.as_str())
+            || state.is_library_file(&file_path)
+        {
+            continue;
+        }
+        let line_index = analysis.file_line_index(contract.file_id);
+        let line = |offset| line_index.line_col(offset).line + 1;
+        reports.push(ContractReport {
+            kind: contract_kind_name(&contract),
+            file: relative_path(&state, &file_path),
+            line: line(contract.range.start()),
+            lines: contract.lines,
+            inheritance_depth: contract.inheritance_depth,
+            external_calls: contract.external_calls(),
+            cyclomatic_complexity: contract.cyclomatic_complexity(),
+            functions: contract
+                .functions
+                .iter()
+                .map(|function| FunctionReport {
+                    name: function.name.clone(),
+                    visibility: function.visibility,
+                    line: line(function.range.start()),
+                    lines: function.lines,
+                    external_calls: function.external_calls,
+                    cyclomatic_complexity: function.cyclomatic_complexity,
+                })
+                .collect(),
+            name: contract.name,
+        });
+    }
+    reports.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(reports)
+}
+
+pub fn render_metrics(reports: &[ContractReport], format: MetricsFormat) -> String {
+    match format {
+        MetricsFormat::Table => render_table(reports),
+        MetricsFormat::Json => {
+            serde_json::to_string_pretty(reports).expect("metrics serialize to JSON")
+        }
+    }
+}
+
+/// A contract table followed by a function table, with aligned columns.
+fn render_table(reports: &[ContractReport]) -> String {
+    let count = |report: &ContractReport, visibility: &str| {
+        report
+            .functions
+            .iter()
+            .filter(|function| function.visibility == visibility)
+            .count()
+            .to_string()
+    };
+    let mut contracts = vec![
+        [
+            "contract",
+            "kind",
+            "lines",
+            "external",
+            "public",
+            "internal",
+            "private",
+            "calls",
+            "complexity",
+            "depth",
+        ]
+        .map(String::from)
+        .to_vec(),
+    ];
+    let mut functions = vec![
+        ["function", "visibility", "lines", "calls", "complexity"]
+            .map(String::from)
+            .to_vec(),
+    ];
+    for report in reports {
+        contracts.push(vec![
+            format!("{}:{}", report.file, report.name),
+            report.kind.to_string(),
+            report.lines.to_string(),
+            count(report, "external"),
+            count(report, "public"),
+            count(report, "internal"),
+            count(report, "private"),
+            report.external_calls.to_string(),
+            report.cyclomatic_complexity.to_string(),
+            report.inheritance_depth.to_string(),
+        ]);
+        for function in &report.functions {
+            functions.push(vec![
+                format!("{}.{}", report.name, function.name),
+                function.visibility.to_string(),
+                function.lines.to_string(),
+                function.external_calls.to_string(),
+                function.cyclomatic_complexity.to_string(),
+            ]);
+        }
+    }
+    let mut out = String::new();
+    write_table(&mut out, &contracts);
+    out.push('\n');
+    write_table(&mut out, &functions);
+    out.trim_end().to_string()
+}
+
+/// Left-aligns the first two columns and right-aligns the numbers after them.
+fn write_table(out: &mut String, rows: &[Vec<String>]) {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths = (0..columns)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    for row in rows {
+        let mut line = String::new();
+        for (column, (cell, width)) in row.iter().zip(widths.iter().copied()).enumerate() {
+            if column > 0 {
+                line.push_str("  ");
+            }
+            if column < 2 {
+                let _ = write!(line, "{cell:<width$}");
+            } else {
+                let _ = write!(line, "{cell:>width$}");
+            }
+        }
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+}
+
+fn contract_kind_name(contract: &ContractMetrics) -> &'static str {
+    match contract.kind {
+        ContractKind::Contract if contract.is_abstract => "abstract contract",
+        ContractKind::Contract => "contract",
+        ContractKind::Interface => "interface",
+        ContractKind::Library => "library",
+    }
+}
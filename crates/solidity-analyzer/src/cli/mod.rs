@@ -0,0 +1,57 @@
+//! Commands run from the command line, without an editor. Each loads the project the way the
+//! server does on startup, so results match what the IDE shows.
+
+use std::path::Path;
+
+use sa_paths::NormalizedPath;
+
+use crate::document;
+use crate::state::ServerState;
+use crate::workspace;
+
+mod analyze;
+mod bench;
+mod dump_symbols;
+mod flatten;
+mod graph;
+mod metrics;
+mod query;
+mod standard_json;
+
+pub use analyze::{AnalyzeFormat, AnalyzeReport, Finding, analyze};
+pub use bench::{BenchReport, LatencyStats, bench};
+pub use dump_symbols::{ExportedSymbol, SymbolsFormat, dump_symbols, render_symbols};
+pub use flatten::flatten;
+pub use graph::{GraphFormat, GraphKind, graph};
+pub use metrics::{ContractReport, FunctionReport, MetricsFormat, metrics, render_metrics};
+pub use query::{QueryKind, QueryLocation, query};
+pub use standard_json::standard_json;
+
+/// Loads the project containing `path`, returning the loaded state and `path` normalized. When
+/// `path` is a file, the library files it imports are loaded too, as opening it in an editor
+/// would.
+fn load(path: &Path) -> anyhow::Result<(ServerState, NormalizedPath)> {
+    let path = NormalizedPath::new(path.canonicalize()?.to_string_lossy());
+    let mut state = ServerState::new();
+    let root = state
+        .discover_foundry_root(&path)
+        .unwrap_or_else(|| path.clone());
+    state.root_path = Some(root.clone());
+    workspace::load(&mut state, &root, None)?;
+    if Path::new(path.as_str()).is_file() {
+        document::load_imports(&mut state, &path);
+    }
+    Ok((state, path))
+}
+
+/// `path` relative to the workspace root, with forward slashes, or as is outside of it.
+fn relative_path(state: &ServerState, path: &NormalizedPath) -> String {
+    let relative = state
+        .root_path
+        .as_ref()
+        .and_then(|root| Path::new(path.as_str()).strip_prefix(root.as_str()).ok());
+    match relative {
+        Some(relative) => relative.to_string_lossy().replace('\\', "/"),
+        None => path.as_str().to_string(),
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use std::path::Path;
+
+use sa_span::TextRange;
+use sa_span::lsp::{LspPosition, PositionEncoding, from_lsp_position_in, to_lsp_position_in};
+use sa_vfs::FileId;
+
+use super::{load, relative_path};
+use crate::state::ServerState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    Definition,
+    References,
+}
+
+impl QueryKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "def" => Some(Self::Definition),
+            "refs" => Some(Self::References),
+            _ => None,
+        }
+    }
+}
+
+/// A location printed as `path:line:column: source line`, like `grep -n` with columns. Lines and
+/// columns are 1-based, columns in UTF-16 code units as editors count them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryLocation {
+    pub path: String,
+    pub line: u32,
+    pub column: u32,
+    pub line_text: String,
+}
+
+impl fmt::Display for QueryLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path, self.line, self.column, self.line_text
+        )
+    }
+}
+
+/// Resolves the definition of, or the references to, the symbol at `target`, given as
+/// `<file>:<line>:<column>` with the same 1-based numbering [`QueryLocation`] prints.
+pub fn query(kind: QueryKind, target: &str) -> anyhow::Result<Vec<QueryLocation>> {
+    let mut parts = target.rsplitn(3, ':');
+    let (Some(column), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("expected <file>:<line>:<column>, got `{target}`");
+    };
+    let line = line.parse::<u32>()?.saturating_sub(1);
+    let column = column.parse::<u32>()?.saturating_sub(1);
+
+    let (state, path) = load(Path::new(file))?;
+    let analysis = state.analysis_host.snapshot();
+    let file_id = state
+        .vfs
+        .snapshot()
+        .file_id(&path)
+        .ok_or_else(|| anyhow::anyhow!("{path} is not part of the workspace"))?;
+    let offset = from_lsp_position_in(
+        LspPosition::new(line, column),
+        &analysis.file_line_index(file_id),
+        PositionEncoding::Utf16,
+    )
+    .ok_or_else(|| anyhow::anyhow!("{target} is past the end of the file"))?;
+
+    let ranges = match kind {
+        QueryKind::Definition => analysis
+            .goto_definition(file_id, offset)
+            .map(|target| (target.file_id, target.range))
+            .into_iter()
+            .collect::<Vec<_>>(),
+        QueryKind::References => analysis
+            .find_references(file_id, offset)
+            .into_iter()
+            .map(|reference| (reference.file_id(), reference.range()))
+            .collect(),
+    };
+    let mut locations = ranges
+        .into_iter()
+        .map(|(file_id, range)| location(&state, &analysis, file_id, range))
+        .collect::<Vec<_>>();
+    locations.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    locations.dedup();
+    Ok(locations)
+}
+
+fn location(
+    state: &ServerState,
+    analysis: &sa_ide::Analysis,
+    file_id: FileId,
+    range: TextRange,
+) -> QueryLocation {
+    let text = analysis.file_text(file_id);
+    let position = to_lsp_position_in(
+        range.start(),
+        &analysis.file_line_index(file_id),
+        PositionEncoding::Utf16,
+    );
+    let line_text = text
+        .lines()
+        .nth(position.line as usize)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    QueryLocation {
+        path: relative_path(state, &analysis.file_path(file_id)),
+        line: position.line + 1,
+        column: position.character + 1,
+        line_text,
+    }
+}
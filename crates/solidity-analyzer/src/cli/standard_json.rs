@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::load;
+use crate::document;
+
+/// Produces the solc standard-json input compiling the project containing `path`, with its
+/// sources, remappings and `foundry.toml` compiler settings.
+pub fn standard_json(path: &Path) -> anyhow::Result<Value> {
+    let (mut state, path) = load(path)?;
+    let root = state
+        .nearest_config(&path)
+        .map(|config| config.workspace().root().clone())
+        .ok_or_else(|| anyhow::anyhow!("no project found at {path}"))?;
+    // Library files are only loaded once something imports them.
+    let sources = state
+        .indexed_files
+        .iter()
+        .filter(|file| !state.is_library_file(file))
+        .cloned()
+        .collect::<Vec<_>>();
+    for file in &sources {
+        document::load_imports(&mut state, file);
+    }
+
+    let analysis = state.analysis_host.snapshot();
+    analysis
+        .project_ids()
+        .into_iter()
+        .find(|project_id| {
+            analysis
+                .project_structure(*project_id)
+                .is_some_and(|structure| structure.root == root)
+        })
+        .and_then(|project_id| analysis.standard_json_input(project_id))
+        .ok_or_else(|| anyhow::anyhow!("{root} is not a loaded project"))
+}
@@ -1,3 +1,8 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use sa_ide::SemaCacheConfig;
+use sa_ide_diagnostics::{Diagnostic, DiagnosticSeverity, DiagnosticSource};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -14,6 +19,11 @@ pub struct LspConfig {
     pub format: FormatConfig,
     pub lint: LintConfig,
     pub toolchain: ToolchainConfig,
+    pub foundry: FoundryConfig,
+    pub indexing: IndexingConfig,
+    pub sema: SemaConfig,
+    pub onchain: OnchainConfig,
+    pub abi: AbiConfig,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -29,6 +39,14 @@ pub struct DiagnosticsConfig {
     pub on_save: bool,
     /// Runs diagnostics on file edits/keystrokes. Defaults to true.
     pub on_change: bool,
+    /// Runs the analyzer's own checks, such as ERC interface compliance, ERC-7201 storage
+    /// namespaces, upgrade-safe storage layouts and selector collisions, on open files.
+    /// Defaults to true.
+    pub checks: bool,
+    /// Contracts deployed as the facets of an EIP-2535 diamond, as `Name` or
+    /// `path/to/File.sol:Name`. The checks report selectors a facet shares with another one.
+    /// Defaults to none.
+    pub diamond_facets: Vec<String>,
 }
 
 impl Default for DiagnosticsConfig {
@@ -37,6 +55,8 @@ impl Default for DiagnosticsConfig {
             enable: true,
             on_save: true,
             on_change: true,
+            checks: true,
+            diamond_facets: Vec::new(),
         }
     }
 }
@@ -57,6 +77,8 @@ pub struct LintConfig {
     pub on_save: bool,
     /// Runs lint diagnostics on file edits/keystrokes. Defaults to false.
     pub on_change: bool,
+    /// Overrides the severity of lints by code, e.g. `{ "mixed-case-function": "off" }`.
+    pub severity: BTreeMap<String, LintSeverity>,
 }
 
 impl Default for LintConfig {
@@ -65,10 +87,47 @@ impl Default for LintConfig {
             enable: true,
             on_save: true,
             on_change: false,
+            severity: BTreeMap::new(),
         }
     }
 }
 
+impl LintConfig {
+    /// Applies the severity overrides to the lint diagnostics in `diagnostics`, dropping the
+    /// lints turned off.
+    pub fn apply_severity(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        if self.severity.is_empty() {
+            return diagnostics;
+        }
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diag| {
+                let severity = match (&diag.source, &diag.code) {
+                    (DiagnosticSource::Solar, Some(code)) => self.severity.get(code),
+                    _ => None,
+                };
+                match severity {
+                    Some(LintSeverity::Off) => return None,
+                    Some(LintSeverity::Error) => diag.severity = DiagnosticSeverity::Error,
+                    Some(LintSeverity::Warning) => diag.severity = DiagnosticSeverity::Warning,
+                    Some(LintSeverity::Info) => diag.severity = DiagnosticSeverity::Info,
+                    None => {}
+                }
+                Some(diag)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct ToolchainConfig {
@@ -87,17 +146,149 @@ impl Default for ToolchainConfig {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FoundryConfig {
+    /// Profile to load the workspace with. When unset, `FOUNDRY_PROFILE` or `default` is used.
+    pub profile: Option<String>,
+}
+
+impl FoundryConfig {
+    /// Returns the configured profile, treating an empty string as unset.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile
+            .as_deref()
+            .filter(|profile| !profile.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IndexingConfig {
+    /// Globs (relative to the workspace root) to index even when excluded or never imported.
+    pub include: Vec<String>,
+    /// Globs (relative to the workspace root) to skip during indexing, e.g. `out` or `cache`.
+    pub exclude: Vec<String>,
+    /// Which files are loaded at startup. Defaults to `full`.
+    pub mode: IndexingMode,
+    /// Globs (relative to the workspace root) of the files lazy indexing starts from besides the
+    /// open files, e.g. `src/**`.
+    pub entry_points: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingMode {
+    /// Loads every source file of the project and the dependencies they import.
+    #[default]
+    Full,
+    /// Loads only the files reachable through imports from open files and the entry points;
+    /// the rest load once opened or imported by an open file.
+    Lazy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+/// Bounds the memory held by semantic analysis, for memory-constrained environments, and sets
+/// how often it is rebuilt while typing.
+pub struct SemaConfig {
+    /// Number of semantic snapshots that stay cached, counted separately for whole projects and
+    /// for single files with their imports. `0` keeps all. Defaults to 8.
+    pub cache_capacity: usize,
+    /// Keeps a second snapshot for projects with unresolved imports so the importing files
+    /// still get semantic features. Defaults to true.
+    pub fallback_snapshot: bool,
+    /// Milliseconds edits must pause before semantic analysis and the other on-change work is
+    /// rebuilt for the new text; an edit within the delay restarts it. Defaults to 250.
+    pub rebuild_delay_ms: u64,
+}
+
+impl Default for SemaConfig {
+    fn default() -> Self {
+        let defaults = SemaCacheConfig::default();
+        Self {
+            cache_capacity: defaults.capacity,
+            fallback_snapshot: defaults.fallback_snapshot,
+            rebuild_delay_ms: 250,
+        }
+    }
+}
+
+impl SemaConfig {
+    pub fn rebuild_delay(&self) -> Duration {
+        Duration::from_millis(self.rebuild_delay_ms)
+    }
+
+    pub fn cache_config(&self) -> SemaCacheConfig {
+        SemaCacheConfig {
+            capacity: self.cache_capacity,
+            fallback_snapshot: self.fallback_snapshot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+/// Fetching the verified sources of deployed contracts. Off unless enabled, as the addresses
+/// looked up are sent to Sourcify and Etherscan.
+pub struct OnchainConfig {
+    /// Allows fetching verified sources. Defaults to false.
+    pub enable: bool,
+    /// The chain addresses are looked up on when a fetch names none. Defaults to 1, Ethereum
+    /// mainnet.
+    pub chain_id: u64,
+    /// Etherscan API key, used when Sourcify has no match. Defaults to `etherscan_api_key`
+    /// from foundry.toml.
+    pub etherscan_api_key: Option<String>,
+}
+
+impl Default for OnchainConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            chain_id: 1,
+            etherscan_api_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+/// Contracts known only by their ABI. Each gets a generated interface, so code using them has
+/// completion and signature help without their Solidity source.
+pub struct AbiConfig {
+    /// Generates interfaces for `forge build` artifacts whose source file is missing, at the
+    /// path of that source. Defaults to false.
+    pub artifacts: bool,
+    /// ABI JSON files (relative to the workspace root), bare or inside an artifact. The
+    /// interface of `abi/Vendor.json` is named `Vendor` and imported as `abi/Vendor.sol`.
+    pub files: Vec<String>,
+}
+
 impl LspConfig {
     pub fn from_settings(settings: Value) -> Self {
         parse_settings(settings).unwrap_or_default()
     }
+
+    /// Whether going from `previous` to `self` needs the workspace reloaded. Everything else is
+    /// read on use and applies to the next request.
+    pub fn needs_reload(&self, previous: &LspConfig) -> bool {
+        self.foundry != previous.foundry
+            || self.indexing != previous.indexing
+            || self.abi != previous.abi
+    }
 }
 
 fn parse_settings(settings: Value) -> Option<LspConfig> {
     let has_top_level = settings.get("diagnostics").is_some()
         || settings.get("format").is_some()
         || settings.get("lint").is_some()
-        || settings.get("toolchain").is_some();
+        || settings.get("toolchain").is_some()
+        || settings.get("foundry").is_some()
+        || settings.get("indexing").is_some()
+        || settings.get("sema").is_some()
+        || settings.get("onchain").is_some()
+        || settings.get("abi").is_some();
     if has_top_level && let Ok(config) = serde_json::from_value::<LspConfig>(settings.clone()) {
         return Some(config);
     }
@@ -111,9 +302,14 @@ fn parse_settings(settings: Value) -> Option<LspConfig> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use sa_ide_diagnostics::{Diagnostic, DiagnosticSeverity, DiagnosticSource};
+    use sa_paths::NormalizedPath;
+    use sa_span::{TextRange, TextSize};
     use serde_json::json;
 
-    use super::LspConfig;
+    use super::{IndexingMode, LspConfig};
 
     #[test]
     fn default_settings_match_extension_defaults() {
@@ -121,38 +317,145 @@ mod tests {
         assert!(config.diagnostics.enable);
         assert!(config.diagnostics.on_save);
         assert!(config.diagnostics.on_change);
+        assert!(config.diagnostics.checks);
+        assert!(config.diagnostics.diamond_facets.is_empty());
         assert!(!config.format.on_save);
         assert!(config.lint.enable);
         assert!(config.lint.on_save);
         assert!(!config.lint.on_change);
         assert!(config.toolchain.prompt_install);
         assert!(config.toolchain.solc_jobs.is_none());
+        assert!(config.foundry.profile().is_none());
+        assert_eq!(config.indexing.mode, IndexingMode::Full);
+        assert_eq!(config.sema.cache_capacity, 8);
+        assert!(config.sema.fallback_snapshot);
+        assert_eq!(config.sema.rebuild_delay(), Duration::from_millis(250));
+        assert!(!config.onchain.enable);
+        assert_eq!(config.onchain.chain_id, 1);
     }
 
     #[test]
     fn settings_round_trip() {
         let settings = json!({
-            "diagnostics": { "enable": true, "onSave": true, "onChange": false },
+            "diagnostics": {
+                "enable": true,
+                "onSave": true,
+                "onChange": false,
+                "checks": false,
+                "diamondFacets": ["OwnershipFacet", "src/facets/Vault.sol:VaultFacet"]
+            },
             "format": { "onSave": true },
             "lint": { "enable": false, "onSave": true, "onChange": true },
-            "toolchain": { "promptInstall": false, "solcJobs": 3 }
+            "toolchain": { "promptInstall": false, "solcJobs": 3 },
+            "foundry": { "profile": "ci" },
+            "indexing": {
+                "include": ["generated/keep"],
+                "exclude": ["generated", "out"],
+                "mode": "lazy",
+                "entryPoints": ["src/**"]
+            },
+            "sema": { "cacheCapacity": 2, "fallbackSnapshot": false, "rebuildDelayMs": 500 },
+            "onchain": { "enable": true, "chainId": 10, "etherscanApiKey": "key" },
+            "abi": { "artifacts": true, "files": ["abi/Vendor.json"] }
         });
         let config = LspConfig::from_settings(settings);
         assert!(config.diagnostics.enable);
         assert!(config.diagnostics.on_save);
         assert!(!config.diagnostics.on_change);
+        assert!(!config.diagnostics.checks);
+        assert_eq!(
+            config.diagnostics.diamond_facets,
+            vec![
+                "OwnershipFacet".to_string(),
+                "src/facets/Vault.sol:VaultFacet".to_string()
+            ]
+        );
         assert!(config.format.on_save);
         assert!(!config.lint.enable);
         assert!(config.lint.on_save);
         assert!(config.lint.on_change);
         assert!(!config.toolchain.prompt_install);
         assert_eq!(config.toolchain.solc_jobs, Some(3));
+        assert_eq!(config.foundry.profile(), Some("ci"));
+        assert_eq!(config.indexing.include, vec!["generated/keep".to_string()]);
+        assert_eq!(
+            config.indexing.exclude,
+            vec!["generated".to_string(), "out".to_string()]
+        );
+        assert_eq!(config.indexing.mode, IndexingMode::Lazy);
+        assert_eq!(config.indexing.entry_points, vec!["src/**".to_string()]);
+        assert_eq!(config.sema.cache_capacity, 2);
+        assert!(!config.sema.fallback_snapshot);
+        assert_eq!(config.sema.rebuild_delay_ms, 500);
+        assert!(config.onchain.enable);
+        assert_eq!(config.onchain.chain_id, 10);
+        assert_eq!(config.onchain.etherscan_api_key.as_deref(), Some("key"));
+        assert!(config.abi.artifacts);
+        assert_eq!(config.abi.files, vec!["abi/Vendor.json".to_string()]);
 
         let serialized = serde_json::to_value(&config).expect("serialize config");
         let reparsed = LspConfig::from_settings(serialized);
         assert_eq!(reparsed, config);
     }
 
+    #[test]
+    fn lint_severity_overrides_only_touch_matching_lints() {
+        let config = LspConfig::from_settings(json!({
+            "lint": { "severity": { "mixed-case-function": "off", "unsafe-cheatcode": "error" } }
+        }));
+        let diag = |source, code: &str| Diagnostic {
+            file_path: NormalizedPath::new("/src/A.sol"),
+            range: TextRange::empty(TextSize::from(0)),
+            severity: DiagnosticSeverity::Info,
+            code: Some(code.to_string()),
+            source,
+            fixable: false,
+            message: String::new(),
+        };
+        let diagnostics = config.lint.apply_severity(vec![
+            diag(DiagnosticSource::Solar, "mixed-case-function"),
+            diag(DiagnosticSource::Solar, "unsafe-cheatcode"),
+            diag(DiagnosticSource::Solar, "asm-keccak256"),
+            diag(DiagnosticSource::Solc, "mixed-case-function"),
+        ]);
+        let summary = diagnostics
+            .iter()
+            .map(|diag| (diag.code.as_deref().unwrap_or_default(), diag.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("unsafe-cheatcode", DiagnosticSeverity::Error),
+                ("asm-keccak256", DiagnosticSeverity::Info),
+                ("mixed-case-function", DiagnosticSeverity::Info),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_project_settings_need_a_reload() {
+        let previous = LspConfig::default();
+        let live = LspConfig::from_settings(json!({
+            "diagnostics": { "onChange": false },
+            "lint": { "severity": { "mixed-case-function": "warning" } },
+            "sema": { "cacheCapacity": 1 }
+        }));
+        assert!(!live.needs_reload(&previous));
+
+        let profile = LspConfig::from_settings(json!({ "foundry": { "profile": "ci" } }));
+        assert!(profile.needs_reload(&previous));
+        let indexing = LspConfig::from_settings(json!({ "indexing": { "exclude": ["out"] } }));
+        assert!(indexing.needs_reload(&previous));
+        let abi = LspConfig::from_settings(json!({ "abi": { "artifacts": true } }));
+        assert!(abi.needs_reload(&previous));
+    }
+
+    #[test]
+    fn empty_foundry_profile_is_unset() {
+        let config = LspConfig::from_settings(json!({ "foundry": { "profile": "" } }));
+        assert!(config.foundry.profile().is_none());
+    }
+
     #[test]
     fn parses_nested_diagnostics_settings() {
         let settings = json!({
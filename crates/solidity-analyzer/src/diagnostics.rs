@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -9,37 +10,47 @@ use sa_flycheck::{
     FlycheckConfig, FlycheckDiagnostic, FlycheckHandle, FlycheckRequest, FlycheckResult,
     FlycheckSeverity,
 };
+use sa_ide::{
+    Analysis, CancellationToken, NamespacedStorageIssueKind, SemaUnavailable,
+    StorageUpgradeIssueKind,
+};
 use sa_ide_diagnostics::{
     Diagnostic, DiagnosticSeverity, DiagnosticSource, collect_solar_lints,
     collect_solar_lints_with_overlay, merge_diagnostics,
 };
-use sa_paths::NormalizedPath;
-use sa_span::lsp::to_lsp_range;
-use sa_vfs::VfsSnapshot;
+use sa_paths::{NormalizedPath, WorkspacePath};
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
+use sa_span::{LineIndex, TextRange};
+use sa_vfs::{FileId, VfsSnapshot};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tower_lsp::Client;
+use tower_lsp::lsp_types::request::WorkspaceDiagnosticRefresh;
 use tower_lsp::lsp_types::{
-    Diagnostic as LspDiagnostic, DiagnosticSeverity as LspSeverity, NumberOrString, Position,
-    Range, Url,
+    Diagnostic as LspDiagnostic, DiagnosticSeverity as LspSeverity, DocumentDiagnosticReport,
+    DocumentDiagnosticReportResult, FullDocumentDiagnosticReport, NumberOrString, Position,
+    PreviousResultId, Range, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, UnchangedDocumentDiagnosticReport, Url,
+    WorkspaceDiagnosticReport, WorkspaceDiagnosticReportResult, WorkspaceDocumentDiagnosticReport,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceUnchangedDocumentDiagnosticReport,
 };
-use tracing::warn;
+use tracing::{debug, info_span, warn};
 
 use crate::lsp_ext::{Health, ServerStatusNotification, ServerStatusParams};
 use crate::lsp_utils::{path_to_url, url_to_path};
 use crate::state::ServerState;
-
-const ON_CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+use crate::task_pool::{Priority, TaskPool};
 
 pub struct Diagnostics {
     client: Client,
     state: Arc<Mutex<ServerState>>,
     flycheck: FlycheckHandle,
     shared: Arc<Mutex<DiagnosticsState>>,
+    task_pool: TaskPool,
 }
 
 impl Diagnostics {
-    pub fn new(client: Client, state: Arc<Mutex<ServerState>>) -> Self {
+    pub fn new(client: Client, state: Arc<Mutex<ServerState>>, task_pool: TaskPool) -> Self {
         let (flycheck, mut results) = FlycheckHandle::spawn(FlycheckConfig::default());
         let shared = Arc::new(Mutex::new(DiagnosticsState::default()));
         let publish_client = client.clone();
@@ -58,6 +69,7 @@ impl Diagnostics {
             state,
             flycheck,
             shared,
+            task_pool,
         }
     }
 
@@ -65,13 +77,323 @@ impl Diagnostics {
         publish_status(&self.client, &self.state, &self.shared).await;
     }
 
+    /// Marks a workspace load as running until the matching [`Self::end_loading`]; loads hold
+    /// the server state, so the status has to go out before the load starts.
+    pub async fn begin_loading(&self) {
+        {
+            let mut data = self.shared.lock().await;
+            data.loading += 1;
+        }
+        publish_status(&self.client, &self.state, &self.shared).await;
+    }
+
+    pub async fn end_loading(&self) {
+        {
+            let mut data = self.shared.lock().await;
+            data.loading = data.loading.saturating_sub(1);
+        }
+        publish_status(&self.client, &self.state, &self.shared).await;
+    }
+
+    /// Checks in the background whether semantic analysis covers `uri`, so the status can say
+    /// why features for it fall back to heuristics. Edits are checked by [`Self::rebuild_sema`].
+    pub async fn check_sema(&self, uri: &Url) {
+        let Some(path) = url_to_path(uri) else {
+            return;
+        };
+        // Only the status reports the result; without it the check is wasted work.
+        if !self.state.lock().await.supports_server_status {
+            return;
+        }
+        let client = self.client.clone();
+        let state = Arc::clone(&self.state);
+        let shared = Arc::clone(&self.shared);
+        let task_pool = self.task_pool.clone();
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let generation = {
+            let mut data = shared.lock().await;
+            data.sema_tasks.register(path.clone(), abort_handle)
+        };
+
+        tokio::spawn(async move {
+            let check = Abortable::new(
+                async {
+                    let (analysis, snapshot) = {
+                        let state = state.lock().await;
+                        (state.analysis_host.snapshot(), state.vfs_snapshot.clone())
+                    };
+                    let file_id = snapshot?.file_id(&path)?;
+                    // Aborting the check drops the token and stops the query at a checkpoint.
+                    let token = CancellationToken::new();
+                    let _cancel_on_drop = token.drop_guard();
+                    let analysis = analysis.with_cancellation(token);
+                    let task = task_pool.spawn_with_priority(Priority::Background, move || {
+                        salsa::Cancelled::catch(AssertUnwindSafe(|| {
+                            analysis.sema_unavailable(file_id)
+                        }))
+                    });
+                    match task.await {
+                        Ok(Ok(unavailable)) => Some(unavailable),
+                        Ok(Err(_)) => None,
+                        Err(error) => {
+                            debug!(?error, "sema availability check failed");
+                            None
+                        }
+                    }
+                },
+                abort_registration,
+            )
+            .await;
+            {
+                let mut data = shared.lock().await;
+                data.sema_tasks.finish(&path, generation);
+                let Ok(Some(unavailable)) = check else {
+                    return;
+                };
+                match unavailable {
+                    Some(reason) => data.sema_unavailable.insert(path, reason),
+                    None => data.sema_unavailable.remove(&path),
+                };
+            }
+            publish_status(&client, &state, &shared).await;
+        });
+    }
+
+    /// Rebuilds the semantic snapshot of the project of `uri` in the background once edits pause
+    /// for the configured delay, so project-wide requests find it ready, then checks whether
+    /// semantic analysis covers the files edited meanwhile. Every edit in the project aborts the
+    /// pending or running rebuild and joins the next one, so a burst of edits compiles the
+    /// project once, with the latest text.
+    pub async fn rebuild_sema(&self, uri: &Url) {
+        let Some(path) = url_to_path(uri) else {
+            return;
+        };
+        let (root, progress) = {
+            let state = self.state.lock().await;
+            let Some(root) = state
+                .nearest_config(&path)
+                .map(|config| config.workspace().root().clone())
+            else {
+                return;
+            };
+            (root, state.progress.clone())
+        };
+        let client = self.client.clone();
+        let state = Arc::clone(&self.state);
+        let shared = Arc::clone(&self.shared);
+        let task_pool = self.task_pool.clone();
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let generation = {
+            let mut data = shared.lock().await;
+            data.rebuild_paths
+                .entry(root.clone())
+                .or_default()
+                .insert(path.clone());
+            data.rebuild_tasks.register(root.clone(), abort_handle)
+        };
+
+        tokio::spawn(async move {
+            let rebuilt = Abortable::new(
+                async {
+                    let delay = state.lock().await.lsp_config.sema.rebuild_delay();
+                    sleep(delay).await;
+                    let (analysis, snapshot, check) = {
+                        let state = state.lock().await;
+                        (
+                            state.analysis_host.snapshot(),
+                            state.vfs_snapshot.clone(),
+                            state.supports_server_status,
+                        )
+                    };
+                    let snapshot = snapshot?;
+                    let project_id = analysis.project_for_file(snapshot.file_id(&path)?);
+                    let edited = if check {
+                        let data = shared.lock().await;
+                        data.rebuild_paths
+                            .get(&root)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|path| Some((path.clone(), snapshot.file_id(path)?)))
+                            .collect::<Vec<_>>()
+                    } else {
+                        Vec::new()
+                    };
+                    // Aborting the rebuild drops the token and stops it at a checkpoint.
+                    let token = CancellationToken::new();
+                    let _cancel_on_drop = token.drop_guard();
+                    let analysis = analysis.with_cancellation(token.clone());
+                    let _report = progress.begin("Analyzing changes", Some(token));
+                    let span = info_span!("sema_rebuild", root = %root);
+                    let task = task_pool.spawn_with_priority(Priority::Background, move || {
+                        span.in_scope(|| {
+                            salsa::Cancelled::catch(AssertUnwindSafe(|| {
+                                analysis.prime_sema(project_id);
+                                edited
+                                    .into_iter()
+                                    .map(|(path, file_id)| {
+                                        (path, analysis.sema_unavailable(file_id))
+                                    })
+                                    .collect::<Vec<_>>()
+                            }))
+                        })
+                    });
+                    match task.await {
+                        Ok(Ok(checked)) => Some(checked),
+                        Ok(Err(_)) => {
+                            debug!("semantic rebuild cancelled");
+                            None
+                        }
+                        Err(error) => {
+                            debug!(?error, "semantic rebuild failed");
+                            None
+                        }
+                    }
+                },
+                abort_registration,
+            )
+            .await;
+            // An aborted rebuild leaves its edits to the rebuild that replaced it.
+            let Ok(checked) = rebuilt else {
+                return;
+            };
+            let open = {
+                let state = state.lock().await;
+                state.open_documents.keys().cloned().collect::<HashSet<_>>()
+            };
+            let changed = {
+                let mut data = shared.lock().await;
+                if !data.rebuild_tasks.finish(&root, generation) {
+                    return;
+                }
+                // A rebuild that did not get to check its edits leaves them to the next one.
+                let Some(checked) = checked else {
+                    return;
+                };
+                data.rebuild_paths.remove(&root);
+                let mut changed = false;
+                for (path, unavailable) in checked {
+                    if !open.contains(&path) {
+                        continue;
+                    }
+                    changed |= match unavailable {
+                        Some(reason) => data.sema_unavailable.insert(path, reason) != Some(reason),
+                        None => data.sema_unavailable.remove(&path).is_some(),
+                    };
+                }
+                changed
+            };
+            if changed {
+                publish_status(&client, &state, &shared).await;
+            }
+        });
+    }
+
+    /// Runs the analyzer's own checks on `uri` in the background and publishes their findings.
+    /// Edits delay the checks until typing pauses.
+    pub async fn run_checks(&self, uri: &Url, debounce: bool) {
+        let Some(path) = url_to_path(uri) else {
+            return;
+        };
+        let client = self.client.clone();
+        let state = Arc::clone(&self.state);
+        let shared = Arc::clone(&self.shared);
+        let task_pool = self.task_pool.clone();
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let generation = {
+            let mut data = shared.lock().await;
+            data.check_tasks.register(path.clone(), abort_handle)
+        };
+
+        tokio::spawn(async move {
+            let findings = Abortable::new(
+                async {
+                    if debounce {
+                        let delay = state.lock().await.lsp_config.sema.rebuild_delay();
+                        sleep(delay).await;
+                    }
+                    let (analysis, snapshot, facets) = {
+                        let state = state.lock().await;
+                        (
+                            state.analysis_host.snapshot(),
+                            state.vfs_snapshot.clone(),
+                            state.lsp_config.diagnostics.diamond_facets.clone(),
+                        )
+                    };
+                    let file_id = snapshot?.file_id(&path)?;
+                    let token = CancellationToken::new();
+                    let _cancel_on_drop = token.drop_guard();
+                    let analysis = analysis.with_cancellation(token);
+                    let check_path = path.clone();
+                    let task = task_pool.spawn_with_priority(Priority::Background, move || {
+                        salsa::Cancelled::catch(AssertUnwindSafe(|| {
+                            check_diagnostics(&analysis, file_id, &check_path, &facets)
+                        }))
+                    });
+                    match task.await {
+                        Ok(Ok(findings)) => Some(findings),
+                        Ok(Err(_)) => None,
+                        Err(error) => {
+                            debug!(?error, "analyzer checks failed");
+                            None
+                        }
+                    }
+                },
+                abort_registration,
+            )
+            .await;
+            let entries = {
+                let mut data = shared.lock().await;
+                data.check_tasks.finish(&path, generation);
+                let Ok(Some(findings)) = findings else {
+                    return;
+                };
+                let previous = data.checks.get(&path).map_or(&[][..], Vec::as_slice);
+                if previous == findings.as_slice() {
+                    return;
+                }
+                if findings.is_empty() {
+                    data.checks.remove(&path);
+                } else {
+                    data.checks.insert(path.clone(), findings);
+                }
+                let merged = data.merged(&path);
+                if merged.is_empty() {
+                    data.last_published.remove(&path);
+                } else {
+                    data.last_published.insert(path.clone());
+                }
+                vec![(path, merged)]
+            };
+            publish_entries(&client, &state, &shared, entries).await;
+        });
+    }
+
+    /// Forgets the sema state of a closed document.
+    pub async fn did_close(&self, uri: &Url) {
+        let Some(path) = url_to_path(uri) else {
+            return;
+        };
+        let removed = {
+            let mut data = self.shared.lock().await;
+            data.sema_tasks.cancel(&path);
+            data.check_tasks.cancel(&path);
+            data.sema_unavailable.remove(&path).is_some()
+        };
+        if removed {
+            publish_status(&self.client, &self.state, &self.shared).await;
+        }
+    }
+
     pub async fn did_save(&self, uri: &Url, run_solc: bool, run_solar: bool) {
         let Some(path) = url_to_path(uri) else {
             return;
         };
         let (config, solc_jobs) = {
             let state = self.state.lock().await;
-            (state.config.clone(), state.lsp_config.toolchain.solc_jobs)
+            (
+                state.config_for_path(&path),
+                state.lsp_config.toolchain.solc_jobs,
+            )
         };
         let should_clear_solc = !run_solc;
         let should_clear_solar = !run_solar;
@@ -118,6 +440,7 @@ impl Diagnostics {
             let client = self.client.clone();
             let state = Arc::clone(&self.state);
             let shared = Arc::clone(&self.shared);
+            let task_pool = self.task_pool.clone();
             let path_clone = path.clone();
             let (abort_handle, abort_registration) = AbortHandle::new_pair();
             let generation = {
@@ -128,7 +451,8 @@ impl Diagnostics {
 
             tokio::spawn(async move {
                 let Some(lints) =
-                    collect_lints(config, path_clone.as_str(), abort_registration).await
+                    collect_lints(&task_pool, config, path_clone.as_str(), abort_registration)
+                        .await
                 else {
                     return;
                 };
@@ -140,13 +464,165 @@ impl Diagnostics {
         }
     }
 
+    /// Answers `textDocument/diagnostic` from the diagnostics computed so far. Lints finishing
+    /// later bump the file's result id and ask the client to pull again.
+    pub async fn document_report(
+        &self,
+        uri: &Url,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReportResult {
+        let path = url_to_path(uri);
+        let (result_id, diagnostics) = {
+            let data = self.shared.lock().await;
+            data.pulled.report(path.as_ref())
+        };
+        let report = if previous_result_id == Some(result_id.as_str()) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        } else {
+            let (snapshot, encoding) = {
+                let state = self.state.lock().await;
+                (state.vfs_snapshot.clone(), state.position_encoding)
+            };
+            let line_index = path
+                .as_ref()
+                .and_then(|path| file_text(&snapshot, path))
+                .map(|text| LineIndex::new(&text));
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(result_id),
+                    items: diagnostics
+                        .into_iter()
+                        .map(|diag| diagnostic_to_lsp(diag, line_index.as_ref(), encoding))
+                        .collect(),
+                },
+            })
+        };
+        DocumentDiagnosticReportResult::Report(report)
+    }
+
+    /// Answers `workspace/diagnostic` with every file that has, or had, diagnostics.
+    pub async fn workspace_report(
+        &self,
+        previous_result_ids: &[PreviousResultId],
+    ) -> WorkspaceDiagnosticReportResult {
+        let mut reports = {
+            let data = self.shared.lock().await;
+            data.pulled.reports.clone().into_iter().collect::<Vec<_>>()
+        };
+        reports.sort_by(|(left, _), (right, _)| left.as_str().cmp(right.as_str()));
+        let (snapshot, open_documents, encoding) = {
+            let state = self.state.lock().await;
+            (
+                state.vfs_snapshot.clone(),
+                state.open_documents.clone(),
+                state.position_encoding,
+            )
+        };
+
+        let mut items = Vec::new();
+        for (path, report) in reports {
+            let Some(uri) = path_to_url(&path) else {
+                continue;
+            };
+            let result_id = report.result_id.to_string();
+            let version = open_documents.get(&path).map(|doc| i64::from(doc.version));
+            let unchanged = previous_result_ids
+                .iter()
+                .any(|previous| previous.uri == uri && previous.value == result_id);
+            let item = if unchanged {
+                WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id,
+                        },
+                    },
+                )
+            } else {
+                let line_index = file_text(&snapshot, &path).map(|text| LineIndex::new(&text));
+                WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                    uri,
+                    version,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: Some(result_id),
+                        items: report
+                            .diagnostics
+                            .into_iter()
+                            .map(|diag| diagnostic_to_lsp(diag, line_index.as_ref(), encoding))
+                            .collect(),
+                    },
+                })
+            };
+            items.push(item);
+        }
+        WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })
+    }
+
+    /// Brings the published diagnostics in line with changed settings: drops the diagnostics of
+    /// disabled sources and republishes the rest with the current lint severities.
+    pub async fn settings_changed(&self) {
+        let (diagnostics_enabled, checks_enabled, lint_enabled) = {
+            let state = self.state.lock().await;
+            (
+                state.lsp_config.diagnostics.enable,
+                state.lsp_config.diagnostics.checks,
+                state.lsp_config.lint.enable,
+            )
+        };
+        let entries = {
+            let mut data = self.shared.lock().await;
+            if !diagnostics_enabled {
+                data.solc.clear();
+            }
+            if !diagnostics_enabled || !checks_enabled {
+                data.checks.clear();
+            }
+            if !lint_enabled {
+                data.solar.clear();
+            }
+            data.merged_entries()
+        };
+        publish_entries(&self.client, &self.state, &self.shared, entries).await;
+    }
+
+    /// Withdraws the diagnostics of every file below `root` and stops rebuilding its project,
+    /// for a workspace folder the client removed.
+    pub async fn remove_root(&self, root: &NormalizedPath) {
+        let entries = {
+            let mut data = self.shared.lock().await;
+            data.rebuild_tasks.cancel(root);
+            data.rebuild_paths.remove(root);
+            let outside = |path: &NormalizedPath| WorkspacePath::new(root, path).is_none();
+            data.solc.retain(|path, _| outside(path));
+            data.solar.retain(|path, _| outside(path));
+            data.tests.retain(|path, _| outside(path));
+            data.slither.retain(|path, _| outside(path));
+            data.checks.retain(|path, _| outside(path));
+            data.sema_unavailable.retain(|path, _| outside(path));
+            data.merged_entries()
+        };
+        publish_entries(&self.client, &self.state, &self.shared, entries).await;
+        publish_status(&self.client, &self.state, &self.shared).await;
+    }
+
     pub async fn did_change(&self, uri: &Url) {
         let Some(path) = url_to_path(uri) else {
             return;
         };
-        let (config, snapshot) = {
+        let (config, snapshot, delay) = {
             let state = self.state.lock().await;
-            (state.config.clone(), state.vfs_snapshot.clone())
+            (
+                state.config_for_path(&path),
+                state.vfs_snapshot.clone(),
+                state.lsp_config.sema.rebuild_delay(),
+            )
         };
         let (Some(config), Some(snapshot)) = (config, snapshot) else {
             return;
@@ -155,19 +631,25 @@ impl Diagnostics {
         let client = self.client.clone();
         let state = Arc::clone(&self.state);
         let shared = Arc::clone(&self.shared);
+        let task_pool = self.task_pool.clone();
         let path_clone = path.clone();
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         let generation = {
             let mut data = shared.lock().await;
+            // A lint of the saved file still running would land after, and overwrite, the lint
+            // of the newer text.
+            data.lint_tasks.cancel(&path);
             data.change_tasks.register(path.clone(), abort_handle)
         };
         publish_status(&client, &state, &shared).await;
 
         tokio::spawn(async move {
             let Some(lints) = collect_lints_with_overlay(
+                &task_pool,
                 config,
                 snapshot,
                 path_clone.as_str(),
+                delay,
                 abort_registration,
             )
             .await
@@ -180,17 +662,156 @@ impl Diagnostics {
             publish_status(&client, &state, &shared).await;
         });
     }
+
+    /// Replaces the test failures reported for the tests of `path` named at `ran` with
+    /// `failures`, keeping those of tests that were not run.
+    pub async fn update_test_results(
+        &self,
+        path: NormalizedPath,
+        ran: &[TextRange],
+        failures: Vec<Diagnostic>,
+    ) {
+        let entries = {
+            let mut data = self.shared.lock().await;
+            let mut tests = data.tests.remove(&path).unwrap_or_default();
+            tests.retain(|diag| !ran.contains(&diag.range));
+            tests.extend(failures);
+            if !tests.is_empty() {
+                data.tests.insert(path.clone(), tests);
+            }
+            let merged = data.merged(&path);
+            if merged.is_empty() {
+                data.last_published.remove(&path);
+            } else {
+                data.last_published.insert(path.clone());
+            }
+            vec![(path, merged)]
+        };
+        publish_entries(&self.client, &self.state, &self.shared, entries).await;
+    }
+
+    /// Replaces the findings of the previously imported Slither report with `findings`.
+    pub async fn update_slither(&self, findings: Vec<Diagnostic>) {
+        let mut slither = HashMap::new();
+        for diag in findings {
+            slither
+                .entry(diag.file_path.clone())
+                .or_insert_with(Vec::new)
+                .push(diag);
+        }
+        let entries = {
+            let mut data = self.shared.lock().await;
+            data.slither = slither;
+            data.merged_entries()
+        };
+        publish_entries(&self.client, &self.state, &self.shared, entries).await;
+    }
 }
 
 #[derive(Default)]
 struct DiagnosticsState {
     solc: HashMap<NormalizedPath, Vec<Diagnostic>>,
     solar: HashMap<NormalizedPath, Vec<Diagnostic>>,
+    /// Failing tests of the last `forge test` run covering each test.
+    tests: HashMap<NormalizedPath, Vec<Diagnostic>>,
+    /// Findings of the last imported Slither report.
+    slither: HashMap<NormalizedPath, Vec<Diagnostic>>,
+    /// Findings of the analyzer's own checks of each open file.
+    checks: HashMap<NormalizedPath, Vec<Diagnostic>>,
     last_published: HashSet<NormalizedPath>,
     lint_tasks: TaskTracker,
     change_tasks: TaskTracker,
     solc_active: bool,
+    /// Workspace loads in flight.
+    loading: usize,
+    sema_tasks: TaskTracker,
+    check_tasks: TaskTracker,
+    /// Background rebuilds of each project's semantic snapshot, by project root.
+    rebuild_tasks: TaskTracker,
+    /// Files edited since the last finished rebuild of their project, by project root.
+    rebuild_paths: HashMap<NormalizedPath, HashSet<NormalizedPath>>,
+    /// Open files semantic analysis does not cover, and why.
+    sema_unavailable: HashMap<NormalizedPath, SemaUnavailable>,
     last_status: Option<ServerStatusParams>,
+    pulled: PulledReports,
+}
+
+impl DiagnosticsState {
+    /// Compiler diagnostics of `path` merged with its lints, failing tests and check findings.
+    fn merged(&self, path: &NormalizedPath) -> Vec<Diagnostic> {
+        let mut other = self.solar.get(path).cloned().unwrap_or_default();
+        other.extend(self.tests.get(path).into_iter().flatten().cloned());
+        other.extend(self.slither.get(path).into_iter().flatten().cloned());
+        other.extend(self.checks.get(path).into_iter().flatten().cloned());
+        merge_diagnostics(self.solc.get(path).cloned().unwrap_or_default(), other)
+    }
+
+    /// The merged diagnostics of every file that has some or had some published.
+    fn merged_entries(&mut self) -> Vec<(NormalizedPath, Vec<Diagnostic>)> {
+        let mut files = HashSet::new();
+        files.extend(self.solc.keys().cloned());
+        files.extend(self.solar.keys().cloned());
+        files.extend(self.tests.keys().cloned());
+        files.extend(self.slither.keys().cloned());
+        files.extend(self.checks.keys().cloned());
+        files.extend(self.last_published.iter().cloned());
+
+        let mut entries = Vec::new();
+        let mut next_published = HashSet::new();
+        for file in files {
+            let merged = self.merged(&file);
+            if !merged.is_empty() {
+                next_published.insert(file.clone());
+            }
+            entries.push((file, merged));
+        }
+        self.last_published = next_published;
+        entries
+    }
+}
+
+/// The latest diagnostics of each file for clients that pull them. A file's result id changes
+/// whenever its diagnostics do, so an unchanged pull costs the client nothing.
+#[derive(Default)]
+struct PulledReports {
+    reports: HashMap<NormalizedPath, PulledReport>,
+    next_result_id: u64,
+}
+
+#[derive(Clone)]
+struct PulledReport {
+    result_id: u64,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl PulledReports {
+    /// Records `diagnostics` for `path`, returning whether they differ from the last report.
+    fn update(&mut self, path: NormalizedPath, diagnostics: Vec<Diagnostic>) -> bool {
+        let unchanged = match self.reports.get(&path) {
+            Some(report) => report.diagnostics == diagnostics,
+            None => diagnostics.is_empty(),
+        };
+        if unchanged {
+            return false;
+        }
+        self.next_result_id += 1;
+        self.reports.insert(
+            path,
+            PulledReport {
+                result_id: self.next_result_id,
+                diagnostics,
+            },
+        );
+        true
+    }
+
+    /// Files never reported have no diagnostics, under result id `0`.
+    fn report(&self, path: Option<&NormalizedPath>) -> (String, Vec<Diagnostic>) {
+        match path.and_then(|path| self.reports.get(path)) {
+            Some(report) => (report.result_id.to_string(), report.diagnostics.clone()),
+            None => ("0".to_string(), Vec::new()),
+        }
+    }
 }
 
 struct LintTask {
@@ -214,14 +835,23 @@ impl TaskTracker {
         generation
     }
 
-    fn finish(&mut self, path: &NormalizedPath, generation: u64) {
-        let should_remove = self
+    /// Forgets the task of `path` if it is still the one registered as `generation`, and
+    /// returns whether it was.
+    fn finish(&mut self, path: &NormalizedPath, generation: u64) -> bool {
+        let current = self
             .tasks
             .get(path)
             .is_some_and(|task| task.generation == generation);
-        if should_remove {
+        if current {
             self.tasks.remove(path);
         }
+        current
+    }
+
+    fn cancel(&mut self, path: &NormalizedPath) {
+        if let Some(task) = self.tasks.remove(path) {
+            task.handle.abort();
+        }
     }
 
     fn is_empty(&self) -> bool {
@@ -247,28 +877,10 @@ async fn publish_flycheck_result(
         let mut data = shared.lock().await;
         data.solc = solc;
         data.solc_active = false;
-        let mut files = HashSet::new();
-        files.extend(data.solc.keys().cloned());
-        files.extend(data.solar.keys().cloned());
-        files.extend(data.last_published.iter().cloned());
-
-        let mut entries = Vec::new();
-        let mut next_published = HashSet::new();
-        for file in files {
-            let merged = merge_diagnostics(
-                data.solc.get(&file).cloned().unwrap_or_default(),
-                data.solar.get(&file).cloned().unwrap_or_default(),
-            );
-            if !merged.is_empty() {
-                next_published.insert(file.clone());
-            }
-            entries.push((file, merged));
-        }
-        data.last_published = next_published;
-        entries
+        data.merged_entries()
     };
 
-    publish_entries(client, state, entries).await;
+    publish_entries(client, state, shared, entries).await;
     publish_status(client, state, shared).await;
 }
 
@@ -291,12 +903,15 @@ async fn finish_change_task(
 }
 
 async fn collect_lints(
+    task_pool: &TaskPool,
     config: ResolvedFoundryConfig,
     path: &str,
     abort_registration: AbortRegistration,
 ) -> Option<Vec<Diagnostic>> {
     let path_buf = PathBuf::from(path);
-    let task = tokio::task::spawn_blocking(move || collect_solar_lints(&config, &[path_buf]));
+    let task = task_pool.spawn_with_priority(Priority::Background, move || {
+        collect_solar_lints(&config, &[path_buf])
+    });
     let result = match Abortable::new(task, abort_registration).await {
         Ok(result) => result,
         Err(_) => return None,
@@ -319,18 +934,22 @@ async fn collect_lints(
 }
 
 async fn collect_lints_with_overlay(
+    task_pool: &TaskPool,
     config: ResolvedFoundryConfig,
     snapshot: VfsSnapshot,
     path: &str,
+    delay: Duration,
     abort_registration: AbortRegistration,
 ) -> Option<Vec<Diagnostic>> {
     let path_buf = PathBuf::from(path);
+    let task_pool = task_pool.clone();
     let task = async move {
-        sleep(ON_CHANGE_DEBOUNCE).await;
-        tokio::task::spawn_blocking(move || {
-            collect_solar_lints_with_overlay(&config, &[path_buf], &snapshot)
-        })
-        .await
+        sleep(delay).await;
+        task_pool
+            .spawn_with_priority(Priority::Background, move || {
+                collect_solar_lints_with_overlay(&config, &[path_buf], &snapshot)
+            })
+            .await
     };
 
     let result = match Abortable::new(task, abort_registration).await {
@@ -368,10 +987,7 @@ async fn update_solar_diagnostics(
         } else {
             data.solar.insert(path.clone(), lints);
         }
-        let merged = merge_diagnostics(
-            data.solc.get(&path).cloned().unwrap_or_default(),
-            data.solar.get(&path).cloned().unwrap_or_default(),
-        );
+        let merged = data.merged(&path);
         if merged.is_empty() {
             data.last_published.remove(&path);
         } else {
@@ -380,7 +996,7 @@ async fn update_solar_diagnostics(
         vec![(path, merged)]
     };
 
-    publish_entries(client, state, entries).await;
+    publish_entries(client, state, shared, entries).await;
 }
 
 async fn clear_disabled_diagnostics(
@@ -399,10 +1015,7 @@ async fn clear_disabled_diagnostics(
         if clear_solar {
             data.solar.remove(&path);
         }
-        let merged = merge_diagnostics(
-            data.solc.get(&path).cloned().unwrap_or_default(),
-            data.solar.get(&path).cloned().unwrap_or_default(),
-        );
+        let merged = data.merged(&path);
         if merged.is_empty() {
             data.last_published.remove(&path);
         } else {
@@ -411,31 +1024,61 @@ async fn clear_disabled_diagnostics(
         vec![(path, merged)]
     };
 
-    publish_entries(client, state, entries).await;
+    publish_entries(client, state, shared, entries).await;
 }
 
 async fn publish_entries(
     client: &Client,
     state: &Arc<Mutex<ServerState>>,
+    shared: &Arc<Mutex<DiagnosticsState>>,
     entries: Vec<(NormalizedPath, Vec<Diagnostic>)>,
 ) {
-    let (snapshot, open_documents) = {
+    let (snapshot, open_documents, pull, refresh, lint, encoding) = {
         let state = state.lock().await;
-        (state.vfs_snapshot.clone(), state.open_documents.clone())
+        (
+            state.vfs_snapshot.clone(),
+            state.open_documents.clone(),
+            state.supports_pull_diagnostics,
+            state.supports_diagnostic_refresh,
+            state.lsp_config.lint.clone(),
+            state.position_encoding,
+        )
     };
+    let entries = entries
+        .into_iter()
+        .map(|(path, diagnostics)| (path, lint.apply_severity(diagnostics)));
 
-    let mut text_cache = HashMap::new();
+    if pull {
+        let changed = {
+            let mut data = shared.lock().await;
+            entries.fold(false, |changed, (path, diagnostics)| {
+                data.pulled.update(path, diagnostics) || changed
+            })
+        };
+        if changed && refresh {
+            // The client answers asynchronously; waiting here would hold up the caller.
+            let client = client.clone();
+            tokio::spawn(async move {
+                if let Err(error) = client.send_request::<WorkspaceDiagnosticRefresh>(()).await {
+                    warn!(?error, "failed to request a diagnostic refresh");
+                }
+            });
+        }
+        return;
+    }
+
+    let mut line_indexes = HashMap::new();
     for (path, diagnostics) in entries {
         let Some(uri) = path_to_url(&path) else {
             warn!(path = %path, "skipping diagnostics for non-file path");
             continue;
         };
-        let text = text_cache
+        let line_index = line_indexes
             .entry(path.clone())
-            .or_insert_with(|| file_text(&snapshot, &path));
+            .or_insert_with(|| file_text(&snapshot, &path).map(|text| LineIndex::new(&text)));
         let lsp_diagnostics = diagnostics
             .into_iter()
-            .map(|diag| diagnostic_to_lsp(diag, text.as_deref()))
+            .map(|diag| diagnostic_to_lsp(diag, line_index.as_ref(), encoding))
             .collect();
         let version = open_documents.get(&path).map(|doc| doc.version);
         client
@@ -444,9 +1087,89 @@ async fn publish_entries(
     }
 }
 
-fn diagnostic_to_lsp(diag: Diagnostic, text: Option<&str>) -> LspDiagnostic {
-    let range = match text {
-        Some(text) => to_lsp_range(diag.range, text),
+/// The findings of the analyzer's checks of `file_id` as diagnostics of `path`.
+fn check_diagnostics(
+    analysis: &Analysis,
+    file_id: FileId,
+    path: &NormalizedPath,
+    facets: &[String],
+) -> Vec<Diagnostic> {
+    let diagnostic = |range, severity, code: &str, message| Diagnostic {
+        file_path: path.clone(),
+        range,
+        severity,
+        code: Some(code.to_string()),
+        source: DiagnosticSource::Analyzer,
+        fixable: false,
+        message,
+    };
+    let mut diagnostics = analysis
+        .erc_compliance(file_id)
+        .into_iter()
+        .map(|issue| {
+            diagnostic(
+                issue.range,
+                DiagnosticSeverity::Warning,
+                "erc-compliance",
+                issue.message,
+            )
+        })
+        .collect::<Vec<_>>();
+    diagnostics.extend(
+        analysis
+            .namespaced_storage_issues(file_id)
+            .into_iter()
+            .map(|issue| {
+                // A wrong slot silently corrupts the storage of upgraded proxies.
+                let severity = match issue.kind {
+                    NamespacedStorageIssueKind::WrongSlot => DiagnosticSeverity::Error,
+                    NamespacedStorageIssueKind::OutsideNamespace => DiagnosticSeverity::Warning,
+                };
+                diagnostic(issue.range, severity, "erc7201", issue.message)
+            }),
+    );
+    diagnostics.extend(
+        analysis
+            .annotated_storage_upgrade_issues(file_id)
+            .into_iter()
+            .map(|issue| {
+                // A removed variable only loses its value; the others misread live state.
+                let severity = match issue.kind {
+                    StorageUpgradeIssueKind::Removed => DiagnosticSeverity::Warning,
+                    _ => DiagnosticSeverity::Error,
+                };
+                diagnostic(issue.range, severity, "storage-upgrade", issue.message)
+            }),
+    );
+    for collision in analysis.selector_collisions(file_id, facets) {
+        // Both functions are flagged when declared here; inherited ones point at the contract.
+        let mut ranges = [&collision.function, &collision.other]
+            .into_iter()
+            .filter(|function| function.file_id == file_id)
+            .map(|function| function.range)
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            ranges.push(collision.contract_range);
+        }
+        diagnostics.extend(ranges.into_iter().map(|range| {
+            diagnostic(
+                range,
+                DiagnosticSeverity::Error,
+                "selector-collision",
+                collision.message.clone(),
+            )
+        }));
+    }
+    diagnostics
+}
+
+fn diagnostic_to_lsp(
+    diag: Diagnostic,
+    line_index: Option<&LineIndex>,
+    encoding: PositionEncoding,
+) -> LspDiagnostic {
+    let range = match line_index {
+        Some(line_index) => to_lsp_range_in(diag.range, line_index, encoding),
         None => {
             warn!(
                 path = %diag.file_path,
@@ -490,16 +1213,23 @@ async fn publish_status(
     state: &Arc<Mutex<ServerState>>,
     shared: &Arc<Mutex<DiagnosticsState>>,
 ) {
-    let supports_status = { state.lock().await.supports_server_status };
+    let (supports_status, load_error) = {
+        let state = state.lock().await;
+        (state.supports_server_status, state.load_error.clone())
+    };
     if !supports_status {
         return;
     }
 
     let status = {
         let mut data = shared.lock().await;
-        let solc_active = data.solc_active;
-        let solar_active = !data.lint_tasks.is_empty() || !data.change_tasks.is_empty();
-        let status = build_status(solc_active, solar_active);
+        let status = build_status(&StatusInputs {
+            loading: data.loading > 0,
+            solc_active: data.solc_active,
+            solar_active: !data.lint_tasks.is_empty() || !data.change_tasks.is_empty(),
+            load_error: load_error.as_deref(),
+            sema_unavailable: &data.sema_unavailable,
+        });
         if data.last_status.as_ref() == Some(&status) {
             None
         } else {
@@ -515,21 +1245,58 @@ async fn publish_status(
     }
 }
 
-fn build_status(solc_active: bool, solar_active: bool) -> ServerStatusParams {
-    let (quiescent, message) = if solc_active && solar_active {
-        (false, Some("Compiling and analyzing...".to_string()))
-    } else if solc_active {
-        (false, Some("Compiling...".to_string()))
-    } else if solar_active {
-        (false, Some("Analyzing...".to_string()))
+struct StatusInputs<'a> {
+    loading: bool,
+    solc_active: bool,
+    solar_active: bool,
+    load_error: Option<&'a str>,
+    sema_unavailable: &'a HashMap<NormalizedPath, SemaUnavailable>,
+}
+
+/// Problems win over progress in the message: a broken foundry.toml is an error, and files
+/// without semantic analysis degrade the server to a warning.
+fn build_status(inputs: &StatusInputs<'_>) -> ServerStatusParams {
+    let quiescent = !(inputs.loading || inputs.solc_active || inputs.solar_active);
+    let activity = if inputs.loading {
+        "Loading workspace..."
+    } else if inputs.solc_active && inputs.solar_active {
+        "Compiling and analyzing..."
+    } else if inputs.solc_active {
+        "Compiling..."
+    } else if inputs.solar_active {
+        "Analyzing..."
     } else {
-        (true, Some("OK".to_string()))
+        "OK"
+    };
+
+    let (health, message) = if let Some(error) = inputs.load_error {
+        (
+            Health::Error,
+            format!("Failed to load the project: {error}"),
+        )
+    } else if let Some((path, reason)) = inputs
+        .sema_unavailable
+        .iter()
+        .min_by_key(|(path, _)| path.as_str())
+    {
+        let reason = match reason {
+            SemaUnavailable::ParseErrors => "parse errors",
+            SemaUnavailable::CompileErrors => "compile errors",
+        };
+        let mut message = format!("Semantic analysis unavailable for {path} due to {reason}");
+        let others = inputs.sema_unavailable.len() - 1;
+        if others > 0 {
+            message.push_str(&format!(" (and {others} more)"));
+        }
+        (Health::Warning, message)
+    } else {
+        (Health::Ok, activity.to_string())
     };
 
     ServerStatusParams {
-        health: Health::Ok,
+        health,
         quiescent,
-        message,
+        message: Some(message),
     }
 }
 
@@ -540,7 +1307,9 @@ fn file_text(snapshot: &Option<VfsSnapshot>, path: &NormalizedPath) -> Option<St
     {
         return Some(text.to_string());
     }
-    std::fs::read_to_string(path.as_str()).ok()
+    sa_vfs::read_text(Path::new(path.as_str()))
+        .ok()
+        .map(|decoded| decoded.text)
 }
 
 #[cfg(test)]
@@ -587,28 +1356,137 @@ mod tests {
         assert!(tracker.is_empty());
     }
 
+    #[tokio::test]
+    async fn task_tracker_cancel_aborts_and_forgets_the_task() {
+        let mut tracker = TaskTracker::default();
+        let path = NormalizedPath::new("src/Main.sol");
+        let (handle, registration) = AbortHandle::new_pair();
+        let task = tokio::spawn(Abortable::new(
+            async { pending::<()>().await },
+            registration,
+        ));
+
+        tracker.register(path.clone(), handle);
+        tracker.cancel(&path);
+        assert!(tracker.is_empty());
+
+        let result = timeout(Duration::from_millis(50), task)
+            .await
+            .expect("abortable should resolve");
+        assert!(result.expect("join").is_err());
+    }
+
+    #[test]
+    fn pulled_reports_change_result_ids_only_on_change() {
+        let mut reports = PulledReports::default();
+        let path = NormalizedPath::new("src/Main.sol");
+        let diag = Diagnostic {
+            file_path: path.clone(),
+            range: TextRange::new(TextSize::new(0), TextSize::new(1)),
+            severity: DiagnosticSeverity::Error,
+            code: None,
+            source: DiagnosticSource::Solar,
+            fixable: false,
+            message: "broken".to_string(),
+        };
+
+        assert!(!reports.update(path.clone(), Vec::new()));
+        assert_eq!(reports.report(Some(&path)).0, "0");
+
+        assert!(reports.update(path.clone(), vec![diag.clone()]));
+        let (first, diagnostics) = reports.report(Some(&path));
+        assert_eq!(diagnostics, vec![diag.clone()]);
+
+        assert!(!reports.update(path.clone(), vec![diag]));
+        assert_eq!(reports.report(Some(&path)).0, first);
+
+        assert!(reports.update(path.clone(), Vec::new()));
+        let (cleared, diagnostics) = reports.report(Some(&path));
+        assert_ne!(cleared, first);
+        assert!(diagnostics.is_empty());
+    }
+
     #[test]
     fn build_status_messages_and_quiescence() {
-        let status = build_status(true, true);
+        let no_files = HashMap::new();
+        let activity = |loading, solc_active, solar_active| {
+            build_status(&StatusInputs {
+                loading,
+                solc_active,
+                solar_active,
+                load_error: None,
+                sema_unavailable: &no_files,
+            })
+        };
+
+        let status = activity(false, true, true);
         assert!(!status.quiescent);
         assert_eq!(
             status.message.as_deref(),
             Some("Compiling and analyzing...")
         );
 
-        let status = build_status(true, false);
+        let status = activity(false, true, false);
         assert!(!status.quiescent);
         assert_eq!(status.message.as_deref(), Some("Compiling..."));
 
-        let status = build_status(false, true);
+        let status = activity(false, false, true);
         assert!(!status.quiescent);
         assert_eq!(status.message.as_deref(), Some("Analyzing..."));
 
-        let status = build_status(false, false);
+        let status = activity(true, false, false);
+        assert!(!status.quiescent);
+        assert_eq!(status.message.as_deref(), Some("Loading workspace..."));
+
+        let status = activity(false, false, false);
         assert!(status.quiescent);
+        assert_eq!(status.health, Health::Ok);
         assert_eq!(status.message.as_deref(), Some("OK"));
     }
 
+    #[test]
+    fn build_status_reports_load_errors_and_degraded_files() {
+        let mut files = HashMap::new();
+        files.insert(
+            NormalizedPath::new("/workspace/src/B.sol"),
+            SemaUnavailable::CompileErrors,
+        );
+        files.insert(
+            NormalizedPath::new("/workspace/src/A.sol"),
+            SemaUnavailable::ParseErrors,
+        );
+        let degraded = build_status(&StatusInputs {
+            loading: false,
+            solc_active: false,
+            solar_active: false,
+            load_error: None,
+            sema_unavailable: &files,
+        });
+        assert_eq!(degraded.health, Health::Warning);
+        assert!(degraded.quiescent);
+        assert_eq!(
+            degraded.message.as_deref(),
+            Some(
+                "Semantic analysis unavailable for /workspace/src/A.sol due to parse errors \
+                 (and 1 more)"
+            )
+        );
+
+        let failed = build_status(&StatusInputs {
+            loading: false,
+            solc_active: true,
+            solar_active: false,
+            load_error: Some("invalid foundry.toml"),
+            sema_unavailable: &files,
+        });
+        assert_eq!(failed.health, Health::Error);
+        assert!(!failed.quiescent);
+        assert_eq!(
+            failed.message.as_deref(),
+            Some("Failed to load the project: invalid foundry.toml")
+        );
+    }
+
     #[test]
     fn diagnostic_to_lsp_uses_fallback_range_without_text() {
         let diag = Diagnostic {
@@ -621,7 +1499,7 @@ mod tests {
             message: "lint".to_string(),
         };
 
-        let lsp = diagnostic_to_lsp(diag, None);
+        let lsp = diagnostic_to_lsp(diag, None, PositionEncoding::Utf16);
         assert_eq!(
             lsp.range,
             Range::new(Position::new(0, 0), Position::new(0, 0))
@@ -1,29 +1,42 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use sa_ide::AnalysisChange;
-use sa_span::lsp::from_lsp_range;
-use sa_vfs::{VfsChange, VfsSnapshot};
+use sa_paths::NormalizedPath;
+use sa_span::lsp::{PositionEncoding, from_lsp_range_with};
+use sa_vfs::{OverlayEdit, VfsChange, VfsSnapshot};
 use tower_lsp::lsp_types::{
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
     DidSaveTextDocumentParams, TextDocumentContentChangeEvent,
 };
 use tracing::{debug, warn};
 
+use crate::config::IndexingMode;
 use crate::indexer;
-use crate::lsp_utils::url_to_path;
+use crate::lsp_utils::{ClientPaths, url_to_path};
 use crate::state::{OpenDocument, ServerState};
 use crate::workspace;
 
 pub fn did_open(state: &mut ServerState, params: DidOpenTextDocumentParams) {
-    let path = match url_to_path(&params.text_document.uri) {
+    let path = match ClientPaths::shared().remember(&params.text_document.uri) {
         Some(path) => path,
         None => return,
     };
 
     let text = params.text_document.text;
-    state.vfs.apply_change(VfsChange::Set {
+    let snapshot = state.vfs.snapshot();
+    let known_on_disk = snapshot
+        .file_id(&path)
+        .is_some_and(|file_id| snapshot.disk_text(file_id).is_some());
+    if !known_on_disk && let Ok(disk_text) = sa_vfs::read_text(Path::new(path.as_str())) {
+        state.vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from(disk_text.text),
+        });
+    }
+    state.vfs.apply_change(VfsChange::SetOverlay {
         path: path.clone(),
-        text: Arc::from(text.clone()),
+        text: Arc::from(text),
     });
     let snapshot = state.vfs.snapshot();
     if snapshot.file_id(&path).is_some() {
@@ -47,35 +60,68 @@ pub fn did_open(state: &mut ServerState, params: DidOpenTextDocumentParams) {
         }
     }
 
-    let (workspace, remappings) = match state.config.as_ref() {
-        Some(config) => (
-            config.workspace().clone(),
-            config.active_profile().remappings(),
-        ),
-        None => return,
-    };
-    let index_result = match indexer::index_open_file_imports(&workspace, remappings, &path, &text)
+    if state.config.is_some()
+        && let Some(root) = state.discover_foundry_root(&path)
+        && !state.is_loaded_root(&root)
+        && let Err(error) = workspace::load_additional(state, &root)
     {
+        warn!(?error, root = %root, "did_open: failed to load nested workspace");
+    }
+
+    load_imports(state, &path);
+}
+
+/// Loads the files `path` reaches through its imports, including library files that workspace
+/// loading deferred. Does nothing if `path` has not changed since the last call.
+pub fn load_imports(state: &mut ServerState, path: &NormalizedPath) {
+    let Some(snapshot) = state.vfs_snapshot.as_ref() else {
+        return;
+    };
+    let Some(file_id) = snapshot.file_id(path) else {
+        return;
+    };
+    let Some(text) = snapshot.file_text(file_id).map(str::to_string) else {
+        return;
+    };
+    let loaded = (file_id, snapshot.file_version(file_id).unwrap_or_default());
+    if state.loaded_imports.get(path) == Some(&loaded) {
+        return;
+    }
+    let Some(config) = state.config_for_path(path) else {
+        return;
+    };
+    let workspace = config.workspace();
+    let remappings = config.active_profile().remappings();
+    let index_result = match indexer::index_open_file_imports(workspace, remappings, path, &text) {
         Ok(result) => result,
         Err(error) => {
-            warn!(?error, path = %path, "did_open: failed to index file imports");
+            warn!(?error, path = %path, "failed to index file imports");
             return;
         }
     };
+    state.loaded_imports.insert(path.clone(), loaded);
 
     let mut changes = Vec::new();
     for indexed_file in index_result.files {
         state.indexed_files.insert(indexed_file.path.clone());
-        if state.open_documents.contains_key(&indexed_file.path) {
+        // `path` itself was indexed from the VFS contents, which may be an unsaved overlay.
+        if indexed_file.path == *path {
             continue;
         }
-        changes.push(VfsChange::Set {
-            path: indexed_file.path,
-            text: Arc::from(indexed_file.text),
-        });
+        let unchanged = snapshot
+            .file_id(&indexed_file.path)
+            .and_then(|file_id| snapshot.disk_text(file_id))
+            .is_some_and(|known| known == indexed_file.text);
+        if !unchanged {
+            changes.push(VfsChange::Set {
+                path: indexed_file.path,
+                text: Arc::from(indexed_file.text),
+            });
+        }
     }
 
     if !changes.is_empty() {
+        debug!(path = %path, files = changes.len(), "loading imported files");
         state.vfs.apply_changes(changes);
         let snapshot = state.vfs.snapshot();
         apply_snapshot(state, snapshot);
@@ -88,18 +134,26 @@ pub fn did_change(state: &mut ServerState, params: DidChangeTextDocumentParams)
         None => return,
     };
 
-    let snapshot = state.vfs.snapshot();
-    let existing_text = snapshot
-        .file_id(&path)
-        .and_then(|file_id| snapshot.file_text(file_id))
-        .unwrap_or("");
-    let Some(new_text) = apply_changes(existing_text, &params.content_changes) else {
+    let existing_text = state.vfs.file_text(&path).unwrap_or("");
+    let Some((new_text, edits)) = apply_changes(
+        existing_text,
+        &params.content_changes,
+        state.position_encoding,
+    ) else {
         return;
     };
 
-    state.vfs.apply_change(VfsChange::Set {
-        path: path.clone(),
-        text: Arc::from(new_text),
+    let text = Arc::from(new_text);
+    state.vfs.apply_change(match edits {
+        Some(edits) => VfsChange::EditOverlay {
+            path: path.clone(),
+            text,
+            edits,
+        },
+        None => VfsChange::SetOverlay {
+            path: path.clone(),
+            text,
+        },
     });
     let snapshot = state.vfs.snapshot();
     if snapshot.file_id(&path).is_some() {
@@ -119,19 +173,24 @@ pub fn did_close(state: &mut ServerState, params: DidCloseTextDocumentParams) {
         None => return,
     };
     let _ = state.open_documents.remove(&path);
+    ClientPaths::shared().forget(&path);
+    // Closing drops the editor contents; indexed files fall back to what is on disk.
     if state.indexed_files.contains(&path) {
-        match std::fs::read_to_string(path.as_str()) {
-            Ok(text) => state.vfs.apply_change(VfsChange::Set {
+        match sa_vfs::read_text(Path::new(path.as_str())) {
+            Ok(disk_text) => state.vfs.apply_change(VfsChange::Set {
                 path: path.clone(),
-                text: Arc::from(text),
+                text: Arc::from(disk_text.text),
             }),
             Err(error) => {
                 debug!(?error, path = %path, "did_close: failed to reload indexed file");
             }
         }
     } else {
-        state.vfs.apply_change(VfsChange::Remove { path });
+        state
+            .vfs
+            .apply_change(VfsChange::Remove { path: path.clone() });
     }
+    state.vfs.apply_change(VfsChange::ClearOverlay { path });
     let snapshot = state.vfs.snapshot();
     apply_snapshot(state, snapshot);
 }
@@ -142,10 +201,18 @@ pub fn did_save(state: &mut ServerState, params: DidSaveTextDocumentParams) {
         None => return,
     };
     if let Some(text) = params.text {
-        state.vfs.apply_change(VfsChange::Set {
-            path: path.clone(),
-            text: Arc::from(text),
-        });
+        let text = Arc::<str>::from(text);
+        // The saved text is now both what is on disk and what the editor holds.
+        state.vfs.apply_changes(vec![
+            VfsChange::Set {
+                path: path.clone(),
+                text: Arc::clone(&text),
+            },
+            VfsChange::SetOverlay {
+                path: path.clone(),
+                text,
+            },
+        ]);
         let snapshot = state.vfs.snapshot();
         if snapshot.file_id(&path).is_some() {
             // didSave doesn't include a document version; keep the last known value.
@@ -162,31 +229,116 @@ pub fn did_save(state: &mut ServerState, params: DidSaveTextDocumentParams) {
     }
 }
 
-fn apply_snapshot(state: &mut ServerState, snapshot: VfsSnapshot) {
+/// What [`apply_disk_changes`] did.
+#[derive(Debug, Default)]
+pub struct AppliedDiskChanges {
+    /// Open documents whose unsaved edits now conflict with the disk.
+    pub conflicts: Vec<NormalizedPath>,
+    /// Files appeared or disappeared, so imports may resolve differently.
+    pub files_added_or_removed: bool,
+}
+
+/// Applies changes the file watcher picked up on disk. Open documents keep showing the editor
+/// contents, files the index filter excludes and library files nothing has imported yet stay
+/// unloaded, as do all such files in lazy indexing mode.
+pub fn apply_disk_changes(state: &mut ServerState, changes: Vec<VfsChange>) -> AppliedDiskChanges {
+    let lazy = state.lsp_config.indexing.mode == IndexingMode::Lazy;
+    let changes = changes
+        .into_iter()
+        .filter(|change| match change {
+            VfsChange::Set { path, .. } => {
+                state.vfs.file_id(path).is_some()
+                    || !(lazy || state.is_library_file(path) || state.is_excluded(path))
+            }
+            _ => true,
+        })
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return AppliedDiskChanges::default();
+    }
+    let before = state.vfs.snapshot();
+    let mut touched = Vec::new();
+    for change in &changes {
+        match change {
+            VfsChange::Set { path, .. } => {
+                state.indexed_files.insert(path.clone());
+                touched.push(path.clone());
+            }
+            VfsChange::Remove { path } => {
+                state.indexed_files.remove(path);
+                touched.push(path.clone());
+            }
+            VfsChange::SetOverlay { .. }
+            | VfsChange::EditOverlay { .. }
+            | VfsChange::ClearOverlay { .. } => {}
+        }
+    }
+    debug!(changes = changes.len(), "applying on-disk file changes");
+    state.vfs.apply_changes(changes);
+    let snapshot = state.vfs.snapshot();
+    let files_added_or_removed = touched
+        .iter()
+        .any(|path| before.file_id(path) != snapshot.file_id(path));
+    if files_added_or_removed {
+        state.loaded_imports.clear();
+    }
+    let conflicts = touched
+        .into_iter()
+        .filter(|path| {
+            let conflicted = |snapshot: &VfsSnapshot| {
+                snapshot
+                    .file_id(path)
+                    .is_some_and(|file_id| snapshot.has_disk_conflict(file_id))
+            };
+            conflicted(&snapshot) && !conflicted(&before)
+        })
+        .collect();
+    apply_snapshot(state, snapshot);
+    AppliedDiskChanges {
+        conflicts,
+        files_added_or_removed,
+    }
+}
+
+pub(crate) fn apply_snapshot(state: &mut ServerState, snapshot: VfsSnapshot) {
     let mut change = AnalysisChange::new();
     change.set_vfs(snapshot.clone());
     state.analysis_host.apply_change(change);
     state.vfs_snapshot = Some(snapshot);
 }
 
-fn apply_changes(text: &str, changes: &[TextDocumentContentChangeEvent]) -> Option<String> {
+/// Applies the changes in order and returns the new text with the byte edits that produced it,
+/// or no edits if a change replaced the whole document.
+fn apply_changes(
+    text: &str,
+    changes: &[TextDocumentContentChangeEvent],
+    encoding: PositionEncoding,
+) -> Option<(String, Option<Vec<OverlayEdit>>)> {
     let mut current = text.to_string();
+    let mut edits = Some(Vec::with_capacity(changes.len()));
     for change in changes {
         if let Some(range) = &change.range {
-            let range = from_lsp_range(*range, &current)?;
+            let range = from_lsp_range_with(*range, &current, encoding)?;
             let start: usize = range.start().into();
             let end: usize = range.end().into();
-            if start > current.len() || end > current.len() || start > end {
+            if end > current.len()
+                || start > end
+                || !current.is_char_boundary(start)
+                || !current.is_char_boundary(end)
+            {
                 return None;
             }
-            let mut next = String::with_capacity(current.len() + change.text.len());
-            next.push_str(&current[..start]);
-            next.push_str(&change.text);
-            next.push_str(&current[end..]);
-            current = next;
+            current.replace_range(start..end, &change.text);
+            if let Some(edits) = &mut edits {
+                edits.push(OverlayEdit {
+                    range,
+                    new_text: change.text.clone(),
+                });
+            }
         } else {
             current = change.text.clone();
+            edits = None;
         }
     }
-    Some(current)
+    Some((current, edits))
 }
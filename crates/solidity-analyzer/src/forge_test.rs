@@ -0,0 +1,186 @@
+//! Runs `forge test --json` for the tests picked in the editor and reads its structured results.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use tower_lsp::Client;
+use tower_lsp::lsp_types::MessageType;
+
+/// The result of one test function, as forge reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TestOutcome {
+    /// The test contract, from the `<path>:<contract>` key of its suite.
+    pub contract: String,
+    pub test: String,
+    pub passed: bool,
+    /// Why a failing test failed, e.g. `assertion failed: 1 != 2`.
+    pub reason: Option<String>,
+}
+
+/// One entry of the `forge test --json` output, keyed by `<path>:<contract>`.
+#[derive(Debug, Deserialize)]
+struct SuiteResult {
+    #[serde(default)]
+    test_results: BTreeMap<String, TestResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestResult {
+    status: TestStatus,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+enum TestStatus {
+    Success,
+    Failure,
+    #[serde(other)]
+    Skipped,
+}
+
+/// Arguments selecting `test` of `contract` in `path`, or every test of the contract.
+pub(crate) fn forge_test_args(path: &str, contract: &str, test: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "test".to_string(),
+        "--json".to_string(),
+        "--match-path".to_string(),
+        path.to_string(),
+        "--match-contract".to_string(),
+        format!("^{contract}$"),
+    ];
+    if let Some(test) = test {
+        args.push("--match-test".to_string());
+        args.push(format!("^{test}$"));
+    }
+    args
+}
+
+/// Runs `forge` with `args` in `root`, forwarding what it prints to stderr to the client's log
+/// as it arrives. Fails when forge cannot be started or exits without reporting any test.
+pub(crate) async fn run_forge_test(
+    client: &Client,
+    root: &Path,
+    args: &[String],
+) -> anyhow::Result<Vec<TestOutcome>> {
+    let mut child = Command::new("forge")
+        .args(args)
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("failed to start forge")?;
+    let mut stdout = child.stdout.take().context("forge stdout")?;
+    let stderr = child.stderr.take().context("forge stderr")?;
+    // Read alongside stderr so a large report cannot fill its pipe and stall forge.
+    let output = tokio::spawn(async move {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output).await;
+        output
+    });
+
+    let mut lines = BufReader::new(stderr).lines();
+    let mut errors = String::new();
+    while let Some(line) = lines.next_line().await? {
+        client.log_message(MessageType::LOG, &line).await;
+        errors.push_str(&line);
+        errors.push('\n');
+    }
+    let output = output.await.unwrap_or_default();
+    let status = child.wait().await?;
+    let outcomes = match parse_test_results(&output) {
+        Ok(outcomes) => outcomes,
+        Err(_) if !status.success() => anyhow::bail!("forge test failed: {}", errors.trim()),
+        Err(error) => return Err(error),
+    };
+    if outcomes.is_empty() && !status.success() {
+        anyhow::bail!("forge test failed: {}", errors.trim());
+    }
+    Ok(outcomes)
+}
+
+/// The outcomes in `forge test --json` output. Forge prints the results as a single JSON object
+/// on the last line; anything before it is ignored. Skipped tests are left out.
+fn parse_test_results(output: &str) -> anyhow::Result<Vec<TestOutcome>> {
+    let json = output
+        .lines()
+        .rev()
+        .find(|line| line.trim_start().starts_with('{'))
+        .context("forge printed no test results")?;
+    let suites: BTreeMap<String, SuiteResult> =
+        serde_json::from_str(json).context("failed to parse forge test results")?;
+    let mut outcomes = Vec::new();
+    for (suite, result) in suites {
+        let contract = suite
+            .rsplit_once(':')
+            .map_or(suite.as_str(), |(_, contract)| contract);
+        for (signature, test) in result.test_results {
+            let passed = match test.status {
+                TestStatus::Success => true,
+                TestStatus::Failure => false,
+                TestStatus::Skipped => continue,
+            };
+            let name = signature
+                .split_once('(')
+                .map_or(signature.as_str(), |(name, _)| name);
+            outcomes.push(TestOutcome {
+                contract: contract.to_string(),
+                test: name.to_string(),
+                passed,
+                reason: test.reason.filter(|reason| !reason.is_empty()),
+            });
+        }
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TestOutcome, forge_test_args, parse_test_results};
+
+    #[test]
+    fn parses_forge_json_results() {
+        let output = r#"{"test/Counter.t.sol:CounterTest":{"duration":"1ms","test_results":{"testFuzz_Set(uint256)":{"status":"Failure","reason":"panic: arithmetic underflow or overflow (0x11)","counterexample":null,"logs":[],"kind":{"Fuzz":{"runs":0,"mean_gas":0,"median_gas":0}}},"test_Increment()":{"status":"Success","reason":null,"counterexample":null,"logs":[],"kind":{"Unit":{"gas":31303}}},"test_Later()":{"status":"Skipped","reason":null,"counterexample":null,"logs":[],"kind":{"Unit":{"gas":0}}}},"warnings":[]}}"#;
+        let outcome = |test: &str, passed: bool, reason: Option<&str>| TestOutcome {
+            contract: "CounterTest".to_string(),
+            test: test.to_string(),
+            passed,
+            reason: reason.map(str::to_string),
+        };
+        assert_eq!(
+            parse_test_results(&format!("Compiling 1 files\n{output}\n")).expect("results"),
+            vec![
+                outcome(
+                    "testFuzz_Set",
+                    false,
+                    Some("panic: arithmetic underflow or overflow (0x11)")
+                ),
+                outcome("test_Increment", true, None),
+            ]
+        );
+        assert!(parse_test_results("Error: compilation failed").is_err());
+    }
+
+    #[test]
+    fn forge_test_args_anchor_names() {
+        assert_eq!(
+            forge_test_args("test/Counter.t.sol", "CounterTest", Some("test_Increment")),
+            vec![
+                "test",
+                "--json",
+                "--match-path",
+                "test/Counter.t.sol",
+                "--match-contract",
+                "^CounterTest$",
+                "--match-test",
+                "^test_Increment$",
+            ]
+        );
+    }
+}
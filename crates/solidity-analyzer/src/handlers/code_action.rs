@@ -1,21 +1,35 @@
-use std::collections::HashMap;
-
-use sa_ide::{CodeActionDiagnostic, SourceChange};
-use sa_span::lsp::from_lsp_range;
+use sa_ide::{AssistResolveStrategy, CodeActionDiagnostic};
+use sa_span::lsp::{PositionEncoding, from_lsp_range_in};
 use sa_vfs::VfsSnapshot;
+use serde::{Deserialize, Serialize};
 use tower_lsp::lsp_types::{
     CodeAction as LspCodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
-    NumberOrString, Url, WorkspaceEdit,
+    NumberOrString, Range, Url,
 };
 use tracing::debug;
 
-use super::text_edit_to_lsp;
+use super::{resolve_file_text, source_change_to_workspace_edit};
 use crate::lsp_utils;
 
+/// Payload stored in `CodeAction.data` for actions whose edits are computed on resolve.
+#[derive(Debug, Serialize, Deserialize)]
+struct CodeActionData {
+    uri: Url,
+    range: Range,
+    id: String,
+}
+
+/// Computes code actions for a document range.
+///
+/// When `lazy` is set, assists are returned without edits and carry a `data` payload that
+/// `code_action_resolve` uses to materialize the edit once the user picks them.
 pub fn code_action(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: CodeActionParams,
+    lazy: bool,
+    resource_operations: bool,
 ) -> Option<Vec<CodeActionOrCommand>> {
     let uri = &params.text_document.uri;
     let path = match lsp_utils::url_to_path(uri) {
@@ -32,13 +46,11 @@ pub fn code_action(
             return None;
         }
     };
-    let text = match vfs.file_text(file_id) {
-        Some(text) => text,
-        None => {
-            debug!(path = %path, file_id = ?file_id, "code_action: file text not found");
-            return None;
-        }
-    };
+    if vfs.file_text(file_id).is_none() {
+        debug!(path = %path, file_id = ?file_id, "code_action: file text not found");
+        return None;
+    }
+    let line_index = analysis.file_line_index(file_id);
 
     let diagnostics = params
         .context
@@ -50,75 +62,97 @@ pub fn code_action(
                 Some(NumberOrString::Number(code)) => Some(code.to_string()),
                 None => None,
             }?;
-            let range = from_lsp_range(diag.range, text)?;
+            let range = from_lsp_range_in(diag.range, &line_index, encoding)?;
             Some(CodeActionDiagnostic { range, code })
         })
         .collect::<Vec<_>>();
 
-    let actions = analysis.code_actions(file_id, &diagnostics);
+    let mut actions = analysis.code_actions(file_id, &diagnostics);
+    if let Some(range) = from_lsp_range_in(params.range, &line_index, encoding) {
+        let resolve = if lazy {
+            AssistResolveStrategy::None
+        } else {
+            AssistResolveStrategy::All
+        };
+        actions.extend(analysis.assists(file_id, range, &resolve));
+    }
     let mut results = Vec::new();
     for action in actions {
-        let edit = match source_change_to_workspace_edit(&action.edit, vfs) {
-            Some(edit) => edit,
-            None => continue,
+        let (edit, data) = match &action.edit {
+            Some(change) => {
+                match source_change_to_workspace_edit(
+                    change,
+                    analysis,
+                    vfs,
+                    encoding,
+                    resource_operations,
+                    "code_action",
+                ) {
+                    Some(edit) => (Some(edit), None),
+                    None => continue,
+                }
+            }
+            None => {
+                let data = CodeActionData {
+                    uri: uri.clone(),
+                    range: params.range,
+                    id: action.id.clone(),
+                };
+                (None, serde_json::to_value(data).ok())
+            }
         };
         results.push(CodeActionOrCommand::CodeAction(LspCodeAction {
             title: action.title,
-            kind: Some(CodeActionKind::QUICKFIX),
+            kind: Some(code_action_kind_to_lsp(action.kind)),
             diagnostics: None,
-            edit: Some(edit),
+            edit,
             command: None,
             is_preferred: None,
             disabled: None,
-            data: None,
+            data,
         }));
     }
 
     Some(results)
 }
 
-fn source_change_to_workspace_edit(
-    change: &SourceChange,
+/// Fills in the edit of a code action previously returned without one.
+pub fn code_action_resolve(
+    analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
-) -> Option<WorkspaceEdit> {
-    let mut changes = HashMap::new();
-    for file_edit in change.edits() {
-        let path = match vfs.path(file_edit.file_id) {
-            Some(path) => path,
-            None => {
-                debug!(target_file_id = ?file_edit.file_id, "code_action: missing target path");
-                continue;
-            }
-        };
-        let uri = match Url::from_file_path(path.as_str()) {
-            Ok(uri) => uri,
-            Err(()) => {
-                debug!(target_file_id = ?file_edit.file_id, path = %path, "code_action: invalid URI");
-                continue;
-            }
-        };
-        let text = match vfs.file_text(file_edit.file_id) {
-            Some(text) => text,
-            None => {
-                debug!(target_file_id = ?file_edit.file_id, path = %path, "code_action: missing text");
-                continue;
-            }
-        };
-        let lsp_edits = file_edit
-            .edits
-            .iter()
-            .map(|edit| text_edit_to_lsp(edit, text))
-            .collect::<Vec<_>>();
-        changes.insert(uri, lsp_edits);
+    encoding: PositionEncoding,
+    mut action: LspCodeAction,
+    resource_operations: bool,
+) -> Option<LspCodeAction> {
+    if action.edit.is_some() {
+        return Some(action);
     }
+    let data = action.data.take()?;
+    let data = match serde_json::from_value::<CodeActionData>(data) {
+        Ok(data) => data,
+        Err(error) => {
+            debug!(?error, "code_action_resolve: invalid data payload");
+            return None;
+        }
+    };
+    let (file_id, _) = resolve_file_text(vfs, &data.uri, "code_action_resolve")?;
+    let range = from_lsp_range_in(data.range, &analysis.file_line_index(file_id), encoding)?;
+    let resolved = analysis.resolve_assist(file_id, range, &data.id)?;
+    let change = resolved.edit?;
+    action.edit = Some(source_change_to_workspace_edit(
+        &change,
+        analysis,
+        vfs,
+        encoding,
+        resource_operations,
+        "code_action_resolve",
+    )?);
+    Some(action)
+}
 
-    if changes.is_empty() {
-        return None;
+fn code_action_kind_to_lsp(kind: sa_ide::CodeActionKind) -> CodeActionKind {
+    match kind {
+        sa_ide::CodeActionKind::QuickFix => CodeActionKind::QUICKFIX,
+        sa_ide::CodeActionKind::Generate => CodeActionKind::REFACTOR,
     }
-
-    Some(WorkspaceEdit {
-        changes: Some(changes),
-        document_changes: None,
-        change_annotations: None,
-    })
 }
@@ -1,5 +1,6 @@
 use sa_ide::{CompletionInsertTextFormat, CompletionItem, CompletionItemKind};
-use sa_span::lsp::{from_lsp_position, to_lsp_range};
+use sa_span::LineIndex;
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
 use tower_lsp::lsp_types::{
     CompletionItem as LspCompletionItem, CompletionItemKind as LspCompletionItemKind,
@@ -13,6 +14,7 @@ use crate::lsp_utils;
 pub fn completion(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: CompletionParams,
 ) -> Option<CompletionResponse> {
     let uri = &params.text_document_position.text_document.uri;
@@ -37,8 +39,9 @@ pub fn completion(
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
@@ -53,14 +56,18 @@ pub fn completion(
     let completions = analysis.completions(file_id, offset);
     let items = completions
         .into_iter()
-        .map(|item| completion_item_to_lsp(item, text))
+        .map(|item| completion_item_to_lsp(item, &line_index, encoding))
         .collect::<Vec<_>>();
 
     Some(CompletionResponse::Array(items))
 }
 
-fn completion_item_to_lsp(item: CompletionItem, text: &str) -> LspCompletionItem {
-    let range = to_lsp_range(item.replacement_range, text);
+fn completion_item_to_lsp(
+    item: CompletionItem,
+    line_index: &LineIndex,
+    encoding: PositionEncoding,
+) -> LspCompletionItem {
+    let range = to_lsp_range_in(item.replacement_range, line_index, encoding);
     let label = item.label;
     let insert_text = item.insert_text.clone().unwrap_or_else(|| label.clone());
     let insert_text_format = match item.insert_text_format {
@@ -0,0 +1,21 @@
+use sa_vfs::VfsSnapshot;
+
+use crate::handlers::resolve_file_text;
+use crate::lsp_ext::{self, ContractArtifactParams};
+
+pub fn contract_artifact(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    params: ContractArtifactParams,
+) -> Option<lsp_ext::ContractArtifact> {
+    let (file_id, _) = resolve_file_text(vfs, &params.text_document.uri, "contractArtifact")?;
+    let artifact = analysis.contract_artifact(file_id, &params.name)?;
+    Some(lsp_ext::ContractArtifact {
+        name: artifact.name().to_string(),
+        source: artifact.source().map(str::to_string),
+        abi: artifact.abi().clone(),
+        method_identifiers: artifact.method_identifiers().clone(),
+        deployed_bytecode_size: artifact.deployed_bytecode_size(),
+        compiler_version: artifact.compiler_version().map(str::to_string),
+    })
+}
@@ -0,0 +1,34 @@
+use sa_span::lsp::{PositionEncoding, from_lsp_range_in};
+use sa_vfs::VfsSnapshot;
+use tower_lsp::lsp_types::Url;
+
+use crate::handlers::resolve_file_text;
+use crate::lsp_ext::SyntaxTreeParams;
+
+pub fn view_hir(analysis: &sa_ide::Analysis, vfs: &VfsSnapshot, uri: &Url) -> Option<String> {
+    let (file_id, _) = resolve_file_text(vfs, uri, "viewHir")?;
+    analysis.hir_debug_dump(file_id)
+}
+
+pub fn view_def_map(analysis: &sa_ide::Analysis, vfs: &VfsSnapshot, uri: &Url) -> Option<String> {
+    let (file_id, _) = resolve_file_text(vfs, uri, "viewDefMap")?;
+    analysis.defmap_debug_dump(analysis.project_for_file(file_id))
+}
+
+pub fn syntax_tree(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: SyntaxTreeParams,
+) -> Option<String> {
+    let (file_id, _) = resolve_file_text(vfs, &params.text_document.uri, "syntaxTree")?;
+    let range = match params.range {
+        Some(range) => Some(from_lsp_range_in(
+            range,
+            &analysis.file_line_index(file_id),
+            encoding,
+        )?),
+        None => None,
+    };
+    Some(analysis.syntax_tree(file_id, range))
+}
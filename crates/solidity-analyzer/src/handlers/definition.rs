@@ -1,11 +1,7 @@
-use std::borrow::Cow;
-
 use sa_ide::Analysis;
-use sa_span::lsp::{from_lsp_position, to_lsp_range};
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
-use tower_lsp::lsp_types::{
-    GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink, Url,
-};
+use tower_lsp::lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink};
 use tracing::debug;
 
 use crate::lsp_utils;
@@ -13,6 +9,7 @@ use crate::lsp_utils;
 pub fn goto_definition(
     analysis: &Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: GotoDefinitionParams,
 ) -> Option<GotoDefinitionResponse> {
     let uri = &params.text_document_position_params.text_document.uri;
@@ -37,8 +34,9 @@ pub fn goto_definition(
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position_params.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
@@ -68,9 +66,9 @@ pub fn goto_definition(
             analysis.file_path(target.file_id).as_ref().clone()
         }
     };
-    let target_uri = match Url::from_file_path(target_path.as_str()) {
-        Ok(uri) => uri,
-        Err(()) => {
+    let target_uri = match lsp_utils::path_to_url(&target_path) {
+        Some(uri) => uri,
+        None => {
             debug!(
                 target_file_id = ?target.file_id,
                 target_path = %target_path,
@@ -79,21 +77,11 @@ pub fn goto_definition(
             return None;
         }
     };
-    let target_text = match vfs.file_text(target.file_id) {
-        Some(text) => Cow::Borrowed(text),
-        None => {
-            debug!(
-                target_file_id = ?target.file_id,
-                target_path = %target_path,
-                "goto_definition: target text missing in VFS, falling back to DB"
-            );
-            Cow::Owned(analysis.file_text(target.file_id).to_string())
-        }
-    };
-    let target_range = to_lsp_range(target.range, &target_text);
+    let target_index = analysis.file_line_index(target.file_id);
+    let target_range = to_lsp_range_in(target.range, &target_index, encoding);
 
     if let Some(origin_range) = target.origin_range {
-        let origin_range = to_lsp_range(origin_range, text);
+        let origin_range = to_lsp_range_in(origin_range, &line_index, encoding);
         let link = LocationLink {
             origin_selection_range: Some(origin_range),
             target_uri,
@@ -114,7 +102,9 @@ mod tests {
     use sa_ide::{AnalysisChange, AnalysisHost};
     use sa_paths::NormalizedPath;
     use sa_project_model::{FoundryProfile, FoundryWorkspace};
-    use sa_span::{TextRange, TextSize, lsp::to_lsp_position, lsp::to_lsp_range};
+    use sa_span::{
+        TextRange, TextSize, lsp::PositionEncoding, lsp::to_lsp_position, lsp::to_lsp_range,
+    };
     use sa_test_support::extract_offset;
     use sa_vfs::{Vfs, VfsChange};
     use tower_lsp::lsp_types::{
@@ -191,7 +181,7 @@ contract Child is Parent {
             partial_result_params: Default::default(),
         };
 
-        let response = goto_definition(&analysis, &snapshot, params);
+        let response = goto_definition(&analysis, &snapshot, PositionEncoding::Utf16, params);
         let location = match response {
             Some(GotoDefinitionResponse::Scalar(location)) => location,
             Some(GotoDefinitionResponse::Array(locations)) => {
@@ -1,5 +1,5 @@
 use sa_config::{ResolvedFoundryConfig, formatter_config};
-use sa_span::lsp::to_lsp_range;
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
 use tower_lsp::lsp_types::{
     DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier, TextDocumentEdit, TextEdit,
@@ -11,24 +11,34 @@ use super::resolve_file_text;
 pub fn format_on_save(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     uri: &Url,
     config: &ResolvedFoundryConfig,
 ) -> Option<WorkspaceEdit> {
-    let (file_id, text) = resolve_file_text(vfs, uri, "did_save")?;
+    let (file_id, _) = resolve_file_text(vfs, uri, "did_save")?;
 
     let formatter = formatter_config(config);
-    let edit = analysis.format_document(file_id, &formatter)?;
-    let lsp_edit = TextEdit {
-        range: to_lsp_range(edit.range, text),
-        new_text: edit.new_text,
-    };
+    let edits = analysis.format_document_edits(file_id, &formatter)?;
+    if edits.is_empty() {
+        return None;
+    }
+    let line_index = analysis.file_line_index(file_id);
+    let lsp_edits = edits
+        .into_iter()
+        .map(|edit| {
+            OneOf::Left(TextEdit {
+                range: to_lsp_range_in(edit.range, &line_index, encoding),
+                new_text: edit.new_text,
+            })
+        })
+        .collect();
 
     let document_edit = TextDocumentEdit {
         text_document: OptionalVersionedTextDocumentIdentifier {
             uri: uri.clone(),
             version: None,
         },
-        edits: vec![OneOf::Left(lsp_edit)],
+        edits: lsp_edits,
     };
 
     Some(WorkspaceEdit {
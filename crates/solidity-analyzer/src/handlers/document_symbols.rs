@@ -1,5 +1,6 @@
 use sa_ide::{SymbolInfo, SymbolKind};
-use sa_span::lsp::to_lsp_range;
+use sa_span::LineIndex;
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
 use tower_lsp::lsp_types::{DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse};
 use tracing::debug;
@@ -9,6 +10,7 @@ use crate::lsp_utils;
 pub fn document_symbols(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: DocumentSymbolParams,
 ) -> Option<DocumentSymbolResponse> {
     let uri = &params.text_document.uri;
@@ -26,24 +28,26 @@ pub fn document_symbols(
             return None;
         }
     };
-    let text = match vfs.file_text(file_id) {
-        Some(text) => text,
-        None => {
-            debug!(path = %path, file_id = ?file_id, "document_symbols: file text not found");
-            return None;
-        }
-    };
+    if vfs.file_text(file_id).is_none() {
+        debug!(path = %path, file_id = ?file_id, "document_symbols: file text not found");
+        return None;
+    }
 
     let symbols = analysis.document_symbols(file_id);
+    let index = analysis.file_line_index(file_id);
     let lsp_symbols = symbols
         .into_iter()
-        .map(|symbol| symbol_to_lsp(symbol, text))
+        .map(|symbol| symbol_to_lsp(symbol, &index, encoding))
         .collect::<Vec<_>>();
 
     Some(DocumentSymbolResponse::Nested(lsp_symbols))
 }
 
-fn symbol_to_lsp(symbol: SymbolInfo, text: &str) -> DocumentSymbol {
+fn symbol_to_lsp(
+    symbol: SymbolInfo,
+    index: &LineIndex,
+    encoding: PositionEncoding,
+) -> DocumentSymbol {
     let children = if symbol.children.is_empty() {
         None
     } else {
@@ -51,7 +55,7 @@ fn symbol_to_lsp(symbol: SymbolInfo, text: &str) -> DocumentSymbol {
             symbol
                 .children
                 .into_iter()
-                .map(|child| symbol_to_lsp(child, text))
+                .map(|child| symbol_to_lsp(child, index, encoding))
                 .collect::<Vec<_>>(),
         )
     };
@@ -63,8 +67,8 @@ fn symbol_to_lsp(symbol: SymbolInfo, text: &str) -> DocumentSymbol {
         kind: symbol_kind_to_lsp(symbol.kind),
         tags: None,
         deprecated: None,
-        range: to_lsp_range(symbol.range, text),
-        selection_range: to_lsp_range(symbol.selection_range, text),
+        range: to_lsp_range_in(symbol.range, index, encoding),
+        selection_range: to_lsp_range_in(symbol.selection_range, index, encoding),
         children,
     };
     symbol
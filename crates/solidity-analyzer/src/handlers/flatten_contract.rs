@@ -0,0 +1,17 @@
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in};
+use sa_vfs::VfsSnapshot;
+use tower_lsp::lsp_types::TextDocumentPositionParams;
+
+use crate::handlers::resolve_file_text;
+
+pub fn flatten_contract(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: TextDocumentPositionParams,
+) -> Option<String> {
+    let (file_id, _) = resolve_file_text(vfs, &params.text_document.uri, "flattenContract")?;
+    let line_index = analysis.file_line_index(file_id);
+    let offset = from_lsp_position_in(params.position, &line_index, encoding)?;
+    analysis.flatten_contract(file_id, offset)
+}
@@ -1,6 +1,7 @@
 use sa_config::{ResolvedFoundryConfig, formatter_config};
+use sa_span::lsp::{PositionEncoding, from_lsp_range_in};
 use sa_vfs::VfsSnapshot;
-use tower_lsp::lsp_types::{DocumentFormattingParams, TextEdit};
+use tower_lsp::lsp_types::{DocumentFormattingParams, DocumentRangeFormattingParams, TextEdit};
 use tracing::debug;
 
 use super::{resolve_file_text, text_edit_to_lsp};
@@ -8,6 +9,7 @@ use super::{resolve_file_text, text_edit_to_lsp};
 pub fn formatting(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: DocumentFormattingParams,
     config: Option<ResolvedFoundryConfig>,
 ) -> Option<Vec<TextEdit>> {
@@ -19,12 +21,48 @@ pub fn formatting(
             return None;
         }
     };
-    let (file_id, text) = resolve_file_text(vfs, uri, "formatting")?;
+    let (file_id, _) = resolve_file_text(vfs, uri, "formatting")?;
+    let line_index = analysis.file_line_index(file_id);
 
     let formatter = formatter_config(&config);
-    let edit = analysis.format_document(file_id, &formatter);
-    match edit {
-        Some(edit) => Some(vec![text_edit_to_lsp(&edit, text)]),
-        None => Some(Vec::new()),
-    }
+    let edits = analysis
+        .format_document_edits(file_id, &formatter)
+        .unwrap_or_default();
+    Some(
+        edits
+            .iter()
+            .map(|edit| text_edit_to_lsp(edit, &line_index, encoding))
+            .collect(),
+    )
+}
+
+pub fn range_formatting(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: DocumentRangeFormattingParams,
+    config: Option<ResolvedFoundryConfig>,
+) -> Option<Vec<TextEdit>> {
+    let uri = &params.text_document.uri;
+    let config = match config {
+        Some(config) => config,
+        None => {
+            debug!(%uri, "range_formatting: missing ResolvedFoundryConfig");
+            return None;
+        }
+    };
+    let (file_id, _) = resolve_file_text(vfs, uri, "range_formatting")?;
+    let line_index = analysis.file_line_index(file_id);
+    let range = from_lsp_range_in(params.range, &line_index, encoding)?;
+
+    let formatter = formatter_config(&config);
+    let edits = analysis
+        .format_range(file_id, range, &formatter)
+        .unwrap_or_default();
+    Some(
+        edits
+            .iter()
+            .map(|edit| text_edit_to_lsp(edit, &line_index, encoding))
+            .collect(),
+    )
 }
@@ -1,5 +1,5 @@
 use sa_ide::Analysis;
-use sa_span::lsp::{from_lsp_position, to_lsp_range};
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in, to_lsp_range_in};
 use sa_span::{TextRange, TextSize};
 use sa_vfs::VfsSnapshot;
 use tower_lsp::lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
@@ -7,7 +7,12 @@ use tracing::debug;
 
 use crate::lsp_utils;
 
-pub fn hover(analysis: &Analysis, vfs: &VfsSnapshot, params: HoverParams) -> Option<Hover> {
+pub fn hover(
+    analysis: &Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: HoverParams,
+) -> Option<Hover> {
     let uri = &params.text_document_position_params.text_document.uri;
     let path = match lsp_utils::url_to_path(uri) {
         Some(path) => path,
@@ -30,8 +35,9 @@ pub fn hover(analysis: &Analysis, vfs: &VfsSnapshot, params: HoverParams) -> Opt
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position_params.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
@@ -51,7 +57,8 @@ pub fn hover(analysis: &Analysis, vfs: &VfsSnapshot, params: HoverParams) -> Opt
         }
     };
 
-    let range = hover_range_in_bounds(hover.range, text).map(|range| to_lsp_range(range, text));
+    let range = hover_range_in_bounds(hover.range, text)
+        .map(|range| to_lsp_range_in(range, &line_index, encoding));
     Some(Hover {
         contents: HoverContents::Markup(MarkupContent {
             kind: MarkupKind::Markdown,
@@ -0,0 +1,64 @@
+use sa_ide::{ContractGraph, ContractKind};
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in};
+use sa_vfs::VfsSnapshot;
+use tracing::debug;
+
+use crate::handlers::{file_location, resolve_file_text};
+use crate::lsp_ext::{self, InheritanceGraphParams};
+
+pub fn inheritance_graph(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: InheritanceGraphParams,
+) -> Option<lsp_ext::InheritanceGraph> {
+    let (file_id, _) = resolve_file_text(vfs, &params.text_document.uri, "inheritanceGraph")?;
+    let offset = match params.position {
+        Some(position) => Some(from_lsp_position_in(
+            position,
+            &analysis.file_line_index(file_id),
+            encoding,
+        )?),
+        None => None,
+    };
+    let graph = analysis.contract_graph(file_id, offset)?;
+    Some(graph_to_lsp(analysis, vfs, encoding, graph))
+}
+
+fn graph_to_lsp(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    graph: ContractGraph,
+) -> lsp_ext::InheritanceGraph {
+    let nodes = graph
+        .nodes
+        .into_iter()
+        .map(|node| {
+            let location = file_location(analysis, vfs, node.file_id, node.range, encoding);
+            if location.is_none() {
+                debug!(file_id = ?node.file_id, name = %node.name, "inheritanceGraph: missing location");
+            }
+            lsp_ext::InheritanceNode {
+                name: node.name,
+                kind: match node.kind {
+                    ContractKind::Contract => lsp_ext::ContractKind::Contract,
+                    ContractKind::Interface => lsp_ext::ContractKind::Interface,
+                    ContractKind::Library => lsp_ext::ContractKind::Library,
+                },
+                is_abstract: node.is_abstract,
+                location,
+                linearization: node.linearization,
+            }
+        })
+        .collect();
+    let edges = graph
+        .edges
+        .into_iter()
+        .map(|edge| lsp_ext::InheritanceEdge {
+            derived: edge.derived,
+            base: edge.base,
+        })
+        .collect();
+    lsp_ext::InheritanceGraph { nodes, edges }
+}
@@ -0,0 +1,28 @@
+use sa_vfs::VfsSnapshot;
+
+use crate::lsp_ext;
+
+pub fn memory_usage(analysis: &sa_ide::Analysis, vfs: &VfsSnapshot) -> lsp_ext::MemoryUsage {
+    let usage = analysis.memory_usage(vfs);
+    lsp_ext::MemoryUsage {
+        total_bytes: usage.total_bytes(),
+        report: usage.to_string(),
+        caches: usage
+            .caches
+            .into_iter()
+            .map(|cache| lsp_ext::CacheStats {
+                name: cache.name.to_string(),
+                entries: cache.entries,
+            })
+            .collect(),
+        entries: usage
+            .entries
+            .into_iter()
+            .map(|entry| lsp_ext::MemoryUsageEntry {
+                name: entry.name.to_string(),
+                count: entry.count,
+                bytes: entry.bytes,
+            })
+            .collect(),
+    }
+}
@@ -1,14 +1,24 @@
 pub mod code_action;
 pub mod completion;
+pub mod contract_artifact;
+pub mod debug_dump;
 pub mod definition;
 pub mod did_save;
 pub mod document_symbols;
+pub mod flatten_contract;
 pub mod formatting;
 pub mod hover;
+pub mod inheritance_graph;
+pub mod memory_usage;
+pub mod project_structure;
 pub mod references;
 pub mod rename;
 pub mod signature_help;
+pub mod storage_layout;
+pub mod test_discovery;
 mod utils;
 pub mod workspace_symbols;
 
-pub(crate) use utils::{resolve_file_text, text_edit_to_lsp};
+pub(crate) use utils::{
+    file_location, resolve_file_text, source_change_to_workspace_edit, text_edit_to_lsp,
+};
@@ -0,0 +1,48 @@
+use sa_ide::ProjectStructure;
+use sa_paths::NormalizedPath;
+use sa_project_model::Remapping;
+
+use crate::lsp_ext;
+
+pub fn project_structure(analysis: &sa_ide::Analysis) -> Vec<lsp_ext::ProjectStructure> {
+    analysis
+        .project_ids()
+        .into_iter()
+        .filter_map(|project_id| analysis.project_structure(project_id))
+        .map(structure_to_lsp)
+        .collect()
+}
+
+fn structure_to_lsp(structure: ProjectStructure) -> lsp_ext::ProjectStructure {
+    lsp_ext::ProjectStructure {
+        root: structure.root.as_str().to_string(),
+        source_roots: paths_to_lsp(&structure.source_roots),
+        test_roots: paths_to_lsp(&structure.test_roots),
+        script_roots: paths_to_lsp(&structure.script_roots),
+        library_roots: paths_to_lsp(&structure.library_roots),
+        include_paths: paths_to_lsp(&structure.include_paths),
+        dependencies: structure
+            .dependencies
+            .iter()
+            .map(|package| lsp_ext::DependencyPackage {
+                name: package.name().to_string(),
+                version: package.version().map(str::to_string),
+                path: package.path().as_str().to_string(),
+                remapping: package.remapping().map(remapping_to_lsp),
+            })
+            .collect(),
+        profile: structure.profile,
+        solc_version: structure.solc_version,
+    }
+}
+
+fn paths_to_lsp(paths: &[NormalizedPath]) -> Vec<String> {
+    paths.iter().map(|path| path.as_str().to_string()).collect()
+}
+
+fn remapping_to_lsp(remapping: &Remapping) -> String {
+    match remapping.context() {
+        Some(context) => format!("{context}:{}={}", remapping.from(), remapping.to()),
+        None => format!("{}={}", remapping.from(), remapping.to()),
+    }
+}
@@ -1,7 +1,7 @@
 use sa_ide::Analysis;
-use sa_span::lsp::{from_lsp_position, to_lsp_range};
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
-use tower_lsp::lsp_types::{Location, ReferenceParams, Url};
+use tower_lsp::lsp_types::{Location, ReferenceParams};
 use tracing::debug;
 
 use crate::lsp_utils;
@@ -9,6 +9,7 @@ use crate::lsp_utils;
 pub fn references(
     analysis: &Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: ReferenceParams,
 ) -> Option<Vec<Location>> {
     let uri = &params.text_document_position.text_document.uri;
@@ -33,8 +34,9 @@ pub fn references(
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
@@ -57,29 +59,19 @@ pub fn references(
                 continue;
             }
         };
-        let target_uri = match Url::from_file_path(target_path.as_str()) {
-            Ok(uri) => uri,
-            Err(()) => {
-                debug!(
-                    target_file_id = ?reference.file_id(),
-                    target_path = %target_path,
-                    "references: failed to convert target path to URI"
-                );
-                continue;
-            }
-        };
-        let target_text = match vfs.file_text(reference.file_id()) {
-            Some(text) => text,
+        let target_uri = match lsp_utils::path_to_url(target_path) {
+            Some(uri) => uri,
             None => {
                 debug!(
                     target_file_id = ?reference.file_id(),
                     target_path = %target_path,
-                    "references: target text not found"
+                    "references: failed to convert target path to URI"
                 );
                 continue;
             }
         };
-        let target_range = to_lsp_range(reference.range(), target_text);
+        let target_index = analysis.file_line_index(reference.file_id());
+        let target_range = to_lsp_range_in(reference.range(), &target_index, encoding);
         locations.push(Location::new(target_uri, target_range));
     }
 
@@ -1,18 +1,17 @@
-use std::collections::HashMap;
-
-use sa_ide::SourceChange;
-use sa_span::lsp::from_lsp_position;
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in};
 use sa_vfs::VfsSnapshot;
-use tower_lsp::lsp_types::{RenameParams, Url, WorkspaceEdit};
+use tower_lsp::lsp_types::{RenameParams, WorkspaceEdit};
 use tracing::debug;
 
-use super::text_edit_to_lsp;
+use super::source_change_to_workspace_edit;
 use crate::lsp_utils;
 
 pub fn rename(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: RenameParams,
+    resource_operations: bool,
 ) -> Option<WorkspaceEdit> {
     let uri = &params.text_document_position.text_document.uri;
     let path = match lsp_utils::url_to_path(uri) {
@@ -36,8 +35,9 @@ pub fn rename(
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
@@ -51,51 +51,12 @@ pub fn rename(
     };
 
     let change = analysis.rename(file_id, offset, &params.new_name)?;
-    source_change_to_workspace_edit(change, vfs)
-}
-
-fn source_change_to_workspace_edit(
-    change: SourceChange,
-    vfs: &VfsSnapshot,
-) -> Option<WorkspaceEdit> {
-    let mut changes = HashMap::new();
-    for file_edit in change.edits() {
-        let path = match vfs.path(file_edit.file_id) {
-            Some(path) => path,
-            None => {
-                debug!(target_file_id = ?file_edit.file_id, "rename: missing target path");
-                continue;
-            }
-        };
-        let uri = match Url::from_file_path(path.as_str()) {
-            Ok(uri) => uri,
-            Err(()) => {
-                debug!(target_file_id = ?file_edit.file_id, path = %path, "rename: invalid URI");
-                continue;
-            }
-        };
-        let text = match vfs.file_text(file_edit.file_id) {
-            Some(text) => text,
-            None => {
-                debug!(target_file_id = ?file_edit.file_id, path = %path, "rename: missing text");
-                continue;
-            }
-        };
-        let lsp_edits = file_edit
-            .edits
-            .iter()
-            .map(|edit| text_edit_to_lsp(edit, text))
-            .collect::<Vec<_>>();
-        changes.insert(uri, lsp_edits);
-    }
-
-    if changes.is_empty() {
-        return None;
-    }
-
-    Some(WorkspaceEdit {
-        changes: Some(changes),
-        document_changes: None,
-        change_annotations: None,
-    })
+    source_change_to_workspace_edit(
+        &change,
+        analysis,
+        vfs,
+        encoding,
+        resource_operations,
+        "rename",
+    )
 }
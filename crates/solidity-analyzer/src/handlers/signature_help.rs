@@ -1,5 +1,5 @@
 use sa_ide::{ParameterInformation, SignatureHelp, SignatureInformation};
-use sa_span::lsp::from_lsp_position;
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in};
 use sa_vfs::VfsSnapshot;
 use tower_lsp::lsp_types::{
     Documentation, MarkupContent, MarkupKind, ParameterLabel, SignatureHelp as LspSignatureHelp,
@@ -12,6 +12,7 @@ use crate::lsp_utils;
 pub fn signature_help(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: SignatureHelpParams,
 ) -> Option<LspSignatureHelp> {
     let uri = &params.text_document_position_params.text_document.uri;
@@ -36,8 +37,9 @@ pub fn signature_help(
             return None;
         }
     };
+    let line_index = analysis.file_line_index(file_id);
     let position = params.text_document_position_params.position;
-    let offset = match from_lsp_position(position, text) {
+    let offset = match from_lsp_position_in(position, &line_index, encoding) {
         Some(offset) => offset,
         None => {
             debug!(
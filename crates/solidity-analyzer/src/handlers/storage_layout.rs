@@ -0,0 +1,73 @@
+use sa_ide::StorageUpgradeIssueKind;
+use sa_span::lsp::{PositionEncoding, from_lsp_position_in};
+use sa_vfs::VfsSnapshot;
+use tower_lsp::lsp_types::TextDocumentPositionParams;
+
+use crate::handlers::{file_location, resolve_file_text};
+use crate::lsp_ext;
+
+pub fn storage_layout(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: TextDocumentPositionParams,
+) -> Option<Vec<lsp_ext::StorageSlot>> {
+    let (file_id, _) = resolve_file_text(vfs, &params.text_document.uri, "storageLayout")?;
+    let line_index = analysis.file_line_index(file_id);
+    let offset = from_lsp_position_in(params.position, &line_index, encoding)?;
+    let layout = analysis.storage_layout(file_id, offset)?;
+    Some(
+        layout
+            .into_iter()
+            .map(|slot| lsp_ext::StorageSlot {
+                location: file_location(analysis, vfs, slot.file_id, slot.range, encoding),
+                name: slot.name,
+                contract: slot.contract,
+                slot: slot.slot,
+                offset: slot.offset,
+                ty: slot.ty,
+            })
+            .collect(),
+    )
+}
+
+pub fn storage_upgrade_check(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: lsp_ext::StorageUpgradeCheckParams,
+) -> Option<Vec<lsp_ext::StorageUpgradeIssue>> {
+    let current = params.text_document_position;
+    let (file_id, _) = resolve_file_text(vfs, &current.text_document.uri, "storageUpgradeCheck")?;
+    let line_index = analysis.file_line_index(file_id);
+    let offset = from_lsp_position_in(current.position, &line_index, encoding)?;
+    let previous = match params.previous {
+        Some(previous) => {
+            let (file_id, _) =
+                resolve_file_text(vfs, &previous.text_document.uri, "storageUpgradeCheck")?;
+            let line_index = analysis.file_line_index(file_id);
+            let offset = from_lsp_position_in(previous.position, &line_index, encoding)?;
+            Some((file_id, offset))
+        }
+        None => None,
+    };
+    let issues = analysis.storage_upgrade_issues(file_id, offset, previous)?;
+    Some(
+        issues
+            .into_iter()
+            .map(|issue| lsp_ext::StorageUpgradeIssue {
+                kind: match issue.kind {
+                    StorageUpgradeIssueKind::Moved => "moved",
+                    StorageUpgradeIssueKind::TypeChanged => "typeChanged",
+                    StorageUpgradeIssueKind::Removed => "removed",
+                    StorageUpgradeIssueKind::Overlaps => "overlaps",
+                    StorageUpgradeIssueKind::GapMismatch => "gapMismatch",
+                }
+                .to_string(),
+                location: file_location(analysis, vfs, issue.file_id, issue.range, encoding),
+                name: issue.name,
+                message: issue.message,
+            })
+            .collect(),
+    )
+}
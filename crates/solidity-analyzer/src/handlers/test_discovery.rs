@@ -0,0 +1,120 @@
+use sa_ide::TestKind;
+use sa_span::lsp::PositionEncoding;
+use sa_vfs::{FileId, VfsSnapshot};
+use tower_lsp::lsp_types::{CodeLens, CodeLensParams, Command, Range, Url};
+
+use crate::handlers::{file_location, resolve_file_text};
+use crate::lsp_ext::{self, DiscoverTestsParams, RunTestArguments};
+use crate::server::COMMAND_RUN_TEST;
+
+pub fn discover_tests(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: DiscoverTestsParams,
+) -> Vec<lsp_ext::TestContract> {
+    let file_ids = match params.text_document {
+        Some(document) => resolve_file_text(vfs, &document.uri, "discoverTests")
+            .map(|(file_id, _)| vec![file_id])
+            .unwrap_or_default(),
+        None => {
+            let mut files = vfs
+                .iter()
+                .filter(|(_, path)| path.as_str().ends_with(".t.sol"))
+                .collect::<Vec<_>>();
+            files.sort_by(|a, b| a.1.as_str().cmp(b.1.as_str()));
+            files.into_iter().map(|(file_id, _)| file_id).collect()
+        }
+    };
+    file_ids
+        .into_iter()
+        .flat_map(|file_id| test_contracts(analysis, vfs, encoding, file_id))
+        .collect()
+}
+
+/// A "Run tests" lens on every test contract and a "Run test" lens on every test function.
+pub fn code_lens(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    params: CodeLensParams,
+) -> Option<Vec<CodeLens>> {
+    let uri = params.text_document.uri;
+    let (file_id, _) = resolve_file_text(vfs, &uri, "codeLens")?;
+    let mut lenses = Vec::new();
+    for contract in test_contracts(analysis, vfs, encoding, file_id) {
+        lenses.push(run_test_lens(
+            &uri,
+            contract.location.range,
+            &contract.name,
+            None,
+        ));
+        for test in &contract.tests {
+            lenses.push(run_test_lens(
+                &uri,
+                test.location.range,
+                &contract.name,
+                Some(&test.name),
+            ));
+        }
+    }
+    Some(lenses)
+}
+
+fn test_contracts(
+    analysis: &sa_ide::Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    file_id: FileId,
+) -> Vec<lsp_ext::TestContract> {
+    analysis
+        .discover_tests(file_id)
+        .into_iter()
+        .filter_map(|contract| {
+            let tests = contract
+                .tests
+                .into_iter()
+                .filter_map(|test| {
+                    Some(lsp_ext::TestFunction {
+                        location: file_location(analysis, vfs, file_id, test.range, encoding)?,
+                        name: test.name,
+                        kind: match test.kind {
+                            TestKind::Test => lsp_ext::TestKind::Test,
+                            TestKind::Fuzz => lsp_ext::TestKind::Fuzz,
+                            TestKind::Invariant => lsp_ext::TestKind::Invariant,
+                        },
+                    })
+                })
+                .collect();
+            Some(lsp_ext::TestContract {
+                location: file_location(analysis, vfs, file_id, contract.range, encoding)?,
+                name: contract.name,
+                tests,
+            })
+        })
+        .collect()
+}
+
+fn run_test_lens(uri: &Url, range: Range, contract: &str, test: Option<&str>) -> CodeLens {
+    let arguments = RunTestArguments {
+        uri: uri.clone(),
+        contract: contract.to_string(),
+        test: test.map(str::to_string),
+    };
+    CodeLens {
+        range,
+        command: Some(Command {
+            title: if test.is_some() {
+                "Run test"
+            } else {
+                "Run tests"
+            }
+            .to_string(),
+            command: COMMAND_RUN_TEST.to_string(),
+            arguments: serde_json::to_value(arguments)
+                .ok()
+                .map(|value| vec![value]),
+        }),
+        data: None,
+    }
+}
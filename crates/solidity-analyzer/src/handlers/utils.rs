@@ -1,9 +1,17 @@
+use std::collections::HashMap;
+
+use sa_ide::{Analysis, FileSystemEdit, SourceChange};
+use sa_span::{LineIndex, TextRange};
 use sa_vfs::{FileId, VfsSnapshot};
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{
+    CreateFile, DocumentChangeOperation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Range, RenameFile, ResourceOp, TextDocumentEdit, Url,
+    WorkspaceEdit,
+};
 use tracing::debug;
 
 use crate::lsp_utils;
-use sa_span::lsp::to_lsp_range;
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
 
 pub(crate) fn resolve_file_text<'a>(
     vfs: &'a VfsSnapshot,
@@ -37,10 +45,214 @@ pub(crate) fn resolve_file_text<'a>(
 
 pub(crate) fn text_edit_to_lsp(
     edit: &sa_ide::TextEdit,
-    text: &str,
+    line_index: &LineIndex,
+    encoding: PositionEncoding,
 ) -> tower_lsp::lsp_types::TextEdit {
     tower_lsp::lsp_types::TextEdit {
-        range: to_lsp_range(edit.range, text),
+        range: to_lsp_range_in(edit.range, line_index, encoding),
         new_text: edit.new_text.clone(),
     }
 }
+
+/// The location of `range` in `file_id`, or `None` if the file has no path in `vfs`.
+pub(crate) fn file_location(
+    analysis: &Analysis,
+    vfs: &VfsSnapshot,
+    file_id: FileId,
+    range: TextRange,
+    encoding: PositionEncoding,
+) -> Option<Location> {
+    let path = vfs.path(file_id)?;
+    let uri = lsp_utils::path_to_url(path)?;
+    let line_index = analysis.file_line_index(file_id);
+    Some(Location::new(
+        uri,
+        to_lsp_range_in(range, &line_index, encoding),
+    ))
+}
+
+/// Converts `change` to a workspace edit. Text-only changes use `changes`; files created or
+/// moved need resource operations in `documentChanges`, so such a change yields `None` unless
+/// `resource_operations` says the client applies them.
+pub(crate) fn source_change_to_workspace_edit(
+    change: &SourceChange,
+    analysis: &Analysis,
+    vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
+    resource_operations: bool,
+    context: &str,
+) -> Option<WorkspaceEdit> {
+    let mut text_edits = Vec::new();
+    for file_edit in change.edits() {
+        let Some(uri) = file_uri(vfs, file_edit.file_id, context) else {
+            continue;
+        };
+        let line_index = analysis.file_line_index(file_edit.file_id);
+        let lsp_edits = file_edit
+            .edits
+            .iter()
+            .map(|edit| text_edit_to_lsp(edit, &line_index, encoding))
+            .collect::<Vec<_>>();
+        text_edits.push((uri, lsp_edits));
+    }
+
+    if change.file_system_edits().is_empty() {
+        if text_edits.is_empty() {
+            return None;
+        }
+        return Some(WorkspaceEdit {
+            changes: Some(text_edits.into_iter().collect::<HashMap<_, _>>()),
+            document_changes: None,
+            change_annotations: None,
+        });
+    }
+    if !resource_operations {
+        debug!(%context, "client does not support creating or renaming files");
+        return None;
+    }
+
+    // Text edits refer to files by their current URI, so they go before any file is moved.
+    let mut operations = text_edits
+        .into_iter()
+        .map(|(uri, edits)| document_edit(uri, edits))
+        .collect::<Vec<_>>();
+    for edit in change.file_system_edits() {
+        match edit {
+            FileSystemEdit::CreateFile { path, text } => {
+                let Some(uri) = lsp_utils::path_to_url(path) else {
+                    debug!(path = %path, %context, "invalid URI for new file");
+                    return None;
+                };
+                operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+                    CreateFile {
+                        uri: uri.clone(),
+                        options: None,
+                        annotation_id: None,
+                    },
+                )));
+                if !text.is_empty() {
+                    let insert = tower_lsp::lsp_types::TextEdit {
+                        range: Range::default(),
+                        new_text: text.clone(),
+                    };
+                    operations.push(document_edit(uri, vec![insert]));
+                }
+            }
+            FileSystemEdit::MoveFile {
+                file_id,
+                destination,
+            } => {
+                let old_uri = file_uri(vfs, *file_id, context)?;
+                let Some(new_uri) = lsp_utils::path_to_url(destination) else {
+                    debug!(path = %destination, %context, "invalid URI for moved file");
+                    return None;
+                };
+                operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+                    RenameFile {
+                        old_uri,
+                        new_uri,
+                        options: None,
+                        annotation_id: None,
+                    },
+                )));
+            }
+        }
+    }
+
+    Some(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+fn file_uri(vfs: &VfsSnapshot, file_id: FileId, context: &str) -> Option<Url> {
+    let Some(path) = vfs.path(file_id) else {
+        debug!(target_file_id = ?file_id, %context, "missing target path");
+        return None;
+    };
+    match lsp_utils::path_to_url(path) {
+        Some(uri) => Some(uri),
+        None => {
+            debug!(target_file_id = ?file_id, path = %path, %context, "invalid URI");
+            None
+        }
+    }
+}
+
+fn document_edit(uri: Url, edits: Vec<tower_lsp::lsp_types::TextEdit>) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sa_ide::{AnalysisChange, AnalysisHost, SourceChange, TextEdit};
+    use sa_paths::NormalizedPath;
+    use sa_span::{TextRange, TextSize, lsp::PositionEncoding};
+    use sa_vfs::{Vfs, VfsChange};
+    use tower_lsp::lsp_types::{DocumentChangeOperation, DocumentChanges, ResourceOp, Url};
+
+    use super::source_change_to_workspace_edit;
+
+    #[test]
+    fn file_operations_follow_text_edits_when_the_client_supports_them() {
+        let path = NormalizedPath::new("/workspace/src/Token.sol");
+        let mut vfs = Vfs::default();
+        vfs.apply_change(VfsChange::Set {
+            path: path.clone(),
+            text: Arc::from("contract Token {}"),
+        });
+        let snapshot = vfs.snapshot();
+        let file_id = snapshot.file_id(&path).expect("file id");
+        let mut host = AnalysisHost::new();
+        let mut analysis_change = AnalysisChange::new();
+        analysis_change.set_vfs(snapshot.clone());
+        host.apply_change(analysis_change);
+        let analysis = host.snapshot();
+
+        let mut change = SourceChange::default();
+        change.insert_edit(
+            file_id,
+            TextEdit {
+                range: TextRange::new(TextSize::from(9), TextSize::from(14)),
+                new_text: "Coin".to_string(),
+            },
+        );
+        change.create_file(
+            NormalizedPath::new("/workspace/src/ICoin.sol"),
+            "interface ICoin {}\n",
+        );
+        change.move_file(file_id, NormalizedPath::new("/workspace/src/Coin.sol"));
+
+        let encoding = PositionEncoding::Utf16;
+        assert_eq!(
+            source_change_to_workspace_edit(&change, &analysis, &snapshot, encoding, false, "test"),
+            None
+        );
+        let edit =
+            source_change_to_workspace_edit(&change, &analysis, &snapshot, encoding, true, "test")
+                .expect("workspace edit");
+        let Some(DocumentChanges::Operations(operations)) = edit.document_changes else {
+            panic!("expected resource operations");
+        };
+        let token = Url::from_file_path("/workspace/src/Token.sol").expect("uri");
+        let icoin = Url::from_file_path("/workspace/src/ICoin.sol").expect("uri");
+        assert!(matches!(
+            &operations[..],
+            [
+                DocumentChangeOperation::Edit(rename),
+                DocumentChangeOperation::Op(ResourceOp::Create(create)),
+                DocumentChangeOperation::Edit(contents),
+                DocumentChangeOperation::Op(ResourceOp::Rename(moved)),
+            ] if rename.text_document.uri == token
+                && create.uri == icoin
+                && contents.text_document.uri == icoin
+                && moved.old_uri == token
+        ));
+    }
+}
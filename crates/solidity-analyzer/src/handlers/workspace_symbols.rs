@@ -1,12 +1,15 @@
 use sa_def::DefKind;
-use sa_span::lsp::to_lsp_range;
+use sa_span::lsp::{PositionEncoding, to_lsp_range_in};
 use sa_vfs::VfsSnapshot;
-use tower_lsp::lsp_types::{Location, SymbolInformation, Url, WorkspaceSymbolParams};
+use tower_lsp::lsp_types::{Location, SymbolInformation, WorkspaceSymbolParams};
 use tracing::debug;
 
+use crate::lsp_utils;
+
 pub fn workspace_symbols(
     analysis: &sa_ide::Analysis,
     vfs: &VfsSnapshot,
+    encoding: PositionEncoding,
     params: WorkspaceSymbolParams,
 ) -> Option<Vec<SymbolInformation>> {
     let symbols = analysis.workspace_symbols(&params.query);
@@ -21,21 +24,15 @@ pub fn workspace_symbols(
                 continue;
             }
         };
-        let uri = match Url::from_file_path(path.as_str()) {
-            Ok(uri) => uri,
-            Err(()) => {
-                debug!(symbol_file_id = ?file_id, path = %path, "workspace_symbols: invalid uri");
-                continue;
-            }
-        };
-        let text = match vfs.file_text(file_id) {
-            Some(text) => text,
+        let uri = match lsp_utils::path_to_url(path) {
+            Some(uri) => uri,
             None => {
-                debug!(symbol_file_id = ?file_id, path = %path, "workspace_symbols: missing text");
+                debug!(symbol_file_id = ?file_id, path = %path, "workspace_symbols: invalid uri");
                 continue;
             }
         };
-        let range = to_lsp_range(symbol.range(), text);
+        let line_index = analysis.file_line_index(file_id);
+        let range = to_lsp_range_in(symbol.range(), &line_index, encoding);
         #[allow(deprecated)]
         let info = SymbolInformation {
             name: symbol.name().to_string(),
@@ -54,7 +51,7 @@ pub fn workspace_symbols(
 fn symbol_kind_to_lsp(kind: DefKind) -> tower_lsp::lsp_types::SymbolKind {
     match kind {
         DefKind::Contract => tower_lsp::lsp_types::SymbolKind::CLASS,
-        DefKind::Function => tower_lsp::lsp_types::SymbolKind::FUNCTION,
+        DefKind::Function | DefKind::YulFunction => tower_lsp::lsp_types::SymbolKind::FUNCTION,
         DefKind::Struct => tower_lsp::lsp_types::SymbolKind::STRUCT,
         DefKind::Enum => tower_lsp::lsp_types::SymbolKind::ENUM,
         DefKind::Event => tower_lsp::lsp_types::SymbolKind::EVENT,
@@ -62,5 +59,7 @@ fn symbol_kind_to_lsp(kind: DefKind) -> tower_lsp::lsp_types::SymbolKind {
         DefKind::Modifier => tower_lsp::lsp_types::SymbolKind::METHOD,
         DefKind::Variable => tower_lsp::lsp_types::SymbolKind::VARIABLE,
         DefKind::Udvt => tower_lsp::lsp_types::SymbolKind::TYPE_PARAMETER,
+        DefKind::Field => tower_lsp::lsp_types::SymbolKind::FIELD,
+        DefKind::Variant => tower_lsp::lsp_types::SymbolKind::ENUM_MEMBER,
     }
 }
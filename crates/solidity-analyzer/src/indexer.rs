@@ -1,6 +1,10 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::UNIX_EPOCH;
 
 use anyhow::Context;
 use foundry_compilers::{
@@ -14,10 +18,14 @@ use sa_project_model::{
     FoundryResolver, FoundryWorkspace, Remapping, ResolvedImport, project_paths_from_config,
     resolve_import_path_with_resolver,
 };
+use serde_json::Value;
 use tracing::{debug, warn};
 
 use crate::lsp_utils;
 
+/// Forge's record of every compiled source file and its resolved imports.
+pub const SOLIDITY_FILES_CACHE: &str = "solidity-files-cache.json";
+
 /// An indexed file with its path and contents.
 #[derive(Debug, Clone)]
 pub struct IndexedFile {
@@ -43,17 +51,56 @@ pub fn index_workspace(
     workspace: &FoundryWorkspace,
     remappings: &[Remapping],
 ) -> anyhow::Result<IndexResult> {
-    let mut result = IndexResult::default();
+    let files_cache = Path::new(workspace.root().as_str())
+        .join("cache")
+        .join(SOLIDITY_FILES_CACHE);
+    index_workspace_with_files_cache(workspace, remappings, &files_cache, &|_, _| {})
+}
+
+/// Indexes the workspace, seeding the file set and import edges from forge's files cache at
+/// `files_cache` when it is readable. Otherwise every source file is parsed up front.
+///
+/// `progress` is called with the number of files indexed so far and the number known; the
+/// total grows as imports are discovered.
+pub fn index_workspace_with_files_cache(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+    files_cache: &Path,
+    progress: &dyn Fn(usize, usize),
+) -> anyhow::Result<IndexResult> {
     let paths = project_paths_from_config(workspace, remappings)
         .with_context(|| "indexer: failed to build project paths")?;
     let sol_paths = paths.with_language::<SolcLanguage>();
-    let sources = read_input_files_lenient(&sol_paths);
-    let graph = Graph::<SolParser>::resolve_sources(&sol_paths, sources)
+    if let Some(cache) = read_files_cache(Path::new(workspace.root().as_str()), files_cache) {
+        debug!(path = %files_cache.display(), files = cache.len(), "indexer: using files cache");
+        return index_from_files_cache(workspace, remappings, &sol_paths, cache, progress);
+    }
+    index_from_graph(workspace, &sol_paths, progress)
+}
+
+fn index_from_graph(
+    workspace: &FoundryWorkspace,
+    sol_paths: &ProjectPathsConfig<SolcLanguage>,
+    progress: &dyn Fn(usize, usize),
+) -> anyhow::Result<IndexResult> {
+    let mut result = IndexResult::default();
+    let mut sources = read_input_files_lenient(sol_paths, workspace);
+    for file in workspace.index_filter().included_files(workspace.root()) {
+        if !sources.contains_key(&file) {
+            read_source_lenient(&mut sources, &file);
+        }
+    }
+    let graph = Graph::<SolParser>::resolve_sources(sol_paths, sources)
         .with_context(|| "indexer: failed to resolve workspace sources")?;
 
-    for node in &graph.nodes {
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        progress(idx + 1, graph.nodes.len());
+        let path = lsp_utils::normalize_path(node.path());
+        if workspace.is_excluded(&path) {
+            continue;
+        }
         result.files.push(IndexedFile {
-            path: NormalizedPath::new(node.path().to_string_lossy()),
+            path,
             text: node.content().to_string(),
         });
     }
@@ -61,9 +108,209 @@ pub fn index_workspace(
     result
         .files
         .sort_by(|a, b| a.path.as_str().cmp(b.path.as_str()));
+    // A file reached through a symlink and at its real path is indexed once.
+    result.files.dedup_by(|a, b| a.path == b.path);
     Ok(result)
 }
 
+/// A files cache entry: the modification time forge saw and the imports it resolved.
+#[derive(Debug)]
+struct CachedFile {
+    modified: Option<u64>,
+    imports: Vec<PathBuf>,
+}
+
+impl CachedFile {
+    fn is_fresh(&self, path: &Path) -> bool {
+        self.modified.is_some() && self.modified == modified_millis(path)
+    }
+}
+
+/// Walks the import graph starting from the cached files and the project's own sources, reusing
+/// cached import edges for files that have not changed since forge last compiled them. Only new
+/// or modified files are parsed.
+fn index_from_files_cache(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+    sol_paths: &ProjectPathsConfig<SolcLanguage>,
+    cache: HashMap<PathBuf, CachedFile>,
+    progress: &dyn Fn(usize, usize),
+) -> anyhow::Result<IndexResult> {
+    let resolver = FoundryResolver::new(workspace, remappings)?;
+    let mut seeds = cache.keys().cloned().collect::<Vec<_>>();
+    seeds.sort();
+    seeds.extend(sol_paths.input_files_iter());
+    seeds.extend(workspace.index_filter().included_files(workspace.root()));
+
+    Ok(walk_imports(
+        workspace,
+        seeds,
+        |file, path, text| match cache.get(file).filter(|cached| cached.is_fresh(file)) {
+            Some(cached) => cached.imports.clone(),
+            None => import_files(workspace, remappings, &resolver, path, text),
+        },
+        progress,
+    ))
+}
+
+/// Indexes only `seeds` and the files they reach through imports, for lazy indexing.
+pub fn index_reachable(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+    seeds: Vec<PathBuf>,
+    progress: &dyn Fn(usize, usize),
+) -> anyhow::Result<IndexResult> {
+    let resolver = FoundryResolver::new(workspace, remappings)?;
+    Ok(walk_imports(
+        workspace,
+        seeds,
+        |_, path, text| import_files(workspace, remappings, &resolver, path, text),
+        progress,
+    ))
+}
+
+/// Walks the import graph breadth first from `seeds`, reading and parsing each level of the walk
+/// in parallel. `imports` lists the files a file imports given its path and contents.
+fn walk_imports(
+    workspace: &FoundryWorkspace,
+    seeds: Vec<PathBuf>,
+    imports: impl Fn(&Path, &NormalizedPath, &str) -> Vec<PathBuf> + Sync,
+    progress: &dyn Fn(usize, usize),
+) -> IndexResult {
+    let mut result = IndexResult::default();
+    let mut seen = HashSet::new();
+    let mut frontier = seeds;
+
+    while !frontier.is_empty() {
+        let level = frontier
+            .into_iter()
+            .filter_map(|file| {
+                let path = lsp_utils::normalize_path(&file);
+                (!workspace.is_excluded(&path) && seen.insert(path.clone())).then_some((file, path))
+            })
+            .collect::<Vec<_>>();
+        let indexed = parallel_map(&level, |(file, path)| {
+            let text = read_file(file)?;
+            let imports = imports(file, path, &text);
+            Some((
+                IndexedFile {
+                    path: path.clone(),
+                    text,
+                },
+                imports,
+            ))
+        });
+
+        frontier = Vec::new();
+        for (file, imports) in indexed.into_iter().flatten() {
+            result.files.push(file);
+            frontier.extend(imports);
+        }
+        progress(result.files.len(), result.files.len() + frontier.len());
+    }
+
+    result
+        .files
+        .sort_by(|a, b| a.path.as_str().cmp(b.path.as_str()));
+    result
+}
+
+fn import_files(
+    workspace: &FoundryWorkspace,
+    remappings: &[Remapping],
+    resolver: &FoundryResolver,
+    path: &NormalizedPath,
+    text: &str,
+) -> Vec<PathBuf> {
+    resolved_import_paths(workspace, remappings, resolver, path, text, true)
+        .into_iter()
+        .map(|import| PathBuf::from(import.as_str()))
+        .filter(|import| import.is_file())
+        .collect()
+}
+
+/// Maps `items` on a scoped worker per available core, returning the results in item order
+/// whichever worker produced them.
+fn parallel_map<T, R>(items: &[T], map: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+    if workers <= 1 {
+        return items.iter().map(map).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break done;
+                        };
+                        done.push((index, map(item)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Vec<_>>()
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Reads forge's `solidity-files-cache.json`, keeping only entries whose files still exist.
+/// Paths in the cache are relative to `root` unless they are absolute.
+fn read_files_cache(root: &Path, files_cache: &Path) -> Option<HashMap<PathBuf, CachedFile>> {
+    let text = fs::read_to_string(files_cache).ok()?;
+    let cache = match serde_json::from_str::<Value>(&text) {
+        Ok(cache) => cache,
+        Err(error) => {
+            debug!(?error, path = %files_cache.display(), "indexer: invalid files cache");
+            return None;
+        }
+    };
+    let files = cache.get("files")?.as_object()?;
+
+    let mut entries = HashMap::new();
+    for (file, entry) in files {
+        let path = root.join(file);
+        if !path.is_file() {
+            continue;
+        }
+        let imports = entry
+            .get("imports")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|import| root.join(import))
+            .filter(|import| import.is_file())
+            .collect();
+        let modified = entry.get("lastModificationDate").and_then(Value::as_u64);
+        entries.insert(path, CachedFile { modified, imports });
+    }
+    Some(entries)
+}
+
+fn modified_millis(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let millis = modified.duration_since(UNIX_EPOCH).ok()?.as_millis();
+    u64::try_from(millis).ok()
+}
+
 pub fn index_open_file_imports(
     workspace: &FoundryWorkspace,
     remappings: &[Remapping],
@@ -85,19 +332,16 @@ pub fn index_open_file_imports(
 
         let text = match text_override {
             Some(text) => text,
-            None => match fs::read_to_string(path.as_str()) {
-                Ok(text) => text,
-                Err(error) => {
-                    debug!(?error, path = %path, "indexer: failed to read file");
-                    continue;
-                }
+            None => match read_file(Path::new(path.as_str())) {
+                Some(text) => text,
+                None => continue,
             },
         };
 
         for resolved in resolved_import_paths(workspace, remappings, &resolver, &path, &text, true)
         {
             let resolved = lsp_utils::normalize_path(Path::new(resolved.as_str()));
-            if !PathBuf::from(resolved.as_str()).is_file() {
+            if !PathBuf::from(resolved.as_str()).is_file() || workspace.is_excluded(&resolved) {
                 continue;
             }
             if !seen.contains(&resolved) {
@@ -175,35 +419,61 @@ fn resolved_imports_with_resolver(
         .collect()
 }
 
-fn read_input_files_lenient<Lang>(paths: &ProjectPathsConfig<Lang>) -> Sources
+fn read_input_files_lenient<Lang>(
+    paths: &ProjectPathsConfig<Lang>,
+    workspace: &FoundryWorkspace,
+) -> Sources
 where
     Lang: Language,
 {
-    let mut sources = Sources::new();
+    let files = paths
+        .input_files_iter()
+        .filter(|file| !workspace.is_excluded(&NormalizedPath::new(file.to_string_lossy())))
+        .collect::<Vec<_>>();
+    let texts = parallel_map(&files, |file| read_file(file));
 
-    for file in paths.input_files_iter() {
-        match Source::read(&file) {
-            Ok(source) => {
-                sources.insert(file.to_path_buf(), source);
-            }
-            Err(error) => {
-                warn!(?error, path = %file.display(), "indexer: failed to read input file");
+    files
+        .into_iter()
+        .zip(texts)
+        .filter_map(|(file, text)| Some((file, Source::new(text?))))
+        .collect()
+}
+
+fn read_source_lenient(sources: &mut Sources, file: &Path) {
+    if let Some(text) = read_file(file) {
+        sources.insert(file.to_path_buf(), Source::new(text));
+    }
+}
+
+/// Reads a source file the way the VFS decodes it, so files with a BOM or invalid UTF-8 are
+/// indexed rather than skipped.
+fn read_file(file: &Path) -> Option<String> {
+    match sa_vfs::read_text(file) {
+        Ok(decoded) => {
+            if decoded.lossy {
+                warn!(path = %file.display(), "indexer: replaced invalid UTF-8 in file");
             }
+            Some(decoded.text)
+        }
+        Err(error) => {
+            debug!(?error, path = %file.display(), "indexer: failed to read file");
+            None
         }
     }
-
-    sources
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
     use std::fs;
 
     use sa_paths::NormalizedPath;
-    use sa_project_model::{FoundryResolver, FoundryWorkspace, Remapping, ResolvedImport};
+    use sa_project_model::{
+        FoundryResolver, FoundryWorkspace, IndexFilter, Remapping, ResolvedImport,
+    };
     use tempfile::tempdir;
 
-    use super::index_workspace;
+    use super::{index_workspace, index_workspace_with_files_cache};
 
     fn result_contains_path(result: &super::IndexResult, path: &NormalizedPath) -> bool {
         result.paths().any(|p| p == path)
@@ -294,6 +564,232 @@ contract Unused {}
         assert_eq!(result.files[0].text, main_text);
     }
 
+    #[test]
+    fn reachable_indexing_skips_files_no_seed_imports() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::create_dir_all(root.join("lib/dep/src")).expect("lib dir");
+        fs::write(
+            root.join("src/Main.sol"),
+            "import \"dep/Dep.sol\";\ncontract Main {}",
+        )
+        .expect("write main");
+        fs::write(root.join("src/Other.sol"), "contract Other {}").expect("write other");
+        fs::write(root.join("lib/dep/src/Dep.sol"), "contract Dep {}").expect("write dep");
+        fs::write(root.join("lib/dep/src/Unused.sol"), "contract Unused {}").expect("write unused");
+
+        let workspace = FoundryWorkspace::new(NormalizedPath::new(root.to_string_lossy()));
+        let remappings = vec![Remapping::new("dep/", "lib/dep/src/")];
+        let result = super::index_reachable(
+            &workspace,
+            &remappings,
+            vec![root.join("src/Main.sol")],
+            &|_, _| {},
+        )
+        .expect("index reachable");
+
+        let paths = result
+            .paths()
+            .map(|path| path.as_str().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                root.join("lib/dep/src/Dep.sol")
+                    .to_string_lossy()
+                    .into_owned(),
+                root.join("src/Main.sol").to_string_lossy().into_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parallel_map_keeps_item_order() {
+        let items = (0..1000).collect::<Vec<u32>>();
+        let doubled = super::parallel_map(&items, |item| item * 2);
+        assert_eq!(
+            doubled,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn indexer_reports_progress_up_to_the_file_count() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::write(root.join("src/A.sol"), "import \"./B.sol\";\ncontract A {}").expect("write a");
+        fs::write(root.join("src/B.sol"), "contract B {}").expect("write b");
+
+        let workspace = FoundryWorkspace::new(NormalizedPath::new(root.to_string_lossy()));
+        let reports = RefCell::new(Vec::new());
+        let result = index_workspace_with_files_cache(
+            &workspace,
+            &[],
+            &root.join("cache").join(super::SOLIDITY_FILES_CACHE),
+            &|done, total| reports.borrow_mut().push((done, total)),
+        )
+        .expect("index workspace");
+
+        let reports = reports.into_inner();
+        assert_eq!(result.files.len(), 2);
+        assert_eq!(reports.last(), Some(&(2, 2)));
+        assert!(reports.iter().all(|(done, total)| done <= total));
+    }
+
+    #[test]
+    fn indexer_honors_include_and_exclude_globs() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src/generated")).expect("generated dir");
+        fs::create_dir_all(root.join("extra")).expect("extra dir");
+        fs::write(root.join("src/Main.sol"), "contract Main {}").expect("write main");
+        fs::write(root.join("src/generated/Types.sol"), "contract Types {}")
+            .expect("write generated");
+        fs::write(root.join("extra/Extra.sol"), "contract Extra {}").expect("write extra");
+
+        let root_path = NormalizedPath::new(root.to_string_lossy());
+        let filter = IndexFilter::new(vec!["extra".to_string()], vec!["src/generated".to_string()])
+            .expect("index filter");
+        let workspace = FoundryWorkspace::new(root_path).with_index_filter(filter);
+
+        let result = index_workspace(&workspace, &[]).expect("index workspace");
+        let mut paths = result
+            .paths()
+            .map(|path| path.as_str().to_string())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                root.join("extra/Extra.sol").to_string_lossy().to_string(),
+                root.join("src/Main.sol").to_string_lossy().to_string(),
+            ]
+        );
+    }
+
+    fn write_files_cache(root: &std::path::Path, modified: u64) {
+        let cache = serde_json::json!({
+            "_format": "ethers-rs-sol-cache-4",
+            "files": {
+                "src/Main.sol": {
+                    "lastModificationDate": modified,
+                    "sourceName": "src/Main.sol",
+                    "imports": ["lib/dep/Dep.sol"],
+                },
+                "lib/dep/Dep.sol": {
+                    "lastModificationDate": 0,
+                    "sourceName": "lib/dep/Dep.sol",
+                    "imports": [],
+                },
+                "src/Deleted.sol": {
+                    "lastModificationDate": 0,
+                    "sourceName": "src/Deleted.sol",
+                    "imports": [],
+                },
+            },
+        });
+        fs::create_dir_all(root.join("cache")).expect("cache dir");
+        fs::write(
+            root.join("cache").join(super::SOLIDITY_FILES_CACHE),
+            cache.to_string(),
+        )
+        .expect("write files cache");
+    }
+
+    #[test]
+    fn indexer_reuses_import_edges_from_files_cache() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::create_dir_all(root.join("lib/dep")).expect("dep dir");
+        fs::create_dir_all(root.join("lib/unused")).expect("unused dir");
+        // Unresolvable without remappings, so Dep.sol can only be found through the cache.
+        fs::write(
+            root.join("src/Main.sol"),
+            "import \"dep/Dep.sol\";\ncontract Main {}",
+        )
+        .expect("write main");
+        fs::write(root.join("src/New.sol"), "contract New {}").expect("write new");
+        fs::write(root.join("lib/dep/Dep.sol"), "contract Dep {}").expect("write dep");
+        fs::write(root.join("lib/unused/Unused.sol"), "contract Unused {}").expect("write unused");
+        let modified = super::modified_millis(&root.join("src/Main.sol")).expect("mtime");
+        write_files_cache(&root, modified);
+
+        let workspace = FoundryWorkspace::new(NormalizedPath::new(root.to_string_lossy()));
+        let result = index_workspace(&workspace, &[]).expect("index workspace");
+        let mut paths = result
+            .paths()
+            .map(|path| path.as_str().to_string())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                root.join("lib/dep/Dep.sol").to_string_lossy().to_string(),
+                root.join("src/Main.sol").to_string_lossy().to_string(),
+                root.join("src/New.sol").to_string_lossy().to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn indexer_reparses_files_changed_since_the_files_cache() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::create_dir_all(root.join("lib/dep")).expect("dep dir");
+        fs::write(root.join("src/Main.sol"), "contract Main {}").expect("write main");
+        fs::write(root.join("lib/dep/Dep.sol"), "contract Dep {}").expect("write dep");
+        write_files_cache(&root, 0);
+
+        let workspace = FoundryWorkspace::new(NormalizedPath::new(root.to_string_lossy()));
+        let result = index_workspace(&workspace, &[]).expect("index workspace");
+
+        assert!(result_contains_path(
+            &result,
+            &NormalizedPath::new(root.join("src/Main.sol").to_string_lossy())
+        ));
+        // Main.sol is reparsed because its entry is stale; the cached files stay in the index.
+        assert!(result_contains_path(
+            &result,
+            &NormalizedPath::new(root.join("lib/dep/Dep.sol").to_string_lossy())
+        ));
+        assert!(!result_contains_path(
+            &result,
+            &NormalizedPath::new(root.join("src/Deleted.sol").to_string_lossy())
+        ));
+    }
+
+    #[test]
+    fn indexer_falls_back_to_the_graph_on_an_invalid_files_cache() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::create_dir_all(root.join("cache")).expect("cache dir");
+        fs::write(root.join("src/Main.sol"), "contract Main {}").expect("write main");
+        fs::write(
+            root.join("cache").join(super::SOLIDITY_FILES_CACHE),
+            "{ not json",
+        )
+        .expect("write files cache");
+
+        let workspace = FoundryWorkspace::new(NormalizedPath::new(root.to_string_lossy()));
+        let result = index_workspace(&workspace, &[]).expect("index workspace");
+
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].text, "contract Main {}");
+    }
+
     #[test]
     fn indexer_handles_unresolved_imports_without_failing() {
         let temp = tempdir().expect("tempdir");
@@ -355,18 +851,25 @@ contract Main { Dep dep; }
         ));
     }
 
+    #[cfg(unix)]
     #[test]
-    fn indexer_skips_unreadable_sources() {
+    fn indexer_uses_the_real_path_of_symlinked_dependencies() {
         let temp = tempdir().expect("tempdir");
         let root = temp.path().canonicalize().expect("canonicalize root");
 
         fs::create_dir_all(root.join("src")).expect("src dir");
+        fs::create_dir_all(root.join("vendor/dep")).expect("vendor dir");
+        fs::create_dir_all(root.join("lib")).expect("lib dir");
+        std::os::unix::fs::symlink(root.join("vendor/dep"), root.join("lib/dep"))
+            .expect("symlink dep");
 
-        let good_path = root.join("src/Good.sol");
-        fs::write(&good_path, "contract Good {}").expect("write good");
-
-        let bad_path = root.join("src/Bad.sol");
-        fs::write(&bad_path, [0xff, 0xfe, 0xfd]).expect("write bad");
+        let dep_path = root.join("vendor/dep/Dep.sol");
+        fs::write(&dep_path, "contract Dep {}").expect("write dep");
+        let main_text = r#"
+import "../lib/dep/Dep.sol";
+contract Main { Dep dep; }
+"#;
+        fs::write(root.join("src/Main.sol"), main_text).expect("write main");
 
         let root_path = NormalizedPath::new(root.to_string_lossy());
         let remappings = Vec::new();
@@ -376,12 +879,47 @@ contract Main { Dep dep; }
 
         assert!(result_contains_path(
             &result,
-            &NormalizedPath::new(good_path.to_string_lossy())
+            &NormalizedPath::new(dep_path.to_string_lossy())
         ));
         assert!(!result_contains_path(
             &result,
-            &NormalizedPath::new(bad_path.to_string_lossy())
+            &NormalizedPath::new(root.join("lib/dep/Dep.sol").to_string_lossy())
         ));
+        assert_eq!(result.files.len(), 2);
+    }
+
+    #[test]
+    fn indexer_decodes_bom_and_invalid_utf8_sources() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+
+        fs::create_dir_all(root.join("src")).expect("src dir");
+
+        let bom_path = root.join("src/Bom.sol");
+        fs::write(&bom_path, b"\xEF\xBB\xBFcontract Bom {}\r\n").expect("write bom");
+
+        let bad_path = root.join("src/Bad.sol");
+        fs::write(&bad_path, [0xff, 0xfe, 0xfd]).expect("write bad");
+
+        let root_path = NormalizedPath::new(root.to_string_lossy());
+        let remappings = Vec::new();
+        let workspace = FoundryWorkspace::new(root_path);
+
+        let result = index_workspace(&workspace, &remappings).expect("index workspace");
+        let text_of = |path: &std::path::Path| {
+            let path = NormalizedPath::new(path.to_string_lossy());
+            result
+                .files
+                .iter()
+                .find(|file| file.path == path)
+                .map(|file| file.text.clone())
+        };
+
+        assert_eq!(text_of(&bom_path).as_deref(), Some("contract Bom {}\r\n"));
+        assert_eq!(
+            text_of(&bad_path).as_deref(),
+            Some("\u{FFFD}\u{FFFD}\u{FFFD}")
+        );
     }
 
     #[test]
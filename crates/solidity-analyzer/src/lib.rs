@@ -1,31 +1,121 @@
-use tracing_subscriber::EnvFilter;
+use std::path::Path;
 
+use sa_paths::NormalizedPath;
+use tower_lsp::LspServiceBuilder;
+use tower_lsp::lsp_types::request::Request;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+pub mod cli;
 mod config;
 mod diagnostics;
 mod document;
+mod forge_test;
 mod handlers;
 mod indexer;
 pub mod lsp_ext;
 mod lsp_utils;
+mod onchain;
 mod profile;
+mod progress;
 mod server;
 mod state;
 mod status;
 mod task_pool;
+mod timing;
 mod workspace;
 
 pub use server::Server;
 
 pub fn init_tracing() {
+    init_tracing_with_timings(false);
+}
+
+/// Like [`init_tracing`], also printing a timing summary of every request to stderr when
+/// `print_time` is set (`--print-time`) or `SA_PRINT_TIME` is.
+pub fn init_tracing_with_timings(print_time: bool) {
     // Called in normal startup; Server::new also calls for test/harness coverage.
     // init_from_env is idempotent, so this is safe to repeat.
     profile::init_from_env();
+    let print_time = print_time
+        || std::env::var("SA_PRINT_TIME").is_ok_and(|value| !matches!(value.trim(), "" | "0"));
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    let _ = tracing_subscriber::fmt()
-        .with_env_filter(filter)
+    let fmt = tracing_subscriber::fmt::layer()
         .with_writer(std::io::stderr)
         .with_ansi(false)
-        .try_init();
+        .with_filter(filter);
+    // The summary needs every span, so the log filter only applies to the log output.
+    let _ = if print_time {
+        tracing_subscriber::registry()
+            .with(fmt)
+            .with(timing::TimingLayer::new(std::io::stderr))
+            .try_init()
+    } else {
+        tracing_subscriber::registry().with(fmt).try_init()
+    };
+}
+
+/// Registers the `solidity-analyzer/*` requests that are not part of the LSP spec.
+pub fn register_custom_methods(builder: LspServiceBuilder<Server>) -> LspServiceBuilder<Server> {
+    builder
+        .custom_method(lsp_ext::SyntaxTree::METHOD, Server::syntax_tree)
+        .custom_method(lsp_ext::ViewHir::METHOD, Server::view_hir)
+        .custom_method(
+            lsp_ext::InheritanceGraphRequest::METHOD,
+            Server::inheritance_graph,
+        )
+        .custom_method(lsp_ext::StorageLayout::METHOD, Server::storage_layout)
+        .custom_method(
+            lsp_ext::StorageUpgradeCheck::METHOD,
+            Server::storage_upgrade_check,
+        )
+        .custom_method(lsp_ext::FlattenContract::METHOD, Server::flatten_contract)
+        .custom_method(lsp_ext::ReloadWorkspace::METHOD, Server::reload_workspace)
+        .custom_method(lsp_ext::DiscoverTests::METHOD, Server::discover_tests)
+        .custom_method(
+            lsp_ext::ContractArtifactRequest::METHOD,
+            Server::contract_artifact,
+        )
+}
+
+/// Loads the workspace at `root`, runs whole-project queries so derived data is populated,
+/// and returns the memory usage table printed by `--memory-usage`.
+pub fn memory_usage_report(root: &Path) -> anyhow::Result<String> {
+    let root = NormalizedPath::new(root.canonicalize()?.to_string_lossy());
+    let mut state = state::ServerState::new();
+    state.root_path = Some(root.clone());
+    workspace::load(&mut state, &root, None)?;
+    let analysis = state.analysis_host.snapshot();
+    let vfs = state.vfs.snapshot();
+    analysis.workspace_symbols("");
+    Ok(analysis.memory_usage(&vfs).to_string())
+}
+
+/// Loads the workspace at `root` and returns the HIR dump of `file` printed by `--dump-hir`, or
+/// the DefMap of every project for `--dump-def-map`.
+pub fn debug_dump_report(root: &Path, file: Option<&Path>) -> anyhow::Result<String> {
+    let root = NormalizedPath::new(root.canonicalize()?.to_string_lossy());
+    let mut state = state::ServerState::new();
+    state.root_path = Some(root.clone());
+    workspace::load(&mut state, &root, None)?;
+    let analysis = state.analysis_host.snapshot();
+    let vfs = state.vfs.snapshot();
+    if let Some(file) = file {
+        let path = NormalizedPath::new(file.canonicalize()?.to_string_lossy());
+        let file_id = vfs
+            .file_id(&path)
+            .ok_or_else(|| anyhow::anyhow!("{path} is not part of the workspace"))?;
+        return analysis
+            .hir_debug_dump(file_id)
+            .ok_or_else(|| anyhow::anyhow!("{path} does not belong to a project"));
+    }
+    Ok(analysis
+        .project_ids()
+        .into_iter()
+        .filter_map(|project_id| analysis.defmap_debug_dump(project_id))
+        .collect::<Vec<_>>()
+        .join("\n"))
 }
 
 #[cfg(test)]
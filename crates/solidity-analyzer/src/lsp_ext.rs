@@ -1,5 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::request::Request;
+use tower_lsp::lsp_types::{
+    Location, Position, Range, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
 
 pub enum ServerStatusNotification {}
 
@@ -23,3 +29,292 @@ pub enum Health {
     Warning,
     Error,
 }
+
+/// Returns the syntax tree of a document with byte ranges, or of the innermost node covering
+/// `range` when one is given.
+pub enum SyntaxTree {}
+
+impl Request for SyntaxTree {
+    type Params = SyntaxTreeParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "solidity-analyzer/syntaxTree";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxTreeParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Option<Range>,
+}
+
+/// Returns the lowered HIR of a document: its resolved imports, items, function bodies and local
+/// scopes.
+pub enum ViewHir {}
+
+impl Request for ViewHir {
+    type Params = ViewHirParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "solidity-analyzer/viewHir";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewHirParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Returns the inheritance graph of the document's project, or with `position`, of the contract
+/// declared there together with its bases and the contracts deriving from it.
+pub enum InheritanceGraphRequest {}
+
+impl Request for InheritanceGraphRequest {
+    type Params = InheritanceGraphParams;
+    type Result = Option<InheritanceGraph>;
+    const METHOD: &'static str = "solidity-analyzer/inheritanceGraph";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InheritanceGraphParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Option<Position>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InheritanceGraph {
+    pub nodes: Vec<InheritanceNode>,
+    pub edges: Vec<InheritanceEdge>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InheritanceNode {
+    pub name: String,
+    pub kind: ContractKind,
+    pub is_abstract: bool,
+    pub location: Option<Location>,
+    /// Indices into `nodes` of the C3 linearization, starting with the contract itself.
+    pub linearization: Vec<usize>,
+}
+
+/// `derived is base`, as indices into `nodes`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InheritanceEdge {
+    pub derived: usize,
+    pub base: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ContractKind {
+    Contract,
+    Interface,
+    Library,
+}
+
+/// Renders the contract declared at the position with the members of its bases merged in, each
+/// group annotated with the contract it comes from.
+pub enum FlattenContract {}
+
+impl Request for FlattenContract {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "solidity-analyzer/flattenContract";
+}
+
+/// Returns the storage layout of the contract declared at the position, most base first.
+pub enum StorageLayout {}
+
+impl Request for StorageLayout {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<Vec<StorageSlot>>;
+    const METHOD: &'static str = "solidity-analyzer/storageLayout";
+}
+
+/// Reloads every project from disk, re-reading configs and remappings, and drops all cached
+/// analysis so the next request starts from a fresh compilation. Returns a summary of what
+/// changed.
+pub enum ReloadWorkspace {}
+
+impl Request for ReloadWorkspace {
+    type Params = ();
+    type Result = String;
+    const METHOD: &'static str = "solidity-analyzer/reloadWorkspace";
+}
+
+/// Lists the forge tests of a document, or of every test file in the workspace when no
+/// document is given.
+pub enum DiscoverTests {}
+
+impl Request for DiscoverTests {
+    type Params = DiscoverTestsParams;
+    type Result = Vec<TestContract>;
+    const METHOD: &'static str = "solidity-analyzer/discoverTests";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverTestsParams {
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestContract {
+    pub name: String,
+    pub location: Location,
+    pub tests: Vec<TestFunction>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestFunction {
+    pub name: String,
+    pub kind: TestKind,
+    pub location: Location,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TestKind {
+    Test,
+    Fuzz,
+    Invariant,
+}
+
+/// Argument of the `solidity-analyzer.runTest` command: one test, or every test of `contract`
+/// when `test` is omitted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTestArguments {
+    pub uri: Url,
+    pub contract: String,
+    pub test: Option<String>,
+}
+
+/// Returns what `forge build` recorded about a contract: its ABI, selectors and bytecode size.
+/// Also answers for contracts whose sources are not part of the workspace.
+pub enum ContractArtifactRequest {}
+
+impl Request for ContractArtifactRequest {
+    type Params = ContractArtifactParams;
+    type Result = Option<ContractArtifact>;
+    const METHOD: &'static str = "solidity-analyzer/contractArtifact";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractArtifactParams {
+    /// Picks the project, and the artifact compiled from this document when several share
+    /// `name`.
+    pub text_document: TextDocumentIdentifier,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractArtifact {
+    pub name: String,
+    pub source: Option<String>,
+    pub abi: serde_json::Value,
+    pub method_identifiers: BTreeMap<String, String>,
+    pub deployed_bytecode_size: Option<usize>,
+    pub compiler_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlot {
+    pub name: String,
+    /// The contract declaring the variable.
+    pub contract: String,
+    /// The slot in decimal; it may not fit in a JSON number.
+    pub slot: String,
+    pub offset: u32,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub location: Option<Location>,
+}
+
+/// Compares the storage layout of the contract declared at the position with the one of an
+/// earlier version, reporting the changes that would corrupt the storage of an upgraded proxy.
+pub enum StorageUpgradeCheck {}
+
+impl Request for StorageUpgradeCheck {
+    type Params = StorageUpgradeCheckParams;
+    type Result = Option<Vec<StorageUpgradeIssue>>;
+    const METHOD: &'static str = "solidity-analyzer/storageUpgradeCheck";
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUpgradeCheckParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    /// The earlier version; without it, the contract named by the `@custom:oz-upgrades-from`
+    /// tag of the current one.
+    pub previous: Option<TextDocumentPositionParams>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUpgradeIssue {
+    /// One of `moved`, `typeChanged`, `removed`, `overlaps` or `gapMismatch`.
+    pub kind: String,
+    pub name: String,
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+/// Result of the `solidity-analyzer.projectStructure` command, one entry per loaded project.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStructure {
+    pub root: String,
+    pub source_roots: Vec<String>,
+    pub test_roots: Vec<String>,
+    pub script_roots: Vec<String>,
+    pub library_roots: Vec<String>,
+    pub include_paths: Vec<String>,
+    pub dependencies: Vec<DependencyPackage>,
+    pub profile: String,
+    pub solc_version: Option<String>,
+}
+
+/// Result of the `solidity-analyzer.memoryUsage` command. Byte counts are estimates.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub entries: Vec<MemoryUsageEntry>,
+    pub total_bytes: usize,
+    pub caches: Vec<CacheStats>,
+    /// The same statistics as a plain-text table, for an output channel.
+    pub report: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsageEntry {
+    pub name: String,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub name: String,
+    pub entries: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyPackage {
+    pub name: String,
+    pub version: Option<String>,
+    pub path: String,
+    /// The remapping pointing into the package, as `[context:]from=to`.
+    pub remapping: Option<String>,
+}
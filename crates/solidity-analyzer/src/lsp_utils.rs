@@ -1,10 +1,14 @@
-use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 
-use foundry_compilers::utils::canonicalize;
 use foundry_config::Config;
 use foundry_config::utils::find_project_root;
-use sa_paths::NormalizedPath;
+use sa_paths::{NormalizedPath, RealPathCache, WorkspacePath};
+use sa_project_model::{
+    APE_CONFIG_FILE, BROWNIE_CONFIG_FILE, HARDHAT_CONFIG_FILES, contains_hardhat_config,
+    contains_python_project_config,
+};
 use tower_lsp::lsp_types::Url;
 
 pub fn url_to_path(uri: &Url) -> Option<NormalizedPath> {
@@ -12,9 +16,63 @@ pub fn url_to_path(uri: &Url) -> Option<NormalizedPath> {
     Some(normalize_path(&path))
 }
 
+/// The URI the client knows `path` by, which differs from its real path when the client
+/// reached it through a symlink.
 pub fn path_to_url(path: &NormalizedPath) -> Option<Url> {
-    let cleaned = strip_verbatim_prefix(path.as_str());
-    Url::from_file_path(PathBuf::from(cleaned.as_ref())).ok()
+    let path = ClientPaths::shared()
+        .client_path(path)
+        .unwrap_or_else(|| path.clone());
+    Url::from_file_path(PathBuf::from(path.as_str())).ok()
+}
+
+/// The paths the client named open documents and workspace folders by, for those that are not
+/// their real path. Files are identified by their real path internally and reported to the
+/// client under the path it used.
+#[derive(Debug, Default)]
+pub struct ClientPaths {
+    aliases: RwLock<HashMap<NormalizedPath, NormalizedPath>>,
+}
+
+static CLIENT_PATHS: LazyLock<ClientPaths> = LazyLock::new(ClientPaths::default);
+
+impl ClientPaths {
+    pub fn shared() -> &'static ClientPaths {
+        &CLIENT_PATHS
+    }
+
+    /// Remembers the path `uri` names under its real path, which is returned.
+    pub fn remember(&self, uri: &Url) -> Option<NormalizedPath> {
+        let path = uri.to_file_path().ok()?;
+        let client = NormalizedPath::new(path.to_string_lossy());
+        let real = normalize_path(&path);
+        if real != client {
+            self.write().insert(real.clone(), client);
+        }
+        Some(real)
+    }
+
+    pub fn forget(&self, real: &NormalizedPath) {
+        self.write().remove(real);
+    }
+
+    /// The client's path for `real`: the path it opened the file under, or the path below the
+    /// nearest folder it named through a symlink. `None` when the client uses the real path.
+    pub fn client_path(&self, real: &NormalizedPath) -> Option<NormalizedPath> {
+        let aliases = self.aliases.read().expect("client paths lock");
+        if let Some(client) = aliases.get(real) {
+            return Some(client.clone());
+        }
+        let (root, client) = aliases
+            .iter()
+            .filter(|(root, _)| WorkspacePath::new(root, real).is_some())
+            .max_by_key(|(root, _)| root.as_str().len())?;
+        let relative = WorkspacePath::new(root, real)?;
+        Some(NormalizedPath::new(format!("{client}/{relative}")))
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<NormalizedPath, NormalizedPath>> {
+        self.aliases.write().expect("client paths lock")
+    }
 }
 
 pub fn is_foundry_config_path(path: &NormalizedPath) -> bool {
@@ -24,27 +82,32 @@ pub fn is_foundry_config_path(path: &NormalizedPath) -> bool {
     matches!(file_name, Some("foundry.toml" | "remappings.txt"))
 }
 
-pub fn normalize_path(path: &Path) -> NormalizedPath {
-    let canonical = canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-    NormalizedPath::new(canonical.to_string_lossy())
+/// Returns true for JSON files below an `out/` directory, where `forge build` writes artifacts.
+pub fn is_artifact_path(path: &NormalizedPath) -> bool {
+    let path = Path::new(path.as_str());
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+        && path
+            .components()
+            .any(|component| component.as_os_str() == "out")
 }
 
-fn strip_verbatim_prefix(path: &str) -> Cow<'_, str> {
-    let rest = match path.strip_prefix("//?/") {
-        Some(rest) => rest,
-        None => match path.strip_prefix("//./") {
-            Some(rest) => rest,
-            None => return Cow::Borrowed(path),
-        },
-    };
-    let rest = rest.strip_prefix('/').unwrap_or(rest);
-    if rest
-        .get(0..4)
-        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("unc/"))
-    {
-        return Cow::Owned(format!("//{}", &rest[4..]));
-    }
-    Cow::Borrowed(rest)
+pub fn is_hardhat_config_path(path: &NormalizedPath) -> bool {
+    let file_name = Path::new(path.as_str())
+        .file_name()
+        .and_then(|name| name.to_str());
+    file_name.is_some_and(|name| HARDHAT_CONFIG_FILES.contains(&name))
+}
+
+pub fn is_python_project_config_path(path: &NormalizedPath) -> bool {
+    let file_name = Path::new(path.as_str())
+        .file_name()
+        .and_then(|name| name.to_str());
+    matches!(file_name, Some(APE_CONFIG_FILE | BROWNIE_CONFIG_FILE))
+}
+
+pub fn normalize_path(path: &Path) -> NormalizedPath {
+    RealPathCache::shared().real_path(&NormalizedPath::new(path.to_string_lossy()))
 }
 
 pub fn contains_foundry_config(path: &Path) -> bool {
@@ -57,6 +120,31 @@ pub fn find_foundry_root(path: &Path) -> Option<NormalizedPath> {
     contains_foundry_config(&root).then(|| normalize_path(&root))
 }
 
+pub fn contains_project_config(path: &Path) -> bool {
+    contains_foundry_config(path)
+        || contains_hardhat_config(path)
+        || contains_python_project_config(path)
+}
+
+/// Finds the nearest Foundry, Hardhat, Ape or Brownie project root containing `path`.
+pub fn find_workspace_root(path: &Path) -> Option<NormalizedPath> {
+    let start = if path.is_dir() { path } else { path.parent()? };
+    let other = start
+        .ancestors()
+        .find(|dir| contains_hardhat_config(dir) || contains_python_project_config(dir))
+        .map(normalize_path);
+    match (find_foundry_root(start), other) {
+        (Some(foundry), Some(other)) => {
+            if other.as_str().len() > foundry.as_str().len() {
+                Some(other)
+            } else {
+                Some(foundry)
+            }
+        }
+        (foundry, other) => foundry.or(other),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -64,10 +152,11 @@ mod tests {
     use tempfile::tempdir;
 
     use super::{
-        contains_foundry_config, find_foundry_root, is_foundry_config_path, normalize_path,
-        path_to_url, url_to_path,
+        contains_foundry_config, find_foundry_root, find_workspace_root, is_artifact_path,
+        is_foundry_config_path, is_hardhat_config_path, is_python_project_config_path,
+        normalize_path, path_to_url, url_to_path,
     };
-    use sa_paths::NormalizedPath;
+    use sa_paths::{NormalizedPath, RealPathCache};
     use tower_lsp::lsp_types::Url;
 
     #[test]
@@ -126,6 +215,81 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn find_workspace_root_detects_hardhat_projects() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let contracts = root.join("contracts");
+        fs::create_dir_all(&contracts).expect("create dirs");
+        fs::write(root.join("hardhat.config.ts"), "export default {};").expect("write config");
+
+        let found = find_workspace_root(&contracts).expect("found root");
+        assert_eq!(found.as_str(), root.to_string_lossy());
+        assert!(find_foundry_root(&contracts).is_none());
+    }
+
+    #[test]
+    fn find_workspace_root_prefers_nearest_project() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let nested = root.join("packages/contracts");
+        fs::create_dir_all(nested.join("contracts")).expect("create dirs");
+        fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+        fs::write(nested.join("hardhat.config.js"), "module.exports = {};").expect("write config");
+
+        let found = find_workspace_root(&nested.join("contracts")).expect("found root");
+        assert_eq!(found.as_str(), nested.to_string_lossy());
+    }
+
+    #[test]
+    fn find_workspace_root_detects_ape_and_brownie_projects() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().canonicalize().expect("canonicalize root");
+        let ape = root.join("ape");
+        let brownie = root.join("brownie");
+        fs::create_dir_all(ape.join("contracts")).expect("create dirs");
+        fs::create_dir_all(brownie.join("contracts")).expect("create dirs");
+        fs::write(ape.join("ape-config.yaml"), "name: token\n").expect("write config");
+        fs::write(brownie.join("brownie-config.yaml"), "dependencies: []\n").expect("write config");
+
+        let found = find_workspace_root(&ape.join("contracts")).expect("found ape root");
+        assert_eq!(found.as_str(), ape.to_string_lossy());
+        let found = find_workspace_root(&brownie.join("contracts")).expect("found brownie root");
+        assert_eq!(found.as_str(), brownie.to_string_lossy());
+        assert!(is_python_project_config_path(&NormalizedPath::new(
+            "/workspace/brownie-config.yaml"
+        )));
+        assert!(!is_python_project_config_path(&NormalizedPath::new(
+            "/workspace/config.yaml"
+        )));
+    }
+
+    #[test]
+    fn is_hardhat_config_path_matches_expected_files() {
+        assert!(is_hardhat_config_path(&NormalizedPath::new(
+            "/workspace/hardhat.config.ts"
+        )));
+        assert!(is_hardhat_config_path(&NormalizedPath::new(
+            "/workspace/hardhat.config.js"
+        )));
+        assert!(!is_hardhat_config_path(&NormalizedPath::new(
+            "/workspace/hardhat.config.json"
+        )));
+    }
+
+    #[test]
+    fn is_artifact_path_matches_json_below_out() {
+        assert!(is_artifact_path(&NormalizedPath::new(
+            "/workspace/out/Counter.sol/Counter.json"
+        )));
+        assert!(!is_artifact_path(&NormalizedPath::new(
+            "/workspace/src/Counter.sol"
+        )));
+        assert!(!is_artifact_path(&NormalizedPath::new(
+            "/workspace/outputs/Counter.json"
+        )));
+    }
+
     #[test]
     fn contains_foundry_config_requires_foundry_toml() {
         let temp = tempdir().expect("tempdir");
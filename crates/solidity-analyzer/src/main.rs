@@ -1,11 +1,235 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use solidity_analyzer::cli::{
+    self, AnalyzeFormat, GraphFormat, GraphKind, MetricsFormat, QueryKind, SymbolsFormat,
+};
 use tracing::{error, info};
 
+/// A Solidity language server with first-class Foundry support. Without a command, serves LSP
+/// over stdio.
+#[derive(Debug, Parser)]
+#[command(name = "solidity-analyzer", version)]
+struct Args {
+    /// Print a timing summary of every request to stderr.
+    #[arg(long, global = true)]
+    print_time: bool,
+    /// Accepted for clients that pass it; the server always talks over stdio.
+    #[arg(long = "stdio", hide = true)]
+    _stdio: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the lowered HIR of a file.
+    #[command(long_flag = "dump-hir")]
+    DumpHir {
+        file: PathBuf,
+        #[arg(default_value = ".")]
+        root: PathBuf,
+    },
+    /// Print the definition map of a project.
+    #[command(long_flag = "dump-def-map")]
+    DumpDefMap {
+        #[arg(default_value = ".")]
+        root: PathBuf,
+    },
+    /// Print how much memory the analysis of a project holds.
+    #[command(long_flag = "memory-usage")]
+    MemoryUsage {
+        #[arg(default_value = ".")]
+        root: PathBuf,
+    },
+    /// Report diagnostics for a project; exits with 1 when there are errors.
+    Analyze {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long, default_value = "text", value_parser = parse_with(AnalyzeFormat::parse, "text, json or sarif"))]
+        format: AnalyzeFormat,
+    },
+    /// Export the symbols of a project.
+    DumpSymbols {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long, default_value = "json", value_parser = parse_with(SymbolsFormat::parse, "json or ndjson"))]
+        format: SymbolsFormat,
+    },
+    /// Find the definition (`def`) or references (`refs`) at `<file>:<line>:<column>`.
+    Query {
+        #[arg(value_parser = parse_with(QueryKind::parse, "def or refs"))]
+        kind: QueryKind,
+        target: String,
+    },
+    /// Inline a file's imports into a single source.
+    Flatten {
+        file: PathBuf,
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the standard-json compiler input for a project.
+    StandardJson {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Print the `imports` or `inheritance` graph of a project.
+    Graph {
+        #[arg(value_parser = parse_with(GraphKind::parse, "imports or inheritance"))]
+        kind: GraphKind,
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long, default_value = "dot", value_parser = parse_with(GraphFormat::parse, "dot or json"))]
+        format: GraphFormat,
+    },
+    /// Report size and complexity metrics for a project.
+    Metrics {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long, default_value = "table", value_parser = parse_with(MetricsFormat::parse, "table or json"))]
+        format: MetricsFormat,
+    },
+    /// Measure load and query latencies on a project.
+    Bench {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Adapts the `parse` functions of the CLI enums to clap value parsers.
+fn parse_with<T: 'static>(
+    parse: fn(&str) -> Option<T>,
+    expected: &'static str,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |value| parse(value).ok_or_else(|| format!("expected {expected}"))
+}
+
 #[tokio::main]
 async fn main() {
-    solidity_analyzer::init_tracing();
+    let args = Args::parse();
+    solidity_analyzer::init_tracing_with_timings(args.print_time);
+    match args.command {
+        Some(command) => run(command),
+        None => serve().await,
+    }
+}
+
+fn run(command: Command) {
+    match command {
+        Command::DumpHir { file, root } => dump(&root, Some(file)),
+        Command::DumpDefMap { root } => dump(&root, None),
+        Command::MemoryUsage { root } => match solidity_analyzer::memory_usage_report(&root) {
+            Ok(report) => println!("{report}"),
+            Err(error) => {
+                error!(?error, "failed to collect memory usage");
+                std::process::exit(1);
+            }
+        },
+        Command::Analyze { path, format } => match cli::analyze(&path) {
+            Ok(report) => {
+                println!("{}", report.render(format));
+                if report.has_errors() {
+                    std::process::exit(1);
+                }
+            }
+            Err(error) => {
+                error!(?error, "failed to analyze project");
+                std::process::exit(2);
+            }
+        },
+        Command::DumpSymbols { path, format } => match cli::dump_symbols(&path) {
+            Ok(symbols) => println!("{}", cli::render_symbols(&symbols, format)),
+            Err(error) => {
+                error!(?error, "failed to export symbols");
+                std::process::exit(1);
+            }
+        },
+        Command::Query { kind, target } => match cli::query(kind, &target) {
+            Ok(locations) => {
+                for location in locations {
+                    println!("{location}");
+                }
+            }
+            Err(error) => {
+                error!(?error, "query failed");
+                std::process::exit(1);
+            }
+        },
+        Command::Flatten { file, output } => {
+            let flattened = match cli::flatten(&file) {
+                Ok(flattened) => flattened,
+                Err(error) => {
+                    error!(?error, "failed to flatten {}", file.display());
+                    std::process::exit(1);
+                }
+            };
+            match output {
+                Some(output) => {
+                    if let Err(error) = std::fs::write(&output, flattened) {
+                        error!(?error, "failed to write {}", output.display());
+                        std::process::exit(1);
+                    }
+                }
+                None => print!("{flattened}"),
+            }
+        }
+        Command::StandardJson { path } => match cli::standard_json(&path) {
+            Ok(input) => println!(
+                "{}",
+                serde_json::to_string_pretty(&input).expect("standard-json input serializes")
+            ),
+            Err(error) => {
+                error!(?error, "failed to build standard-json input");
+                std::process::exit(1);
+            }
+        },
+        Command::Graph { kind, path, format } => match cli::graph(kind, &path, format) {
+            Ok(graph) => println!("{graph}"),
+            Err(error) => {
+                error!(?error, "failed to build the graph");
+                std::process::exit(1);
+            }
+        },
+        Command::Metrics { path, format } => match cli::metrics(&path) {
+            Ok(reports) => println!("{}", cli::render_metrics(&reports, format)),
+            Err(error) => {
+                error!(?error, "failed to collect metrics");
+                std::process::exit(1);
+            }
+        },
+        Command::Bench { path, json } => match cli::bench(&path) {
+            Ok(report) if json => println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("bench report serializes to JSON")
+            ),
+            Ok(report) => println!("{report}"),
+            Err(error) => {
+                error!(?error, "benchmark failed");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+fn dump(root: &Path, file: Option<PathBuf>) {
+    match solidity_analyzer::debug_dump_report(root, file.as_deref()) {
+        Ok(report) => println!("{report}"),
+        Err(error) => {
+            error!(?error, "failed to dump analysis state");
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn serve() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
-    let (service, socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let (service, socket) = solidity_analyzer::register_custom_methods(
+        tower_lsp::LspService::build(solidity_analyzer::Server::new),
+    )
+    .finish();
     let server = tokio::spawn(tower_lsp::Server::new(stdin, stdout, socket).serve(service));
 
     tokio::select! {
@@ -0,0 +1,461 @@
+//! Verified sources of deployed contracts, fetched from Sourcify or Etherscan into a read-only
+//! cache below the project's cache directory. Verified sources never change, so a contract is
+//! downloaded once and served from the cache from then on, also when offline.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use sa_config::ResolvedFoundryConfig;
+use sa_span::{TextRange, is_ident_byte};
+use sa_syntax::cst::parse_cst;
+use serde_json::{Value, json};
+
+use crate::config::OnchainConfig;
+
+const SOURCIFY_URL: &str = "https://sourcify.dev/server/files/any";
+const ETHERSCAN_URL: &str = "https://api.etherscan.io/v2/api";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+const CONTRACT_FILE: &str = "contract.json";
+const SOURCES_DIR: &str = "sources";
+
+/// A verified contract: its name, the file declaring it and every source it was compiled from,
+/// keyed by the path it was compiled under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VerifiedContract {
+    pub name: String,
+    pub main: String,
+    pub compiler_version: Option<String>,
+    pub sources: Vec<(String, String)>,
+}
+
+/// A contract in the cache, with its sources written below `dir`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CachedContract {
+    pub name: String,
+    pub main: PathBuf,
+    pub files: Vec<PathBuf>,
+}
+
+/// `text` as a lowercase address if it is a `0x`-prefixed, 20-byte hex literal.
+pub(crate) fn parse_address(text: &str) -> Option<String> {
+    let hex = text.strip_prefix("0x")?;
+    (hex.len() == 40 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .then(|| text.to_ascii_lowercase())
+}
+
+/// The address literal in `text` covering `offset`, lowercased.
+pub(crate) fn address_at(text: &str, offset: usize) -> Option<String> {
+    let is_word = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+    let bytes = text.as_bytes();
+    let mut start = offset.min(bytes.len());
+    while start > 0 && is_word(bytes[start - 1]) {
+        start -= 1;
+    }
+    let mut end = offset.min(bytes.len());
+    while end < bytes.len() && is_word(bytes[end]) {
+        end += 1;
+    }
+    parse_address(&text[start..end])
+}
+
+/// Where the sources of `address` on `chain_id` are cached for the project of `config`.
+pub(crate) fn cache_dir(config: &ResolvedFoundryConfig, chain_id: u64, address: &str) -> PathBuf {
+    Path::new(config.workspace().root().as_str())
+        .join(&config.foundry_config().cache_path)
+        .join("onchain")
+        .join(chain_id.to_string())
+        .join(address)
+}
+
+/// Reads the contract cached in `dir`, or `None` if it has not been fetched yet.
+pub(crate) fn read_cache(dir: &Path) -> Option<CachedContract> {
+    let contract = fs::read_to_string(dir.join(CONTRACT_FILE)).ok()?;
+    let contract = serde_json::from_str::<Value>(&contract).ok()?;
+    let name = contract.get("name")?.as_str()?.to_string();
+    let sources = dir.join(SOURCES_DIR);
+    let main = sources.join(contract.get("main")?.as_str()?);
+    let files = contract
+        .get("sources")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|source| sources.join(source))
+        .collect();
+    Some(CachedContract { name, main, files })
+}
+
+/// The name of the cached contract in its main source, together with the source's text.
+pub(crate) fn contract_declaration(contract: &CachedContract) -> Option<(String, TextRange)> {
+    let text = fs::read_to_string(&contract.main).ok()?;
+    let range = parse_cst(&text)
+        .contract_by_name(&contract.name)?
+        .name()?
+        .range();
+    Some((text, range))
+}
+
+/// Writes `contract` to `dir`, marking the sources read-only as they mirror on-chain code.
+pub(crate) fn write_cache(dir: &Path, contract: &VerifiedContract) -> Result<CachedContract> {
+    let sources = dir.join(SOURCES_DIR);
+    let mut files = Vec::new();
+    for (path, content) in &contract.sources {
+        let file = sources.join(path);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        // Left behind by an interrupted download; read-only, so replaced rather than written.
+        if file.exists() {
+            fs::remove_file(&file)
+                .with_context(|| format!("failed to replace {}", file.display()))?;
+        }
+        fs::write(&file, content).with_context(|| format!("failed to write {}", file.display()))?;
+        let mut permissions = fs::metadata(&file)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions)?;
+        files.push(file);
+    }
+    // Written last, so an interrupted download is fetched again rather than half-used.
+    let record = json!({
+        "name": contract.name,
+        "main": contract.main,
+        "compilerVersion": contract.compiler_version,
+        "sources": contract.sources.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+    });
+    fs::write(dir.join(CONTRACT_FILE), record.to_string())?;
+    Ok(CachedContract {
+        name: contract.name.clone(),
+        main: sources.join(&contract.main),
+        files,
+    })
+}
+
+/// Downloads the verified sources of `address`, from Sourcify first and then from Etherscan if
+/// an API key is configured.
+pub(crate) async fn fetch(
+    config: &OnchainConfig,
+    etherscan_api_key: Option<&str>,
+    chain_id: u64,
+    address: &str,
+) -> Result<VerifiedContract> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .context("failed to create HTTP client")?;
+    let sourcify = async {
+        let url = format!("{SOURCIFY_URL}/{chain_id}/{address}");
+        let response = client.get(url).send().await?;
+        if !response.status().is_success() {
+            bail!("Sourcify answered {}", response.status());
+        }
+        parse_sourcify_response(&response.text().await?)
+    };
+    let sourcify_error = match sourcify.await {
+        Ok(contract) => return Ok(contract),
+        Err(error) => error,
+    };
+    let Some(api_key) = config.etherscan_api_key.as_deref().or(etherscan_api_key) else {
+        return Err(sourcify_error.context(format!("{address} is not verified on Sourcify")));
+    };
+    let response = client
+        .get(ETHERSCAN_URL)
+        .query(&[
+            ("chainid", chain_id.to_string().as_str()),
+            ("module", "contract"),
+            ("action", "getsourcecode"),
+            ("address", address),
+            ("apikey", api_key),
+        ])
+        .send()
+        .await
+        .context("failed to reach Etherscan")?;
+    parse_etherscan_response(&response.text().await?)
+        .with_context(|| format!("{address} is not verified on Sourcify or Etherscan"))
+}
+
+/// Parses a Sourcify `files/any` response, which lists the metadata and every source file.
+pub(crate) fn parse_sourcify_response(json: &str) -> Result<VerifiedContract> {
+    let response = serde_json::from_str::<Value>(json).context("invalid Sourcify response")?;
+    let files = response
+        .get("files")
+        .and_then(Value::as_array)
+        .context("Sourcify response lists no files")?;
+    let mut metadata = None;
+    let mut sources = Vec::new();
+    for file in files {
+        let (Some(path), Some(content)) = (
+            file.get("path").and_then(Value::as_str),
+            file.get("content").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        if file.get("name").and_then(Value::as_str) == Some("metadata.json") {
+            metadata = serde_json::from_str::<Value>(content).ok();
+            continue;
+        }
+        // Repository paths look like `.../full_match/1/0x.../sources/contracts/Token.sol`.
+        let Some((_, source)) = path.split_once("/sources/") else {
+            continue;
+        };
+        sources.push((sanitize(source)?, content.to_string()));
+    }
+    let metadata = metadata.context("Sourcify response has no metadata")?;
+    let (main, name) = metadata
+        .pointer("/settings/compilationTarget")
+        .and_then(Value::as_object)
+        .and_then(|targets| targets.iter().next())
+        .and_then(|(path, name)| Some((sanitize(path).ok()?, name.as_str()?.to_string())))
+        .context("metadata names no compilation target")?;
+    verified(
+        name,
+        main,
+        metadata
+            .pointer("/compiler/version")
+            .and_then(Value::as_str),
+        sources,
+    )
+}
+
+/// Parses an Etherscan `getsourcecode` response. The source is either a single file, a JSON
+/// object of files, or a standard-json input wrapped in an extra pair of braces.
+pub(crate) fn parse_etherscan_response(json: &str) -> Result<VerifiedContract> {
+    let response = serde_json::from_str::<Value>(json).context("invalid Etherscan response")?;
+    if response.get("status").and_then(Value::as_str) != Some("1") {
+        let message = response
+            .get("result")
+            .and_then(Value::as_str)
+            .or_else(|| response.get("message").and_then(Value::as_str))
+            .unwrap_or("unknown error");
+        bail!("Etherscan: {message}");
+    }
+    let result = response
+        .pointer("/result/0")
+        .context("Etherscan returned no contract")?;
+    let field = |key: &str| {
+        result
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+    };
+    let source = field("SourceCode").context("contract is not verified")?;
+    let name = field("ContractName").context("contract has no name")?;
+    let compiler_version = field("CompilerVersion");
+
+    let trimmed = source.trim();
+    let layout = trimmed
+        .strip_prefix("{{")
+        .and_then(|inner| inner.strip_suffix("}}"))
+        .map(|inner| format!("{{{inner}}}"))
+        .or_else(|| trimmed.starts_with('{').then(|| trimmed.to_string()));
+    let Some(files) = layout.and_then(|layout| serde_json::from_str::<Value>(&layout).ok()) else {
+        let main = format!("{name}.sol");
+        return verified(
+            name.to_string(),
+            main.clone(),
+            compiler_version,
+            vec![(main, source.to_string())],
+        );
+    };
+    let files = files.get("sources").unwrap_or(&files);
+    let mut sources = Vec::new();
+    for (path, file) in files
+        .as_object()
+        .context("unexpected Etherscan source layout")?
+    {
+        if let Some(content) = file.get("content").and_then(Value::as_str) {
+            sources.push((sanitize(path)?, content.to_string()));
+        }
+    }
+    let main = sources
+        .iter()
+        .find(|(_, content)| declares(content, name))
+        .or_else(|| sources.first())
+        .map(|(path, _)| path.clone())
+        .context("Etherscan returned no sources")?;
+    verified(name.to_string(), main, compiler_version, sources)
+}
+
+/// Whether `content` has `contract`, `abstract contract` or `library` followed by `name` as a
+/// whole identifier, so `Vault` is not found in `contract VaultFactory`.
+fn declares(content: &str, name: &str) -> bool {
+    let bytes = content.as_bytes();
+    content.match_indices(name).any(|(start, _)| {
+        let end = start + name.len();
+        if bytes.get(end).is_some_and(|byte| is_ident_byte(*byte)) {
+            return false;
+        }
+        let before = &content[..start];
+        let keyword_end = before.trim_end();
+        if keyword_end.len() == before.len() {
+            return false;
+        }
+        ["contract", "library"].iter().any(|keyword| {
+            keyword_end.strip_suffix(keyword).is_some_and(|rest| {
+                rest.as_bytes()
+                    .last()
+                    .is_none_or(|byte| !is_ident_byte(*byte))
+            })
+        })
+    })
+}
+
+fn verified(
+    name: String,
+    main: String,
+    compiler_version: Option<&str>,
+    mut sources: Vec<(String, String)>,
+) -> Result<VerifiedContract> {
+    if !sources.iter().any(|(path, _)| *path == main) {
+        bail!("the sources of {name} do not include {main}");
+    }
+    sources.sort();
+    Ok(VerifiedContract {
+        name,
+        main,
+        compiler_version: compiler_version.map(str::to_string),
+        sources,
+    })
+}
+
+/// `path` made relative, rejecting paths that would escape the cache directory.
+fn sanitize(path: &str) -> Result<String> {
+    let path = path.trim_start_matches('/');
+    let escapes = Path::new(path)
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if path.is_empty() || escapes {
+        bail!("refusing source path {path:?}");
+    }
+    Ok(path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        address_at, parse_address, parse_etherscan_response, parse_sourcify_response, read_cache,
+        write_cache,
+    };
+
+    const ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    #[test]
+    fn finds_address_literals() {
+        let text = format!("IERC20 usdc = IERC20({ADDRESS});");
+        let offset = text.find("0xA0").expect("address") + 10;
+        assert_eq!(
+            address_at(&text, offset),
+            Some(ADDRESS.to_ascii_lowercase())
+        );
+        assert_eq!(address_at(&text, 2), None);
+        assert_eq!(parse_address("0x1234"), None);
+    }
+
+    #[test]
+    fn parses_sourcify_files() {
+        let metadata = json!({
+            "compiler": {"version": "0.8.24+commit.e11b9ed9"},
+            "settings": {"compilationTarget": {"src/Token.sol": "Token"}}
+        });
+        let response = json!({
+            "status": "full",
+            "files": [
+                {"name": "metadata.json", "path": "/repo/full_match/1/0xabc/metadata.json", "content": metadata.to_string()},
+                {"name": "Token.sol", "path": "/repo/full_match/1/0xabc/sources/src/Token.sol", "content": "import \"./Base.sol\";\ncontract Token is Base {}"},
+                {"name": "Base.sol", "path": "/repo/full_match/1/0xabc/sources/src/Base.sol", "content": "contract Base {}"}
+            ]
+        });
+        let contract = parse_sourcify_response(&response.to_string()).expect("contract");
+        assert_eq!(contract.name, "Token");
+        assert_eq!(contract.main, "src/Token.sol");
+        assert_eq!(
+            contract.compiler_version.as_deref(),
+            Some("0.8.24+commit.e11b9ed9")
+        );
+        let paths = contract
+            .sources
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["src/Base.sol", "src/Token.sol"]);
+    }
+
+    #[test]
+    fn parses_etherscan_source_layouts() {
+        let single = json!({
+            "status": "1",
+            "result": [{"SourceCode": "contract Vault {}", "ContractName": "Vault", "CompilerVersion": "v0.8.19"}]
+        });
+        let contract = parse_etherscan_response(&single.to_string()).expect("single file");
+        assert_eq!(contract.main, "Vault.sol");
+        assert_eq!(contract.sources[0].1, "contract Vault {}");
+
+        let standard_json = json!({
+            "language": "Solidity",
+            "sources": {
+                "contracts/Lib.sol": {"content": "library Lib {}"},
+                "contracts/Vault.sol": {"content": "import \"./Lib.sol\";\ncontract Vault {}"}
+            }
+        });
+        let wrapped = json!({
+            "status": "1",
+            "result": [{"SourceCode": format!("{{{standard_json}}}"), "ContractName": "Vault"}]
+        });
+        let contract = parse_etherscan_response(&wrapped.to_string()).expect("standard json");
+        assert_eq!(contract.main, "contracts/Vault.sol");
+        assert_eq!(contract.sources.len(), 2);
+
+        let similar_names = json!({
+            "sources": {
+                "contracts/Factory.sol": {"content": "contract VaultFactory {}"},
+                "contracts/Vault.sol": {"content": "abstract contract Base {}\nabstract contract  Vault is Base {}"}
+            }
+        });
+        let prefixed = json!({
+            "status": "1",
+            "result": [{"SourceCode": similar_names.to_string(), "ContractName": "Vault"}]
+        });
+        let contract = parse_etherscan_response(&prefixed.to_string()).expect("similar names");
+        assert_eq!(contract.main, "contracts/Vault.sol");
+
+        let unverified = json!({"status": "1", "result": [{"SourceCode": "", "ContractName": ""}]});
+        assert!(parse_etherscan_response(&unverified.to_string()).is_err());
+        let rejected = json!({"status": "0", "message": "NOTOK", "result": "Invalid API Key"});
+        let error = parse_etherscan_response(&rejected.to_string()).expect_err("bad key");
+        assert!(error.to_string().contains("Invalid API Key"));
+    }
+
+    #[test]
+    fn rejects_sources_escaping_the_cache() {
+        let response = json!({
+            "status": "1",
+            "result": [{"SourceCode": json!({"../../evil.sol": {"content": "contract Evil {}"}}).to_string(), "ContractName": "Evil"}]
+        });
+        assert!(parse_etherscan_response(&response.to_string()).is_err());
+    }
+
+    #[test]
+    fn cached_contracts_round_trip() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let response = json!({
+            "status": "1",
+            "result": [{"SourceCode": "contract Vault {}", "ContractName": "Vault"}]
+        });
+        let contract = parse_etherscan_response(&response.to_string()).expect("contract");
+        assert_eq!(read_cache(dir.path()), None);
+
+        let written = write_cache(dir.path(), &contract).expect("write cache");
+        // Writing again replaces the read-only files.
+        write_cache(dir.path(), &contract).expect("rewrite cache");
+        let cached = read_cache(dir.path()).expect("cached contract");
+        assert_eq!(cached, written);
+        assert_eq!(cached.main, dir.path().join("sources/Vault.sol"));
+        let permissions = std::fs::metadata(&cached.main)
+            .expect("metadata")
+            .permissions();
+        assert!(permissions.readonly());
+    }
+}
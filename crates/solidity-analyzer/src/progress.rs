@@ -0,0 +1,188 @@
+//! `window/workDoneProgress` reporting for long-running work.
+//!
+//! Workspace loading runs synchronously while the server state is locked, so reporters only
+//! queue events; a task owned by the [`Progress`] handle turns them into `$/progress`
+//! notifications in order.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use sa_ide::CancellationToken;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tower_lsp::Client;
+use tower_lsp::lsp_types::notification::Progress as ProgressNotification;
+use tower_lsp::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use tracing::debug;
+
+enum Event {
+    Begin {
+        token: String,
+        title: String,
+        cancellation: Option<CancellationToken>,
+    },
+    Report {
+        token: String,
+        message: String,
+        percentage: u32,
+    },
+    End {
+        token: String,
+    },
+    Cancel {
+        token: String,
+    },
+}
+
+/// Starts progress reports. The default handle is disabled, for clients without
+/// `window.workDoneProgress` support, and drops every report.
+#[derive(Clone, Default)]
+pub struct Progress {
+    sender: Option<UnboundedSender<Event>>,
+    next_token: Arc<AtomicU64>,
+}
+
+impl Progress {
+    pub fn spawn(client: Client) -> Self {
+        let (sender, mut events) = unbounded_channel();
+        tokio::spawn(async move {
+            let mut active = HashMap::<String, Option<CancellationToken>>::new();
+            while let Some(event) = events.recv().await {
+                match event {
+                    Event::Begin {
+                        token,
+                        title,
+                        cancellation,
+                    } => {
+                        let created = client
+                            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                                token: NumberOrString::String(token.clone()),
+                            })
+                            .await;
+                        if let Err(error) = created {
+                            debug!(?error, "client refused a progress token");
+                            continue;
+                        }
+                        let begin = WorkDoneProgressBegin {
+                            title,
+                            cancellable: Some(cancellation.is_some()),
+                            message: None,
+                            percentage: Some(0),
+                        };
+                        notify(&client, &token, WorkDoneProgress::Begin(begin)).await;
+                        active.insert(token, cancellation);
+                    }
+                    Event::Report {
+                        token,
+                        message,
+                        percentage,
+                    } => {
+                        if active.contains_key(&token) {
+                            let report = WorkDoneProgressReport {
+                                cancellable: None,
+                                message: Some(message),
+                                percentage: Some(percentage),
+                            };
+                            notify(&client, &token, WorkDoneProgress::Report(report)).await;
+                        }
+                    }
+                    Event::End { token } => {
+                        if active.remove(&token).is_some() {
+                            let end = WorkDoneProgressEnd { message: None };
+                            notify(&client, &token, WorkDoneProgress::End(end)).await;
+                        }
+                    }
+                    Event::Cancel { token } => {
+                        if let Some(Some(cancellation)) = active.get(&token) {
+                            cancellation.cancel();
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            sender: Some(sender),
+            next_token: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Begins a report titled `title`. Passing `cancellation` lets the user cancel the work,
+    /// which cancels the token. The report ends when the returned task is dropped.
+    pub fn begin(&self, title: &str, cancellation: Option<CancellationToken>) -> ProgressTask {
+        let id = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let token = format!("solidity-analyzer/progress/{id}");
+        self.send(Event::Begin {
+            token: token.clone(),
+            title: title.to_string(),
+            cancellation,
+        });
+        ProgressTask {
+            progress: self.clone(),
+            token,
+            percentage: AtomicU32::new(0),
+        }
+    }
+
+    /// Handles `window/workDoneProgress/cancel` for `token`.
+    pub fn cancel(&self, token: &NumberOrString) {
+        if let NumberOrString::String(token) = token {
+            self.send(Event::Cancel {
+                token: token.clone(),
+            });
+        }
+    }
+
+    fn send(&self, event: Event) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+pub struct ProgressTask {
+    progress: Progress,
+    token: String,
+    percentage: AtomicU32,
+}
+
+impl ProgressTask {
+    /// Reports `done` of `total` items, e.g. "Indexing 312/1840 files". Reports that would not
+    /// move the percentage are skipped.
+    pub fn report(&self, verb: &str, done: usize, total: usize, noun: &str) {
+        let percentage = if total == 0 {
+            100
+        } else {
+            (done.min(total) * 100 / total) as u32
+        };
+        let previous = self.percentage.swap(percentage, Ordering::Relaxed);
+        if previous == percentage && done != total {
+            return;
+        }
+        self.progress.send(Event::Report {
+            token: self.token.clone(),
+            message: format!("{verb} {done}/{total} {noun}"),
+            percentage,
+        });
+    }
+}
+
+impl Drop for ProgressTask {
+    fn drop(&mut self) {
+        self.progress.send(Event::End {
+            token: self.token.clone(),
+        });
+    }
+}
+
+async fn notify(client: &Client, token: &str, value: WorkDoneProgress) {
+    client
+        .send_notification::<ProgressNotification>(ProgressParams {
+            token: NumberOrString::String(token.to_string()),
+            value: ProgressParamsValue::WorkDone(value),
+        })
+        .await;
+}
@@ -1,27 +1,34 @@
 use std::collections::HashMap;
 use std::env;
 use std::panic::AssertUnwindSafe;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 
-use anyhow::Result as AnyhowResult;
+use anyhow::{Context, Result as AnyhowResult};
 use futures::future::{AbortHandle, Abortable};
 use serde_json::Value;
 use tokio::sync::Mutex;
 use tokio::task;
 use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp::lsp_types::notification::{self, Notification};
 use tower_lsp::lsp_types::request::Request;
 use tower_lsp::lsp_types::{
-    CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability, CompletionOptions,
-    CompletionParams, CompletionResponse, DidChangeConfigurationParams,
-    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
-    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentFormattingParams,
-    DocumentSymbolParams, DocumentSymbolResponse, ExecuteCommandOptions, ExecuteCommandParams,
+    CodeAction, CodeActionKind, CodeActionOptions, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, CodeLens, CodeLensOptions, CodeLensParams, CompletionOptions,
+    CompletionParams, CompletionResponse, DiagnosticOptions, DiagnosticServerCapabilities,
+    DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidChangeWatchedFilesRegistrationOptions, DidChangeWorkspaceFoldersParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    DocumentDiagnosticParams, DocumentDiagnosticReportResult, DocumentFormattingParams,
+    DocumentRangeFormattingParams, DocumentSymbolParams, DocumentSymbolResponse,
+    ExecuteCommandOptions, ExecuteCommandParams, FileChangeType, FileSystemWatcher, GlobPattern,
     GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, InitializeParams,
-    InitializeResult, InitializedParams, Location, MessageActionItem, MessageType, OneOf,
-    ReferenceParams, RenameParams, ServerCapabilities, SignatureHelp, SignatureHelpOptions,
-    SignatureHelpParams, SymbolInformation, TextDocumentSyncCapability, TextDocumentSyncKind,
+    InitializeResult, InitializedParams, Location, MessageActionItem, MessageType, OneOf, Position,
+    ReferenceParams, Registration, RenameParams, ResourceOperationKind, ServerCapabilities,
+    SignatureHelp, SignatureHelpOptions, SignatureHelpParams, SymbolInformation,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    WorkDoneProgressCancelParams, WorkspaceDiagnosticParams, WorkspaceDiagnosticReportResult,
     WorkspaceEdit, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
     WorkspaceSymbolParams, request,
 };
@@ -31,15 +38,24 @@ use tracing::{debug, error, info_span, warn};
 use crate::config;
 use crate::diagnostics::Diagnostics;
 use crate::document;
+use crate::forge_test;
 use crate::handlers;
-use crate::lsp_utils;
+use crate::lsp_ext;
+use crate::lsp_utils::{self, ClientPaths};
+use crate::onchain;
 use crate::profile;
+use crate::progress::Progress;
 use crate::state::ServerState;
 use crate::status;
-use crate::task_pool::TaskPool;
+use crate::task_pool::{Priority, TaskPool};
 use crate::workspace;
 use sa_config::ResolvedFoundryConfig;
+use sa_ide::CancellationToken;
+use sa_ide_diagnostics::{Diagnostic, DiagnosticSeverity, DiagnosticSource};
+use sa_paths::{NormalizedPath, RealPathCache};
+use sa_span::lsp::{PositionEncoding, from_lsp_position_with, to_lsp_range_with};
 use sa_toolchain::{Toolchain, is_svm_installed};
+use sa_vfs::{VfsChange, VfsWatcher};
 
 const PROFILE_METHOD_SLOW_REQUEST: &str = "solidity-analyzer/slowRequest";
 const METHOD_GOTO_DEFINITION: &str = request::GotoDefinition::METHOD;
@@ -47,20 +63,75 @@ const METHOD_HOVER: &str = request::HoverRequest::METHOD;
 const METHOD_SIGNATURE_HELP: &str = request::SignatureHelpRequest::METHOD;
 const METHOD_COMPLETION: &str = request::Completion::METHOD;
 const METHOD_FORMATTING: &str = request::Formatting::METHOD;
+const METHOD_RANGE_FORMATTING: &str = request::RangeFormatting::METHOD;
 const METHOD_CODE_ACTION: &str = request::CodeActionRequest::METHOD;
+const METHOD_CODE_ACTION_RESOLVE: &str = request::CodeActionResolveRequest::METHOD;
 const METHOD_REFERENCES: &str = request::References::METHOD;
 const METHOD_RENAME: &str = request::Rename::METHOD;
 const METHOD_DOCUMENT_SYMBOL: &str = request::DocumentSymbolRequest::METHOD;
 const METHOD_WORKSPACE_SYMBOL: &str = request::WorkspaceSymbolRequest::METHOD;
+const METHOD_SYNTAX_TREE: &str = lsp_ext::SyntaxTree::METHOD;
+const METHOD_VIEW_HIR: &str = lsp_ext::ViewHir::METHOD;
+const METHOD_INHERITANCE_GRAPH: &str = lsp_ext::InheritanceGraphRequest::METHOD;
+const METHOD_STORAGE_LAYOUT: &str = lsp_ext::StorageLayout::METHOD;
+const METHOD_STORAGE_UPGRADE_CHECK: &str = lsp_ext::StorageUpgradeCheck::METHOD;
+const METHOD_FLATTEN_CONTRACT: &str = lsp_ext::FlattenContract::METHOD;
+const METHOD_RELOAD_WORKSPACE: &str = lsp_ext::ReloadWorkspace::METHOD;
+const METHOD_DISCOVER_TESTS: &str = lsp_ext::DiscoverTests::METHOD;
+const METHOD_CONTRACT_ARTIFACT: &str = lsp_ext::ContractArtifactRequest::METHOD;
+const METHOD_CODE_LENS: &str = request::CodeLensRequest::METHOD;
 const COMMAND_INSTALL_FOUNDRY_SOLC: &str = "solidity-analyzer.installFoundrySolc";
 const COMMAND_LIST_INDEXED_FILES: &str = "solidity-analyzer.indexedFiles";
+const COMMAND_SELECT_PROFILE: &str = "solidity-analyzer.selectProfile";
+const COMMAND_PROJECT_STRUCTURE: &str = "solidity-analyzer.projectStructure";
+const COMMAND_MEMORY_USAGE: &str = "solidity-analyzer.memoryUsage";
+const COMMAND_VIEW_HIR: &str = "solidity-analyzer.viewHir";
+const COMMAND_VIEW_DEF_MAP: &str = "solidity-analyzer.viewDefMap";
+pub(crate) const COMMAND_RUN_TEST: &str = "solidity-analyzer.runTest";
+const COMMAND_IMPORT_SLITHER: &str = "solidity-analyzer.importSlither";
+const COMMAND_FETCH_VERIFIED_SOURCES: &str = "solidity-analyzer.fetchVerifiedSources";
+/// The report `solidity-analyzer.importSlither` reads when given no path, relative to the root.
+const DEFAULT_SLITHER_REPORT: &str = "slither.json";
+
+/// Requests that walk the whole project run on the background lane; everything else answers
+/// the user as they type.
+fn priority(method: &str) -> Priority {
+    match method {
+        METHOD_REFERENCES
+        | METHOD_RENAME
+        | METHOD_WORKSPACE_SYMBOL
+        | METHOD_INHERITANCE_GRAPH
+        | METHOD_DISCOVER_TESTS
+        | COMMAND_PROJECT_STRUCTURE
+        | COMMAND_MEMORY_USAGE => Priority::Background,
+        _ => Priority::Latency,
+    }
+}
 const ERROR_SERVER_NOT_INITIALIZED: i64 = -32002;
+const DIAGNOSTIC_IDENTIFIER: &str = "solidity-analyzer";
+const FILE_WATCHER_ID: &str = "solidity-analyzer/fileWatcher";
+/// Sources are watched too, so changes the server's own watcher misses (remote file systems,
+/// roots it could not watch) still reach the VFS.
+const WATCH_PATTERNS: [&str; 6] = [
+    "**/*.sol",
+    "**/foundry.toml",
+    "**/remappings.txt",
+    "**/hardhat.config.{js,ts}",
+    "**/{ape,brownie}-config.yaml",
+    "**/out/**/*.json",
+];
+/// Editors and `git checkout` tend to emit several events per config change; only the last one
+/// within this window triggers a reload.
+const CONFIG_RELOAD_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Quiet period before on-disk source changes are applied, and how often the watcher is polled.
+const FILE_WATCH_DEBOUNCE: Duration = Duration::from_millis(50);
+const FILE_WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct Server {
     client: Client,
     state: Arc<Mutex<ServerState>>,
     task_pool: TaskPool,
-    diagnostics: Diagnostics,
+    diagnostics: Arc<Diagnostics>,
 }
 
 impl Server {
@@ -69,11 +140,16 @@ impl Server {
         // Safe to call repeatedly because init_from_env is idempotent.
         profile::init_from_env();
         let state = Arc::new(Mutex::new(ServerState::new()));
-        let diagnostics = Diagnostics::new(client.clone(), Arc::clone(&state));
+        let task_pool = TaskPool::new();
+        let diagnostics = Arc::new(Diagnostics::new(
+            client.clone(),
+            Arc::clone(&state),
+            task_pool.clone(),
+        ));
         Self {
             client,
             state,
-            task_pool: TaskPool::new(),
+            task_pool,
             diagnostics,
         }
     }
@@ -95,6 +171,113 @@ impl Server {
         }
     }
 
+    pub async fn syntax_tree(&self, params: lsp_ext::SyntaxTreeParams) -> Result<Option<String>> {
+        self.run_handler(METHOD_SYNTAX_TREE, move |analysis, vfs, encoding| {
+            handlers::debug_dump::syntax_tree(analysis, vfs, encoding, params)
+        })
+        .await
+    }
+
+    pub async fn view_hir(&self, params: lsp_ext::ViewHirParams) -> Result<Option<String>> {
+        self.run_handler(METHOD_VIEW_HIR, move |analysis, vfs, _encoding| {
+            handlers::debug_dump::view_hir(analysis, vfs, &params.text_document.uri)
+        })
+        .await
+    }
+
+    pub async fn inheritance_graph(
+        &self,
+        params: lsp_ext::InheritanceGraphParams,
+    ) -> Result<Option<lsp_ext::InheritanceGraph>> {
+        self.run_handler(METHOD_INHERITANCE_GRAPH, move |analysis, vfs, encoding| {
+            handlers::inheritance_graph::inheritance_graph(analysis, vfs, encoding, params)
+        })
+        .await
+    }
+
+    pub async fn storage_layout(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<Vec<lsp_ext::StorageSlot>>> {
+        self.run_handler(METHOD_STORAGE_LAYOUT, move |analysis, vfs, encoding| {
+            handlers::storage_layout::storage_layout(analysis, vfs, encoding, params)
+        })
+        .await
+    }
+
+    pub async fn storage_upgrade_check(
+        &self,
+        params: lsp_ext::StorageUpgradeCheckParams,
+    ) -> Result<Option<Vec<lsp_ext::StorageUpgradeIssue>>> {
+        self.run_handler(
+            METHOD_STORAGE_UPGRADE_CHECK,
+            move |analysis, vfs, encoding| {
+                handlers::storage_layout::storage_upgrade_check(analysis, vfs, encoding, params)
+            },
+        )
+        .await
+    }
+
+    pub async fn flatten_contract(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<String>> {
+        self.run_handler(METHOD_FLATTEN_CONTRACT, move |analysis, vfs, encoding| {
+            handlers::flatten_contract::flatten_contract(analysis, vfs, encoding, params)
+        })
+        .await
+    }
+
+    pub async fn reload_workspace(&self) -> Result<String> {
+        self.diagnostics.begin_loading().await;
+        let result = {
+            let mut state = self.state.lock().await;
+            workspace::reload_workspace(&mut state)
+        };
+        self.diagnostics.end_loading().await;
+        match result {
+            Ok(summary) => {
+                let summary = summary.to_string();
+                self.client
+                    .log_message(MessageType::INFO, summary.clone())
+                    .await;
+                Ok(summary)
+            }
+            Err(error) => {
+                warn!(?error, "failed to reload workspace");
+                Err(Error {
+                    code: ErrorCode::InternalError,
+                    message: format!("{METHOD_RELOAD_WORKSPACE} failed: {error:#}").into(),
+                    data: None,
+                })
+            }
+        }
+    }
+
+    pub async fn discover_tests(
+        &self,
+        params: lsp_ext::DiscoverTestsParams,
+    ) -> Result<Vec<lsp_ext::TestContract>> {
+        Ok(self
+            .run_handler(METHOD_DISCOVER_TESTS, move |analysis, vfs, encoding| {
+                Some(handlers::test_discovery::discover_tests(
+                    analysis, vfs, encoding, params,
+                ))
+            })
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub async fn contract_artifact(
+        &self,
+        params: lsp_ext::ContractArtifactParams,
+    ) -> Result<Option<lsp_ext::ContractArtifact>> {
+        self.run_handler(METHOD_CONTRACT_ARTIFACT, move |analysis, vfs, _| {
+            handlers::contract_artifact::contract_artifact(analysis, vfs, params)
+        })
+        .await
+    }
+
     /// Runs a handler function with the standard snapshot/VFS/cancellation pattern.
     ///
     /// This helper encapsulates the common pattern for request handlers:
@@ -105,17 +288,35 @@ impl Server {
     async fn run_handler<T, F>(&self, method: &'static str, handler: F) -> Result<Option<T>>
     where
         T: Send + 'static,
-        F: FnOnce(&sa_ide::Analysis, &sa_vfs::VfsSnapshot) -> Option<T> + Send + 'static,
+        F: FnOnce(&sa_ide::Analysis, &sa_vfs::VfsSnapshot, PositionEncoding) -> Option<T>
+            + Send
+            + 'static,
     {
-        let (analysis, vfs) = self.snapshot().await;
+        let (analysis, vfs, encoding) = {
+            let state = self.state.lock().await;
+            (
+                state.analysis_host.snapshot(),
+                state.vfs_snapshot.clone(),
+                state.position_encoding,
+            )
+        };
         let Some(vfs) = vfs else {
             return Ok(None);
         };
-        let task = self.task_pool.spawn(move || {
-            let _profile = profile::ProfileSpan::new(method);
-            let span = info_span!("lsp_request", method = %method);
-            span.in_scope(|| salsa::Cancelled::catch(AssertUnwindSafe(|| handler(&analysis, &vfs))))
-        });
+        // Dropping this future (the client sent `$/cancelRequest`) stops the handler at its next
+        // checkpoint instead of letting it run to completion on the pool.
+        let token = CancellationToken::new();
+        let _cancel_on_drop = token.drop_guard();
+        let analysis = analysis.with_cancellation(token);
+        let task = self
+            .task_pool
+            .spawn_with_priority(priority(method), move || {
+                let _profile = profile::ProfileSpan::new(method);
+                let span = info_span!("lsp_request", method = %method);
+                span.in_scope(|| {
+                    salsa::Cancelled::catch(AssertUnwindSafe(|| handler(&analysis, &vfs, encoding)))
+                })
+            });
         match task.await {
             Ok(Ok(result)) => Ok(result),
             Ok(Err(_)) => Err(Error::request_cancelled()),
@@ -124,8 +325,20 @@ impl Server {
         }
     }
 
-    fn capabilities() -> ServerCapabilities {
+    /// Pull diagnostics are only advertised to clients that support them; the others keep
+    /// receiving `publishDiagnostics`.
+    fn capabilities(pull_diagnostics: bool, encoding: PositionEncoding) -> ServerCapabilities {
+        let diagnostic_provider = pull_diagnostics.then(|| {
+            DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some(DIAGNOSTIC_IDENTIFIER.to_string()),
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: Default::default(),
+            })
+        });
         ServerCapabilities {
+            position_encoding: Some(encoding.kind()),
+            diagnostic_provider,
             text_document_sync: Some(TextDocumentSyncCapability::Kind(
                 TextDocumentSyncKind::INCREMENTAL,
             )),
@@ -143,12 +356,20 @@ impl Server {
                 completion_item: None,
                 work_done_progress_options: Default::default(),
             }),
-            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX, CodeActionKind::REFACTOR]),
+                resolve_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })),
             references_provider: Some(OneOf::Left(true)),
             rename_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
             document_symbol_provider: Some(OneOf::Left(true)),
             workspace_symbol_provider: Some(OneOf::Left(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
             workspace: Some(WorkspaceServerCapabilities {
                 workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                     supported: Some(true),
@@ -160,6 +381,14 @@ impl Server {
                 commands: vec![
                     COMMAND_INSTALL_FOUNDRY_SOLC.to_string(),
                     COMMAND_LIST_INDEXED_FILES.to_string(),
+                    COMMAND_SELECT_PROFILE.to_string(),
+                    COMMAND_PROJECT_STRUCTURE.to_string(),
+                    COMMAND_MEMORY_USAGE.to_string(),
+                    COMMAND_VIEW_HIR.to_string(),
+                    COMMAND_VIEW_DEF_MAP.to_string(),
+                    COMMAND_RUN_TEST.to_string(),
+                    COMMAND_IMPORT_SLITHER.to_string(),
+                    COMMAND_FETCH_VERIFIED_SOURCES.to_string(),
                 ],
                 work_done_progress_options: Default::default(),
             }),
@@ -185,6 +414,23 @@ impl Server {
         });
     }
 
+    /// Loads what `uri` imports before a navigation request, so targets in library files that
+    /// workspace loading deferred exist in the VFS.
+    async fn load_imports(&self, uri: &Url) {
+        if let Some(path) = lsp_utils::url_to_path(uri) {
+            let mut state = self.state.lock().await;
+            document::load_imports(&mut state, &path);
+        }
+    }
+
+    async fn config_for_uri(&self, uri: &Url) -> Option<ResolvedFoundryConfig> {
+        let state = self.state.lock().await;
+        match lsp_utils::url_to_path(uri) {
+            Some(path) => state.config_for_path(&path),
+            None => state.config.clone(),
+        }
+    }
+
     async fn install_foundry_solc(&self) -> Result<String> {
         let config = { self.state.lock().await.config.clone() };
         let Some(config) = config else {
@@ -219,6 +465,209 @@ impl Server {
             }),
         }
     }
+
+    /// Publishes the findings of a `slither --json` report, replacing those of the previous
+    /// import. `path` defaults to `slither.json` and is resolved against the workspace root.
+    async fn import_slither(&self, path: Option<String>) -> Result<String> {
+        let Some(root) = self.state.lock().await.root_path.clone() else {
+            return Err(Error {
+                code: ErrorCode::ServerError(ERROR_SERVER_NOT_INITIALIZED),
+                message: "workspace root unavailable; server not initialized".into(),
+                data: None,
+            });
+        };
+        let root = PathBuf::from(root.as_str());
+        let report = root.join(path.as_deref().unwrap_or(DEFAULT_SLITHER_REPORT));
+        let findings = std::fs::read_to_string(&report)
+            .with_context(|| format!("failed to read {}", report.display()))
+            .and_then(|json| sa_ide_diagnostics::parse_slither_report(&json, &root))
+            .map_err(|error| Error {
+                code: ErrorCode::InternalError,
+                message: format!("{error:#}").into(),
+                data: None,
+            })?;
+        let count = findings.len();
+        self.diagnostics.update_slither(findings).await;
+        Ok(format!("Imported {count} Slither finding(s)"))
+    }
+
+    /// Loads the verified sources of the contract at `address` on `chain_id`, downloading them
+    /// into the project's cache unless an earlier fetch left them there. Requires
+    /// `onchain.enable`, as the lookup goes to Sourcify and Etherscan.
+    async fn fetch_verified_sources(&self, address: &str, chain_id: Option<u64>) -> Result<String> {
+        let address = onchain::parse_address(address)
+            .ok_or_else(|| Error::invalid_params("expected a contract address"))?;
+        let (settings, config) = {
+            let state = self.state.lock().await;
+            (state.lsp_config.onchain.clone(), state.config.clone())
+        };
+        if !settings.enable {
+            return Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: "fetching verified sources is disabled; enable onchain.enable".into(),
+                data: None,
+            });
+        }
+        let Some(config) = config else {
+            return Err(Error {
+                code: ErrorCode::ServerError(ERROR_SERVER_NOT_INITIALIZED),
+                message: "workspace configuration unavailable; server not initialized".into(),
+                data: None,
+            });
+        };
+        let chain_id = chain_id.unwrap_or(settings.chain_id);
+        let dir = onchain::cache_dir(&config, chain_id, &address);
+        let contract = match onchain::read_cache(&dir) {
+            Some(contract) => contract,
+            None => {
+                let etherscan_api_key = config.foundry_config().etherscan_api_key.as_deref();
+                onchain::fetch(&settings, etherscan_api_key, chain_id, &address)
+                    .await
+                    .and_then(|contract| onchain::write_cache(&dir, &contract))
+                    .map_err(|error| Error {
+                        code: ErrorCode::InternalError,
+                        message: format!("{error:#}").into(),
+                        data: None,
+                    })?
+            }
+        };
+        let changes = contract
+            .files
+            .iter()
+            .filter_map(|file| {
+                let text = std::fs::read_to_string(file).ok()?;
+                Some(VfsChange::Set {
+                    path: lsp_utils::normalize_path(file),
+                    text: Arc::from(text),
+                })
+            })
+            .collect::<Vec<_>>();
+        let count = changes.len();
+        {
+            let mut state = self.state.lock().await;
+            state.vfs.apply_changes(changes);
+            let snapshot = state.vfs.snapshot();
+            document::apply_snapshot(&mut state, snapshot);
+        }
+        Ok(format!(
+            "Loaded {count} verified source file(s) of {}",
+            contract.name
+        ))
+    }
+
+    /// The verified source of the contract deployed at the address literal under `position`,
+    /// if it has been fetched.
+    async fn verified_source_definition(
+        &self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<GotoDefinitionResponse> {
+        let path = lsp_utils::url_to_path(uri)?;
+        let state = self.state.lock().await;
+        let settings = &state.lsp_config.onchain;
+        if !settings.enable {
+            return None;
+        }
+        let snapshot = state.vfs_snapshot.as_ref()?;
+        let text = snapshot.file_text(snapshot.file_id(&path)?)?;
+        let offset = from_lsp_position_with(position, text, state.position_encoding)?;
+        let address = onchain::address_at(text, usize::from(offset))?;
+        let config = state.nearest_config(&path)?;
+        let contract =
+            onchain::read_cache(&onchain::cache_dir(config, settings.chain_id, &address))?;
+        let (source, range) = onchain::contract_declaration(&contract)?;
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: Url::from_file_path(&contract.main).ok()?,
+            range: to_lsp_range_with(range, &source, state.position_encoding),
+        }))
+    }
+
+    /// Runs `forge test` for the test, or every test of the contract, named in `arguments` and
+    /// publishes the failures on the failing tests. Returns a pass/fail summary.
+    async fn run_test(&self, arguments: lsp_ext::RunTestArguments) -> Result<String> {
+        let path = lsp_utils::url_to_path(&arguments.uri)
+            .ok_or_else(|| Error::invalid_params("expected a file URI"))?;
+        let (root, analysis, vfs) = {
+            let state = self.state.lock().await;
+            let root = state
+                .nearest_config(&path)
+                .map(|config| config.workspace().root().clone());
+            (
+                root,
+                state.analysis_host.snapshot(),
+                state.vfs_snapshot.clone(),
+            )
+        };
+        let Some(root) = root else {
+            return Err(Error {
+                code: ErrorCode::ServerError(ERROR_SERVER_NOT_INITIALIZED),
+                message: "workspace configuration unavailable; server not initialized".into(),
+                data: None,
+            });
+        };
+        // Forge can run for minutes; the analysis snapshot is not held across it, as it would
+        // block every edit until the run finishes.
+        let tests = vfs
+            .file_id(&path)
+            .map(|file_id| analysis.discover_tests(file_id))
+            .unwrap_or_default();
+        drop(analysis);
+        let ran = tests
+            .into_iter()
+            .filter(|contract| contract.name == arguments.contract)
+            .flat_map(|contract| contract.tests)
+            .filter(|test| {
+                arguments
+                    .test
+                    .as_ref()
+                    .is_none_or(|name| *name == test.name)
+            })
+            .map(|test| (test.name, test.range))
+            .collect::<Vec<_>>();
+
+        let relative = Path::new(path.as_str())
+            .strip_prefix(root.as_str())
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| path.as_str().to_string());
+        let args =
+            forge_test::forge_test_args(&relative, &arguments.contract, arguments.test.as_deref());
+        let outcomes = forge_test::run_forge_test(&self.client, Path::new(root.as_str()), &args)
+            .await
+            .map_err(|error| Error {
+                code: ErrorCode::InternalError,
+                message: format!("{error:#}").into(),
+                data: None,
+            })?;
+
+        let failures = outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed && outcome.contract == arguments.contract)
+            .filter_map(|outcome| {
+                let (_, range) = ran.iter().find(|(name, _)| *name == outcome.test)?;
+                Some(Diagnostic {
+                    file_path: path.clone(),
+                    range: *range,
+                    severity: DiagnosticSeverity::Error,
+                    code: None,
+                    source: DiagnosticSource::ForgeTest,
+                    fixable: false,
+                    message: match &outcome.reason {
+                        Some(reason) => format!("{} failed: {reason}", outcome.test),
+                        None => format!("{} failed", outcome.test),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let ranges = ran.iter().map(|(_, range)| *range).collect::<Vec<_>>();
+        self.diagnostics
+            .update_test_results(path, &ranges, failures)
+            .await;
+        let passed = outcomes.iter().filter(|outcome| outcome.passed).count();
+        Ok(format!(
+            "{passed} passed, {} failed",
+            outcomes.len() - passed
+        ))
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -227,6 +676,8 @@ impl LanguageServer for Server {
         let mut state = self.state.lock().await;
         if let Some(settings) = params.initialization_options.clone() {
             state.lsp_config = config::LspConfig::from_settings(settings);
+            let sema = state.lsp_config.sema.cache_config();
+            state.analysis_host.set_sema_cache_config(sema);
         }
         state.supports_server_status = params
             .capabilities
@@ -235,15 +686,72 @@ impl LanguageServer for Server {
             .and_then(|value| value.get("serverStatusNotification"))
             .and_then(Value::as_bool)
             .unwrap_or(false);
-        let root_path = params
-            .workspace_folders
+        state.supports_code_action_resolve = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|caps| caps.code_action.as_ref())
+            .and_then(|caps| caps.resolve_support.as_ref())
+            .is_some_and(|support| support.properties.iter().any(|prop| prop == "edit"));
+        state.supports_resource_operations = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|caps| caps.workspace_edit.as_ref())
+            .is_some_and(|caps| {
+                caps.document_changes == Some(true)
+                    && caps.resource_operations.as_ref().is_some_and(|kinds| {
+                        kinds.contains(&ResourceOperationKind::Create)
+                            && kinds.contains(&ResourceOperationKind::Rename)
+                    })
+            });
+        state.supports_watched_files_registration = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|caps| caps.did_change_watched_files.as_ref())
+            .and_then(|caps| caps.dynamic_registration)
+            .unwrap_or(false);
+        state.supports_pull_diagnostics = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|caps| caps.diagnostic.is_some());
+        state.supports_diagnostic_refresh = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|caps| caps.diagnostic.as_ref())
+            .and_then(|caps| caps.refresh_support)
+            .unwrap_or(false);
+        // Clients list the encodings they support in order of preference; UTF-16 is the
+        // fallback every client has to understand.
+        state.position_encoding = params
+            .capabilities
+            .general
             .as_ref()
-            .and_then(|folders| folders.first())
-            .and_then(|folder| lsp_utils::url_to_path(&folder.uri))
-            .or_else(|| params.root_uri.as_ref().and_then(lsp_utils::url_to_path));
+            .and_then(|caps| caps.position_encodings.as_ref())
+            .and_then(|kinds| kinds.iter().find_map(PositionEncoding::from_kind))
+            .unwrap_or_default();
+        state.supports_work_done_progress = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|caps| caps.work_done_progress)
+            .unwrap_or(false);
+        let mut folder_paths = params
+            .workspace_folders
+            .iter()
+            .flatten()
+            .filter_map(|folder| ClientPaths::shared().remember(&folder.uri));
+        let root_path = folder_paths.next().or_else(|| {
+            let root_uri = params.root_uri.as_ref()?;
+            ClientPaths::shared().remember(root_uri)
+        });
+        let extra_folders = folder_paths.collect::<Vec<_>>();
         let mut discovered_root = root_path.as_ref().and_then(|root| {
             let path = Path::new(root.as_str());
-            if lsp_utils::contains_foundry_config(path) {
+            if lsp_utils::contains_project_config(path) {
                 Some(root.clone())
             } else {
                 state.discover_foundry_root(root)
@@ -261,8 +769,22 @@ impl LanguageServer for Server {
         {
             warn!(?error, root = %root, "failed to load foundry workspace");
         }
+        for folder in extra_folders {
+            let Some(root) = state.discover_foundry_root(&folder) else {
+                continue;
+            };
+            if state.is_loaded_root(&root) {
+                continue;
+            }
+            if let Err(error) = workspace::load_additional(&mut state, &root) {
+                warn!(?error, root = %root, "failed to load workspace folder");
+            }
+        }
         let result = InitializeResult {
-            capabilities: Self::capabilities(),
+            capabilities: Self::capabilities(
+                state.supports_pull_diagnostics,
+                state.position_encoding,
+            ),
             server_info: None,
         };
         drop(state);
@@ -270,18 +792,72 @@ impl LanguageServer for Server {
     }
 
     async fn initialized(&self, _: InitializedParams) {
-        let status_config = { self.state.lock().await.config.clone() };
+        let (status_config, register_watchers) = {
+            let mut state = self.state.lock().await;
+            // Notifications sent before `initialized` are dropped, so reports start here.
+            if state.supports_work_done_progress {
+                state.progress = Progress::spawn(self.client.clone());
+            }
+            (
+                state.config.clone(),
+                state.supports_watched_files_registration,
+            )
+        };
         self.log_status_for_config(status_config);
         self.diagnostics.publish_status().await;
 
+        if register_watchers {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                register_file_watchers(client).await;
+            });
+        }
+
+        match VfsWatcher::new(FILE_WATCH_DEBOUNCE) {
+            Ok(watcher) => {
+                let state = Arc::clone(&self.state);
+                let client = self.client.clone();
+                let diagnostics = Arc::downgrade(&self.diagnostics);
+                tokio::spawn(async move {
+                    watch_workspace_files(client, state, diagnostics, watcher).await;
+                });
+            }
+            Err(error) => warn!(?error, "failed to start the file watcher"),
+        }
+
         let client = self.client.clone();
         let state = Arc::clone(&self.state);
         tokio::spawn(async move {
             prompt_install_solc(client, state).await;
         });
+
+        let state = Arc::clone(&self.state);
+        let task_pool = self.task_pool.clone();
+        tokio::spawn(async move {
+            prime_sema(state, task_pool).await;
+        });
     }
 
     async fn shutdown(&self) -> Result<()> {
+        let (analysis, path) = {
+            let state = self.state.lock().await;
+            let Some(config) = state.config.as_ref() else {
+                return Ok(());
+            };
+            (
+                state.analysis_host.snapshot(),
+                workspace::hir_cache_path(config),
+            )
+        };
+        let saved = task::spawn_blocking(move || {
+            salsa::Cancelled::catch(AssertUnwindSafe(|| analysis.hir_cache().save(&path)))
+        })
+        .await;
+        match saved {
+            Ok(Ok(Ok(()))) => {}
+            Ok(Ok(Err(error))) => warn!(?error, "failed to save hir cache"),
+            Ok(Err(_)) | Err(_) => warn!("hir cache was not saved"),
+        }
         Ok(())
     }
 
@@ -295,6 +871,10 @@ impl LanguageServer for Server {
         if lsp_config.diagnostics.enable && lsp_config.diagnostics.on_change {
             self.diagnostics.did_change(&uri).await;
         }
+        if lsp_config.diagnostics.enable && lsp_config.diagnostics.checks {
+            self.diagnostics.run_checks(&uri, false).await;
+        }
+        self.diagnostics.check_sema(&uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -307,23 +887,36 @@ impl LanguageServer for Server {
         if lsp_config.diagnostics.enable && lsp_config.diagnostics.on_change {
             self.diagnostics.did_change(&uri).await;
         }
+        if lsp_config.diagnostics.enable && lsp_config.diagnostics.checks {
+            self.diagnostics.run_checks(&uri, true).await;
+        }
+        self.diagnostics.rebuild_sema(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let mut state = self.state.lock().await;
-        document::did_close(&mut state, params);
+        let uri = params.text_document.uri.clone();
+        {
+            let mut state = self.state.lock().await;
+            document::did_close(&mut state, params);
+        }
+        self.diagnostics.did_close(&uri).await;
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let uri = params.text_document.uri.clone();
-        let (analysis, vfs, config, lsp_config) = {
+        let (analysis, vfs, config, lsp_config, encoding) = {
             let mut state = self.state.lock().await;
             document::did_save(&mut state, params);
+            let config = match lsp_utils::url_to_path(&uri) {
+                Some(path) => state.config_for_path(&path),
+                None => state.config.clone(),
+            };
             (
                 state.analysis_host.snapshot(),
                 state.vfs_snapshot.clone(),
-                state.config.clone(),
+                config,
                 state.lsp_config.clone(),
+                state.position_encoding,
             )
         };
 
@@ -358,6 +951,7 @@ impl LanguageServer for Server {
                             handlers::did_save::format_on_save(
                                 &analysis,
                                 &vfs,
+                                encoding,
                                 &uri_for_task,
                                 &config,
                             )
@@ -413,53 +1007,244 @@ impl LanguageServer for Server {
     }
 
     async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
-        let mut state = self.state.lock().await;
-        state.lsp_config = config::LspConfig::from_settings(params.settings);
-        if let Err(error) = workspace::reload(&mut state) {
-            warn!(?error, "failed to reload foundry workspace");
+        let current = config::LspConfig::from_settings(params.settings);
+        let previous = {
+            let mut state = self.state.lock().await;
+            let previous = std::mem::replace(&mut state.lsp_config, current.clone());
+            if current.sema != previous.sema {
+                let sema = current.sema.cache_config();
+                state.analysis_host.set_sema_cache_config(sema);
+            }
+            previous
+        };
+        if current.needs_reload(&previous) {
+            self.diagnostics.begin_loading().await;
+            let result = {
+                let mut state = self.state.lock().await;
+                workspace::reload(&mut state)
+            };
+            self.diagnostics.end_loading().await;
+            if let Err(error) = result {
+                warn!(?error, "failed to reload foundry workspace");
+            }
+        }
+        if current.diagnostics != previous.diagnostics || current.lint != previous.lint {
+            self.diagnostics.settings_changed().await;
+        }
+    }
+
+    async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
+        let state = self.state.lock().await;
+        state.progress.cancel(&params.token);
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        if params.event.added.is_empty() && params.event.removed.is_empty() {
+            return;
         }
+        self.diagnostics.begin_loading().await;
+        let mut removed = Vec::new();
+        {
+            let mut state = self.state.lock().await;
+            for folder in params.event.removed {
+                let Some(path) = lsp_utils::url_to_path(&folder.uri) else {
+                    continue;
+                };
+                let Some(root) = state.discover_foundry_root(&path) else {
+                    ClientPaths::shared().forget(&path);
+                    continue;
+                };
+                match workspace::unload(&mut state, &root) {
+                    Ok(true) => removed.push((path, root)),
+                    Ok(false) => ClientPaths::shared().forget(&path),
+                    Err(error) => {
+                        warn!(?error, root = %root, "failed to unload workspace folder");
+                        removed.push((path, root));
+                    }
+                }
+            }
+            for folder in params.event.added {
+                let Some(root) = ClientPaths::shared()
+                    .remember(&folder.uri)
+                    .and_then(|path| state.discover_foundry_root(&path))
+                else {
+                    continue;
+                };
+                if state.is_loaded_root(&root) {
+                    continue;
+                }
+                let result = if state.config.is_none() {
+                    state.root_path = Some(root.clone());
+                    workspace::load(&mut state, &root, None)
+                } else {
+                    workspace::load_additional(&mut state, &root)
+                };
+                if let Err(error) = result {
+                    warn!(?error, root = %root, "failed to load workspace folder");
+                }
+            }
+        }
+        // The diagnostics go out under the folder's URIs, so it is forgotten only afterwards.
+        for (path, root) in removed {
+            self.diagnostics.remove_root(&root).await;
+            ClientPaths::shared().forget(&path);
+        }
+        self.diagnostics.end_loading().await;
     }
 
     async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
-        let should_reload = params.changes.iter().any(|change| {
-            lsp_utils::url_to_path(&change.uri)
-                .is_some_and(|path| lsp_utils::is_foundry_config_path(&path))
-        });
-        if should_reload {
+        // A directory symlink that was created, removed or replaced resolves differently now.
+        if params
+            .changes
+            .iter()
+            .any(|change| change.typ != FileChangeType::CHANGED)
+        {
+            RealPathCache::shared().clear();
+            self.state.lock().await.analysis_host.clear_node_modules();
+        }
+        let (configs, sources): (Vec<_>, Vec<_>) = params
+            .changes
+            .iter()
+            .filter_map(|change| lsp_utils::url_to_path(&change.uri))
+            .partition(|path| {
+                lsp_utils::is_foundry_config_path(path)
+                    || lsp_utils::is_hardhat_config_path(path)
+                    || lsp_utils::is_python_project_config_path(path)
+            });
+        let (artifacts, sources): (Vec<_>, Vec<_>) =
+            sources.into_iter().partition(lsp_utils::is_artifact_path);
+
+        let snapshot = { self.state.lock().await.vfs_snapshot.clone() };
+        if let Some(snapshot) = snapshot
+            && !sources.is_empty()
+        {
+            let paths = sources.iter().map(|path| PathBuf::from(path.as_str()));
+            let changes = sa_vfs::resolve_changes(paths, &snapshot);
+            if !changes.is_empty() {
+                apply_disk_changes(
+                    &self.client,
+                    &self.state,
+                    Some(Arc::clone(&self.diagnostics)),
+                    changes,
+                )
+                .await;
+            }
+        }
+
+        if configs.is_empty() && artifacts.is_empty() {
+            return;
+        }
+
+        let generation = {
+            let mut state = self.state.lock().await;
+            state.config_reload_generation = state.config_reload_generation.wrapping_add(1);
+            state.config_reload_generation
+        };
+        tokio::time::sleep(CONFIG_RELOAD_DEBOUNCE).await;
+
+        if configs.is_empty() {
+            // A `forge build` finished; only the artifacts need re-reading.
+            let loaded = {
+                let mut state = self.state.lock().await;
+                (state.config_reload_generation == generation)
+                    .then(|| workspace::reload_artifacts(&mut state))
+            };
+            if let Some(loaded) = loaded {
+                debug!(loaded, "reloaded build artifacts");
+            }
+            return;
+        }
+
+        self.diagnostics.begin_loading().await;
+        let result = {
             let mut state = self.state.lock().await;
-            if let Err(error) = workspace::reload(&mut state) {
+            (state.config_reload_generation == generation).then(|| workspace::reload(&mut state))
+        };
+        self.diagnostics.end_loading().await;
+        let Some(result) = result else {
+            debug!("config reload superseded by a newer change");
+            return;
+        };
+        match result {
+            Ok(summary) => {
+                self.client
+                    .log_message(MessageType::INFO, summary.to_string())
+                    .await;
+            }
+            Err(error) => {
                 warn!(?error, "failed to reload foundry workspace");
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("Failed to reload workspace configuration: {error:#}"),
+                    )
+                    .await;
             }
         }
     }
 
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        Ok(self
+            .diagnostics
+            .document_report(
+                &params.text_document.uri,
+                params.previous_result_id.as_deref(),
+            )
+            .await)
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        Ok(self
+            .diagnostics
+            .workspace_report(&params.previous_result_ids)
+            .await)
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
-        self.run_handler(METHOD_GOTO_DEFINITION, move |analysis, vfs| {
-            handlers::definition::goto_definition(analysis, vfs, params)
-        })
-        .await
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .clone();
+        let position = params.text_document_position_params.position;
+        self.load_imports(&uri).await;
+        let definition = self
+            .run_handler(METHOD_GOTO_DEFINITION, move |analysis, vfs, encoding| {
+                handlers::definition::goto_definition(analysis, vfs, encoding, params)
+            })
+            .await?;
+        if definition.is_some() {
+            return Ok(definition);
+        }
+        Ok(self.verified_source_definition(&uri, position).await)
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
-        self.run_handler(METHOD_HOVER, move |analysis, vfs| {
-            handlers::hover::hover(analysis, vfs, params)
+        self.run_handler(METHOD_HOVER, move |analysis, vfs, encoding| {
+            handlers::hover::hover(analysis, vfs, encoding, params)
         })
         .await
     }
 
     async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
-        self.run_handler(METHOD_SIGNATURE_HELP, move |analysis, vfs| {
-            handlers::signature_help::signature_help(analysis, vfs, params)
+        self.run_handler(METHOD_SIGNATURE_HELP, move |analysis, vfs, encoding| {
+            handlers::signature_help::signature_help(analysis, vfs, encoding, params)
         })
         .await
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
-        self.run_handler(METHOD_COMPLETION, move |analysis, vfs| {
-            handlers::completion::completion(analysis, vfs, params)
+        self.run_handler(METHOD_COMPLETION, move |analysis, vfs, encoding| {
+            handlers::completion::completion(analysis, vfs, encoding, params)
         })
         .await
     }
@@ -468,9 +1253,20 @@ impl LanguageServer for Server {
         &self,
         params: DocumentFormattingParams,
     ) -> Result<Option<Vec<tower_lsp::lsp_types::TextEdit>>> {
-        let config = { self.state.lock().await.config.clone() };
-        self.run_handler(METHOD_FORMATTING, move |analysis, vfs| {
-            handlers::formatting::formatting(analysis, vfs, params, config)
+        let config = self.config_for_uri(&params.text_document.uri).await;
+        self.run_handler(METHOD_FORMATTING, move |analysis, vfs, encoding| {
+            handlers::formatting::formatting(analysis, vfs, encoding, params, config)
+        })
+        .await
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<tower_lsp::lsp_types::TextEdit>>> {
+        let config = self.config_for_uri(&params.text_document.uri).await;
+        self.run_handler(METHOD_RANGE_FORMATTING, move |analysis, vfs, encoding| {
+            handlers::formatting::range_formatting(analysis, vfs, encoding, params, config)
         })
         .await
     }
@@ -479,12 +1275,46 @@ impl LanguageServer for Server {
         &self,
         params: CodeActionParams,
     ) -> Result<Option<Vec<CodeActionOrCommand>>> {
-        self.run_handler(METHOD_CODE_ACTION, move |analysis, vfs| {
-            handlers::code_action::code_action(analysis, vfs, params)
+        let (lazy, resource_operations) = {
+            let state = self.state.lock().await;
+            (
+                state.supports_code_action_resolve,
+                state.supports_resource_operations,
+            )
+        };
+        self.run_handler(METHOD_CODE_ACTION, move |analysis, vfs, encoding| {
+            handlers::code_action::code_action(
+                analysis,
+                vfs,
+                encoding,
+                params,
+                lazy,
+                resource_operations,
+            )
         })
         .await
     }
 
+    async fn code_action_resolve(&self, params: CodeAction) -> Result<CodeAction> {
+        let fallback = params.clone();
+        let resource_operations = { self.state.lock().await.supports_resource_operations };
+        let resolved = self
+            .run_handler(
+                METHOD_CODE_ACTION_RESOLVE,
+                move |analysis, vfs, encoding| {
+                    handlers::code_action::code_action_resolve(
+                        analysis,
+                        vfs,
+                        encoding,
+                        params,
+                        resource_operations,
+                    )
+                },
+            )
+            .await?;
+        Ok(resolved.unwrap_or(fallback))
+    }
+
     async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
         match params.command.as_str() {
             COMMAND_INSTALL_FOUNDRY_SOLC => {
@@ -503,20 +1333,124 @@ impl LanguageServer for Server {
                     paths.into_iter().map(Value::String).collect(),
                 )))
             }
+            COMMAND_SELECT_PROFILE => {
+                let profile = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .filter(|profile| !profile.is_empty())
+                    .map(str::to_string);
+                self.diagnostics.begin_loading().await;
+                let result = {
+                    let mut state = self.state.lock().await;
+                    workspace::select_profile(&mut state, profile).map(|()| {
+                        state
+                            .config
+                            .as_ref()
+                            .map(|config| Value::String(config.active_profile().name().to_string()))
+                    })
+                };
+                self.diagnostics.end_loading().await;
+                result.map_err(|error| Error {
+                    code: ErrorCode::InternalError,
+                    message: format!("failed to switch foundry profile: {error}").into(),
+                    data: None,
+                })
+            }
+            COMMAND_PROJECT_STRUCTURE => {
+                let projects = self
+                    .run_handler(COMMAND_PROJECT_STRUCTURE, |analysis, _vfs, _encoding| {
+                        Some(handlers::project_structure::project_structure(analysis))
+                    })
+                    .await?
+                    .unwrap_or_default();
+                serde_json::to_value(projects)
+                    .map(Some)
+                    .map_err(|error| Error {
+                        code: ErrorCode::InternalError,
+                        message: format!("failed to serialize project structure: {error}").into(),
+                        data: None,
+                    })
+            }
+            COMMAND_MEMORY_USAGE => {
+                let usage = self
+                    .run_handler(COMMAND_MEMORY_USAGE, |analysis, vfs, _encoding| {
+                        Some(handlers::memory_usage::memory_usage(analysis, vfs))
+                    })
+                    .await?;
+                usage
+                    .map(serde_json::to_value)
+                    .transpose()
+                    .map_err(|error| Error {
+                        code: ErrorCode::InternalError,
+                        message: format!("failed to serialize memory usage: {error}").into(),
+                        data: None,
+                    })
+            }
+            COMMAND_VIEW_HIR => {
+                let uri = command_uri(&params)?;
+                let dump = self
+                    .run_handler(COMMAND_VIEW_HIR, move |analysis, vfs, _encoding| {
+                        handlers::debug_dump::view_hir(analysis, vfs, &uri)
+                    })
+                    .await?;
+                Ok(dump.map(Value::String))
+            }
+            COMMAND_RUN_TEST => {
+                let arguments = params
+                    .arguments
+                    .into_iter()
+                    .next()
+                    .and_then(|argument| serde_json::from_value(argument).ok())
+                    .ok_or_else(|| Error::invalid_params("expected runTest arguments"))?;
+                let summary = self.run_test(arguments).await?;
+                Ok(Some(Value::String(summary)))
+            }
+            COMMAND_IMPORT_SLITHER => {
+                let path = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                let summary = self.import_slither(path).await?;
+                Ok(Some(Value::String(summary)))
+            }
+            COMMAND_FETCH_VERIFIED_SOURCES => {
+                let address = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| Error::invalid_params("expected a contract address"))?;
+                let chain_id = params.arguments.get(1).and_then(Value::as_u64);
+                let summary = self.fetch_verified_sources(address, chain_id).await?;
+                Ok(Some(Value::String(summary)))
+            }
+            COMMAND_VIEW_DEF_MAP => {
+                let uri = command_uri(&params)?;
+                let dump = self
+                    .run_handler(COMMAND_VIEW_DEF_MAP, move |analysis, vfs, _encoding| {
+                        handlers::debug_dump::view_def_map(analysis, vfs, &uri)
+                    })
+                    .await?;
+                Ok(dump.map(Value::String))
+            }
             _ => Ok(None),
         }
     }
 
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
-        self.run_handler(METHOD_REFERENCES, move |analysis, vfs| {
-            handlers::references::references(analysis, vfs, params)
+        self.load_imports(&params.text_document_position.text_document.uri)
+            .await;
+        self.run_handler(METHOD_REFERENCES, move |analysis, vfs, encoding| {
+            handlers::references::references(analysis, vfs, encoding, params)
         })
         .await
     }
 
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
-        self.run_handler(METHOD_RENAME, move |analysis, vfs| {
-            handlers::rename::rename(analysis, vfs, params)
+        let resource_operations = { self.state.lock().await.supports_resource_operations };
+        self.run_handler(METHOD_RENAME, move |analysis, vfs, encoding| {
+            handlers::rename::rename(analysis, vfs, encoding, params, resource_operations)
         })
         .await
     }
@@ -525,8 +1459,15 @@ impl LanguageServer for Server {
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
-        self.run_handler(METHOD_DOCUMENT_SYMBOL, move |analysis, vfs| {
-            handlers::document_symbols::document_symbols(analysis, vfs, params)
+        self.run_handler(METHOD_DOCUMENT_SYMBOL, move |analysis, vfs, encoding| {
+            handlers::document_symbols::document_symbols(analysis, vfs, encoding, params)
+        })
+        .await
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        self.run_handler(METHOD_CODE_LENS, move |analysis, vfs, encoding| {
+            handlers::test_discovery::code_lens(analysis, vfs, encoding, params)
         })
         .await
     }
@@ -535,13 +1476,165 @@ impl LanguageServer for Server {
         &self,
         params: WorkspaceSymbolParams,
     ) -> Result<Option<Vec<SymbolInformation>>> {
-        self.run_handler(METHOD_WORKSPACE_SYMBOL, move |analysis, vfs| {
-            handlers::workspace_symbols::workspace_symbols(analysis, vfs, params)
+        self.run_handler(METHOD_WORKSPACE_SYMBOL, move |analysis, vfs, encoding| {
+            handlers::workspace_symbols::workspace_symbols(analysis, vfs, encoding, params)
         })
         .await
     }
 }
 
+async fn register_file_watchers(client: Client) {
+    let watchers = WATCH_PATTERNS
+        .iter()
+        .map(|pattern| FileSystemWatcher {
+            glob_pattern: GlobPattern::String(pattern.to_string()),
+            kind: None,
+        })
+        .collect();
+    let options = DidChangeWatchedFilesRegistrationOptions { watchers };
+    let registration = Registration {
+        id: FILE_WATCHER_ID.to_string(),
+        method: notification::DidChangeWatchedFiles::METHOD.to_string(),
+        register_options: serde_json::to_value(options).ok(),
+    };
+    if let Err(error) = client.register_capability(vec![registration]).await {
+        warn!(?error, "failed to register file watchers");
+    }
+}
+
+/// The build output and compiler cache of a project, which the file watcher skips.
+fn build_dirs(config: &ResolvedFoundryConfig) -> Vec<PathBuf> {
+    let root = Path::new(config.workspace().root().as_str());
+    let foundry = config.foundry_config();
+    vec![root.join(&foundry.out), root.join(&foundry.cache_path)]
+}
+
+/// Feeds changes made outside the editor into the VFS until the server is dropped. Roots loaded
+/// after startup are picked up on the next poll.
+async fn watch_workspace_files(
+    client: Client,
+    state: Arc<Mutex<ServerState>>,
+    diagnostics: Weak<Diagnostics>,
+    mut watcher: VfsWatcher,
+) {
+    let mut attempted_roots = HashMap::<NormalizedPath, Vec<PathBuf>>::new();
+    while Arc::strong_count(&state) > 1 {
+        tokio::time::sleep(FILE_WATCH_POLL_INTERVAL).await;
+        let (roots, snapshot) = {
+            let state = state.lock().await;
+            let roots = state
+                .config
+                .iter()
+                .chain(state.projects.values().map(|project| &project.config))
+                .map(|config| (config.workspace().root().clone(), build_dirs(config)))
+                .collect::<HashMap<_, _>>();
+            (roots, state.vfs_snapshot.clone())
+        };
+        // Roots of removed workspace folders are no longer watched.
+        attempted_roots.retain(|root, _| {
+            let loaded = roots.contains_key(root);
+            if !loaded && let Err(error) = watcher.unwatch(Path::new(root.as_str())) {
+                warn!(?error, root = %root, "failed to stop watching workspace root");
+            }
+            loaded
+        });
+        for (root, ignored) in roots {
+            if attempted_roots.get(&root) == Some(&ignored) {
+                continue;
+            }
+            attempted_roots.insert(root.clone(), ignored.clone());
+            if let Err(error) = watcher.watch(Path::new(root.as_str()), ignored) {
+                warn!(?error, root = %root, "failed to watch workspace root");
+            }
+        }
+        let Some(snapshot) = snapshot else {
+            continue;
+        };
+        let Some(paths) = watcher.take_settled() else {
+            continue;
+        };
+        // Resolving reads every changed file and walks new directories.
+        let changes =
+            match task::spawn_blocking(move || sa_vfs::resolve_changes(paths, &snapshot)).await {
+                Ok(changes) => changes,
+                Err(error) => {
+                    warn!(?error, "failed to resolve on-disk file changes");
+                    continue;
+                }
+            };
+        if changes.is_empty() {
+            continue;
+        }
+        apply_disk_changes(&client, &state, diagnostics.upgrade(), changes).await;
+    }
+}
+
+/// Applies changes found on disk, re-checking open documents when files appeared or
+/// disappeared and warning about unsaved buffers the disk now disagrees with.
+async fn apply_disk_changes(
+    client: &Client,
+    state: &Arc<Mutex<ServerState>>,
+    diagnostics: Option<Arc<Diagnostics>>,
+    changes: Vec<VfsChange>,
+) {
+    let (applied, open_documents, lsp_config) = {
+        let mut state = state.lock().await;
+        let applied = document::apply_disk_changes(&mut state, changes);
+        if applied.files_added_or_removed {
+            RealPathCache::shared().clear();
+        }
+        let open_documents = state.open_documents.keys().cloned().collect::<Vec<_>>();
+        (applied, open_documents, state.lsp_config.clone())
+    };
+    // A file appearing or disappearing can break or fix imports in open documents.
+    if applied.files_added_or_removed
+        && lsp_config.diagnostics.enable
+        && lsp_config.diagnostics.on_change
+        && let Some(diagnostics) = diagnostics
+    {
+        for path in open_documents {
+            if let Some(uri) = lsp_utils::path_to_url(&path) {
+                diagnostics.did_change(&uri).await;
+            }
+        }
+    }
+    for path in applied.conflicts {
+        let message = format!(
+            "{path} changed on disk while it has unsaved changes; saving will overwrite the \
+             disk contents"
+        );
+        client.show_message(MessageType::WARNING, message).await;
+    }
+}
+
+/// Builds the semantic snapshot of every loaded project on the background lane, so the first
+/// request that needs one does not wait for it. Edits and the user cancelling the progress
+/// report stop it early.
+async fn prime_sema(state: Arc<Mutex<ServerState>>, task_pool: TaskPool) {
+    let (analysis, progress) = {
+        let state = state.lock().await;
+        (state.analysis_host.snapshot(), state.progress.clone())
+    };
+    let token = CancellationToken::new();
+    let analysis = analysis.with_cancellation(token.clone());
+    let report = progress.begin("Analyzing", Some(token));
+    let task = task_pool.spawn_with_priority(Priority::Background, move || {
+        let projects = analysis.project_ids();
+        for (idx, project_id) in projects.iter().enumerate() {
+            let primed =
+                salsa::Cancelled::catch(AssertUnwindSafe(|| analysis.prime_sema(*project_id)));
+            if primed.is_err() {
+                debug!("semantic analysis warm-up cancelled");
+                break;
+            }
+            report.report("Analyzing", idx + 1, projects.len(), "projects");
+        }
+    });
+    if let Err(error) = task.await {
+        warn!(?error, "semantic analysis warm-up failed");
+    }
+}
+
 async fn prompt_install_solc(client: Client, state: Arc<Mutex<ServerState>>) {
     let (config, lsp_config, already_prompted) = {
         let state = state.lock().await;
@@ -624,6 +1717,16 @@ async fn prompt_install_solc(client: Client, state: Arc<Mutex<ServerState>>) {
     }
 }
 
+/// The document URI passed as the first argument of a command.
+fn command_uri(params: &ExecuteCommandParams) -> Result<Url> {
+    params
+        .arguments
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|uri| Url::parse(uri).ok())
+        .ok_or_else(|| Error::invalid_params("expected a document URI argument"))
+}
+
 fn detect_missing_solc_prompt(config: ResolvedFoundryConfig) -> AnyhowResult<Option<String>> {
     if test_solc_install_message().is_some() {
         return Ok(Some(
@@ -690,7 +1793,6 @@ fn test_solc_install_message() -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sa_paths::NormalizedPath;
     use sa_project_model::{FoundryProfile, FoundryWorkspace};
     use sa_test_support::setup_foundry_root;
     use sa_test_support::write_stub_solc;
@@ -708,7 +1810,7 @@ mod tests {
         let called_for_handler = Arc::clone(&called);
 
         let result: Result<Option<()>> = server
-            .run_handler("test", move |_analysis, _vfs| {
+            .run_handler("test", move |_analysis, _vfs, _encoding| {
                 called_for_handler.store(true, Ordering::SeqCst);
                 Some(())
             })
@@ -3,30 +3,60 @@ use std::path::Path;
 
 use crate::config::LspConfig;
 use crate::lsp_utils;
+use crate::progress::Progress;
 use futures::future::AbortHandle;
 use sa_config::ResolvedFoundryConfig;
-use sa_ide::AnalysisHost;
-use sa_paths::NormalizedPath;
-use sa_vfs::{Vfs, VfsSnapshot};
+use sa_ide::{AnalysisHost, ProjectId};
+use sa_paths::{NormalizedPath, WorkspacePath};
+use sa_span::lsp::PositionEncoding;
+use sa_vfs::{FileId, Vfs, VfsSnapshot};
 
 #[derive(Debug, Clone, Copy)]
 pub struct OpenDocument {
     pub version: i32,
 }
 
+/// A project loaded alongside the primary workspace, e.g. a nested foundry.toml root.
+#[derive(Debug, Clone)]
+pub struct LoadedProject {
+    pub id: ProjectId,
+    pub config: ResolvedFoundryConfig,
+    pub indexed_files: HashSet<NormalizedPath>,
+}
+
 pub struct ServerState {
     pub(crate) analysis_host: AnalysisHost,
     pub(crate) vfs: Vfs,
     pub(crate) vfs_snapshot: Option<VfsSnapshot>,
     pub(crate) open_documents: HashMap<NormalizedPath, OpenDocument>,
     pub(crate) indexed_files: HashSet<NormalizedPath>,
+    /// Interfaces generated for ABI-only contracts, loaded in place of their missing sources.
+    pub(crate) abi_interfaces: HashSet<NormalizedPath>,
+    /// The file id and version each file had when its imports were last loaded.
+    pub(crate) loaded_imports: HashMap<NormalizedPath, (FileId, u32)>,
     pub(crate) foundry_root_cache: HashMap<NormalizedPath, Option<NormalizedPath>>,
     pub(crate) config: Option<ResolvedFoundryConfig>,
+    pub(crate) projects: HashMap<NormalizedPath, LoadedProject>,
+    pub(crate) next_project_id: u32,
     pub(crate) lsp_config: LspConfig,
     pub(crate) supports_server_status: bool,
+    pub(crate) supports_code_action_resolve: bool,
+    /// The client applies `CreateFile` and `RenameFile` operations in workspace edits.
+    pub(crate) supports_resource_operations: bool,
+    pub(crate) supports_watched_files_registration: bool,
+    /// The client pulls diagnostics (`textDocument/diagnostic`) instead of receiving them.
+    pub(crate) supports_pull_diagnostics: bool,
+    pub(crate) supports_diagnostic_refresh: bool,
+    pub(crate) supports_work_done_progress: bool,
+    /// The encoding negotiated for `Position.character` during `initialize`.
+    pub(crate) position_encoding: PositionEncoding,
+    pub(crate) config_reload_generation: u64,
     pub(crate) root_path: Option<NormalizedPath>,
     pub(crate) prompted_solc_install: bool,
+    /// Why loading a project failed the last time it was tried, e.g. a malformed foundry.toml.
+    pub(crate) load_error: Option<String>,
     pub(crate) format_tasks: FormatTaskState,
+    pub(crate) progress: Progress,
 }
 
 impl ServerState {
@@ -37,13 +67,27 @@ impl ServerState {
             vfs_snapshot: None,
             open_documents: HashMap::new(),
             indexed_files: HashSet::new(),
+            abi_interfaces: HashSet::new(),
+            loaded_imports: HashMap::new(),
             foundry_root_cache: HashMap::new(),
             config: None,
+            projects: HashMap::new(),
+            next_project_id: 1,
             lsp_config: LspConfig::default(),
             supports_server_status: false,
+            supports_code_action_resolve: false,
+            supports_resource_operations: false,
+            supports_watched_files_registration: false,
+            supports_pull_diagnostics: false,
+            supports_diagnostic_refresh: false,
+            supports_work_done_progress: false,
+            position_encoding: PositionEncoding::default(),
+            config_reload_generation: 0,
             root_path: None,
             prompted_solc_install: false,
+            load_error: None,
             format_tasks: FormatTaskState::default(),
+            progress: Progress::default(),
         }
     }
 }
@@ -55,6 +99,50 @@ impl Default for ServerState {
 }
 
 impl ServerState {
+    /// Returns the configuration of the nearest loaded project containing `path`, falling back
+    /// to the primary workspace.
+    pub(crate) fn config_for_path(&self, path: &NormalizedPath) -> Option<ResolvedFoundryConfig> {
+        self.nearest_config(path).cloned()
+    }
+
+    pub(crate) fn nearest_config(&self, path: &NormalizedPath) -> Option<&ResolvedFoundryConfig> {
+        self.config
+            .iter()
+            .chain(self.projects.values().map(|project| &project.config))
+            .filter(|config| WorkspacePath::new(config.workspace().root(), path).is_some())
+            .max_by_key(|config| config.workspace().root().as_str().len())
+            .or(self.config.as_ref())
+    }
+
+    /// Returns true if the project containing `path` treats it as part of an installed dependency.
+    pub(crate) fn is_library_file(&self, path: &NormalizedPath) -> bool {
+        self.nearest_config(path)
+            .is_some_and(|config| config.workspace().is_library_file(path))
+    }
+
+    /// Returns true if the index filter of the project containing `path` keeps it out of the index.
+    pub(crate) fn is_excluded(&self, path: &NormalizedPath) -> bool {
+        self.nearest_config(path)
+            .is_some_and(|config| config.workspace().is_excluded(path))
+    }
+
+    /// Returns true if `root` is the primary workspace or an already loaded project.
+    pub(crate) fn is_loaded_root(&self, root: &NormalizedPath) -> bool {
+        self.config
+            .as_ref()
+            .is_some_and(|config| config.workspace().root() == root)
+            || self.projects.contains_key(root)
+    }
+
+    pub(crate) fn project_id_for_root(&mut self, root: &NormalizedPath) -> ProjectId {
+        if let Some(project) = self.projects.get(root) {
+            return project.id;
+        }
+        let id = ProjectId::from_raw(self.next_project_id);
+        self.next_project_id += 1;
+        id
+    }
+
     pub(crate) fn discover_foundry_root(
         &mut self,
         path: &NormalizedPath,
@@ -69,7 +157,7 @@ impl ServerState {
         if let Some(cached) = self.foundry_root_cache.get(&start_dir) {
             return cached.clone();
         }
-        let found = lsp_utils::find_foundry_root(Path::new(start_dir.as_str()));
+        let found = lsp_utils::find_workspace_root(Path::new(start_dir.as_str()));
         self.foundry_root_cache.insert(start_dir, found.clone());
         found
     }
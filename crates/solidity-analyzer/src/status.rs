@@ -1,4 +1,5 @@
 use sa_config::ResolvedFoundryConfig;
+use sa_paths::NormalizedPath;
 use sa_project_model::Remapping;
 use sa_toolchain::{Toolchain, is_svm_installed};
 use tracing::error;
@@ -12,7 +13,13 @@ pub fn startup_status(config: &ResolvedFoundryConfig) -> String {
     lines.push(format!("profile: {}", profile.name()));
     lines.push(format!("root: {}", workspace.root()));
     lines.push(format!("src: {}", workspace.src()));
-    lines.push(format!("lib: {}", workspace.lib()));
+    lines.push(format!("lib: {}", join_paths(workspace.libs())));
+    if !workspace.include_paths().is_empty() {
+        lines.push(format!(
+            "include paths: {}",
+            join_paths(workspace.include_paths().iter())
+        ));
+    }
     lines.push(format!("test: {}", workspace.test()));
     lines.push(format!("script: {}", workspace.script()));
     lines.push(format!(
@@ -26,6 +33,13 @@ pub fn startup_status(config: &ResolvedFoundryConfig) -> String {
     lines.join("\n")
 }
 
+fn join_paths<'a>(paths: impl Iterator<Item = &'a NormalizedPath>) -> String {
+    paths
+        .map(NormalizedPath::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn format_remappings(remappings: &[Remapping]) -> String {
     if remappings.is_empty() {
         return "0".to_string();
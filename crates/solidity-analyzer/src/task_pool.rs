@@ -1,15 +1,42 @@
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use tokio::task::JoinHandle;
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinHandle};
 
-#[derive(Clone, Default)]
-pub struct TaskPool;
+/// Which lane a task runs on. Latency tasks start right away; background tasks share a few
+/// workers so project-wide work never takes every core from the requests the user waits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Latency,
+    Background,
+}
+
+#[derive(Clone)]
+pub struct TaskPool {
+    background: Arc<Semaphore>,
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl TaskPool {
     pub fn new() -> Self {
-        Self
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |threads| threads.get() / 2)
+            .max(1);
+        Self::with_background_workers(workers)
+    }
+
+    pub fn with_background_workers(workers: usize) -> Self {
+        Self {
+            background: Arc::new(Semaphore::new(workers.max(1))),
+        }
     }
 
     pub fn spawn<F, T>(&self, f: F) -> Task<T>
@@ -17,26 +44,97 @@ impl TaskPool {
         F: FnOnce() -> T + Send + 'static,
         T: Send + 'static,
     {
-        Task {
-            handle: tokio::task::spawn_blocking(f),
-        }
+        self.spawn_with_priority(Priority::Latency, f)
     }
+
+    pub fn spawn_with_priority<F, T>(&self, priority: Priority, f: F) -> Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let handle = match priority {
+            Priority::Latency => TaskHandle::Direct(tokio::task::spawn_blocking(f)),
+            Priority::Background => {
+                let background = Arc::clone(&self.background);
+                TaskHandle::Queued(tokio::spawn(async move {
+                    let permit = background
+                        .acquire_owned()
+                        .await
+                        .expect("background semaphore is never closed");
+                    tokio::task::spawn_blocking(move || {
+                        let _permit = permit;
+                        f()
+                    })
+                    .await
+                }))
+            }
+        };
+        Task { handle }
+    }
+}
+
+enum TaskHandle<T> {
+    Direct(JoinHandle<T>),
+    /// Waits for a background worker before starting the blocking task.
+    Queued(JoinHandle<Result<T, JoinError>>),
 }
 
 pub struct Task<T> {
-    handle: JoinHandle<T>,
+    handle: TaskHandle<T>,
 }
 
 impl<T> Future for Task<T> {
-    type Output = Result<T, tokio::task::JoinError>;
+    type Output = Result<T, JoinError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut self.handle).poll(cx)
+        match &mut self.handle {
+            TaskHandle::Direct(handle) => Pin::new(handle).poll(cx),
+            TaskHandle::Queued(handle) => Pin::new(handle).poll(cx).map(|result| result?),
+        }
     }
 }
 
 impl<T> Drop for Task<T> {
     fn drop(&mut self) {
-        self.handle.abort();
+        match &self.handle {
+            TaskHandle::Direct(handle) => handle.abort(),
+            TaskHandle::Queued(handle) => handle.abort(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::{Priority, TaskPool};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn latency_tasks_do_not_wait_for_busy_background_workers() {
+        let pool = TaskPool::with_background_workers(1);
+        let (release, blocked) = mpsc::channel::<()>();
+        let busy = pool.spawn_with_priority(Priority::Background, move || {
+            blocked.recv().ok();
+        });
+        let queued = pool.spawn_with_priority(Priority::Background, || 2);
+
+        let fast = timeout(Duration::from_secs(5), pool.spawn(|| 1))
+            .await
+            .expect("latency task should not queue");
+        assert_eq!(fast.expect("join"), 1);
+
+        let mut queued = Box::pin(queued);
+        assert!(
+            timeout(Duration::from_millis(50), &mut queued)
+                .await
+                .is_err()
+        );
+
+        release.send(()).expect("release");
+        busy.await.expect("join");
+        assert_eq!(queued.await.expect("join"), 2);
     }
 }
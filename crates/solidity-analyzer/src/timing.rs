@@ -0,0 +1,193 @@
+//! The per-request timing summary enabled with `--print-time` or `SA_PRINT_TIME=1`.
+//!
+//! When a top-level span at `INFO` or above closes (an LSP request, a semantic rebuild), the
+//! spans it entered are written to stderr as a tree with their durations and fields:
+//!
+//! ```text
+//!    14.20ms lsp_request method=textDocument/completion
+//!      13.95ms completions file=/workspace/src/Vault.sol project_id=ProjectId(0)
+//!        11.02ms lowered_program root=/workspace
+//! ```
+//!
+//! Spans faster than a millisecond are left out below the top level.
+
+use std::fmt::{self, Write as _};
+use std::io::Write as _;
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+const MIN_CHILD_DURATION: Duration = Duration::from_millis(1);
+
+pub(crate) struct TimingLayer<W> {
+    make_writer: W,
+}
+
+impl<W> TimingLayer<W> {
+    pub(crate) fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+struct SpanTiming {
+    start: Instant,
+    fields: String,
+    children: Vec<TimingNode>,
+}
+
+struct TimingNode {
+    name: &'static str,
+    fields: String,
+    duration: Duration,
+    children: Vec<TimingNode>,
+}
+
+impl TimingNode {
+    fn render(&self, depth: usize, out: &mut String) {
+        let _ = writeln!(
+            out,
+            "{:indent$}{:>8.2}ms {}{}",
+            "",
+            self.duration.as_secs_f64() * 1000.0,
+            self.name,
+            self.fields,
+            indent = depth * 2
+        );
+        for child in &self.children {
+            child.render(depth + 1, out);
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldWriter(String);
+
+impl Visit for FieldWriter {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={value}", field.name());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = write!(self.0, " {}={value:?}", field.name());
+    }
+}
+
+impl<S, W> Layer<S> for TimingLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = FieldWriter::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanTiming {
+            start: Instant::now(),
+            fields: fields.0,
+            children: Vec::new(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+            let mut fields = FieldWriter(std::mem::take(&mut timing.fields));
+            values.record(&mut fields);
+            timing.fields = fields.0;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let node = TimingNode {
+            name: span.name(),
+            fields: timing.fields,
+            duration: timing.start.elapsed(),
+            children: timing.children,
+        };
+        match span.parent() {
+            Some(parent) => {
+                if node.duration >= MIN_CHILD_DURATION
+                    && let Some(timing) = parent.extensions_mut().get_mut::<SpanTiming>()
+                {
+                    timing.children.push(node);
+                }
+            }
+            None if *span.metadata().level() <= Level::INFO => {
+                let mut summary = String::new();
+                node.render(0, &mut summary);
+                let _ = self.make_writer.make_writer().write_all(summary.as_bytes());
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tracing::{debug_span, info_span};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::TimingLayer;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().expect("output lock").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn prints_request_spans_with_their_slow_children() {
+        let output = Output::default();
+        let layer = || {
+            let output = output.clone();
+            TimingLayer::new(move || output.clone())
+        };
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer()), || {
+            let _request = info_span!("lsp_request", method = "textDocument/hover").entered();
+            {
+                let _hover = debug_span!("hover", file = %"/workspace/src/A.sol").entered();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            let _fast = debug_span!("fast").entered();
+        });
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer()), || {
+            let _background = debug_span!("lowered_program").entered();
+        });
+
+        let printed = String::from_utf8(output.0.lock().expect("output lock").clone())
+            .expect("utf-8 summary");
+        let lines = printed.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2, "{printed}");
+        assert!(lines[0].ends_with("ms lsp_request method=textDocument/hover"));
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        assert_eq!(indent(lines[1]), indent(lines[0]) + 2);
+        assert!(lines[1].ends_with("ms hover file=/workspace/src/A.sol"));
+    }
+}
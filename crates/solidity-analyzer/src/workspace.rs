@@ -1,13 +1,17 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::config::IndexingMode;
 use crate::indexer;
-use crate::state::ServerState;
+use crate::state::{LoadedProject, ServerState};
 use sa_config::ResolvedFoundryConfig;
-use sa_ide::AnalysisChange;
-use sa_paths::NormalizedPath;
+use sa_ide::{AnalysisChange, HIR_CACHE_FILE, HirCache};
+use sa_paths::{NormalizedPath, RealPathCache};
+use sa_project_model::{ContractArtifact, IndexFilter, abi_interface, parse_abi};
 use sa_vfs::VfsChange;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 pub fn load(
     state: &mut ServerState,
@@ -15,36 +19,459 @@ pub fn load(
     profile: Option<&str>,
 ) -> anyhow::Result<()> {
     let root_path = PathBuf::from(root.as_str());
-    info!(root = %root, profile = ?profile, "loading foundry workspace");
-    let resolved = sa_load_foundry::load_foundry(&root_path, profile)?;
+    let selected = selected_profile(state);
+    let profile = profile.or(selected.as_deref());
+    info!(root = %root, profile = ?profile, "loading workspace");
+    let discovery = state.progress.begin("Loading Foundry project", None);
+    let loaded = sa_load_foundry::load_project(&root_path, profile);
+    drop(discovery);
+    state.load_error = loaded.as_ref().err().map(|error| format!("{error:#}"));
+    let resolved = loaded?.with_index_filter(index_filter(state));
     log_resolved_config(&resolved);
     apply_config(state, resolved)?;
+    refresh_abi_interfaces(state);
     Ok(())
 }
 
-pub fn reload(state: &mut ServerState) -> anyhow::Result<()> {
+/// What changed across all loaded projects during a [`reload`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadSummary {
+    pub projects: usize,
+    pub remappings_added: usize,
+    pub remappings_removed: usize,
+    pub files_added: usize,
+    pub files_removed: usize,
+}
+
+impl fmt::Display for ReloadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Reloaded {} project(s): remappings +{}/-{}, indexed files +{}/-{}",
+            self.projects,
+            self.remappings_added,
+            self.remappings_removed,
+            self.files_added,
+            self.files_removed
+        )
+    }
+}
+
+pub fn reload(state: &mut ServerState) -> anyhow::Result<ReloadSummary> {
     let Some(root) = state.root_path.clone() else {
         debug!("reload requested without a workspace root");
-        return Ok(());
+        return Ok(ReloadSummary::default());
     };
-    let profile = state
+    let (remappings_before, files_before) = loaded_inputs(state);
+    // A `forge install` may have replaced the symlinks dependencies are reached through.
+    RealPathCache::shared().clear();
+    load(state, &root, None)?;
+
+    let roots = state.projects.keys().cloned().collect::<Vec<_>>();
+    for root in roots {
+        load_additional(state, &root)?;
+    }
+
+    let (remappings_after, files_after) = loaded_inputs(state);
+    let summary = ReloadSummary {
+        projects: usize::from(state.config.is_some()) + state.projects.len(),
+        remappings_added: remappings_after.difference(&remappings_before).count(),
+        remappings_removed: remappings_before.difference(&remappings_after).count(),
+        files_added: files_after.difference(&files_before).count(),
+        files_removed: files_before.difference(&files_after).count(),
+    };
+    info!(%summary, "workspace reloaded");
+    Ok(summary)
+}
+
+/// Like [`reload`], but also drops every cached analysis result and loads the projects of open
+/// documents that are not loaded yet, for when something changed that the file watcher cannot
+/// see (e.g. a `forge install` outside the workspace or a new `solc`).
+pub fn reload_workspace(state: &mut ServerState) -> anyhow::Result<ReloadSummary> {
+    state.analysis_host.clear_caches();
+    let mut summary = reload(state)?;
+    let open = state.open_documents.keys().cloned().collect::<Vec<_>>();
+    for path in open {
+        if let Some(root) = state.discover_foundry_root(&path)
+            && !state.is_loaded_root(&root)
+        {
+            load_additional(state, &root)?;
+            summary.projects += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// Remappings (keyed by project root) and indexed files of every loaded project.
+fn loaded_inputs(state: &ServerState) -> (HashSet<String>, HashSet<NormalizedPath>) {
+    let configs = state
+        .config
+        .iter()
+        .chain(state.projects.values().map(|project| &project.config));
+    let remappings = configs
+        .flat_map(|config| {
+            let root = config.workspace().root();
+            config
+                .active_profile()
+                .remappings()
+                .iter()
+                .map(move |remapping| {
+                    format!(
+                        "{root}|{}:{}={}",
+                        remapping.context().unwrap_or_default(),
+                        remapping.from(),
+                        remapping.to()
+                    )
+                })
+        })
+        .collect();
+    let files = state
+        .indexed_files
+        .iter()
+        .chain(
+            state
+                .projects
+                .values()
+                .flat_map(|project| &project.indexed_files),
+        )
+        .cloned()
+        .collect();
+    (remappings, files)
+}
+
+/// Switches every loaded project to `profile` (or back to `FOUNDRY_PROFILE`/`default` when
+/// `None`) and reloads them.
+pub fn select_profile(state: &mut ServerState, profile: Option<String>) -> anyhow::Result<()> {
+    state.lsp_config.foundry.profile = profile;
+    reload(state).map(|_| ())
+}
+
+/// Loads `root` as a project of its own next to the primary workspace, so files below it are
+/// analyzed with its own remappings and layout.
+pub fn load_additional(state: &mut ServerState, root: &NormalizedPath) -> anyhow::Result<()> {
+    let root_path = PathBuf::from(root.as_str());
+    let profile = selected_profile(state);
+    info!(root = %root, profile = ?profile, "loading nested workspace");
+    let loaded = sa_load_foundry::load_project(&root_path, profile.as_deref());
+    if let Err(error) = &loaded {
+        state.load_error = Some(format!("{error:#}"));
+    }
+    let resolved = loaded?.with_index_filter(index_filter(state));
+    log_resolved_config(&resolved);
+
+    let project_id = state.project_id_for_root(root);
+    let previous = state
+        .projects
+        .get(root)
+        .map(|project| project.indexed_files.clone())
+        .unwrap_or_default();
+    let (changes, indexed_files) = index_changes(state, &resolved, &previous, |state, path| {
+        state.indexed_files.contains(path)
+    })?;
+
+    state.vfs.apply_changes(changes);
+    state.loaded_imports.clear();
+    let snapshot = state.vfs.snapshot();
+    let mut change = AnalysisChange::new();
+    change.set_vfs(snapshot.clone());
+    change.set_project_config(project_id, resolved.clone());
+    change.set_project_artifacts(project_id, load_artifacts(&resolved));
+    state.analysis_host.apply_change(change);
+    state.vfs_snapshot = Some(snapshot);
+    state.projects.insert(
+        root.clone(),
+        LoadedProject {
+            id: project_id,
+            config: resolved,
+            indexed_files,
+        },
+    );
+    refresh_abi_interfaces(state);
+    Ok(())
+}
+
+/// Unloads the project at `root`, for a workspace folder the client removed. Its files leave the
+/// VFS unless they are open or another project indexes them. Without the primary workspace,
+/// another loaded project takes its place. Returns false if no project is loaded at `root`.
+pub fn unload(state: &mut ServerState, root: &NormalizedPath) -> anyhow::Result<bool> {
+    info!(root = %root, "unloading workspace folder");
+    if let Some(project) = state.projects.remove(root) {
+        let changes = unused_files(state, &project.indexed_files);
+        let mut change = AnalysisChange::new();
+        change.remove_project(project.id);
+        apply_removals(state, changes, change);
+        return Ok(true);
+    }
+    let is_primary = state
         .config
         .as_ref()
-        .map(|config| config.active_profile().name().to_string());
-    load(state, &root, profile.as_deref())
+        .is_some_and(|config| config.workspace().root() == root);
+    if !is_primary {
+        return Ok(false);
+    }
+    let next = state.projects.keys().min().cloned();
+    let Some(next) = next else {
+        let indexed_files = std::mem::take(&mut state.indexed_files);
+        let changes = unused_files(state, &indexed_files);
+        state.config = None;
+        state.root_path = None;
+        state.load_error = None;
+        apply_removals(state, changes, AnalysisChange::new());
+        return Ok(true);
+    };
+    // Loading `next` as the primary workspace drops the files only the old one indexed.
+    if let Some(project) = state.projects.remove(&next) {
+        let mut change = AnalysisChange::new();
+        change.remove_project(project.id);
+        state.analysis_host.apply_change(change);
+        state.indexed_files.extend(project.indexed_files);
+    }
+    state.root_path = Some(next.clone());
+    load(state, &next, None)?;
+    Ok(true)
+}
+
+/// Removes the files of `files` that are neither open nor indexed by a loaded project.
+fn unused_files(state: &ServerState, files: &HashSet<NormalizedPath>) -> Vec<VfsChange> {
+    files
+        .iter()
+        .filter(|path| {
+            !state.open_documents.contains_key(*path)
+                && !state.indexed_files.contains(*path)
+                && !state
+                    .projects
+                    .values()
+                    .any(|project| project.indexed_files.contains(*path))
+        })
+        .map(|path| VfsChange::Remove { path: path.clone() })
+        .collect()
+}
+
+fn apply_removals(state: &mut ServerState, changes: Vec<VfsChange>, mut change: AnalysisChange) {
+    state.vfs.apply_changes(changes);
+    state.loaded_imports.clear();
+    let snapshot = state.vfs.snapshot();
+    change.set_vfs(snapshot.clone());
+    state.analysis_host.apply_change(change);
+    state.vfs_snapshot = Some(snapshot);
+    refresh_abi_interfaces(state);
+}
+
+fn selected_profile(state: &ServerState) -> Option<String> {
+    state.lsp_config.foundry.profile().map(str::to_string)
+}
+
+fn index_filter(state: &ServerState) -> IndexFilter {
+    let indexing = &state.lsp_config.indexing;
+    IndexFilter::new(indexing.include.clone(), indexing.exclude.clone()).unwrap_or_else(|error| {
+        warn!(?error, "ignoring invalid indexing globs");
+        IndexFilter::default()
+    })
 }
 
 fn apply_config(state: &mut ServerState, resolved: ResolvedFoundryConfig) -> anyhow::Result<()> {
-    let workspace = resolved.workspace().clone();
+    if state.config.is_none() {
+        let cache = HirCache::load(&hir_cache_path(&resolved));
+        if !cache.is_empty() {
+            state.analysis_host.set_hir_cache(cache);
+        }
+    }
+    let previous = state.indexed_files.clone();
+    let (changes, new_indexed_paths) =
+        index_changes(state, &resolved, &previous, |state, path| {
+            state
+                .projects
+                .values()
+                .any(|project| project.indexed_files.contains(path))
+        })?;
+
+    state.vfs.apply_changes(changes);
+    // Remappings may have changed, so imports resolve differently.
+    state.loaded_imports.clear();
+    let snapshot = state.vfs.snapshot();
+    let mut change = AnalysisChange::new();
+    change.set_vfs(snapshot.clone());
+    change.set_config(resolved.clone());
+    change.set_artifacts(load_artifacts(&resolved));
+    state.analysis_host.apply_change(change);
+    state.vfs_snapshot = Some(snapshot);
+    state.indexed_files = new_indexed_paths;
+    state.config = Some(resolved);
+    Ok(())
+}
+
+/// Re-reads the `forge build` artifacts of every loaded project, leaving sources and settings
+/// alone. Returns how many artifacts were loaded.
+pub fn reload_artifacts(state: &mut ServerState) -> usize {
+    let mut change = AnalysisChange::new();
+    let mut loaded = 0;
+    if let Some(config) = &state.config {
+        let artifacts = load_artifacts(config);
+        loaded += artifacts.len();
+        change.set_artifacts(artifacts);
+    }
+    for project in state.projects.values() {
+        let artifacts = load_artifacts(&project.config);
+        loaded += artifacts.len();
+        change.set_project_artifacts(project.id, artifacts);
+    }
+    state.analysis_host.apply_change(change);
+    refresh_abi_interfaces(state);
+    loaded
+}
+
+fn load_artifacts(resolved: &ResolvedFoundryConfig) -> Vec<ContractArtifact> {
+    let out = Path::new(resolved.workspace().root().as_str()).join(&resolved.foundry_config().out);
+    sa_project_model::load_artifacts(&out)
+}
+
+/// Loads a generated interface for every ABI-only contract (see [`crate::config::AbiConfig`])
+/// in place of its missing source, and drops the interfaces no longer needed. Sources that
+/// exist on disk always win.
+fn refresh_abi_interfaces(state: &mut ServerState) {
+    let settings = state.lsp_config.abi.clone();
+    let mut interfaces = BTreeMap::<PathBuf, String>::new();
+    if settings.artifacts {
+        let configs = state
+            .config
+            .iter()
+            .chain(state.projects.values().map(|project| &project.config));
+        for config in configs {
+            let root = Path::new(config.workspace().root().as_str());
+            for artifact in load_artifacts(config) {
+                let Some(source) = artifact.source() else {
+                    continue;
+                };
+                // One source file can declare several contracts.
+                interfaces
+                    .entry(root.join(source))
+                    .or_default()
+                    .push_str(&abi_interface(artifact.name(), artifact.abi()));
+            }
+        }
+    }
+    if let Some(root) = &state.root_path {
+        for file in &settings.files {
+            let path = Path::new(root.as_str()).join(file);
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+                .filter(|name| !name.is_empty())
+            else {
+                continue;
+            };
+            let abi = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| parse_abi(&text));
+            let Some(abi) = abi else {
+                warn!(path = %path.display(), "ignoring unreadable ABI file");
+                continue;
+            };
+            interfaces.insert(
+                path.with_file_name(format!("{name}.sol")),
+                abi_interface(name, &abi),
+            );
+        }
+    }
+
+    let mut changes = Vec::new();
+    let mut loaded = HashSet::new();
+    for (path, text) in interfaces {
+        if path.is_file() {
+            continue;
+        }
+        let path = NormalizedPath::new(path.to_string_lossy());
+        loaded.insert(path.clone());
+        let unchanged = state.vfs_snapshot.as_ref().is_some_and(|snapshot| {
+            snapshot
+                .file_id(&path)
+                .and_then(|file_id| snapshot.file_text(file_id))
+                .is_some_and(|known| known == text)
+        });
+        if !unchanged {
+            changes.push(VfsChange::Set {
+                path,
+                text: Arc::from(text),
+            });
+        }
+    }
+    // A source that appeared on disk since is the watcher's to load; it is not removed here.
+    for path in state.abi_interfaces.difference(&loaded) {
+        if !Path::new(path.as_str()).is_file() {
+            changes.push(VfsChange::Remove { path: path.clone() });
+        }
+    }
+    state.abi_interfaces = loaded;
+    if changes.is_empty() {
+        return;
+    }
+    debug!(
+        interfaces = state.abi_interfaces.len(),
+        "loading ABI-only contracts"
+    );
+    state.vfs.apply_changes(changes);
+    let snapshot = state.vfs.snapshot();
+    let mut change = AnalysisChange::new();
+    change.set_vfs(snapshot.clone());
+    state.analysis_host.apply_change(change);
+    state.vfs_snapshot = Some(snapshot);
+}
+
+/// Where derived per-file data is saved between runs, next to Foundry's own caches.
+pub fn hir_cache_path(resolved: &ResolvedFoundryConfig) -> PathBuf {
+    Path::new(resolved.workspace().root().as_str())
+        .join(&resolved.foundry_config().cache_path)
+        .join(HIR_CACHE_FILE)
+}
+
+/// Indexes `resolved` and returns the VFS changes together with the new set of indexed paths.
+///
+/// Library files are indexed but only loaded if they are already in the VFS; the rest wait until
+/// an open file imports them (see [`crate::document::load_imports`]). In lazy mode only the files
+/// reachable from the open files and the entry points are indexed, and all of them are loaded.
+/// Files from `previous` that are gone are removed unless they are open or `still_used` claims
+/// them for another project.
+fn index_changes(
+    state: &ServerState,
+    resolved: &ResolvedFoundryConfig,
+    previous: &HashSet<NormalizedPath>,
+    still_used: impl Fn(&ServerState, &NormalizedPath) -> bool,
+) -> anyhow::Result<(Vec<VfsChange>, HashSet<NormalizedPath>)> {
     let remappings = resolved.active_profile().remappings();
-    let index_result = indexer::index_workspace(&workspace, remappings)?;
+    let root = Path::new(resolved.workspace().root().as_str());
+    let files_cache = root
+        .join(&resolved.foundry_config().cache_path)
+        .join(indexer::SOLIDITY_FILES_CACHE);
+    let lazy = state.lsp_config.indexing.mode == IndexingMode::Lazy;
+    let progress = state.progress.begin("Indexing", None);
+    let report = |done, total| progress.report("Indexing", done, total, "files");
+    let index_result = if lazy {
+        indexer::index_reachable(
+            resolved.workspace(),
+            remappings,
+            lazy_index_seeds(state, resolved),
+            &report,
+        )?
+    } else {
+        indexer::index_workspace_with_files_cache(
+            resolved.workspace(),
+            remappings,
+            &files_cache,
+            &report,
+        )?
+    };
+    drop(progress);
 
     let mut changes = Vec::new();
-    let mut new_indexed_paths = std::collections::HashSet::new();
+    let mut indexed_paths = HashSet::new();
 
     for indexed_file in index_result.files {
-        new_indexed_paths.insert(indexed_file.path.clone());
-        if state.open_documents.contains_key(&indexed_file.path) {
+        indexed_paths.insert(indexed_file.path.clone());
+        if !lazy
+            && resolved.workspace().is_library_file(&indexed_file.path)
+            && state.vfs.file_id(&indexed_file.path).is_none()
+        {
             continue;
         }
         changes.push(VfsChange::Set {
@@ -54,24 +481,37 @@ fn apply_config(state: &mut ServerState, resolved: ResolvedFoundryConfig) -> any
     }
 
     // Remove stale files that were previously indexed but are no longer present.
-    for old_path in &state.indexed_files {
-        if !new_indexed_paths.contains(old_path) && !state.open_documents.contains_key(old_path) {
+    for old_path in previous {
+        if !indexed_paths.contains(old_path)
+            && !state.open_documents.contains_key(old_path)
+            && !still_used(state, old_path)
+        {
             changes.push(VfsChange::Remove {
                 path: old_path.clone(),
             });
         }
     }
 
-    state.vfs.apply_changes(changes);
-    let snapshot = state.vfs.snapshot();
-    let mut change = AnalysisChange::new();
-    change.set_vfs(snapshot.clone());
-    change.set_config(resolved.clone());
-    state.analysis_host.apply_change(change);
-    state.vfs_snapshot = Some(snapshot);
-    state.indexed_files = new_indexed_paths;
-    state.config = Some(resolved);
-    Ok(())
+    Ok((changes, indexed_paths))
+}
+
+/// The open files of the project at `resolved` and the files matching the entry point globs.
+fn lazy_index_seeds(state: &ServerState, resolved: &ResolvedFoundryConfig) -> Vec<PathBuf> {
+    let root = resolved.workspace().root();
+    let entry_points = IndexFilter::new(state.lsp_config.indexing.entry_points.clone(), Vec::new())
+        .unwrap_or_else(|error| {
+            warn!(?error, "ignoring invalid indexing entry points");
+            IndexFilter::default()
+        });
+    let mut seeds = state
+        .open_documents
+        .keys()
+        .map(|path| PathBuf::from(path.as_str()))
+        .filter(|path| path.starts_with(root.as_str()))
+        .collect::<Vec<_>>();
+    seeds.sort();
+    seeds.extend(entry_points.included_files(root));
+    seeds
 }
 
 fn log_resolved_config(resolved: &ResolvedFoundryConfig) {
@@ -81,8 +521,11 @@ fn log_resolved_config(resolved: &ResolvedFoundryConfig) {
         root = %workspace.root(),
         src = %workspace.src(),
         lib = %workspace.lib(),
+        extra_libs = workspace.libs().count() - 1,
+        include_paths = workspace.include_paths().len(),
         test = %workspace.test(),
         script = %workspace.script(),
+        index_filter = ?workspace.index_filter(),
         profile = %profile.name(),
         solc_version = ?profile.solc_version(),
         remappings = profile.remappings().len(),
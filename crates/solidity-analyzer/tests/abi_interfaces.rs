@@ -0,0 +1,77 @@
+use sa_span::lsp::to_lsp_position;
+use sa_test_support::extract_offset;
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    DidOpenTextDocumentParams, InitializeParams, SignatureHelp, SignatureHelpParams,
+    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url,
+};
+
+#[tokio::test]
+async fn abi_files_provide_signature_help() {
+    let (text, offset) = extract_offset(
+        r#"import {Vendor} from "../abi/Vendor.sol";
+
+contract User {
+    function run(address target) external {
+        Vendor(target).ping(/*caret*/);
+    }
+}
+"#,
+    );
+    let abi = json!([{
+        "type": "function",
+        "name": "ping",
+        "stateMutability": "nonpayable",
+        "inputs": [{"name": "amount", "type": "uint256", "internalType": "uint256"}],
+        "outputs": []
+    }]);
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/User.sol", text.as_str())
+        .file("abi/Vendor.json", abi.to_string())
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        initialization_options: Some(json!({ "abi": { "files": ["abi/Vendor.json"] } })),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params(params, solidity_analyzer::Server::new).await;
+    let uri = Url::from_file_path(fixture.root().join("src/User.sol")).expect("file uri");
+    harness
+        .notify(
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "solidity".to_string(),
+                    version: 1,
+                    text: text.clone(),
+                },
+            },
+        )
+        .await;
+
+    let help: Option<SignatureHelp> = harness
+        .request(
+            "textDocument/signatureHelp",
+            SignatureHelpParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: to_lsp_position(offset, &text),
+                },
+                work_done_progress_params: Default::default(),
+                context: None,
+            },
+        )
+        .await;
+    let help = help.expect("signature help");
+    let signature = help.signatures.first().expect("signature");
+    assert!(
+        signature.label.contains("function ping(uint256 amount)"),
+        "{}",
+        signature.label
+    );
+}
@@ -0,0 +1,49 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli::{self, AnalyzeFormat};
+
+#[test]
+fn analyze_reports_findings_relative_to_the_project_root() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Ok.sol", "contract Ok {}\n")
+        .file("src/Broken.sol", "contract Broken {\n    function\n}\n")
+        .build()
+        .expect("fixture");
+
+    let report = cli::analyze(fixture.root()).expect("analyze");
+    assert!(report.has_errors());
+    assert!(
+        report
+            .findings
+            .iter()
+            .all(|finding| finding.path == "src/Broken.sol"),
+        "{:?}",
+        report.findings
+    );
+
+    let text = report.render(AnalyzeFormat::Text);
+    assert!(text.starts_with("src/Broken.sol:"), "{text}");
+
+    let sarif: serde_json::Value =
+        serde_json::from_str(&report.render(AnalyzeFormat::Sarif)).expect("SARIF JSON");
+    assert_eq!(sarif["version"], "2.1.0");
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["level"], "error");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "src/Broken.sol"
+    );
+}
+
+#[test]
+fn analyze_limits_findings_to_the_given_file() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Ok.sol", "contract Ok {}\n")
+        .file("src/Broken.sol", "contract Broken {\n    function\n}\n")
+        .build()
+        .expect("fixture");
+
+    let report = cli::analyze(&fixture.root().join("src/Ok.sol")).expect("analyze");
+    assert!(!report.has_errors(), "{:?}", report.findings);
+}
@@ -0,0 +1,30 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli;
+
+#[test]
+fn bench_measures_a_project() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml("[profile.default]\n")
+        .file(
+            "src/Counter.sol",
+            "contract Counter {\n    uint256 public count;\n\n    function increment() external {\n        count = this.count() + 1;\n    }\n}\n",
+        )
+        .build()
+        .expect("fixture");
+
+    let report = cli::bench(fixture.root()).expect("bench");
+    assert_eq!(report.projects, 1);
+    assert_eq!(report.files, 1);
+    assert_eq!(report.completion.samples, 1);
+    assert!(report.edit_rebuild_ms.is_some());
+    assert!(report.memory_bytes > 0);
+
+    let text = report.to_string();
+    assert!(text.starts_with("projects          1\n"), "{text}");
+    assert!(
+        text.lines()
+            .last()
+            .is_some_and(|line| line.starts_with("memory"))
+    );
+}
@@ -0,0 +1,31 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli::{self, SymbolsFormat};
+
+#[test]
+fn dump_symbols_exports_one_object_per_line() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/Token.sol",
+            "contract Token {\n    function transfer(address to, uint256 amount) external {}\n}\n",
+        )
+        .build()
+        .expect("fixture");
+
+    let symbols = cli::dump_symbols(fixture.root()).expect("symbols");
+    let ndjson = cli::render_symbols(&symbols, SymbolsFormat::Ndjson);
+    let lines = ndjson
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("JSON line"))
+        .collect::<Vec<_>>();
+
+    let transfer = lines
+        .iter()
+        .find(|symbol| symbol["name"] == "transfer")
+        .expect("transfer symbol");
+    assert_eq!(transfer["kind"], "function");
+    assert_eq!(transfer["container"], "Token");
+    assert_eq!(transfer["file"], "src/Token.sol");
+    assert_eq!(transfer["range"]["start"]["line"], 1);
+    assert_eq!(transfer["selector"], "0xa9059cbb");
+}
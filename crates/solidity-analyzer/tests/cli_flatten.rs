@@ -0,0 +1,31 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli;
+
+#[test]
+fn flatten_resolves_imports_through_remappings() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml("[profile.default]\nremappings = [\"@shared/=src/shared/\"]\n")
+        .file(
+            "src/shared/Math.sol",
+            "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.20;\n\nlibrary Math {}\n",
+        )
+        .file(
+            "src/Token.sol",
+            "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.20;\n\nimport \"@shared/Math.sol\";\n\ncontract Token {}\n",
+        )
+        .build()
+        .expect("fixture");
+
+    let flattened = cli::flatten(&fixture.root().join("src/Token.sol")).expect("flatten");
+    assert_eq!(
+        flattened.matches("pragma solidity").count(),
+        1,
+        "{flattened}"
+    );
+    assert_eq!(flattened.matches("SPDX-License-Identifier").count(), 1);
+    let math = flattened.find("library Math").expect("inlined import");
+    let token = flattened.find("contract Token").expect("flattened file");
+    assert!(math < token, "{flattened}");
+    assert!(!flattened.contains("import"), "{flattened}");
+}
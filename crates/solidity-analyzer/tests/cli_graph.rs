@@ -0,0 +1,65 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli::{self, GraphFormat, GraphKind};
+
+fn fixture() -> sa_test_utils::Fixture {
+    FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml("[profile.default]\n")
+        .file("src/IToken.sol", "interface IToken {}\n")
+        .file(
+            "src/Base.sol",
+            "import \"./IToken.sol\";\nabstract contract Base is IToken {}\n",
+        )
+        .file(
+            "src/Token.sol",
+            "import \"./Base.sol\";\ncontract Token is Base {}\n",
+        )
+        .build()
+        .expect("fixture")
+}
+
+#[test]
+fn graph_exports_imports_as_dot() {
+    let fixture = fixture();
+    let dot = cli::graph(GraphKind::Imports, fixture.root(), GraphFormat::Dot).expect("graph");
+
+    assert!(dot.starts_with("digraph imports {"), "{dot}");
+    assert!(
+        dot.contains("\"src/Token.sol\" -> \"src/Base.sol\";"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("\"src/Base.sol\" -> \"src/IToken.sol\";"),
+        "{dot}"
+    );
+    assert!(
+        !dot.contains("\"src/Token.sol\" -> \"src/IToken.sol\""),
+        "{dot}"
+    );
+}
+
+#[test]
+fn graph_exports_inheritance_as_json() {
+    let fixture = fixture();
+    let json =
+        cli::graph(GraphKind::Inheritance, fixture.root(), GraphFormat::Json).expect("graph");
+    let graph: serde_json::Value = serde_json::from_str(&json).expect("json");
+
+    let base = graph["nodes"]
+        .as_array()
+        .expect("nodes")
+        .iter()
+        .find(|node| node["name"] == "Base")
+        .expect("Base node");
+    assert_eq!(base["kind"], "abstract contract");
+    assert_eq!(base["file"], "src/Base.sol");
+    let edges = graph["edges"].as_array().expect("edges");
+    assert!(edges.contains(&serde_json::json!({
+        "from": "src/Token.sol:Token",
+        "to": "src/Base.sol:Base",
+    })));
+    assert!(edges.contains(&serde_json::json!({
+        "from": "src/Base.sol:Base",
+        "to": "src/IToken.sol:IToken",
+    })));
+}
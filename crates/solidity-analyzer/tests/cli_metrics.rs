@@ -0,0 +1,39 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli::{self, MetricsFormat};
+
+#[test]
+fn metrics_report_project_contracts() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml("[profile.default]\n")
+        .file("lib/dep/src/Dep.sol", "contract Dep {}\n")
+        .file(
+            "src/Counter.sol",
+            "contract Counter {\n    uint256 public count;\n\n    function increment(uint256 by) external {\n        if (by == 0) {\n            revert();\n        }\n        count += by;\n    }\n}\n",
+        )
+        .build()
+        .expect("fixture");
+
+    let reports = cli::metrics(fixture.root()).expect("metrics");
+    assert_eq!(reports.len(), 1, "{reports:?}");
+    let counter = &reports[0];
+    assert_eq!(counter.file, "src/Counter.sol");
+    assert_eq!(counter.line, 1);
+    assert_eq!(counter.lines, 9);
+    assert_eq!(counter.functions[0].name, "increment");
+    assert_eq!(counter.functions[0].line, 4);
+    assert_eq!(counter.functions[0].cyclomatic_complexity, 2);
+
+    let table = cli::render_metrics(&reports, MetricsFormat::Table);
+    assert!(table.starts_with("contract"), "{table}");
+    assert!(
+        table.contains("src/Counter.sol:Counter  contract"),
+        "{table}"
+    );
+    assert!(table.contains("Counter.increment"), "{table}");
+
+    let json = cli::render_metrics(&reports, MetricsFormat::Json);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("json");
+    assert_eq!(value[0]["functions"][0]["visibility"], "external");
+    assert_eq!(value[0]["inheritanceDepth"], 0);
+}
@@ -0,0 +1,34 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli::{self, QueryKind};
+
+#[test]
+fn query_resolves_definitions_and_references_by_line_and_column() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Base.sol", "contract Base {\n    uint256 internal total;\n}\n")
+        .file(
+            "src/Token.sol",
+            "import \"./Base.sol\";\n\ncontract Token is Base {\n    function supply() public view returns (uint256) {\n        return total;\n    }\n}\n",
+        )
+        .build()
+        .expect("fixture");
+    let target = format!("{}:5:16", fixture.root().join("src/Token.sol").display());
+
+    let definition = cli::query(QueryKind::Definition, &target).expect("definition");
+    let definition = definition
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        definition,
+        vec!["src/Base.sol:2:22: uint256 internal total;".to_string()]
+    );
+
+    let references = cli::query(QueryKind::References, &target).expect("references");
+    assert!(
+        references
+            .iter()
+            .any(|location| location.path == "src/Token.sol" && location.line == 5),
+        "{references:?}"
+    );
+}
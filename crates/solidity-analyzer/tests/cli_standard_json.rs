@@ -0,0 +1,40 @@
+use sa_test_utils::FixtureBuilder;
+use solidity_analyzer::cli;
+
+#[test]
+fn standard_json_collects_sources_remappings_and_optimizer_settings() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml(
+            "[profile.default]\noptimizer = true\noptimizer_runs = 1000\nremappings = [\"@dep/=lib/dep/src/\"]\n",
+        )
+        .file("lib/dep/src/Math.sol", "library Math {}\n")
+        .file("lib/dep/src/Unused.sol", "library Unused {}\n")
+        .file(
+            "src/Token.sol",
+            "import \"@dep/Math.sol\";\n\ncontract Token {}\n",
+        )
+        .build()
+        .expect("fixture");
+
+    let input = cli::standard_json(fixture.root()).expect("standard json");
+    assert_eq!(input["language"], "Solidity");
+    let sources = input["sources"].as_object().expect("sources");
+    assert!(sources.contains_key("src/Token.sol"), "{input}");
+    assert_eq!(
+        sources["lib/dep/src/Math.sol"]["content"],
+        "library Math {}\n"
+    );
+    assert!(!sources.contains_key("lib/dep/src/Unused.sol"), "{input}");
+    let settings = &input["settings"];
+    assert_eq!(settings["optimizer"]["enabled"], true);
+    assert_eq!(settings["optimizer"]["runs"], 1000);
+    assert!(
+        settings["remappings"]
+            .as_array()
+            .expect("remappings")
+            .iter()
+            .any(|remapping| remapping == "@dep/=lib/dep/src/"),
+        "{input}"
+    );
+}
@@ -3,8 +3,10 @@ use sa_test_support::find_range;
 use sa_test_utils::FixtureBuilder;
 use sa_test_utils::lsp::LspTestHarness;
 use tower_lsp::lsp_types::{
-    CodeActionContext, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic,
-    DidOpenTextDocumentParams, NumberOrString, TextDocumentIdentifier, TextDocumentItem, Url,
+    ClientCapabilities, CodeAction, CodeActionClientCapabilities, CodeActionContext,
+    CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResolveClientCapabilities,
+    Diagnostic, DidOpenTextDocumentParams, InitializeParams, NumberOrString,
+    TextDocumentClientCapabilities, TextDocumentIdentifier, TextDocumentItem, Url,
 };
 
 #[tokio::test]
@@ -84,3 +86,95 @@ contract Main {
     assert_eq!(edits.len(), 1);
     assert_eq!(edits[0].new_text, "fooBar");
 }
+
+#[tokio::test]
+async fn code_action_resolves_generate_getter_lazily() {
+    let text = r#"
+contract Main {
+    uint256 internal _total;
+}
+"#
+    .trim();
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", text)
+        .build()
+        .expect("fixture");
+
+    let capabilities = ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            code_action: Some(CodeActionClientCapabilities {
+                data_support: Some(true),
+                resolve_support: Some(CodeActionResolveClientCapabilities {
+                    properties: vec!["edit".to_string()],
+                }),
+                ..CodeActionClientCapabilities::default()
+            }),
+            ..TextDocumentClientCapabilities::default()
+        }),
+        ..ClientCapabilities::default()
+    };
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        capabilities,
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params(params, solidity_analyzer::Server::new).await;
+
+    let main_uri = Url::from_file_path(fixture.root().join("src/Main.sol")).expect("main uri");
+    harness
+        .notify(
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: main_uri.clone(),
+                    language_id: "solidity".to_string(),
+                    version: 1,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+
+    let params = CodeActionParams {
+        text_document: TextDocumentIdentifier {
+            uri: main_uri.clone(),
+        },
+        range: to_lsp_range(find_range(text, "_total"), text),
+        context: CodeActionContext {
+            diagnostics: Vec::new(),
+            only: None,
+            trigger_kind: None,
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let actions = harness
+        .request::<_, Option<Vec<CodeActionOrCommand>>>("textDocument/codeAction", params)
+        .await
+        .unwrap_or_default();
+    let action = actions
+        .into_iter()
+        .find_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => Some(action),
+            _ => None,
+        })
+        .expect("code action");
+    assert_eq!(action.kind, Some(CodeActionKind::REFACTOR));
+    assert!(action.edit.is_none());
+    assert!(action.data.is_some());
+
+    let resolved = harness
+        .request::<_, CodeAction>("codeAction/resolve", action)
+        .await;
+    let edit = resolved.edit.expect("workspace edit");
+    let changes = edit.changes.expect("changes");
+    let edits = changes.get(&main_uri).expect("main edits");
+    assert_eq!(edits.len(), 1);
+    assert!(
+        edits[0]
+            .new_text
+            .contains("function total() external view returns (uint256)")
+    );
+}
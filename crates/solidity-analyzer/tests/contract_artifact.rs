@@ -0,0 +1,85 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::{ContractArtifact, ContractArtifactParams};
+use tower_lsp::lsp_types::{InitializeParams, TextDocumentIdentifier, Url};
+
+#[tokio::test]
+async fn contract_artifact_reads_forge_build_output() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Counter.sol", "contract Counter {}")
+        .file(
+            "out/Counter.sol/Counter.json",
+            r#"{
+    "abi": [],
+    "deployedBytecode": {"object": "0x6080"},
+    "methodIdentifiers": {},
+    "metadata": {"settings": {"compilationTarget": {"src/Counter.sol": "Counter"}}}
+}"#,
+        )
+        .file(
+            "out/Vendor.sol/Vendor.json",
+            r#"{
+    "abi": [{"type": "function", "name": "ping", "inputs": [], "outputs": []}],
+    "deployedBytecode": {"object": "0x"},
+    "methodIdentifiers": {"ping()": "5c36b186"},
+    "metadata": {"compiler": {"version": "0.8.24"}, "settings": {"compilationTarget": {"lib/vendor/Vendor.sol": "Vendor"}}}
+}"#,
+        )
+        .file("out/build-info/0a1b.json", r#"{"abi": []}"#)
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("src/Counter.sol")).expect("file uri");
+
+    let counter: Option<ContractArtifact> = harness
+        .request(
+            "solidity-analyzer/contractArtifact",
+            ContractArtifactParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                name: "Counter".to_string(),
+            },
+        )
+        .await;
+    let counter = counter.expect("counter artifact");
+    assert_eq!(counter.source.as_deref(), Some("src/Counter.sol"));
+    assert_eq!(counter.deployed_bytecode_size, Some(2));
+
+    // The vendored source is not in the workspace; the artifact alone answers.
+    let vendor: Option<ContractArtifact> = harness
+        .request(
+            "solidity-analyzer/contractArtifact",
+            ContractArtifactParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                name: "Vendor".to_string(),
+            },
+        )
+        .await;
+    let vendor = vendor.expect("vendor artifact");
+    assert_eq!(
+        vendor.method_identifiers.get("ping()").map(String::as_str),
+        Some("5c36b186")
+    );
+    assert_eq!(vendor.deployed_bytecode_size, None);
+    assert_eq!(vendor.compiler_version.as_deref(), Some("0.8.24"));
+
+    let missing: Option<ContractArtifact> = harness
+        .request(
+            "solidity-analyzer/contractArtifact",
+            ContractArtifactParams {
+                text_document: TextDocumentIdentifier { uri },
+                name: "Missing".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(missing, None);
+}
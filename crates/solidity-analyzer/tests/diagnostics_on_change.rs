@@ -9,9 +9,10 @@ use std::time::Duration;
 use tempfile::tempdir;
 use tower_lsp::ClientSocket;
 use tower_lsp::lsp_types::{
-    ClientCapabilities, DidChangeConfigurationParams, DidChangeTextDocumentParams,
-    DidOpenTextDocumentParams, InitializeParams, InitializedParams, TextDocumentContentChangeEvent,
-    TextDocumentItem, Url, VersionedTextDocumentIdentifier,
+    ClientCapabilities, DiagnosticSeverity, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, InitializeParams, InitializedParams,
+    PublishDiagnosticsParams, TextDocumentContentChangeEvent, TextDocumentItem, Url,
+    VersionedTextDocumentIdentifier,
 };
 
 async fn initialize_server(
@@ -154,3 +155,57 @@ contract Main {
     .await;
     assert!(!publish.diagnostics.is_empty());
 }
+
+fn has_error(publish: &PublishDiagnosticsParams) -> bool {
+    publish
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deleting_an_imported_file_reports_the_broken_import() {
+    let root_dir = tempdir().expect("tempdir");
+    let root = root_dir.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+
+    let main = r#"
+pragma solidity ^0.8.20;
+import "./Dep.sol";
+contract Main is Dep {}
+"#;
+    let dep_path = root.join("src/Dep.sol");
+    fs::write(&dep_path, "pragma solidity ^0.8.20;\ncontract Dep {}\n").expect("write dep");
+    let main_path = root.join("src/Main.sol");
+    fs::write(&main_path, main).expect("write main");
+
+    let (mut service, mut socket) = initialize_server(&root).await;
+    enable_diagnostics_on_change(&mut service).await;
+
+    let main_uri = Url::from_file_path(&main_path).expect("main uri");
+    let open_params = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: main_uri.clone(),
+            language_id: "solidity".to_string(),
+            version: 1,
+            text: main.to_string(),
+        },
+    };
+    send_notification(&mut service, "textDocument/didOpen", open_params).await;
+    let clean = wait_for_publish(&mut socket, Duration::from_secs(10), &main_uri, |_| true).await;
+    assert!(!has_error(&clean));
+    // Give the file watcher a poll to register the workspace root.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    fs::remove_file(&dep_path).expect("remove dep");
+
+    let broken = wait_for_publish(&mut socket, Duration::from_secs(10), &main_uri, has_error).await;
+    assert!(has_error(&broken));
+    let (_, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    assert!(
+        vfs.file_id(&sa_paths::NormalizedPath::new(dep_path.to_string_lossy()))
+            .is_none()
+    );
+}
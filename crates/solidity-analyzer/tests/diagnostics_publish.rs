@@ -92,3 +92,54 @@ async fn diagnostics_on_save_from_initialize_options() {
     assert_eq!(solc_diag.severity, Some(DiagnosticSeverity::ERROR));
     assert_eq!(solc_diag.source.as_deref(), Some("solc"));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn settings_changes_republish_without_a_save() {
+    let context = DiagnosticsTestContext::new();
+    let (mut service, mut socket) = context.start_service(None).await;
+
+    context.open_and_save(&mut service).await;
+    wait_for_diagnostics_with_codes(
+        &mut socket,
+        &context.file_uri,
+        &["1234", "mixed-case-function"],
+        TEST_TIMEOUT,
+    )
+    .await;
+
+    let settings = json!({
+        "lint": { "severity": { "mixed-case-function": "warning" } }
+    });
+    send_notification(
+        &mut service,
+        "workspace/didChangeConfiguration",
+        DidChangeConfigurationParams { settings },
+    )
+    .await;
+    let publish = wait_for_publish(&mut socket, TEST_TIMEOUT, &context.file_uri, |publish| {
+        publish.diagnostics.iter().any(|diag| {
+            matches!(diag.code, Some(NumberOrString::String(ref code)) if code == "mixed-case-function")
+                && diag.severity == Some(DiagnosticSeverity::WARNING)
+        })
+    })
+    .await;
+    assert_eq!(
+        find_diagnostic(&publish, "1234").severity,
+        Some(DiagnosticSeverity::ERROR)
+    );
+
+    let settings = json!({ "lint": { "enable": false } });
+    send_notification(
+        &mut service,
+        "workspace/didChangeConfiguration",
+        DidChangeConfigurationParams { settings },
+    )
+    .await;
+    let publish = wait_for_publish(&mut socket, TEST_TIMEOUT, &context.file_uri, |publish| {
+        !diagnostic_codes(publish)
+            .iter()
+            .any(|code| code == "mixed-case-function")
+    })
+    .await;
+    assert!(diagnostic_codes(&publish).iter().any(|code| code == "1234"));
+}
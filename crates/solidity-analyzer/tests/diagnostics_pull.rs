@@ -0,0 +1,130 @@
+use futures::StreamExt;
+use sa_test_support::lsp::{
+    drain_startup_messages, respond_to_request, response_result, send_notification, send_request,
+};
+use sa_test_support::setup_foundry_root;
+use serde_json::json;
+use std::fs;
+use std::time::Duration;
+use tempfile::tempdir;
+use tower_lsp::lsp_types::{
+    ClientCapabilities, DiagnosticClientCapabilities, DiagnosticServerCapabilities,
+    DidChangeConfigurationParams, DidOpenTextDocumentParams, DocumentDiagnosticParams,
+    DocumentDiagnosticReport, DocumentDiagnosticReportResult, InitializeParams, InitializeResult,
+    InitializedParams, TextDocumentClientCapabilities, TextDocumentIdentifier, TextDocumentItem,
+    Url,
+};
+
+fn pull_capabilities() -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            diagnostic: Some(DiagnosticClientCapabilities::default()),
+            ..TextDocumentClientCapabilities::default()
+        }),
+        ..ClientCapabilities::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pull_diagnostics_report_full_then_unchanged() {
+    let root_dir = tempdir().expect("tempdir");
+    let root = root_dir.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    let broken = r#"
+pragma solidity ^0.8.20;
+contract Main {
+    function run() public {
+        uint256 value =
+    }
+}
+"#;
+    let file_path = root.join("src/Main.sol");
+    fs::write(&file_path, broken).expect("write source");
+    let file_uri = Url::from_file_path(file_path.canonicalize().expect("canonicalize file"))
+        .expect("file uri");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(&root).expect("root uri")),
+        capabilities: pull_capabilities(),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let result = response_result::<InitializeResult>(response);
+    let Some(DiagnosticServerCapabilities::Options(options)) =
+        result.capabilities.diagnostic_provider
+    else {
+        panic!("expected diagnostic options");
+    };
+    assert!(options.workspace_diagnostics);
+
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    drain_startup_messages(&mut socket).await;
+    tokio::spawn(async move {
+        while let Some(request) = socket.next().await {
+            respond_to_request(&mut socket, &request).await;
+        }
+    });
+
+    let settings = json!({
+        "solidityAnalyzer": { "diagnostics": { "enable": true, "onChange": true } }
+    });
+    send_notification(
+        &mut service,
+        "workspace/didChangeConfiguration",
+        DidChangeConfigurationParams { settings },
+    )
+    .await;
+    send_notification(
+        &mut service,
+        "textDocument/didOpen",
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_uri.clone(),
+                language_id: "solidity".to_string(),
+                version: 1,
+                text: broken.to_string(),
+            },
+        },
+    )
+    .await;
+
+    let pull = |previous_result_id: Option<String>| DocumentDiagnosticParams {
+        text_document: TextDocumentIdentifier {
+            uri: file_uri.clone(),
+        },
+        identifier: None,
+        previous_result_id,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let mut id = 2;
+    let result_id = loop {
+        let response = send_request(&mut service, id, "textDocument/diagnostic", pull(None)).await;
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) =
+            response_result::<DocumentDiagnosticReportResult>(response)
+        else {
+            panic!("expected a full report");
+        };
+        let report = report.full_document_diagnostic_report;
+        if !report.items.is_empty() {
+            break report.result_id.expect("result id");
+        }
+        assert!(id < 100, "diagnostics never arrived");
+        id += 1;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    let params = pull(Some(result_id.clone()));
+    let response = send_request(&mut service, id + 1, "textDocument/diagnostic", params).await;
+    let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(report)) =
+        response_result::<DocumentDiagnosticReportResult>(response)
+    else {
+        panic!("expected an unchanged report");
+    };
+    assert_eq!(
+        report.unchanged_document_diagnostic_report.result_id,
+        result_id
+    );
+}
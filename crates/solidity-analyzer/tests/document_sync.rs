@@ -71,3 +71,67 @@ async fn document_sync_updates_vfs_and_analysis() {
     let vfs = vfs.expect("vfs snapshot after close");
     assert!(vfs.file_id(&path).is_none());
 }
+
+#[tokio::test]
+async fn incremental_changes_apply_in_order_and_keep_their_edits() {
+    let (mut service, _socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::parse("file:///workspace").expect("root uri")),
+        capabilities: ClientCapabilities::default(),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<tower_lsp::lsp_types::InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+
+    let uri = Url::parse("file:///workspace/src/Main.sol").expect("file uri");
+    let text = "contract Foo {\n    function f() public {}\n}";
+    let open_params = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "solidity".to_string(),
+            version: 1,
+            text: text.to_string(),
+        },
+    };
+    send_notification(&mut service, "textDocument/didOpen", open_params).await;
+
+    // The second range is relative to the text left by the first change.
+    let body = TextRange::at(TextSize::from(40), TextSize::from(0));
+    let name = TextRange::at(TextSize::from(9), TextSize::from(3));
+    let after_body = "contract Foo {\n    function f() public {x;}\n}";
+    let change_params = DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri.clone(),
+            version: 2,
+        },
+        content_changes: vec![
+            TextDocumentContentChangeEvent {
+                range: Some(to_lsp_range(body, text)),
+                range_length: None,
+                text: "x;".to_string(),
+            },
+            TextDocumentContentChangeEvent {
+                range: Some(to_lsp_range(name, after_body)),
+                range_length: None,
+                text: "Bar".to_string(),
+            },
+        ],
+    };
+    send_notification(&mut service, "textDocument/didChange", change_params).await;
+
+    let (analysis, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot after change");
+    let path = NormalizedPath::new("/workspace/src/Main.sol");
+    let file_id = vfs.file_id(&path).expect("file id");
+    assert_eq!(
+        analysis.file_text(file_id).as_ref(),
+        "contract Bar {\n    function f() public {x;}\n}"
+    );
+    let edits = vfs
+        .last_edits(file_id)
+        .iter()
+        .map(|edit| (edit.range, edit.new_text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(edits, vec![(body, "x;"), (name, "Bar")]);
+}
@@ -0,0 +1,86 @@
+use sa_test_support::lsp::{
+    drain_startup_messages, response_result, send_notification, send_request, wait_for_publish,
+};
+use sa_test_utils::FixtureBuilder;
+use tokio::time::Duration;
+use tower_lsp::lsp_types::{
+    DiagnosticSeverity, DidOpenTextDocumentParams, InitializeParams, InitializeResult,
+    InitializedParams, NumberOrString, TextDocumentItem, Url,
+};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn open_documents_report_erc_compliance_issues() {
+    let source = r#"pragma solidity ^0.8.20;
+
+/// @custom:erc 20
+contract Token {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    uint256 public totalSupply;
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        return true;
+    }
+
+    function transferFrom(address from, address to, uint256 amount) external returns (bool) {
+        return true;
+    }
+}
+"#;
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Token.sol", source)
+        .build()
+        .expect("fixture");
+    let file_uri = Url::from_file_path(fixture.root().join("src/Token.sol")).expect("file uri");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    drain_startup_messages(&mut socket).await;
+
+    let open = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: file_uri.clone(),
+            language_id: "solidity".to_string(),
+            version: 1,
+            text: source.to_string(),
+        },
+    };
+    send_notification(&mut service, "textDocument/didOpen", open).await;
+
+    let publish = wait_for_publish(&mut socket, TEST_TIMEOUT, &file_uri, |publish| {
+        publish
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.source.as_deref() == Some("solidity-analyzer"))
+    })
+    .await;
+    let diagnostics = publish
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("solidity-analyzer"))
+        .collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = diagnostics[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostic.code,
+        Some(NumberOrString::String("erc-compliance".to_string()))
+    );
+    assert_eq!(diagnostic.range.start.line, 3);
+    assert_eq!(
+        diagnostic.message,
+        "`Token` claims ERC-20: missing function `approve(address,uint256)`"
+    );
+}
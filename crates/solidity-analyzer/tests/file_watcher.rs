@@ -0,0 +1,253 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use sa_paths::NormalizedPath;
+use sa_test_support::lsp::{response_result, send_notification, send_request};
+use sa_test_support::setup_foundry_root;
+use serde_json::json;
+use tempfile::tempdir;
+use tower_lsp::lsp_types::{
+    ClientCapabilities, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, FileChangeType, FileEvent, InitializeParams, InitializeResult,
+    InitializedParams, TextDocumentIdentifier, TextDocumentItem, Url,
+};
+
+async fn initialize_server(root_uri: Url) -> tower_lsp::LspService<solidity_analyzer::Server> {
+    let (mut service, _socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(root_uri),
+        capabilities: ClientCapabilities::default(),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    service
+}
+
+async fn file_text(
+    service: &tower_lsp::LspService<solidity_analyzer::Server>,
+    path: &NormalizedPath,
+) -> Option<String> {
+    let (_, vfs) = service.inner().snapshot().await;
+    let vfs = vfs?;
+    let file_id = vfs.file_id(path)?;
+    vfs.file_text(file_id).map(str::to_string)
+}
+
+async fn wait_for_text(
+    service: &tower_lsp::LspService<solidity_analyzer::Server>,
+    path: &NormalizedPath,
+    expected: Option<&str>,
+) -> Option<String> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let text = file_text(service, path).await;
+        if text.as_deref() == expected || Instant::now() >= deadline {
+            return text;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn on_disk_edits_and_deletes_reach_the_vfs() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    let main = root.join("src/Main.sol");
+    fs::write(&main, "contract Main {}").expect("write main");
+
+    let service = initialize_server(Url::from_file_path(&root).expect("root uri")).await;
+    let main_path = NormalizedPath::new(main.to_string_lossy());
+    assert_eq!(
+        file_text(&service, &main_path).await.as_deref(),
+        Some("contract Main {}")
+    );
+    // Give the watcher a poll to register the workspace root.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    fs::write(&main, "contract Main { uint x; }").expect("rewrite main");
+    let created = root.join("src/Created.sol");
+    fs::write(&created, "contract Created {}").expect("write created");
+    let created_path = NormalizedPath::new(created.to_string_lossy());
+
+    assert_eq!(
+        wait_for_text(&service, &main_path, Some("contract Main { uint x; }"))
+            .await
+            .as_deref(),
+        Some("contract Main { uint x; }")
+    );
+    assert_eq!(
+        wait_for_text(&service, &created_path, Some("contract Created {}"))
+            .await
+            .as_deref(),
+        Some("contract Created {}")
+    );
+
+    fs::remove_file(&main).expect("remove main");
+    assert_eq!(wait_for_text(&service, &main_path, None).await, None);
+}
+
+#[tokio::test]
+async fn unsaved_buffers_shadow_disk_changes_until_closed() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    let main = root.join("src/Main.sol");
+    fs::write(&main, "contract Main {}").expect("write main");
+
+    let mut service = initialize_server(Url::from_file_path(&root).expect("root uri")).await;
+    let main_path = NormalizedPath::new(main.to_string_lossy());
+    let uri = Url::from_file_path(&main).expect("main uri");
+    send_notification(
+        &mut service,
+        "textDocument/didOpen",
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "solidity".to_string(),
+                version: 1,
+                text: "contract Main { uint unsaved; }".to_string(),
+            },
+        },
+    )
+    .await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    fs::write(&main, "contract Main { uint fromDisk; }").expect("rewrite main");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let (_, vfs) = service.inner().snapshot().await;
+        let vfs = vfs.expect("vfs snapshot");
+        let file_id = vfs.file_id(&main_path).expect("file id");
+        if vfs.has_disk_conflict(file_id) || Instant::now() >= deadline {
+            assert!(vfs.has_disk_conflict(file_id));
+            assert_eq!(
+                vfs.file_text(file_id),
+                Some("contract Main { uint unsaved; }")
+            );
+            assert_eq!(
+                vfs.disk_text(file_id),
+                Some("contract Main { uint fromDisk; }")
+            );
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    send_notification(
+        &mut service,
+        "textDocument/didClose",
+        DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri },
+        },
+    )
+    .await;
+    assert_eq!(
+        file_text(&service, &main_path).await.as_deref(),
+        Some("contract Main { uint fromDisk; }")
+    );
+}
+
+#[tokio::test]
+async fn watched_file_notifications_update_the_vfs() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    let main = root.join("src/Main.sol");
+    fs::write(&main, "contract Main {}").expect("write main");
+
+    let mut service = initialize_server(Url::from_file_path(&root).expect("root uri")).await;
+    let main_path = NormalizedPath::new(main.to_string_lossy());
+    let pulled = root.join("src/Pulled.sol");
+    fs::write(&pulled, "contract Pulled {}").expect("write pulled");
+    fs::remove_file(&main).expect("remove main");
+
+    send_notification(
+        &mut service,
+        "workspace/didChangeWatchedFiles",
+        DidChangeWatchedFilesParams {
+            changes: vec![
+                FileEvent {
+                    uri: Url::from_file_path(&pulled).expect("pulled uri"),
+                    typ: FileChangeType::CREATED,
+                },
+                FileEvent {
+                    uri: Url::from_file_path(&main).expect("main uri"),
+                    typ: FileChangeType::DELETED,
+                },
+            ],
+        },
+    )
+    .await;
+
+    let pulled_path = NormalizedPath::new(pulled.to_string_lossy());
+    assert_eq!(
+        file_text(&service, &pulled_path).await.as_deref(),
+        Some("contract Pulled {}")
+    );
+    assert_eq!(file_text(&service, &main_path).await, None);
+}
+
+#[tokio::test]
+async fn excluded_files_stay_out_of_the_vfs_until_opened() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    fs::write(root.join("src/Main.sol"), "contract Main {}").expect("write main");
+
+    let (mut service, _socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(&root).expect("root uri")),
+        capabilities: ClientCapabilities::default(),
+        initialization_options: Some(json!({ "indexing": { "exclude": ["src/generated"] } })),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+
+    fs::create_dir_all(root.join("src/generated")).expect("create generated dir");
+    let generated = root.join("src/generated/Types.sol");
+    fs::write(&generated, "contract Types {}").expect("write generated");
+    let generated_uri = Url::from_file_path(&generated).expect("generated uri");
+    send_notification(
+        &mut service,
+        "workspace/didChangeWatchedFiles",
+        DidChangeWatchedFilesParams {
+            changes: vec![FileEvent {
+                uri: generated_uri.clone(),
+                typ: FileChangeType::CREATED,
+            }],
+        },
+    )
+    .await;
+    let generated_path = NormalizedPath::new(generated.to_string_lossy());
+    assert_eq!(file_text(&service, &generated_path).await, None);
+
+    send_notification(
+        &mut service,
+        "textDocument/didOpen",
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: generated_uri,
+                language_id: "solidity".to_string(),
+                version: 1,
+                text: "contract Types { uint x; }".to_string(),
+            },
+        },
+    )
+    .await;
+    assert_eq!(
+        file_text(&service, &generated_path).await.as_deref(),
+        Some("contract Types { uint x; }")
+    );
+    let (analysis, _) = service.inner().snapshot().await;
+    let symbols = analysis.workspace_symbols("Types");
+    assert!(symbols.iter().any(|symbol| symbol.name() == "Types"));
+}
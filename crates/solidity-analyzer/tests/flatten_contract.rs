@@ -0,0 +1,53 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use tower_lsp::lsp_types::{
+    InitializeParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+#[tokio::test]
+async fn flatten_contract_annotates_inherited_members() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/Token.sol",
+            r#"contract Base {
+    uint256 internal total;
+}
+
+contract Token is Base {
+    function supply() public view returns (uint256) {
+        return total;
+    }
+}"#,
+        )
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("src/Token.sol")).expect("file uri");
+
+    let flattened: Option<String> = harness
+        .request(
+            "solidity-analyzer/flattenContract",
+            TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(4, 9),
+            },
+        )
+        .await;
+    let flattened = flattened.expect("flattened contract");
+    assert!(
+        flattened.starts_with(
+            "contract Token is Base {\n    // from Base\n    uint256 internal total;\n\n    // from Token\n"
+        ),
+        "{flattened}"
+    );
+}
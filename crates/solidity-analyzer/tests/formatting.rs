@@ -1,11 +1,13 @@
 use std::cmp::Reverse;
 
-use sa_span::lsp::from_lsp_range;
+use sa_span::lsp::{from_lsp_range, to_lsp_range};
+use sa_test_support::find_range;
 use sa_test_utils::FixtureBuilder;
 use sa_test_utils::lsp::LspTestHarness;
 use tower_lsp::lsp_types::{
     DidChangeTextDocumentParams, DidOpenTextDocumentParams, DocumentFormattingParams,
-    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem, Url,
+    DocumentRangeFormattingParams, TextDocumentContentChangeEvent, TextDocumentIdentifier,
+    TextDocumentItem, Url,
 };
 
 fn apply_text_edits(text: &str, edits: &[tower_lsp::lsp_types::TextEdit]) -> String {
@@ -121,3 +123,54 @@ contract Foo {
         .expect("textDocument/formatting returned null");
     assert!(edits.is_empty());
 }
+
+#[tokio::test]
+async fn range_formatting_only_formats_selected_statement() {
+    let text = r#"contract Foo {
+    function a() public {
+        uint256 x=1;
+        uint256 y=2;
+    }
+}
+"#;
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", text)
+        .build()
+        .expect("fixture");
+
+    let mut harness = LspTestHarness::new(fixture.root(), solidity_analyzer::Server::new).await;
+
+    let main_uri = Url::from_file_path(fixture.root().join("src/Main.sol")).expect("main uri");
+    harness
+        .notify(
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: main_uri.clone(),
+                    language_id: "solidity".to_string(),
+                    version: 1,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+
+    let params = DocumentRangeFormattingParams {
+        text_document: TextDocumentIdentifier { uri: main_uri },
+        range: to_lsp_range(find_range(text, "y=2"), text),
+        options: Default::default(),
+        work_done_progress_params: Default::default(),
+    };
+    let edits = harness
+        .request::<_, Option<Vec<tower_lsp::lsp_types::TextEdit>>>(
+            "textDocument/rangeFormatting",
+            params,
+        )
+        .await
+        .expect("textDocument/rangeFormatting returned null");
+
+    let formatted = apply_text_edits(text, &edits);
+    assert!(formatted.contains("        uint256 x=1;\n"));
+    assert!(formatted.contains("        uint256 y = 2;\n"));
+}
@@ -0,0 +1,73 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::{ContractKind, InheritanceGraph, InheritanceGraphParams};
+use tower_lsp::lsp_types::{InitializeParams, Position, TextDocumentIdentifier, Url};
+
+#[tokio::test]
+async fn inheritance_graph_returns_the_project_or_one_contract() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/Main.sol",
+            r#"import "./Base.sol";
+
+contract Main is Base {}
+contract Unrelated {}"#,
+        )
+        .file("src/Base.sol", "abstract contract Base {}")
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("src/Main.sol")).expect("file uri");
+
+    let graph: Option<InheritanceGraph> = harness
+        .request(
+            "solidity-analyzer/inheritanceGraph",
+            InheritanceGraphParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: None,
+            },
+        )
+        .await;
+    let graph = graph.expect("project graph");
+    let names = graph
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Base", "Main", "Unrelated"]);
+    assert!(graph.nodes[0].is_abstract);
+    assert_eq!(graph.nodes[1].kind, ContractKind::Contract);
+    assert_eq!(graph.nodes[1].linearization, [1, 0]);
+    let location = graph.nodes[1].location.as_ref().expect("main location");
+    assert_eq!(location.uri, uri);
+    assert_eq!(location.range.start, Position::new(2, 9));
+
+    let graph: Option<InheritanceGraph> = harness
+        .request(
+            "solidity-analyzer/inheritanceGraph",
+            InheritanceGraphParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Some(Position::new(2, 10)),
+            },
+        )
+        .await;
+    let graph = graph.expect("contract graph");
+    let names = graph
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Base", "Main"]);
+    assert_eq!(graph.edges.len(), 1);
+    assert_eq!((graph.edges[0].derived, graph.edges[0].base), (1, 0));
+}
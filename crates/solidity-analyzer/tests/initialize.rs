@@ -1,7 +1,7 @@
 use sa_test_support::lsp::{response_result, send_request};
 use tower_lsp::lsp_types::{
-    ClientCapabilities, InitializeParams, OneOf, TextDocumentSyncCapability, TextDocumentSyncKind,
-    Url, WorkspaceFoldersServerCapabilities,
+    ClientCapabilities, GeneralClientCapabilities, InitializeParams, OneOf, PositionEncodingKind,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url, WorkspaceFoldersServerCapabilities,
 };
 
 #[tokio::test]
@@ -49,6 +49,48 @@ async fn initialize_advertises_basic_capabilities() {
         vec![
             "solidity-analyzer.installFoundrySolc".to_string(),
             "solidity-analyzer.indexedFiles".to_string(),
+            "solidity-analyzer.selectProfile".to_string(),
+            "solidity-analyzer.projectStructure".to_string(),
+            "solidity-analyzer.memoryUsage".to_string(),
+            "solidity-analyzer.viewHir".to_string(),
+            "solidity-analyzer.viewDefMap".to_string(),
+            "solidity-analyzer.runTest".to_string(),
+            "solidity-analyzer.importSlither".to_string(),
+            "solidity-analyzer.fetchVerifiedSources".to_string(),
         ]
     );
 }
+
+#[tokio::test]
+async fn initialize_negotiates_the_first_supported_position_encoding() {
+    let (mut service, _socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+
+    let params = InitializeParams {
+        capabilities: ClientCapabilities {
+            general: Some(GeneralClientCapabilities {
+                position_encodings: Some(vec![
+                    PositionEncodingKind::new("utf-7"),
+                    PositionEncodingKind::UTF8,
+                    PositionEncodingKind::UTF16,
+                ]),
+                ..GeneralClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        },
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", params).await;
+    let result = response_result::<tower_lsp::lsp_types::InitializeResult>(response);
+    assert_eq!(
+        result.capabilities.position_encoding,
+        Some(PositionEncodingKind::UTF8)
+    );
+
+    let (mut service, _socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let response = send_request(&mut service, 1, "initialize", InitializeParams::default()).await;
+    let result = response_result::<tower_lsp::lsp_types::InitializeResult>(response);
+    assert_eq!(
+        result.capabilities.position_encoding,
+        Some(PositionEncodingKind::UTF16)
+    );
+}
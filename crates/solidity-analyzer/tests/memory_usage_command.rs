@@ -0,0 +1,66 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::MemoryUsage;
+use tower_lsp::lsp_types::ExecuteCommandParams;
+
+#[tokio::test]
+async fn memory_usage_command_reports_loaded_files() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", r#"contract Main {}"#)
+        .build()
+        .expect("fixture");
+
+    let mut harness = LspTestHarness::new(fixture.root(), solidity_analyzer::Server::new).await;
+    let params = ExecuteCommandParams {
+        command: "solidity-analyzer.memoryUsage".to_string(),
+        arguments: Vec::new(),
+        work_done_progress_params: Default::default(),
+    };
+    let result: Option<MemoryUsage> = harness.request("workspace/executeCommand", params).await;
+    let usage = result.expect("command result");
+
+    let vfs = usage
+        .entries
+        .iter()
+        .find(|entry| entry.name == "vfs texts")
+        .expect("vfs entry");
+    assert_eq!(vfs.count, 1);
+    assert_eq!(vfs.bytes, "contract Main {}".len());
+    assert_eq!(
+        usage.total_bytes,
+        usage.entries.iter().map(|entry| entry.bytes).sum::<usize>()
+    );
+    let inputs = usage
+        .caches
+        .iter()
+        .find(|cache| cache.name == "salsa inputs")
+        .expect("salsa inputs entry");
+    assert_eq!(inputs.entries, 2);
+    for row in [
+        "parse cache",
+        "def map entries",
+        "salsa memos",
+        "syntax trees",
+    ] {
+        assert!(usage.report.contains(row), "{row}");
+    }
+}
+
+#[test]
+fn memory_usage_report_loads_the_workspace() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", r#"contract Main {}"#)
+        .build()
+        .expect("fixture");
+
+    let report = solidity_analyzer::memory_usage_report(fixture.root()).expect("report");
+    assert!(report.contains("vfs texts"));
+    assert!(
+        report
+            .lines()
+            .last()
+            .is_some_and(|line| line.starts_with("total"))
+    );
+}
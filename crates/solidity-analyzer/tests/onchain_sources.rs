@@ -0,0 +1,82 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use serde_json::{Value, json};
+use tower_lsp::lsp_types::{
+    ExecuteCommandParams, GotoDefinitionParams, GotoDefinitionResponse, InitializeParams, Position,
+    TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+const ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+#[tokio::test]
+async fn cached_verified_sources_load_without_network() {
+    let cache = format!("cache/onchain/1/{}", ADDRESS.to_ascii_lowercase());
+    let deploy = format!("contract Deploy {{\n    address constant USDC = {ADDRESS};\n}}\n");
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Deploy.sol", deploy.as_str())
+        .file(
+            format!("{cache}/contract.json"),
+            json!({
+                "name": "FiatToken",
+                "main": "contracts/FiatToken.sol",
+                "sources": ["contracts/FiatToken.sol"]
+            })
+            .to_string(),
+        )
+        .file(
+            format!("{cache}/sources/contracts/FiatToken.sol"),
+            "// Verified\ncontract FiatToken {}\n",
+        )
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        initialization_options: Some(json!({ "onchain": { "enable": true } })),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params(params, solidity_analyzer::Server::new).await;
+
+    let summary: Option<Value> = harness
+        .request(
+            "workspace/executeCommand",
+            ExecuteCommandParams {
+                command: "solidity-analyzer.fetchVerifiedSources".to_string(),
+                arguments: vec![Value::String(ADDRESS.to_string())],
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .await;
+    assert_eq!(
+        summary,
+        Some(Value::String(
+            "Loaded 1 verified source file(s) of FiatToken".to_string()
+        ))
+    );
+
+    let uri = Url::from_file_path(fixture.root().join("src/Deploy.sol")).expect("file uri");
+    let definition: Option<GotoDefinitionResponse> = harness
+        .request(
+            "textDocument/definition",
+            GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: Position::new(1, 35),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .await;
+    let Some(GotoDefinitionResponse::Scalar(location)) = definition else {
+        panic!("expected the verified contract, got {definition:?}");
+    };
+    assert!(
+        location
+            .uri
+            .path()
+            .ends_with("sources/contracts/FiatToken.sol"),
+        "{location:?}"
+    );
+    assert_eq!(location.range.start, Position::new(1, 9));
+}
@@ -0,0 +1,184 @@
+use std::fs;
+
+use futures::StreamExt;
+use sa_test_support::lsp::{respond_to_request, response_result, send_notification, send_request};
+use sa_test_support::setup_foundry_root;
+use serde_json::json;
+use tempfile::tempdir;
+use tokio::time::{Duration, timeout};
+use tower_lsp::ClientSocket;
+use tower_lsp::lsp_types::{
+    ClientCapabilities, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+    DidOpenTextDocumentParams, InitializeParams, InitializeResult, InitializedParams,
+    ProgressParams, ProgressParamsValue, TextDocumentContentChangeEvent, TextDocumentItem, Url,
+    VersionedTextDocumentIdentifier, WindowClientCapabilities, WorkDoneProgress,
+};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answers the server's requests and collects `$/progress` values until a report titled
+/// `title` has ended.
+async fn progress_until_end(socket: &mut ClientSocket, title: &str) -> Vec<WorkDoneProgress> {
+    let mut token = None;
+    let mut values = Vec::new();
+    timeout(TEST_TIMEOUT, async {
+        while let Some(request) = socket.next().await {
+            if request.method() != "$/progress" {
+                respond_to_request(socket, &request).await;
+                continue;
+            }
+            let params: ProgressParams =
+                serde_json::from_value(request.params().cloned().expect("progress params"))
+                    .expect("progress params");
+            let ProgressParamsValue::WorkDone(value) = params.value;
+            if let WorkDoneProgress::Begin(begin) = &value
+                && begin.title == title
+            {
+                token = Some(params.token.clone());
+            }
+            if token.as_ref() != Some(&params.token) {
+                continue;
+            }
+            let ended = matches!(value, WorkDoneProgress::End(_));
+            values.push(value);
+            if ended {
+                return;
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timed out waiting for {title} progress"));
+    values
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn indexing_and_analysis_report_progress() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    fs::write(root.join("src/A.sol"), "import \"./B.sol\";\ncontract A {}").expect("write a");
+    fs::write(root.join("src/B.sol"), "contract B {}").expect("write b");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(&root).expect("root uri")),
+        capabilities: ClientCapabilities {
+            window: Some(WindowClientCapabilities {
+                work_done_progress: Some(true),
+                ..WindowClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        },
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+
+    let analysis = progress_until_end(&mut socket, "Analyzing").await;
+    assert!(matches!(
+        analysis.first(),
+        Some(WorkDoneProgress::Begin(begin)) if begin.cancellable == Some(true)
+    ));
+
+    // Indexing settings take a reload, which indexes the workspace again.
+    let settings = json!({ "indexing": { "exclude": ["out"] } });
+    send_notification(
+        &mut service,
+        "workspace/didChangeConfiguration",
+        DidChangeConfigurationParams { settings },
+    )
+    .await;
+    let indexing = progress_until_end(&mut socket, "Indexing").await;
+    let messages = indexing
+        .iter()
+        .filter_map(|value| match value {
+            WorkDoneProgress::Report(report) => report.message.clone(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages.last().map(String::as_str),
+        Some("Indexing 2/2 files")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quick_edits_rebuild_the_project_once() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    let main = root.join("src/Main.sol");
+    fs::write(&main, "contract Main {}").expect("write main");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(&root).expect("root uri")),
+        capabilities: ClientCapabilities {
+            window: Some(WindowClientCapabilities {
+                work_done_progress: Some(true),
+                ..WindowClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        },
+        initialization_options: Some(json!({ "sema": { "rebuildDelayMs": 300 } })),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    progress_until_end(&mut socket, "Analyzing").await;
+
+    let uri = Url::from_file_path(&main).expect("main uri");
+    send_notification(
+        &mut service,
+        "textDocument/didOpen",
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "solidity".to_string(),
+                version: 1,
+                text: "contract Main {}".to_string(),
+            },
+        },
+    )
+    .await;
+    for version in 2..=6 {
+        send_notification(
+            &mut service,
+            "textDocument/didChange",
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: format!("contract Main {{ uint256 value{version}; }}"),
+                }],
+            },
+        )
+        .await;
+    }
+
+    // Every rebuild reports its own progress; the edits above should share one. The socket is
+    // read until it stays quiet for well past the rebuild delay.
+    let mut rebuilds = 0;
+    while let Ok(Some(request)) = timeout(Duration::from_secs(2), socket.next()).await {
+        if request.method() != "$/progress" {
+            respond_to_request(&mut socket, &request).await;
+            continue;
+        }
+        let params: ProgressParams =
+            serde_json::from_value(request.params().cloned().expect("progress params"))
+                .expect("progress params");
+        let ProgressParamsValue::WorkDone(value) = params.value;
+        if matches!(&value, WorkDoneProgress::Begin(begin) if begin.title == "Analyzing changes") {
+            rebuilds += 1;
+        }
+    }
+    assert_eq!(rebuilds, 1);
+}
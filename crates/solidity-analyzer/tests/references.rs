@@ -136,6 +136,73 @@ async fn references_return_locations_across_files() {
     assert!(result.iter().any(|location| location.uri == thing_uri));
 }
 
+#[cfg(unix)]
+#[tokio::test]
+async fn references_keep_the_uris_of_a_workspace_opened_through_a_symlink() {
+    let temp = tempdir().expect("tempdir");
+    let real = temp
+        .path()
+        .canonicalize()
+        .expect("canonicalize root")
+        .join("real");
+    fs::create_dir_all(&real).expect("create workspace");
+    create_foundry_workspace(&real);
+    let root = real.with_file_name("link");
+    std::os::unix::fs::symlink(&real, &root).expect("symlink workspace");
+
+    let (main_text, offset) =
+        extract_offset("import \"./Thing.sol\"; contract Main { /*caret*/Lib lib; }");
+    fs::write(root.join("src/Main.sol"), &main_text).expect("write main");
+    fs::write(root.join("src/Thing.sol"), "contract Lib {}").expect("write thing");
+
+    let (mut service, _root_uri) = setup_lsp_service(&root, || {
+        tower_lsp::LspService::new(solidity_analyzer::Server::new)
+    })
+    .await;
+
+    let main_uri = Url::from_file_path(root.join("src/Main.sol")).expect("main uri");
+    let thing_uri = Url::from_file_path(root.join("src/Thing.sol")).expect("thing uri");
+    send_notification(
+        &mut service,
+        "textDocument/didOpen",
+        tower_lsp::lsp_types::DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: main_uri.clone(),
+                language_id: "solidity".to_string(),
+                version: 1,
+                text: main_text.clone(),
+            },
+        },
+    )
+    .await;
+
+    let params = ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: main_uri.clone(),
+            },
+            position: to_lsp_position(offset, &main_text),
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext {
+            include_declaration: true,
+        },
+    };
+    let response = send_request(&mut service, 2, "textDocument/references", params).await;
+    let result = response_result::<Option<Vec<Location>>>(response).expect("references response");
+
+    let mut uris = result
+        .iter()
+        .map(|location| location.uri.clone())
+        .collect::<Vec<_>>();
+    uris.sort();
+    uris.dedup();
+    let mut expected = vec![main_uri, thing_uri];
+    expected.sort();
+    assert_eq!(uris, expected);
+}
+
 #[tokio::test]
 async fn references_include_definition_site_and_modifier_usage() {
     let (text, offset) = extract_offset(
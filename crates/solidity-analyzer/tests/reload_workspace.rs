@@ -0,0 +1,40 @@
+use std::fs;
+
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use tower_lsp::lsp_types::{InitializeParams, Url};
+
+#[tokio::test]
+async fn reload_workspace_picks_up_new_files_and_remappings() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .foundry_toml("[profile.default]\nsrc = \"src\"\n")
+        .file("src/A.sol", "contract A {}\n")
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+
+    fs::write(
+        fixture.root().join("foundry.toml"),
+        "[profile.default]\nsrc = \"src\"\nremappings = [\"lib/=src/\"]\n",
+    )
+    .expect("write foundry.toml");
+    fs::write(fixture.root().join("src/B.sol"), "contract B {}\n").expect("write B.sol");
+
+    let summary: String = harness
+        .request("solidity-analyzer/reloadWorkspace", ())
+        .await;
+    assert_eq!(
+        summary,
+        "Reloaded 1 project(s): remappings +1/-0, indexed files +1/-0"
+    );
+}
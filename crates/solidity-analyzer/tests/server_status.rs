@@ -3,8 +3,9 @@ use std::time::Duration;
 use test_fixtures::DiagnosticsTestContext;
 use tokio::time::Instant;
 use tower_lsp::lsp_types::{
-    ClientCapabilities, DidOpenTextDocumentParams, DidSaveTextDocumentParams, InitializeParams,
-    TextDocumentIdentifier, TextDocumentItem, Url,
+    ClientCapabilities, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, InitializeParams, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentItem, Url, VersionedTextDocumentIdentifier,
 };
 
 use sa_test_support::lsp::LspTestHarness;
@@ -125,3 +126,75 @@ contract StatusTest {
     assert_eq!(done.health, Health::Ok);
     assert!(done.quiescent);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_status_reports_files_without_semantic_analysis() {
+    let source = "contract Broken {\n    function run() public {\n".to_string();
+    let context = DiagnosticsTestContext::with_solc_options(
+        StubSolcOptions {
+            json: Some(stub_solc_output_empty()),
+            sleep_seconds: None,
+            capture_stdin: false,
+        },
+        source,
+    );
+
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(&context.root).expect("root uri")),
+        capabilities: ClientCapabilities {
+            experimental: Some(json!({ "serverStatusNotification": true })),
+            ..ClientCapabilities::default()
+        },
+        initialization_options: Some(json!({
+            "solidityAnalyzer": {
+                "diagnostics": { "enable": false },
+                "lint": { "enable": false }
+            }
+        })),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params(params, solidity_analyzer::Server::new).await;
+
+    harness
+        .notify(
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: context.file_uri.clone(),
+                    language_id: "solidity".to_string(),
+                    version: 1,
+                    text: context.source.clone(),
+                },
+            },
+        )
+        .await;
+    let degraded = wait_for_status(&mut harness, STATUS_TIMEOUT, |status| {
+        status.health == Health::Warning
+    })
+    .await;
+    let message = degraded.message.expect("status message");
+    assert!(message.contains("Main.sol"), "{message}");
+    assert!(message.ends_with("due to parse errors"), "{message}");
+
+    harness
+        .notify(
+            "textDocument/didChange",
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: context.file_uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "contract Fixed {\n    function run() public {}\n}\n".to_string(),
+                }],
+            },
+        )
+        .await;
+    let recovered = wait_for_status(&mut harness, STATUS_TIMEOUT, |status| {
+        status.health == Health::Ok
+    })
+    .await;
+    assert_eq!(recovered.message.as_deref(), Some("OK"));
+}
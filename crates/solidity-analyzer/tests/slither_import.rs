@@ -0,0 +1,79 @@
+use sa_test_support::lsp::{
+    drain_startup_messages, response_result, send_notification, send_request, wait_for_publish,
+};
+use sa_test_utils::FixtureBuilder;
+use serde_json::{Value, json};
+use tokio::time::Duration;
+use tower_lsp::lsp_types::{
+    DiagnosticSeverity, ExecuteCommandParams, InitializeParams, InitializeResult,
+    InitializedParams, NumberOrString, Url,
+};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn import_slither_publishes_findings() {
+    let source = "contract Vault {\n    function withdraw() external {}\n}\n";
+    let start = source.find("function").expect("function");
+    let report = json!({
+        "success": true,
+        "error": null,
+        "results": {
+            "detectors": [{
+                "check": "reentrancy-eth",
+                "impact": "High",
+                "description": "Reentrancy in Vault.withdraw()",
+                "elements": [{
+                    "type": "function",
+                    "source_mapping": {
+                        "start": start,
+                        "length": 31,
+                        "filename_relative": "src/Vault.sol",
+                        "is_dependency": false
+                    }
+                }]
+            }]
+        }
+    });
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Vault.sol", source)
+        .file("reports/slither.json", report.to_string())
+        .build()
+        .expect("fixture");
+    let file_uri = Url::from_file_path(fixture.root().join("src/Vault.sol")).expect("file uri");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    drain_startup_messages(&mut socket).await;
+
+    let params = ExecuteCommandParams {
+        command: "solidity-analyzer.importSlither".to_string(),
+        arguments: vec![Value::String("reports/slither.json".to_string())],
+        work_done_progress_params: Default::default(),
+    };
+    let (response, publish) = tokio::join!(
+        send_request(&mut service, 2, "workspace/executeCommand", params),
+        wait_for_publish(&mut socket, TEST_TIMEOUT, &file_uri, |publish| {
+            !publish.diagnostics.is_empty()
+        }),
+    );
+    let summary = response_result::<Option<String>>(response);
+    assert_eq!(summary.as_deref(), Some("Imported 1 Slither finding(s)"));
+
+    let diagnostic = &publish.diagnostics[0];
+    assert_eq!(diagnostic.source.as_deref(), Some("slither"));
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostic.code,
+        Some(NumberOrString::String("reentrancy-eth".to_string()))
+    );
+    assert_eq!(diagnostic.range.start.line, 1);
+    assert_eq!(diagnostic.message, "Reentrancy in Vault.withdraw()");
+}
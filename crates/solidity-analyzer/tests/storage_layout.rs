@@ -0,0 +1,70 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::StorageSlot;
+use tower_lsp::lsp_types::{
+    InitializeParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+#[tokio::test]
+async fn storage_layout_lists_slots_of_the_contract_at_the_position() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/Vault.sol",
+            r#"contract Base {
+    address owner;
+}
+
+contract Vault is Base {
+    bool paused;
+    mapping(address => uint256) balances;
+}"#,
+        )
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("src/Vault.sol")).expect("file uri");
+
+    let layout: Option<Vec<StorageSlot>> = harness
+        .request(
+            "solidity-analyzer/storageLayout",
+            TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(5, 4),
+            },
+        )
+        .await;
+    let layout = layout.expect("storage layout");
+    let rows = layout
+        .iter()
+        .map(|slot| {
+            (
+                slot.contract.as_str(),
+                slot.name.as_str(),
+                slot.slot.as_str(),
+                slot.offset,
+                slot.ty.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            ("Base", "owner", "0", 0, "address"),
+            ("Vault", "paused", "0", 20, "bool"),
+            ("Vault", "balances", "1", 0, "mapping(address => uint256)"),
+        ]
+    );
+    let location = layout[0].location.as_ref().expect("owner location");
+    assert_eq!(location.uri, uri);
+    assert_eq!(location.range.start, Position::new(1, 12));
+}
@@ -0,0 +1,71 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::{StorageUpgradeCheckParams, StorageUpgradeIssue};
+use tower_lsp::lsp_types::{
+    InitializeParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+#[tokio::test]
+async fn storage_upgrade_check_compares_with_the_previous_version() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/VaultV1.sol",
+            r#"contract VaultV1 {
+    address owner;
+    uint256 total;
+}"#,
+        )
+        .file(
+            "src/VaultV2.sol",
+            r#"contract VaultV2 {
+    uint256 total;
+    address owner;
+}"#,
+        )
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let previous = Url::from_file_path(fixture.root().join("src/VaultV1.sol")).expect("file uri");
+    let current = Url::from_file_path(fixture.root().join("src/VaultV2.sol")).expect("file uri");
+
+    let issues: Option<Vec<StorageUpgradeIssue>> = harness
+        .request(
+            "solidity-analyzer/storageUpgradeCheck",
+            StorageUpgradeCheckParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: current.clone(),
+                    },
+                    position: Position::new(0, 9),
+                },
+                previous: Some(TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: previous },
+                    position: Position::new(0, 9),
+                }),
+            },
+        )
+        .await;
+    let issues = issues.expect("storage upgrade issues");
+    let rows = issues
+        .iter()
+        .map(|issue| (issue.kind.as_str(), issue.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(rows, vec![("moved", "owner"), ("moved", "total")]);
+    assert_eq!(
+        issues[0].message,
+        "`owner` moved from slot 0 (offset 0) to slot 1 (offset 0)"
+    );
+    let location = issues[0].location.as_ref().expect("owner location");
+    assert_eq!(location.uri, current);
+    assert_eq!(location.range.start, Position::new(2, 12));
+}
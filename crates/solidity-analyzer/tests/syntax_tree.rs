@@ -0,0 +1,52 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use tower_lsp::lsp_types::{InitializeParams, Position, Range, TextDocumentIdentifier, Url};
+
+#[tokio::test]
+async fn syntax_tree_dumps_the_file_or_the_selected_node() {
+    let text = "contract Main {\n    function foo() public {}\n}\n";
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Main.sol", text)
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("src/Main.sol")).expect("file uri");
+
+    let tree: Option<String> = harness
+        .request(
+            "solidity-analyzer/syntaxTree",
+            solidity_analyzer::lsp_ext::SyntaxTreeParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                range: None,
+            },
+        )
+        .await;
+    let tree = tree.expect("syntax tree");
+    assert!(
+        tree.starts_with("SourceUnit@0..47\n  Contract@0..46\n"),
+        "{tree}"
+    );
+
+    let selection = Range::new(Position::new(1, 13), Position::new(1, 25));
+    let tree: Option<String> = harness
+        .request(
+            "solidity-analyzer/syntaxTree",
+            solidity_analyzer::lsp_ext::SyntaxTreeParams {
+                text_document: TextDocumentIdentifier { uri },
+                range: Some(selection),
+            },
+        )
+        .await;
+    let tree = tree.expect("selected syntax tree");
+    assert!(tree.starts_with("Function@20..44\n"), "{tree}");
+}
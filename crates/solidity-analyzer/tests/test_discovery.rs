@@ -0,0 +1,97 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::{DiscoverTestsParams, RunTestArguments, TestContract, TestKind};
+use tower_lsp::lsp_types::{
+    CodeLens, CodeLensParams, InitializeParams, PartialResultParams, Position,
+    TextDocumentIdentifier, Url, WorkDoneProgressParams,
+};
+
+#[tokio::test]
+async fn discovers_tests_and_offers_run_lenses() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file("src/Counter.sol", "contract Counter {}")
+        .file(
+            "test/Counter.t.sol",
+            r#"contract CounterTest {
+    function setUp() public {}
+    function test_Increment() public {}
+    function testFuzz_Set(uint256 x) public {}
+    function invariant_Positive() public {}
+    function testHelper() internal {}
+}"#,
+        )
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let uri = Url::from_file_path(fixture.root().join("test/Counter.t.sol")).expect("file uri");
+
+    let contracts: Vec<TestContract> = harness
+        .request(
+            "solidity-analyzer/discoverTests",
+            DiscoverTestsParams {
+                text_document: None,
+            },
+        )
+        .await;
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].name, "CounterTest");
+    assert_eq!(contracts[0].location.uri, uri);
+    let tests = contracts[0]
+        .tests
+        .iter()
+        .map(|test| (test.name.as_str(), test.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tests,
+        [
+            ("test_Increment", TestKind::Test),
+            ("testFuzz_Set", TestKind::Fuzz),
+            ("invariant_Positive", TestKind::Invariant),
+        ]
+    );
+    assert_eq!(
+        contracts[0].tests[0].location.range.start,
+        Position::new(2, 13)
+    );
+
+    let lenses: Option<Vec<CodeLens>> = harness
+        .request(
+            "textDocument/codeLens",
+            CodeLensParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            },
+        )
+        .await;
+    let lenses = lenses.expect("code lenses");
+    assert_eq!(lenses.len(), 4);
+    let command = lenses[1].command.as_ref().expect("run test command");
+    assert_eq!(command.command, "solidity-analyzer.runTest");
+    let arguments: RunTestArguments = serde_json::from_value(
+        command
+            .arguments
+            .as_ref()
+            .and_then(|arguments| arguments.first().cloned())
+            .expect("command argument"),
+    )
+    .expect("run test arguments");
+    assert_eq!(
+        arguments,
+        RunTestArguments {
+            uri,
+            contract: "CounterTest".to_string(),
+            test: Some("test_Increment".to_string()),
+        }
+    );
+}
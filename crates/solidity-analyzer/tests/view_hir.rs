@@ -0,0 +1,55 @@
+use sa_test_utils::FixtureBuilder;
+use sa_test_utils::lsp::LspTestHarness;
+use solidity_analyzer::lsp_ext::ViewHirParams;
+use tower_lsp::lsp_types::{InitializeParams, TextDocumentIdentifier, Url};
+
+#[tokio::test]
+async fn view_hir_returns_imports_items_and_locals() {
+    let fixture = FixtureBuilder::new()
+        .expect("fixture builder")
+        .file(
+            "src/Main.sol",
+            r#"import "./Dep.sol";
+
+contract Main {
+    function run(uint256 a) public returns (uint256) {
+        uint256 b = a;
+        return b;
+    }
+}"#,
+        )
+        .file("src/Dep.sol", "contract Dep {}")
+        .build()
+        .expect("fixture");
+    let params = InitializeParams {
+        root_uri: Some(Url::from_file_path(fixture.root()).expect("root uri")),
+        ..InitializeParams::default()
+    };
+    let mut harness = LspTestHarness::new_with_params_and_builder(
+        params,
+        solidity_analyzer::Server::new,
+        solidity_analyzer::register_custom_methods,
+    )
+    .await;
+    let main = fixture.root().join("src/Main.sol");
+
+    let hir: Option<String> = harness
+        .request(
+            "solidity-analyzer/viewHir",
+            ViewHirParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&main).expect("file uri"),
+                },
+            },
+        )
+        .await;
+    let hir = hir.expect("hir dump");
+    let dep = fixture.root().join("src/Dep.sol");
+    assert!(
+        hir.contains(&format!("import \"./Dep.sol\" -> {}", dep.display())),
+        "{hir}"
+    );
+    assert!(hir.contains("  contract Main "), "{hir}");
+    assert!(hir.contains("fn Main.run "), "{hir}");
+    assert!(hir.contains("\nlocals\n"), "{hir}");
+}
@@ -2,12 +2,17 @@ use std::fs;
 
 use sa_paths::NormalizedPath;
 use sa_project_model::Remapping;
-use sa_test_support::lsp::{response_result, send_notification, send_request};
+use sa_test_support::lsp::{
+    drain_startup_messages, response_result, send_notification, send_request, wait_for_publish,
+};
 use sa_test_support::setup_foundry_root;
+use serde_json::{Value, json};
 use tempfile::tempdir;
+use tokio::time::Duration;
 use tower_lsp::lsp_types::{
-    ClientCapabilities, DidChangeWatchedFilesParams, FileChangeType, FileEvent, InitializeParams,
-    InitializedParams, Url, WorkspaceFolder,
+    ClientCapabilities, DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams,
+    DidOpenTextDocumentParams, ExecuteCommandParams, FileChangeType, FileEvent, InitializeParams,
+    InitializedParams, TextDocumentItem, Url, WorkspaceFolder, WorkspaceFoldersChangeEvent,
 };
 
 async fn initialize_server(
@@ -254,3 +259,385 @@ remappings = ["lib/=lib/new/src/"]
     let target = remapping_target(remappings, "lib/").expect("remapping after create");
     assert!(target.ends_with("lib/new/src/"));
 }
+
+#[tokio::test]
+async fn initialize_loads_every_workspace_folder_as_a_project() {
+    let temp_a = tempdir().expect("tempdir a");
+    let root_a = temp_a.path().canonicalize().expect("canonicalize root a");
+    setup_foundry_root(&root_a);
+    fs::write(root_a.join("foundry.toml"), "[profile.default]").expect("write foundry.toml a");
+    fs::write(root_a.join("src/A.sol"), "contract A {}").expect("write a");
+
+    let temp_b = tempdir().expect("tempdir b");
+    let root_b = temp_b.path().canonicalize().expect("canonicalize root b");
+    setup_foundry_root(&root_b);
+    fs::write(root_b.join("foundry.toml"), "[profile.default]").expect("write foundry.toml b");
+    fs::write(root_b.join("src/B.sol"), "contract B {}").expect("write b");
+
+    let folders = vec![
+        WorkspaceFolder {
+            uri: Url::from_file_path(&root_a).expect("root a uri"),
+            name: "alpha".to_string(),
+        },
+        WorkspaceFolder {
+            uri: Url::from_file_path(&root_b).expect("root b uri"),
+            name: "beta".to_string(),
+        },
+    ];
+    let service = initialize_server(None, Some(folders)).await;
+
+    let (analysis, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    assert_eq!(analysis.project_ids().len(), 2);
+
+    let file_a = vfs
+        .file_id(&NormalizedPath::new(
+            root_a.join("src/A.sol").to_string_lossy(),
+        ))
+        .expect("file a");
+    let file_b = vfs
+        .file_id(&NormalizedPath::new(
+            root_b.join("src/B.sol").to_string_lossy(),
+        ))
+        .expect("file b");
+    assert_ne!(
+        analysis.project_for_file(file_a),
+        analysis.project_for_file(file_b)
+    );
+    let config_b = analysis.config_for_file(file_b).expect("config b");
+    assert_eq!(
+        config_b.workspace().root(),
+        &NormalizedPath::new(root_b.to_string_lossy())
+    );
+}
+
+fn foundry_folder(name: &str, contract: &str) -> (tempfile::TempDir, WorkspaceFolder) {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+    fs::write(
+        root.join(format!("src/{contract}.sol")),
+        format!("contract {contract} {{}}\n"),
+    )
+    .expect("write source");
+    let folder = WorkspaceFolder {
+        uri: Url::from_file_path(&root).expect("root uri"),
+        name: name.to_string(),
+    };
+    (temp, folder)
+}
+
+fn remove_folder(folder: WorkspaceFolder) -> DidChangeWorkspaceFoldersParams {
+    DidChangeWorkspaceFoldersParams {
+        event: WorkspaceFoldersChangeEvent {
+            added: Vec::new(),
+            removed: vec![folder],
+        },
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn removing_a_workspace_folder_unloads_its_project_and_clears_its_diagnostics() {
+    let (temp_a, folder_a) = foundry_folder("alpha", "A");
+    let (temp_b, folder_b) = foundry_folder("beta", "B");
+    let root_a = temp_a.path().canonicalize().expect("canonicalize root a");
+    let file_b = temp_b
+        .path()
+        .canonicalize()
+        .expect("canonicalize root b")
+        .join("src/B.sol");
+    // A Slither finding in the second folder gives it published diagnostics.
+    let report = json!({
+        "success": true,
+        "error": null,
+        "results": {
+            "detectors": [{
+                "check": "reentrancy-eth",
+                "impact": "High",
+                "description": "Reentrancy in B",
+                "elements": [{
+                    "type": "contract",
+                    "source_mapping": {
+                        "start": 0,
+                        "length": 8,
+                        "filename_relative": file_b.to_string_lossy(),
+                        "is_dependency": false
+                    }
+                }]
+            }]
+        }
+    });
+    fs::write(root_a.join("slither.json"), report.to_string()).expect("write report");
+
+    let (mut service, mut socket) = tower_lsp::LspService::new(solidity_analyzer::Server::new);
+    let initialize = InitializeParams {
+        workspace_folders: Some(vec![folder_a, folder_b.clone()]),
+        ..InitializeParams::default()
+    };
+    let response = send_request(&mut service, 1, "initialize", initialize).await;
+    let _ = response_result::<tower_lsp::lsp_types::InitializeResult>(response);
+    send_notification(&mut service, "initialized", InitializedParams {}).await;
+    drain_startup_messages(&mut socket).await;
+
+    let uri_b = Url::from_file_path(&file_b).expect("file b uri");
+    let timeout = Duration::from_secs(10);
+    let import = ExecuteCommandParams {
+        command: "solidity-analyzer.importSlither".to_string(),
+        arguments: Vec::new(),
+        work_done_progress_params: Default::default(),
+    };
+    let _ = tokio::join!(
+        send_request(&mut service, 2, "workspace/executeCommand", import),
+        wait_for_publish(&mut socket, timeout, &uri_b, |publish| {
+            !publish.diagnostics.is_empty()
+        }),
+    );
+
+    tokio::join!(
+        send_notification(
+            &mut service,
+            "workspace/didChangeWorkspaceFolders",
+            remove_folder(folder_b),
+        ),
+        wait_for_publish(&mut socket, timeout, &uri_b, |publish| {
+            publish.diagnostics.is_empty()
+        }),
+    );
+
+    let (analysis, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    assert_eq!(analysis.project_ids().len(), 1);
+    assert!(
+        vfs.file_id(&NormalizedPath::new(file_b.to_string_lossy()))
+            .is_none()
+    );
+    assert!(
+        vfs.file_id(&NormalizedPath::new(
+            root_a.join("src/A.sol").to_string_lossy()
+        ))
+        .is_some()
+    );
+}
+
+#[tokio::test]
+async fn removing_the_primary_workspace_folder_makes_another_project_primary() {
+    let (temp_a, folder_a) = foundry_folder("alpha", "A");
+    let (temp_b, folder_b) = foundry_folder("beta", "B");
+    let root_a = temp_a.path().canonicalize().expect("canonicalize root a");
+    let root_b = temp_b.path().canonicalize().expect("canonicalize root b");
+    let mut service = initialize_server(None, Some(vec![folder_a.clone(), folder_b])).await;
+
+    send_notification(
+        &mut service,
+        "workspace/didChangeWorkspaceFolders",
+        remove_folder(folder_a),
+    )
+    .await;
+
+    let (analysis, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    assert_eq!(analysis.project_ids().len(), 1);
+    assert_eq!(
+        analysis.workspace().root(),
+        &NormalizedPath::new(root_b.to_string_lossy())
+    );
+    assert!(
+        vfs.file_id(&NormalizedPath::new(
+            root_a.join("src/A.sol").to_string_lossy()
+        ))
+        .is_none()
+    );
+    let file_b = vfs
+        .file_id(&NormalizedPath::new(
+            root_b.join("src/B.sol").to_string_lossy(),
+        ))
+        .expect("file b");
+    assert_eq!(
+        analysis
+            .config_for_file(file_b)
+            .expect("config b")
+            .workspace()
+            .root(),
+        &NormalizedPath::new(root_b.to_string_lossy())
+    );
+}
+
+#[tokio::test]
+async fn did_open_routes_nested_project_files_to_their_own_root() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+
+    let nested = root.join("lib/dep");
+    setup_foundry_root(&nested);
+    let nested_toml = r#"
+[profile.default]
+remappings = ["dep/=src/"]
+"#;
+    fs::write(nested.join("foundry.toml"), nested_toml).expect("write nested foundry.toml");
+    let dep_path = nested.join("src/Dep.sol");
+    let dep_text = "contract Dep {}";
+    fs::write(&dep_path, dep_text).expect("write dep");
+
+    let root_uri = Url::from_file_path(&root).expect("root uri");
+    let mut service = initialize_server(Some(root_uri), None).await;
+
+    let open = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: Url::from_file_path(&dep_path).expect("dep uri"),
+            language_id: "solidity".to_string(),
+            version: 1,
+            text: dep_text.to_string(),
+        },
+    };
+    send_notification(&mut service, "textDocument/didOpen", open).await;
+
+    let (analysis, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    let file_id = vfs
+        .file_id(&NormalizedPath::new(dep_path.to_string_lossy()))
+        .expect("dep file");
+    let config = analysis.config_for_file(file_id).expect("nested config");
+    assert_eq!(
+        config.workspace().root(),
+        &NormalizedPath::new(nested.to_string_lossy())
+    );
+    assert!(remapping_target(config.active_profile().remappings(), "dep/").is_some());
+    assert_eq!(
+        analysis.workspace().root(),
+        &NormalizedPath::new(root.to_string_lossy())
+    );
+}
+
+#[tokio::test]
+async fn select_profile_command_reloads_with_profile_layout() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::create_dir_all(root.join("contracts")).expect("contracts dir");
+    let foundry_toml = r#"
+[profile.default]
+src = "src"
+
+[profile.ci]
+src = "contracts"
+"#;
+    fs::write(root.join("foundry.toml"), foundry_toml).expect("write foundry.toml");
+
+    let root_uri = Url::from_file_path(&root).expect("root uri");
+    let mut service = initialize_server(Some(root_uri), None).await;
+    let (analysis, _) = service.inner().snapshot().await;
+    assert!(analysis.workspace().src().as_str().ends_with("/src"));
+
+    let select = |profile: Value| ExecuteCommandParams {
+        command: "solidity-analyzer.selectProfile".to_string(),
+        arguments: vec![profile],
+        work_done_progress_params: Default::default(),
+    };
+    let response = send_request(
+        &mut service,
+        2,
+        "workspace/executeCommand",
+        select(Value::from("ci")),
+    )
+    .await;
+    let active = response_result::<Option<String>>(response);
+    assert_eq!(active.as_deref(), Some("ci"));
+    let (analysis, _) = service.inner().snapshot().await;
+    assert!(analysis.workspace().src().as_str().ends_with("/contracts"));
+
+    let response = send_request(
+        &mut service,
+        3,
+        "workspace/executeCommand",
+        select(Value::Null),
+    )
+    .await;
+    let active = response_result::<Option<String>>(response);
+    assert_eq!(active.as_deref(), Some("default"));
+    let (analysis, _) = service.inner().snapshot().await;
+    assert!(analysis.workspace().src().as_str().ends_with("/src"));
+}
+
+#[tokio::test]
+async fn reloads_when_remappings_txt_is_created() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    fs::write(root.join("foundry.toml"), "[profile.default]").expect("write foundry.toml");
+
+    let root_uri = Url::from_file_path(&root).expect("root uri");
+    let mut service = initialize_server(Some(root_uri), None).await;
+
+    let remappings_path = root.join("remappings.txt");
+    fs::write(&remappings_path, "dep/=lib/dep/contracts/\n").expect("write remappings.txt");
+    let watched = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent {
+            uri: Url::from_file_path(&remappings_path).expect("remappings uri"),
+            typ: FileChangeType::CREATED,
+        }],
+    };
+    send_notification(&mut service, "workspace/didChangeWatchedFiles", watched).await;
+
+    let (analysis, _) = service.inner().snapshot().await;
+    let config = analysis.config();
+    let target = remapping_target(config.active_profile().remappings(), "dep/").expect("remapping");
+    assert!(target.ends_with("lib/dep/contracts/"));
+}
+
+#[tokio::test]
+async fn library_files_load_when_an_open_file_imports_them() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().canonicalize().expect("canonicalize root");
+    setup_foundry_root(&root);
+    let foundry_toml = r#"
+[profile.default]
+remappings = ["solmate/=lib/solmate/src/"]
+"#;
+    fs::write(root.join("foundry.toml"), foundry_toml).expect("write foundry.toml");
+    fs::create_dir_all(root.join("lib/solmate/src")).expect("create solmate dir");
+    let owned_path = root.join("lib/solmate/src/Owned.sol");
+    fs::write(&owned_path, "contract Owned {}").expect("write Owned");
+    let unused_path = root.join("lib/solmate/src/Unused.sol");
+    fs::write(&unused_path, "contract Unused {}").expect("write Unused");
+    let main_path = root.join("src/Main.sol");
+    let main_text = "import \"solmate/Owned.sol\";\ncontract Main is Owned {}";
+    fs::write(&main_path, main_text).expect("write main");
+    fs::write(
+        root.join("src/Other.sol"),
+        "import \"solmate/Unused.sol\";\ncontract Other {}",
+    )
+    .expect("write other");
+
+    let root_uri = Url::from_file_path(&root).expect("root uri");
+    let mut service = initialize_server(Some(root_uri), None).await;
+    let owned = NormalizedPath::new(owned_path.to_string_lossy());
+    let unused = NormalizedPath::new(unused_path.to_string_lossy());
+
+    let (_, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    assert!(
+        vfs.file_id(&NormalizedPath::new(main_path.to_string_lossy()))
+            .is_some()
+    );
+    assert!(vfs.file_id(&owned).is_none());
+    assert!(vfs.file_id(&unused).is_none());
+
+    let open = DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: Url::from_file_path(&main_path).expect("main uri"),
+            language_id: "solidity".to_string(),
+            version: 1,
+            text: main_text.to_string(),
+        },
+    };
+    send_notification(&mut service, "textDocument/didOpen", open).await;
+
+    let (_, vfs) = service.inner().snapshot().await;
+    let vfs = vfs.expect("vfs snapshot");
+    let file_id = vfs.file_id(&owned).expect("imported library file");
+    assert_eq!(vfs.file_text(file_id), Some("contract Owned {}"));
+    assert!(vfs.file_id(&unused).is_none());
+}
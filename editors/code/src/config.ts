@@ -1,6 +1,7 @@
 export type TraceLevel = "off" | "messages" | "verbose";
 export type StatusBarShow = "always" | "never" | "whenActive";
 export type StatusBarClickAction = "openLogs" | "restartServer";
+export type IndexingMode = "full" | "lazy";
 
 type RawFeatureToggle = {
     enable?: boolean;
@@ -31,6 +32,8 @@ export type RawConfig = {
         enable?: boolean;
         onSave?: boolean;
         onChange?: boolean;
+        checks?: boolean;
+        diamondFacets?: string[] | null;
     };
     format?: {
         enable?: boolean;
@@ -44,6 +47,20 @@ export type RawConfig = {
     toolchain?: {
         promptInstall?: boolean;
     };
+    foundry?: {
+        profile?: string | null;
+    };
+    indexing?: {
+        include?: string[] | null;
+        exclude?: string[] | null;
+        mode?: IndexingMode;
+        entryPoints?: string[] | null;
+    };
+    sema?: {
+        cacheCapacity?: number;
+        fallbackSnapshot?: boolean;
+        rebuildDelayMs?: number;
+    };
     initializeStopped?: boolean;
 };
 
@@ -68,6 +85,8 @@ export type ExtensionConfig = {
         enable: boolean;
         onSave: boolean;
         onChange: boolean;
+        checks: boolean;
+        diamondFacets: string[];
     };
     format: {
         enable: boolean;
@@ -81,6 +100,20 @@ export type ExtensionConfig = {
     toolchain: {
         promptInstall: boolean;
     };
+    foundry: {
+        profile: string | null;
+    };
+    indexing: {
+        include: string[];
+        exclude: string[];
+        mode: IndexingMode;
+        entryPoints: string[];
+    };
+    sema: {
+        cacheCapacity: number;
+        fallbackSnapshot: boolean;
+        rebuildDelayMs: number;
+    };
     initializeStopped: boolean;
 };
 
@@ -115,6 +148,8 @@ const defaultConfig: ExtensionConfig = {
         enable: true,
         onSave: true,
         onChange: true,
+        checks: true,
+        diamondFacets: [],
     },
     format: {
         enable: true,
@@ -128,6 +163,20 @@ const defaultConfig: ExtensionConfig = {
     toolchain: {
         promptInstall: true,
     },
+    foundry: {
+        profile: null,
+    },
+    indexing: {
+        include: [],
+        exclude: [],
+        mode: "full",
+        entryPoints: [],
+    },
+    sema: {
+        cacheCapacity: 8,
+        fallbackSnapshot: true,
+        rebuildDelayMs: 250,
+    },
     initializeStopped: false,
 };
 
@@ -184,6 +233,8 @@ export function normalizeConfig(raw: RawConfig = {}, env: NodeJS.ProcessEnv = pr
             enable: raw.diagnostics?.enable ?? defaultConfig.diagnostics.enable,
             onSave: raw.diagnostics?.onSave ?? defaultConfig.diagnostics.onSave,
             onChange: raw.diagnostics?.onChange ?? defaultConfig.diagnostics.onChange,
+            checks: raw.diagnostics?.checks ?? defaultConfig.diagnostics.checks,
+            diamondFacets: raw.diagnostics?.diamondFacets ?? defaultConfig.diagnostics.diamondFacets,
         },
         format: {
             enable: raw.format?.enable ?? defaultConfig.format.enable,
@@ -197,6 +248,20 @@ export function normalizeConfig(raw: RawConfig = {}, env: NodeJS.ProcessEnv = pr
         toolchain: {
             promptInstall: raw.toolchain?.promptInstall ?? defaultConfig.toolchain.promptInstall,
         },
+        foundry: {
+            profile: raw.foundry?.profile || defaultConfig.foundry.profile,
+        },
+        indexing: {
+            include: raw.indexing?.include ?? defaultConfig.indexing.include,
+            exclude: raw.indexing?.exclude ?? defaultConfig.indexing.exclude,
+            mode: raw.indexing?.mode ?? defaultConfig.indexing.mode,
+            entryPoints: raw.indexing?.entryPoints ?? defaultConfig.indexing.entryPoints,
+        },
+        sema: {
+            cacheCapacity: raw.sema?.cacheCapacity ?? defaultConfig.sema.cacheCapacity,
+            fallbackSnapshot: raw.sema?.fallbackSnapshot ?? defaultConfig.sema.fallbackSnapshot,
+            rebuildDelayMs: raw.sema?.rebuildDelayMs ?? defaultConfig.sema.rebuildDelayMs,
+        },
         initializeStopped: raw.initializeStopped ?? defaultConfig.initializeStopped,
     };
 }
@@ -233,6 +298,8 @@ export function prepareVSCodeConfig(config: ExtensionConfig): ExtensionConfig {
             enable: config.diagnostics.enable,
             onSave: config.diagnostics.onSave,
             onChange: config.diagnostics.onChange,
+            checks: config.diagnostics.checks,
+            diamondFacets: config.diagnostics.diamondFacets,
         },
         format: {
             enable: config.format.enable,
@@ -246,6 +313,20 @@ export function prepareVSCodeConfig(config: ExtensionConfig): ExtensionConfig {
         toolchain: {
             promptInstall: config.toolchain.promptInstall,
         },
+        foundry: {
+            profile: config.foundry.profile,
+        },
+        indexing: {
+            include: config.indexing.include,
+            exclude: config.indexing.exclude,
+            mode: config.indexing.mode,
+            entryPoints: config.indexing.entryPoints,
+        },
+        sema: {
+            cacheCapacity: config.sema.cacheCapacity,
+            fallbackSnapshot: config.sema.fallbackSnapshot,
+            rebuildDelayMs: config.sema.rebuildDelayMs,
+        },
         initializeStopped: config.initializeStopped,
     };
 }
@@ -285,6 +366,8 @@ export async function loadConfig(): Promise<ExtensionConfig> {
             enable: config.get("diagnostics.enable"),
             onSave: config.get("diagnostics.onSave"),
             onChange: config.get("diagnostics.onChange"),
+            checks: config.get("diagnostics.checks"),
+            diamondFacets: config.get("diagnostics.diamondFacets"),
         },
         format: {
             enable: config.get("format.enable"),
@@ -298,6 +381,20 @@ export async function loadConfig(): Promise<ExtensionConfig> {
         toolchain: {
             promptInstall: config.get("toolchain.promptInstall"),
         },
+        foundry: {
+            profile: config.get("foundry.profile"),
+        },
+        indexing: {
+            include: config.get("indexing.include"),
+            exclude: config.get("indexing.exclude"),
+            mode: config.get("indexing.mode"),
+            entryPoints: config.get("indexing.entryPoints"),
+        },
+        sema: {
+            cacheCapacity: config.get("sema.cacheCapacity"),
+            fallbackSnapshot: config.get("sema.fallbackSnapshot"),
+            rebuildDelayMs: config.get("sema.rebuildDelayMs"),
+        },
         initializeStopped: config.get("initializeStopped"),
     };
 
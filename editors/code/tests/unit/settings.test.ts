@@ -10,6 +10,7 @@ describe("extension settings", () => {
         expect(config.statusBar.show).toBe("whenActive");
         expect(config.statusBar.clickAction).toBe("openLogs");
         expect(config.toolchain.promptInstall).toBe(true);
+        expect(config.foundry.profile).toBeNull();
     });
 
     test("environment variables are expanded in server.extraEnv", () => {
@@ -29,6 +30,11 @@ describe("extension settings", () => {
         expect(config.server.extraEnv.SA_MODE).toBe("debug-fast-1");
     });
 
+    test("empty foundry profile falls back to the environment", () => {
+        expect(normalizeConfig({ foundry: { profile: "" } }).foundry.profile).toBeNull();
+        expect(normalizeConfig({ foundry: { profile: "ci" } }).foundry.profile).toBe("ci");
+    });
+
     test("prepareVSCodeConfig returns initialization options", () => {
         const config = normalizeConfig({
             server: { path: "/bin/sa", extraEnv: { SA_LOG: "trace" } },
@@ -51,6 +57,9 @@ describe("extension settings", () => {
             format: { enable: true, onSave: false },
             lint: { enable: true, onSave: true, fixOnSave: false },
             toolchain: { promptInstall: true },
+            foundry: { profile: null },
+            indexing: { include: [], exclude: [] },
+            sema: { cacheCapacity: 8, fallbackSnapshot: true },
         });
     });
 });